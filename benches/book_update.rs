@@ -0,0 +1,73 @@
+//! WS `book` snapshot cost: the JSON tree + Vec<BookLevel> path (one String per price and size,
+//! then `BookDepth::from_levels`) vs `BookSnapshot`, which parses the borrowed level strings
+//! straight into the depth maps. Also `Decimal::from_str` vs the `parse_decimal` fast path on the
+//! price / size strings the feed sends.
+//!
+//! Run: `cargo bench --bench book_update`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rust_decimal::Decimal;
+use sniper_core::book::{parse_decimal, BookDepth, BookSnapshot};
+use sniper_core::types::OrderBookRaw;
+use std::str::FromStr;
+
+/// A snapshot with `levels` bids (0.01..) and asks (0.99 down), as the market channel sends it.
+fn snapshot(levels: usize) -> String {
+    let side = |start: i64, step: i64| {
+        (0..levels as i64)
+            .map(|i| {
+                format!(
+                    r#"{{"price":"{}","size":"{}.25"}}"#,
+                    Decimal::new(start + step * i, 3),
+                    100 + i
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    format!(
+        r#"{{"event_type":"book","asset_id":"71321045679252212594626385532706912750332728571942532289631379312455583992563","market":"0x5f65177b394277fd294cd75650044e32ba009a95022d88a0c1d565897d72f8f1","bids":[{}],"asks":[{}],"timestamp":"1772169300000","hash":"0x0"}}"#,
+        side(10, 10),
+        side(990, -10)
+    )
+}
+
+fn book_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("book_snapshot");
+    for levels in [10, 50] {
+        let text = snapshot(levels);
+        group.bench_function(format!("vec_levels_{}", levels), |b| {
+            b.iter(|| {
+                let value: serde_json::Value = serde_json::from_str(black_box(&text)).unwrap();
+                let raw: OrderBookRaw = serde_json::from_str(black_box(&text)).unwrap();
+                black_box(value.get("event_type").is_some());
+                BookDepth::from_raw(&raw)
+            })
+        });
+        group.bench_function(format!("borrowed_levels_{}", levels), |b| {
+            b.iter(|| BookSnapshot::parse(black_box(&text)).unwrap().into_depth())
+        });
+    }
+    group.finish();
+
+    let strings = ["0.52", "0.001", "0.999", "123.45", "5000", "17.3333"];
+    let mut group = c.benchmark_group("decimal_parse");
+    group.bench_function("from_str", |b| {
+        b.iter(|| {
+            for s in black_box(&strings) {
+                black_box(Decimal::from_str(s).ok());
+            }
+        })
+    });
+    group.bench_function("parse_decimal", |b| {
+        b.iter(|| {
+            for s in black_box(&strings) {
+                black_box(parse_decimal(s));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, book_update);
+criterion_main!(benches);
@@ -0,0 +1,143 @@
+//! Buy-path signing cost: per-call EIP-712 domain / base64 secret decode (`sign_order`,
+//! `build_poly_hmac`) vs the per-account templates LiveClob keeps (`OrderSigner`, `PolyHmacKey`),
+//! and a POST /orders batch signed in order vs spread over worker threads (MM_SIGNING_WORKERS).
+//!
+//! Run: `cargo bench --bench order_signing`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{H160, U256};
+use futures_util::FutureExt;
+use sniper_core::signing::{
+    build_poly_hmac, sign_order, OrderFields, OrderSigner, PolyHmacKey, EXCHANGE_ADDRESS_POLYGON,
+    SIGNATURE_TYPE_GNOSIS_SAFE,
+};
+
+const PRIVATE_KEY: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const SECRET: &str = "c2VjcmV0LXNlY3JldC1zZWNyZXQtc2VjcmV0LXNlY3JldA==";
+const BODY: &str = r#"{"order":{"salt":1,"maker":"0x0000000000000000000000000000000000000001"},"owner":"key","orderType":"FAK","deferExec":false}"#;
+
+fn order_signing(c: &mut Criterion) {
+    let wallet: LocalWallet = PRIVATE_KEY.parse().unwrap();
+    let exchange: H160 = EXCHANGE_ADDRESS_POLYGON.parse().unwrap();
+    let funder = H160::from_low_u64_be(1);
+    let token_id = U256::from_dec_str(
+        "71321045679252212594626385532706912750332728571942532289631379312455583992563",
+    )
+    .unwrap();
+    let (maker_amount, taker_amount) = (U256::from(4_750_000u64), U256::from(5_000_000u64));
+
+    let mut group = c.benchmark_group("order_signature");
+    group.bench_function("sign_order", |b| {
+        b.iter(|| {
+            sign_order(
+                &wallet,
+                137,
+                exchange,
+                funder,
+                wallet.address(),
+                SIGNATURE_TYPE_GNOSIS_SAFE,
+                &OrderFields {
+                    salt: black_box(1_772_169_300_000),
+                    taker: H160::zero(),
+                    token_id,
+                    maker_amount,
+                    taker_amount,
+                    expiration: 0,
+                    nonce: 0,
+                    fee_rate_bps: 1000,
+                    side: 0,
+                },
+            )
+            .now_or_never()
+            .unwrap()
+            .unwrap()
+        })
+    });
+    let signer = OrderSigner::new(
+        137,
+        exchange,
+        funder,
+        wallet.address(),
+        SIGNATURE_TYPE_GNOSIS_SAFE,
+    );
+    group.bench_function("order_signer_cached", |b| {
+        b.iter(|| {
+            signer
+                .sign(
+                    &wallet,
+                    &OrderFields {
+                        salt: black_box(1_772_169_300_000),
+                        taker: H160::zero(),
+                        token_id,
+                        maker_amount,
+                        taker_amount,
+                        expiration: 0,
+                        nonce: 0,
+                        fee_rate_bps: 1000,
+                        side: 0,
+                    },
+                )
+                .unwrap()
+        })
+    });
+    group.finish();
+
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut group = c.benchmark_group("batch_signature");
+    for len in [2u64, 15] {
+        let orders: Vec<OrderFields> = (0..len)
+            .map(|i| OrderFields {
+                salt: 1_772_169_300_000 + i,
+                taker: H160::zero(),
+                token_id,
+                maker_amount,
+                taker_amount,
+                expiration: 0,
+                nonce: 0,
+                fee_rate_bps: 1000,
+                side: 0,
+            })
+            .collect();
+        group.bench_with_input(BenchmarkId::new("in_order", len), &orders, |b, orders| {
+            b.iter(|| signer.sign_batch(&wallet, black_box(orders), 1).unwrap())
+        });
+        group.bench_with_input(
+            BenchmarkId::new(format!("{}_workers", workers), len),
+            &orders,
+            |b, orders| {
+                b.iter(|| {
+                    signer
+                        .sign_batch(&wallet, black_box(orders), workers)
+                        .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("l2_hmac");
+    group.bench_function("build_poly_hmac", |b| {
+        b.iter(|| {
+            build_poly_hmac(
+                SECRET,
+                black_box(1_772_169_300),
+                "POST",
+                "/order",
+                Some(BODY),
+            )
+            .unwrap()
+        })
+    });
+    let key = PolyHmacKey::new(SECRET).unwrap();
+    group.bench_function("hmac_key_cached", |b| {
+        b.iter(|| {
+            key.sign(black_box(1_772_169_300), "POST", "/order", Some(BODY))
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, order_signing);
+criterion_main!(benches);
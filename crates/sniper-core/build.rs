@@ -0,0 +1,12 @@
+//! With the `grpc` feature: generate the executor service from proto/executor.proto, with a
+//! protoc built from source (protobuf-src) so no system protoc is needed.
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/executor.proto");
+        std::env::set_var("PROTOC", protobuf_src::protoc());
+        tonic_build::compile_protos("proto/executor.proto").expect("compile proto/executor.proto");
+    }
+}
@@ -0,0 +1,133 @@
+//! CLOB API credentials from the private key (L1 -> L2): create or derive the API key, secret and
+//! passphrase via /auth and write them to .env, so LiveClob users don't need the Python client.
+
+use crate::signing::sign_clob_auth;
+use anyhow::{Context, Result};
+use ethers::signers::{LocalWallet, Signer};
+use serde::Deserialize;
+use std::fmt;
+use std::time::UNIX_EPOCH;
+
+/// L2 credentials as returned by /auth/api-key and /auth/derive-api-key.
+#[derive(Clone, Deserialize)]
+pub struct ApiCreds {
+    #[serde(rename = "apiKey")]
+    pub api_key: String,
+    pub secret: String,
+    pub passphrase: String,
+}
+
+/// Secret and passphrase redacted, so the credentials can end up in logs and error contexts.
+impl fmt::Debug for ApiCreds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiCreds")
+            .field("api_key", &self.api_key)
+            .field("secret", &"<redacted>")
+            .field("passphrase", &"<redacted>")
+            .finish()
+    }
+}
+
+async fn l1_request(
+    client: &reqwest::Client,
+    method: reqwest::Method,
+    url: &str,
+    wallet: &LocalWallet,
+    chain_id: u64,
+    nonce: u64,
+) -> Result<reqwest::Response> {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let sig = sign_clob_auth(wallet, chain_id, timestamp, nonce)?;
+    let address = format!("{:?}", wallet.address());
+    Ok(client
+        .request(method, url)
+        .header("POLY_ADDRESS", address.trim_matches('"'))
+        .header("POLY_SIGNATURE", sig)
+        .header("POLY_TIMESTAMP", timestamp.to_string())
+        .header("POLY_NONCE", nonce.to_string())
+        .send()
+        .await?)
+}
+
+/// Create a new API key for `nonce` (POST /auth/api-key); if one already exists for that nonce,
+/// derive it instead (GET /auth/derive-api-key).
+pub async fn create_or_derive_api_key(
+    client: &reqwest::Client,
+    clob_host: &str,
+    wallet: &LocalWallet,
+    chain_id: u64,
+    nonce: u64,
+) -> Result<ApiCreds> {
+    let host = clob_host.trim_end_matches('/');
+    let create_url = format!("{}/auth/api-key", host);
+    let res = l1_request(
+        client,
+        reqwest::Method::POST,
+        &create_url,
+        wallet,
+        chain_id,
+        nonce,
+    )
+    .await?;
+    let status = res.status();
+    let text = res.text().await.unwrap_or_default();
+    if status.is_success() {
+        if let Ok(creds) = serde_json::from_str::<ApiCreds>(&text) {
+            return Ok(creds);
+        }
+    }
+    tracing::info!(
+        "[Auth] create api key returned {} ({}), deriving existing key",
+        status,
+        text.trim()
+    );
+    let derive_url = format!("{}/auth/derive-api-key", host);
+    let res = l1_request(
+        client,
+        reqwest::Method::GET,
+        &derive_url,
+        wallet,
+        chain_id,
+        nonce,
+    )
+    .await?;
+    let status = res.status();
+    let text = res.text().await.unwrap_or_default();
+    if !status.is_success() {
+        anyhow::bail!("derive api key failed: {} {}", status, text.trim());
+    }
+    serde_json::from_str(&text).with_context(|| format!("derive api key response: {}", text))
+}
+
+/// Set API_KEY, SECRET and PASSPHRASE in the env file at `path` (replacing existing lines,
+/// appending missing ones; the file is created if it does not exist).
+pub fn write_env_file(path: &str, creds: &ApiCreds) -> Result<()> {
+    let existing = std::fs::read_to_string(path).unwrap_or_default();
+    let values = [
+        ("API_KEY", creds.api_key.as_str()),
+        ("SECRET", creds.secret.as_str()),
+        ("PASSPHRASE", creds.passphrase.as_str()),
+    ];
+    let mut written = [false; 3];
+    let mut lines: Vec<String> = existing
+        .lines()
+        .map(|line| {
+            for (i, (key, value)) in values.iter().enumerate() {
+                if line.trim_start().starts_with(&format!("{}=", key)) {
+                    written[i] = true;
+                    return format!("{}={}", key, value);
+                }
+            }
+            line.to_string()
+        })
+        .collect();
+    for (i, (key, value)) in values.iter().enumerate() {
+        if !written[i] {
+            lines.push(format!("{}={}", key, value));
+        }
+    }
+    std::fs::write(path, lines.join("\n") + "\n").with_context(|| format!("write {}", path))
+}
@@ -0,0 +1,143 @@
+//! Backtest input: intervals recorded by the book recorder (MM_BOOK_RECORD, `<slug>.csv` plus
+//! `<slug>.meta.json`) replayed tick by tick through the shadow simulation (shadow.rs).
+
+use crate::market::{DEFAULT_MIN_ORDER_SIZE, DEFAULT_TICK_SIZE};
+use crate::market_store;
+use crate::shadow::{ShadowResult, ShadowRunner};
+use crate::strategy::TickContext;
+use crate::types::{Config, ResolvedMarket, TopOfBook, TopOfBookSide};
+use anyhow::{Context, Result};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use tracing::warn;
+
+/// One recorded interval: the market bounds and the top of book at each sample.
+pub struct RecordedInterval {
+    /// Token IDs are the outcome labels ("Up" / "Down"); the recorder does not keep them.
+    pub market: ResolvedMarket,
+    /// (timestamp_ms, top of book), in time order.
+    pub ticks: Vec<(u64, TopOfBook)>,
+}
+
+/// Every interval in `dir` that has a meta sidecar, sorted by interval start. Files that cannot
+/// be read are skipped with a warning.
+pub fn load_intervals(dir: &Path) -> Result<Vec<RecordedInterval>> {
+    let mut intervals = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+        let path = entry?.path();
+        let Some(slug) = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_suffix(".meta.json"))
+        else {
+            continue;
+        };
+        match load_interval(dir, slug) {
+            Ok(i) if !i.ticks.is_empty() => intervals.push(i),
+            Ok(_) => {}
+            Err(e) => warn!("[Backtest] skipping {}: {:#}", slug, e),
+        }
+    }
+    intervals.sort_by_key(|i| i.market.interval_start_unix);
+    Ok(intervals)
+}
+
+fn load_interval(dir: &Path, slug: &str) -> Result<RecordedInterval> {
+    let meta: serde_json::Value = serde_json::from_str(&fs::read_to_string(
+        dir.join(format!("{}.meta.json", slug)),
+    )?)?;
+    let unix = |key: &str| {
+        meta.get(key)
+            .and_then(|v| v.as_u64())
+            .with_context(|| format!("meta has no {}", key))
+    };
+    // Condition ID, tick and min size from the market store (MM_MARKET_CACHE_FILE) when the
+    // market was seen there.
+    let stored = market_store::lookup(slug);
+    let market = ResolvedMarket {
+        slug: slug.to_string(),
+        condition_id: stored
+            .as_ref()
+            .map(|m| m.condition_id.clone())
+            .unwrap_or_default(),
+        close_time_unix: unix("close_time_unix")?,
+        interval_start_unix: unix("interval_start_unix")?,
+        token_id_up: "Up".to_string(),
+        token_id_down: "Down".to_string(),
+        tick_size: stored.as_ref().map_or(DEFAULT_TICK_SIZE, |m| m.tick_size),
+        min_order_size: stored
+            .as_ref()
+            .map_or(DEFAULT_MIN_ORDER_SIZE, |m| m.min_order_size),
+        neg_risk: stored.is_some_and(|m| m.neg_risk),
+    };
+
+    let csv = fs::read_to_string(dir.join(format!("{}.csv", slug)))?;
+    let mut lines = csv.lines();
+    let header: Vec<&str> = lines.next().context("empty CSV")?.split(',').collect();
+    let col = |name: &str| {
+        header
+            .iter()
+            .position(|h| *h == name)
+            .with_context(|| format!("CSV has no {} column", name))
+    };
+    let (bid_px, bid_sz, ask_px, ask_sz) = (
+        col("bid_px_1")?,
+        col("bid_sz_1")?,
+        col("ask_px_1")?,
+        col("ask_sz_1")?,
+    );
+    let mut ticks: BTreeMap<u64, TopOfBook> = BTreeMap::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split(',').collect();
+        let (Some(ts), Some(outcome)) = (
+            fields.first().and_then(|t| t.parse::<u64>().ok()),
+            fields.get(2),
+        ) else {
+            continue;
+        };
+        let value = |i: usize| fields.get(i).and_then(|v| Decimal::from_str(v).ok());
+        let side = TopOfBookSide {
+            best_bid: value(bid_px),
+            best_bid_size: value(bid_sz),
+            best_ask: value(ask_px),
+            best_ask_size: value(ask_sz),
+            ..TopOfBookSide::default()
+        };
+        let top = ticks.entry(ts).or_default();
+        match *outcome {
+            "Up" => top.token_id_up = Some(side),
+            "Down" => top.token_id_down = Some(side),
+            _ => {}
+        }
+    }
+    Ok(RecordedInterval {
+        market,
+        ticks: ticks.into_iter().collect(),
+    })
+}
+
+/// Replay one interval through every candidate of `shadow`; results in candidate order.
+pub fn replay(
+    shadow: &mut ShadowRunner,
+    config: &Config,
+    interval: &RecordedInterval,
+) -> Vec<ShadowResult> {
+    let market = &interval.market;
+    shadow.on_interval_start(market);
+    for (ts, top) in &interval.ticks {
+        shadow.on_tick(&TickContext {
+            config,
+            market,
+            top,
+            secs_to_close: market.close_time_unix.saturating_sub(ts / 1000),
+            tick_size: market.tick_size,
+            min_order_size: market.min_order_size,
+            now_ms: *ts,
+            spot_move: None,
+        });
+    }
+    shadow.on_interval_end(market)
+}
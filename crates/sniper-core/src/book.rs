@@ -0,0 +1,468 @@
+//! Order book model shared by the REST and WebSocket feeds: [BookDepth] holds the levels of one
+//! token, and both feeds derive the [TopOfBookSide] the runner reads from it, so best bid / ask and
+//! their sizes are computed the same way whatever the source. Each side carries the time it was
+//! last updated ([book_age_ms]). REST fetches (GET /book, /fee-rate) live here too, with the
+//! unauthenticated client they go through ([public_client]); the WS feed is clob_ws_book.rs.
+
+use crate::rate_limit::EndpointClass;
+use crate::retry;
+use crate::types::{
+    BookLevel, BookSource, OrderBookRaw, TopOfBook, TopOfBookSide, TriggerPriceSource,
+};
+use anyhow::Result;
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::de::{Deserializer, SeqAccess, Visitor};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Per-side deadline for the REST book in fetch_top_of_book (and the WS quiet-token refresh).
+pub const SIDE_FETCH_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Client for the public reads (GET /book, /fee-rate, Gamma markets): no credentials and a
+/// connection pool of its own, so read traffic never takes a connection of the authed client
+/// (LiveClob: orders, cancels, balance) or queues behind an order POST on it. Its requests draw on
+/// the [EndpointClass::Book] rate budget, apart from the order / balance ones.
+pub fn public_client(timeout: Duration) -> Result<Client> {
+    Ok(Client::builder()
+        .timeout(timeout)
+        .tcp_nodelay(true)
+        .pool_idle_timeout(Duration::from_secs(90))
+        .pool_max_idle_per_host(8)
+        .build()?)
+}
+
+/// Fetch order book for one token (no auth required).
+pub async fn fetch_order_book(
+    client: &Client,
+    clob_host: &str,
+    token_id: &str,
+) -> Result<OrderBookRaw> {
+    let base = clob_host.trim_end_matches('/');
+    let url = format!("{}/book?token_id={}", base, urlencoding::encode(token_id));
+    let res = retry::send(EndpointClass::Book, "GET /book", || {
+        Ok(client.get(&url).header("Accept", "application/json"))
+    })
+    .await?;
+    if !res.status().is_success() {
+        let status = res.status();
+        let text = res.text().await.unwrap_or_default();
+        anyhow::bail!(
+            "CLOB {}: {}",
+            status,
+            text.chars().take(200).collect::<String>()
+        );
+    }
+    let raw: OrderBookRaw = res.json().await?;
+    Ok(raw)
+}
+
+/// Fetch the market's base fee for a token (GET /fee-rate?token_id=..., no auth). Returns bps.
+pub async fn fetch_fee_rate_bps(client: &Client, clob_host: &str, token_id: &str) -> Result<u64> {
+    let base = clob_host.trim_end_matches('/');
+    let url = format!(
+        "{}/fee-rate?token_id={}",
+        base,
+        urlencoding::encode(token_id)
+    );
+    let res = retry::send(EndpointClass::Book, "GET /fee-rate", || {
+        Ok(client.get(&url).header("Accept", "application/json"))
+    })
+    .await?;
+    if !res.status().is_success() {
+        let status = res.status();
+        let text = res.text().await.unwrap_or_default();
+        anyhow::bail!(
+            "CLOB fee-rate {}: {}",
+            status,
+            text.chars().take(200).collect::<String>()
+        );
+    }
+    let json: serde_json::Value = res.json().await?;
+    let fee = json.get("base_fee").or_else(|| json.get("fee_rate_bps"));
+    fee.and_then(|v| {
+        v.as_u64()
+            .or_else(|| v.as_str().and_then(|s| s.trim().parse().ok()))
+    })
+    .ok_or_else(|| anyhow::anyhow!("fee-rate response has no base_fee: {}", json))
+}
+
+/// Book levels as (price, size), best first.
+pub type Levels = Vec<(Decimal, Decimal)>;
+
+/// Longest string [parse_decimal] builds directly: 19 digits always fit the 64 low bits of the
+/// Decimal mantissa.
+const FAST_PARSE_MAX_LEN: usize = 19;
+
+/// Decimal from a feed string. Plain `digits[.digits]` of up to 19 characters, which covers every
+/// price and size the CLOB sends, is built straight from its digits with the same scale as
+/// Decimal::from_str; anything else (sign, exponent, longer) goes through Decimal::from_str.
+pub fn parse_decimal(s: &str) -> Option<Decimal> {
+    fast_decimal(s.as_bytes()).or_else(|| Decimal::from_str(s.trim()).ok())
+}
+
+fn fast_decimal(s: &[u8]) -> Option<Decimal> {
+    if s.is_empty() || s.len() > FAST_PARSE_MAX_LEN {
+        return None;
+    }
+    let mut mantissa: u64 = 0;
+    let mut point = None;
+    for (i, &b) in s.iter().enumerate() {
+        let digit = b.wrapping_sub(b'0');
+        if digit < 10 {
+            mantissa = mantissa * 10 + u64::from(digit);
+        } else if b == b'.' && point.is_none() {
+            point = Some(i);
+        } else {
+            return None;
+        }
+    }
+    let scale = match point {
+        Some(_) if s.len() == 1 => return None,
+        Some(i) => s.len() - 1 - i,
+        None => 0,
+    };
+    Some(Decimal::from_parts(
+        mantissa as u32,
+        (mantissa >> 32) as u32,
+        0,
+        false,
+        scale as u32,
+    ))
+}
+
+/// Positive decimal from a feed string (None if empty, zero or unparseable).
+pub fn parse_positive(s: &str) -> Option<Decimal> {
+    parse_decimal(s).filter(|d| *d > Decimal::ZERO)
+}
+
+/// Full depth for one token: price -> size.
+#[derive(Debug, Clone, Default)]
+pub struct BookDepth {
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+}
+
+impl BookDepth {
+    /// Depth from snapshot levels; levels with a zero or unparseable price / size are skipped.
+    pub fn from_levels(bids: &[BookLevel], asks: &[BookLevel]) -> Self {
+        let collect = |levels: &[BookLevel]| {
+            levels
+                .iter()
+                .filter_map(|l| Some((parse_positive(&l.price)?, parse_positive(&l.size)?)))
+                .collect()
+        };
+        Self {
+            bids: collect(bids),
+            asks: collect(asks),
+        }
+    }
+
+    /// Depth from a REST book (GET /book).
+    pub fn from_raw(raw: &OrderBookRaw) -> Self {
+        Self::from_levels(
+            raw.bids.as_deref().unwrap_or_default(),
+            raw.asks.as_deref().unwrap_or_default(),
+        )
+    }
+
+    /// Set one level (size 0 removes it).
+    pub fn set_level(&mut self, is_bid: bool, price: Decimal, size: Decimal) {
+        let levels = if is_bid {
+            &mut self.bids
+        } else {
+            &mut self.asks
+        };
+        if size.is_zero() {
+            levels.remove(&price);
+        } else {
+            levels.insert(price, size);
+        }
+    }
+
+    /// Size resting at `price` (None if no level there).
+    pub fn size_at(&self, is_bid: bool, price: Decimal) -> Option<Decimal> {
+        if is_bid {
+            self.bids.get(&price).copied()
+        } else {
+            self.asks.get(&price).copied()
+        }
+    }
+
+    /// Total size of the bids or asks priced in [lo, hi].
+    pub fn size_between(&self, is_bid: bool, lo: Decimal, hi: Decimal) -> Decimal {
+        let levels = if is_bid { &self.bids } else { &self.asks };
+        if lo > hi {
+            return Decimal::ZERO;
+        }
+        levels.range(lo..=hi).map(|(_, s)| *s).sum()
+    }
+
+    /// Best `n` bids (highest first) and asks (lowest first) as (price, size).
+    pub fn top_levels(&self, n: usize) -> (Levels, Levels) {
+        let bids = self
+            .bids
+            .iter()
+            .rev()
+            .take(n)
+            .map(|(p, s)| (*p, *s))
+            .collect();
+        let asks = self.asks.iter().take(n).map(|(p, s)| (*p, *s)).collect();
+        (bids, asks)
+    }
+
+    /// Best bid / ask and their sizes (tick size, min size and update time are left unset).
+    pub fn top_side(&self) -> TopOfBookSide {
+        let best_bid = self.bids.iter().next_back();
+        let best_ask = self.asks.iter().next();
+        TopOfBookSide {
+            best_bid: best_bid.map(|(p, _)| *p),
+            best_bid_size: best_bid.map(|(_, s)| *s),
+            best_ask: best_ask.map(|(p, _)| *p),
+            best_ask_size: best_ask.map(|(_, s)| *s),
+            ..TopOfBookSide::default()
+        }
+    }
+}
+
+/// A WS `book` snapshot read straight from the message text: price / size strings are borrowed
+/// and parsed into the depth maps as the levels are read, with no Vec of levels and no String per
+/// field on the way.
+#[derive(Debug, serde::Deserialize)]
+pub struct BookSnapshot<'a> {
+    /// Token the snapshot is for.
+    #[serde(borrow)]
+    pub asset_id: Cow<'a, str>,
+    #[serde(default, deserialize_with = "deserialize_levels")]
+    bids: BTreeMap<Decimal, Decimal>,
+    #[serde(default, deserialize_with = "deserialize_levels")]
+    asks: BTreeMap<Decimal, Decimal>,
+}
+
+impl<'a> BookSnapshot<'a> {
+    /// Parse one WS `book` message.
+    pub fn parse(text: &'a str) -> serde_json::Result<Self> {
+        serde_json::from_str(text)
+    }
+
+    /// The snapshot's levels as depth maps.
+    pub fn into_depth(self) -> BookDepth {
+        BookDepth {
+            bids: self.bids,
+            asks: self.asks,
+        }
+    }
+}
+
+/// One snapshot level, borrowed unless the JSON string has escapes.
+#[derive(serde::Deserialize)]
+struct RawLevel<'a> {
+    #[serde(borrow)]
+    price: Cow<'a, str>,
+    #[serde(borrow)]
+    size: Cow<'a, str>,
+}
+
+/// A level array (or null) as price -> size; levels with a zero or unparseable price / size are
+/// skipped, as in [BookDepth::from_levels].
+fn deserialize_levels<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<Decimal, Decimal>, D::Error> {
+    struct LevelsVisitor;
+
+    impl<'de> Visitor<'de> for LevelsVisitor {
+        type Value = BTreeMap<Decimal, Decimal>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("an array of price levels")
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E> {
+            Ok(BTreeMap::new())
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut levels = BTreeMap::new();
+            while let Some(level) = seq.next_element::<RawLevel>()? {
+                if let (Some(price), Some(size)) =
+                    (parse_positive(&level.price), parse_positive(&level.size))
+                {
+                    levels.insert(price, size);
+                }
+            }
+            Ok(levels)
+        }
+    }
+
+    deserializer.deserialize_any(LevelsVisitor)
+}
+
+/// Build TopOfBookSide from raw order book.
+/// Best bid = highest bid price; best ask = lowest ask price (robust to API sort order).
+pub fn raw_to_side(raw: &OrderBookRaw, updated_ms: u64) -> TopOfBookSide {
+    TopOfBookSide {
+        tick_size: tick_size_from_raw(raw),
+        min_order_size: min_order_size_from_raw(raw),
+        updated_ms: Some(updated_ms),
+        source: Some(BookSource::Rest),
+        ..BookDepth::from_raw(raw).top_side()
+    }
+}
+
+/// Price of `side` for a trigger check: bid, ask, mid, or `last_trade` (from the WS tape, if
+/// recent). Mid and last trade fall back to the bid when unavailable.
+pub fn trigger_price(
+    source: TriggerPriceSource,
+    side: &TopOfBookSide,
+    last_trade: Option<Decimal>,
+) -> Option<Decimal> {
+    match source {
+        TriggerPriceSource::Bid => side.best_bid,
+        TriggerPriceSource::Ask => side.best_ask,
+        TriggerPriceSource::Mid => match (side.best_bid, side.best_ask) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / Decimal::TWO),
+            (bid, _) => bid,
+        },
+        TriggerPriceSource::LastTrade => last_trade.or(side.best_bid),
+    }
+}
+
+/// Age of the older side of `top` at `now_ms` (None if neither side has an update time).
+pub fn book_age_ms(top: &TopOfBook, now_ms: u64) -> Option<u64> {
+    [top.token_id_up.as_ref(), top.token_id_down.as_ref()]
+        .into_iter()
+        .flatten()
+        .filter_map(|s| s.updated_ms)
+        .min()
+        .map(|t| now_ms.saturating_sub(t))
+}
+
+/// Fetch order books for both tokens concurrently and return TopOfBook, its sides updated at
+/// `now_ms` (the engine's time of the read). A side that fails or takes longer than
+/// SIDE_FETCH_TIMEOUT is left None; errors only if both sides fail.
+pub async fn fetch_top_of_book(
+    client: &Client,
+    clob_host: &str,
+    token_id_up: &str,
+    token_id_down: &str,
+    now_ms: u64,
+) -> Result<TopOfBook> {
+    let side = |label: &'static str, id| async move {
+        match tokio::time::timeout(SIDE_FETCH_TIMEOUT, fetch_order_book(client, clob_host, id))
+            .await
+        {
+            Ok(r) => r,
+            Err(_) => Err(anyhow::anyhow!(
+                "{} book fetch timed out after {} ms",
+                label,
+                SIDE_FETCH_TIMEOUT.as_millis()
+            )),
+        }
+    };
+    let (up_raw, down_raw) = tokio::join!(side("Up", token_id_up), side("Down", token_id_down));
+    // One slow or failing side must not hold back the other: keep whichever side arrived.
+    match (up_raw, down_raw) {
+        (Err(e), Err(_)) => Err(e),
+        (up, down) => {
+            for e in [up.as_ref().err(), down.as_ref().err()]
+                .into_iter()
+                .flatten()
+            {
+                tracing::debug!("[OrderBook] {}; using the other side only", e);
+            }
+            Ok(TopOfBook {
+                token_id_up: up.ok().map(|raw| raw_to_side(&raw, now_ms)),
+                token_id_down: down.ok().map(|raw| raw_to_side(&raw, now_ms)),
+            })
+        }
+    }
+}
+
+/// Tick size from raw book (None if missing or unparseable).
+pub fn tick_size_from_raw(raw: &OrderBookRaw) -> Option<Decimal> {
+    raw.tick_size.as_deref().and_then(parse_positive)
+}
+
+/// Min order size from raw book (None if missing or unparseable).
+pub fn min_order_size_from_raw(raw: &OrderBookRaw) -> Option<Decimal> {
+    raw.min_order_size.as_deref().and_then(parse_positive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The fast path builds the same value and scale as Decimal::from_str.
+    fn assert_fast(s: &str) {
+        let fast = fast_decimal(s.as_bytes()).expect(s);
+        let std = Decimal::from_str(s).expect(s);
+        assert_eq!(fast, std, "{s}");
+        assert_eq!(fast.scale(), std.scale(), "{s}");
+        assert_eq!(parse_decimal(s), Some(std), "{s}");
+    }
+
+    #[test]
+    fn fast_path_matches_from_str_with_scale() {
+        for s in ["0", "12", "0.5", "0.50", "0.001", "100.25", "0.990", "007"] {
+            assert_fast(s);
+        }
+        assert_eq!(parse_decimal("0.50").unwrap().to_string(), "0.50");
+    }
+
+    #[test]
+    fn leading_and_trailing_point() {
+        assert_fast(".5");
+        assert_fast("5.");
+        assert_fast(".05");
+        assert_eq!(fast_decimal(b"."), None);
+        assert_eq!(parse_decimal("."), None);
+    }
+
+    #[test]
+    fn nineteen_digits_stay_on_the_fast_path() {
+        for s in [
+            "9999999999999999999",
+            "1234567890123456789",
+            "1.23456789012345678",
+            "0.00000000000000001",
+        ] {
+            assert!(s.len() <= FAST_PARSE_MAX_LEN, "{s}");
+            assert_fast(s);
+        }
+    }
+
+    #[test]
+    fn longer_input_falls_back_to_from_str() {
+        for s in [
+            "99999999999999999999",
+            "12345678901234567890",
+            "0.12345678901234567891",
+        ] {
+            assert_eq!(fast_decimal(s.as_bytes()), None, "{s}");
+            assert_eq!(parse_decimal(s), Decimal::from_str(s).ok(), "{s}");
+            assert!(parse_decimal(s).is_some(), "{s}");
+        }
+    }
+
+    #[test]
+    fn signs_go_through_from_str() {
+        for s in ["-0.5", "+1", "-3"] {
+            assert_eq!(fast_decimal(s.as_bytes()), None, "{s}");
+            assert_eq!(parse_decimal(s), Decimal::from_str(s).ok(), "{s}");
+        }
+        assert_eq!(parse_positive("-0.5"), None);
+        assert_eq!(parse_positive("0"), None);
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        for s in [
+            "", " ", "abc", "1.2.3", "1,5", "0x10", "1 2", "..5", "-", "NaN",
+        ] {
+            assert_eq!(fast_decimal(s.as_bytes()), None, "{s:?}");
+            assert_eq!(parse_decimal(s), None, "{s:?}");
+        }
+    }
+}
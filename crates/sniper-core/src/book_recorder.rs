@@ -0,0 +1,214 @@
+//! Order book recorder: samples the top N levels of both tokens from the WS book every X ms and
+//! writes one CSV per interval (`<dir>/<slug>.csv`) for analysis outside Rust.
+//!
+//! When the interval ends a `<slug>.meta.json` sidecar is written with the interval bounds, row
+//! count and last Up mid. Its outcome is the market's resolution: the runner polls Gamma for the
+//! intervals still pending and rewrites the sidecar once it resolves. Until then (or for good, if
+//! it never does within [PENDING_MAX_SECS]) the outcome is null, or with MM_BOOK_RECORD_MID_OUTCOME
+//! the one inferred from the last recorded Up mid (> 0.5 = Up).
+
+use crate::clob_ws_book::ClobWsBook;
+use crate::types::ResolvedMarket;
+use anyhow::Result;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+/// Stop waiting for the resolution of an interval this long after its close.
+pub const PENDING_MAX_SECS: u64 = 3600;
+
+/// CSV being written for the current interval.
+struct IntervalFile {
+    slug: String,
+    interval_start_unix: u64,
+    close_time_unix: u64,
+    writer: BufWriter<File>,
+    rows: u64,
+    last_up_mid: Option<Decimal>,
+}
+
+/// Samples WS depth into per-interval CSV files.
+pub struct BookRecorder {
+    dir: PathBuf,
+    levels: usize,
+    sample_ms: u64,
+    last_sample_ms: u64,
+    current: Option<IntervalFile>,
+    /// MM_BOOK_RECORD_MID_OUTCOME: label an unresolved interval with the outcome of its last mid.
+    mid_outcome: bool,
+    /// Finished intervals waiting for their resolution: (slug, close time).
+    pending: Vec<(String, u64)>,
+}
+
+impl BookRecorder {
+    /// Creates `dir` if it does not exist.
+    pub fn new(dir: &str, levels: usize, sample_ms: u64, mid_outcome: bool) -> Result<Self> {
+        let dir = PathBuf::from(dir);
+        fs::create_dir_all(&dir)?;
+        tracing::info!(
+            "[BookRecorder] writing {} levels every {} ms to {}",
+            levels,
+            sample_ms,
+            dir.display()
+        );
+        Ok(Self {
+            dir,
+            levels: levels.max(1),
+            sample_ms,
+            last_sample_ms: 0,
+            current: None,
+            mid_outcome,
+            pending: Vec::new(),
+        })
+    }
+
+    /// True when the next sample is due.
+    pub fn due(&self, now_ms: u64) -> bool {
+        now_ms.saturating_sub(self.last_sample_ms) >= self.sample_ms
+    }
+
+    /// Write one row per token (Up, Down) with the top levels. Starts a new file on a new slug.
+    pub async fn sample(
+        &mut self,
+        market: &ResolvedMarket,
+        ws: &ClobWsBook,
+        now_ms: u64,
+    ) -> Result<()> {
+        self.last_sample_ms = now_ms;
+        if self.current.as_ref().map(|f| f.slug.as_str()) != Some(market.slug.as_str()) {
+            self.finish()?;
+            self.current = Some(self.open(market)?);
+        }
+        let levels = self.levels;
+        let Some(file) = self.current.as_mut() else {
+            return Ok(());
+        };
+        for (outcome, token_id) in [("Up", &market.token_id_up), ("Down", &market.token_id_down)] {
+            let (bids, asks) = ws.depth(token_id, levels).await;
+            if bids.is_empty() && asks.is_empty() {
+                continue;
+            }
+            if outcome == "Up" {
+                if let (Some((bid, _)), Some((ask, _))) = (bids.first(), asks.first()) {
+                    file.last_up_mid = Some((bid + ask) / dec!(2));
+                }
+            }
+            let mut row = format!("{},{},{}", now_ms, market.slug, outcome);
+            for side in [&bids, &asks] {
+                for i in 0..levels {
+                    match side.get(i) {
+                        Some((p, s)) => row.push_str(&format!(",{},{}", p, s)),
+                        None => row.push_str(",,"),
+                    }
+                }
+            }
+            writeln!(file.writer, "{}", row)?;
+            file.rows += 1;
+        }
+        Ok(())
+    }
+
+    fn open(&self, market: &ResolvedMarket) -> Result<IntervalFile> {
+        let path = self.dir.join(format!("{}.csv", market.slug));
+        let mut writer = BufWriter::new(File::create(&path)?);
+        let mut header = String::from("timestamp_ms,slug,outcome");
+        for side in ["bid", "ask"] {
+            for i in 1..=self.levels {
+                header.push_str(&format!(",{}_px_{},{}_sz_{}", side, i, side, i));
+            }
+        }
+        writeln!(writer, "{}", header)?;
+        Ok(IntervalFile {
+            slug: market.slug.clone(),
+            interval_start_unix: market.interval_start_unix,
+            close_time_unix: market.close_time_unix,
+            writer,
+            rows: 0,
+            last_up_mid: None,
+        })
+    }
+
+    /// Flush the current interval's CSV and write its meta sidecar; the interval waits for its
+    /// resolution ([BookRecorder::resolve]).
+    pub fn finish(&mut self) -> Result<()> {
+        let Some(mut file) = self.current.take() else {
+            return Ok(());
+        };
+        file.writer.flush()?;
+        let mid_outcome = file.last_up_mid.filter(|_| self.mid_outcome).map(|m| {
+            if m > dec!(0.5) {
+                "Up"
+            } else {
+                "Down"
+            }
+        });
+        let meta = serde_json::json!({
+            "slug": file.slug,
+            "interval_start_unix": file.interval_start_unix,
+            "close_time_unix": file.close_time_unix,
+            "rows": file.rows,
+            "levels": self.levels,
+            "outcome": mid_outcome,
+            "outcome_source": mid_outcome.map(|_| "last_book_mid"),
+            "up_mid_at_close": file.last_up_mid.map(|m| m.to_string()),
+        });
+        self.write_meta(&file.slug, &meta)?;
+        tracing::info!(
+            "[BookRecorder] {} rows for {} (outcome {:?}, waiting for the resolution)",
+            file.rows,
+            file.slug,
+            mid_outcome
+        );
+        self.pending.push((file.slug, file.close_time_unix));
+        Ok(())
+    }
+
+    /// Finished intervals wait for their resolution.
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Slugs of the finished intervals still waiting for their resolution. Intervals closed more
+    /// than [PENDING_MAX_SECS] before `now_unix` are given up (their outcome stays as written).
+    pub fn pending(&mut self, now_unix: u64) -> Vec<String> {
+        self.pending.retain(|(slug, close)| {
+            let waiting = now_unix.saturating_sub(*close) < PENDING_MAX_SECS;
+            if !waiting {
+                tracing::warn!(
+                    "[BookRecorder] {} not resolved {} s after the close; outcome left as written",
+                    slug,
+                    PENDING_MAX_SECS
+                );
+            }
+            waiting
+        });
+        self.pending.iter().map(|(slug, _)| slug.clone()).collect()
+    }
+
+    /// Label the interval of `slug` with its resolution ((Up, Down) payouts from Gamma).
+    pub fn resolve(&mut self, slug: &str, (up, _down): (Decimal, Decimal)) -> Result<()> {
+        self.pending.retain(|(s, _)| s != slug);
+        let path = self.dir.join(format!("{}.meta.json", slug));
+        let mut meta: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        let outcome = if up == Decimal::ONE { "Up" } else { "Down" };
+        meta["outcome"] = serde_json::json!(outcome);
+        meta["outcome_source"] = serde_json::json!("gamma");
+        self.write_meta(slug, &meta)?;
+        tracing::info!("[BookRecorder] {} resolved: {}", slug, outcome);
+        Ok(())
+    }
+
+    fn write_meta(&self, slug: &str, meta: &serde_json::Value) -> Result<()> {
+        let path = self.dir.join(format!("{}.meta.json", slug));
+        fs::write(&path, serde_json::to_string_pretty(meta)?)?;
+        Ok(())
+    }
+}
+
+impl Drop for BookRecorder {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
@@ -0,0 +1,66 @@
+//! Carryover: shares still held when their market closes (e.g. an SL retry aborted at the switch)
+//! while Gamma has not resolved the market yet. The lots leave the live inventory and wait here,
+//! with their market, until the resolution is known; the close is then logged at the real payout
+//! (RESOLUTION, counted in PnL) instead of the payout inferred from the last best bid.
+
+use crate::fee_rates::FeeRates;
+use crate::inventory::Lot;
+use crate::types::ResolvedMarket;
+use rust_decimal::Decimal;
+use serde_json::json;
+
+/// A position carried past its market's close.
+#[derive(Debug, Clone)]
+pub struct CarriedPosition {
+    /// The closed market.
+    pub market: ResolvedMarket,
+    /// Open lots of the Up token.
+    pub lots_up: Vec<Lot>,
+    /// Open lots of the Down token.
+    pub lots_down: Vec<Lot>,
+    /// (Up, Down) payout inferred from the last best bid at the close.
+    pub inferred: (Decimal, Decimal),
+    /// Fee rates of the market's tokens (fee estimate of the close).
+    pub fee_rates: FeeRates,
+    /// Min / max best bid of Up, then Down, over the interval (session log).
+    pub interval_bids: [Option<Decimal>; 4],
+    /// When the market closed (ms).
+    pub since_ms: u64,
+}
+
+impl CarriedPosition {
+    /// Shares held across both tokens.
+    pub fn size(&self) -> Decimal {
+        self.lots().map(|l| l.size).sum()
+    }
+
+    /// Cost basis of the held shares.
+    pub fn cost(&self) -> Decimal {
+        self.lots().map(|l| l.entry_price * l.size).sum()
+    }
+
+    /// PnL at (Up, Down) payouts, before fees.
+    pub fn pnl_at(&self, up: Decimal, down: Decimal) -> Decimal {
+        let value =
+            |lots: &[Lot], payout: Decimal| lots.iter().map(|l| l.size).sum::<Decimal>() * payout;
+        value(&self.lots_up, up) + value(&self.lots_down, down) - self.cost()
+    }
+
+    fn lots(&self) -> impl Iterator<Item = &Lot> {
+        self.lots_up.iter().chain(&self.lots_down)
+    }
+
+    /// Status snapshot entry (MM_STATUS_FILE).
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "slug": self.market.slug,
+            "condition_id": self.market.condition_id,
+            "shares_up": self.lots_up.iter().map(|l| l.size).sum::<Decimal>().to_string(),
+            "shares_down": self.lots_down.iter().map(|l| l.size).sum::<Decimal>().to_string(),
+            "cost": self.cost().to_string(),
+            "inferred_payout_up": self.inferred.0.to_string(),
+            "inferred_payout_down": self.inferred.1.to_string(),
+            "since_ms": self.since_ms,
+        })
+    }
+}
@@ -0,0 +1,247 @@
+//! Polygon RPC helpers shared by the on-chain tools (sweep, redeem): provider with RPC fallback,
+//! typed ERC-20 / ERC-1155 reads, nonce and EIP-1559 fee estimates, 1-of-1 Safe transactions.
+
+use anyhow::{bail, Context, Result};
+use ethers::contract::abigen;
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, BlockNumber, Bytes, H160, U256};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Default Polygon RPC when POLYGON_RPC_URL is unset.
+pub const DEFAULT_POLYGON_RPC_URL: &str = "https://polygon-rpc.com";
+/// Bridged USDC (USDC.e) on Polygon, the Polymarket collateral token.
+pub const USDC_E_POLYGON: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+/// Conditional Tokens (ERC-1155 outcome shares) on Polygon.
+pub const CONDITIONAL_TOKENS_POLYGON: &str = "0x4D97DCd97eC945f40cF65F87097ACe5EA0476045";
+pub const USDC_DECIMALS: u32 = 6;
+/// Polygon rejects priority fees below 30 gwei.
+const MIN_PRIORITY_FEE_GWEI: u64 = 30;
+
+abigen!(
+    Erc20,
+    r#"[
+        function balanceOf(address account) external view returns (uint256)
+        function allowance(address owner, address spender) external view returns (uint256)
+        function transfer(address to, uint256 amount) external returns (bool)
+    ]"#
+);
+
+abigen!(
+    Erc1155,
+    r#"[
+        function isApprovedForAll(address account, address operator) external view returns (bool)
+    ]"#
+);
+
+abigen!(
+    ConditionalTokens,
+    r#"[
+        function redeemPositions(address collateralToken, bytes32 parentCollectionId, bytes32 conditionId, uint256[] indexSets) external
+    ]"#
+);
+
+abigen!(
+    GnosisSafe,
+    r#"[
+        function nonce() external view returns (uint256)
+        function getThreshold() external view returns (uint256)
+        function isOwner(address owner) external view returns (bool)
+        function getTransactionHash(address to, uint256 value, bytes data, uint8 operation, uint256 safeTxGas, uint256 baseGas, uint256 gasPrice, address gasToken, address refundReceiver, uint256 _nonce) external view returns (bytes32)
+        function execTransaction(address to, uint256 value, bytes data, uint8 operation, uint256 safeTxGas, uint256 baseGas, uint256 gasPrice, address gasToken, address payable refundReceiver, bytes signatures) external payable returns (bool)
+    ]"#
+);
+
+/// RPC URLs from POLYGON_RPC_URL (comma-separated, tried in order); default polygon-rpc.com.
+pub fn rpc_urls_from_env() -> Vec<String> {
+    let urls: Vec<String> = std::env::var("POLYGON_RPC_URL")
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if urls.is_empty() {
+        vec![DEFAULT_POLYGON_RPC_URL.to_string()]
+    } else {
+        urls
+    }
+}
+
+/// Provider for the first URL that answers eth_chainId; the others are fallbacks.
+pub async fn connect(urls: &[String]) -> Result<Provider<Http>> {
+    for url in urls {
+        let provider = match Provider::<Http>::try_from(url.as_str()) {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("[Chain] invalid RPC URL {}: {}", url, e);
+                continue;
+            }
+        };
+        match provider.get_chainid().await {
+            Ok(_) => return Ok(provider),
+            Err(e) => warn!("[Chain] RPC {} unavailable: {}", url, e),
+        }
+    }
+    bail!("no Polygon RPC reachable (tried {})", urls.join(", "))
+}
+
+/// USDC amount to token units (6 decimals), truncated.
+pub fn usdc_to_units(amount: Decimal) -> Result<U256> {
+    let units = (amount * Decimal::from(10u64.pow(USDC_DECIMALS)))
+        .trunc()
+        .to_u128()
+        .context("USDC amount out of range")?;
+    Ok(U256::from(units))
+}
+
+/// Token units (6 decimals) to USDC; saturates at Decimal::MAX.
+pub fn usdc_from_units(units: U256) -> Decimal {
+    let units = units.min(U256::from(i128::MAX as u128)).as_u128() as i128;
+    Decimal::try_from_i128_with_scale(units, USDC_DECIMALS).unwrap_or(Decimal::MAX)
+}
+
+/// ERC-20 balanceOf (token units).
+pub async fn erc20_balance<M: Middleware + 'static>(
+    client: Arc<M>,
+    token: Address,
+    owner: H160,
+) -> Result<U256> {
+    Erc20::new(token, client)
+        .balance_of(owner)
+        .call()
+        .await
+        .with_context(|| format!("balanceOf {:?} on {:?}", owner, token))
+}
+
+/// ERC-20 allowance (token units).
+pub async fn erc20_allowance<M: Middleware + 'static>(
+    client: Arc<M>,
+    token: Address,
+    owner: H160,
+    spender: H160,
+) -> Result<U256> {
+    Erc20::new(token, client)
+        .allowance(owner, spender)
+        .call()
+        .await
+        .with_context(|| format!("allowance {:?} -> {:?} on {:?}", owner, spender, token))
+}
+
+/// ERC-1155 isApprovedForAll (e.g. Conditional Tokens for an exchange contract).
+pub async fn erc1155_is_approved_for_all<M: Middleware + 'static>(
+    client: Arc<M>,
+    token: Address,
+    owner: H160,
+    operator: H160,
+) -> Result<bool> {
+    Erc1155::new(token, client)
+        .is_approved_for_all(owner, operator)
+        .call()
+        .await
+        .with_context(|| {
+            format!(
+                "isApprovedForAll {:?} -> {:?} on {:?}",
+                owner, operator, token
+            )
+        })
+}
+
+/// Next nonce for `address`, counting transactions still in the mempool.
+pub async fn pending_nonce<M: Middleware>(client: &M, address: H160) -> Result<U256> {
+    client
+        .get_transaction_count(address, Some(BlockNumber::Pending.into()))
+        .await
+        .map_err(|e| anyhow::anyhow!("eth_getTransactionCount {:?}: {}", address, e))
+}
+
+/// EIP-1559 (max_fee_per_gas, max_priority_fee_per_gas), with the priority fee raised to
+/// Polygon's 30 gwei minimum.
+pub async fn eip1559_fees<M: Middleware>(client: &M) -> Result<(U256, U256)> {
+    let (max_fee, priority) = client
+        .estimate_eip1559_fees(None)
+        .await
+        .map_err(|e| anyhow::anyhow!("fee estimate: {}", e))?;
+    let min_priority = U256::from(MIN_PRIORITY_FEE_GWEI) * U256::exp10(9);
+    let bump = min_priority.saturating_sub(priority);
+    Ok((max_fee + bump, priority.max(min_priority)))
+}
+
+/// Execute a call from `safe` (execTransaction, operation CALL, no refund), signed by `wallet` as
+/// the Safe's only required owner; the EOA pays the gas. Returns the transaction hash once mined.
+pub async fn exec_safe_transaction(
+    provider: Provider<Http>,
+    wallet: LocalWallet,
+    safe: H160,
+    to: Address,
+    data: Bytes,
+) -> Result<String> {
+    let chain_id = provider.get_chainid().await?.as_u64();
+    let wallet = wallet.with_chain_id(chain_id);
+    let owner = wallet.address();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet.clone()));
+    let safe_contract = GnosisSafe::new(safe, client.clone());
+
+    if !safe_contract.is_owner(owner).call().await? {
+        bail!("{:?} is not an owner of Safe {:?}", owner, safe);
+    }
+    let threshold = safe_contract.get_threshold().call().await?;
+    if threshold != U256::one() {
+        bail!(
+            "Safe {:?} needs {} signatures; only 1-of-1 Safes are supported",
+            safe,
+            threshold
+        );
+    }
+
+    let nonce = safe_contract.nonce().call().await?;
+    let tx_hash = safe_contract
+        .get_transaction_hash(
+            to,
+            U256::zero(),
+            data.clone(),
+            0,
+            U256::zero(),
+            U256::zero(),
+            U256::zero(),
+            Address::zero(),
+            Address::zero(),
+            nonce,
+        )
+        .call()
+        .await?;
+    // Owner signature over the Safe tx hash itself (v = 27/28, no eth_sign prefix).
+    let signature = wallet.sign_hash(tx_hash.into())?;
+    info!("[Chain] Safe {:?} nonce {}: call to {:?}", safe, nonce, to);
+    let mut call = safe_contract.exec_transaction(
+        to,
+        U256::zero(),
+        data,
+        0,
+        U256::zero(),
+        U256::zero(),
+        U256::zero(),
+        Address::zero(),
+        Address::zero(),
+        Bytes::from(signature.to_vec()),
+    );
+    let (max_fee, priority_fee) = eip1559_fees(client.as_ref()).await?;
+    if let Some(tx) = call.tx.as_eip1559_mut() {
+        tx.max_fee_per_gas = Some(max_fee);
+        tx.max_priority_fee_per_gas = Some(priority_fee);
+    }
+    call.tx
+        .set_nonce(pending_nonce(client.as_ref(), owner).await?);
+    let pending = call.send().await.context("send execTransaction")?;
+    let hash = format!("{:?}", pending.tx_hash());
+    let receipt = pending
+        .await?
+        .with_context(|| format!("execTransaction {} dropped", hash))?;
+    if receipt.status != Some(1u64.into()) {
+        bail!("execTransaction {} reverted", hash);
+    }
+    Ok(hash)
+}
@@ -0,0 +1,220 @@
+//! Fault injection for dry runs (cargo feature `chaos`, then MM_CHAOS=true): the dry-run / sim
+//! client is wrapped in [ChaosClob], which delays every order, answers some with 400/429/500 and
+//! cuts some FAK orders short, and the WS book drops all frames for a while now and then. A 500
+//! may come back after the order was actually executed, as a lost response does live. Used to
+//! check that the engine never double-buys or over-sells under faults. Never wraps a live client.
+
+use crate::clob::{CancelOrdersResult, ClobClient, LimitOrderParams, OrderType, PlaceOrderResult};
+use crate::clock::wall_ms;
+use crate::trades::Fill;
+use anyhow::{Context, Result};
+use rand::Rng;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Fault rates (percent of orders or WS frames) and latency, from MM_CHAOS_*.
+#[derive(Debug, Clone)]
+pub struct ChaosConfig {
+    /// Added before every order and cancel, uniform in [min, max] ms.
+    pub latency_min_ms: u64,
+    pub latency_max_ms: u64,
+    pub http_400_pct: f64,
+    pub http_429_pct: f64,
+    pub http_500_pct: f64,
+    /// Share of the 500s where the order was executed before the response was lost.
+    pub http_500_placed_pct: f64,
+    /// FAK orders sent for 10–90% of their size.
+    pub partial_fill_pct: f64,
+    /// WS text frames that start a gap of `ws_gap_ms` with no book updates.
+    pub ws_gap_pct: f64,
+    pub ws_gap_ms: u64,
+}
+
+fn env_num<T: FromStr>(name: &str, default: T) -> Result<T>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match std::env::var(name) {
+        Ok(v) if !v.trim().is_empty() => v
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid {}", name)),
+        _ => Ok(default),
+    }
+}
+
+impl ChaosConfig {
+    /// None unless MM_CHAOS=true.
+    pub fn from_env() -> Result<Option<Self>> {
+        let on = std::env::var("MM_CHAOS")
+            .map(|v| v.to_lowercase() == "true" || v == "1")
+            .unwrap_or(false);
+        if !on {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            latency_min_ms: env_num("MM_CHAOS_LATENCY_MIN_MS", 50)?,
+            latency_max_ms: env_num("MM_CHAOS_LATENCY_MAX_MS", 500)?,
+            http_400_pct: env_num("MM_CHAOS_400_PCT", 5.0)?,
+            http_429_pct: env_num("MM_CHAOS_429_PCT", 5.0)?,
+            http_500_pct: env_num("MM_CHAOS_500_PCT", 5.0)?,
+            http_500_placed_pct: env_num("MM_CHAOS_500_PLACED_PCT", 50.0)?,
+            partial_fill_pct: env_num("MM_CHAOS_PARTIAL_PCT", 20.0)?,
+            ws_gap_pct: env_num("MM_CHAOS_WS_GAP_PCT", 0.5)?,
+            ws_gap_ms: env_num("MM_CHAOS_WS_GAP_MS", 3000)?,
+        }))
+    }
+}
+
+static CONFIG: OnceLock<Option<ChaosConfig>> = OnceLock::new();
+/// WS frames are dropped until this time (unix ms).
+static WS_GAP_UNTIL_MS: AtomicU64 = AtomicU64::new(0);
+
+/// The process-wide chaos settings; None when off (or MM_CHAOS_* is invalid, logged once).
+pub fn global() -> Option<&'static ChaosConfig> {
+    CONFIG
+        .get_or_init(|| match ChaosConfig::from_env() {
+            Ok(cfg) => {
+                if let Some(ref c) = cfg {
+                    info!("[Chaos] fault injection on: {:?}", c);
+                }
+                cfg
+            }
+            Err(e) => {
+                warn!("[Chaos] disabled: {:#}", e);
+                None
+            }
+        })
+        .as_ref()
+}
+
+fn roll(pct: f64) -> bool {
+    pct > 0.0 && rand::thread_rng().gen_range(0.0..100.0) < pct
+}
+
+/// Whether the WS book should drop this frame: inside a gap, or starting one.
+pub fn drop_ws_frame() -> bool {
+    let Some(cfg) = global() else {
+        return false;
+    };
+    let now = wall_ms();
+    if now < WS_GAP_UNTIL_MS.load(Ordering::Relaxed) {
+        return true;
+    }
+    if roll(cfg.ws_gap_pct) {
+        info!("[Chaos] WS gap for {} ms", cfg.ws_gap_ms);
+        WS_GAP_UNTIL_MS.store(now + cfg.ws_gap_ms, Ordering::Relaxed);
+        return true;
+    }
+    false
+}
+
+fn http_failure(status: u16, msg: &str) -> PlaceOrderResult {
+    PlaceOrderResult {
+        http_status: Some(status),
+        ..PlaceOrderResult::failed(format!("HTTP {}: {} (chaos)", status, msg))
+    }
+}
+
+/// A dry-run client with injected latency and failures.
+pub struct ChaosClob {
+    inner: Box<dyn ClobClient>,
+    cfg: &'static ChaosConfig,
+}
+
+impl ChaosClob {
+    pub fn new(inner: Box<dyn ClobClient>, cfg: &'static ChaosConfig) -> Self {
+        Self { inner, cfg }
+    }
+
+    async fn delay(&self) {
+        let (lo, hi) = (
+            self.cfg.latency_min_ms,
+            self.cfg.latency_max_ms.max(self.cfg.latency_min_ms),
+        );
+        let ms = rand::thread_rng().gen_range(lo..=hi);
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+    }
+}
+
+#[async_trait::async_trait]
+impl ClobClient for ChaosClob {
+    async fn place_limit_order(
+        &self,
+        mut params: LimitOrderParams,
+        order_type: OrderType,
+    ) -> Result<PlaceOrderResult> {
+        self.delay().await;
+        if roll(self.cfg.http_429_pct) {
+            info!("[Chaos] 429 for {:?} {}", params.side, params.size);
+            return Ok(http_failure(429, "rate limited"));
+        }
+        if roll(self.cfg.http_400_pct) {
+            info!("[Chaos] 400 for {:?} {}", params.side, params.size);
+            return Ok(http_failure(400, "invalid order"));
+        }
+        if roll(self.cfg.http_500_pct) {
+            let placed = roll(self.cfg.http_500_placed_pct);
+            info!(
+                "[Chaos] 500 for {:?} {} (order {})",
+                params.side,
+                params.size,
+                if placed { "executed" } else { "not placed" }
+            );
+            if placed {
+                let _ = self.inner.place_limit_order(params, order_type).await;
+            }
+            return Ok(http_failure(500, "internal error"));
+        }
+        if matches!(order_type, OrderType::Fak) && roll(self.cfg.partial_fill_pct) {
+            let pct = Decimal::from(rand::thread_rng().gen_range(10u32..=90)) / Decimal::from(100);
+            let size = (params.size * pct).round_dp(2);
+            if size > Decimal::ZERO {
+                info!(
+                    "[Chaos] FAK {:?} {} cut to {}",
+                    params.side, params.size, size
+                );
+                params.size = size;
+            }
+        }
+        self.inner.place_limit_order(params, order_type).await
+    }
+
+    async fn cancel_orders_for_token(&self, token_id: &str) -> Result<CancelOrdersResult> {
+        self.delay().await;
+        self.inner.cancel_orders_for_token(token_id).await
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<CancelOrdersResult> {
+        self.delay().await;
+        self.inner.cancel_order(order_id).await
+    }
+
+    async fn get_balance_allowance(&self, token_id: &str) -> Result<String> {
+        self.inner.get_balance_allowance(token_id).await
+    }
+
+    async fn get_available_balance(&self, token_id: &str) -> Result<Option<Decimal>> {
+        self.inner.get_available_balance(token_id).await
+    }
+
+    async fn warm_up(&self) {
+        self.inner.warm_up().await
+    }
+
+    async fn get_collateral_balance(&self) -> Result<Option<Decimal>> {
+        self.inner.get_collateral_balance().await
+    }
+
+    fn funders(&self) -> Vec<String> {
+        self.inner.funders()
+    }
+
+    async fn get_trades(&self, after_unix: u64) -> Result<Vec<Fill>> {
+        self.inner.get_trades(after_unix).await
+    }
+}
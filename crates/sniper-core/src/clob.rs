@@ -0,0 +1,1680 @@
+//! CLOB client: place/cancel orders. Dry-run implementation logs only; live uses EIP-712 signing + HMAC L2.
+
+use crate::book::{fetch_fee_rate_bps, public_client};
+use crate::clock::{Clock, SystemClock};
+use crate::multi_clob::{AccountMode, MultiClob, MAX_ACCOUNTS};
+use crate::order_journal::{JournalEntry, JournalOutcome, OrderJournal};
+use crate::rate_limit::EndpointClass;
+use crate::retry;
+use crate::signing::{
+    parse_token_id, polymarket_proxy_address, polymarket_safe_address, OrderFields, OrderSigner,
+    PolyHmacKey, EXCHANGE_ADDRESS_POLYGON, NEG_RISK_EXCHANGE_POLYGON, SIGNATURE_TYPE_EOA,
+    SIGNATURE_TYPE_GNOSIS_SAFE, SIGNATURE_TYPE_POLY_PROXY,
+};
+use crate::sim_clob::SimClob;
+use crate::trades::{parse_trades_page, Fill};
+use crate::types::SellOrderTimeInForce;
+use anyhow::{Context, Result};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::H160;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use tracing::{debug, info, warn};
+
+const CONDITIONAL_BASE_DECIMALS: u32 = 6;
+const CONDITIONAL_BASE_FACTOR: Decimal = dec!(1000000);
+
+/// Order type for placement.
+#[derive(Debug, Clone, Copy)]
+pub enum OrderType {
+    /// Good till cancelled: rests on the book.
+    Gtc,
+    /// Good till date: rests until [LimitOrderParams::expiration_unix].
+    Gtd,
+    /// Fill or kill: fills in full at once or not at all.
+    Fok,
+    /// Fill and kill: fills what it can at once, the rest is cancelled.
+    Fak,
+}
+
+fn order_type_str(t: OrderType) -> &'static str {
+    match t {
+        OrderType::Gtc => "GTC",
+        OrderType::Gtd => "GTD",
+        OrderType::Fok => "FOK",
+        OrderType::Fak => "FAK",
+    }
+}
+
+/// The CLOB rejects GTD orders that expire less than a minute out.
+pub const GTD_MIN_LIFETIME_SECS: u64 = 60;
+
+/// Max orders per POST /orders request (CLOB batch limit).
+const MAX_BATCH_ORDERS: usize = 15;
+/// After an unclear order POST, wait this long before looking the order up.
+const AMBIGUOUS_LOOKUP_DELAY_MS: u64 = 500;
+/// Journaled orders this old without an outcome are looked up at warm-up.
+const JOURNAL_RECOVER_AGE_MS: u64 = 60_000;
+
+/// An order POST that may or may not have reached the book (gateway error or timeout status).
+fn is_ambiguous(r: &PlaceOrderResult) -> bool {
+    !r.success && r.http_status.is_some_and(|s| s >= 500)
+}
+
+fn journal_outcome(r: &PlaceOrderResult) -> JournalOutcome {
+    if r.success {
+        JournalOutcome::Accepted
+    } else {
+        JournalOutcome::Rejected
+    }
+}
+
+/// Estimated fee in USDC for a fill: rate * min(price, 1 - price) * size (Polymarket fee curve).
+pub fn estimated_fee_usd(fee_rate_bps: u64, price: Decimal, size: Decimal) -> Decimal {
+    let rate = Decimal::from(fee_rate_bps) / dec!(10000);
+    rate * price.min(Decimal::ONE - price).max(Decimal::ZERO) * size
+}
+
+/// Why the CLOB refused an order, parsed once from the response (error code or message) so the
+/// retry / give-up decisions do not match on strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClobError {
+    /// Not enough balance or allowance: the shares are already sold, or still locked by an order.
+    InsufficientBalance,
+    /// Maker or taker amount rounds to zero (dust size, or the balance has not caught up yet).
+    InvalidAmounts,
+    /// FOK/FAK order found nothing to match at its limit.
+    NoMatch,
+    /// The same signed order was already posted.
+    Duplicate,
+    /// Not an answer from the exchange: the order's POST failed without a clear answer and its
+    /// lookup failed too, so it may be on the book (and filled) or not.
+    Unresolved,
+    /// Anything else, with the exchange's message.
+    Other(String),
+}
+
+/// Error body of a refused order: `{"error": "..."}` on an HTTP error, `errorMsg` in an order
+/// response, and an error `code` when the API sends one.
+#[derive(serde::Deserialize)]
+struct ErrorBody {
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default, alias = "errorMsg")]
+    error: Option<String>,
+}
+
+impl ClobError {
+    /// Classify an error body (JSON with `code` / `error` / `errorMsg`) or a bare error code or
+    /// message.
+    pub fn parse(body: &str) -> Self {
+        let body = body.trim();
+        let Ok(ErrorBody { code, error }) = serde_json::from_str::<ErrorBody>(body) else {
+            return Self::from_code(body)
+                .or_else(|| Self::from_message(body))
+                .unwrap_or_else(|| ClobError::Other(body.to_string()));
+        };
+        code.as_deref()
+            .and_then(Self::from_code)
+            .or_else(|| error.as_deref().and_then(Self::from_code))
+            .or_else(|| error.as_deref().and_then(Self::from_message))
+            .unwrap_or_else(|| ClobError::Other(error.or(code).unwrap_or_else(|| body.to_string())))
+    }
+
+    /// The API's error codes.
+    fn from_code(code: &str) -> Option<Self> {
+        match code.trim() {
+            "INVALID_ORDER_NOT_ENOUGH_BALANCE" => Some(ClobError::InsufficientBalance),
+            "INVALID_ORDER_DUPLICATED" => Some(ClobError::Duplicate),
+            "FOK_ORDER_NOT_FILLED_ERROR" => Some(ClobError::NoMatch),
+            _ => None,
+        }
+    }
+
+    /// The API's error messages (details after the fixed start vary per order).
+    fn from_message(msg: &str) -> Option<Self> {
+        let msg = msg.trim().to_lowercase();
+        if msg.starts_with("not enough balance") {
+            Some(ClobError::InsufficientBalance)
+        } else if msg.starts_with("invalid amounts") {
+            Some(ClobError::InvalidAmounts)
+        } else if msg.starts_with("order couldn't be fully filled")
+            || msg.starts_with("no orders found to match")
+        {
+            Some(ClobError::NoMatch)
+        } else if msg.trim_end_matches('.').ends_with("duplicated") {
+            // "order 0x… is invalid. Duplicated."
+            Some(ClobError::Duplicate)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for ClobError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClobError::InsufficientBalance => write!(f, "not enough balance / allowance"),
+            ClobError::InvalidAmounts => write!(f, "invalid amounts"),
+            ClobError::NoMatch => write!(f, "no orders found to match"),
+            ClobError::Duplicate => write!(f, "duplicated order"),
+            ClobError::Unresolved => write!(f, "order outcome unknown"),
+            ClobError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// Result of placing an order.
+#[derive(Debug)]
+pub struct PlaceOrderResult {
+    /// Exchange order ID (the order hash), when the order was accepted.
+    pub order_id: Option<String>,
+    /// The exchange accepted the order.
+    pub success: bool,
+    /// Error text from the exchange or the transport; None on success.
+    pub error_msg: Option<String>,
+    /// `error_msg` classified; None on success.
+    pub error: Option<ClobError>,
+    /// Filled size in shares (from API takingAmount when matched). Use this for TP/SL so we sell 100% of what was actually bought.
+    pub filled_size: Option<Decimal>,
+    /// HTTP status from the order API (e.g. 400 when TP/SL fails with balance/allowance).
+    pub http_status: Option<u16>,
+    /// Average price of the immediate fill (from makingAmount / takingAmount), when known.
+    pub avg_price: Option<Decimal>,
+    /// Errors of the parts that failed when the order was split across accounts while others
+    /// went through (`success` is then true and `filled_size` covers only those). Empty otherwise.
+    pub failed_parts: Vec<ClobError>,
+}
+
+impl PlaceOrderResult {
+    pub(crate) fn failed(error_msg: String) -> Self {
+        Self {
+            order_id: None,
+            success: false,
+            error: Some(ClobError::parse(&error_msg)),
+            error_msg: Some(error_msg),
+            filled_size: None,
+            http_status: None,
+            avg_price: None,
+            failed_parts: Vec::new(),
+        }
+    }
+
+    /// Order `order_id` is on the exchange, fill not known yet.
+    pub(crate) fn accepted(order_id: &str) -> Self {
+        Self {
+            order_id: Some(order_id.to_string()),
+            success: true,
+            error_msg: None,
+            error: None,
+            filled_size: None,
+            http_status: None,
+            avg_price: None,
+            failed_parts: Vec::new(),
+        }
+    }
+
+    /// Order `order_id` whose outcome could not be read back ([ClobError::Unresolved]).
+    pub(crate) fn unresolved(order_id: &str, error_msg: String) -> Self {
+        Self {
+            order_id: Some(order_id.to_string()),
+            error: Some(ClobError::Unresolved),
+            ..Self::failed(error_msg)
+        }
+    }
+
+    /// The exchange refused the order with `kind`.
+    pub fn is_error(&self, kind: &ClobError) -> bool {
+        self.error.as_ref() == Some(kind)
+    }
+}
+
+/// Parameters for a limit order.
+#[derive(Debug, Clone)]
+pub struct LimitOrderParams {
+    /// Outcome token (decimal token ID).
+    pub token_id: String,
+    /// Buy or sell the token.
+    pub side: OrderSide,
+    /// Limit price per share (USDC).
+    pub price: Decimal,
+    /// Size in shares.
+    pub size: Decimal,
+    /// Expiry (unix seconds) of a GTD order; None for the other order types.
+    pub expiration_unix: Option<u64>,
+    /// Reject the order instead of matching it on arrival (GTC / GTD only).
+    pub post_only: bool,
+    /// Fee rate to sign; None = the token's rate from GET /fee-rate.
+    pub fee_rate_bps: Option<u64>,
+    /// The market's min order size (None = [crate::market::DEFAULT_MIN_ORDER_SIZE]); a buy split
+    /// across accounts keeps every part at least this large.
+    pub min_order_size: Option<Decimal>,
+}
+
+/// Side of an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSide {
+    /// Buy the token with USDC.
+    Buy,
+    /// Sell held shares of the token.
+    Sell,
+}
+
+/// Result of cancelling orders (e.g. cancel-market-orders).
+#[derive(Debug, Default)]
+pub struct CancelOrdersResult {
+    /// IDs of the cancelled orders.
+    pub canceled: Vec<String>,
+    /// Order ID -> reason, for orders the exchange did not cancel.
+    pub not_canceled: std::collections::HashMap<String, String>,
+}
+
+/// Abstraction for CLOB order placement (dry-run or live).
+#[async_trait::async_trait]
+pub trait ClobClient: Send + Sync {
+    /// Sign and submit one limit order. Exchange refusals come back as a failed
+    /// [PlaceOrderResult]; `Err` is for orders that could not be built or sent.
+    async fn place_limit_order(
+        &self,
+        params: LimitOrderParams,
+        order_type: OrderType,
+    ) -> Result<PlaceOrderResult>;
+
+    /// Place several orders together (e.g. Up and Down for dual-side entry) so one side does not
+    /// fill while the other is still waiting for its round trip. Results are in input order; a
+    /// per-order transport error is reported as a failed result rather than aborting the batch.
+    /// Default: submit concurrently via [ClobClient::place_limit_order].
+    async fn place_orders_batch(
+        &self,
+        orders: Vec<(LimitOrderParams, OrderType)>,
+    ) -> Result<Vec<PlaceOrderResult>> {
+        let futs = orders
+            .into_iter()
+            .map(|(params, order_type)| self.place_limit_order(params, order_type));
+        Ok(futures_util::future::join_all(futs)
+            .await
+            .into_iter()
+            .map(|r| r.unwrap_or_else(|e| PlaceOrderResult::failed(e.to_string())))
+            .collect())
+    }
+
+    /// Cancel all open orders for a given outcome token. Use before placing SL/TP sell so any
+    /// resting GTC order (e.g. TP) does not lock balance and cause "not enough balance" on SL.
+    async fn cancel_orders_for_token(&self, _token_id: &str) -> Result<CancelOrdersResult> {
+        Ok(CancelOrdersResult::default())
+    }
+
+    /// Cancel one order by its exchange ID (DELETE /order). Default: nothing to cancel.
+    async fn cancel_order(&self, _order_id: &str) -> Result<CancelOrdersResult> {
+        Ok(CancelOrdersResult::default())
+    }
+
+    /// Fetch balance/allowance for conditional token (GET /balance-allowance?asset_type=CONDITIONAL&token_id=...&signature_type=...).
+    /// Used when TP/SL returns 400 to debug balance/allowance.
+    async fn get_balance_allowance(&self, _token_id: &str) -> Result<String> {
+        Ok("(not available)".to_string())
+    }
+
+    /// Available balance for token (from balance-allowance endpoint). Returns None on error or parse failure.
+    /// Used to compute sell_size = min(position_size_real, available) for TP/SL.
+    async fn get_available_balance(&self, _token_id: &str) -> Result<Option<Decimal>> {
+        Ok(None)
+    }
+
+    /// Open the connection to the CLOB ahead of the next order (startup, interval switch) so the
+    /// first order does not pay the TLS handshake. No-op by default.
+    async fn warm_up(&self) {}
+
+    /// USDC (collateral) available to the funder (GET /balance-allowance?asset_type=COLLATERAL).
+    /// None when unknown (e.g. dry run).
+    async fn get_collateral_balance(&self) -> Result<Option<Decimal>> {
+        Ok(None)
+    }
+
+    /// Addresses that hold this client's positions (for the data API). Empty when not trading
+    /// live.
+    fn funders(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Our fills since `after_unix` from the trades history (GET /data/trades). Empty when not
+    /// trading live.
+    async fn get_trades(&self, _after_unix: u64) -> Result<Vec<Fill>> {
+        Ok(Vec::new())
+    }
+
+    /// Sell `size` shares of `token_id` at `price` with the given time in force.
+    async fn place_sell_order(
+        &self,
+        token_id: &str,
+        price: Decimal,
+        size: Decimal,
+        time_in_force: SellOrderTimeInForce,
+    ) -> Result<PlaceOrderResult> {
+        let (order_type, expiration_unix) = match time_in_force {
+            SellOrderTimeInForce::Gtc => (OrderType::Gtc, None),
+            SellOrderTimeInForce::Gtd { expiration_unix } => {
+                (OrderType::Gtd, Some(expiration_unix))
+            }
+            SellOrderTimeInForce::Fok => (OrderType::Fok, None),
+            SellOrderTimeInForce::Fak => (OrderType::Fak, None),
+        };
+        self.place_limit_order(
+            LimitOrderParams {
+                token_id: token_id.to_string(),
+                side: OrderSide::Sell,
+                price,
+                size,
+                expiration_unix,
+                post_only: false,
+                fee_rate_bps: None,
+                min_order_size: None,
+            },
+            order_type,
+        )
+        .await
+    }
+}
+
+/// Dry-run: log order and return success with fake order ID.
+pub struct DryRunClob;
+
+#[async_trait::async_trait]
+impl ClobClient for DryRunClob {
+    async fn place_limit_order(
+        &self,
+        params: LimitOrderParams,
+        order_type: OrderType,
+    ) -> Result<PlaceOrderResult> {
+        let side_str = match params.side {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        };
+        let type_str = order_type_str(order_type);
+        info!(
+            "[DryRun] place_limit_order {} {} @ {} size={} type={} token_id={}",
+            side_str,
+            type_str,
+            params.price,
+            params.size,
+            type_str,
+            &params.token_id[..params.token_id.len().min(18)]
+        );
+        Ok(PlaceOrderResult {
+            order_id: Some("dry-run".to_string()),
+            success: true,
+            error_msg: None,
+            error: None,
+            filled_size: Some(params.size),
+            http_status: None,
+            avg_price: None,
+            failed_parts: Vec::new(),
+        })
+    }
+}
+
+/// Live CLOB client: EIP-712 order signing + HMAC L2 auth.
+pub struct LiveClob {
+    clob_host: String,
+    wallet: LocalWallet,
+    api_key: String,
+    /// L2 SECRET, decoded once.
+    hmac_key: PolyHmacKey,
+    /// Signer address as sent in POLY_ADDRESS and the order's `signer` field.
+    signer_addr: String,
+    /// Domain separator and account fields for order signing, computed once.
+    order_signer: OrderSigner,
+    api_passphrase: String,
+    funder: H160,
+    signature_type: u8,
+    /// MM_FEE_RATE_BPS: use this fee for every order instead of fetching it per token.
+    fee_rate_bps_override: Option<u64>,
+    /// MM_SIGNING_WORKERS: threads a batch's signatures are spread over (0 or 1 = in order).
+    signing_workers: usize,
+    /// Fee rate per token_id, fetched once from GET /fee-rate.
+    fee_rates: std::sync::Mutex<std::collections::HashMap<String, u64>>,
+    /// Submitted orders by client order ID (MM_ORDER_JOURNAL{suffix} when set).
+    journal: OrderJournal,
+    /// Authed requests (orders, cancels, balance, order / trade lookups).
+    client: reqwest::Client,
+    /// Public reads (GET /fee-rate), on their own pool: see book::public_client.
+    reads: reqwest::Client,
+    /// Retry backoffs are slept on this clock (the wall clock unless [LiveClob::with_clock]).
+    clock: Arc<dyn Clock>,
+}
+
+/// [retry::send_with_clock] on `clob`'s clock.
+async fn retry_send<F>(
+    clob: &LiveClob,
+    class: EndpointClass,
+    label: &str,
+    build: F,
+) -> Result<reqwest::Response>
+where
+    F: Fn() -> Result<reqwest::RequestBuilder>,
+{
+    retry::send_with_clock(clob.clock.as_ref(), class, label, build).await
+}
+
+impl LiveClob {
+    /// The primary account ([LiveClob::from_env_account] with no suffix).
+    pub fn from_env() -> Result<Self> {
+        Self::from_env_account("")
+    }
+
+    /// Account credentials from PRIVATE_KEY{suffix}, API_KEY{suffix}, SECRET{suffix},
+    /// PASSPHRASE{suffix}, FUNDER_ADDRESS{suffix} and SIGNATURE_TYPE{suffix} (suffix "" for the
+    /// primary account, "_2", "_3", ... for extra accounts). Host, chain and fee settings are shared.
+    pub fn from_env_account(suffix: &str) -> Result<Self> {
+        let var = |key: &str| std::env::var(format!("{}{}", key, suffix));
+        let clob_host = std::env::var("POLYMARKET_CLOB_HOST")
+            .or_else(|_| std::env::var("POLYMARKET_CLOB_URL"))
+            .unwrap_or_else(|_| "https://clob.polymarket.com".to_string());
+        let pk = var("PRIVATE_KEY")
+            .or_else(|_| var("POLYMARKET_PRIVATE_KEY"))
+            .with_context(|| {
+                format!(
+                    "PRIVATE_KEY{0} or POLYMARKET_PRIVATE_KEY{0} required for live CLOB",
+                    suffix
+                )
+            })?;
+        let wallet = pk
+            .trim()
+            .strip_prefix("0x")
+            .unwrap_or(pk.trim())
+            .parse::<LocalWallet>()
+            .with_context(|| format!("Invalid PRIVATE_KEY{}", suffix))?;
+        let api_key = var("API_KEY").with_context(|| format!("API_KEY{} required", suffix))?;
+        let api_secret = var("SECRET")
+            .or_else(|_| var("API_SECRET"))
+            .with_context(|| format!("SECRET{0} or API_SECRET{0} required", suffix))?;
+        let api_passphrase = var("PASSPHRASE")
+            .or_else(|_| var("API_PASSPHRASE"))
+            .with_context(|| format!("PASSPHRASE{} required", suffix))?;
+        let chain_id: u64 = std::env::var("POLYMARKET_CHAIN_ID")
+            .unwrap_or_else(|_| "137".to_string())
+            .parse()
+            .unwrap_or(137);
+        let funder = var("FUNDER_ADDRESS")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| {
+                s.trim()
+                    .strip_prefix("0x")
+                    .unwrap_or(s.trim())
+                    .parse::<H160>()
+                    .with_context(|| format!("Invalid FUNDER_ADDRESS{}", suffix))
+            })
+            .transpose()?;
+        let explicit_signature_type = var("SIGNATURE_TYPE")
+            .ok()
+            .map(|s| {
+                s.trim()
+                    .parse::<u8>()
+                    .with_context(|| format!("Invalid SIGNATURE_TYPE{}", suffix))
+            })
+            .transpose()?;
+        let (funder, signature_type) =
+            resolve_signature_type(wallet.address(), funder, explicit_signature_type)?;
+        info!(
+            "[LiveClob] signer {:?} funder {:?} signature_type={}",
+            wallet.address(),
+            funder,
+            signature_type
+        );
+        let neg_risk = std::env::var("MM_NEG_RISK")
+            .map(|v| v.to_lowercase() == "true" || v == "1")
+            .unwrap_or(false);
+        let journal = OrderJournal::open(
+            std::env::var("MM_ORDER_JOURNAL")
+                .ok()
+                .filter(|p| !p.trim().is_empty())
+                .map(|p| format!("{}{}", p.trim(), suffix))
+                .as_deref(),
+        )?;
+        let fee_rate_bps_override = std::env::var("MM_FEE_RATE_BPS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok());
+        let signing_workers = std::env::var("MM_SIGNING_WORKERS")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .unwrap_or(0)
+            .min(MAX_BATCH_ORDERS);
+        let verifying_contract = H160::from_str(if neg_risk {
+            NEG_RISK_EXCHANGE_POLYGON
+        } else {
+            EXCHANGE_ADDRESS_POLYGON
+        })
+        .expect("valid exchange address");
+        let order_signer = OrderSigner::new(
+            chain_id,
+            verifying_contract,
+            funder,
+            wallet.address(),
+            signature_type,
+        );
+        let clob_host = clob_host.trim_end_matches('/').to_string();
+        // Reuse one connection per host (HTTP/2 when the server offers it via ALPN), no Nagle delay
+        // on small order bodies, idle connections kept longer than the keep-alive ping interval.
+        let client = reqwest::Client::builder()
+            .timeout(retry::timeouts().client)
+            .tcp_nodelay(true)
+            .tcp_keepalive(Duration::from_secs(30))
+            .pool_idle_timeout(Duration::from_secs(90))
+            .pool_max_idle_per_host(4)
+            .http2_keep_alive_interval(Duration::from_secs(20))
+            .http2_keep_alive_while_idle(true)
+            .build()?;
+        let keepalive_secs = std::env::var("MM_CLOB_KEEPALIVE_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(15);
+        if keepalive_secs > 0 {
+            if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                handle.spawn(keep_warm(
+                    client.clone(),
+                    clob_host.clone(),
+                    Duration::from_secs(keepalive_secs),
+                ));
+            }
+        }
+        Ok(Self {
+            clob_host,
+            signer_addr: format!("{:?}", wallet.address()),
+            wallet,
+            api_key,
+            hmac_key: PolyHmacKey::new(&api_secret)?,
+            order_signer,
+            api_passphrase,
+            funder,
+            signature_type,
+            fee_rate_bps_override,
+            signing_workers,
+            fee_rates: std::sync::Mutex::new(std::collections::HashMap::new()),
+            journal,
+            client,
+            reads: public_client(retry::timeouts().client)?,
+            clock: Arc::new(SystemClock),
+        })
+    }
+
+    /// Sleep retry backoffs on `clock` (the engine's).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Address that holds the positions and collateral (FUNDER_ADDRESS or the signer).
+    pub fn funder(&self) -> H160 {
+        self.funder
+    }
+
+    /// Cancel every open order of this account, across all markets (DELETE /cancel-all).
+    pub async fn cancel_all(&self) -> Result<CancelOrdersResult> {
+        let path = "/cancel-all";
+        let url = format!("{}{}", self.clob_host, path);
+        let res = retry_send(self, EndpointClass::Cancel, "DELETE /cancel-all", || {
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let sig = self.hmac_key.sign(timestamp, "DELETE", path, None)?;
+            Ok(self
+                .client
+                .request(reqwest::Method::DELETE, &url)
+                .header("POLY_API_KEY", &self.api_key)
+                .header("POLY_ADDRESS", &self.signer_addr)
+                .header("POLY_SIGNATURE", &sig)
+                .header("POLY_TIMESTAMP", timestamp.to_string())
+                .header("POLY_PASSPHRASE", &self.api_passphrase))
+        })
+        .await?;
+        let status = res.status();
+        let text = res.text().await.unwrap_or_default();
+        if !status.is_success() {
+            anyhow::bail!("DELETE /cancel-all HTTP {}: {}", status, text);
+        }
+        Ok(parse_cancel_response(&text))
+    }
+
+    /// Fee rate for a token: MM_FEE_RATE_BPS override, else cached, else GET /fee-rate. A failed
+    /// fetch fails the order rather than signing it with a guessed fee.
+    async fn fee_rate_bps_for(&self, token_id: &str) -> Result<u64> {
+        if let Some(bps) = self.fee_rate_bps_override {
+            return Ok(bps);
+        }
+        if let Some(bps) = self
+            .fee_rates
+            .lock()
+            .ok()
+            .and_then(|m| m.get(token_id).copied())
+        {
+            return Ok(bps);
+        }
+        let bps = fetch_fee_rate_bps(&self.reads, &self.clob_host, token_id)
+            .await
+            .context("fee rate")?;
+        if let Ok(mut m) = self.fee_rates.lock() {
+            m.insert(token_id.to_string(), bps);
+        }
+        Ok(bps)
+    }
+
+    /// API requires: sell orders — maker amount max 2 decimals, taker amount max 4 decimals.
+    fn maker_taker_amounts_6dec(
+        &self,
+        side: OrderSide,
+        price: &Decimal,
+        size: &Decimal,
+    ) -> Result<(ethers::types::U256, ethers::types::U256)> {
+        let six = dec!(1000000);
+        let (maker_human, taker_human) = match side {
+            OrderSide::Buy => (size * price, *size),
+            // SELL: maker = size (shares) max 2 decimals; use floor so we never round up and exceed balance.
+            OrderSide::Sell => {
+                let factor = dec!(100);
+                let size_2d = ((size * factor).trunc()) / factor;
+                (size_2d, (size_2d * price).round_dp(4))
+            }
+        };
+        let maker = (maker_human * six).trunc();
+        let taker = (taker_human * six).trunc();
+        let maker_u =
+            ethers::types::U256::from_dec_str(&maker.to_string()).context("maker amount")?;
+        let taker_u =
+            ethers::types::U256::from_dec_str(&taker.to_string()).context("taker amount")?;
+        Ok((maker_u, taker_u))
+    }
+
+    async fn post_order(
+        &self,
+        order_type: &str,
+        order_json: &serde_json::Value,
+        side: OrderSide,
+        price: Option<Decimal>,
+    ) -> Result<PlaceOrderResult> {
+        let path = "/order";
+        let body = serde_json::json!({
+            "order": order_json,
+            "owner": self.api_key,
+            "orderType": order_type,
+            "deferExec": false
+        });
+        let body_str = body.to_string();
+        let url = format!("{}{}", self.clob_host, path);
+        let res = retry_send(self, EndpointClass::Order, "POST /order", || {
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let sig = self
+                .hmac_key
+                .sign(timestamp, "POST", path, Some(&body_str))?;
+            Ok(self
+                .client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .header("POLY_API_KEY", &self.api_key)
+                .header("POLY_ADDRESS", &self.signer_addr)
+                .header("POLY_SIGNATURE", &sig)
+                .header("POLY_TIMESTAMP", timestamp.to_string())
+                .header("POLY_PASSPHRASE", &self.api_passphrase)
+                .body(body_str.clone()))
+        })
+        .await?;
+        let status = res.status();
+        let text = res.text().await.unwrap_or_default();
+        let json: serde_json::Value =
+            serde_json::from_str(&text).unwrap_or(serde_json::Value::Null);
+        if !status.is_success() {
+            return Ok(PlaceOrderResult {
+                order_id: json
+                    .get("orderID")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+                success: false,
+                error_msg: Some(format!(
+                    "HTTP {}: {}",
+                    status,
+                    text.chars().take(200).collect::<String>()
+                )),
+                error: Some(ClobError::parse(&text)),
+                filled_size: None,
+                http_status: Some(status.as_u16()),
+                avg_price: None,
+                failed_parts: Vec::new(),
+            });
+        }
+        Ok(Self::parse_order_response(&json, side, price, status.as_u16()))
+    }
+
+    /// POST /orders: submit several signed orders in one request. `orders` holds
+    /// (signed order JSON, order type, side, price) per order; results are in input order.
+    async fn post_orders_batch(
+        &self,
+        orders: &[(serde_json::Value, OrderType, OrderSide, Decimal)],
+    ) -> Result<Vec<PlaceOrderResult>> {
+        let path = "/orders";
+        let body = serde_json::Value::Array(
+            orders
+                .iter()
+                .map(|(order_json, order_type, _, _)| {
+                    serde_json::json!({
+                        "order": order_json,
+                        "owner": self.api_key,
+                        "orderType": order_type_str(*order_type),
+                        "deferExec": false
+                    })
+                })
+                .collect(),
+        );
+        let body_str = body.to_string();
+        let url = format!("{}{}", self.clob_host, path);
+        let res = retry_send(self, EndpointClass::Order, "POST /orders", || {
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let sig = self
+                .hmac_key
+                .sign(timestamp, "POST", path, Some(&body_str))?;
+            Ok(self
+                .client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .header("POLY_API_KEY", &self.api_key)
+                .header("POLY_ADDRESS", &self.signer_addr)
+                .header("POLY_SIGNATURE", &sig)
+                .header("POLY_TIMESTAMP", timestamp.to_string())
+                .header("POLY_PASSPHRASE", &self.api_passphrase)
+                .body(body_str.clone()))
+        })
+        .await?;
+        let status = res.status();
+        let text = res.text().await.unwrap_or_default();
+        if !status.is_success() {
+            let msg = format!(
+                "HTTP {}: {}",
+                status,
+                text.chars().take(200).collect::<String>()
+            );
+            return Ok(orders
+                .iter()
+                .map(|_| PlaceOrderResult {
+                    http_status: Some(status.as_u16()),
+                    error: Some(ClobError::parse(&text)),
+                    ..PlaceOrderResult::failed(msg.clone())
+                })
+                .collect());
+        }
+        let json: serde_json::Value =
+            serde_json::from_str(&text).unwrap_or(serde_json::Value::Null);
+        let items = json.as_array().cloned().unwrap_or_default();
+        Ok(orders
+            .iter()
+            .enumerate()
+            .map(|(i, (_, _, side, price))| match items.get(i) {
+                Some(item) => {
+                    Self::parse_order_response(item, *side, Some(*price), status.as_u16())
+                }
+                None => PlaceOrderResult::failed("missing result in batch response".to_string()),
+            })
+            .collect())
+    }
+
+    /// Parse one order response object (from POST /order or one element of POST /orders).
+    fn parse_order_response(
+        json: &serde_json::Value,
+        side: OrderSide,
+        price: Option<Decimal>,
+        http_status: u16,
+    ) -> PlaceOrderResult {
+        let success = json
+            .get("success")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let order_id = json
+            .get("orderID")
+            .and_then(|v| v.as_str())
+            .map(String::from);
+        let error_msg = json
+            .get("errorMsg")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(String::from);
+        // Parse filled size from API. Amounts are in 6 decimals (string or number).
+        // For SELL: makingAmount = filled shares (what we offered), takingAmount = USDC received. Prefer makingAmount for shares.
+        // For BUY: takingAmount = filled shares.
+        let maker_6dec_opt = json.get("makingAmount").and_then(|v| {
+            v.as_str()
+                .and_then(|s| Decimal::from_str(s).ok())
+                .or_else(|| v.as_i64().map(Decimal::from))
+                .or_else(|| v.as_u64().map(Decimal::from))
+        });
+        let taker_6dec_opt = json.get("takingAmount").and_then(|v| {
+            v.as_str()
+                .and_then(|s| Decimal::from_str(s).ok())
+                .or_else(|| v.as_i64().map(Decimal::from))
+                .or_else(|| v.as_u64().map(Decimal::from))
+        });
+        let filled_size = match side {
+            OrderSide::Sell => maker_6dec_opt
+                .map(|m| m / dec!(1000000))
+                .or_else(|| {
+                    taker_6dec_opt.and_then(|t| {
+                        price.filter(|p| !p.is_zero()).map(|p| (t / dec!(1000000)) / p)
+                    })
+                }),
+            OrderSide::Buy => taker_6dec_opt.map(|t| t / dec!(1000000)),
+        };
+        // Shares over USDC: BUY pays makingAmount for takingAmount, SELL the other way round.
+        let (usdc, shares) = match side {
+            OrderSide::Buy => (maker_6dec_opt, taker_6dec_opt),
+            OrderSide::Sell => (taker_6dec_opt, maker_6dec_opt),
+        };
+        let avg_price = usdc
+            .zip(shares)
+            .filter(|(u, s)| !u.is_zero() && !s.is_zero())
+            .map(|(u, s)| u / s);
+        PlaceOrderResult {
+            order_id,
+            success,
+            error: error_msg.as_deref().map(ClobError::parse),
+            error_msg,
+            filled_size,
+            http_status: Some(http_status),
+            avg_price,
+            failed_parts: Vec::new(),
+        }
+    }
+
+    /// Build the EIP-712 signed order JSON for POST /order and POST /orders, with its order hash
+    /// (the client order ID: the CLOB reports the same hash as orderID).
+    async fn build_signed_order(
+        &self,
+        params: &LimitOrderParams,
+        order_type: OrderType,
+    ) -> Result<(serde_json::Value, String)> {
+        let fields = self.order_fields(params, order_type).await?;
+        let (signature, order_hash) = self.order_signer.sign_with_hash(&self.wallet, &fields)?;
+        Ok((self.order_json(params, &fields, signature), order_hash))
+    }
+
+    /// [LiveClob::build_signed_order] for a batch: the fields are built in order, then the
+    /// signatures are spread over MM_SIGNING_WORKERS threads.
+    async fn build_signed_orders(
+        &self,
+        orders: &[(LimitOrderParams, OrderType)],
+    ) -> Result<Vec<(serde_json::Value, String)>> {
+        let mut fields = Vec::with_capacity(orders.len());
+        for (params, order_type) in orders {
+            fields.push(self.order_fields(params, *order_type).await?);
+        }
+        let signed = self
+            .order_signer
+            .sign_batch(&self.wallet, &fields, self.signing_workers)?;
+        Ok(orders
+            .iter()
+            .zip(&fields)
+            .zip(signed)
+            .map(|(((params, _), fields), (signature, order_hash))| {
+                (self.order_json(params, fields, signature), order_hash)
+            })
+            .collect())
+    }
+
+    /// EIP-712 Order fields for `params` (amounts, expiration, fee rate, salt).
+    async fn order_fields(
+        &self,
+        params: &LimitOrderParams,
+        order_type: OrderType,
+    ) -> Result<OrderFields> {
+        let (maker_amount, taker_amount) =
+            self.maker_taker_amounts_6dec(params.side, &params.price, &params.size)?;
+        let token_id = parse_token_id(&params.token_id)?;
+        // For non-GTD orders use expiration 0 in both signature and API (API parses as big.Int).
+        let expiration = match order_type {
+            OrderType::Gtd => params.expiration_unix.unwrap_or(0),
+            _ => 0u64,
+        };
+        let fee_rate_bps = match params.fee_rate_bps {
+            Some(bps) => bps,
+            None => self.fee_rate_bps_for(&params.token_id).await?,
+        };
+        let side = match params.side {
+            OrderSide::Buy => 0u8,
+            OrderSide::Sell => 1u8,
+        };
+        // Unique per process (and across restarts with MM_SALT_FILE): two identical orders would
+        // otherwise get the same hash and the second would be rejected as a duplicate.
+        let salt = crate::salt::next_salt();
+        Ok(OrderFields {
+            salt,
+            taker: H160::zero(),
+            token_id,
+            maker_amount,
+            taker_amount,
+            expiration,
+            nonce: 0,
+            fee_rate_bps,
+            side,
+        })
+    }
+
+    /// The `order` object of POST /order for signed `fields`.
+    fn order_json(
+        &self,
+        params: &LimitOrderParams,
+        fields: &OrderFields,
+        signature: String,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "maker": format!("0x{:x}", self.funder),
+            "signer": &self.signer_addr,
+            "taker": "0x0000000000000000000000000000000000000000",
+            "tokenId": params.token_id.as_str(),
+            "makerAmount": fields.maker_amount.to_string(),
+            "takerAmount": fields.taker_amount.to_string(),
+            "side": if params.side == OrderSide::Buy { "BUY" } else { "SELL" },
+            "expiration": fields.expiration.to_string(),
+            "nonce": fields.nonce.to_string(),
+            "feeRateBps": fields.fee_rate_bps.to_string(),
+            "signature": signature,
+            "salt": fields.salt,
+            "signatureType": self.signature_type
+        })
+    }
+
+    /// GET /data/order/{order_id}: the exchange's record of the order, None if it does not know
+    /// it.
+    async fn get_order(&self, order_id: &str) -> Result<Option<serde_json::Value>> {
+        let path = format!("/data/order/{}", order_id);
+        let url = format!("{}{}", self.clob_host, path);
+        let res = retry_send(self, EndpointClass::Balance, "GET /data/order", || {
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let sig = self.hmac_key.sign(timestamp, "GET", &path, None)?;
+            Ok(self
+                .client
+                .get(&url)
+                .header("POLY_API_KEY", &self.api_key)
+                .header("POLY_ADDRESS", &self.signer_addr)
+                .header("POLY_SIGNATURE", &sig)
+                .header("POLY_TIMESTAMP", timestamp.to_string())
+                .header("POLY_PASSPHRASE", &self.api_passphrase))
+        })
+        .await?;
+        let status = res.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let text = res.text().await.unwrap_or_default();
+        if !status.is_success() {
+            anyhow::bail!(
+                "order lookup HTTP {}: {}",
+                status,
+                text.chars().take(200).collect::<String>()
+            );
+        }
+        let json: serde_json::Value =
+            serde_json::from_str(&text).unwrap_or(serde_json::Value::Null);
+        Ok(json.get("id").is_some().then_some(json))
+    }
+
+    /// Placement result from the exchange's record of an order: the matched size is the filled
+    /// size; canceled without a fill counts as not placed.
+    fn result_from_order(order_id: &str, order: &serde_json::Value) -> PlaceOrderResult {
+        let matched = order
+            .get("size_matched")
+            .and_then(|v| {
+                v.as_str()
+                    .and_then(|s| Decimal::from_str(s.trim()).ok())
+                    .or_else(|| {
+                        v.as_f64()
+                            .and_then(|f| Decimal::from_str(&f.to_string()).ok())
+                    })
+            })
+            .unwrap_or(Decimal::ZERO);
+        let status = order
+            .get("status")
+            .and_then(|s| s.as_str())
+            .unwrap_or("")
+            .to_uppercase();
+        if status.contains("CANCEL") && matched <= Decimal::ZERO {
+            return PlaceOrderResult::failed(format!("order {} canceled without a fill", status));
+        }
+        PlaceOrderResult {
+            order_id: Some(order_id.to_string()),
+            success: true,
+            error_msg: None,
+            error: None,
+            filled_size: (matched > Decimal::ZERO).then_some(matched),
+            http_status: None,
+            avg_price: None,
+            failed_parts: Vec::new(),
+        }
+    }
+
+    /// Look up journaled orders left without an outcome (a previous run, or a POST whose lookup
+    /// failed) and record what the exchange has.
+    async fn recover_journal(&self) {
+        for e in self
+            .journal
+            .pending_older_than(JOURNAL_RECOVER_AGE_MS, self.clock.now_ms())
+        {
+            match self.get_order(&e.order_id).await {
+                Ok(Some(order)) => {
+                    let r = Self::result_from_order(&e.order_id, &order);
+                    warn!(
+                        "[LiveClob] journaled {} {} @ {} (order {}) is on the exchange: filled {:?}",
+                        e.side, e.size, e.price, e.order_id, r.filled_size
+                    );
+                    self.journal.resolved(&e.order_id, JournalOutcome::Accepted);
+                }
+                Ok(None) => {
+                    info!(
+                        "[LiveClob] journaled {} {} @ {} (order {}) never reached the exchange",
+                        e.side, e.size, e.price, e.order_id
+                    );
+                    self.journal
+                        .resolved(&e.order_id, JournalOutcome::NotPlaced);
+                }
+                Err(err) => warn!(
+                    "[LiveClob] journaled order {} lookup failed, still pending: {:#}",
+                    e.order_id, err
+                ),
+            }
+        }
+    }
+
+    /// Outcome of batch order `order_id` after an unclear POST: None while it is not on the
+    /// exchange and this is not the `last` lookup. A failed lookup leaves this order unresolved
+    /// (still pending in the journal) without losing the other orders of the batch.
+    async fn look_up_batch_order(&self, order_id: &str, last: bool) -> Option<PlaceOrderResult> {
+        match self.get_order(order_id).await {
+            Ok(Some(order)) => Some(Self::result_from_order(order_id, &order)),
+            Ok(None) if !last => None,
+            Ok(None) => {
+                self.journal.resolved(order_id, JournalOutcome::NotPlaced);
+                Some(PlaceOrderResult::failed(format!(
+                    "order {} not placed (batch POST failed)",
+                    order_id
+                )))
+            }
+            Err(e) => {
+                warn!(
+                    "[LiveClob] batch order {} lookup failed, outcome unknown: {:#}",
+                    order_id, e
+                );
+                Some(PlaceOrderResult::unresolved(
+                    order_id,
+                    format!(
+                        "order {} outcome unknown (lookup failed: {:#})",
+                        order_id, e
+                    ),
+                ))
+            }
+        }
+    }
+
+    /// Balance-allowance of the conditional token `token_id`.
+    async fn get_balance_allowance_inner(&self, token_id: &str) -> Result<String> {
+        self.balance_allowance(&format!(
+            "asset_type=CONDITIONAL&token_id={}",
+            urlencoding::encode(token_id)
+        ))
+        .await
+    }
+
+    /// GET /balance-allowance?{asset_query} with HMAC auth. L2 HMAC is over path only (no query), per py-clob-client.
+    async fn balance_allowance(&self, asset_query: &str) -> Result<String> {
+        let path_for_sig = "/balance-allowance";
+        let path_with_query = format!(
+            "{}?{}&signature_type={}",
+            path_for_sig, asset_query, self.signature_type
+        );
+        let url = format!("{}{}", self.clob_host, path_with_query);
+        let res = retry_send(
+            self,
+            EndpointClass::Balance,
+            "GET /balance-allowance",
+            || {
+                let timestamp = std::time::SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let sig = self.hmac_key.sign(timestamp, "GET", path_for_sig, None)?;
+                Ok(self
+                    .client
+                    .get(&url)
+                    .header("POLY_API_KEY", &self.api_key)
+                    .header("POLY_ADDRESS", &self.signer_addr)
+                    .header("POLY_SIGNATURE", &sig)
+                    .header("POLY_TIMESTAMP", timestamp.to_string())
+                    .header("POLY_PASSPHRASE", &self.api_passphrase))
+            },
+        )
+        .await?;
+        let status = res.status();
+        let text = res.text().await.unwrap_or_default();
+        if !status.is_success() {
+            anyhow::bail!(
+                "balance-allowance HTTP {}: {}",
+                status,
+                text.chars().take(200).collect::<String>()
+            );
+        }
+        Ok(text)
+    }
+
+    /// Parse balance from balance-allowance JSON and normalize to shares (or USDC for collateral).
+    /// Conditional and collateral balances are returned in base units (1e6).
+    fn parse_balance_from_response(text: &str) -> Option<Decimal> {
+        let json: serde_json::Value = serde_json::from_str(text).ok()?;
+        let balance = json.get("balance")?;
+        let s = balance
+            .as_str()
+            .map(String::from)
+            .or_else(|| balance.as_f64().map(|f| f.to_string()))
+            .or_else(|| balance.as_i64().map(|i| i.to_string()))
+            .or_else(|| balance.as_u64().map(|u| u.to_string()))?;
+        let raw = Decimal::from_str(s.trim())
+            .ok()
+            .filter(|d| *d >= Decimal::ZERO)?;
+        let shares = if CONDITIONAL_BASE_DECIMALS == 6 {
+            raw / CONDITIONAL_BASE_FACTOR
+        } else {
+            let divisor = Decimal::from(10u64.pow(CONDITIONAL_BASE_DECIMALS));
+            raw / divisor
+        };
+        Some(shares)
+    }
+}
+
+#[async_trait::async_trait]
+impl ClobClient for LiveClob {
+    async fn place_limit_order(
+        &self,
+        params: LimitOrderParams,
+        order_type: OrderType,
+    ) -> Result<PlaceOrderResult> {
+        let (order_json, order_id) = self.build_signed_order(&params, order_type).await?;
+        self.journal.submitted(JournalEntry::new(
+            &order_id,
+            &params,
+            order_type_str(order_type),
+            self.clock.now_ms(),
+        ));
+        let post = self
+            .post_order(
+                order_type_str(order_type),
+                &order_json,
+                params.side,
+                Some(params.price),
+            )
+            .await;
+        let result = match post {
+            Ok(r) if !is_ambiguous(&r) => r,
+            post => {
+                warn!(
+                    "[LiveClob] order {} POST without a clear answer ({}); looking it up before resending",
+                    order_id,
+                    match &post {
+                        Ok(r) => r.error_msg.clone().unwrap_or_default(),
+                        Err(e) => format!("{:#}", e),
+                    }
+                );
+                self.clock
+                    .sleep(Duration::from_millis(AMBIGUOUS_LOOKUP_DELAY_MS))
+                    .await;
+                match self.get_order(&order_id).await? {
+                    Some(order) => {
+                        info!("[LiveClob] order {} did reach the exchange", order_id);
+                        Self::result_from_order(&order_id, &order)
+                    }
+                    None => {
+                        // Same signed order: if the first POST still lands, the exchange rejects
+                        // this one as a duplicate instead of filling twice.
+                        info!(
+                            "[LiveClob] order {} not on the exchange, resending",
+                            order_id
+                        );
+                        let resent = self
+                            .post_order(
+                                order_type_str(order_type),
+                                &order_json,
+                                params.side,
+                                Some(params.price),
+                            )
+                            .await?;
+                        if resent.is_error(&ClobError::Duplicate) {
+                            // The first POST landed after the lookup: that order is the outcome.
+                            info!(
+                                "[LiveClob] order {} landed late (resend duplicated); looking it up again",
+                                order_id
+                            );
+                            match self.get_order(&order_id).await? {
+                                Some(order) => Self::result_from_order(&order_id, &order),
+                                None => PlaceOrderResult::accepted(&order_id),
+                            }
+                        } else {
+                            resent
+                        }
+                    }
+                }
+            }
+        };
+        if !is_ambiguous(&result) {
+            self.journal.resolved(&order_id, journal_outcome(&result));
+        }
+        if result.success {
+            info!("[LiveClob] order placed order_id={:?}", result.order_id);
+        } else if let Some(ref msg) = result.error_msg {
+            info!("[LiveClob] order failed: {}", msg);
+        }
+        Ok(result)
+    }
+
+    async fn place_orders_batch(
+        &self,
+        orders: Vec<(LimitOrderParams, OrderType)>,
+    ) -> Result<Vec<PlaceOrderResult>> {
+        let mut results = Vec::with_capacity(orders.len());
+        for chunk in orders.chunks(MAX_BATCH_ORDERS) {
+            let mut signed = Vec::with_capacity(chunk.len());
+            let mut ids = Vec::with_capacity(chunk.len());
+            let built = self.build_signed_orders(chunk).await?;
+            for ((params, order_type), (order_json, order_id)) in chunk.iter().zip(built) {
+                self.journal.submitted(JournalEntry::new(
+                    &order_id,
+                    params,
+                    order_type_str(*order_type),
+                    self.clock.now_ms(),
+                ));
+                ids.push(order_id);
+                signed.push((order_json, *order_type, params.side, params.price));
+            }
+            let chunk_results = match self.post_orders_batch(&signed).await {
+                Ok(rs) if !rs.iter().any(is_ambiguous) => rs,
+                _ => {
+                    // Not resent: a batch is one decision (e.g. both legs of a pair), so an order
+                    // the exchange never got is reported as failed. The POST may still land after
+                    // a first lookup misses it: only a second miss counts as not placed.
+                    warn!("[LiveClob] batch POST without a clear answer; looking up its orders");
+                    let mut rs: Vec<Option<PlaceOrderResult>> = ids.iter().map(|_| None).collect();
+                    for last in [false, true] {
+                        self.clock
+                            .sleep(Duration::from_millis(AMBIGUOUS_LOOKUP_DELAY_MS))
+                            .await;
+                        for (id, r) in ids.iter().zip(rs.iter_mut()) {
+                            if r.is_none() {
+                                *r = self.look_up_batch_order(id, last).await;
+                            }
+                        }
+                    }
+                    rs.into_iter().flatten().collect()
+                }
+            };
+            for (id, r) in ids.iter().zip(&chunk_results) {
+                if !is_ambiguous(r) && !r.is_error(&ClobError::Unresolved) {
+                    self.journal.resolved(id, journal_outcome(r));
+                }
+            }
+            for r in chunk_results.iter() {
+                if r.success {
+                    info!("[LiveClob] batch order placed order_id={:?}", r.order_id);
+                } else if let Some(ref msg) = r.error_msg {
+                    info!("[LiveClob] batch order failed: {}", msg);
+                }
+            }
+            results.extend(chunk_results);
+        }
+        Ok(results)
+    }
+
+    async fn cancel_orders_for_token(&self, token_id: &str) -> Result<CancelOrdersResult> {
+        let path = "/cancel-market-orders";
+        let body = serde_json::json!({ "asset_id": token_id });
+        let body_str = body.to_string();
+        let url = format!("{}{}", self.clob_host, path);
+        let res = retry_send(
+            self,
+            EndpointClass::Cancel,
+            "DELETE /cancel-market-orders",
+            || {
+                let timestamp = std::time::SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let sig = self
+                    .hmac_key
+                    .sign(timestamp, "DELETE", path, Some(&body_str))?;
+                Ok(self
+                    .client
+                    .request(reqwest::Method::DELETE, &url)
+                    .header("Content-Type", "application/json")
+                    .header("POLY_API_KEY", &self.api_key)
+                    .header("POLY_ADDRESS", &self.signer_addr)
+                    .header("POLY_SIGNATURE", &sig)
+                    .header("POLY_TIMESTAMP", timestamp.to_string())
+                    .header("POLY_PASSPHRASE", &self.api_passphrase)
+                    .body(body_str.clone()))
+            },
+        )
+        .await?;
+        let CancelOrdersResult {
+            canceled,
+            not_canceled,
+        } = parse_cancel_response(&res.text().await.unwrap_or_default());
+        if !canceled.is_empty() {
+            info!(
+                "[LiveClob] canceled {} open order(s) for token to free balance",
+                canceled.len()
+            );
+        }
+        if !not_canceled.is_empty() {
+            warn!(
+                "[LiveClob] {} order(s) could not be canceled (balance may stay locked): {:?}",
+                not_canceled.len(),
+                not_canceled
+            );
+        }
+        Ok(CancelOrdersResult {
+            canceled,
+            not_canceled,
+        })
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<CancelOrdersResult> {
+        let path = "/order";
+        let body_str = serde_json::json!({ "orderID": order_id }).to_string();
+        let url = format!("{}{}", self.clob_host, path);
+        let res = retry_send(self, EndpointClass::Cancel, "DELETE /order", || {
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let sig = self
+                .hmac_key
+                .sign(timestamp, "DELETE", path, Some(&body_str))?;
+            Ok(self
+                .client
+                .request(reqwest::Method::DELETE, &url)
+                .header("Content-Type", "application/json")
+                .header("POLY_API_KEY", &self.api_key)
+                .header("POLY_ADDRESS", &self.signer_addr)
+                .header("POLY_SIGNATURE", &sig)
+                .header("POLY_TIMESTAMP", timestamp.to_string())
+                .header("POLY_PASSPHRASE", &self.api_passphrase)
+                .body(body_str.clone()))
+        })
+        .await?;
+        let status = res.status();
+        let text = res.text().await.unwrap_or_default();
+        if !status.is_success() {
+            anyhow::bail!("DELETE /order HTTP {}: {}", status, text);
+        }
+        Ok(parse_cancel_response(&text))
+    }
+
+    async fn get_balance_allowance(&self, token_id: &str) -> Result<String> {
+        self.get_balance_allowance_inner(token_id).await
+    }
+
+    async fn get_available_balance(&self, token_id: &str) -> Result<Option<Decimal>> {
+        let text = self.get_balance_allowance_inner(token_id).await.ok();
+        Ok(text.as_deref().and_then(Self::parse_balance_from_response))
+    }
+
+    async fn warm_up(&self) {
+        warm(&self.client, &self.clob_host).await;
+        self.recover_journal().await;
+    }
+
+    async fn get_collateral_balance(&self) -> Result<Option<Decimal>> {
+        let text = self.balance_allowance("asset_type=COLLATERAL").await?;
+        Ok(Self::parse_balance_from_response(&text))
+    }
+
+    fn funders(&self) -> Vec<String> {
+        vec![format!("{:?}", self.funder)]
+    }
+
+    async fn get_trades(&self, after_unix: u64) -> Result<Vec<Fill>> {
+        let path = "/data/trades";
+        let funder = format!("{:?}", self.funder);
+        let addresses = vec![funder.to_lowercase()];
+        let mut fills = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let url = format!(
+                "{}{}?maker_address={}&after={}{}",
+                self.clob_host,
+                path,
+                funder,
+                after_unix,
+                cursor
+                    .as_deref()
+                    .map(|c| format!("&next_cursor={}", urlencoding::encode(c)))
+                    .unwrap_or_default()
+            );
+            let res = retry_send(self, EndpointClass::Balance, "GET /data/trades", || {
+                let timestamp = std::time::SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let sig = self.hmac_key.sign(timestamp, "GET", path, None)?;
+                Ok(self
+                    .client
+                    .get(&url)
+                    .header("POLY_API_KEY", &self.api_key)
+                    .header("POLY_ADDRESS", &self.signer_addr)
+                    .header("POLY_SIGNATURE", &sig)
+                    .header("POLY_TIMESTAMP", timestamp.to_string())
+                    .header("POLY_PASSPHRASE", &self.api_passphrase))
+            })
+            .await?;
+            let status = res.status();
+            let text = res.text().await.unwrap_or_default();
+            if !status.is_success() {
+                anyhow::bail!(
+                    "trades HTTP {}: {}",
+                    status,
+                    text.chars().take(200).collect::<String>()
+                );
+            }
+            let json: serde_json::Value = serde_json::from_str(&text).context("trades JSON")?;
+            let (page, next) = parse_trades_page(&json, &addresses);
+            fills.extend(page);
+            match next {
+                Some(c) if cursor.as_ref() != Some(&c) => cursor = Some(c),
+                _ => break,
+            }
+        }
+        Ok(fills)
+    }
+}
+
+/// `{"canceled": [ids], "not_canceled": {id: reason}}` from the cancel endpoints.
+fn parse_cancel_response(text: &str) -> CancelOrdersResult {
+    let json: serde_json::Value = serde_json::from_str(text).unwrap_or(serde_json::Value::Null);
+    CancelOrdersResult {
+        canceled: json
+            .get("canceled")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        not_canceled: json
+            .get("not_canceled")
+            .and_then(|v| v.as_object())
+            .map(|obj| {
+                obj.iter()
+                    .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// Keep the pooled connection to the CLOB host open with a HEAD request every `every`, so the
+/// first order after a quiet stretch does not pay a new TCP + TLS handshake.
+async fn keep_warm(client: reqwest::Client, host: String, every: Duration) {
+    let mut ticker = tokio::time::interval(every);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // The first tick fires at once; the runner already warms up at startup.
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        warm(&client, &host).await;
+    }
+}
+
+/// One HEAD to the CLOB host to open (or keep) the pooled connection.
+async fn warm(client: &reqwest::Client, host: &str) {
+    let started = std::time::Instant::now();
+    match client.head(host).send().await {
+        Ok(res) => debug!(
+            "[LiveClob] warm {} {:?} in {} ms",
+            host,
+            res.version(),
+            started.elapsed().as_millis()
+        ),
+        Err(e) => debug!("[LiveClob] warm HEAD {} failed: {}", host, e),
+    }
+}
+
+/// Pick the signature type from the funder: the signer itself (EOA, 0), its Polymarket proxy (1)
+/// or its Polymarket Safe (2). An explicit SIGNATURE_TYPE that contradicts a recognized funder is
+/// an error (such orders are always rejected); an unrecognized funder needs an explicit type.
+/// Without FUNDER_ADDRESS the signer trades as an EOA.
+fn resolve_signature_type(
+    eoa: H160,
+    funder: Option<H160>,
+    explicit: Option<u8>,
+) -> Result<(H160, u8)> {
+    let safe = polymarket_safe_address(eoa);
+    let proxy = polymarket_proxy_address(eoa);
+    let Some(funder) = funder else {
+        let signature_type = explicit.unwrap_or(SIGNATURE_TYPE_EOA);
+        if signature_type != SIGNATURE_TYPE_EOA {
+            anyhow::bail!(
+                "SIGNATURE_TYPE={} needs FUNDER_ADDRESS (your Polymarket Safe is {:?}, proxy {:?})",
+                signature_type,
+                safe,
+                proxy
+            );
+        }
+        info!(
+            "[LiveClob] FUNDER_ADDRESS unset: trading from the signer EOA; if your funds are in the Polymarket Safe set FUNDER_ADDRESS={:?}",
+            safe
+        );
+        return Ok((eoa, SIGNATURE_TYPE_EOA));
+    };
+    let detected = if funder == eoa {
+        Some(SIGNATURE_TYPE_EOA)
+    } else if funder == safe {
+        Some(SIGNATURE_TYPE_GNOSIS_SAFE)
+    } else if funder == proxy {
+        Some(SIGNATURE_TYPE_POLY_PROXY)
+    } else {
+        None
+    };
+    match (detected, explicit) {
+        (Some(d), Some(e)) if d != e => anyhow::bail!(
+            "SIGNATURE_TYPE={} does not match FUNDER_ADDRESS {:?}, which is the signer's {} (type {}); fix or remove SIGNATURE_TYPE",
+            e,
+            funder,
+            match d {
+                SIGNATURE_TYPE_EOA => "own address",
+                SIGNATURE_TYPE_POLY_PROXY => "Polymarket proxy",
+                _ => "Polymarket Safe",
+            },
+            d
+        ),
+        (Some(d), _) => Ok((funder, d)),
+        (None, Some(e)) => {
+            warn!(
+                "[LiveClob] FUNDER_ADDRESS {:?} is not the signer, its Safe ({:?}) or its proxy ({:?}); using SIGNATURE_TYPE={} as set",
+                funder, safe, proxy, e
+            );
+            Ok((funder, e))
+        }
+        (None, None) => anyhow::bail!(
+            "FUNDER_ADDRESS {:?} is not the signer {:?}, its Polymarket Safe {:?} or its proxy {:?}; set SIGNATURE_TYPE explicitly if this is intended",
+            funder,
+            eoa,
+            safe,
+            proxy
+        ),
+    }
+}
+
+/// MM_DRY_RUN_SIM_FILLS: dry runs match orders against the live book ([SimClob]).
+pub fn sim_fills_enabled() -> bool {
+    std::env::var("MM_DRY_RUN_SIM_FILLS")
+        .map(|v| v.to_lowercase() == "true" || v == "1")
+        .unwrap_or(false)
+}
+
+/// Build a CLOB client from config: DryRun if dry_run (SimClob with MM_DRY_RUN_SIM_FILLS), else Live. Extra accounts
+/// (PRIVATE_KEY_2 ... PRIVATE_KEY_9 with matching API credentials) put a [MultiClob] in front. With the `chaos`
+/// feature and MM_CHAOS=true the dry-run client is wrapped in a [crate::chaos::ChaosClob].
+pub fn create_clob_client(dry_run: bool) -> Result<Box<dyn ClobClient>> {
+    create_clob_client_with_clock(dry_run, Arc::new(SystemClock))
+}
+
+/// [create_clob_client] whose live clients sleep retry backoffs on `clock`.
+pub fn create_clob_client_with_clock(
+    dry_run: bool,
+    clock: Arc<dyn Clock>,
+) -> Result<Box<dyn ClobClient>> {
+    if dry_run {
+        let client: Box<dyn ClobClient> = if sim_fills_enabled() {
+            Box::new(SimClob::from_env()?)
+        } else {
+            Box::new(DryRunClob)
+        };
+        #[cfg(feature = "chaos")]
+        if let Some(cfg) = crate::chaos::global() {
+            return Ok(Box::new(crate::chaos::ChaosClob::new(client, cfg)));
+        }
+        return Ok(client);
+    }
+    #[cfg(feature = "chaos")]
+    if crate::chaos::global().is_some() {
+        anyhow::bail!("MM_CHAOS is for dry runs only (MM_DRY_RUN=true)");
+    }
+    let mut accounts: Vec<(String, Box<dyn ClobClient>)> = vec![(
+        "1".to_string(),
+        Box::new(LiveClob::from_env()?.with_clock(clock.clone())),
+    )];
+    for i in 2..=MAX_ACCOUNTS {
+        let suffix = format!("_{}", i);
+        if std::env::var(format!("PRIVATE_KEY{}", suffix)).is_err() {
+            continue;
+        }
+        accounts.push((
+            i.to_string(),
+            Box::new(LiveClob::from_env_account(&suffix)?.with_clock(clock.clone())),
+        ));
+    }
+    if accounts.len() == 1 {
+        return Ok(accounts.pop().expect("one account").1);
+    }
+    let mode = AccountMode::from_env();
+    info!(
+        "[LiveClob] {} accounts configured, mode={:?}",
+        accounts.len(),
+        mode
+    );
+    Ok(Box::new(MultiClob::new(accounts, mode)))
+}
@@ -0,0 +1,947 @@
+//! CLOB WebSocket client for real-time order book (Polymarket).
+//!
+//! Connects to `wss://ws-subscriptions-clob.polymarket.com/ws/market`, subscribes to
+//! asset IDs (token_id_up, token_id_down), and keeps a shared [TopOfBook] updated from
+//! `book`, `best_bid_ask`, `price_change` and `tick_size_change` events. `last_trade_price` events
+//! feed a [TradeTape]; `book` and `price_change` levels maintain a full [BookDepth] per token (the
+//! book model shared with REST, book.rs), which gives the sizes at the best bid / ask. Send PING
+//! every 10s per docs.
+//!
+//! Health: any inbound frame counts as a sign of life. A connection that goes quiet for
+//! [WS_STALE_SECS] is reported [WsConnectionState::Stale] (the runner reads REST meanwhile); after
+//! [MAX_MISSED_PONGS] pings without a frame back, or when the socket closes, the book is cleared
+//! and the client reconnects with backoff and resubscribes.
+//!
+//! [ClobWsBook::replace_assets] moves an open connection to another pair of tokens (interval
+//! switch) with unsubscribe / subscribe frames instead of a new connection.
+//!
+//! Per-asset feed stats ([ClobWsBook::asset_stats]): messages, `book` snapshots vs deltas
+//! (`price_change`, `best_bid_ask`, `tick_size_change`) vs trades, messages/s over the last
+//! [RATE_WINDOW_SECS] and the age of the last message, so one token going silent while the other
+//! updates shows up even though the connection as a whole looks live.
+//!
+//! Quiet-token refresh ([ClobWsBook::start_rest_refresh], MM_WS_QUIET_REFRESH_SECS): a token with
+//! no WS message for that long has its book replaced from GET /book, at most once per
+//! MM_WS_QUIET_REFRESH_EVERY_SECS, while the connection is up. The refreshed side is tagged
+//! [BookSource::Rest] (WS updates tag it [BookSource::Ws]); a WS message that lands during the
+//! fetch wins over it. Refreshes are counted in [WsAssetStats::rest_refreshes].
+//!
+//! Each side's update time ([TopOfBookSide::updated_ms]) is read on the engine's [Clock], so the
+//! runner ages the book on the same time it decides on; feed stats and the quiet-token timers run
+//! on the wall clock.
+
+use crate::book::{
+    fetch_order_book, parse_decimal, parse_positive, raw_to_side, BookDepth, BookSnapshot, Levels,
+    SIDE_FETCH_TIMEOUT,
+};
+use crate::clock::{wall_ms, Clock, SystemClock};
+use crate::trade_tape::{TradeStats, TradeTape};
+use crate::types::{BookSource, TopOfBook, TopOfBookSide};
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use rust_decimal::Decimal;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, oneshot, RwLock};
+use tokio::time::interval;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use tracing::{debug, info, warn};
+
+/// Default CLOB WebSocket market endpoint (no auth).
+pub const DEFAULT_WS_MARKET_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/market";
+
+/// Heartbeat interval per Polymarket docs.
+const PING_INTERVAL_SECS: u64 = 10;
+/// No inbound frame for this long: the book is reported stale.
+pub const WS_STALE_SECS: u64 = 15;
+/// Pings sent without any inbound frame before the connection is dropped and reopened.
+const MAX_MISSED_PONGS: u32 = 2;
+/// How long replace_assets waits for the receive loop to switch tokens.
+const REPLACE_ASSETS_TIMEOUT: Duration = Duration::from_secs(5);
+/// Window of the per-asset messages/s figure.
+pub const RATE_WINDOW_SECS: u64 = 10;
+/// Reconnect backoff bounds.
+const RECONNECT_MIN: Duration = Duration::from_secs(1);
+const RECONNECT_MAX: Duration = Duration::from_secs(30);
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Connection health as seen by the runner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WsConnectionState {
+    /// Connected and frames are arriving.
+    Live,
+    /// Connected but nothing received for [WS_STALE_SECS]; the book may be out of date.
+    Stale,
+    /// Connection lost; reconnecting (the book is empty until the new snapshot).
+    Reconnecting,
+}
+
+/// Up / Down token IDs the connection is subscribed to.
+type Assets = (String, String);
+
+/// Switch to new assets; acked once the book is cleared and the frames are sent.
+struct ReplaceAssets {
+    assets: Assets,
+    done: oneshot::Sender<()>,
+}
+
+struct Health {
+    connected: bool,
+    last_frame: Instant,
+}
+
+/// Kind of message counted in [WsAssetStats].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MessageKind {
+    Snapshot,
+    Delta,
+    Trade,
+}
+
+/// Messages seen for one asset, with per-second counts over the last [RATE_WINDOW_SECS].
+#[derive(Debug, Default)]
+struct AssetFeed {
+    snapshots: u64,
+    deltas: u64,
+    trades: u64,
+    last_message_ms: Option<u64>,
+    /// (unix second, messages in it), oldest first.
+    recent: std::collections::VecDeque<(u64, u32)>,
+    /// Books taken from REST while the token was quiet; not WS messages.
+    rest_refreshes: u64,
+    last_refresh_ms: Option<u64>,
+}
+
+impl AssetFeed {
+    fn record(&mut self, kind: MessageKind, now_ms: u64) {
+        match kind {
+            MessageKind::Snapshot => self.snapshots += 1,
+            MessageKind::Delta => self.deltas += 1,
+            MessageKind::Trade => self.trades += 1,
+        }
+        self.last_message_ms = Some(now_ms);
+        let sec = now_ms / 1000;
+        match self.recent.back_mut() {
+            Some((s, n)) if *s == sec => *n += 1,
+            _ => self.recent.push_back((sec, 1)),
+        }
+        while self
+            .recent
+            .front()
+            .is_some_and(|(s, _)| *s + RATE_WINDOW_SECS <= sec)
+        {
+            self.recent.pop_front();
+        }
+    }
+
+    fn stats(&self, now_ms: u64) -> WsAssetStats {
+        let sec = now_ms / 1000;
+        let in_window: u32 = self
+            .recent
+            .iter()
+            .filter(|(s, _)| s + RATE_WINDOW_SECS > sec)
+            .map(|(_, n)| n)
+            .sum();
+        WsAssetStats {
+            messages: self.snapshots + self.deltas + self.trades,
+            snapshots: self.snapshots,
+            deltas: self.deltas,
+            trades: self.trades,
+            messages_per_sec: in_window as f64 / RATE_WINDOW_SECS as f64,
+            last_message_age_ms: self.last_message_ms.map(|t| now_ms.saturating_sub(t)),
+            rest_refreshes: self.rest_refreshes,
+        }
+    }
+}
+
+/// Feed stats of one subscribed asset (token).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct WsAssetStats {
+    /// Messages for this asset since it was subscribed.
+    pub messages: u64,
+    /// Full `book` snapshots.
+    pub snapshots: u64,
+    /// Incremental updates (`price_change`, `best_bid_ask`, `tick_size_change`).
+    pub deltas: u64,
+    /// `last_trade_price` events.
+    pub trades: u64,
+    /// Messages per second over the last [RATE_WINDOW_SECS].
+    pub messages_per_sec: f64,
+    /// Time since the last message (None before the first one).
+    pub last_message_age_ms: Option<u64>,
+    /// Books refreshed from REST while the token was quiet (MM_WS_QUIET_REFRESH_SECS).
+    pub rest_refreshes: u64,
+}
+
+impl WsAssetStats {
+    /// As JSON for the status file.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "messages": self.messages,
+            "snapshots": self.snapshots,
+            "deltas": self.deltas,
+            "trades": self.trades,
+            "messages_per_sec": (self.messages_per_sec * 100.0).round() / 100.0,
+            "last_message_age_ms": self.last_message_age_ms,
+            "rest_refreshes": self.rest_refreshes,
+        })
+    }
+}
+
+/// The subscribed assets, since when, and their feed stats.
+#[derive(Debug, Default)]
+struct FeedState {
+    assets: Assets,
+    subscribed_ms: u64,
+    by_asset: HashMap<String, AssetFeed>,
+}
+
+impl FeedState {
+    /// New subscription: stats start over.
+    fn reset(&mut self, assets: &Assets) {
+        self.assets = assets.clone();
+        self.subscribed_ms = wall_ms();
+        self.by_asset.clear();
+    }
+
+    /// When the last WS message for `asset_id` arrived (the subscription time before the first).
+    fn last_heard_ms(&self, asset_id: &str) -> u64 {
+        self.by_asset
+            .get(asset_id)
+            .and_then(|f| f.last_message_ms)
+            .unwrap_or(self.subscribed_ms)
+    }
+}
+
+type Feeds = Mutex<FeedState>;
+
+/// Count a message for `asset_id` when it is one of the subscribed tokens.
+fn record_feed(feeds: &Feeds, asset_id: &str, assets: (&str, &str), kind: MessageKind) {
+    if asset_id != assets.0 && asset_id != assets.1 {
+        return;
+    }
+    feeds
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .by_asset
+        .entry(asset_id.to_string())
+        .or_default()
+        .record(kind, wall_ms());
+}
+
+/// Quiet-token refresh settings (see the module doc).
+#[derive(Debug, Clone, Copy)]
+pub struct RestRefresh {
+    /// No WS message for a token for this long: refresh its book from REST.
+    pub quiet: Duration,
+    /// Time between two refreshes of the same token.
+    pub every: Duration,
+}
+
+/// Event type of a WS message, read without building a JSON tree. Full book snapshots are
+/// [BookSnapshot] (book.rs); the other book messages below borrow their strings from the frame.
+#[derive(Debug, serde::Deserialize)]
+struct WsEventType<'a> {
+    #[serde(borrow, default)]
+    event_type: Cow<'a, str>,
+}
+
+/// WebSocket message: best bid/ask only (custom_feature_enabled).
+#[derive(Debug, serde::Deserialize)]
+pub struct WsBestBidAskMessage<'a> {
+    #[serde(borrow)]
+    pub asset_id: Cow<'a, str>,
+    #[serde(borrow)]
+    pub best_bid: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub best_ask: Option<Cow<'a, str>>,
+}
+
+/// WebSocket message: price change (best_bid/best_ask per asset).
+#[derive(Debug, serde::Deserialize)]
+pub struct WsPriceChangeMessage<'a> {
+    #[serde(borrow)]
+    pub price_changes: Option<Vec<WsPriceChangeItem<'a>>>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct WsPriceChangeItem<'a> {
+    #[serde(borrow)]
+    pub asset_id: Cow<'a, str>,
+    #[serde(borrow)]
+    pub best_bid: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub best_ask: Option<Cow<'a, str>>,
+    /// Level update: price, new size (0 = removed) and side (BUY = bid, SELL = ask).
+    #[serde(borrow)]
+    pub price: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub size: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub side: Option<Cow<'a, str>>,
+}
+
+/// WebSocket message: tick size change (price crossed into / out of the 0.001-tick zone).
+#[derive(Debug, serde::Deserialize)]
+pub struct WsTickSizeChangeMessage {
+    #[serde(rename = "asset_id")]
+    pub asset_id: String,
+    #[serde(rename = "new_tick_size")]
+    pub new_tick_size: String,
+}
+
+/// WebSocket message: a trade printed (trade tape).
+#[derive(Debug, serde::Deserialize)]
+pub struct WsLastTradeMessage {
+    #[serde(rename = "asset_id")]
+    pub asset_id: String,
+    pub price: String,
+    pub size: String,
+    /// Unix ms as a string.
+    pub timestamp: Option<String>,
+}
+
+/// The side of `book` for `asset_id`, created empty on first use (None for another token).
+fn side_mut<'a>(
+    book: &'a mut TopOfBook,
+    asset_id: &str,
+    token_id_up: &str,
+    token_id_down: &str,
+) -> Option<&'a mut TopOfBookSide> {
+    let side = if asset_id == token_id_up {
+        &mut book.token_id_up
+    } else if asset_id == token_id_down {
+        &mut book.token_id_down
+    } else {
+        return None;
+    };
+    Some(side.get_or_insert_with(TopOfBookSide::default))
+}
+
+/// Set the best bid / ask of `side` from a WS message (None keeps the current value), taking
+/// the size at that price from `depth`. Without a level there (no snapshot yet, or the level
+/// update is still in flight) the previous size is kept if the price did not move. The side is
+/// stamped updated at `now_ms`.
+fn set_best(
+    side: &mut TopOfBookSide,
+    depth: Option<&BookDepth>,
+    best_bid: Option<Decimal>,
+    best_ask: Option<Decimal>,
+    now_ms: u64,
+) {
+    if let Some(bid) = best_bid {
+        let kept = side.best_bid_size.filter(|_| side.best_bid == Some(bid));
+        side.best_bid = Some(bid);
+        side.best_bid_size = depth.and_then(|d| d.size_at(true, bid)).or(kept);
+    }
+    if let Some(ask) = best_ask {
+        let kept = side.best_ask_size.filter(|_| side.best_ask == Some(ask));
+        side.best_ask = Some(ask);
+        side.best_ask_size = depth.and_then(|d| d.size_at(false, ask)).or(kept);
+    }
+    side.updated_ms = Some(now_ms);
+    side.source = Some(BookSource::Ws);
+}
+
+/// Client for CLOB WebSocket order book. Holds shared [TopOfBook] updated in a background task.
+pub struct ClobWsBook {
+    /// Current top of book for both tokens; updated by the WS receive loop.
+    state: Arc<RwLock<TopOfBook>>,
+    /// Recent trades for both tokens; updated by the WS receive loop.
+    tape: Arc<RwLock<TradeTape>>,
+    /// Full depth per token ID; updated by the WS receive loop.
+    depth: Arc<RwLock<HashMap<String, BookDepth>>>,
+    health: Arc<Mutex<Health>>,
+    /// Per-asset feed stats; updated by the WS receive loop.
+    feeds: Arc<Feeds>,
+    replace: mpsc::Sender<ReplaceAssets>,
+    join: tokio::task::JoinHandle<()>,
+    /// Quiet-token REST refresh task, once started.
+    refresh: Option<tokio::task::JoinHandle<()>>,
+    /// Time of the book updates.
+    clock: Arc<dyn Clock>,
+}
+
+impl Drop for ClobWsBook {
+    /// Stop the receive / reconnect loop (and the refresh task) with the client.
+    fn drop(&mut self) {
+        self.join.abort();
+        if let Some(refresh) = &self.refresh {
+            refresh.abort();
+        }
+    }
+}
+
+impl ClobWsBook {
+    /// Connect to the CLOB WebSocket, subscribe to the two token IDs, and start the receive + ping loop
+    /// (which reconnects on its own). Uses [DEFAULT_WS_MARKET_URL] if `ws_url` is empty.
+    pub async fn connect(ws_url: &str, token_id_up: &str, token_id_down: &str) -> Result<Self> {
+        Self::connect_with_clock(ws_url, token_id_up, token_id_down, Arc::new(SystemClock)).await
+    }
+
+    /// [ClobWsBook::connect] with book updates timed on `clock` (the engine's).
+    pub async fn connect_with_clock(
+        ws_url: &str,
+        token_id_up: &str,
+        token_id_down: &str,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self> {
+        let url = if ws_url.is_empty() {
+            DEFAULT_WS_MARKET_URL
+        } else {
+            ws_url
+        }
+        .to_string();
+        let mut assets: Assets = (token_id_up.to_string(), token_id_down.to_string());
+        let ws_stream = Self::open(&url, &assets).await?;
+
+        let state: Arc<RwLock<TopOfBook>> = Arc::new(RwLock::new(TopOfBook::default()));
+        let tape: Arc<RwLock<TradeTape>> = Arc::new(RwLock::new(TradeTape::default()));
+        let depth: Arc<RwLock<HashMap<String, BookDepth>>> = Arc::new(RwLock::new(HashMap::new()));
+        let health = Arc::new(Mutex::new(Health {
+            connected: true,
+            last_frame: Instant::now(),
+        }));
+        let feeds: Arc<Feeds> = Arc::default();
+        feeds
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .reset(&assets);
+        let (state_recv, tape_recv, depth_recv, health_recv, feeds_recv) = (
+            Arc::clone(&state),
+            Arc::clone(&tape),
+            Arc::clone(&depth),
+            Arc::clone(&health),
+            Arc::clone(&feeds),
+        );
+        let (replace, mut replace_rx) = mpsc::channel::<ReplaceAssets>(4);
+        let clock_recv = Arc::clone(&clock);
+
+        let join = tokio::spawn(async move {
+            let mut ws_stream = ws_stream;
+            loop {
+                let reason = Self::pump(
+                    ws_stream,
+                    &state_recv,
+                    &tape_recv,
+                    &depth_recv,
+                    &health_recv,
+                    &feeds_recv,
+                    clock_recv.as_ref(),
+                    &mut assets,
+                    &mut replace_rx,
+                )
+                .await;
+                health_recv
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .connected = false;
+                *state_recv.write().await = TopOfBook::default();
+                depth_recv.write().await.clear();
+                warn!("[ClobWsBook] connection lost ({}), reconnecting", reason);
+                let mut backoff = RECONNECT_MIN;
+                ws_stream = loop {
+                    tokio::time::sleep(backoff).await;
+                    // Switches requested while disconnected: the new connection subscribes to them.
+                    while let Ok(req) = replace_rx.try_recv() {
+                        assets = req.assets;
+                        feeds_recv
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .reset(&assets);
+                        let _ = req.done.send(());
+                    }
+                    match Self::open(&url, &assets).await {
+                        Ok(s) => break s,
+                        Err(e) => {
+                            warn!("[ClobWsBook] reconnect failed: {:#}", e);
+                            backoff = (backoff * 2).min(RECONNECT_MAX);
+                        }
+                    }
+                };
+                *health_recv.lock().unwrap_or_else(PoisonError::into_inner) = Health {
+                    connected: true,
+                    last_frame: Instant::now(),
+                };
+                info!("[ClobWsBook] reconnected and resubscribed");
+            }
+        });
+
+        Ok(Self {
+            state,
+            tape,
+            depth,
+            health,
+            feeds,
+            replace,
+            join,
+            refresh: None,
+            clock,
+        })
+    }
+
+    /// Open the socket and subscribe to both tokens (the server may close if we don't right away).
+    async fn open(url: &str, assets: &Assets) -> Result<WsStream> {
+        let (mut ws_stream, _) = connect_async(url).await.context("CLOB WebSocket connect")?;
+        let sub = serde_json::json!({
+            "assets_ids": [assets.0.as_str(), assets.1.as_str()],
+            "type": "market",
+            "custom_feature_enabled": true
+        });
+        ws_stream
+            .send(Message::Text(sub.to_string()))
+            .await
+            .context("send subscribe")?;
+        Ok(ws_stream)
+    }
+
+    /// Receive and ping until the connection dies; returns why.
+    #[allow(clippy::too_many_arguments)]
+    async fn pump(
+        ws_stream: WsStream,
+        state: &RwLock<TopOfBook>,
+        tape: &RwLock<TradeTape>,
+        depth: &RwLock<HashMap<String, BookDepth>>,
+        health: &Mutex<Health>,
+        feeds: &Feeds,
+        clock: &dyn Clock,
+        assets: &mut Assets,
+        replace: &mut mpsc::Receiver<ReplaceAssets>,
+    ) -> String {
+        let (mut write, mut read) = ws_stream.split();
+        let mut ping_interval = interval(Duration::from_secs(PING_INTERVAL_SECS));
+        ping_interval.tick().await; // first tick fires immediately, skip
+        let mut missed_pongs = 0u32;
+
+        loop {
+            tokio::select! {
+                _ = ping_interval.tick() => {
+                    if missed_pongs >= MAX_MISSED_PONGS {
+                        return format!("{} pings without a reply", missed_pongs);
+                    }
+                    if let Err(e) = write.send(Message::Ping(vec![])).await {
+                        return format!("ping failed: {}", e);
+                    }
+                    missed_pongs += 1;
+                }
+                Some(req) = replace.recv() => {
+                    let old = std::mem::replace(assets, req.assets);
+                    *state.write().await = TopOfBook::default();
+                    depth.write().await.clear();
+                    feeds.lock().unwrap_or_else(PoisonError::into_inner).reset(assets);
+                    let unsub = serde_json::json!({
+                        "assets_ids": [old.0, old.1],
+                        "operation": "unsubscribe"
+                    });
+                    let sub = serde_json::json!({
+                        "assets_ids": [assets.0.as_str(), assets.1.as_str()],
+                        "operation": "subscribe",
+                        "custom_feature_enabled": true
+                    });
+                    let sent = match write.send(Message::Text(unsub.to_string())).await {
+                        Ok(()) => write.send(Message::Text(sub.to_string())).await,
+                        Err(e) => Err(e),
+                    };
+                    // On failure the reconnect subscribes to the new assets.
+                    let _ = req.done.send(());
+                    if let Err(e) = sent {
+                        return format!("resubscribe failed: {}", e);
+                    }
+                }
+                msg = read.next() => {
+                    let msg = match msg {
+                        Some(Ok(msg)) => msg,
+                        Some(Err(e)) => return e.to_string(),
+                        None => return "closed by server".to_string(),
+                    };
+                    // Chaos WS gap: the frame never arrived.
+                    #[cfg(feature = "chaos")]
+                    if matches!(msg, Message::Text(_)) && crate::chaos::drop_ws_frame() {
+                        continue;
+                    }
+                    missed_pongs = 0;
+                    health.lock().unwrap_or_else(PoisonError::into_inner).last_frame = Instant::now();
+                    match msg {
+                        Message::Text(text) => {
+                            if let Err(e) = Self::apply_message(state, tape, depth, feeds, &text, &assets.0, &assets.1, clock.now_ms()).await {
+                                debug!("ClobWsBook parse/apply: {} | payload: {}", e, text.chars().take(200).collect::<String>());
+                            }
+                        }
+                        Message::Close(frame) => return format!("close frame {:?}", frame),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Subscribe to `token_id_up` / `token_id_down` instead of the current pair on the open
+    /// connection. When this returns the book, depth and token filter have been switched together,
+    /// so no update for the old tokens is served.
+    pub async fn replace_assets(&self, token_id_up: &str, token_id_down: &str) -> Result<()> {
+        let (done, ack) = oneshot::channel();
+        self.replace
+            .send(ReplaceAssets {
+                assets: (token_id_up.to_string(), token_id_down.to_string()),
+                done,
+            })
+            .await
+            .context("WS receive loop stopped")?;
+        tokio::time::timeout(REPLACE_ASSETS_TIMEOUT, ack)
+            .await
+            .context("WS resubscribe timed out")?
+            .context("WS receive loop stopped")
+    }
+
+    /// Current connection health.
+    pub fn connection_state(&self) -> WsConnectionState {
+        let health = self.health.lock().unwrap_or_else(PoisonError::into_inner);
+        if !health.connected {
+            WsConnectionState::Reconnecting
+        } else if health.last_frame.elapsed() > Duration::from_secs(WS_STALE_SECS) {
+            WsConnectionState::Stale
+        } else {
+            WsConnectionState::Live
+        }
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.connection_state() == WsConnectionState::Live
+    }
+
+    /// Build WebSocket URL from REST CLOB host (e.g. https://clob.polymarket.com -> wss://ws-subscriptions-clob.polymarket.com/ws/market).
+    /// POLYMARKET_WS_URL overrides it (e.g. a local mock next to a mock REST host).
+    pub fn ws_url_from_rest_host(_rest_host: &str) -> String {
+        if let Some(url) = std::env::var("POLYMARKET_WS_URL")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+        {
+            return url;
+        }
+        DEFAULT_WS_MARKET_URL.to_string()
+    }
+
+    /// Apply one WS message received at `now_ms` (engine clock).
+    #[allow(clippy::too_many_arguments)]
+    async fn apply_message(
+        state: &RwLock<TopOfBook>,
+        tape: &RwLock<TradeTape>,
+        depth: &RwLock<HashMap<String, BookDepth>>,
+        feeds: &Feeds,
+        text: &str,
+        token_id_up: &str,
+        token_id_down: &str,
+        now_ms: u64,
+    ) -> Result<()> {
+        let assets = (token_id_up, token_id_down);
+        // Arrays (the snapshots answering a subscribe) carry no event_type and are not applied.
+        if text.trim_start().starts_with('[') {
+            return Ok(());
+        }
+        let event: WsEventType = serde_json::from_str(text).context("parse JSON")?;
+
+        match event.event_type.as_ref() {
+            "book" => {
+                let msg = BookSnapshot::parse(text).context("parse book")?;
+                if msg.asset_id != token_id_up && msg.asset_id != token_id_down {
+                    return Ok(());
+                }
+                record_feed(feeds, &msg.asset_id, assets, MessageKind::Snapshot);
+                let asset_id = msg.asset_id.clone();
+                let snapshot = msg.into_depth();
+                let mut book = state.write().await;
+                if let Some(side) = side_mut(&mut book, &asset_id, token_id_up, token_id_down) {
+                    *side = TopOfBookSide {
+                        tick_size: side.tick_size,
+                        min_order_size: side.min_order_size,
+                        updated_ms: Some(now_ms),
+                        source: Some(BookSource::Ws),
+                        ..snapshot.top_side()
+                    };
+                }
+                let mut depth = depth.write().await;
+                match depth.get_mut(asset_id.as_ref()) {
+                    Some(d) => *d = snapshot,
+                    None => {
+                        depth.insert(asset_id.into_owned(), snapshot);
+                    }
+                }
+            }
+            "best_bid_ask" => {
+                let msg: WsBestBidAskMessage =
+                    serde_json::from_str(text).context("parse best_bid_ask")?;
+                record_feed(feeds, &msg.asset_id, assets, MessageKind::Delta);
+                let best_bid = msg.best_bid.as_deref().and_then(parse_positive);
+                let best_ask = msg.best_ask.as_deref().and_then(parse_positive);
+                let mut book = state.write().await;
+                let depth = depth.read().await;
+                if let Some(side) = side_mut(&mut book, &msg.asset_id, token_id_up, token_id_down) {
+                    set_best(
+                        side,
+                        depth.get(msg.asset_id.as_ref()),
+                        best_bid,
+                        best_ask,
+                        now_ms,
+                    );
+                }
+            }
+            "price_change" => {
+                let msg: WsPriceChangeMessage =
+                    serde_json::from_str(text).context("parse price_change")?;
+                let Some(ref changes) = msg.price_changes else {
+                    return Ok(());
+                };
+                // One message per asset it touches.
+                for (i, c) in changes.iter().enumerate() {
+                    if !changes[..i].iter().any(|p| p.asset_id == c.asset_id) {
+                        record_feed(feeds, &c.asset_id, assets, MessageKind::Delta);
+                    }
+                }
+                let mut book = state.write().await;
+                let mut depth = depth.write().await;
+                for c in changes.iter() {
+                    let (Some(price), Some(size), Some(side)) = (
+                        c.price.as_deref().and_then(parse_decimal),
+                        c.size.as_deref().and_then(parse_decimal),
+                        c.side.as_deref(),
+                    ) else {
+                        continue;
+                    };
+                    if let Some(d) = depth.get_mut(c.asset_id.as_ref()) {
+                        d.set_level(side.eq_ignore_ascii_case("BUY"), price, size);
+                    }
+                }
+                for c in changes.iter() {
+                    let best_bid = c.best_bid.as_deref().and_then(parse_positive);
+                    let best_ask = c.best_ask.as_deref().and_then(parse_positive);
+                    if let Some(side) = side_mut(&mut book, &c.asset_id, token_id_up, token_id_down)
+                    {
+                        set_best(
+                            side,
+                            depth.get(c.asset_id.as_ref()),
+                            best_bid,
+                            best_ask,
+                            now_ms,
+                        );
+                    }
+                }
+            }
+            "tick_size_change" => {
+                let msg: WsTickSizeChangeMessage =
+                    serde_json::from_str(text).context("parse tick_size_change")?;
+                record_feed(feeds, &msg.asset_id, assets, MessageKind::Delta);
+                let Some(tick) = parse_positive(&msg.new_tick_size) else {
+                    return Ok(());
+                };
+                let mut book = state.write().await;
+                if let Some(side) = side_mut(&mut book, &msg.asset_id, token_id_up, token_id_down) {
+                    side.tick_size = Some(tick);
+                }
+            }
+            "last_trade_price" => {
+                let msg: WsLastTradeMessage =
+                    serde_json::from_str(text).context("parse last_trade_price")?;
+                if msg.asset_id != *token_id_up && msg.asset_id != *token_id_down {
+                    return Ok(());
+                }
+                record_feed(feeds, &msg.asset_id, assets, MessageKind::Trade);
+                let (Some(price), Some(size)) =
+                    (parse_positive(&msg.price), parse_positive(&msg.size))
+                else {
+                    return Ok(());
+                };
+                let timestamp_ms = msg
+                    .timestamp
+                    .as_deref()
+                    .and_then(|t| t.trim().parse::<u64>().ok())
+                    .unwrap_or_else(|| chrono::Utc::now().timestamp_millis().max(0) as u64);
+                tape.write()
+                    .await
+                    .record(&msg.asset_id, timestamp_ms, price, size);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Best `n` levels per side for one token (empty until the first `book` snapshot).
+    pub async fn depth(&self, token_id: &str, n: usize) -> (Levels, Levels) {
+        self.depth
+            .read()
+            .await
+            .get(token_id)
+            .map(|d| d.top_levels(n))
+            .unwrap_or_default()
+    }
+
+    /// Ask size of one token priced in [lo, hi] (None until the first `book` snapshot).
+    pub async fn ask_size_between(
+        &self,
+        token_id: &str,
+        lo: Decimal,
+        hi: Decimal,
+    ) -> Option<Decimal> {
+        self.depth
+            .read()
+            .await
+            .get(token_id)
+            .map(|d| d.size_between(false, lo, hi))
+    }
+
+    /// Trade stats for one token over the last `window_ms`.
+    pub async fn trade_stats(&self, token_id: &str, now_ms: u64, window_ms: u64) -> TradeStats {
+        self.tape.read().await.stats(token_id, now_ms, window_ms)
+    }
+
+    /// Feed stats of a subscribed asset (zeroed until its first message).
+    pub fn asset_stats(&self, token_id: &str) -> WsAssetStats {
+        let now = wall_ms();
+        self.feeds
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .by_asset
+            .get(token_id)
+            .map(|f| f.stats(now))
+            .unwrap_or_default()
+    }
+
+    /// Refresh the book of a token quiet on the WS from GET /book on `clob_host` (see the module
+    /// doc). Replaces a refresh task already running.
+    pub fn start_rest_refresh(
+        &mut self,
+        client: reqwest::Client,
+        clob_host: &str,
+        refresh: RestRefresh,
+    ) {
+        let (state, depth, health, feeds, clock) = (
+            Arc::clone(&self.state),
+            Arc::clone(&self.depth),
+            Arc::clone(&self.health),
+            Arc::clone(&self.feeds),
+            Arc::clone(&self.clock),
+        );
+        let clob_host = clob_host.to_string();
+        let task = tokio::spawn(async move {
+            let mut tick = interval(Duration::from_secs(1));
+            loop {
+                tick.tick().await;
+                if !health
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .connected
+                {
+                    continue;
+                }
+                let now = wall_ms();
+                let quiet: Vec<(String, u64)> = {
+                    let f = feeds.lock().unwrap_or_else(PoisonError::into_inner);
+                    [&f.assets.0, &f.assets.1]
+                        .into_iter()
+                        .filter(|id| !id.is_empty())
+                        .filter_map(|id| {
+                            let heard = f.last_heard_ms(id);
+                            let refreshed =
+                                f.by_asset.get(id.as_str()).and_then(|a| a.last_refresh_ms);
+                            let due = now.saturating_sub(heard) >= refresh.quiet.as_millis() as u64
+                                && refreshed.is_none_or(|t| {
+                                    now.saturating_sub(t) >= refresh.every.as_millis() as u64
+                                });
+                            due.then(|| (id.clone(), heard))
+                        })
+                        .collect()
+                };
+                for (token_id, heard) in quiet {
+                    Self::refresh_from_rest(
+                        &client,
+                        &clob_host,
+                        &state,
+                        &depth,
+                        &feeds,
+                        clock.as_ref(),
+                        &token_id,
+                        heard,
+                    )
+                    .await;
+                }
+            }
+        });
+        if let Some(old) = self.refresh.replace(task) {
+            old.abort();
+        }
+    }
+
+    /// Replace `token_id`'s book with the REST one, unless a WS message arrived since `heard_ms`
+    /// or the token was unsubscribed meanwhile.
+    #[allow(clippy::too_many_arguments)]
+    async fn refresh_from_rest(
+        client: &reqwest::Client,
+        clob_host: &str,
+        state: &RwLock<TopOfBook>,
+        depth: &RwLock<HashMap<String, BookDepth>>,
+        feeds: &Feeds,
+        clock: &dyn Clock,
+        token_id: &str,
+        heard_ms: u64,
+    ) {
+        let raw = match tokio::time::timeout(
+            SIDE_FETCH_TIMEOUT,
+            fetch_order_book(client, clob_host, token_id),
+        )
+        .await
+        {
+            Ok(Ok(raw)) => raw,
+            Ok(Err(e)) => {
+                debug!(
+                    "[ClobWsBook] REST refresh of quiet token {}: {:#}",
+                    token_id, e
+                );
+                return;
+            }
+            Err(_) => {
+                debug!(
+                    "[ClobWsBook] REST refresh of quiet token {} timed out",
+                    token_id
+                );
+                return;
+            }
+        };
+        // Same lock order as the receive loop (book, then depth), which records the message in
+        // the feeds before taking the book: a message not seen here is applied after this.
+        let mut book = state.write().await;
+        let mut depth = depth.write().await;
+        let mut f = feeds.lock().unwrap_or_else(PoisonError::into_inner);
+        if f.last_heard_ms(token_id) != heard_ms {
+            return;
+        }
+        let (up, down) = f.assets.clone();
+        let Some(side) = side_mut(&mut book, token_id, &up, &down) else {
+            return;
+        };
+        let now = wall_ms();
+        let fresh = raw_to_side(&raw, clock.now_ms());
+        *side = TopOfBookSide {
+            tick_size: fresh.tick_size.or(side.tick_size),
+            min_order_size: fresh.min_order_size.or(side.min_order_size),
+            ..fresh
+        };
+        depth.insert(token_id.to_string(), BookDepth::from_raw(&raw));
+        let feed = f.by_asset.entry(token_id.to_string()).or_default();
+        feed.rest_refreshes += 1;
+        feed.last_refresh_ms = Some(now);
+        debug!(
+            "[ClobWsBook] token {} quiet for {} ms, book refreshed from REST",
+            token_id,
+            now.saturating_sub(heard_ms)
+        );
+    }
+
+    /// Return a copy of the current top of book (both tokens).
+    pub async fn get_top_of_book(&self) -> TopOfBook {
+        self.state.read().await.clone()
+    }
+}
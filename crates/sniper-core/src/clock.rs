@@ -0,0 +1,107 @@
+//! Time source of the engine. [SystemClock] is the wall clock. [SimClock] is set by hand for
+//! replays and tests: it starts at a given time and every sleep moves it forward by the sleep's
+//! length at once, so a run crosses 5-minute interval boundaries as fast as the loop ticks and
+//! reads the same times on every replay. A paced [SimClock] also waits out each sleep on the wall
+//! clock, for runs whose other parts (HTTP latency, the WS feed's timers) take real time.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Where the engine reads the time and waits.
+#[async_trait::async_trait]
+pub trait Clock: Send + Sync {
+    /// Milliseconds since the unix epoch.
+    fn now_ms(&self) -> u64;
+
+    /// Seconds since the unix epoch.
+    fn now_unix(&self) -> u64 {
+        self.now_ms() / 1000
+    }
+
+    /// Wait `d` of this clock's time.
+    async fn sleep(&self, d: Duration);
+}
+
+/// The wall clock (tokio timers for sleeps).
+pub struct SystemClock;
+
+#[async_trait::async_trait]
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        wall_ms()
+    }
+
+    async fn sleep(&self, d: Duration) {
+        tokio::time::sleep(d).await;
+    }
+}
+
+/// Wall clock in ms, for what runs on real time whatever the engine's clock (WS frame times, spot
+/// quotes, salts).
+pub fn wall_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// `ms` (since the unix epoch) as a UTC date-time, e.g. to stamp a file name with the engine's
+/// time.
+pub fn utc(ms: u64) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::from_timestamp_millis(ms as i64).unwrap_or_default()
+}
+
+/// A manual clock: time only moves on [SimClock::advance], [SimClock::set] and sleeps.
+pub struct SimClock {
+    now_ms: AtomicU64,
+    /// Sleeps also wait on the wall clock.
+    paced: bool,
+}
+
+impl SimClock {
+    /// Clock starting at `start_ms` (ms since the unix epoch).
+    pub fn new(start_ms: u64) -> Self {
+        Self {
+            now_ms: AtomicU64::new(start_ms),
+            paced: false,
+        }
+    }
+
+    /// Clock starting at `start_ms` whose sleeps take as long on the wall clock as they move it.
+    pub fn paced(start_ms: u64) -> Self {
+        Self {
+            now_ms: AtomicU64::new(start_ms),
+            paced: true,
+        }
+    }
+
+    /// Move forward by `d`.
+    pub fn advance(&self, d: Duration) {
+        self.now_ms
+            .fetch_add(d.as_millis() as u64, Ordering::SeqCst);
+    }
+
+    /// Jump to `ms` (never backwards).
+    pub fn set(&self, ms: u64) {
+        self.now_ms.fetch_max(ms, Ordering::SeqCst);
+    }
+}
+
+#[async_trait::async_trait]
+impl Clock for SimClock {
+    fn now_ms(&self) -> u64 {
+        self.now_ms.load(Ordering::SeqCst)
+    }
+
+    /// Advances the clock and yields, so other tasks (order submissions, the WS reader) run
+    /// between ticks; a paced clock waits `d` first.
+    async fn sleep(&self, d: Duration) {
+        if self.paced {
+            tokio::time::sleep(d).await;
+            self.advance(d);
+        } else {
+            self.advance(d);
+            tokio::task::yield_now().await;
+        }
+    }
+}
@@ -0,0 +1,458 @@
+//! Config from environment (MM_* / INTERVAL_SNIPER_*).
+
+use crate::clock::{Clock, SystemClock};
+use crate::hourly::{hour_close_unix, hourly_event_slug};
+use crate::types::{
+    BuyRangePoint, Config, HourlyAsset, LogLang, MarketTarget, OrderStrategy, ReEntryPolicy,
+    SellOrderTimeInForce, ShadowParams, SideConfig, SidePolicy, SizingMode, TargetWindow,
+    TpSchedulePoint, TriggerPriceSource, UnlockPolicy,
+};
+use anyhow::Result;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// Length of one 5-minute interval market.
+pub const BTC_5MIN_INTERVAL_SEC: u64 = 300;
+const DEFAULT_SECONDS_BEFORE_CLOSE: u32 = 20;
+const DEFAULT_SIZE_SHARES: &str = "5";
+const DEFAULT_MIN_BUY_PRICE: &str = "0.9";
+const DEFAULT_MAX_BUY_PRICE: &str = "0.95";
+
+fn env(key: &str, default: &str) -> String {
+    std::env::var(key).unwrap_or_else(|_| default.to_string())
+}
+
+fn env_decimal(key: &str, default: &str) -> Decimal {
+    Decimal::from_str(env(key, default).as_str())
+        .or_else(|_| Decimal::from_str(default))
+        .unwrap_or_default()
+}
+
+fn env_u32(key: &str, default: u32) -> u32 {
+    env(key, &default.to_string()).parse().unwrap_or(default)
+}
+
+fn env_u64(key: &str, default: u64) -> u64 {
+    env(key, &default.to_string()).parse().unwrap_or(default)
+}
+
+fn env_bool(key: &str, default: bool) -> bool {
+    let v = env(key, if default { "true" } else { "false" });
+    v.to_lowercase() == "true" || v == "1"
+}
+
+/// Normalize price to 0..=1 (Polymarket probabilities). Values > 1 treated as cents (90 -> 0.9).
+fn normalize_price(v: Decimal) -> Decimal {
+    if v > Decimal::ONE {
+        (v / Decimal::from(100))
+            .min(Decimal::ONE)
+            .max(Decimal::ZERO)
+    } else {
+        v.min(Decimal::ONE).max(Decimal::ZERO)
+    }
+}
+
+/// MM_TP_SCHEDULE, e.g. `180:0.97,60:0.95,20:0.93` (seconds to close : TP price). Invalid entries
+/// are skipped; duplicate seconds keep the first.
+fn tp_schedule(spec: &str) -> Vec<TpSchedulePoint> {
+    let mut points: Vec<TpSchedulePoint> = spec
+        .split(',')
+        .filter_map(|part| {
+            let (secs, price) = part.split_once(':')?;
+            Some(TpSchedulePoint {
+                secs_to_close: secs.trim().parse().ok()?,
+                price: normalize_price(Decimal::from_str(price.trim()).ok()?),
+            })
+        })
+        .collect();
+    points.sort_by_key(|p| std::cmp::Reverse(p.secs_to_close));
+    points.dedup_by_key(|p| p.secs_to_close);
+    points
+}
+
+/// MM_BUY_RANGE_SCHEDULE, e.g. `0:0.85-0.95,120:0.88-0.95,240:0.90-0.95` (seconds since open :
+/// min-max). Invalid entries (or min > max) are skipped; duplicate seconds keep the first.
+fn buy_range_schedule(spec: &str) -> Vec<BuyRangePoint> {
+    let mut points: Vec<BuyRangePoint> = spec
+        .split(',')
+        .filter_map(|part| {
+            let (secs, range) = part.split_once(':')?;
+            let (min, max) = range.split_once('-')?;
+            let price = |v: &str| Decimal::from_str(v.trim()).ok().map(normalize_price);
+            Some(BuyRangePoint {
+                secs_after_open: secs.trim().parse().ok()?,
+                min_buy_price: price(min)?,
+                max_buy_price: price(max)?,
+            })
+            .filter(|p| p.min_buy_price <= p.max_buy_price)
+        })
+        .collect();
+    points.sort_by_key(|p| p.secs_after_open);
+    points.dedup_by_key(|p| p.secs_after_open);
+    points
+}
+
+/// MM_SHADOW, e.g. `wide:min_buy=0.80,max_buy=0.95;tight_sl:sl=0.85` (`name:key=value,...`
+/// candidates separated by `;`). Keys: min_buy, max_buy, tp, sl, size, window (seconds before
+/// close), strategy. Invalid values and unknown keys are skipped.
+fn shadow_params(spec: &str) -> Vec<ShadowParams> {
+    spec.split(';')
+        .filter_map(|candidate| {
+            let (name, params) = candidate.split_once(':')?;
+            let name = name.trim();
+            if name.is_empty() {
+                return None;
+            }
+            let mut p = ShadowParams {
+                name: name.to_string(),
+                ..ShadowParams::default()
+            };
+            for kv in params.split(',') {
+                let Some((key, value)) = kv.split_once('=') else {
+                    continue;
+                };
+                let value = value.trim();
+                let price = || Decimal::from_str(value).ok().map(normalize_price);
+                match key.trim().to_lowercase().as_str() {
+                    "min_buy" => p.min_buy_price = price(),
+                    "max_buy" => p.max_buy_price = price(),
+                    "tp" => p.take_profit_price = price(),
+                    "sl" => p.stop_loss_price = price(),
+                    "size" => p.size_shares = Decimal::from_str(value).ok(),
+                    "window" => p.seconds_before_close = value.parse().ok(),
+                    "strategy" => p.strategy = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+            Some(p)
+        })
+        .collect()
+}
+
+/// Per-side overrides ({prefix}_MIN_BUY, _MAX_BUY, _TAKE_PROFIT, _STOP_LOSS); unset keys keep `shared`.
+fn side_config(prefix: &str, shared: &SideConfig) -> SideConfig {
+    let price = |key: &str, default: Decimal| {
+        normalize_price(env_decimal(
+            &format!("{}_{}", prefix, key),
+            &default.to_string(),
+        ))
+    };
+    SideConfig {
+        min_buy_price: price("MIN_BUY", shared.min_buy_price),
+        max_buy_price: price("MAX_BUY", shared.max_buy_price),
+        take_profit_price: price("TAKE_PROFIT", shared.take_profit_price),
+        stop_loss_price: price("STOP_LOSS", shared.stop_loss_price),
+    }
+}
+
+/// Start (unix) of the 5min interval containing `now_unix`. Polymarket slug uses interval start.
+pub fn interval_start_unix(now_unix: u64) -> u64 {
+    (now_unix / BTC_5MIN_INTERVAL_SEC) * BTC_5MIN_INTERVAL_SEC
+}
+
+/// Current 5min interval start (unix) by the wall clock.
+pub fn current_5min_interval_start_unix() -> u64 {
+    interval_start_unix(SystemClock.now_unix())
+}
+
+/// Slug prefix for asset.
+pub fn slug_prefix(asset: crate::types::IntervalMarketAsset) -> &'static str {
+    match asset {
+        crate::types::IntervalMarketAsset::Btc5m => "btc-updown-5m",
+        crate::types::IntervalMarketAsset::Sol5m => "sol-updown-5m",
+    }
+}
+
+/// Slug of the 5min interval starting at `start_unix`.
+pub fn interval_slug_at(asset: crate::types::IntervalMarketAsset, start_unix: u64) -> String {
+    format!("{}-{}", slug_prefix(asset), start_unix)
+}
+
+/// Slug traded at `now_unix` for an interval target: the current interval, or the previous one
+/// (closing).
+pub fn interval_slug(
+    asset: crate::types::IntervalMarketAsset,
+    window: TargetWindow,
+    now_unix: u64,
+) -> String {
+    let start = interval_start_unix(now_unix);
+    match window {
+        TargetWindow::Active => interval_slug_at(asset, start),
+        TargetWindow::Closing => interval_slug_at(asset, start - BTC_5MIN_INTERVAL_SEC),
+    }
+}
+
+/// Slug an interval target switches to at the next boundary after `now_unix`.
+pub fn next_interval_slug(
+    asset: crate::types::IntervalMarketAsset,
+    window: TargetWindow,
+    now_unix: u64,
+) -> String {
+    let start = interval_start_unix(now_unix);
+    match window {
+        TargetWindow::Active => interval_slug_at(asset, start + BTC_5MIN_INTERVAL_SEC),
+        TargetWindow::Closing => interval_slug_at(asset, start),
+    }
+}
+
+/// Load config from environment.
+pub fn load_config() -> Result<Config> {
+    // MM_TARGET_WINDOW=closing trades the interval that just closed instead, until it resolves.
+    let target_window = match env("MM_TARGET_WINDOW", "active").to_lowercase().as_str() {
+        "closing" => TargetWindow::Closing,
+        _ => TargetWindow::Active,
+    };
+    let interval_market = crate::types::IntervalMarketAsset::from_str(
+        env("INTERVAL_SNIPER_MARKET", "btc_5m").as_str(),
+    );
+    let interval_market = interval_market.unwrap(); // FromStr Err is Infallible
+                                                    // For BTC/SOL 5m we always use the current 5-min interval slug (e.g. btc-updown-5m-1772169300 for 5:15–5:20).
+                                                    // Do not pin to a fixed MM_MARKET_SLUG so the bot subscribes to the live interval.
+    let market_slug = interval_slug(interval_market, target_window, SystemClock.now_unix());
+    // MM_TARGET_SLUG / MM_TARGET_CONDITION_ID point the sniper at one fixed market instead.
+    let target_slug = env("MM_TARGET_SLUG", "").trim().to_string();
+    let target_condition_id = env("MM_TARGET_CONDITION_ID", "").trim().to_string();
+    let target_event_slug = env("MM_TARGET_EVENT_SLUG", "").trim().to_string();
+    let target_hourly_above = HourlyAsset::parse(&env("MM_TARGET_HOURLY_ABOVE", ""));
+    let market_target = if !target_slug.is_empty() {
+        MarketTarget::Slug(target_slug)
+    } else if !target_condition_id.is_empty() {
+        MarketTarget::ConditionId(target_condition_id)
+    } else if !target_event_slug.is_empty() {
+        MarketTarget::Event(target_event_slug)
+    } else if let Some(asset) = target_hourly_above {
+        MarketTarget::HourlyAbove(asset)
+    } else {
+        MarketTarget::Interval(interval_market, target_window)
+    };
+    let market_slug = match &market_target {
+        MarketTarget::Interval(..) => market_slug,
+        MarketTarget::IntervalAt(asset, start) => interval_slug_at(*asset, *start),
+        MarketTarget::Slug(s) | MarketTarget::ConditionId(s) | MarketTarget::Event(s) => s.clone(),
+        MarketTarget::HourlyAbove(asset) => {
+            hourly_event_slug(*asset, hour_close_unix(SystemClock.now_unix()))
+        }
+    };
+    // 5-min markets cannot be open longer than 300s; fixed markets may be open for days.
+    let max_seconds_after_open = match market_target {
+        MarketTarget::Interval(..) | MarketTarget::IntervalAt(..) => BTC_5MIN_INTERVAL_SEC as u32,
+        _ => u32::MAX,
+    };
+
+    let order_strategy = match env("MM_ORDER_STRATEGY", "fak_cross_spread")
+        .to_lowercase()
+        .as_str()
+    {
+        "gtc_resting" => OrderStrategy::GtcResting,
+        "fok_same_price" => OrderStrategy::FokSamePrice,
+        "fak_same_price" => OrderStrategy::FakSamePrice,
+        "cross_spread" => OrderStrategy::CrossSpread,
+        "fok_cross_spread" => OrderStrategy::FokCrossSpread,
+        "fak_cross_spread" => OrderStrategy::FakCrossSpread,
+        "market_fok" => OrderStrategy::MarketFok,
+        _ => OrderStrategy::FakCrossSpread,
+    };
+
+    let take_profit_tif = match env("MM_TAKE_PROFIT_TIME_IN_FORCE", "FAK")
+        .to_uppercase()
+        .as_str()
+    {
+        "FOK" => SellOrderTimeInForce::Fok,
+        "FAK" => SellOrderTimeInForce::Fak,
+        _ => SellOrderTimeInForce::Gtc,
+    };
+
+    let re_entry = match env("MM_REENTRY", "after_sl").to_lowercase().as_str() {
+        "always" => ReEntryPolicy::Always,
+        _ => ReEntryPolicy::AfterStopLoss,
+    };
+
+    let auto_unlock = match env("MM_AUTO_UNLOCK", "sl_priority").to_lowercase().as_str() {
+        "tp_priority" => UnlockPolicy::TpPriority,
+        _ => UnlockPolicy::SlPriority,
+    };
+
+    let loop_ms = env_u64("MM_LOOP_MS", 100).clamp(1, 500);
+    let cooldown_ms = env_u64("MM_COOLDOWN_MS", 2000).min(60000);
+    let order_sync_min_ms = env_u64("MM_ORDER_SYNC_MIN_MS", 1000).max(100);
+    // Take profit / stop loss: fixed prices (0..=1). Sell when best_bid >= take_profit_price (TP) or best_bid <= stop_loss_price (SL).
+    let tp_default = env("TAKE_PROFIT", "0.97");
+    let sl_default = env("STOP_LOSS", "0.90");
+    let shared = SideConfig {
+        min_buy_price: normalize_price(env_decimal("MM_MIN_BUY_PRICE", DEFAULT_MIN_BUY_PRICE)),
+        max_buy_price: normalize_price(env_decimal("MM_MAX_BUY_PRICE", DEFAULT_MAX_BUY_PRICE)),
+        take_profit_price: normalize_price(env_decimal("MM_TAKE_PROFIT_PRICE", &tp_default)),
+        stop_loss_price: normalize_price(env_decimal("MM_STOP_LOSS_PRICE", &sl_default)),
+    };
+    let take_profit_margin = env_decimal("MM_TAKE_PROFIT_PRICE_MARGIN", "0.01");
+    let take_profit_margin = take_profit_margin
+        .max(Decimal::ZERO)
+        .min(Decimal::from_str("0.05").unwrap_or(take_profit_margin));
+
+    // MM_OBSERVE_ONLY: the dry-run pipeline whatever MM_DRY_RUN says, with the would-be orders and
+    // closes logged.
+    let observe_only = env_bool("MM_OBSERVE_ONLY", false);
+
+    Ok(Config {
+        interval_market,
+        market_target,
+        market_slug: market_slug.clone(),
+        dual_window: env_bool("MM_DUAL_WINDOW", false),
+        dual_window_lead_secs: env_u64("MM_DUAL_WINDOW_LEAD_SECS", 30),
+        gamma_base_url: env("POLYMARKET_REST_BASE", "https://gamma-api.polymarket.com"),
+        data_api_url: env("POLYMARKET_DATA_API_URL", "https://data-api.polymarket.com"),
+        positions_check_secs: env_u64("MM_POSITIONS_CHECK_SECS", 60),
+        balance_drift_secs: env_u64("MM_BALANCE_DRIFT_SECS", 0),
+        order_sync_min_ms,
+        order_sync_max_ms: env_u64("MM_ORDER_SYNC_MAX_MS", 5000).max(order_sync_min_ms),
+        balance_drift_shares: env_decimal("MM_BALANCE_DRIFT_SHARES", "0.01").abs(),
+        balance_drift_usdc: env_decimal("MM_BALANCE_DRIFT_USDC", "1").abs(),
+        seconds_before_close: env_u32("MM_SECONDS_BEFORE_CLOSE", DEFAULT_SECONDS_BEFORE_CLOSE),
+        size_shares: env_decimal("MM_SIZE_SHARES", DEFAULT_SIZE_SHARES).round_dp(2),
+        autosize_to_balance: env_bool("MM_AUTOSIZE_TO_BALANCE", false),
+        sizing: match env("MM_SIZING", "fixed").trim().to_lowercase().as_str() {
+            "fraction" => SizingMode::Fraction,
+            "kelly" => SizingMode::Kelly,
+            _ => SizingMode::Fixed,
+        },
+        sizing_fraction: env_decimal("MM_SIZING_FRACTION", "0.05")
+            .max(Decimal::ZERO)
+            .min(Decimal::ONE),
+        kelly_multiplier: env_decimal("MM_KELLY_MULTIPLIER", "0.5")
+            .max(Decimal::ZERO)
+            .min(Decimal::ONE),
+        kelly_min_trades: env_u32("MM_KELLY_MIN_TRADES", 20),
+        sizing_max_fraction: env_decimal("MM_SIZING_MAX_FRACTION", "0.25")
+            .max(Decimal::ZERO)
+            .min(Decimal::ONE),
+        sizing_max_shares: env_decimal("MM_SIZING_MAX_SHARES", "0").max(Decimal::ZERO),
+        up: side_config("MM_UP", &shared),
+        down: side_config("MM_DOWN", &shared),
+        allow_buy_up: env_bool("MM_ALLOW_BUY_UP", true),
+        allow_buy_down: env_bool("MM_ALLOW_BUY_DOWN", true),
+        min_btc_price_diff_usd: env_decimal("MM_MIN_BTC_PRICE_DIFF_USD", "0"),
+        side_policy: match env("MM_SIDE_POLICY", "highest_ask")
+            .trim()
+            .to_lowercase()
+            .as_str()
+        {
+            "most_bid_depth" => SidePolicy::MostBidDepth,
+            "imbalance" => SidePolicy::Imbalance,
+            "spot_direction" => SidePolicy::SpotDirection,
+            _ => SidePolicy::HighestAsk,
+        },
+        spot_base_url: env("MM_SPOT_URL", "https://api.binance.com"),
+        spot_poll_ms: env_u64("MM_SPOT_POLL_MS", 1000).max(200),
+        strike_min_distance_bps: env_u64("MM_STRIKE_MIN_DISTANCE_BPS", 10),
+        dry_run: observe_only || env_bool("MM_DRY_RUN", true),
+        observe_only,
+        order_strategy,
+        strategy: env("MM_STRATEGY", "range_sniper").trim().to_string(),
+        stop_entry_trigger: normalize_price(env_decimal("MM_STOP_ENTRY_TRIGGER", "0.8")),
+        enable_auto_sell: env_bool("MM_ENABLE_AUTO_SELL", true),
+        auto_sell_at_max_price: env_bool("MM_AUTO_SELL_AT_MAX_PRICE", false),
+        auto_sell_quantity_percent: env_u32("MM_AUTO_SELL_QUANTITY_PERCENT", 100).clamp(1, 100)
+            as u8,
+        take_profit_time_in_force: take_profit_tif,
+        sell_gtd_margin_secs: env_u64("MM_SELL_GTD_MARGIN_SECS", 0),
+        enable_stop_loss: env_bool("MM_ENABLE_STOP_LOSS", true),
+        stop_loss_quantity_percent: env_u32("MM_STOP_LOSS_QUANTITY_PERCENT", 100).clamp(1, 100)
+            as u8,
+        soft_stop_price: normalize_price(env_decimal("MM_SOFT_STOP_PRICE", "0")),
+        soft_stop_percent: env_u32("MM_SOFT_STOP_PERCENT", 50).clamp(1, 99) as u8,
+        breakeven_trigger: normalize_price(env_decimal("MM_BREAKEVEN_TRIGGER", "0")),
+        trigger_price_source: match env("MM_TRIGGER_PRICE_SOURCE", "bid")
+            .trim()
+            .to_lowercase()
+            .as_str()
+        {
+            "ask" => TriggerPriceSource::Ask,
+            "mid" => TriggerPriceSource::Mid,
+            "last_trade" => TriggerPriceSource::LastTrade,
+            _ => TriggerPriceSource::Bid,
+        },
+        sl_confirm_ms: env_u64("MM_SL_CONFIRM_MS", 0),
+        sl_hedge_exit: env_bool("MM_SL_HEDGE_EXIT", false),
+        sl_hedge_after_ms: env_u64("MM_SL_HEDGE_AFTER_MS", 1000),
+        sl_hedge_slippage: env_decimal("MM_SL_HEDGE_SLIPPAGE", "0.02").max(Decimal::ZERO),
+        sl_hedge_bid: env_bool("MM_SL_HEDGE_BID", false),
+        auto_unlock,
+        loop_ms,
+        slow_tick_ms: env_u64("MM_SLOW_TICK_MS", 0),
+        decision_trace: env_u64("MM_DECISION_TRACE", 0) as usize,
+        log_lang: match env("MM_LOG_LANG", "en").trim().to_lowercase().as_str() {
+            "es" => LogLang::Es,
+            _ => LogLang::En,
+        },
+        cooldown_between_orders_ms: cooldown_ms,
+        max_interval_range: env_decimal("MM_MAX_INTERVAL_RANGE", "0").max(Decimal::ZERO),
+        blackout_file: std::env::var("MM_BLACKOUT_FILE")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty()),
+        size_jitter_percent: env_decimal("MM_SIZE_JITTER_PERCENT", "0")
+            .max(Decimal::ZERO)
+            .min(Decimal::from(50)),
+        entry_jitter_ms: env_u64("MM_ENTRY_JITTER_MS", 0).min(5000),
+        buy_order_max_age_ms: env_u64("MM_BUY_ORDER_MAX_AGE_MS", 0),
+        buy_order_reevaluate: env_bool("MM_BUY_ORDER_REEVALUATE", true),
+        max_chase_ticks: env_u32("MM_MAX_CHASE_TICKS", 0),
+        max_chase_price: env_decimal("MM_MAX_CHASE_PRICE", "0.99"),
+        max_trades_per_interval: env_u32("MM_MAX_TRADES_PER_INTERVAL", 2).max(1),
+        re_entry,
+        no_window_all_intervals: env_bool("MM_NO_WINDOW_ALL_INTERVALS", true),
+        min_seconds_after_market_open: env_u32("MM_MIN_SECONDS_AFTER_MARKET_OPEN", 0)
+            .min(max_seconds_after_open),
+        min_seconds_after_buy_before_auto_sell: env_u32(
+            "MM_MIN_SECONDS_AFTER_BUY_BEFORE_AUTO_SELL",
+            0,
+        )
+        .min(30),
+        take_profit_price_margin: take_profit_margin,
+        tp_schedule: tp_schedule(&env("MM_TP_SCHEDULE", "")),
+        buy_range_schedule: buy_range_schedule(&env("MM_BUY_RANGE_SCHEDULE", "")),
+        take_profit_passive_ms: env_u64("MM_TP_PASSIVE_MS", 0),
+        hold_to_resolution_above: normalize_price(env_decimal("MM_HOLD_TO_RESOLUTION_ABOVE", "0")),
+        hold_to_resolution_secs: env_u32("MM_HOLD_TO_RESOLUTION_SECONDS", 30),
+        carryover_wait_secs: env_u64("MM_CARRYOVER_WAIT_SECS", 0),
+        auto_redeem: env_bool("MM_AUTO_REDEEM", false),
+        session_log_enabled: observe_only || env_bool("MM_SESSION_LOG", false),
+        session_log_dir: env("MM_SESSION_LOG_DIR", "logs").to_string(),
+        session_db: std::env::var("MM_SESSION_DB")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty()),
+        allow_multiple_instances: env_bool("MM_ALLOW_MULTIPLE_INSTANCES", false),
+        instance_lock_dir: std::env::var("MM_INSTANCE_LOCK_DIR")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty()),
+        order_audit: observe_only || env_bool("MM_ORDER_AUDIT", false),
+        dry_run_report: env_bool("MM_DRY_RUN_REPORT", true),
+        fee_rate_bps: std::env::var("MM_FEE_RATE_BPS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok()),
+        dual_side_entry: env_bool("MM_DUAL_SIDE_ENTRY", false),
+        dual_side_max_pair_cost: normalize_price(env_decimal("MM_DUAL_SIDE_MAX_PAIR_COST", "0.98")),
+        min_recent_volume: env_decimal("MM_MIN_RECENT_VOLUME", "0").max(Decimal::ZERO),
+        trade_window_secs: env_u64("MM_TRADE_WINDOW_SECONDS", 60).clamp(1, 600),
+        max_last_trade_deviation: env_decimal("MM_MAX_LAST_TRADE_DEVIATION", "0")
+            .max(Decimal::ZERO),
+        min_zone_depth: env_decimal("MM_MIN_ZONE_DEPTH", "0").max(Decimal::ZERO),
+        zone_ticks: env_u32("MM_ZONE_TICKS", 2).min(50),
+        ws_quiet_refresh_secs: env_u64("MM_WS_QUIET_REFRESH_SECS", 0),
+        ws_quiet_refresh_every_secs: env_u64("MM_WS_QUIET_REFRESH_EVERY_SECS", 5).max(1),
+        max_book_age_ms: env_u64("MM_MAX_BOOK_AGE_MS", 0),
+        book_record_enabled: env_bool("MM_BOOK_RECORD", false),
+        book_record_dir: env("MM_BOOK_RECORD_DIR", "data/books"),
+        book_record_levels: env_u32("MM_BOOK_RECORD_LEVELS", 5).clamp(1, 50) as usize,
+        book_record_interval_ms: env_u64("MM_BOOK_RECORD_INTERVAL_MS", 500).max(50),
+        book_record_mid_outcome: env_bool("MM_BOOK_RECORD_MID_OUTCOME", false),
+        status_file: std::env::var("MM_STATUS_FILE")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty()),
+        status_interval_secs: env_u64("MM_STATUS_SECS", 5).max(1),
+        shadow: shadow_params(&env("MM_SHADOW", "")),
+    })
+}
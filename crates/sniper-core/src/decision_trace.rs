@@ -0,0 +1,106 @@
+//! Strategy decision trace (MM_DECISION_TRACE): one JSON record per entry evaluation (the
+//! strategy's inputs: top of book, position, interval flags and buy ranges; and the actions it
+//! returned), kept in memory for the last N evaluations. The buffer is written to
+//! `decision_trace_<time>_<slug>.jsonl` in MM_SESSION_LOG_DIR when the engine stops with an error,
+//! or on demand ([request_dump]: SIGUSR1 on Unix, or a program embedding the engine), for a
+//! post-mortem of a bad trade. Nothing is written otherwise.
+
+use crate::clock::utc;
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// Dump requests so far; every engine dumps once per new request.
+static DUMP_REQUESTS: AtomicU64 = AtomicU64::new(0);
+
+/// Ask every running engine with a trace to write it out (at its next tick).
+pub fn request_dump() {
+    DUMP_REQUESTS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Number of [request_dump] calls so far.
+pub fn dump_requests() -> u64 {
+    DUMP_REQUESTS.load(Ordering::Relaxed)
+}
+
+/// Ring buffer of the last `capacity` evaluations of one engine.
+pub struct DecisionTrace {
+    entries: Mutex<VecDeque<serde_json::Value>>,
+    capacity: usize,
+    dir: PathBuf,
+    slug: String,
+}
+
+impl DecisionTrace {
+    /// Keep the last `capacity` records; dumps go to `dir`, named after `slug`.
+    pub fn new(capacity: usize, dir: &str, slug: &str) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::with_capacity(capacity.min(4096))),
+            capacity: capacity.max(1),
+            dir: PathBuf::from(dir),
+            slug: slug.to_string(),
+        }
+    }
+
+    /// Add a record, dropping the oldest one when full.
+    pub fn record(&self, entry: serde_json::Value) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Records in the buffer.
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// No record yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Write the buffer, oldest first, one record per line, to a file stamped with `now_ms`; the
+    /// buffer is kept. Returns the file.
+    pub fn dump(&self, now_ms: u64) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+        let name: String = self
+            .slug
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let path = self.dir.join(format!(
+            "decision_trace_{}_{}.jsonl",
+            utc(now_ms).format("%Y-%m-%dT%H-%M-%S%.3f"),
+            name
+        ));
+        write_lines(
+            &path,
+            &self.entries.lock().unwrap_or_else(PoisonError::into_inner),
+        )?;
+        Ok(path)
+    }
+}
+
+fn write_lines(path: &Path, entries: &VecDeque<serde_json::Value>) -> Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    for entry in entries {
+        writeln!(out, "{}", entry)?;
+    }
+    out.flush()?;
+    Ok(())
+}
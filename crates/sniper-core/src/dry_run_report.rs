@@ -0,0 +1,324 @@
+//! Dry-run report (MM_DRY_RUN_REPORT): every simulated entry and close of a dry run, summarized
+//! when the run ends into `dry_run_report_<time>.json` in MM_SESSION_LOG_DIR and a few log lines.
+//! The report holds the trades (entry and exit prices, exit type, PnL), the fill model the prices
+//! assume, the hypothetical PnL before and after estimated fees, and how many seconds before the
+//! close the entries were made.
+
+use crate::clob::estimated_fee_usd;
+use crate::clock::utc;
+use crate::fee_rates::FeeRates;
+use crate::inventory::ClosedLot;
+use crate::session_log::{exit_type_str, ExitType};
+use crate::types::EntrySide;
+use anyhow::Result;
+use rust_decimal::Decimal;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Upper bounds (seconds to close at entry) of the distribution buckets; the last bucket is
+/// everything above.
+const SECS_TO_CLOSE_BUCKETS: [u64; 6] = [10, 20, 30, 60, 120, 300];
+
+/// One simulated buy.
+#[derive(Debug, Clone)]
+struct SimEntry {
+    slug: String,
+    side: EntrySide,
+    price: Decimal,
+    size: Decimal,
+    entry_ms: u64,
+    secs_to_close: u64,
+}
+
+/// One closed lot of a simulated position.
+#[derive(Debug, Clone)]
+struct SimClose {
+    slug: String,
+    exit_type: ExitType,
+    lot: ClosedLot,
+    exit_ms: u64,
+    fees: Decimal,
+}
+
+/// Collects the simulated trades of a dry run; writes the report once, at the end.
+pub struct DryRunReport {
+    path: PathBuf,
+    /// How simulated orders fill (MM_DRY_RUN_SIM_FILLS).
+    sim_fills: bool,
+    entries: Vec<SimEntry>,
+    closes: Vec<SimClose>,
+    written: bool,
+}
+
+fn side_str(side: EntrySide) -> &'static str {
+    match side {
+        EntrySide::Up => "Up",
+        EntrySide::Down => "Down",
+    }
+}
+
+fn bucket_label(i: usize) -> String {
+    match i {
+        0 => format!("<{}s", SECS_TO_CLOSE_BUCKETS[0]),
+        i if i < SECS_TO_CLOSE_BUCKETS.len() => format!(
+            "{}-{}s",
+            SECS_TO_CLOSE_BUCKETS[i - 1],
+            SECS_TO_CLOSE_BUCKETS[i]
+        ),
+        _ => format!(
+            ">={}s",
+            SECS_TO_CLOSE_BUCKETS[SECS_TO_CLOSE_BUCKETS.len() - 1]
+        ),
+    }
+}
+
+fn bucket_of(secs_to_close: u64) -> usize {
+    SECS_TO_CLOSE_BUCKETS
+        .iter()
+        .position(|&hi| secs_to_close < hi)
+        .unwrap_or(SECS_TO_CLOSE_BUCKETS.len())
+}
+
+impl DryRunReport {
+    /// Report for `dir`/`dry_run_report_YYYY-MM-DDTHH-MM-SS.json` stamped with `now_ms`; creates
+    /// `dir` if missing.
+    pub fn new(dir: &str, sim_fills: bool, now_ms: u64) -> Result<Self> {
+        let dir = Path::new(dir);
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!(
+            "dry_run_report_{}.json",
+            utc(now_ms).format("%Y-%m-%dT%H-%M-%S")
+        ));
+        Ok(Self {
+            path,
+            sim_fills,
+            entries: Vec::new(),
+            closes: Vec::new(),
+            written: false,
+        })
+    }
+
+    /// A simulated buy of `size` at `price`, `secs_to_close` before the market closes.
+    pub fn on_entry(
+        &mut self,
+        slug: &str,
+        side: EntrySide,
+        price: Decimal,
+        size: Decimal,
+        secs_to_close: u64,
+        now_ms: u64,
+    ) {
+        self.entries.push(SimEntry {
+            slug: slug.to_string(),
+            side,
+            price,
+            size,
+            entry_ms: now_ms,
+            secs_to_close,
+        });
+    }
+
+    /// Lots closed by a simulated exit (sell, or settlement at the close).
+    pub fn on_close(
+        &mut self,
+        slug: &str,
+        exit_type: ExitType,
+        closed: &[ClosedLot],
+        fee_rates: &FeeRates,
+        now_ms: u64,
+    ) {
+        for lot in closed.iter().filter(|l| l.size > Decimal::ZERO) {
+            let fee_rate_bps = fee_rates.side(lot.side);
+            let fees = estimated_fee_usd(fee_rate_bps, lot.entry_price, lot.size)
+                + estimated_fee_usd(fee_rate_bps, lot.exit_price, lot.size);
+            self.closes.push(SimClose {
+                slug: slug.to_string(),
+                exit_type,
+                lot: lot.clone(),
+                exit_ms: now_ms,
+                fees,
+            });
+        }
+    }
+
+    /// Seconds to close of the entry a closed lot came from (None for lots bought outside
+    /// [DryRunReport::on_entry], e.g. hedge legs).
+    fn secs_to_close_of(&self, close: &SimClose) -> Option<u64> {
+        self.entries
+            .iter()
+            .find(|e| {
+                e.entry_ms == close.lot.entry_ms && e.side == close.lot.side && e.slug == close.slug
+            })
+            .map(|e| e.secs_to_close)
+    }
+
+    fn fill_model(&self) -> serde_json::Value {
+        if self.sim_fills {
+            serde_json::json!({
+                "mode": "book_matched",
+                "description": "orders matched level by level against the live book at submit time (MM_DRY_RUN_SIM_FILLS): FAK partials, FOK kills and slippage past the best level included",
+            })
+        } else {
+            serde_json::json!({
+                "mode": "limit_price",
+                "description": "every order fills in full at its limit price (no book check): entries pay the entry limit, TP/SL sells get the bid they were priced at",
+            })
+        }
+    }
+
+    /// The report as JSON.
+    pub fn to_json(&self) -> serde_json::Value {
+        let gross: Decimal = self
+            .closes
+            .iter()
+            .map(|c| (c.lot.exit_price - c.lot.entry_price) * c.lot.size)
+            .sum();
+        let fees: Decimal = self.closes.iter().map(|c| c.fees).sum();
+        let wins = self
+            .closes
+            .iter()
+            .filter(|c| c.lot.exit_price > c.lot.entry_price)
+            .count();
+        let mut by_exit: Vec<(&'static str, u32, Decimal)> = Vec::new();
+        for c in &self.closes {
+            let label = exit_type_str(c.exit_type);
+            let pnl = (c.lot.exit_price - c.lot.entry_price) * c.lot.size;
+            match by_exit.iter_mut().find(|(l, _, _)| *l == label) {
+                Some(row) => {
+                    row.1 += 1;
+                    row.2 += pnl;
+                }
+                None => by_exit.push((label, 1, pnl)),
+            }
+        }
+        let trades: Vec<_> = self
+            .closes
+            .iter()
+            .map(|c| {
+                serde_json::json!({
+                    "slug": c.slug,
+                    "side": side_str(c.lot.side),
+                    "entry_price": c.lot.entry_price.to_string(),
+                    "exit_price": c.lot.exit_price.to_string(),
+                    "size": c.lot.size.to_string(),
+                    "exit_type": exit_type_str(c.exit_type),
+                    "entry_time_ms": c.lot.entry_ms,
+                    "exit_time_ms": c.exit_ms,
+                    "secs_to_close_at_entry": self.secs_to_close_of(c),
+                    "pnl_usd": ((c.lot.exit_price - c.lot.entry_price) * c.lot.size).round_dp(6).to_string(),
+                    "fees_usd": c.fees.round_dp(6).to_string(),
+                })
+            })
+            .collect();
+
+        let mut buckets = vec![(0u32, Decimal::ZERO); SECS_TO_CLOSE_BUCKETS.len() + 1];
+        for e in &self.entries {
+            buckets[bucket_of(e.secs_to_close)].0 += 1;
+        }
+        for c in &self.closes {
+            if let Some(secs) = self.secs_to_close_of(c) {
+                buckets[bucket_of(secs)].1 += (c.lot.exit_price - c.lot.entry_price) * c.lot.size;
+            }
+        }
+        let distribution: Vec<_> = buckets
+            .iter()
+            .enumerate()
+            .map(|(i, (entries, pnl))| {
+                serde_json::json!({
+                    "bucket": bucket_label(i),
+                    "entries": entries,
+                    "pnl_usd": pnl.round_dp(6).to_string(),
+                })
+            })
+            .collect();
+        let mut secs: Vec<u64> = self.entries.iter().map(|e| e.secs_to_close).collect();
+        secs.sort_unstable();
+
+        serde_json::json!({
+            "event": "dry_run_report",
+            "fill_model": self.fill_model(),
+            "entries": self.entries.len(),
+            "entry_shares": self.entries.iter().map(|e| e.size).sum::<Decimal>().to_string(),
+            "avg_entry_price": (!self.entries.is_empty()).then(|| {
+                let size: Decimal = self.entries.iter().map(|e| e.size).sum();
+                let cost: Decimal = self.entries.iter().map(|e| e.price * e.size).sum();
+                (cost / size).round_dp(4).to_string()
+            }),
+            "closes": self.closes.len(),
+            "win_rate": (!self.closes.is_empty()).then(|| wins as f64 / self.closes.len() as f64),
+            "by_exit_type": by_exit
+                .iter()
+                .map(|(label, count, pnl)| serde_json::json!({
+                    "exit_type": label,
+                    "closes": count,
+                    "pnl_usd": pnl.round_dp(6).to_string(),
+                }))
+                .collect::<Vec<_>>(),
+            "gross_pnl_usd": gross.round_dp(6).to_string(),
+            "est_fees_usd": fees.round_dp(6).to_string(),
+            "net_pnl_usd": (gross - fees).round_dp(6).to_string(),
+            "secs_to_close_at_entry": {
+                "min": secs.first(),
+                "median": secs.get(secs.len() / 2),
+                "max": secs.last(),
+                "distribution": distribution,
+            },
+            "trades": trades,
+        })
+    }
+
+    /// Log the summary and write the file (once; later calls do nothing).
+    pub fn write(&mut self) -> Result<()> {
+        if self.written {
+            return Ok(());
+        }
+        self.written = true;
+        let report = self.to_json();
+        let field = |k: &str| report[k].as_str().unwrap_or("0").to_string();
+        tracing::info!(
+            "[DryRunReport] {} entries, {} closes: gross PnL {} USDC, est. fees {}, net {} ({} fills)",
+            self.entries.len(),
+            self.closes.len(),
+            field("gross_pnl_usd"),
+            field("est_fees_usd"),
+            field("net_pnl_usd"),
+            report["fill_model"]["mode"].as_str().unwrap_or_default()
+        );
+        for row in report["by_exit_type"].as_array().into_iter().flatten() {
+            tracing::info!(
+                "[DryRunReport]   {:<12} {:>4} closes  PnL {}",
+                row["exit_type"].as_str().unwrap_or_default(),
+                row["closes"],
+                row["pnl_usd"].as_str().unwrap_or_default()
+            );
+        }
+        let distribution = report["secs_to_close_at_entry"]["distribution"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|b| b["entries"].as_u64().unwrap_or(0) > 0)
+            .map(|b| {
+                format!(
+                    "{} {}",
+                    b["bucket"].as_str().unwrap_or_default(),
+                    b["entries"]
+                )
+            })
+            .collect::<Vec<_>>();
+        if !distribution.is_empty() {
+            tracing::info!(
+                "[DryRunReport]   secs to close at entry: {}",
+                distribution.join(", ")
+            );
+        }
+        fs::write(&self.path, serde_json::to_vec_pretty(&report)?)?;
+        tracing::info!("[DryRunReport] written to {}", self.path.display());
+        Ok(())
+    }
+}
+
+impl Drop for DryRunReport {
+    fn drop(&mut self) {
+        let _ = self.write();
+    }
+}
@@ -0,0 +1,134 @@
+//! Declined entries: when a side's best ask is inside its buy range but no buy goes out, the gate
+//! that held it back (open position, cooldown, interval already traded, outside the entry window,
+//! strategy, ...) is counted per tick, for the interval and the session. The runner logs each
+//! change of reason, the session log's interval summary carries the interval's counts and the
+//! status file the session's, so "why didn't it buy" has an answer without debug logs.
+
+use crate::types::{Config, TopOfBook};
+use std::collections::BTreeMap;
+
+/// Gate that declined an entry while the price was in range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DeclineReason {
+    /// A position (or resting buy, or hedged pair) is already open.
+    PositionOpen,
+    /// An entry order is still in flight.
+    OrderInFlight,
+    /// Within MM_COOLDOWN_MS of the last fill.
+    Cooldown,
+    /// This interval's entries are used up (first order sent, max trades, or re-entry policy).
+    IntervalTraded,
+    /// Before the entry window (MM_SECONDS_BEFORE_CLOSE).
+    OutsideWindow,
+    /// Too soon after the market opened or the interval switch (MM_MIN_SECONDS_AFTER_MARKET_OPEN).
+    TooSoonAfterOpen,
+    /// The interval's bid range is above MM_MAX_INTERVAL_RANGE.
+    Volatile,
+    /// Inside an economic-event blackout (MM_BLACKOUT_FILE).
+    Blackout,
+    /// The strategy placed nothing (thin ask, side policy, stop entry not armed, ...).
+    Strategy,
+    /// Blocked by the trade tape (MM_MIN_RECENT_VOLUME, MM_MAX_LAST_TRADE_DEVIATION).
+    TradeTape,
+    /// Too little ask depth near the limit price (MM_MIN_ZONE_DEPTH).
+    DepthZone,
+    /// Spot on the wrong side of the hourly market's strike.
+    Strike,
+    /// The sized order is under the market's minimum size.
+    BelowMinSize,
+    /// A token's fee rate could not be fetched (no order is signed with a guessed fee).
+    FeeRateUnknown,
+    /// The book is older than MM_MAX_BOOK_AGE_MS.
+    StaleBook,
+}
+
+impl DeclineReason {
+    /// Key in logs and JSON.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DeclineReason::PositionOpen => "position_open",
+            DeclineReason::OrderInFlight => "order_in_flight",
+            DeclineReason::Cooldown => "cooldown",
+            DeclineReason::IntervalTraded => "interval_traded",
+            DeclineReason::OutsideWindow => "outside_window",
+            DeclineReason::TooSoonAfterOpen => "too_soon_after_open",
+            DeclineReason::Volatile => "volatile",
+            DeclineReason::Blackout => "blackout",
+            DeclineReason::Strategy => "strategy",
+            DeclineReason::TradeTape => "trade_tape",
+            DeclineReason::DepthZone => "depth_zone",
+            DeclineReason::Strike => "strike",
+            DeclineReason::BelowMinSize => "below_min_size",
+            DeclineReason::FeeRateUnknown => "fee_rate_unknown",
+            DeclineReason::StaleBook => "stale_book",
+        }
+    }
+}
+
+/// Declined ticks per reason, for the current interval and the session.
+#[derive(Debug, Default)]
+pub struct DeclineCounters {
+    interval: BTreeMap<DeclineReason, u64>,
+    session: BTreeMap<DeclineReason, u64>,
+    last: Option<DeclineReason>,
+}
+
+impl DeclineCounters {
+    /// Count one declined tick. True when the reason differs from the previous tick's (log it).
+    pub fn record(&mut self, reason: DeclineReason) -> bool {
+        *self.interval.entry(reason).or_default() += 1;
+        *self.session.entry(reason).or_default() += 1;
+        self.last.replace(reason) != Some(reason)
+    }
+
+    /// The price left the range or an order went out: the next decline is logged again.
+    pub fn clear_last(&mut self) {
+        self.last = None;
+    }
+
+    /// This interval's counts, reset for the next interval.
+    pub fn take_interval(&mut self) -> BTreeMap<DeclineReason, u64> {
+        self.last = None;
+        std::mem::take(&mut self.interval)
+    }
+
+    /// Counts since the run started.
+    pub fn session(&self) -> &BTreeMap<DeclineReason, u64> {
+        &self.session
+    }
+}
+
+/// Counts as a JSON object keyed by [DeclineReason::as_str].
+pub fn counts_json(counts: &BTreeMap<DeclineReason, u64>) -> serde_json::Value {
+    counts
+        .iter()
+        .map(|(r, n)| (r.as_str().to_string(), serde_json::json!(n)))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+/// Counts as `reason=n, ...` for logs.
+pub fn counts_line(counts: &BTreeMap<DeclineReason, u64>) -> String {
+    counts
+        .iter()
+        .map(|(r, n)| format!("{}={}", r.as_str(), n))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Some side the config allows buying has its best ask inside its buy range.
+pub fn ask_in_range(config: &Config, top: &TopOfBook) -> bool {
+    let up = config.allow_buy_up
+        && top
+            .token_id_up
+            .as_ref()
+            .and_then(|s| s.best_ask)
+            .is_some_and(|ask| config.up.in_range(ask));
+    let down = config.allow_buy_down
+        && top
+            .token_id_down
+            .as_ref()
+            .and_then(|s| s.best_ask)
+            .is_some_and(|ask| config.down.in_range(ask));
+    up || down
+}
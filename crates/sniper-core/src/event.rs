@@ -0,0 +1,178 @@
+//! Event-level sniping: scan every market in a Gamma event (e.g. "BTC price ranges") and pick the
+//! outcome with the best entry, using the same rule as the interval side choice (highest best ask
+//! in the side's [min_buy_price, max_buy_price] with at least min_order_size shares on the ask).
+//! Hourly "above $X" events (hourly.rs) are scanned the same way, keeping only the sides the spot
+//! price is on.
+
+use crate::book::fetch_top_of_book;
+use crate::hourly::strike_allows;
+use crate::market::{fetch_event_markets, fetch_strike_markets};
+use crate::types::{Config, EntrySide, ResolvedMarket, TopOfBookSide};
+use anyhow::Result;
+use reqwest::Client;
+use rust_decimal::Decimal;
+use tracing::debug;
+
+/// Outcome chosen across all markets of the event.
+#[derive(Debug, Clone)]
+pub struct EventPick {
+    pub market: ResolvedMarket,
+    pub side: EntrySide,
+    pub best_ask: Decimal,
+    pub size_available: Decimal,
+    /// Strike of the picked market (hourly "above $X" events).
+    pub strike: Option<Decimal>,
+}
+
+/// Result of one event scan.
+#[derive(Debug, Clone)]
+pub enum EventScan {
+    /// Every market of the event has closed.
+    Closed,
+    /// Open markets exist but no outcome is in range with enough liquidity.
+    NoEntry {
+        open_markets: usize,
+    },
+    Pick(EventPick),
+}
+
+/// Fetch the event's open markets, read both books of each, and pick the best outcome.
+pub async fn scan_event(
+    http: &Client,
+    clob_host: &str,
+    config: &Config,
+    event_slug: &str,
+    now_unix: u64,
+) -> Result<EventScan> {
+    let markets: Vec<(Option<Decimal>, ResolvedMarket)> =
+        fetch_event_markets(http, &config.gamma_base_url, event_slug)
+            .await?
+            .into_iter()
+            .filter(|m| m.close_time_unix > now_unix)
+            .map(|m| (None, m))
+            .collect();
+    scan_markets(http, clob_host, config, markets, now_unix, |_, _| true).await
+}
+
+/// Scan the strikes of an hourly "above $X" event: only sides the spot is on, by at least
+/// MM_STRIKE_MIN_DISTANCE_BPS (hourly::strike_allows). No entry without a spot price.
+pub async fn scan_hourly_above(
+    http: &Client,
+    clob_host: &str,
+    config: &Config,
+    event_slug: &str,
+    spot: Option<Decimal>,
+    now_unix: u64,
+) -> Result<EventScan> {
+    let markets: Vec<(Option<Decimal>, ResolvedMarket)> =
+        fetch_strike_markets(http, &config.gamma_base_url, event_slug)
+            .await?
+            .into_iter()
+            .filter(|(_, m)| m.close_time_unix > now_unix)
+            .map(|(strike, m)| (Some(strike), m))
+            .collect();
+    scan_markets(
+        http,
+        clob_host,
+        config,
+        markets,
+        now_unix,
+        |strike, side| match (spot, strike) {
+            (Some(spot), Some(strike)) => {
+                strike_allows(side, spot, strike, config.strike_min_distance_bps)
+            }
+            _ => false,
+        },
+    )
+    .await
+}
+
+/// Read both books of each market and pick the best outcome among the sides `allowed` (strike,
+/// side) accepts.
+async fn scan_markets(
+    http: &Client,
+    clob_host: &str,
+    config: &Config,
+    markets: Vec<(Option<Decimal>, ResolvedMarket)>,
+    now_unix: u64,
+    allowed: impl Fn(Option<Decimal>, EntrySide) -> bool,
+) -> Result<EventScan> {
+    if markets.is_empty() {
+        return Ok(EventScan::Closed);
+    }
+    let books = futures_util::future::join_all(markets.iter().map(|(_, m)| {
+        fetch_top_of_book(
+            http,
+            clob_host,
+            &m.token_id_up,
+            &m.token_id_down,
+            now_unix * 1000,
+        )
+    }))
+    .await;
+
+    let mut best: Option<(usize, EntrySide, Decimal, Decimal)> = None;
+    for (i, ((strike, market), book)) in markets.iter().zip(books).enumerate() {
+        let book = match book {
+            Ok(b) => b,
+            Err(e) => {
+                debug!("[Event] book fetch failed for {}: {}", market.slug, e);
+                continue;
+            }
+        };
+        let min_order_size = [book.token_id_up.as_ref(), book.token_id_down.as_ref()]
+            .into_iter()
+            .flatten()
+            .filter_map(|s| s.min_order_size)
+            .max()
+            .unwrap_or(market.min_order_size);
+        let sides = [
+            (
+                EntrySide::Up,
+                config.allow_buy_up,
+                book.token_id_up.as_ref(),
+            ),
+            (
+                EntrySide::Down,
+                config.allow_buy_down,
+                book.token_id_down.as_ref(),
+            ),
+        ];
+        for (side, side_allowed, top) in sides {
+            let Some((ask, size)) = top
+                .filter(|_| side_allowed && allowed(*strike, side))
+                .and_then(|t| entry_candidate(config, side, t, min_order_size))
+            else {
+                continue;
+            };
+            if best.map(|(_, _, a, _)| ask > a).unwrap_or(true) {
+                best = Some((i, side, ask, size));
+            }
+        }
+    }
+
+    Ok(match best {
+        Some((i, side, best_ask, size_available)) => EventScan::Pick(EventPick {
+            market: markets[i].1.clone(),
+            side,
+            best_ask,
+            size_available,
+            strike: markets[i].0,
+        }),
+        None => EventScan::NoEntry {
+            open_markets: markets.len(),
+        },
+    })
+}
+
+/// (best ask, ask size) when the ask is in the buy range with at least min_order_size shares.
+fn entry_candidate(
+    config: &Config,
+    side: EntrySide,
+    top: &TopOfBookSide,
+    min_order_size: Decimal,
+) -> Option<(Decimal, Decimal)> {
+    let ask = top.best_ask?;
+    let size = top.best_ask_size.unwrap_or(Decimal::ZERO);
+    (config.side(side).in_range(ask) && size >= min_order_size).then_some((ask, size))
+}
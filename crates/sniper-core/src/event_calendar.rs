@@ -0,0 +1,180 @@
+//! Economic-event blackouts (FOMC, CPI, ...): windows in which no new position is opened.
+//! Loaded once from MM_BLACKOUT_FILE, either JSON or an ICS calendar (by `.ics` extension).
+//!
+//! JSON: `[{"name": "FOMC", "start": "2026-10-28T18:00:00Z", "end": "2026-10-28T19:00:00Z"}]`
+//! (`start` / `end` as RFC 3339 or unix seconds; `end` defaults to `start`).
+//! ICS: each VEVENT's DTSTART / DTEND and SUMMARY. Times are UTC (`YYYYMMDDTHHMMSSZ`) or local to
+//! an IANA zone given as `TZID=` (e.g. `DTSTART;TZID=America/New_York:20261028T140000`); a
+//! floating time (neither) is a parse error rather than a guess.
+
+use anyhow::{anyhow, bail};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::path::Path;
+
+/// One blackout window [start_unix, end_unix].
+#[derive(Debug, Clone)]
+pub struct Blackout {
+    pub name: String,
+    pub start_unix: u64,
+    pub end_unix: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlackoutJson {
+    name: Option<String>,
+    start: serde_json::Value,
+    end: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Default)]
+pub struct EventCalendar {
+    blackouts: Vec<Blackout>,
+}
+
+impl EventCalendar {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("read blackout file {}", path))?;
+        let is_ics = Path::new(path)
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("ics"));
+        let mut blackouts = if is_ics {
+            parse_ics(&text)
+        } else {
+            parse_json(&text)
+        }
+        .with_context(|| format!("parse blackout file {}", path))?;
+        blackouts.sort_by_key(|b| b.start_unix);
+        Ok(Self { blackouts })
+    }
+
+    pub fn len(&self) -> usize {
+        self.blackouts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blackouts.is_empty()
+    }
+
+    /// First blackout intersecting [from_unix, to_unix], if any.
+    pub fn blackout_for(&self, from_unix: u64, to_unix: u64) -> Option<&Blackout> {
+        self.blackouts
+            .iter()
+            .find(|b| b.start_unix <= to_unix && b.end_unix >= from_unix)
+    }
+}
+
+fn parse_json(text: &str) -> Result<Vec<Blackout>> {
+    let raw: Vec<BlackoutJson> = serde_json::from_str(text)?;
+    raw.into_iter()
+        .map(|b| {
+            let start_unix = json_time(&b.start).context("invalid blackout start")?;
+            let end_unix = match b.end {
+                Some(ref v) => json_time(v).context("invalid blackout end")?,
+                None => start_unix,
+            };
+            Ok(Blackout {
+                name: b.name.unwrap_or_else(|| "blackout".to_string()),
+                start_unix,
+                end_unix: end_unix.max(start_unix),
+            })
+        })
+        .collect()
+}
+
+fn json_time(v: &serde_json::Value) -> Option<u64> {
+    match v {
+        serde_json::Value::Number(n) => n.as_u64(),
+        serde_json::Value::String(s) => s.trim().parse::<u64>().ok().or_else(|| {
+            DateTime::parse_from_rfc3339(s.trim())
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc).timestamp().max(0) as u64)
+        }),
+        _ => None,
+    }
+}
+
+/// Minimal ICS reader: VEVENT blocks with DTSTART / DTEND / SUMMARY. Events without a DTSTART
+/// are skipped; a DTSTART / DTEND that cannot be placed in time fails the file.
+fn parse_ics(text: &str) -> Result<Vec<Blackout>> {
+    let mut out = Vec::new();
+    let mut current: Option<(Option<u64>, Option<u64>, String)> = None;
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+        if line == "BEGIN:VEVENT" {
+            current = Some((None, None, "blackout".to_string()));
+            continue;
+        }
+        if line == "END:VEVENT" {
+            if let Some((Some(start_unix), end, name)) = current.take() {
+                out.push(Blackout {
+                    name,
+                    start_unix,
+                    end_unix: end.unwrap_or(start_unix).max(start_unix),
+                });
+            }
+            continue;
+        }
+        let Some((start, end, name)) = current.as_mut() else {
+            continue;
+        };
+        // Property name may carry parameters (DTSTART;TZID=...:value).
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut params = key.split(';');
+        let key = params.next().unwrap_or(key);
+        let tzid = params.find_map(|p| p.strip_prefix("TZID="));
+        match key {
+            "DTSTART" | "DTEND" => {
+                let t =
+                    ics_time(value, tzid).with_context(|| format!("line {}: {}", n + 1, line))?;
+                if key == "DTSTART" {
+                    *start = Some(t);
+                } else {
+                    *end = Some(t);
+                }
+            }
+            "SUMMARY" => *name = value.to_string(),
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+/// `YYYYMMDDTHHMMSS` or `YYYYMMDD` (all day, start of day): UTC with a trailing `Z`, else local
+/// to `tzid`. Floating (no `Z`, no zone) is an error.
+fn ics_time(v: &str, tzid: Option<&str>) -> Result<u64> {
+    let v = v.trim();
+    let (v, utc) = match v.strip_suffix('Z') {
+        Some(v) => (v, true),
+        None => (v, false),
+    };
+    let dt = NaiveDateTime::parse_from_str(v, "%Y%m%dT%H%M%S")
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(v, "%Y%m%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| anyhow!("invalid time {:?}", v))?;
+    let t = match (utc, tzid) {
+        (true, _) => dt.and_utc(),
+        (false, Some(tzid)) => {
+            let tz: Tz = tzid
+                .trim_matches('"')
+                .parse()
+                .map_err(|_| anyhow!("unknown TZID {:?}", tzid))?;
+            // Ambiguous (clocks going back): the first occurrence.
+            tz.from_local_datetime(&dt)
+                .earliest()
+                .ok_or_else(|| anyhow!("{} does not exist in {}", dt, tzid))?
+                .with_timezone(&Utc)
+        }
+        (false, None) => bail!("floating time {:?}: add a TZID or a trailing Z", v),
+    };
+    Ok(t.timestamp().max(0) as u64)
+}
@@ -0,0 +1,121 @@
+//! Fee rates (bps) of the traded market's Up and Down tokens: MM_FEE_RATE_BPS for both, else
+//! GET /fee-rate per token at the interval switch. A failed fetch leaves its token without a rate
+//! instead of assuming one: entries wait for a retry ([FEE_RATE_RETRY_MS] apart) rather than sign
+//! with a guessed fee, and fee estimates of closes count the missing rate as 0.
+
+use crate::book::fetch_fee_rate_bps;
+use crate::types::{EntrySide, ResolvedMarket};
+use reqwest::Client;
+use std::fmt;
+use tracing::warn;
+
+/// Least time between fetches of a missing fee rate.
+pub const FEE_RATE_RETRY_MS: u64 = 5000;
+
+/// Fee rates of one market's tokens.
+#[derive(Debug, Clone, Default)]
+pub struct FeeRates {
+    token_id_up: String,
+    token_id_down: String,
+    up: Option<u64>,
+    down: Option<u64>,
+    last_fetch_ms: u64,
+}
+
+impl FeeRates {
+    /// `bps` for both tokens of `market` (MM_FEE_RATE_BPS).
+    pub fn fixed(market: &ResolvedMarket, bps: u64) -> Self {
+        Self {
+            token_id_up: market.token_id_up.clone(),
+            token_id_down: market.token_id_down.clone(),
+            up: Some(bps),
+            down: Some(bps),
+            last_fetch_ms: 0,
+        }
+    }
+
+    /// Rates of `market`'s tokens: `fixed` when set, else fetched from `clob_host` per token.
+    pub async fn fetch(
+        client: &Client,
+        clob_host: &str,
+        market: &ResolvedMarket,
+        fixed: Option<u64>,
+        now_ms: u64,
+    ) -> Self {
+        if let Some(bps) = fixed {
+            return Self::fixed(market, bps);
+        }
+        let mut rates = Self {
+            token_id_up: market.token_id_up.clone(),
+            token_id_down: market.token_id_down.clone(),
+            ..Self::default()
+        };
+        rates.fetch_missing(client, clob_host, now_ms).await;
+        rates
+    }
+
+    /// Fetch the rates still missing, at most once every [FEE_RATE_RETRY_MS].
+    pub async fn retry(&mut self, client: &Client, clob_host: &str, now_ms: u64) {
+        if self.complete() || now_ms.saturating_sub(self.last_fetch_ms) < FEE_RATE_RETRY_MS {
+            return;
+        }
+        self.fetch_missing(client, clob_host, now_ms).await;
+    }
+
+    async fn fetch_missing(&mut self, client: &Client, clob_host: &str, now_ms: u64) {
+        self.last_fetch_ms = now_ms;
+        for (token_id, rate) in [
+            (&self.token_id_up, &mut self.up),
+            (&self.token_id_down, &mut self.down),
+        ] {
+            if rate.is_some() {
+                continue;
+            }
+            match fetch_fee_rate_bps(client, clob_host, token_id).await {
+                Ok(bps) => *rate = Some(bps),
+                Err(e) => warn!(
+                    "[FeeRates] fee-rate fetch for {} failed: {:#}; no entries until it succeeds",
+                    &token_id[..token_id.len().min(12)],
+                    e
+                ),
+            }
+        }
+    }
+
+    /// Both tokens have a rate.
+    pub fn complete(&self) -> bool {
+        self.up.is_some() && self.down.is_some()
+    }
+
+    /// Rate of `token_id`; None when its fetch failed or it is not one of the market's tokens.
+    pub fn token(&self, token_id: &str) -> Option<u64> {
+        if token_id == self.token_id_up {
+            self.up
+        } else if token_id == self.token_id_down {
+            self.down
+        } else {
+            None
+        }
+    }
+
+    /// Rate of `side`'s token for fee estimates (0 when unknown).
+    pub fn side(&self, side: EntrySide) -> u64 {
+        match side {
+            EntrySide::Up => self.up,
+            EntrySide::Down => self.down,
+        }
+        .unwrap_or(0)
+    }
+}
+
+/// `100` when both tokens share a rate, else `Up/Down` with `?` for a missing one.
+impl fmt::Display for FeeRates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |r: Option<u64>| r.map_or("?".to_string(), |r| r.to_string());
+        if self.up.is_some() && self.up == self.down {
+            write!(f, "{}", show(self.up))
+        } else {
+            write!(f, "{}/{}", show(self.up), show(self.down))
+        }
+    }
+}
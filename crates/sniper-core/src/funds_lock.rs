@@ -0,0 +1,85 @@
+//! Funds-locked detector (MM_AUTO_UNLOCK): the shares of a token our own resting sells hold (open
+//! sells in the order tracker, size less filled) and which of them a TP or SL exit may cancel to
+//! free the balance it needs. The policy ranks the two exits: an exit always outranks orders of
+//! its own kind and sells we did not tag (e.g. left by a previous run); `sl_priority` lets the stop
+//! loss cancel a resting take profit, `tp_priority` keeps the take profit resting and the stop loss
+//! sells only what is free.
+
+use crate::clob::OrderSide;
+use crate::order_tracker::OrderTracker;
+use crate::sell_campaign::SellKind;
+use crate::types::UnlockPolicy;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// `exit` may cancel a resting sell placed by `role` (None = untagged) under `policy`.
+pub fn may_cancel(policy: UnlockPolicy, exit: SellKind, role: Option<SellKind>) -> bool {
+    let Some(role) = role else {
+        return true;
+    };
+    role == exit
+        || match policy {
+            UnlockPolicy::TpPriority => exit == SellKind::TakeProfit,
+            UnlockPolicy::SlPriority => exit == SellKind::StopLoss,
+        }
+}
+
+/// One of our resting sells holding shares of the token.
+#[derive(Debug, Clone)]
+pub struct LockedOrder {
+    /// Exchange order ID.
+    pub order_id: String,
+    /// Exit that placed it, when tagged.
+    pub role: Option<SellKind>,
+    /// Shares still held by the order.
+    pub remaining: Decimal,
+}
+
+/// Shares of one token held by our resting sells.
+#[derive(Debug, Clone, Default)]
+pub struct FundsLock {
+    /// Our open sells of the token that still hold shares.
+    pub orders: Vec<LockedOrder>,
+}
+
+impl FundsLock {
+    /// Open sells of `token_id` in `tracker`; `roles` tags them by exchange order ID.
+    pub fn scan(tracker: &OrderTracker, token_id: &str, roles: &HashMap<String, SellKind>) -> Self {
+        let orders = tracker
+            .open_orders(token_id)
+            .filter(|o| o.side == OrderSide::Sell)
+            .filter_map(|o| {
+                let order_id = o.exchange_id.clone()?;
+                Some(LockedOrder {
+                    role: roles.get(&order_id).copied(),
+                    remaining: (o.size - o.filled).max(Decimal::ZERO),
+                    order_id,
+                })
+            })
+            .filter(|o| o.remaining > Decimal::ZERO)
+            .collect();
+        Self { orders }
+    }
+
+    /// Shares held by all our resting sells of the token.
+    pub fn locked(&self) -> Decimal {
+        self.orders.iter().map(|o| o.remaining).sum()
+    }
+
+    /// Orders `exit` may cancel under `policy`.
+    pub fn cancelable(&self, policy: UnlockPolicy, exit: SellKind) -> Vec<&LockedOrder> {
+        self.orders
+            .iter()
+            .filter(|o| may_cancel(policy, exit, o.role))
+            .collect()
+    }
+
+    /// Shares held by orders `exit` must leave resting under `policy`.
+    pub fn protected(&self, policy: UnlockPolicy, exit: SellKind) -> Decimal {
+        self.orders
+            .iter()
+            .filter(|o| !may_cancel(policy, exit, o.role))
+            .map(|o| o.remaining)
+            .sum()
+    }
+}
@@ -0,0 +1,275 @@
+//! Gamma API client: fetches raw markets by slug, condition ID or event. A market by slug is
+//! looked up on /markets/slug, /events/slug and /markets?slug= in that order (new 5m markets
+//! sometimes show up under the event first) and only accepted with two outcome tokens.
+//! Results by slug are cached (TTL, revalidated with ETag). Parsing into ResolvedMarket is in
+//! market.rs.
+
+use crate::market::parse_token_ids;
+use crate::types::{GammaEvent, GammaMarket};
+use anyhow::{Context, Result};
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::{Client, StatusCode};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// Cached Gamma market is used without a request for this long.
+pub const GAMMA_CACHE_TTL: Duration = Duration::from_secs(60);
+/// Cache entries older than this are dropped.
+const GAMMA_CACHE_MAX_AGE: Duration = Duration::from_secs(900);
+const USER_AGENT: &str = "polybot-interval-sniper-rust";
+
+/// Gamma endpoint a market was found on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GammaPath {
+    /// GET /markets/slug/{slug}.
+    MarketSlug,
+    /// GET /events/slug/{slug} (the event's first market).
+    EventSlug,
+    /// GET /markets?slug={slug}.
+    MarketsQuery,
+}
+
+impl fmt::Display for GammaPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GammaPath::MarketSlug => "/markets/slug",
+            GammaPath::EventSlug => "/events/slug",
+            GammaPath::MarketsQuery => "/markets?slug=",
+        })
+    }
+}
+
+struct CachedMarket {
+    market: GammaMarket,
+    /// ETag of the /markets/slug response (None when found on another path).
+    etag: Option<String>,
+    fetched: Instant,
+}
+
+fn gamma_cache() -> &'static Mutex<HashMap<String, CachedMarket>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, CachedMarket>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Gamma list fields come as JSON-encoded strings ("[\"Up\", \"Down\"]") or as arrays of
+/// strings / numbers; both give the items as strings.
+pub fn string_list(v: &serde_json::Value) -> Vec<String> {
+    match v {
+        serde_json::Value::String(s) => serde_json::from_str::<Vec<serde_json::Value>>(s)
+            .map_or_else(
+                |_| {
+                    s.split(',')
+                        .map(|p| p.trim().to_string())
+                        .filter(|p| !p.is_empty())
+                        .collect()
+                },
+                |a| a.iter().flat_map(string_list).collect(),
+            ),
+        serde_json::Value::Array(a) => a.iter().flat_map(string_list).collect(),
+        serde_json::Value::Number(n) => vec![n.to_string()],
+        _ => Vec::new(),
+    }
+}
+
+/// Binary market check: Up and Down token IDs resolve and Gamma lists two outcomes.
+fn validate(m: &GammaMarket) -> Result<()> {
+    parse_token_ids(m)?;
+    if let Some(outcomes) = m.outcomes.as_ref().map(string_list) {
+        if outcomes.len() != 2 {
+            anyhow::bail!("expected 2 outcomes, got {:?}", outcomes);
+        }
+    }
+    Ok(())
+}
+
+/// Market by slug. A cached market younger than `max_age` is returned as is; an older one is
+/// revalidated with If-None-Match on /markets/slug (304 keeps it).
+pub async fn market_by_slug(
+    client: &Client,
+    base_url: &str,
+    slug: &str,
+    max_age: Duration,
+) -> Result<GammaMarket> {
+    let cached_etag = {
+        let cache = gamma_cache().lock().unwrap_or_else(PoisonError::into_inner);
+        match cache.get(slug) {
+            Some(c) if c.fetched.elapsed() < max_age => return Ok(c.market.clone()),
+            Some(c) => c.etag.clone(),
+            None => None,
+        }
+    };
+    let base = base_url.trim_end_matches('/');
+    let mut failures = Vec::new();
+    for path in [
+        GammaPath::MarketSlug,
+        GammaPath::EventSlug,
+        GammaPath::MarketsQuery,
+    ] {
+        let found = match path {
+            GammaPath::MarketSlug => {
+                match fetch_market_slug(client, base, slug, cached_etag.as_deref()).await {
+                    Ok(MarketSlugResponse::NotModified) => {
+                        let mut cache =
+                            gamma_cache().lock().unwrap_or_else(PoisonError::into_inner);
+                        if let Some(c) = cache.get_mut(slug) {
+                            debug!("[Gamma] {} not modified", slug);
+                            c.fetched = Instant::now();
+                            return Ok(c.market.clone());
+                        }
+                        Err(anyhow::anyhow!("304 without a cached market"))
+                    }
+                    Ok(MarketSlugResponse::Found(m, etag)) => Ok((*m, etag)),
+                    Err(e) => Err(e),
+                }
+            }
+            GammaPath::EventSlug => fetch_event_slug(client, base, slug)
+                .await
+                .map(|markets| pick_from_event(markets, slug))
+                .and_then(|m| m.context("event has no markets"))
+                .map(|m| (m, None)),
+            GammaPath::MarketsQuery => fetch_markets_query(client, base, slug)
+                .await
+                .map(|m| (m, None)),
+        };
+        let (m, etag) = match found.and_then(|(m, etag)| validate(&m).map(|_| (m, etag))) {
+            Ok(v) => v,
+            Err(e) => {
+                debug!("[Gamma] {} via {}: {:#}", slug, path, e);
+                failures.push(format!("{}: {:#}", path, e));
+                continue;
+            }
+        };
+        if path != GammaPath::MarketSlug {
+            info!("[Gamma] {} resolved via {}", slug, path);
+        }
+        let mut cache = gamma_cache().lock().unwrap_or_else(PoisonError::into_inner);
+        cache.retain(|_, c| c.fetched.elapsed() < GAMMA_CACHE_MAX_AGE);
+        cache.insert(
+            slug.to_string(),
+            CachedMarket {
+                market: m.clone(),
+                etag,
+                fetched: Instant::now(),
+            },
+        );
+        return Ok(m);
+    }
+    anyhow::bail!(
+        "Gamma market \"{}\" not found ({})",
+        slug,
+        failures.join("; ")
+    )
+}
+
+enum MarketSlugResponse {
+    NotModified,
+    Found(Box<GammaMarket>, Option<String>),
+}
+
+/// GET /markets/slug/{slug}, conditional on `etag`.
+async fn fetch_market_slug(
+    client: &Client,
+    base: &str,
+    slug: &str,
+    etag: Option<&str>,
+) -> Result<MarketSlugResponse> {
+    let url = format!("{}/markets/slug/{}", base, urlencoding::encode(slug));
+    let mut req = client.get(&url).header("user-agent", USER_AGENT);
+    if let Some(etag) = etag {
+        req = req.header(IF_NONE_MATCH, etag);
+    }
+    let res = req.send().await.context("Gamma API request")?;
+    match res.status() {
+        StatusCode::NOT_MODIFIED => return Ok(MarketSlugResponse::NotModified),
+        s if !s.is_success() => anyhow::bail!("HTTP {}", s),
+        _ => {}
+    }
+    let etag = res
+        .headers()
+        .get(ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let m = res
+        .json::<GammaMarket>()
+        .await
+        .context("Gamma market JSON")?;
+    Ok(MarketSlugResponse::Found(Box::new(m), etag))
+}
+
+/// Market of an event matching `slug`, else its first market.
+fn pick_from_event(markets: Vec<GammaMarket>, slug: &str) -> Option<GammaMarket> {
+    let i = markets
+        .iter()
+        .position(|m| m.slug.as_deref() == Some(slug))
+        .unwrap_or(0);
+    markets.into_iter().nth(i)
+}
+
+/// GET /events/slug/{slug}: the event's markets.
+async fn fetch_event_slug(client: &Client, base: &str, slug: &str) -> Result<Vec<GammaMarket>> {
+    let url = format!("{}/events/slug/{}", base, urlencoding::encode(slug));
+    let res = client
+        .get(&url)
+        .header("user-agent", USER_AGENT)
+        .send()
+        .await
+        .context("Gamma API event request")?;
+    if !res.status().is_success() {
+        anyhow::bail!("HTTP {}", res.status());
+    }
+    let event: GammaEvent = res.json().await.context("Gamma event JSON")?;
+    event.markets.context("Event has no markets")
+}
+
+/// GET /markets?slug={slug}.
+async fn fetch_markets_query(client: &Client, base: &str, slug: &str) -> Result<GammaMarket> {
+    let url = format!("{}/markets?slug={}", base, urlencoding::encode(slug));
+    let res = client
+        .get(&url)
+        .header("user-agent", USER_AGENT)
+        .send()
+        .await
+        .context("Gamma API request")?;
+    if !res.status().is_success() {
+        anyhow::bail!("HTTP {}", res.status());
+    }
+    let markets: Vec<GammaMarket> = res.json().await.context("Gamma markets JSON")?;
+    markets.into_iter().next().context("no market")
+}
+
+/// Every market of an event (GET /events/slug/{slug}), uncached.
+pub async fn event_markets(
+    client: &Client,
+    base_url: &str,
+    event_slug: &str,
+) -> Result<Vec<GammaMarket>> {
+    fetch_event_slug(client, base_url.trim_end_matches('/'), event_slug).await
+}
+
+/// Market by condition ID (GET /markets?condition_ids=...).
+pub async fn market_by_condition_id(
+    client: &Client,
+    base_url: &str,
+    condition_id: &str,
+) -> Result<GammaMarket> {
+    let base = base_url.trim_end_matches('/');
+    let url = format!(
+        "{}/markets?condition_ids={}",
+        base,
+        urlencoding::encode(condition_id)
+    );
+    let res = client
+        .get(&url)
+        .header("user-agent", USER_AGENT)
+        .send()
+        .await
+        .context("Gamma API request")?;
+    let markets: Vec<GammaMarket> = res.json().await.context("Gamma markets JSON")?;
+    markets
+        .into_iter()
+        .next()
+        .with_context(|| format!("No market with condition ID {}", condition_id))
+}
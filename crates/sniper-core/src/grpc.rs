@@ -0,0 +1,307 @@
+//! gRPC executor service (`grpc` feature, proto/executor.proto): book snapshots, order placement
+//! and cancellation, and position queries over the engine's own CLOB client, so a strategy written
+//! in another language keeps this crate as its execution and market-data layer. Books come from
+//! GET /book or, streamed, from the WebSocket feed ([ClobWsBook]); orders go through
+//! [ClobClient] (live, dry run or several accounts, as [crate::clob::create_clob_client] builds
+//! it); positions from the data API for the client's funders. Served by the `executor` binary.
+
+use crate::book::{self, fetch_order_book, parse_positive, BookDepth, Levels};
+use crate::clob::{
+    CancelOrdersResult, ClobClient, LimitOrderParams, OrderSide, OrderType, PlaceOrderResult,
+};
+use crate::clob_ws_book::ClobWsBook;
+use crate::clock::wall_ms;
+use crate::positions::fetch_all_positions;
+use reqwest::Client;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+/// Generated messages, client and server of proto/executor.proto.
+pub mod proto {
+    tonic::include_proto!("sniper.executor.v1");
+}
+
+use proto::executor_server::{Executor, ExecutorServer};
+
+/// How often a WatchBooks stream looks at the WS book for changes.
+const WATCH_POLL_MS: u64 = 20;
+/// Snapshots a WatchBooks stream buffers for a slow client before it stops waiting for it.
+const WATCH_BUFFER: usize = 64;
+/// Timeout of the public reads (GET /book, data API).
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The Executor service over one CLOB client.
+pub struct ExecutorService {
+    clob: Box<dyn ClobClient>,
+    /// Public reads: GET /book and the data API ([book::public_client]).
+    reads: Client,
+    clob_host: String,
+    data_api_url: String,
+}
+
+impl ExecutorService {
+    /// Service trading through `clob`, reading books from `clob_host` and positions from
+    /// `data_api_url`.
+    pub fn new(
+        clob: Box<dyn ClobClient>,
+        clob_host: &str,
+        data_api_url: &str,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            clob,
+            reads: book::public_client(READ_TIMEOUT)?,
+            clob_host: clob_host.trim_end_matches('/').to_string(),
+            data_api_url: data_api_url.to_string(),
+        })
+    }
+}
+
+/// Serve `service` on `addr` until the process stops.
+pub async fn serve(service: ExecutorService, addr: SocketAddr) -> anyhow::Result<()> {
+    info!("[Executor] gRPC service listening on {}", addr);
+    tonic::transport::Server::builder()
+        .add_service(ExecutorServer::new(service))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
+
+/// Serve `service` on an already bound listener (e.g. port 0 in tests).
+pub async fn serve_on(
+    service: ExecutorService,
+    listener: tokio::net::TcpListener,
+) -> anyhow::Result<()> {
+    tonic::transport::Server::builder()
+        .add_service(ExecutorServer::new(service))
+        .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+        .await?;
+    Ok(())
+}
+
+fn levels(levels: Levels) -> Vec<proto::Level> {
+    levels
+        .into_iter()
+        .map(|(price, size)| proto::Level {
+            price: price.to_string(),
+            size: size.to_string(),
+        })
+        .collect()
+}
+
+/// Levels per side a request asks for (0 = all).
+fn depth(n: u32) -> usize {
+    if n == 0 {
+        usize::MAX
+    } else {
+        n as usize
+    }
+}
+
+/// Answer to a request field that is not a positive decimal.
+fn not_positive(field: &str) -> Status {
+    Status::invalid_argument(format!("{} must be a positive decimal", field))
+}
+
+fn cancel_reply(result: CancelOrdersResult) -> proto::CancelReply {
+    proto::CancelReply {
+        canceled: result.canceled,
+        not_canceled: result.not_canceled,
+    }
+}
+
+fn place_reply(result: PlaceOrderResult) -> proto::PlaceOrderReply {
+    let text = |d: Option<rust_decimal::Decimal>| d.map(|d| d.to_string()).unwrap_or_default();
+    proto::PlaceOrderReply {
+        success: result.success,
+        order_id: result.order_id.unwrap_or_default(),
+        error: result.error_msg.unwrap_or_default(),
+        filled_size: text(result.filled_size),
+        avg_price: text(result.avg_price),
+        http_status: result.http_status.map(u32::from).unwrap_or_default(),
+    }
+}
+
+#[tonic::async_trait]
+impl Executor for ExecutorService {
+    async fn get_book(
+        &self,
+        request: Request<proto::GetBookRequest>,
+    ) -> Result<Response<proto::BookSnapshot>, Status> {
+        let req = request.into_inner();
+        if req.token_id.is_empty() {
+            return Err(Status::invalid_argument("token_id is required"));
+        }
+        let raw = fetch_order_book(&self.reads, &self.clob_host, &req.token_id)
+            .await
+            .map_err(|e| Status::unavailable(format!("GET /book: {:#}", e)))?;
+        let (bids, asks) = BookDepth::from_raw(&raw).top_levels(depth(req.depth));
+        Ok(Response::new(proto::BookSnapshot {
+            token_id: req.token_id,
+            bids: levels(bids),
+            asks: levels(asks),
+            timestamp_ms: wall_ms(),
+            tick_size: raw.tick_size.unwrap_or_default(),
+            min_order_size: raw.min_order_size.unwrap_or_default(),
+        }))
+    }
+
+    type WatchBooksStream = ReceiverStream<Result<proto::BookSnapshot, Status>>;
+
+    async fn watch_books(
+        &self,
+        request: Request<proto::WatchBooksRequest>,
+    ) -> Result<Response<Self::WatchBooksStream>, Status> {
+        let req = request.into_inner();
+        if req.up_token_id.is_empty() || req.down_token_id.is_empty() {
+            return Err(Status::invalid_argument(
+                "up_token_id and down_token_id are required",
+            ));
+        }
+        let ws_url = ClobWsBook::ws_url_from_rest_host(&self.clob_host);
+        let ws = ClobWsBook::connect(&ws_url, &req.up_token_id, &req.down_token_id)
+            .await
+            .map_err(|e| Status::unavailable(format!("WebSocket book: {:#}", e)))?;
+        let (tx, rx) = mpsc::channel(WATCH_BUFFER);
+        let n = depth(req.depth);
+        tokio::spawn(async move {
+            let tokens = [req.up_token_id, req.down_token_id];
+            let mut sent: [Option<(Levels, Levels)>; 2] = [None, None];
+            let mut poll = tokio::time::interval(Duration::from_millis(WATCH_POLL_MS));
+            // Dropping `ws` when the client hangs up closes the feed.
+            while !tx.is_closed() {
+                poll.tick().await;
+                for (token_id, last) in tokens.iter().zip(sent.iter_mut()) {
+                    let top = ws.depth(token_id, n).await;
+                    // Nothing until the first snapshot, then only changes.
+                    if (top.0.is_empty() && top.1.is_empty()) || last.as_ref() == Some(&top) {
+                        continue;
+                    }
+                    *last = Some(top.clone());
+                    let snapshot = proto::BookSnapshot {
+                        token_id: token_id.clone(),
+                        bids: levels(top.0),
+                        asks: levels(top.1),
+                        timestamp_ms: wall_ms(),
+                        tick_size: String::new(),
+                        min_order_size: String::new(),
+                    };
+                    if tx.send(Ok(snapshot)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn place_order(
+        &self,
+        request: Request<proto::PlaceOrderRequest>,
+    ) -> Result<Response<proto::PlaceOrderReply>, Status> {
+        let req = request.into_inner();
+        if req.token_id.is_empty() {
+            return Err(Status::invalid_argument("token_id is required"));
+        }
+        let side = match proto::Side::try_from(req.side) {
+            Ok(proto::Side::Buy) => OrderSide::Buy,
+            Ok(proto::Side::Sell) => OrderSide::Sell,
+            Err(_) => return Err(Status::invalid_argument("unknown side")),
+        };
+        let order_type = match proto::OrderType::try_from(req.order_type) {
+            Ok(proto::OrderType::Gtc) => OrderType::Gtc,
+            Ok(proto::OrderType::Gtd) => OrderType::Gtd,
+            Ok(proto::OrderType::Fok) => OrderType::Fok,
+            Ok(proto::OrderType::Fak) => OrderType::Fak,
+            Err(_) => return Err(Status::invalid_argument("unknown order_type")),
+        };
+        if matches!(order_type, OrderType::Gtd) && req.expiration_unix.is_none() {
+            return Err(Status::invalid_argument("GTD orders need expiration_unix"));
+        }
+        let params = LimitOrderParams {
+            token_id: req.token_id,
+            side,
+            price: parse_positive(&req.price).ok_or_else(|| not_positive("price"))?,
+            size: parse_positive(&req.size).ok_or_else(|| not_positive("size"))?,
+            expiration_unix: req.expiration_unix,
+            post_only: req.post_only,
+            fee_rate_bps: req.fee_rate_bps,
+            min_order_size: None,
+        };
+        let result = self
+            .clob
+            .place_limit_order(params, order_type)
+            .await
+            .map_err(|e| Status::internal(format!("order not sent: {:#}", e)))?;
+        if !result.success {
+            warn!(
+                "[Executor] order refused: {}",
+                result.error_msg.as_deref().unwrap_or("unknown error")
+            );
+        }
+        Ok(Response::new(place_reply(result)))
+    }
+
+    async fn cancel_order(
+        &self,
+        request: Request<proto::CancelOrderRequest>,
+    ) -> Result<Response<proto::CancelReply>, Status> {
+        let req = request.into_inner();
+        if req.order_id.is_empty() {
+            return Err(Status::invalid_argument("order_id is required"));
+        }
+        let result = self
+            .clob
+            .cancel_order(&req.order_id)
+            .await
+            .map_err(|e| Status::unavailable(format!("cancel: {:#}", e)))?;
+        Ok(Response::new(cancel_reply(result)))
+    }
+
+    async fn cancel_token(
+        &self,
+        request: Request<proto::CancelTokenRequest>,
+    ) -> Result<Response<proto::CancelReply>, Status> {
+        let req = request.into_inner();
+        if req.token_id.is_empty() {
+            return Err(Status::invalid_argument("token_id is required"));
+        }
+        let result = self
+            .clob
+            .cancel_orders_for_token(&req.token_id)
+            .await
+            .map_err(|e| Status::unavailable(format!("cancel: {:#}", e)))?;
+        Ok(Response::new(cancel_reply(result)))
+    }
+
+    async fn get_positions(
+        &self,
+        _request: Request<proto::GetPositionsRequest>,
+    ) -> Result<Response<proto::Positions>, Status> {
+        let held = fetch_all_positions(&self.reads, &self.data_api_url, &self.clob.funders())
+            .await
+            .map_err(|e| Status::unavailable(format!("positions: {:#}", e)))?;
+        let collateral = self
+            .clob
+            .get_collateral_balance()
+            .await
+            .map_err(|e| Status::unavailable(format!("collateral balance: {:#}", e)))?;
+        Ok(Response::new(proto::Positions {
+            positions: held
+                .into_iter()
+                .map(|p| proto::Position {
+                    token_id: p.asset,
+                    size: p.size.to_string(),
+                    avg_price: p.avg_price.to_string(),
+                    slug: p.slug,
+                    outcome: p.outcome,
+                    redeemable: p.redeemable,
+                })
+                .collect(),
+            collateral: collateral.map(|c| c.to_string()).unwrap_or_default(),
+        }))
+    }
+}
@@ -0,0 +1,81 @@
+//! Hourly "above $X" crypto markets (MM_TARGET_HOURLY_ABOVE): Polymarket lists one event per
+//! asset and hour ("Ethereum above ___ on October 16, 3PM ET?") with one Yes/No market per strike,
+//! all closing on the hour. The event slug follows from the asset and the hour in US Eastern time;
+//! the runner scans its strikes like an event target (event::scan_hourly_above) and only buys
+//! the side the spot price is on.
+
+use crate::types::{EntrySide, HourlyAsset};
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Weekday};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+pub const HOUR_SECS: u64 = 3600;
+
+/// Close (unix) of the hourly markets open at `now_unix`: the next full hour.
+pub fn hour_close_unix(now_unix: u64) -> u64 {
+    (now_unix / HOUR_SECS + 1) * HOUR_SECS
+}
+
+/// UTC offset of US Eastern time at `unix`, in hours: -4 during daylight saving time (second
+/// Sunday of March to first Sunday of November, at 2 AM local), -5 otherwise.
+fn eastern_offset_hours(unix: u64) -> i64 {
+    let Some(utc) = DateTime::from_timestamp(unix as i64, 0) else {
+        return -5;
+    };
+    let sunday_utc = |month: u32, n: u8, utc_hour: u32| {
+        NaiveDate::from_weekday_of_month_opt(utc.year(), month, Weekday::Sun, n)
+            .and_then(|d| d.and_hms_opt(utc_hour, 0, 0))
+            .map(|t| t.and_utc().timestamp())
+            .unwrap_or(0)
+    };
+    // 2 AM EST = 07:00 UTC; 2 AM EDT = 06:00 UTC.
+    let (start, end) = (sunday_utc(3, 2, 7), sunday_utc(11, 1, 6));
+    if (start..end).contains(&utc.timestamp()) {
+        -4
+    } else {
+        -5
+    }
+}
+
+fn slug_name(asset: HourlyAsset) -> &'static str {
+    match asset {
+        HourlyAsset::Eth => "ethereum",
+        HourlyAsset::Sol => "solana",
+    }
+}
+
+/// Gamma event slug of the hourly markets closing at `close_unix`, e.g.
+/// `ethereum-above-on-october-16-3pm-et`.
+pub fn hourly_event_slug(asset: HourlyAsset, close_unix: u64) -> String {
+    let local = close_unix as i64 + eastern_offset_hours(close_unix) * HOUR_SECS as i64;
+    let et = DateTime::from_timestamp(local, 0).unwrap_or_default();
+    let (hour, meridiem) = match et.hour() {
+        0 => (12, "am"),
+        h @ 1..=11 => (h, "am"),
+        12 => (12, "pm"),
+        h => (h - 12, "pm"),
+    };
+    format!(
+        "{}-above-on-{}-{}-{}{}-et",
+        slug_name(asset),
+        et.format("%B").to_string().to_lowercase(),
+        et.day(),
+        hour,
+        meridiem
+    )
+}
+
+/// True when the spot is on `side`'s side of the strike (Up = Yes = above, Down = No = below)
+/// by at least `min_distance_bps` of the strike.
+pub fn strike_allows(
+    side: EntrySide,
+    spot: Decimal,
+    strike: Decimal,
+    min_distance_bps: u64,
+) -> bool {
+    let margin = strike * Decimal::from(min_distance_bps) / dec!(10000);
+    match side {
+        EntrySide::Up => spot >= strike + margin,
+        EntrySide::Down => spot <= strike - margin,
+    }
+}
@@ -0,0 +1,108 @@
+//! Instance lock: a live engine holds an exclusive file lock per wallet and market target, so a
+//! second bot started by mistake with the same key on the same market refuses to trade instead of
+//! fighting the first over its orders (cancelling its TP/SL, double buying, breaking the
+//! one-entry-per-interval dedupe). Lock files live in MM_INSTANCE_LOCK_DIR (default: the system
+//! temp dir) and name the holder (pid, start time). The OS drops the lock when the process exits,
+//! so a crash never leaves it stuck. Engines of the same process (MM_DUAL_WINDOW, the scanner)
+//! share it. MM_ALLOW_MULTIPLE_INSTANCES=true skips the check.
+
+use crate::config::slug_prefix;
+use crate::types::MarketTarget;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, Weak};
+
+/// Lock file held open (and locked) while any engine of this process uses it.
+struct Held {
+    _file: File,
+}
+
+/// One wallet's lock on one market target; released when the last clone is dropped.
+#[derive(Clone)]
+pub struct InstanceLock {
+    _held: Arc<Held>,
+    path: PathBuf,
+}
+
+/// Locks this process holds, by file, so its own engines share them.
+fn held() -> &'static Mutex<HashMap<PathBuf, Weak<Held>>> {
+    static HELD: OnceLock<Mutex<HashMap<PathBuf, Weak<Held>>>> = OnceLock::new();
+    HELD.get_or_init(Mutex::default)
+}
+
+/// What two instances must not share: the interval series (any window), or the fixed market.
+pub fn target_key(target: &MarketTarget) -> String {
+    match target {
+        MarketTarget::Interval(asset, _) | MarketTarget::IntervalAt(asset, _) => {
+            slug_prefix(*asset).to_string()
+        }
+        MarketTarget::Slug(s) | MarketTarget::ConditionId(s) | MarketTarget::Event(s) => s.clone(),
+        MarketTarget::HourlyAbove(asset) => format!("{:?}-hourly-above", asset).to_lowercase(),
+    }
+}
+
+/// Lock file of `wallet` on `key` in `dir`.
+pub fn lock_path(dir: &Path, wallet: &str, key: &str) -> PathBuf {
+    let name: String = format!("sniper-{}-{}", wallet.to_lowercase(), key)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    dir.join(format!("{}.lock", name))
+}
+
+impl InstanceLock {
+    /// Lock `wallet` on `key` in `dir`. Fails when another process holds it, naming the holder.
+    pub fn acquire(dir: &Path, wallet: &str, key: &str, now_ms: u64) -> Result<Self> {
+        let path = lock_path(dir, wallet, key);
+        let mut held = held().lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(h) = held.get(&path).and_then(Weak::upgrade) {
+            return Ok(Self { _held: h, path });
+        }
+        std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .with_context(|| format!("open {}", path.display()))?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let mut holder = String::new();
+                let _ = file.read_to_string(&mut holder);
+                anyhow::bail!(
+                    "another instance is trading wallet {} on {} ({}, lock {}); stop it or set MM_ALLOW_MULTIPLE_INSTANCES=true",
+                    wallet,
+                    key,
+                    holder.trim(),
+                    path.display()
+                );
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("lock {}", path.display()))
+            }
+        }
+        let owner = serde_json::json!({ "pid": std::process::id(), "since_ms": now_ms });
+        file.set_len(0)?;
+        file.rewind()?;
+        writeln!(file, "{}", owner)?;
+        let h = Arc::new(Held { _file: file });
+        held.insert(path.clone(), Arc::downgrade(&h));
+        Ok(Self { _held: h, path })
+    }
+
+    /// The lock file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
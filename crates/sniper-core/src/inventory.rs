@@ -0,0 +1,310 @@
+//! Inventory: open lots per token (entry price, size, time) with FIFO closes, so re-entries,
+//! dual-side pairs and partial exits keep a correct cost basis and per-lot PnL. Realized closes
+//! feed the session's trade stats (win rate and average payoffs, used by Kelly sizing), one trade
+//! per position: every close from its first lot until the token is flat again, however many
+//! sells (retries, partial fills) that took.
+
+use crate::types::EntrySide;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+
+/// One buy fill still (partly) held.
+#[derive(Debug, Clone)]
+pub struct Lot {
+    pub side: EntrySide,
+    pub entry_price: Decimal,
+    pub size: Decimal,
+    pub timestamp_ms: u64,
+}
+
+/// The part of a lot consumed by a sell.
+#[derive(Debug, Clone)]
+pub struct ClosedLot {
+    pub side: EntrySide,
+    pub entry_price: Decimal,
+    pub exit_price: Decimal,
+    pub size: Decimal,
+    pub entry_ms: u64,
+}
+
+/// Realized results of every position closed this session, each summed over the lots it closed.
+/// Payoffs are per USDC of entry cost.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TradeStats {
+    pub wins: u32,
+    pub losses: u32,
+    /// Trades closed at exactly their entry cost: neither a win nor a loss.
+    pub scratches: u32,
+    win_cost: Decimal,
+    win_pnl: Decimal,
+    loss_cost: Decimal,
+    loss_pnl: Decimal,
+}
+
+impl TradeStats {
+    /// One trade: the lots a position's closes consumed.
+    fn record(&mut self, closed: &[ClosedLot]) {
+        let cost: Decimal = closed.iter().map(|c| c.entry_price * c.size).sum();
+        let pnl: Decimal = closed
+            .iter()
+            .map(|c| (c.exit_price - c.entry_price) * c.size)
+            .sum();
+        if cost <= Decimal::ZERO {
+            return;
+        }
+        if pnl > Decimal::ZERO {
+            self.wins += 1;
+            self.win_cost += cost;
+            self.win_pnl += pnl;
+        } else if pnl.is_zero() {
+            self.scratches += 1;
+        } else {
+            self.losses += 1;
+            self.loss_cost += cost;
+            self.loss_pnl -= pnl;
+        }
+    }
+
+    pub fn trades(&self) -> u32 {
+        self.wins + self.losses + self.scratches
+    }
+
+    /// Share of the trades that won or lost with a positive PnL (None before the first).
+    pub fn win_rate(&self) -> Option<Decimal> {
+        let decided = self.wins + self.losses;
+        (decided > 0).then(|| Decimal::from(self.wins) / Decimal::from(decided))
+    }
+
+    /// Realized PnL of every trade (before fees).
+    pub fn realized_pnl(&self) -> Decimal {
+        self.win_pnl - self.loss_pnl
+    }
+
+    /// Average gain per USDC staked on winning trades.
+    pub fn avg_win(&self) -> Option<Decimal> {
+        (self.win_cost > Decimal::ZERO).then(|| self.win_pnl / self.win_cost)
+    }
+
+    /// Average loss per USDC staked on losing trades (positive).
+    pub fn avg_loss(&self) -> Option<Decimal> {
+        (self.loss_cost > Decimal::ZERO).then(|| self.loss_pnl / self.loss_cost)
+    }
+}
+
+/// Open lots keyed by token ID, plus the last mark (best bid) per token.
+#[derive(Debug, Default)]
+pub struct Inventory {
+    lots: HashMap<String, VecDeque<Lot>>,
+    marks: HashMap<String, Decimal>,
+    /// Lots closed so far of a position still (partly) open, per token: recorded as one trade
+    /// once the token is flat.
+    open_trades: HashMap<String, Vec<ClosedLot>>,
+    stats: TradeStats,
+    /// USDC received from sells minus USDC paid for buys (before fees).
+    cash_flow: Decimal,
+}
+
+impl Inventory {
+    pub fn add_lot(
+        &mut self,
+        token_id: &str,
+        side: EntrySide,
+        entry_price: Decimal,
+        size: Decimal,
+        timestamp_ms: u64,
+    ) {
+        if size <= Decimal::ZERO {
+            return;
+        }
+        self.cash_flow -= entry_price * size;
+        self.lots
+            .entry(token_id.to_string())
+            .or_default()
+            .push_back(Lot {
+                side,
+                entry_price,
+                size,
+                timestamp_ms,
+            });
+    }
+
+    /// Sell `size` of `token_id` at `exit_price`, consuming lots oldest first. Returns the closed
+    /// parts; selling more than is held closes everything held.
+    pub fn close(&mut self, token_id: &str, size: Decimal, exit_price: Decimal) -> Vec<ClosedLot> {
+        let mut closed = Vec::new();
+        let Some(q) = self.lots.get_mut(token_id) else {
+            return closed;
+        };
+        let mut remaining = size;
+        while remaining > Decimal::ZERO {
+            let Some(lot) = q.front_mut() else {
+                break;
+            };
+            let take = lot.size.min(remaining);
+            self.cash_flow += exit_price * take;
+            closed.push(ClosedLot {
+                side: lot.side,
+                entry_price: lot.entry_price,
+                exit_price,
+                size: take,
+                entry_ms: lot.timestamp_ms,
+            });
+            lot.size -= take;
+            remaining -= take;
+            if lot.size <= Decimal::ZERO {
+                q.pop_front();
+            }
+        }
+        if q.is_empty() {
+            self.lots.remove(token_id);
+        }
+        self.book_closes(token_id, &closed);
+        closed
+    }
+
+    /// Add `closed` to the position's trade, and record the trade once `token_id` is flat.
+    fn book_closes(&mut self, token_id: &str, closed: &[ClosedLot]) {
+        let trade = self.open_trades.entry(token_id.to_string()).or_default();
+        trade.extend_from_slice(closed);
+        if !self.lots.contains_key(token_id) {
+            if let Some(trade) = self.open_trades.remove(token_id) {
+                self.stats.record(&trade);
+            }
+        }
+    }
+
+    /// Drop `size` of a token that was never actually bought (e.g. the canceled rest of a GTC
+    /// buy), newest lots first. Not a trade: nothing is recorded in the stats.
+    pub fn unfill(&mut self, token_id: &str, size: Decimal) {
+        let Some(q) = self.lots.get_mut(token_id) else {
+            return;
+        };
+        let mut remaining = size;
+        while remaining > Decimal::ZERO {
+            let Some(lot) = q.back_mut() else {
+                break;
+            };
+            let take = lot.size.min(remaining);
+            self.cash_flow += lot.entry_price * take;
+            lot.size -= take;
+            remaining -= take;
+            if lot.size <= Decimal::ZERO {
+                q.pop_back();
+            }
+        }
+        if q.is_empty() {
+            self.lots.remove(token_id);
+            // Flat: what was sold before is the whole trade.
+            self.book_closes(token_id, &[]);
+        }
+    }
+
+    /// Close every open lot at its token's last mark (0 if never marked), e.g. at market close:
+    /// one trade per token, with what was sold of it before.
+    pub fn close_all_at_mark(&mut self) -> Vec<ClosedLot> {
+        let mut closed = Vec::new();
+        for (token_id, q) in std::mem::take(&mut self.lots) {
+            let exit_price = self.marks.get(&token_id).copied().unwrap_or(Decimal::ZERO);
+            let token_closed: Vec<ClosedLot> = q
+                .into_iter()
+                .map(|lot| ClosedLot {
+                    side: lot.side,
+                    entry_price: lot.entry_price,
+                    exit_price,
+                    size: lot.size,
+                    entry_ms: lot.timestamp_ms,
+                })
+                .collect();
+            self.book_closes(&token_id, &token_closed);
+            closed.extend(token_closed);
+        }
+        self.marks.clear();
+        closed
+    }
+
+    /// Remove the open lots of a token without closing them, e.g. a position carried past its
+    /// market's close until the resolution is known. Not a trade; close them later with
+    /// [Inventory::close_lots].
+    pub fn take_lots(&mut self, token_id: &str) -> Vec<Lot> {
+        self.lots
+            .remove(token_id)
+            .map(Vec::from)
+            .unwrap_or_default()
+    }
+
+    /// Close lots of `token_id` removed with [Inventory::take_lots] at `exit_price` (the
+    /// resolution payout). Recorded in the stats like any close; not cash, as
+    /// [Inventory::close_all_at_mark].
+    pub fn close_lots(
+        &mut self,
+        token_id: &str,
+        lots: Vec<Lot>,
+        exit_price: Decimal,
+    ) -> Vec<ClosedLot> {
+        let closed: Vec<ClosedLot> = lots
+            .into_iter()
+            .map(|lot| ClosedLot {
+                side: lot.side,
+                entry_price: lot.entry_price,
+                exit_price,
+                size: lot.size,
+                entry_ms: lot.timestamp_ms,
+            })
+            .collect();
+        self.book_closes(token_id, &closed);
+        closed
+    }
+
+    /// Record the latest best bid for a token (used as exit price when the market closes).
+    pub fn set_mark(&mut self, token_id: &str, price: Decimal) {
+        self.marks.insert(token_id.to_string(), price);
+    }
+
+    /// Last mark (best bid) recorded for a token.
+    pub fn mark(&self, token_id: &str) -> Option<Decimal> {
+        self.marks.get(token_id).copied()
+    }
+
+    /// Shares held for a token across all lots.
+    pub fn size(&self, token_id: &str) -> Decimal {
+        self.lots
+            .get(token_id)
+            .map(|q| q.iter().map(|l| l.size).sum())
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    /// Cost basis: sum of entry price × size over the open lots of a token.
+    pub fn cost(&self, token_id: &str) -> Decimal {
+        self.lots
+            .get(token_id)
+            .map(|q| q.iter().map(|l| l.entry_price * l.size).sum())
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    /// Size-weighted average entry price of the open lots (None when flat).
+    pub fn avg_entry(&self, token_id: &str) -> Option<Decimal> {
+        let size = self.size(token_id);
+        (size > Decimal::ZERO).then(|| self.cost(token_id) / size)
+    }
+
+    /// PnL if the open lots of a token were sold at `best_bid` (before fees).
+    pub fn unrealized_pnl(&self, token_id: &str, best_bid: Decimal) -> Decimal {
+        best_bid * self.size(token_id) - self.cost(token_id)
+    }
+
+    /// Realized trade stats of every close so far.
+    pub fn stats(&self) -> &TradeStats {
+        &self.stats
+    }
+
+    /// USDC from sells minus USDC spent on buys so far (before fees). Closes at the mark
+    /// ([Inventory::close_all_at_mark]) are not cash and do not count.
+    pub fn cash_flow(&self) -> Decimal {
+        self.cash_flow
+    }
+
+    /// Open lots for a token, oldest first.
+    pub fn lots(&self, token_id: &str) -> impl Iterator<Item = &Lot> {
+        self.lots.get(token_id).into_iter().flatten()
+    }
+}
@@ -0,0 +1,79 @@
+//! Interval Sniper engine: order books, entry strategies, order execution, Gamma market discovery
+//! and order signing, for the `sniper` bot and its helper binaries (`derive_api_key`,
+//! `sweep_profits`, `optimize`, `flatten`) and for embedding in other programs.
+//!
+//! The stable API is the engine modules below: [runner] (run the bot with a [types::Config] from
+//! [config::load_config] or built by hand, on the wall clock or a [clock::SimClock]), [book],
+//! [strategy] ([strategy::TickStrategy]), [clob] ([clob::ClobClient]), [gamma], [signing] and
+//! [clock]. Their public items are documented (`missing_docs` warns there). The other modules are
+//! public for the binaries and may change between versions.
+//!
+//! Library code does not panic on bad input, exchange answers or I/O: errors come back as
+//! `Result`, clock reads before the unix epoch read as 0 and a poisoned lock is used as is. The
+//! remaining `expect`s are on invariants of the code itself (constant addresses and regexes,
+//! options checked just before).
+
+pub mod auth;
+pub mod backtest;
+#[warn(missing_docs)]
+pub mod book;
+pub mod book_recorder;
+pub mod carryover;
+pub mod chain;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+#[warn(missing_docs)]
+pub mod clob;
+pub mod clob_ws_book;
+#[warn(missing_docs)]
+pub mod clock;
+#[warn(missing_docs)]
+pub mod config;
+pub mod decision_trace;
+pub mod dry_run_report;
+pub mod entry_gate;
+pub mod event;
+pub mod event_calendar;
+pub mod fee_rates;
+pub mod funds_lock;
+#[warn(missing_docs)]
+pub mod gamma;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod hourly;
+pub mod instance_lock;
+pub mod inventory;
+pub mod market;
+pub mod market_store;
+pub mod messages;
+pub mod multi_clob;
+pub mod order_journal;
+pub mod order_manager;
+pub mod order_tracker;
+pub mod phase;
+pub mod positions;
+pub mod rate_limit;
+pub mod redeem;
+pub mod retry;
+#[warn(missing_docs)]
+pub mod runner;
+pub mod salt;
+pub mod scanner;
+pub mod sell_campaign;
+pub mod session_db;
+pub mod session_log;
+pub mod shadow;
+#[warn(missing_docs)]
+pub mod signing;
+pub mod sim_clob;
+pub mod sizing;
+pub mod slippage;
+pub mod spot;
+pub mod status;
+#[warn(missing_docs)]
+pub mod strategy;
+pub mod sweep;
+pub mod tick_profile;
+pub mod trade_tape;
+pub mod trades;
+pub mod types;
@@ -0,0 +1,380 @@
+//! Resolve BTC/SOL 5m market from Gamma API (slug -> token_id_up, token_id_down, close_time_unix).
+//! Also resolves a fixed target market by slug or condition ID (MM_TARGET_SLUG / MM_TARGET_CONDITION_ID)
+//! and the strike markets of an hourly "above $X" event.
+//! Requests (endpoint fallbacks, cache) are in gamma.rs; this module parses the responses.
+
+use crate::config::{interval_slug, interval_slug_at, next_interval_slug};
+use crate::gamma::{
+    event_markets, market_by_condition_id, market_by_slug, string_list, GAMMA_CACHE_TTL,
+};
+use crate::hourly::{hour_close_unix, hourly_event_slug, HOUR_SECS};
+use crate::market_store;
+use crate::types::{GammaMarket, IntervalMarketAsset, MarketTarget, ResolvedMarket, TargetWindow};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use reqwest::Client;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::warn;
+
+const FIVE_MIN_SECONDS: u64 = 300;
+
+/// Tick size when neither Gamma nor the book reports one.
+pub const DEFAULT_TICK_SIZE: Decimal = dec!(0.01);
+/// Min order size (shares) when neither Gamma nor the book reports one.
+pub const DEFAULT_MIN_ORDER_SIZE: Decimal = dec!(5);
+
+/// Resolve the market to trade at `now_unix`: the current (or, closing, the previous) 5-min
+/// interval, the fixed target market, or an open market of the (hourly) event.
+pub async fn resolve_market(
+    client: &Client,
+    base_url: &str,
+    target: &MarketTarget,
+    now_unix: u64,
+) -> Result<ResolvedMarket> {
+    match target {
+        MarketTarget::Interval(asset, window) => {
+            let slug = interval_slug(*asset, *window, now_unix);
+            market_for_slug(client, base_url, &slug, Some(FIVE_MIN_SECONDS)).await
+        }
+        MarketTarget::IntervalAt(asset, start) => {
+            let slug = interval_slug_at(*asset, *start);
+            market_for_slug(client, base_url, &slug, Some(FIVE_MIN_SECONDS)).await
+        }
+        MarketTarget::Slug(slug) => market_for_slug(client, base_url, slug, None).await,
+        MarketTarget::ConditionId(condition_id) => {
+            let m = market_by_condition_id(client, base_url, condition_id).await?;
+            let slug = m.slug.clone().unwrap_or_else(|| condition_id.clone());
+            parse_gamma_market(&m, &slug, None)
+        }
+        // The runner picks among event markets by book (event::scan_event); this is the first open one.
+        MarketTarget::Event(event_slug) => fetch_event_markets(client, base_url, event_slug)
+            .await?
+            .into_iter()
+            .find(|m| m.close_time_unix > now_unix)
+            .with_context(|| format!("Event \"{}\" has no open market", event_slug)),
+        // Likewise the runner picks the strike (event::scan_hourly_above).
+        MarketTarget::HourlyAbove(asset) => {
+            let event_slug = hourly_event_slug(*asset, hour_close_unix(now_unix));
+            fetch_strike_markets(client, base_url, &event_slug)
+                .await?
+                .into_iter()
+                .map(|(_, m)| m)
+                .find(|m| m.close_time_unix > now_unix)
+                .with_context(|| format!("Event \"{}\" has no open market", event_slug))
+        }
+    }
+}
+
+/// Fetch the market the interval switch after `now_unix` moves to into the cache so the switch
+/// resolves it without a Gamma round trip. Fails while Gamma has not listed it yet.
+pub async fn prefetch_next_interval(
+    client: &Client,
+    base_url: &str,
+    asset: IntervalMarketAsset,
+    window: TargetWindow,
+    now_unix: u64,
+) -> Result<()> {
+    let slug = next_interval_slug(asset, window, now_unix);
+    market_for_slug(client, base_url, &slug, Some(FIVE_MIN_SECONDS)).await?;
+    Ok(())
+}
+
+/// Market by slug: from the market store (MM_MARKET_CACHE_FILE) when already seen, else from
+/// Gamma.
+async fn market_for_slug(
+    client: &Client,
+    base_url: &str,
+    slug: &str,
+    interval_secs: Option<u64>,
+) -> Result<ResolvedMarket> {
+    if let Some(m) = market_store::lookup(slug) {
+        return Ok(m);
+    }
+    let m = market_by_slug(client, base_url, slug, GAMMA_CACHE_TTL).await?;
+    parse_gamma_market(&m, slug, interval_secs)
+}
+
+/// Fetch every market of a Gamma event (GET /events/slug/{slug}). Markets that cannot be parsed
+/// (no token IDs, no end date) are skipped.
+pub async fn fetch_event_markets(
+    client: &Client,
+    base_url: &str,
+    event_slug: &str,
+) -> Result<Vec<ResolvedMarket>> {
+    let markets = event_markets(client, base_url, event_slug).await?;
+    Ok(markets
+        .iter()
+        .filter_map(|m| {
+            let slug = m.slug.as_deref().unwrap_or(event_slug);
+            parse_gamma_market(m, slug, None).ok()
+        })
+        .collect())
+}
+
+/// Every market of an "above $X" event with its strike (from groupItemTitle, else the `$X` in
+/// the question). Markets without a readable strike are skipped.
+pub async fn fetch_strike_markets(
+    client: &Client,
+    base_url: &str,
+    event_slug: &str,
+) -> Result<Vec<(Decimal, ResolvedMarket)>> {
+    let markets = event_markets(client, base_url, event_slug).await?;
+    Ok(markets
+        .iter()
+        .filter_map(|m| {
+            let slug = m.slug.as_deref().unwrap_or(event_slug);
+            Some((
+                parse_strike(m)?,
+                parse_gamma_market(m, slug, Some(HOUR_SECS)).ok()?,
+            ))
+        })
+        .collect())
+}
+
+/// Strike of an "above $X" market: "4,200" / "$4,200" / ">4200" in groupItemTitle, or the first
+/// dollar amount in the question.
+fn parse_strike(m: &GammaMarket) -> Option<Decimal> {
+    static NUMBER: OnceLock<Regex> = OnceLock::new();
+    static DOLLARS: OnceLock<Regex> = OnceLock::new();
+    let number = NUMBER.get_or_init(|| Regex::new(r"([0-9][0-9,]*(?:\.[0-9]+)?)").unwrap());
+    let dollars = DOLLARS.get_or_init(|| Regex::new(r"\$\s*([0-9][0-9,]*(?:\.[0-9]+)?)").unwrap());
+    let found = m
+        .group_item_title
+        .as_deref()
+        .and_then(|t| number.captures(t))
+        .or_else(|| m.question.as_deref().and_then(|q| dollars.captures(q)))?;
+    Decimal::from_str(&found[1].replace(',', ""))
+        .ok()
+        .filter(|d| *d > Decimal::ZERO)
+}
+
+/// Settlement payout (Up, Down) of a market from Gamma outcomePrices, e.g. (1, 0). None while the
+/// market is not resolved yet (prices not exactly 0 / 1).
+pub async fn fetch_resolution(
+    client: &Client,
+    base_url: &str,
+    slug: &str,
+) -> Result<Option<(Decimal, Decimal)>> {
+    // Always revalidate: outcomePrices change when the market resolves.
+    let m = market_by_slug(client, base_url, slug, Duration::ZERO).await?;
+    let Some(outcome_prices) = m.outcome_prices.as_ref() else {
+        return Ok(None);
+    };
+    let prices: Vec<Decimal> = string_list(outcome_prices)
+        .iter()
+        .filter_map(|p| Decimal::from_str(p.trim()).ok())
+        .collect();
+    let settled = |p: &Decimal| *p == Decimal::ZERO || *p == Decimal::ONE;
+    Ok(match prices.as_slice() {
+        [up, down] if settled(up) && settled(down) && up + down == Decimal::ONE => {
+            Some((*up, *down))
+        }
+        _ => None,
+    })
+}
+
+/// `interval_secs`: Some(300) for 5-min markets (start = close - 300), Some(3600) for hourly ones;
+/// None uses Gamma startDate. Every market parsed is added to the market store.
+fn parse_gamma_market(
+    m: &GammaMarket,
+    slug: &str,
+    interval_secs: Option<u64>,
+) -> Result<ResolvedMarket> {
+    let condition_id = m
+        .condition_id
+        .as_deref()
+        .or(m.id.as_deref())
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    if condition_id.is_empty() {
+        anyhow::bail!("Market slug \"{}\" has no conditionId", slug);
+    }
+
+    let end_date_str = m
+        .end_date
+        .as_deref()
+        .filter(|s| s.contains('T'))
+        .or(m.end_date_iso.as_deref())
+        .or(m.end_date.as_deref())
+        .unwrap_or("");
+    let close_time_unix = parse_end_date_to_unix(end_date_str)?;
+    let interval_start_unix = match interval_secs {
+        Some(secs) => close_time_unix.saturating_sub(secs),
+        None => m
+            .start_date
+            .as_deref()
+            .and_then(|s| parse_end_date_to_unix(s).ok())
+            .unwrap_or(0),
+    };
+
+    let (token_id_up, token_id_down) = parse_token_ids(m)?;
+    let tick_size =
+        positive_decimal(m.order_price_min_tick_size.as_ref()).unwrap_or(DEFAULT_TICK_SIZE);
+    let min_order_size =
+        positive_decimal(m.order_min_size.as_ref()).unwrap_or(DEFAULT_MIN_ORDER_SIZE);
+
+    let market = ResolvedMarket {
+        slug: slug.to_string(),
+        condition_id,
+        close_time_unix,
+        interval_start_unix,
+        token_id_up,
+        token_id_down,
+        tick_size,
+        min_order_size,
+        neg_risk: m.neg_risk.unwrap_or(false),
+    };
+    market_store::record(&market);
+    Ok(market)
+}
+
+/// Gamma sends some numeric fields as numbers and others as strings.
+pub fn positive_decimal(v: Option<&serde_json::Value>) -> Option<Decimal> {
+    match v? {
+        serde_json::Value::String(s) => Decimal::from_str(s.trim()).ok(),
+        serde_json::Value::Number(n) => Decimal::from_str(&n.to_string()).ok(),
+        _ => None,
+    }
+    .filter(|d| *d > Decimal::ZERO)
+}
+
+fn parse_end_date_to_unix(s: &str) -> Result<u64> {
+    if s.is_empty() {
+        anyhow::bail!("Market has no endDate/endDateIso");
+    }
+    let s = s.trim();
+    if let Ok(t) = s.parse::<u64>() {
+        return Ok(t);
+    }
+    let dt = DateTime::parse_from_rfc3339(s)
+        .or_else(|_| DateTime::parse_from_rfc2822(s))
+        .context("Invalid endDate")?;
+    Ok(dt.with_timezone(&Utc).timestamp().max(0) as u64)
+}
+
+/// Outcome label traded as the Up side of a binary market whose outcomes are not Up/Down or
+/// Yes/No, e.g. a team or candidate name (MM_OUTCOME_UP, read on first use); None when unset.
+fn outcome_up_label() -> Option<&'static str> {
+    static LABEL: OnceLock<Option<String>> = OnceLock::new();
+    LABEL
+        .get_or_init(|| {
+            let label = std::env::var("MM_OUTCOME_UP").ok()?;
+            let label = label.trim();
+            (!label.is_empty()).then(|| label.to_string())
+        })
+        .as_deref()
+}
+
+/// Indices of the Up and Down tokens in clobTokenIds, from the outcomes labels ("Up"/"Down" or
+/// "Yes"/"No", any order). Two other labels take Up from `up_label` (MM_OUTCOME_UP), else keep
+/// Gamma's order with a warning. (0, 1) when Gamma sends no labels; an error when the labels are
+/// not two outcomes.
+fn up_down_indices(m: &GammaMarket, up_label: Option<&str>) -> Result<(usize, usize)> {
+    let Some(outcomes) = m.outcomes.as_ref().map(string_list) else {
+        return Ok((0, 1));
+    };
+    let labels: Vec<String> = outcomes.iter().map(|o| o.trim().to_lowercase()).collect();
+    let find = |names: &[&str]| labels.iter().position(|l| names.contains(&l.as_str()));
+    match (find(&["up", "yes"]), find(&["down", "no"])) {
+        (Some(up), Some(down)) if up < 2 && down < 2 && up != down => {
+            if up != 0 {
+                warn!(
+                    "[Market] outcomes {:?}: Up is not first, swapping clobTokenIds",
+                    outcomes
+                );
+            }
+            Ok((up, down))
+        }
+        _ if labels.len() == 2 => {
+            let configured = up_label.and_then(|l| find(&[l.trim().to_lowercase().as_str()]));
+            match configured {
+                Some(up) => Ok((up, 1 - up)),
+                None => {
+                    warn!(
+                        "[Market] outcomes {:?} are not Up/Down and MM_OUTCOME_UP ({:?}) names neither: trading {:?} as Up (Gamma order)",
+                        outcomes, up_label, outcomes[0]
+                    );
+                    Ok((0, 1))
+                }
+            }
+        }
+        _ => anyhow::bail!(
+            "Market outcomes {:?} are not two outcomes (clobTokenIds={:?}); refusing to guess sides",
+            outcomes,
+            m.clob_token_ids
+        ),
+    }
+}
+
+/// Up and Down token IDs of a binary market, with MM_OUTCOME_UP for labels other than Up/Down or
+/// Yes/No.
+pub(crate) fn parse_token_ids(m: &GammaMarket) -> Result<(String, String)> {
+    token_ids_with_up_label(m, outcome_up_label())
+}
+
+/// [parse_token_ids] with `up_label` in place of MM_OUTCOME_UP.
+pub fn token_ids_with_up_label(
+    m: &GammaMarket,
+    up_label: Option<&str>,
+) -> Result<(String, String)> {
+    let mut token_id_up = String::new();
+    let mut token_id_down = String::new();
+
+    // Prefer tokens with outcome labels (Up/Down, Yes/No) so we don't rely on array order.
+    if let Some(ref tokens) = m.tokens {
+        if tokens.len() >= 2 {
+            for t in tokens {
+                let id = t.token_id.as_deref().unwrap_or("").trim().to_string();
+                if id.is_empty() {
+                    continue;
+                }
+                let outcome = t.outcome.as_deref().unwrap_or("").to_lowercase();
+                if outcome == "up" || outcome == "yes" {
+                    token_id_up = id;
+                } else if outcome == "down" || outcome == "no" {
+                    token_id_down = id;
+                }
+            }
+            if token_id_up.is_empty() || token_id_down.is_empty() {
+                token_id_up.clear();
+                token_id_down.clear();
+            }
+        }
+    }
+
+    // Fallback: clob_token_ids, in the order of the outcomes labels (documented as [Yes, No] =
+    // [Up, Down], but checked: trading the wrong side must not happen silently).
+    if token_id_up.is_empty() || token_id_down.is_empty() {
+        if let Some(ref clob_ids) = m.clob_token_ids {
+            let trimmed = clob_ids.trim();
+            let parts: Vec<&str> = if trimmed.starts_with('[') {
+                serde_json::from_str(trimmed).unwrap_or_else(|_| vec![])
+            } else {
+                trimmed
+                    .split(',')
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            };
+            if parts.len() >= 2 {
+                let (up, down) = up_down_indices(m, up_label)?;
+                token_id_up = parts[up].to_string();
+                token_id_down = parts[down].to_string();
+            }
+        }
+    }
+
+    if token_id_up.is_empty() || token_id_down.is_empty() {
+        anyhow::bail!(
+            "Market could not resolve Up/Down token IDs (clobTokenIds={:?}, tokens with outcome?)",
+            m.clob_token_ids
+        );
+    }
+    Ok((token_id_up, token_id_down))
+}
@@ -0,0 +1,172 @@
+//! Market metadata cache on disk (MM_MARKET_CACHE_FILE): every market resolved from Gamma (slug,
+//! condition ID, token IDs, open and close time, tick and min size, neg-risk flag) is appended to a
+//! JSONL file. A restart resolves the markets it has already seen without Gamma, and offline tools
+//! (backtest, log analysis) can map token IDs back to slugs. What is stored does not change once a
+//! market is listed, except the tick size, which the book's `tick_size_change` overrides at run
+//! time anyway. Resolutions (outcomePrices) are never cached.
+
+use crate::types::ResolvedMarket;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use tracing::warn;
+
+/// One market as stored (a JSONL line).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredMarket {
+    /// Gamma slug.
+    pub slug: String,
+    /// CTF condition ID.
+    pub condition_id: String,
+    /// Up (Yes) token ID.
+    pub token_id_up: String,
+    /// Down (No) token ID.
+    pub token_id_down: String,
+    /// Interval start (Unix seconds; 0 if unknown).
+    pub interval_start_unix: u64,
+    /// Close time (Unix seconds).
+    pub close_time_unix: u64,
+    /// Tick size when resolved.
+    pub tick_size: Decimal,
+    /// Min order size (shares).
+    pub min_order_size: Decimal,
+    /// Neg-risk market (orders go to the neg-risk exchange).
+    pub neg_risk: bool,
+}
+
+impl From<&ResolvedMarket> for StoredMarket {
+    fn from(m: &ResolvedMarket) -> Self {
+        Self {
+            slug: m.slug.clone(),
+            condition_id: m.condition_id.clone(),
+            token_id_up: m.token_id_up.clone(),
+            token_id_down: m.token_id_down.clone(),
+            interval_start_unix: m.interval_start_unix,
+            close_time_unix: m.close_time_unix,
+            tick_size: m.tick_size,
+            min_order_size: m.min_order_size,
+            neg_risk: m.neg_risk,
+        }
+    }
+}
+
+impl From<&StoredMarket> for ResolvedMarket {
+    fn from(m: &StoredMarket) -> Self {
+        Self {
+            slug: m.slug.clone(),
+            condition_id: m.condition_id.clone(),
+            close_time_unix: m.close_time_unix,
+            interval_start_unix: m.interval_start_unix,
+            token_id_up: m.token_id_up.clone(),
+            token_id_down: m.token_id_down.clone(),
+            tick_size: m.tick_size,
+            min_order_size: m.min_order_size,
+            neg_risk: m.neg_risk,
+        }
+    }
+}
+
+/// Markets by slug, loaded from and appended to a JSONL file.
+#[derive(Default)]
+pub struct MarketStore {
+    markets: HashMap<String, StoredMarket>,
+    /// Token ID -> slug.
+    tokens: HashMap<String, String>,
+    file: Option<File>,
+}
+
+impl MarketStore {
+    /// Load the markets in `path` (later lines win; unreadable lines are skipped) and append new
+    /// ones to it. A file that cannot be opened leaves an in-memory store.
+    pub fn open(path: &Path) -> Self {
+        let mut store = Self::default();
+        if let Ok(file) = File::open(path) {
+            for line in BufReader::new(file).lines().map_while(|l| l.ok()) {
+                if let Ok(m) = serde_json::from_str::<StoredMarket>(&line) {
+                    store.index(m);
+                }
+            }
+        }
+        store.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| warn!("[MarketStore] cannot write {}: {}", path.display(), e))
+            .ok();
+        store
+    }
+
+    fn index(&mut self, m: StoredMarket) {
+        self.tokens.insert(m.token_id_up.clone(), m.slug.clone());
+        self.tokens.insert(m.token_id_down.clone(), m.slug.clone());
+        self.markets.insert(m.slug.clone(), m);
+    }
+
+    /// Stored market by slug.
+    pub fn get(&self, slug: &str) -> Option<&StoredMarket> {
+        self.markets.get(slug)
+    }
+
+    /// Slug of the market a token ID (Up or Down) belongs to.
+    pub fn slug_for_token(&self, token_id: &str) -> Option<&str> {
+        self.tokens.get(token_id).map(String::as_str)
+    }
+
+    /// Number of stored markets.
+    pub fn len(&self) -> usize {
+        self.markets.len()
+    }
+
+    /// No market stored.
+    pub fn is_empty(&self) -> bool {
+        self.markets.is_empty()
+    }
+
+    /// Store a market; written to the file only when new or changed.
+    pub fn insert(&mut self, m: StoredMarket) {
+        if self.markets.get(&m.slug) == Some(&m) {
+            return;
+        }
+        if let Some(file) = self.file.as_mut() {
+            let written = serde_json::to_string(&m)
+                .map_err(std::io::Error::other)
+                .and_then(|line| writeln!(file, "{}", line));
+            if let Err(e) = written {
+                warn!("[MarketStore] write failed: {}", e);
+            }
+        }
+        self.index(m);
+    }
+}
+
+/// The process-wide store (MM_MARKET_CACHE_FILE read on first use); None when unset.
+pub fn global() -> Option<&'static Mutex<MarketStore>> {
+    static STORE: OnceLock<Option<Mutex<MarketStore>>> = OnceLock::new();
+    STORE
+        .get_or_init(|| {
+            let path = std::env::var("MM_MARKET_CACHE_FILE").ok()?;
+            let path = path.trim();
+            (!path.is_empty()).then(|| Mutex::new(MarketStore::open(Path::new(path))))
+        })
+        .as_ref()
+}
+
+/// Market by slug from the global store.
+pub fn lookup(slug: &str) -> Option<ResolvedMarket> {
+    let store = global()?.lock().unwrap_or_else(PoisonError::into_inner);
+    store.get(slug).map(ResolvedMarket::from)
+}
+
+/// Add a market resolved from Gamma to the global store (no-op when off).
+pub fn record(m: &ResolvedMarket) {
+    if let Some(store) = global() {
+        store
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(StoredMarket::from(m));
+    }
+}
@@ -0,0 +1,295 @@
+//! Operator-facing log lines (start / stop, interval switches, fills, closes, resolution) in the
+//! language picked by MM_LOG_LANG: `en` (default) or `es`. Only the prose changes: the
+//! `[IntervalSniper]` prefix, the aligned `BUY` / `FAIL` / `HOLD` / `✓ TP` / `✓ SL` tags and the
+//! key=value fields are the same in every language, so alerting regexes can key on them.
+//! Diagnostics meant for debugging stay in English.
+
+use crate::types::LogLang;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+static LANG: OnceLock<LogLang> = OnceLock::new();
+
+/// Set the language once at startup (later calls keep the first one).
+pub fn set_lang(lang: LogLang) {
+    let _ = LANG.set(lang);
+}
+
+fn lang() -> LogLang {
+    LANG.get().copied().unwrap_or_default()
+}
+
+pub fn started(dry_run: bool, slug: &str, strategy: &str) -> String {
+    match lang() {
+        LogLang::En => format!(
+            "started dry_run={} slug={} strategy={}",
+            dry_run, slug, strategy
+        ),
+        LogLang::Es => format!(
+            "iniciado dry_run={} slug={} strategy={}",
+            dry_run, slug, strategy
+        ),
+    }
+}
+
+pub fn ctrl_c() -> String {
+    match lang() {
+        LogLang::En => "Ctrl-C: stopping after this tick".to_string(),
+        LogLang::Es => "Ctrl-C: deteniendo tras este tick".to_string(),
+    }
+}
+
+pub fn target_market_closed(slug: &str) -> String {
+    match lang() {
+        LogLang::En => format!("target market {} closed, stopping", slug),
+        LogLang::Es => format!("mercado objetivo {} cerrado, deteniendo", slug),
+    }
+}
+
+pub fn event_closed(slug: &str) -> String {
+    match lang() {
+        LogLang::En => format!("event {} closed, stopping", slug),
+        LogLang::Es => format!("evento {} cerrado, deteniendo", slug),
+    }
+}
+
+/// `up` / `down` are the (shortened) token IDs.
+pub fn interval_switch(
+    slug: &str,
+    up: &str,
+    down: &str,
+    fee_bps: impl Display,
+    tick: impl Display,
+    min_size: impl Display,
+) -> String {
+    match lang() {
+        LogLang::En => format!(
+            "interval switch -> {} (Up token={}... Down token={}...) fee={} bps tick={} min_size={}",
+            slug, up, down, fee_bps, tick, min_size
+        ),
+        LogLang::Es => format!(
+            "cambio de intervalo -> {} (Up token={}... Down token={}...) fee={} bps tick={} min_size={}",
+            slug, up, down, fee_bps, tick, min_size
+        ),
+    }
+}
+
+pub fn hold_to_resolution(
+    size: impl Display,
+    best_bid: impl Display,
+    above: impl Display,
+    secs_to_close: u64,
+) -> String {
+    match lang() {
+        LogLang::En => format!(
+            " HOLD  {} sh to resolution: best_bid={} >= {} with {}s to close, skipping TP",
+            size, best_bid, above, secs_to_close
+        ),
+        LogLang::Es => format!(
+            " HOLD  {} sh hasta la resolución: best_bid={} >= {} a {}s del cierre, sin TP",
+            size, best_bid, above, secs_to_close
+        ),
+    }
+}
+
+/// `passive`: filled by the resting TP ask rather than a sell at the bid.
+pub fn tp_filled(price: impl Display, passive: bool) -> String {
+    match (lang(), passive) {
+        (LogLang::En, false) => format!("✓ TP filled @ {} — position closed", price),
+        (LogLang::En, true) => format!("✓ TP filled @ {} (passive ask) — position closed", price),
+        (LogLang::Es, false) => format!("✓ TP ejecutado @ {} — posición cerrada", price),
+        (LogLang::Es, true) => {
+            format!("✓ TP ejecutado @ {} (ask pasivo) — posición cerrada", price)
+        }
+    }
+}
+
+pub fn sl_filled(price: impl Display) -> String {
+    match lang() {
+        LogLang::En => format!(
+            "✓ SL filled @ {} — position closed (re-entry allowed)",
+            price
+        ),
+        LogLang::Es => format!(
+            "✓ SL ejecutado @ {} — posición cerrada (se permite reentrar)",
+            price
+        ),
+    }
+}
+
+pub fn sl_moved_to_breakeven(bid: impl Display, from: impl Display, to: impl Display) -> String {
+    match lang() {
+        LogLang::En => format!("bid {} past break-even trigger: SL {} -> {}", bid, from, to),
+        LogLang::Es => format!(
+            "bid {} superó el disparador de break-even: SL {} -> {}",
+            bid, from, to
+        ),
+    }
+}
+
+pub fn soft_stop_filled(size: impl Display, price: impl Display, rest: impl Display) -> String {
+    match lang() {
+        LogLang::En => format!(
+            "✓ soft stop sold {} sh @ {} — {} sh still open (TP / SL armed)",
+            size, price, rest
+        ),
+        LogLang::Es => format!(
+            "✓ stop suave vendió {} sh @ {} — {} sh siguen abiertas (TP / SL activos)",
+            size, price, rest
+        ),
+    }
+}
+
+pub fn sl_hedge_bought(
+    side: impl std::fmt::Debug,
+    size: impl Display,
+    price: impl Display,
+    pair_cost: impl Display,
+) -> String {
+    match lang() {
+        LogLang::En => format!(
+            "✓ SL hedge: bought {:?} {} @ {} — pair cost {} locked, held to resolution",
+            side, size, price, pair_cost
+        ),
+        LogLang::Es => format!(
+            "✓ SL hedge: comprado {:?} {} @ {} — coste del par {} asegurado, hasta la resolución",
+            side, size, price, pair_cost
+        ),
+    }
+}
+
+pub fn sl_hedge_bid_posted(
+    side: impl std::fmt::Debug,
+    size: impl Display,
+    price: impl Display,
+) -> String {
+    match lang() {
+        LogLang::En => format!(
+            "SL hedge: book empty, bidding {:?} {} @ {} — held to resolution as it fills",
+            side, size, price
+        ),
+        LogLang::Es => format!(
+            "SL hedge: libro vacío, pujando {:?} {} @ {} — se mantiene hasta la resolución según se llene",
+            side, size, price
+        ),
+    }
+}
+
+/// `label` is the sell, e.g. "SL" or "TP retry".
+pub fn dust_closed(label: &str, remaining: impl Display, threshold: impl Display) -> String {
+    match lang() {
+        LogLang::En => format!(
+            "{} dust remaining ({}, below {}), considering position closed",
+            label, remaining, threshold
+        ),
+        LogLang::Es => format!(
+            "{} resto mínimo ({}, bajo {}), posición considerada cerrada",
+            label, remaining, threshold
+        ),
+    }
+}
+
+/// A retry found only dust available.
+pub fn sell_partly_filled(
+    label: &str,
+    size: impl Display,
+    price: impl Display,
+    rest: impl Display,
+) -> String {
+    match lang() {
+        LogLang::En => format!(
+            "{} sold {} sh @ {} but an account's part failed — {} sh still open (TP / SL armed)",
+            label, size, price, rest
+        ),
+        LogLang::Es => format!(
+            "{} vendió {} sh @ {} pero falló la parte de una cuenta — {} sh siguen abiertas (TP / SL activos)",
+            label, size, price, rest
+        ),
+    }
+}
+
+pub fn retry_dust_closed(label: &str, available: impl std::fmt::Debug) -> String {
+    match lang() {
+        LogLang::En => format!(
+            "{} retry dust remaining (available {:?}), considering position closed",
+            label, available
+        ),
+        LogLang::Es => format!(
+            "{} retry resto mínimo (disponible {:?}), posición considerada cerrada",
+            label, available
+        ),
+    }
+}
+
+pub fn amount_rejected(label: &str) -> String {
+    match lang() {
+        LogLang::En => format!(
+            "{}: exchange rejected amount (dust/zero), considering position closed",
+            label
+        ),
+        LogLang::Es => format!(
+            "{}: el exchange rechazó la cantidad (resto/cero), posición considerada cerrada",
+            label
+        ),
+    }
+}
+
+pub fn interval_changed_during_retry(label: &str, attempt: u32) -> String {
+    match lang() {
+        LogLang::En => format!(
+            "interval changed during {} retry (attempt {}), stopping retries and switching market",
+            label, attempt
+        ),
+        LogLang::Es => format!(
+            "el intervalo cambió durante el reintento de {} (intento {}), se dejan los reintentos y se cambia de mercado",
+            label, attempt
+        ),
+    }
+}
+
+/// `from_gamma`: payouts from Gamma outcomePrices, else inferred from the last best bid.
+pub fn resolved(slug: &str, up: impl Display, down: impl Display, from_gamma: bool) -> String {
+    match lang() {
+        LogLang::En => format!(
+            "RESOLVED {}: Up pays {} Down pays {} (from {}); redeem the winning shares",
+            slug,
+            up,
+            down,
+            if from_gamma {
+                "Gamma outcomePrices"
+            } else {
+                "last best bid (not yet resolved)"
+            }
+        ),
+        LogLang::Es => format!(
+            "RESOLVED {}: Up paga {} Down paga {} (según {}); hay que redimir las acciones ganadoras",
+            slug,
+            up,
+            down,
+            if from_gamma {
+                "Gamma outcomePrices"
+            } else {
+                "el último best bid (aún sin resolver)"
+            }
+        ),
+    }
+}
+
+/// `up` / `down`: payouts inferred from the last best bid, used if the run ends first.
+pub fn carried_over(
+    slug: &str,
+    size: impl Display,
+    up: impl Display,
+    down: impl Display,
+) -> String {
+    match lang() {
+        LogLang::En => format!(
+            "CARRY {} sh of {} past the close: not resolved yet, waiting for the resolution (inferred Up {} Down {})",
+            size, slug, up, down
+        ),
+        LogLang::Es => format!(
+            "CARRY {} sh de {} tras el cierre: aún sin resolver, se espera la resolución (estimado Up {} Down {})",
+            size, slug, up, down
+        ),
+    }
+}
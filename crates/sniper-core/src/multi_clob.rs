@@ -0,0 +1,547 @@
+//! Multi-account coordinator: one [ClobClient] in front of several live accounts (separate
+//! private key + API credentials). Buys go round-robin or are split across accounts; sells are
+//! routed to the accounts that hold the token. Per-account positions are tracked from fills: the
+//! fill an order reports when placed, and for a GTC / GTD order left resting, the trades-history
+//! fills that match it later. Every order is canceled through the account that placed it, and
+//! no part of a split order goes below the market's min order size.
+
+use crate::clob::{
+    CancelOrdersResult, ClobClient, ClobError, LimitOrderParams, OrderSide, OrderType,
+    PlaceOrderResult,
+};
+use crate::market::DEFAULT_MIN_ORDER_SIZE;
+use crate::trades::Fill;
+use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+/// Accounts are read from PRIVATE_KEY, PRIVATE_KEY_2, ... up to this index.
+pub const MAX_ACCOUNTS: usize = 9;
+
+/// How buy orders are spread across accounts (MM_ACCOUNT_MODE).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountMode {
+    /// Each buy goes to the next account in turn.
+    RoundRobin,
+    /// Each buy is split evenly across accounts (as many as the min order size allows).
+    Split,
+}
+
+impl AccountMode {
+    pub fn from_env() -> Self {
+        match std::env::var("MM_ACCOUNT_MODE")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "split" => AccountMode::Split,
+            _ => AccountMode::RoundRobin,
+        }
+    }
+}
+
+/// A GTC / GTD order of one account still resting on the book.
+struct RestingOrder {
+    order_id: String,
+    token_id: String,
+    side: OrderSide,
+    price: Decimal,
+    /// Shares not filled yet.
+    remaining: Decimal,
+}
+
+struct Account {
+    label: String,
+    client: Box<dyn ClobClient>,
+    /// Shares held per token_id, from this account's fills.
+    positions: Mutex<HashMap<String, Decimal>>,
+    /// Resting orders, oldest first; their fills come from the trades history.
+    resting: Mutex<Vec<RestingOrder>>,
+    /// Trades already counted (trade id and asset).
+    seen_trades: Mutex<HashSet<String>>,
+}
+
+impl Account {
+    fn position(&self, token_id: &str) -> Decimal {
+        self.positions
+            .lock()
+            .ok()
+            .and_then(|p| p.get(token_id).copied())
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    fn record_fill(&self, side: OrderSide, token_id: &str, size: Decimal) {
+        let Ok(mut p) = self.positions.lock() else {
+            return;
+        };
+        let held = p.entry(token_id.to_string()).or_insert(Decimal::ZERO);
+        *held = match side {
+            OrderSide::Buy => *held + size,
+            OrderSide::Sell => (*held - size).max(Decimal::ZERO),
+        };
+        info!(
+            "[MultiClob] account {} holds {} of token {}",
+            self.label,
+            held,
+            &token_id[..token_id.len().min(18)]
+        );
+    }
+
+    async fn place(&self, params: LimitOrderParams, order_type: OrderType) -> PlaceOrderResult {
+        let (side, size, price) = (params.side, params.size, params.price);
+        let token_id = params.token_id.clone();
+        let result = self
+            .client
+            .place_limit_order(params, order_type)
+            .await
+            .unwrap_or_else(|e| PlaceOrderResult::failed(e.to_string()));
+        if !result.success {
+            if let Some(ref msg) = result.error_msg {
+                warn!("[MultiClob] account {} order failed: {}", self.label, msg);
+            }
+            return result;
+        }
+        let filled = reported_fill(&result, size, order_type).unwrap_or(Decimal::ZERO);
+        if filled > Decimal::ZERO {
+            self.record_fill(side, &token_id, filled);
+        }
+        let rests = matches!(order_type, OrderType::Gtc | OrderType::Gtd) && filled < size;
+        if let Some(order_id) = result.order_id.clone().filter(|_| rests) {
+            if let Ok(mut r) = self.resting.lock() {
+                r.push(RestingOrder {
+                    order_id,
+                    token_id,
+                    side,
+                    price,
+                    remaining: size - filled,
+                });
+            }
+        }
+        result
+    }
+
+    /// Trades-history fills of this account: each new one fills the oldest resting order on its
+    /// token, side and price, and moves the position.
+    fn on_fills(&self, fills: &[Fill]) {
+        let (Ok(mut resting), Ok(mut seen)) = (self.resting.lock(), self.seen_trades.lock()) else {
+            return;
+        };
+        for f in fills {
+            if !seen.insert(format!("{}:{}", f.trade_id, f.asset_id)) {
+                continue;
+            }
+            let Some(i) = resting
+                .iter()
+                .position(|o| o.token_id == f.asset_id && o.side == f.side && o.price == f.price)
+            else {
+                continue;
+            };
+            let size = f.size.min(resting[i].remaining);
+            resting[i].remaining -= size;
+            if resting[i].remaining <= Decimal::ZERO {
+                resting.remove(i);
+            }
+            self.record_fill(f.side, &f.asset_id, size);
+        }
+    }
+
+    /// Forget resting orders the exchange canceled.
+    fn on_canceled(&self, canceled: &[String]) {
+        if let Ok(mut r) = self.resting.lock() {
+            r.retain(|o| !canceled.contains(&o.order_id));
+        }
+    }
+}
+
+pub struct MultiClob {
+    accounts: Vec<Account>,
+    mode: AccountMode,
+    next: AtomicUsize,
+    /// Account index of each order placed through this client, by order ID.
+    owners: Mutex<HashMap<String, usize>>,
+}
+
+impl MultiClob {
+    pub fn new(accounts: Vec<(String, Box<dyn ClobClient>)>, mode: AccountMode) -> Self {
+        Self {
+            accounts: accounts
+                .into_iter()
+                .map(|(label, client)| Account {
+                    label,
+                    client,
+                    positions: Mutex::new(HashMap::new()),
+                    resting: Mutex::new(Vec::new()),
+                    seen_trades: Mutex::new(HashSet::new()),
+                })
+                .collect(),
+            mode,
+            next: AtomicUsize::new(0),
+            owners: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Place `parts` (account index, size) concurrently and merge them into one result.
+    async fn place_parts(
+        &self,
+        params: &LimitOrderParams,
+        order_type: OrderType,
+        parts: Vec<(usize, Decimal)>,
+    ) -> PlaceOrderResult {
+        let sizes: Vec<Decimal> = parts.iter().map(|(_, size)| *size).collect();
+        let accounts: Vec<usize> = parts.iter().map(|(i, _)| *i).collect();
+        let futs = parts.into_iter().map(|(i, size)| {
+            let mut p = params.clone();
+            p.size = size;
+            self.accounts[i].place(p, order_type)
+        });
+        let results = futures_util::future::join_all(futs).await;
+        if let Ok(mut owners) = self.owners.lock() {
+            for (i, r) in accounts.iter().zip(&results) {
+                if let Some(id) = r.order_id.as_deref() {
+                    owners.insert(id.to_string(), *i);
+                }
+            }
+        }
+        merge_results(sizes.into_iter().zip(results).collect(), order_type)
+    }
+
+    /// Accounts to cancel `order_id` with: the one that placed it, or every account for an ID
+    /// this client did not place (e.g. an order from before a restart).
+    fn cancel_accounts(&self, order_id: &str) -> Vec<&Account> {
+        match self
+            .owners
+            .lock()
+            .ok()
+            .and_then(|o| o.get(order_id).copied())
+        {
+            Some(i) => vec![&self.accounts[i]],
+            None => self.accounts.iter().collect(),
+        }
+    }
+
+    /// Sell size per account: from each account's balance (or tracked position when the balance
+    /// is unavailable), largest holder first, as [split_sell] cuts it.
+    async fn sell_parts(
+        &self,
+        token_id: &str,
+        size: Decimal,
+        min_size: Decimal,
+    ) -> Vec<(usize, Decimal)> {
+        let mut held = Vec::with_capacity(self.accounts.len());
+        for (i, a) in self.accounts.iter().enumerate() {
+            let balance = a
+                .client
+                .get_available_balance(token_id)
+                .await
+                .ok()
+                .flatten();
+            held.push((i, balance.unwrap_or_else(|| a.position(token_id))));
+        }
+        held.sort_by_key(|h| std::cmp::Reverse(h.1));
+        split_sell(&held, size, min_size)
+    }
+}
+
+/// Parts of a sell of `size` over (account, shares held), largest holder first. Every part is
+/// at least `min_size`: a holder with less is skipped, and a part that would leave less than
+/// that for a later holder able to sell it is cut back. When no part can reach `min_size` (a
+/// small sell, or only small holders), the largest holder sells what it can alone. Shares only
+/// held below `min_size` can't be folded into another account's part: the parts then add up to
+/// less than `size`, and the caller reports the shortfall.
+fn split_sell(
+    held: &[(usize, Decimal)],
+    size: Decimal,
+    min_size: Decimal,
+) -> Vec<(usize, Decimal)> {
+    let mut remaining = size;
+    let mut parts = Vec::new();
+    for (k, &(i, h)) in held.iter().enumerate() {
+        if remaining <= Decimal::ZERO || h <= Decimal::ZERO {
+            break;
+        }
+        let mut take = h.min(remaining);
+        if take < min_size {
+            continue;
+        }
+        let rest = remaining - take;
+        let later_sells_min = held[k + 1..].iter().any(|(_, h)| *h >= min_size);
+        if rest > Decimal::ZERO && rest < min_size && later_sells_min {
+            take -= min_size - rest;
+            if take < min_size {
+                continue;
+            }
+        }
+        parts.push((i, take));
+        remaining -= take;
+    }
+    if parts.is_empty() {
+        return held
+            .first()
+            .filter(|(_, h)| *h > Decimal::ZERO)
+            .map(|&(i, h)| vec![(i, h.min(size))])
+            .unwrap_or_default();
+    }
+    parts
+}
+
+/// Shares a successful order of `size` filled: what the exchange reports, and all of it for a
+/// FOK that went through without reporting a size. None when a non-FOK order reports nothing.
+fn reported_fill(
+    result: &PlaceOrderResult,
+    size: Decimal,
+    order_type: OrderType,
+) -> Option<Decimal> {
+    match (result.filled_size, order_type) {
+        (Some(f), _) => Some(f.min(size)),
+        (None, OrderType::Fok) => Some(size),
+        (None, _) => None,
+    }
+}
+
+/// Split `size` into up to `n` equal parts (2 decimals) of at least `min_size`; the last part
+/// takes the rounding remainder.
+fn split_size(size: Decimal, n: usize, min_size: Decimal) -> Vec<Decimal> {
+    let max_parts = if min_size > Decimal::ZERO {
+        (size / min_size).floor().to_usize().unwrap_or(1)
+    } else {
+        n
+    };
+    let n = n.min(max_parts).max(1);
+    let part = (size / Decimal::from(n as u64))
+        .round_dp_with_strategy(2, rust_decimal::RoundingStrategy::ToZero);
+    let mut parts = vec![part; n - 1];
+    parts.push(size - part * Decimal::from((n - 1) as u64));
+    parts
+}
+
+/// One result for an order spread over accounts, from each part's size and result: success if
+/// any part succeeded, filled size summed over successful parts as [reported_fill] counts them.
+/// Parts that failed next to successful ones are listed in `failed_parts` (the error fields stay
+/// empty); when every part failed, the first failure is the error.
+fn merge_results(
+    parts: Vec<(Decimal, PlaceOrderResult)>,
+    order_type: OrderType,
+) -> PlaceOrderResult {
+    let filled_parts: Vec<(&PlaceOrderResult, Decimal)> = parts
+        .iter()
+        .filter(|(_, r)| r.success)
+        .filter_map(|(size, r)| Some((r, reported_fill(r, *size, order_type)?)))
+        .collect();
+    let results: Vec<&PlaceOrderResult> = parts.iter().map(|(_, r)| r).collect();
+    let success = results.iter().any(|r| r.success);
+    let failed: Vec<&PlaceOrderResult> = results.iter().copied().filter(|r| !r.success).collect();
+    let filled: Vec<Decimal> = filled_parts.iter().map(|(_, f)| *f).collect();
+    // Size-weighted over the parts that report a fill price.
+    let priced: Vec<(Decimal, Decimal)> = filled_parts
+        .iter()
+        .filter_map(|(r, f)| Some((r.avg_price?, *f)))
+        .collect();
+    let priced_size: Decimal = priced.iter().map(|(_, s)| *s).sum();
+    let order_ids: Vec<&str> = results
+        .iter()
+        .filter_map(|r| r.order_id.as_deref())
+        .collect();
+    PlaceOrderResult {
+        order_id: (!order_ids.is_empty()).then(|| order_ids.join(",")),
+        success,
+        error_msg: failed
+            .first()
+            .and_then(|r| r.error_msg.clone())
+            .filter(|_| !success),
+        error: failed
+            .first()
+            .and_then(|r| r.error.clone())
+            .filter(|_| !success),
+        filled_size: (!filled.is_empty()).then(|| filled.iter().copied().sum()),
+        http_status: results
+            .iter()
+            .find(|r| r.success == success)
+            .and_then(|r| r.http_status),
+        avg_price: (!priced_size.is_zero())
+            .then(|| priced.iter().map(|(p, s)| p * s).sum::<Decimal>() / priced_size),
+        failed_parts: if success {
+            failed
+                .iter()
+                .map(|r| {
+                    r.error.clone().unwrap_or_else(|| {
+                        ClobError::Other(r.error_msg.clone().unwrap_or_default())
+                    })
+                })
+                .collect()
+        } else {
+            Vec::new()
+        },
+    }
+}
+
+#[async_trait::async_trait]
+impl ClobClient for MultiClob {
+    async fn place_limit_order(
+        &self,
+        params: LimitOrderParams,
+        order_type: OrderType,
+    ) -> Result<PlaceOrderResult> {
+        let parts = match (params.side, self.mode) {
+            (OrderSide::Buy, AccountMode::RoundRobin) => {
+                let i = self.next.fetch_add(1, Ordering::Relaxed) % self.accounts.len();
+                vec![(i, params.size)]
+            }
+            (OrderSide::Buy, AccountMode::Split) => split_size(
+                params.size,
+                self.accounts.len(),
+                params.min_order_size.unwrap_or(DEFAULT_MIN_ORDER_SIZE),
+            )
+            .into_iter()
+            .enumerate()
+            .collect(),
+            (OrderSide::Sell, _) => {
+                self.sell_parts(
+                    &params.token_id,
+                    params.size,
+                    params.min_order_size.unwrap_or(DEFAULT_MIN_ORDER_SIZE),
+                )
+                .await
+            }
+        };
+        if parts.is_empty() {
+            return Ok(PlaceOrderResult::failed(
+                "no account holds this token".to_string(),
+            ));
+        }
+        let unsold = params.size - parts.iter().map(|(_, size)| *size).sum::<Decimal>();
+        let mut result = self.place_parts(&params, order_type, parts).await;
+        if result.success && unsold > Decimal::ZERO {
+            // Held below the min order size by the other accounts (or not held at all): report
+            // it like a failed part so the caller keeps the rest of its exit armed.
+            warn!(
+                "{} sh of the {} sh sell can't be placed: no account holds them at the min order size",
+                unsold, params.size
+            );
+            result.failed_parts.push(ClobError::Other(format!(
+                "{} sh not sellable at the min order size",
+                unsold
+            )));
+        }
+        Ok(result)
+    }
+
+    async fn cancel_orders_for_token(&self, token_id: &str) -> Result<CancelOrdersResult> {
+        let mut merged = CancelOrdersResult::default();
+        for a in &self.accounts {
+            match a.client.cancel_orders_for_token(token_id).await {
+                Ok(r) => {
+                    a.on_canceled(&r.canceled);
+                    merged.canceled.extend(r.canceled);
+                    merged.not_canceled.extend(r.not_canceled);
+                }
+                Err(e) => warn!("[MultiClob] account {} cancel failed: {}", a.label, e),
+            }
+        }
+        Ok(merged)
+    }
+
+    /// `order_id` may list several IDs (one per account, from a split order); each is canceled
+    /// by the account that placed it.
+    async fn cancel_order(&self, order_id: &str) -> Result<CancelOrdersResult> {
+        let mut merged = CancelOrdersResult::default();
+        for id in order_id.split(',') {
+            for a in self.cancel_accounts(id) {
+                match a.client.cancel_order(id).await {
+                    Ok(r) => {
+                        a.on_canceled(&r.canceled);
+                        merged.canceled.extend(r.canceled);
+                    }
+                    Err(e) => warn!("[MultiClob] account {} cancel failed: {}", a.label, e),
+                }
+            }
+            if merged.canceled.iter().any(|c| c == id) {
+                if let Ok(mut owners) = self.owners.lock() {
+                    owners.remove(id);
+                }
+            } else {
+                merged
+                    .not_canceled
+                    .insert(id.to_string(), "not canceled by any account".to_string());
+            }
+        }
+        Ok(merged)
+    }
+
+    async fn get_balance_allowance(&self, token_id: &str) -> Result<String> {
+        let mut out = Vec::with_capacity(self.accounts.len());
+        for a in &self.accounts {
+            let text = a
+                .client
+                .get_balance_allowance(token_id)
+                .await
+                .unwrap_or_else(|e| e.to_string());
+            out.push(format!("account {}: {}", a.label, text));
+        }
+        Ok(out.join(" | "))
+    }
+
+    /// Sum over accounts; None only if no account reported a balance.
+    async fn get_available_balance(&self, token_id: &str) -> Result<Option<Decimal>> {
+        let mut total: Option<Decimal> = None;
+        for a in &self.accounts {
+            if let Some(b) = a
+                .client
+                .get_available_balance(token_id)
+                .await
+                .ok()
+                .flatten()
+            {
+                total = Some(total.unwrap_or(Decimal::ZERO) + b);
+            }
+        }
+        Ok(total)
+    }
+
+    async fn warm_up(&self) {
+        futures_util::future::join_all(self.accounts.iter().map(|a| a.client.warm_up())).await;
+    }
+
+    /// Usable per buy: the smallest account balance in round-robin (any account may get the next
+    /// buy), the sum in split mode. None if any account's balance is unknown.
+    async fn get_collateral_balance(&self) -> Result<Option<Decimal>> {
+        let mut balances = Vec::with_capacity(self.accounts.len());
+        for a in &self.accounts {
+            match a.client.get_collateral_balance().await {
+                Ok(Some(b)) => balances.push(b),
+                Ok(None) => return Ok(None),
+                Err(e) => {
+                    warn!(
+                        "[MultiClob] account {} collateral balance failed: {}",
+                        a.label, e
+                    );
+                    return Ok(None);
+                }
+            }
+        }
+        Ok(match self.mode {
+            AccountMode::RoundRobin => balances.into_iter().min(),
+            AccountMode::Split => Some(balances.into_iter().sum()),
+        })
+    }
+
+    fn funders(&self) -> Vec<String> {
+        self.accounts
+            .iter()
+            .flat_map(|a| a.client.funders())
+            .collect()
+    }
+
+    async fn get_trades(&self, after_unix: u64) -> Result<Vec<Fill>> {
+        let mut fills = Vec::new();
+        for a in &self.accounts {
+            let account_fills = a.client.get_trades(after_unix).await?;
+            a.on_fills(&account_fills);
+            fills.extend(account_fills);
+        }
+        Ok(fills)
+    }
+}
@@ -0,0 +1,170 @@
+//! Journal of submitted orders keyed by client order ID: the EIP-712 order hash, computed before
+//! the POST and used by the CLOB as the order ID. When a POST times out or gets a 5xx, the live
+//! client looks the ID up (GET /data/order/{id}) before deciding whether to resend, so an
+//! ambiguous response does not turn into a double buy. With MM_ORDER_JOURNAL the journal is also
+//! a JSONL file; intents a previous run left unresolved are looked up at startup.
+
+use crate::clob::{LimitOrderParams, OrderSide};
+use anyhow::Result;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::sync::Mutex;
+use tracing::warn;
+
+/// One submitted order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub order_id: String,
+    pub token_id: String,
+    pub side: String,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub order_type: String,
+    pub submitted_ms: u64,
+}
+
+impl JournalEntry {
+    /// Entry for an order submitted at `submitted_ms` (the client's clock).
+    pub fn new(
+        order_id: &str,
+        params: &LimitOrderParams,
+        order_type: &str,
+        submitted_ms: u64,
+    ) -> Self {
+        Self {
+            order_id: order_id.to_string(),
+            token_id: params.token_id.clone(),
+            side: match params.side {
+                OrderSide::Buy => "BUY",
+                OrderSide::Sell => "SELL",
+            }
+            .to_string(),
+            price: params.price,
+            size: params.size,
+            order_type: order_type.to_string(),
+            submitted_ms,
+        }
+    }
+}
+
+/// How a journaled intent ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalOutcome {
+    /// The exchange has the order (live, matched or canceled after a partial fill).
+    Accepted,
+    /// The exchange answered and refused it.
+    Rejected,
+    /// The POST failed and the lookup shows the order never reached the exchange.
+    NotPlaced,
+}
+
+impl JournalOutcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            JournalOutcome::Accepted => "accepted",
+            JournalOutcome::Rejected => "rejected",
+            JournalOutcome::NotPlaced => "not_placed",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum JournalLine {
+    Submitted(JournalEntry),
+    Resolved { order_id: String, outcome: String },
+}
+
+/// Submitted intents without an outcome yet, plus the optional file.
+#[derive(Default)]
+pub struct OrderJournal {
+    pending: Mutex<HashMap<String, JournalEntry>>,
+    file: Option<Mutex<File>>,
+}
+
+impl OrderJournal {
+    /// Journal appending to `path` (None = in memory only). Intents in the file without an
+    /// outcome are pending again.
+    pub fn open(path: Option<&str>) -> Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+        let mut pending = HashMap::new();
+        if let Ok(f) = File::open(path) {
+            for line in BufReader::new(f).lines() {
+                match serde_json::from_str::<JournalLine>(&line?) {
+                    Ok(JournalLine::Submitted(e)) => {
+                        pending.insert(e.order_id.clone(), e);
+                    }
+                    Ok(JournalLine::Resolved { order_id, .. }) => {
+                        pending.remove(&order_id);
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+        if !pending.is_empty() {
+            warn!(
+                "[OrderJournal] {} order(s) in {} without an outcome from a previous run",
+                pending.len(),
+                path
+            );
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            pending: Mutex::new(pending),
+            file: Some(Mutex::new(file)),
+        })
+    }
+
+    pub fn submitted(&self, entry: JournalEntry) {
+        self.write(&JournalLine::Submitted(entry.clone()));
+        if let Ok(mut p) = self.pending.lock() {
+            p.insert(entry.order_id.clone(), entry);
+        }
+    }
+
+    pub fn resolved(&self, order_id: &str, outcome: JournalOutcome) {
+        let was_pending = self
+            .pending
+            .lock()
+            .map(|mut p| p.remove(order_id).is_some())
+            .unwrap_or(false);
+        if was_pending {
+            self.write(&JournalLine::Resolved {
+                order_id: order_id.to_string(),
+                outcome: outcome.as_str().to_string(),
+            });
+        }
+    }
+
+    /// Pending intents submitted at least `min_age_ms` before `now_ms`.
+    pub fn pending_older_than(&self, min_age_ms: u64, now_ms: u64) -> Vec<JournalEntry> {
+        self.pending
+            .lock()
+            .map(|p| {
+                p.values()
+                    .filter(|e| now_ms.saturating_sub(e.submitted_ms) >= min_age_ms)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn write(&self, line: &JournalLine) {
+        let Some(ref file) = self.file else {
+            return;
+        };
+        let Ok(text) = serde_json::to_string(line) else {
+            return;
+        };
+        if let Ok(mut f) = file.lock() {
+            if let Err(e) = writeln!(f, "{}", text).and_then(|_| f.flush()) {
+                warn!("[OrderJournal] write failed: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! Order manager: entry orders are submitted from a background task so the main loop keeps
+//! reading the book (and evaluating SL) during the HTTP round trip. Intents go in over a bounded
+//! channel, results come back on another; the runner polls them each tick. A GTC order still
+//! resting at its intent's max age (MM_BUY_ORDER_MAX_AGE_MS for buys, MM_TP_PASSIVE_MS for the
+//! passive TP ask) is canceled from the same task and reported as an expiry. A pair of intents
+//! (dual-side entry) goes out as one batch and comes back as one [BatchOutcome]; when the batch
+//! request fails without an answer, its legs (or those whose outcome could not be looked up) are
+//! reconciled from the tokens' balances. Delays,
+//! max ages and elapsed times run on the engine's [Clock].
+
+use crate::clob::{
+    ClobClient, ClobError, LimitOrderParams, OrderSide, OrderType, PlaceOrderResult,
+};
+use crate::clock::Clock;
+use crate::types::EntrySide;
+use anyhow::Result;
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Max queued intents / unread results.
+const CHANNEL_CAPACITY: usize = 16;
+/// After a failed batch, wait this long for fills to reach the balances before reading them.
+const BATCH_RECONCILE_DELAY_MS: u64 = 1500;
+
+/// One entry order to submit.
+#[derive(Debug, Clone)]
+pub struct OrderIntent {
+    pub params: LimitOrderParams,
+    pub order_type: OrderType,
+    /// Wait this long before submitting (entry jitter).
+    pub delay_ms: u64,
+    /// Market the order was placed on; a result for an earlier market arrives after the switch.
+    pub slug: String,
+    pub entry_side: EntrySide,
+    /// Cancel a GTC order still resting this long after submission (0 = never).
+    pub max_age_ms: u64,
+}
+
+/// Result of an intent, with the intent it belongs to.
+#[derive(Debug)]
+pub struct OrderOutcome {
+    pub id: u64,
+    pub intent: OrderIntent,
+    pub result: PlaceOrderResult,
+    /// Time from submission to the exchange response (ms), including the jitter delay.
+    pub elapsed_ms: u64,
+}
+
+/// Result of a batch of intents (one per leg, in order). `results` is the batch error when the
+/// request itself failed.
+#[derive(Debug)]
+pub struct BatchOutcome {
+    pub id: u64,
+    pub intents: Vec<OrderIntent>,
+    pub results: Result<Vec<PlaceOrderResult>, BatchError>,
+    /// Time from submission to the exchange response (ms), including the jitter delay.
+    pub elapsed_ms: u64,
+}
+
+/// A batch request that failed (could not be built or sent), or whose legs could not all be
+/// looked up: those legs may or may not have reached the book.
+#[derive(Debug)]
+pub struct BatchError {
+    pub error: String,
+    /// Each leg's token balance, read after canceling the token's open orders; None = unknown,
+    /// or not read for a leg in `resolved`.
+    pub balances: Vec<Option<Decimal>>,
+    /// Each leg's result when the exchange's answer for it is known; empty when the request
+    /// itself failed.
+    pub resolved: Vec<Option<PlaceOrderResult>>,
+}
+
+/// What the submission task places: one intent, or a batch of them in one request.
+enum Job {
+    Single(u64, OrderIntent),
+    Batch(u64, Vec<OrderIntent>),
+}
+
+/// A resting GTC order canceled at its max age.
+#[derive(Debug)]
+pub struct OrderExpiry {
+    pub intent: OrderIntent,
+    pub order_id: String,
+    /// The cancel took the order off the book (false: it had filled or was gone already).
+    pub canceled: bool,
+}
+
+pub struct OrderManager {
+    intents: mpsc::Sender<Job>,
+    outcomes: mpsc::Receiver<OrderOutcome>,
+    batches: mpsc::Receiver<BatchOutcome>,
+    expiries: mpsc::Receiver<OrderExpiry>,
+    /// Ids of submitted intents without a result yet.
+    in_flight: HashSet<u64>,
+    next_id: u64,
+}
+
+impl OrderManager {
+    /// Start the submission task for `clob`. Each intent is placed in its own task, so several
+    /// can be in flight at once. GTC orders that rest are canceled at the intent's max age on
+    /// `clock`.
+    pub fn spawn(clob: Arc<Box<dyn ClobClient>>, clock: Arc<dyn Clock>) -> Self {
+        let (intent_tx, mut intent_rx) = mpsc::channel::<Job>(CHANNEL_CAPACITY);
+        let (outcome_tx, outcome_rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let (batch_tx, batch_rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let (expiry_tx, expiry_rx) = mpsc::channel(CHANNEL_CAPACITY);
+        tokio::spawn(async move {
+            while let Some(job) = intent_rx.recv().await {
+                let clob = clob.clone();
+                let clock = clock.clone();
+                match job {
+                    Job::Single(id, intent) => {
+                        let outcome_tx = outcome_tx.clone();
+                        let expiry_tx = expiry_tx.clone();
+                        tokio::spawn(place_single(clob, clock, id, intent, outcome_tx, expiry_tx));
+                    }
+                    Job::Batch(id, intents) => {
+                        tokio::spawn(place_batch(clob, clock, id, intents, batch_tx.clone()));
+                    }
+                }
+            }
+        });
+        Self {
+            intents: intent_tx,
+            outcomes: outcome_rx,
+            batches: batch_rx,
+            expiries: expiry_rx,
+            in_flight: HashSet::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Queue an intent; fails if the queue is full or the submission task stopped.
+    pub fn submit(&mut self, intent: OrderIntent) -> Result<u64> {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.intents
+            .try_send(Job::Single(id, intent))
+            .map_err(|e| anyhow::anyhow!("order queue: {}", e))?;
+        self.in_flight.insert(id);
+        Ok(id)
+    }
+
+    /// Queue intents placed together in one batch request, after the first intent's delay.
+    pub fn submit_batch(&mut self, intents: Vec<OrderIntent>) -> Result<u64> {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.intents
+            .try_send(Job::Batch(id, intents))
+            .map_err(|e| anyhow::anyhow!("order queue: {}", e))?;
+        self.in_flight.insert(id);
+        Ok(id)
+    }
+
+    /// Results that arrived since the last call.
+    pub fn poll(&mut self) -> Vec<OrderOutcome> {
+        let mut out = Vec::new();
+        while let Ok(outcome) = self.outcomes.try_recv() {
+            self.in_flight.remove(&outcome.id);
+            out.push(outcome);
+        }
+        out
+    }
+
+    /// Batch results that arrived since the last call.
+    pub fn poll_batches(&mut self) -> Vec<BatchOutcome> {
+        let mut out = Vec::new();
+        while let Ok(outcome) = self.batches.try_recv() {
+            self.in_flight.remove(&outcome.id);
+            out.push(outcome);
+        }
+        out
+    }
+
+    /// Resting orders canceled at their max age since the last call.
+    pub fn poll_expired(&mut self) -> Vec<OrderExpiry> {
+        let mut out = Vec::new();
+        while let Ok(expiry) = self.expiries.try_recv() {
+            out.push(expiry);
+        }
+        out
+    }
+
+    /// True while any submitted order has no result yet.
+    pub fn has_in_flight(&self) -> bool {
+        !self.in_flight.is_empty()
+    }
+}
+
+/// Place one intent; report its result and, for a GTC order still resting at its max age, the
+/// cancel.
+async fn place_single(
+    clob: Arc<Box<dyn ClobClient>>,
+    clock: Arc<dyn Clock>,
+    id: u64,
+    intent: OrderIntent,
+    outcome_tx: mpsc::Sender<OrderOutcome>,
+    expiry_tx: mpsc::Sender<OrderExpiry>,
+) {
+    let started = clock.now_ms();
+    if intent.delay_ms > 0 {
+        clock.sleep(Duration::from_millis(intent.delay_ms)).await;
+    }
+    let result = clob
+        .place_limit_order(intent.params.clone(), intent.order_type)
+        .await
+        .unwrap_or_else(|e| PlaceOrderResult::failed(e.to_string()));
+    let max_age_ms = intent.max_age_ms;
+    let resting_id = resting_order(&intent, &result).filter(|_| max_age_ms > 0);
+    let outcome = OrderOutcome {
+        id,
+        intent: intent.clone(),
+        result,
+        elapsed_ms: clock.now_ms().saturating_sub(started),
+    };
+    if outcome_tx.send(outcome).await.is_err() {
+        warn!(
+            "[OrderManager] result for order {} dropped (runner gone)",
+            id
+        );
+    }
+    if let Some(order_id) = resting_id {
+        let age_ms = clock.now_ms().saturating_sub(started);
+        clock
+            .sleep(Duration::from_millis(max_age_ms.saturating_sub(age_ms)))
+            .await;
+        let canceled = match clob.cancel_order(&order_id).await {
+            Ok(r) => !r.canceled.is_empty(),
+            Err(e) => {
+                warn!(
+                    "[OrderManager] cancel of expired order {} failed: {:#}",
+                    order_id, e
+                );
+                false
+            }
+        };
+        let expiry = OrderExpiry {
+            intent,
+            order_id,
+            canceled,
+        };
+        if expiry_tx.send(expiry).await.is_err() {
+            warn!(
+                "[OrderManager] expiry of order {} dropped (runner gone)",
+                id
+            );
+        }
+    }
+}
+
+/// Place a batch of intents in one request and report the results together.
+async fn place_batch(
+    clob: Arc<Box<dyn ClobClient>>,
+    clock: Arc<dyn Clock>,
+    id: u64,
+    intents: Vec<OrderIntent>,
+    batch_tx: mpsc::Sender<BatchOutcome>,
+) {
+    let started = clock.now_ms();
+    let delay_ms = intents.first().map_or(0, |i| i.delay_ms);
+    if delay_ms > 0 {
+        clock.sleep(Duration::from_millis(delay_ms)).await;
+    }
+    let orders = intents
+        .iter()
+        .map(|i| (i.params.clone(), i.order_type))
+        .collect();
+    let results = match clob.place_orders_batch(orders).await {
+        Ok(results) if !results.iter().any(|r| r.is_error(&ClobError::Unresolved)) => Ok(results),
+        Ok(results) => {
+            let error = results
+                .iter()
+                .find(|r| r.is_error(&ClobError::Unresolved))
+                .and_then(|r| r.error_msg.clone())
+                .unwrap_or_default();
+            warn!(
+                "[OrderManager] batch {}: {}; reconciling that leg",
+                id, error
+            );
+            let resolved: Vec<Option<PlaceOrderResult>> = results
+                .into_iter()
+                .map(|r| (!r.is_error(&ClobError::Unresolved)).then_some(r))
+                .collect();
+            let balances = reconcile_legs(&clob, &clock, id, &intents, |i| {
+                resolved.get(i).is_none_or(|r| r.is_none())
+            })
+            .await;
+            Err(BatchError {
+                error,
+                balances,
+                resolved,
+            })
+        }
+        Err(e) => {
+            warn!(
+                "[OrderManager] batch {} failed ({:#}); reconciling its legs",
+                id, e
+            );
+            Err(BatchError {
+                error: format!("{:#}", e),
+                balances: reconcile_legs(&clob, &clock, id, &intents, |_| true).await,
+                resolved: Vec::new(),
+            })
+        }
+    };
+    let outcome = BatchOutcome {
+        id,
+        intents,
+        results,
+        elapsed_ms: clock.now_ms().saturating_sub(started),
+    };
+    if batch_tx.send(outcome).await.is_err() {
+        warn!(
+            "[OrderManager] result for batch {} dropped (runner gone)",
+            id
+        );
+    }
+}
+
+/// Token balance of each leg of batch `id` that `unknown` selects, read after canceling the
+/// token's open orders and letting fills settle; None for the other legs.
+async fn reconcile_legs(
+    clob: &Arc<Box<dyn ClobClient>>,
+    clock: &Arc<dyn Clock>,
+    id: u64,
+    intents: &[OrderIntent],
+    unknown: impl Fn(usize) -> bool,
+) -> Vec<Option<Decimal>> {
+    clock
+        .sleep(Duration::from_millis(BATCH_RECONCILE_DELAY_MS))
+        .await;
+    let mut balances = Vec::with_capacity(intents.len());
+    for (i, intent) in intents.iter().enumerate() {
+        if !unknown(i) {
+            balances.push(None);
+            continue;
+        }
+        let token_id = &intent.params.token_id;
+        if let Err(e) = clob.cancel_orders_for_token(token_id).await {
+            warn!(
+                "[OrderManager] batch {}: cancel on {} failed: {:#}",
+                id, token_id, e
+            );
+        }
+        balances.push(clob.get_available_balance(token_id).await.ok().flatten());
+    }
+    balances
+}
+
+/// Exchange ID of a GTC (or GTD) order that was accepted without filling completely (part of it rests).
+pub fn resting_order(intent: &OrderIntent, result: &PlaceOrderResult) -> Option<String> {
+    let gtc = matches!(intent.order_type, OrderType::Gtc | OrderType::Gtd);
+    let unfilled = result.filled_size.is_none_or(|f| f < intent.params.size);
+    if gtc && result.success && unfilled {
+        result.order_id.clone()
+    } else {
+        None
+    }
+}
+
+/// [resting_order] for buys only.
+pub fn resting_buy(intent: &OrderIntent, result: &PlaceOrderResult) -> Option<String> {
+    resting_order(intent, result).filter(|_| intent.params.side == OrderSide::Buy)
+}
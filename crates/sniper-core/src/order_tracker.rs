@@ -0,0 +1,573 @@
+//! Order lifecycle: every order placed through the CLOB client goes created → submitted → live /
+//! partially_filled → filled, cancelled or rejected, with the time of each step. [TrackingClob]
+//! wraps the client so the runner's entries, TP/SL sells and hedges are all recorded from the
+//! REST results and cancel responses (the market WS has no per-order updates). With
+//! MM_ORDER_AUDIT each transition is appended to `orders_<time>.jsonl` in MM_SESSION_LOG_DIR, and
+//! with MM_SESSION_DB it updates the order's row in the session database. Fills are priced against
+//! the book the runner last handed over ([OrderTracker::set_book]) for the slippage report.
+
+use crate::clob::{
+    CancelOrdersResult, ClobClient, LimitOrderParams, OrderSide, OrderType, PlaceOrderResult,
+};
+use crate::clock::{utc, wall_ms, Clock};
+use crate::session_db::SessionDb;
+use crate::slippage::SlippageStats;
+use crate::trades::Fill;
+use crate::types::TopOfBook;
+use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderState {
+    Created,
+    Submitted,
+    Live,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+}
+
+impl OrderState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OrderState::Created => "created",
+            OrderState::Submitted => "submitted",
+            OrderState::Live => "live",
+            OrderState::PartiallyFilled => "partially_filled",
+            OrderState::Filled => "filled",
+            OrderState::Cancelled => "cancelled",
+            OrderState::Rejected => "rejected",
+        }
+    }
+
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            OrderState::Filled | OrderState::Cancelled | OrderState::Rejected
+        )
+    }
+
+    /// Whether an order in this state can move to `next`: a rejection only answers the creation or
+    /// the submission, an order on the book only fills or is cancelled, and nothing leaves a
+    /// terminal state.
+    pub fn can_become(self, next: OrderState) -> bool {
+        match self {
+            OrderState::Created => matches!(next, OrderState::Submitted | OrderState::Rejected),
+            OrderState::Submitted => !matches!(next, OrderState::Created | OrderState::Submitted),
+            OrderState::Live | OrderState::PartiallyFilled => matches!(
+                next,
+                OrderState::PartiallyFilled | OrderState::Filled | OrderState::Cancelled
+            ),
+            OrderState::Filled | OrderState::Cancelled | OrderState::Rejected => false,
+        }
+    }
+}
+
+/// One order and its history.
+#[derive(Debug, Clone)]
+pub struct TrackedOrder {
+    pub id: u64,
+    /// Exchange order ID, once the order was accepted.
+    pub exchange_id: Option<String>,
+    pub token_id: String,
+    pub side: OrderSide,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub filled: Decimal,
+    pub order_type: OrderType,
+    /// Best ask (buy) or best bid (sell) of the order's token when it was created.
+    pub decision_price: Option<Decimal>,
+    /// Sum of price × size over the fills with a known price.
+    pub fill_value: Decimal,
+    /// Shares in `fill_value`.
+    pub priced_filled: Decimal,
+    pub state: OrderState,
+    /// (state, ms) for every transition, oldest first.
+    pub history: Vec<(OrderState, u64)>,
+}
+
+/// Ticks from the touch at which an open order is synced at the slowest rate.
+pub const ORDER_SYNC_FAR_TICKS: u64 = 5;
+
+/// Time between two trades-history syncs of the open orders, given the ticks between the closest
+/// one and the touch that fills it: `min_ms` within a tick, `max_ms` from [ORDER_SYNC_FAR_TICKS]
+/// (or with no book), linear in between.
+pub fn order_sync_interval_ms(ticks_away: Option<Decimal>, min_ms: u64, max_ms: u64) -> u64 {
+    let Some(ticks) = ticks_away else {
+        return max_ms;
+    };
+    let far = Decimal::from(ORDER_SYNC_FAR_TICKS - 1);
+    let frac = ((ticks - Decimal::ONE) / far).clamp(Decimal::ZERO, Decimal::ONE);
+    let span = Decimal::from(max_ms.saturating_sub(min_ms));
+    min_ms + (span * frac).round().to_u64().unwrap_or(0)
+}
+
+pub fn order_type_label(t: OrderType) -> &'static str {
+    match t {
+        OrderType::Gtc => "GTC",
+        OrderType::Gtd => "GTD",
+        OrderType::Fok => "FOK",
+        OrderType::Fak => "FAK",
+    }
+}
+
+/// Open orders by local ID; orders are dropped once terminal (their history is in the audit).
+#[derive(Default)]
+pub struct OrderTracker {
+    orders: HashMap<u64, TrackedOrder>,
+    next_id: u64,
+    audit: Option<File>,
+    /// MM_SESSION_DB: latest state of every order.
+    db: Option<Arc<SessionDb>>,
+    /// Trade IDs already applied by [OrderTracker::on_fills] (sync windows overlap).
+    seen_trades: HashSet<String>,
+    /// (best bid, best ask) per token from the runner's latest book.
+    book: HashMap<String, (Option<Decimal>, Option<Decimal>)>,
+    slippage: SlippageStats,
+    /// Time of each transition (None = the wall clock).
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl OrderTracker {
+    /// Tracker writing its audit to `orders_YYYY-MM-DDTHH-MM-SS.jsonl` in `dir` (None = no
+    /// audit file), stamped with `now_ms`.
+    pub fn new(audit_dir: Option<&str>, now_ms: u64) -> Result<Self> {
+        let audit = match audit_dir {
+            Some(dir) => {
+                let path = Path::new(dir);
+                fs::create_dir_all(path)?;
+                let file = path.join(format!(
+                    "orders_{}.jsonl",
+                    utc(now_ms).format("%Y-%m-%dT%H-%M-%S")
+                ));
+                tracing::info!("[OrderTracker] audit to {}", file.display());
+                Some(OpenOptions::new().create(true).append(true).open(file)?)
+            }
+            None => None,
+        };
+        Ok(Self {
+            audit,
+            ..Self::default()
+        })
+    }
+
+    /// Time transitions on `clock` (the engine's) instead of the wall clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Also record every transition in the session database.
+    pub fn with_db(mut self, db: Option<Arc<SessionDb>>) -> Self {
+        self.db = db;
+        self
+    }
+
+    /// The book the runner decides on this tick: orders created from now on take their decision
+    /// price from it.
+    pub fn set_book(&mut self, token_id_up: &str, token_id_down: &str, top: &TopOfBook) {
+        self.book.clear();
+        for (token, side) in [
+            (token_id_up, top.token_id_up.as_ref()),
+            (token_id_down, top.token_id_down.as_ref()),
+        ] {
+            if let Some(s) = side {
+                self.book
+                    .insert(token.to_string(), (s.best_bid, s.best_ask));
+            }
+        }
+    }
+
+    /// Record a new order (state created); returns its local ID.
+    pub fn create(&mut self, params: &LimitOrderParams, order_type: OrderType) -> u64 {
+        self.next_id += 1;
+        let id = self.next_id;
+        let decision_price =
+            self.book
+                .get(&params.token_id)
+                .and_then(|(bid, ask)| match params.side {
+                    OrderSide::Buy => *ask,
+                    OrderSide::Sell => *bid,
+                });
+        let order = TrackedOrder {
+            id,
+            exchange_id: None,
+            token_id: params.token_id.clone(),
+            side: params.side,
+            price: params.price,
+            size: params.size,
+            filled: Decimal::ZERO,
+            order_type,
+            decision_price,
+            fill_value: Decimal::ZERO,
+            priced_filled: Decimal::ZERO,
+            state: OrderState::Created,
+            history: Vec::new(),
+        };
+        self.orders.insert(id, order);
+        self.transition(id, OrderState::Created, None);
+        id
+    }
+
+    pub fn submitted(&mut self, id: u64) {
+        self.transition(id, OrderState::Submitted, None);
+    }
+
+    /// Exchange response to the submission. FOK/FAK successes without a filled size are fully
+    /// filled; a FAK partial fill cancels the rest; a GTC/GTD success rests on the book. Ignored
+    /// unless the order is submitted (one response per submission).
+    pub fn on_result(&mut self, id: u64, result: &PlaceOrderResult) {
+        let Some(order) = self.orders.get_mut(&id) else {
+            return;
+        };
+        if order.state != OrderState::Submitted {
+            warn!(
+                "[OrderTracker] #{} result while {}: ignored",
+                id,
+                order.state.as_str()
+            );
+            return;
+        }
+        if !result.success {
+            let reason = result.error_msg.clone();
+            self.transition(id, OrderState::Rejected, reason);
+            return;
+        }
+        order.exchange_id = result.order_id.clone();
+        let immediate = matches!(order.order_type, OrderType::Fok | OrderType::Fak);
+        let filled = match result.filled_size {
+            Some(f) if f > Decimal::ZERO => f.min(order.size),
+            _ if immediate => order.size,
+            _ => Decimal::ZERO,
+        };
+        order.filled = filled;
+        if let Some(price) = result.avg_price.filter(|_| filled > Decimal::ZERO) {
+            self.record_fill(id, price, filled);
+        }
+        let Some(order) = self.orders.get(&id) else {
+            return;
+        };
+        let complete = filled >= order.size * dec!(0.999);
+        if complete {
+            self.transition(id, OrderState::Filled, None);
+        } else if filled > Decimal::ZERO {
+            self.transition(id, OrderState::PartiallyFilled, None);
+            if immediate {
+                self.transition(
+                    id,
+                    OrderState::Cancelled,
+                    Some("unfilled remainder".to_string()),
+                );
+            }
+        } else {
+            self.transition(id, OrderState::Live, None);
+        }
+    }
+
+    /// Transport error: the order may or may not have reached the exchange.
+    pub fn on_error(&mut self, id: u64, error: &str) {
+        self.transition(id, OrderState::Rejected, Some(error.to_string()));
+    }
+
+    /// Cancel response for `token_id` (any token when None): open orders whose exchange ID was
+    /// canceled end cancelled.
+    pub fn on_cancel(&mut self, token_id: Option<&str>, result: &CancelOrdersResult) {
+        let ids: Vec<u64> = self
+            .orders
+            .values()
+            .filter(|o| {
+                token_id.is_none_or(|t| o.token_id == t)
+                    && o.exchange_id
+                        .as_ref()
+                        .is_some_and(|e| result.canceled.contains(e))
+            })
+            .map(|o| o.id)
+            .collect();
+        for id in ids {
+            self.transition(id, OrderState::Cancelled, Some("canceled".to_string()));
+        }
+    }
+
+    /// Trades-history fills for resting orders: fills the live orders they belong to (matched by
+    /// token, side and price, oldest first).
+    pub fn on_fills(&mut self, fills: &[Fill]) {
+        for f in fills {
+            if !self
+                .seen_trades
+                .insert(format!("{}:{}", f.trade_id, f.asset_id))
+            {
+                continue;
+            }
+            let Some(id) = self
+                .orders
+                .values()
+                .filter(|o| {
+                    matches!(o.state, OrderState::Live | OrderState::PartiallyFilled)
+                        && o.token_id == f.asset_id
+                        && o.side == f.side
+                        && o.price == f.price
+                })
+                .map(|o| o.id)
+                .min()
+            else {
+                continue;
+            };
+            let order = self.orders.get_mut(&id).expect("order just found");
+            let size = f.size.min(order.size - order.filled);
+            order.filled += size;
+            self.record_fill(id, f.price, size);
+            let order = self.orders.get(&id).expect("order just found");
+            let state = if order.filled >= order.size * dec!(0.999) {
+                OrderState::Filled
+            } else {
+                OrderState::PartiallyFilled
+            };
+            self.transition(id, state, None);
+        }
+    }
+
+    /// Orders still open (live or partially filled) on `token_id`.
+    pub fn open_orders<'a>(&'a self, token_id: &'a str) -> impl Iterator<Item = &'a TrackedOrder> {
+        self.orders
+            .values()
+            .filter(move |o| o.token_id == token_id && !o.state.is_terminal())
+    }
+
+    /// Every order not yet terminal, on any token.
+    pub fn open(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders.values().filter(|o| !o.state.is_terminal())
+    }
+
+    /// Ticks between the open order closest to a fill and the touch that fills it (best ask for a
+    /// buy, best bid for a sell; 0 when crossed), from the last [OrderTracker::set_book]. None
+    /// when no open order is on a token of that book.
+    pub fn ticks_to_fill(&self, tick_size: Decimal) -> Option<Decimal> {
+        if tick_size <= Decimal::ZERO {
+            return None;
+        }
+        self.open()
+            .filter_map(|o| {
+                let (bid, ask) = self.book.get(&o.token_id)?;
+                let gap = match o.side {
+                    OrderSide::Buy => (*ask)? - o.price,
+                    OrderSide::Sell => o.price - (*bid)?,
+                };
+                Some(gap.max(Decimal::ZERO) / tick_size)
+            })
+            .min()
+    }
+
+    /// True while any order rests on the book (live or partially filled).
+    pub fn has_open(&self) -> bool {
+        self.orders.values().any(|o| !o.state.is_terminal())
+    }
+
+    pub fn get(&self, id: u64) -> Option<&TrackedOrder> {
+        self.orders.get(&id)
+    }
+
+    /// Slippage of the fills so far, per order type.
+    pub fn slippage(&self) -> &SlippageStats {
+        &self.slippage
+    }
+
+    /// Price a fill of `size` at `price` and count its slippage when the order has a decision price.
+    fn record_fill(&mut self, id: u64, price: Decimal, size: Decimal) {
+        let Some(order) = self.orders.get_mut(&id).filter(|_| size > Decimal::ZERO) else {
+            return;
+        };
+        order.fill_value += price * size;
+        order.priced_filled += size;
+        if let Some(decision) = order.decision_price {
+            let slip = self
+                .slippage
+                .record(order.order_type, order.side, decision, price, size);
+            debug!(
+                "[OrderTracker] #{} fill {} @ {} vs decision {}: slippage {}",
+                id, size, price, decision, slip
+            );
+        }
+    }
+
+    /// Move order `id` to `state`; an illegal transition ([OrderState::can_become]) is logged and
+    /// ignored.
+    fn transition(&mut self, id: u64, state: OrderState, reason: Option<String>) {
+        let Some(order) = self.orders.get_mut(&id) else {
+            return;
+        };
+        // `create` records the initial state.
+        if !order.history.is_empty() && !order.state.can_become(state) {
+            warn!(
+                "[OrderTracker] #{} illegal transition {} -> {}: ignored",
+                id,
+                order.state.as_str(),
+                state.as_str()
+            );
+            return;
+        }
+        let ts = self.clock.as_ref().map_or_else(wall_ms, |c| c.now_ms());
+        order.state = state;
+        order.history.push((state, ts));
+        debug!(
+            "[OrderTracker] #{} {:?} {} {} @ {} {} -> {}{}",
+            id,
+            order.side,
+            order_type_label(order.order_type),
+            order.size,
+            order.price,
+            &order.token_id[..order.token_id.len().min(12)],
+            state.as_str(),
+            reason
+                .as_deref()
+                .map(|r| format!(" ({})", r))
+                .unwrap_or_default()
+        );
+        if self.audit.is_some() || self.db.is_some() {
+            let line = serde_json::json!({
+                "event": "order",
+                "id": id,
+                "exchange_id": order.exchange_id,
+                "token_id": order.token_id,
+                "side": match order.side {
+                    OrderSide::Buy => "BUY",
+                    OrderSide::Sell => "SELL",
+                },
+                "order_type": order_type_label(order.order_type),
+                "price": order.price.to_string(),
+                "size": order.size.to_string(),
+                "filled": order.filled.to_string(),
+                "decision_price": order.decision_price.map(|p| p.to_string()),
+                "avg_fill_price": (!order.priced_filled.is_zero())
+                    .then(|| (order.fill_value / order.priced_filled).round_dp(6).to_string()),
+                "state": state.as_str(),
+                "reason": reason,
+                "ts_ms": ts,
+            });
+            if let Some(ref mut file) = self.audit {
+                if let Err(e) = writeln!(file, "{}", line).and_then(|_| file.flush()) {
+                    warn!("[OrderTracker] audit write failed: {}", e);
+                }
+            }
+            if let Some(ref db) = self.db {
+                db.on_event(&line);
+            }
+        }
+        if state.is_terminal() {
+            self.orders.remove(&id);
+        }
+    }
+}
+
+/// Client wrapper that records every order placed or canceled through it in an [OrderTracker].
+pub struct TrackingClob {
+    inner: Box<dyn ClobClient>,
+    tracker: Arc<Mutex<OrderTracker>>,
+}
+
+impl TrackingClob {
+    pub fn new(inner: Box<dyn ClobClient>, tracker: Arc<Mutex<OrderTracker>>) -> Self {
+        Self { inner, tracker }
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut OrderTracker) -> T) -> Option<T> {
+        self.tracker.lock().ok().map(|mut t| f(&mut t))
+    }
+}
+
+#[async_trait::async_trait]
+impl ClobClient for TrackingClob {
+    async fn place_limit_order(
+        &self,
+        params: LimitOrderParams,
+        order_type: OrderType,
+    ) -> Result<PlaceOrderResult> {
+        let id = self.with(|t| {
+            let id = t.create(&params, order_type);
+            t.submitted(id);
+            id
+        });
+        let result = self.inner.place_limit_order(params, order_type).await;
+        if let Some(id) = id {
+            match &result {
+                Ok(r) => self.with(|t| t.on_result(id, r)),
+                Err(e) => self.with(|t| t.on_error(id, &e.to_string())),
+            };
+        }
+        result
+    }
+
+    async fn place_orders_batch(
+        &self,
+        orders: Vec<(LimitOrderParams, OrderType)>,
+    ) -> Result<Vec<PlaceOrderResult>> {
+        let ids: Vec<Option<u64>> = orders
+            .iter()
+            .map(|(params, order_type)| {
+                self.with(|t| {
+                    let id = t.create(params, *order_type);
+                    t.submitted(id);
+                    id
+                })
+            })
+            .collect();
+        let results = self.inner.place_orders_batch(orders).await;
+        for (i, id) in ids.into_iter().enumerate() {
+            let Some(id) = id else {
+                continue;
+            };
+            match &results {
+                Ok(rs) => match rs.get(i) {
+                    Some(r) => self.with(|t| t.on_result(id, r)),
+                    None => self.with(|t| t.on_error(id, "no result in batch")),
+                },
+                Err(e) => self.with(|t| t.on_error(id, &e.to_string())),
+            };
+        }
+        results
+    }
+
+    async fn cancel_orders_for_token(&self, token_id: &str) -> Result<CancelOrdersResult> {
+        let result = self.inner.cancel_orders_for_token(token_id).await?;
+        self.with(|t| t.on_cancel(Some(token_id), &result));
+        Ok(result)
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<CancelOrdersResult> {
+        let result = self.inner.cancel_order(order_id).await?;
+        self.with(|t| t.on_cancel(None, &result));
+        Ok(result)
+    }
+
+    async fn get_balance_allowance(&self, token_id: &str) -> Result<String> {
+        self.inner.get_balance_allowance(token_id).await
+    }
+
+    async fn get_available_balance(&self, token_id: &str) -> Result<Option<Decimal>> {
+        self.inner.get_available_balance(token_id).await
+    }
+
+    async fn warm_up(&self) {
+        self.inner.warm_up().await
+    }
+
+    async fn get_collateral_balance(&self) -> Result<Option<Decimal>> {
+        self.inner.get_collateral_balance().await
+    }
+
+    fn funders(&self) -> Vec<String> {
+        self.inner.funders()
+    }
+
+    async fn get_trades(&self, after_unix: u64) -> Result<Vec<Fill>> {
+        self.inner.get_trades(after_unix).await
+    }
+}
@@ -0,0 +1,137 @@
+//! Runner phases: where the current interval stands with its entries ([IntervalPhase]) and whether
+//! the position's TP/SL still fire ([PositionPhase]). They replace the runner's interacting flags
+//! (ordered this interval, TP placed, SL placed, re-entry allowed after SL) with explicit states
+//! that only the events of [Phases] change; each change is logged at debug level with its cause.
+//! An interval starts idle and armed. Sending an entry makes it entered; a TP exit leaves it
+//! entered, an SL exit stopped out (re-entry after SL allowed), and either exit, like a close or a
+//! hand-over to resolution, leaves the position exited until the next fill arms it again.
+
+use crate::session_log::{exit_type_str, ExitType};
+use tracing::debug;
+
+/// Entries of the current interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntervalPhase {
+    /// No entry order sent this interval: the first slot is free.
+    #[default]
+    Idle,
+    /// An entry order went out. Further entries only under MM_RE_ENTRY=always.
+    Entered,
+    /// The interval's last position was stopped out: MM_RE_ENTRY=after_sl may buy again (up to
+    /// MM_MAX_TRADES_PER_INTERVAL), until a TP.
+    StoppedOut,
+}
+
+impl IntervalPhase {
+    /// Key in logs and JSON.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IntervalPhase::Idle => "idle",
+            IntervalPhase::Entered => "entered",
+            IntervalPhase::StoppedOut => "stopped_out",
+        }
+    }
+}
+
+/// Exits of the position bought this interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PositionPhase {
+    /// TP/SL fire for the pending exits (none before the first fill).
+    #[default]
+    Armed,
+    /// The position was sold, closed as dust or handed to resolution: TP/SL are off until the next
+    /// fill arms them.
+    Exited,
+}
+
+impl PositionPhase {
+    /// Key in logs and JSON.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PositionPhase::Armed => "armed",
+            PositionPhase::Exited => "exited",
+        }
+    }
+}
+
+/// The runner's interval and position phases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Phases {
+    interval: IntervalPhase,
+    position: PositionPhase,
+}
+
+impl Phases {
+    pub fn interval(&self) -> IntervalPhase {
+        self.interval
+    }
+
+    pub fn position(&self) -> PositionPhase {
+        self.position
+    }
+
+    /// An entry order went out this interval (the first slot is used).
+    pub fn entered(&self) -> bool {
+        self.interval != IntervalPhase::Idle
+    }
+
+    /// MM_RE_ENTRY=after_sl may buy again.
+    pub fn re_entry_after_sl(&self) -> bool {
+        self.interval == IntervalPhase::StoppedOut
+    }
+
+    /// TP/SL still fire.
+    pub fn exits_armed(&self) -> bool {
+        self.position == PositionPhase::Armed
+    }
+
+    /// A new interval: nothing entered, exits armed.
+    pub fn on_interval_start(&mut self) {
+        self.set(IntervalPhase::Idle, PositionPhase::Armed, "interval start");
+    }
+
+    /// An entry order was sent.
+    pub fn on_entry_sent(&mut self) {
+        if self.interval == IntervalPhase::Idle {
+            self.set(IntervalPhase::Entered, self.position, "entry sent");
+        }
+    }
+
+    /// The interval's only entry ended without a fill: its slot is free again.
+    pub fn on_entry_void(&mut self) {
+        self.set(IntervalPhase::Idle, self.position, "entry void");
+    }
+
+    /// A buy filled: TP/SL are armed for it.
+    pub fn on_position_opened(&mut self) {
+        self.set(self.interval, PositionPhase::Armed, "position opened");
+    }
+
+    /// The position left by `exit`. A soft stop sells part of it and changes nothing.
+    pub fn on_exit(&mut self, exit: ExitType) {
+        let interval = match (exit, self.interval) {
+            (ExitType::SoftStop, _) => return,
+            (_, IntervalPhase::Idle) => IntervalPhase::Idle,
+            (ExitType::TakeProfit, _) => IntervalPhase::Entered,
+            (ExitType::StopLoss, _) => IntervalPhase::StoppedOut,
+            (ExitType::MarketClose | ExitType::Resolution, i) => i,
+        };
+        self.set(interval, PositionPhase::Exited, exit_type_str(exit));
+    }
+
+    fn set(&mut self, interval: IntervalPhase, position: PositionPhase, cause: &str) {
+        if (interval, position) == (self.interval, self.position) {
+            return;
+        }
+        debug!(
+            "[Phase] {}/{} -> {}/{} ({})",
+            self.interval.as_str(),
+            self.position.as_str(),
+            interval.as_str(),
+            position.as_str(),
+            cause
+        );
+        self.interval = interval;
+        self.position = position;
+    }
+}
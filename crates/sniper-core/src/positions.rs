@@ -0,0 +1,81 @@
+//! Outcome-token positions of an address from the Polymarket data API (GET /positions), across
+//! all markets: what the wallet actually holds, with sizes and average prices, independent of the
+//! bot's own fill bookkeeping. Used by the `flatten` binary and the runner's holdings check
+//! (MM_POSITIONS_CHECK_SECS).
+
+use anyhow::{Context, Result};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// One held outcome token.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    /// Token ID.
+    pub asset: String,
+    #[serde(deserialize_with = "number")]
+    pub size: Decimal,
+    /// Average entry price over the wallet's buys.
+    #[serde(default, deserialize_with = "number")]
+    pub avg_price: Decimal,
+    #[serde(default, deserialize_with = "number")]
+    pub cur_price: Decimal,
+    #[serde(default)]
+    pub slug: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub outcome: String,
+    /// Market resolved: the token is redeemed, not sold.
+    #[serde(default)]
+    pub redeemable: bool,
+}
+
+/// The data API sends sizes and prices as JSON numbers; read them through their decimal text.
+fn number<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Decimal, D::Error> {
+    let v = serde_json::Value::deserialize(d)?;
+    let s = match v {
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    };
+    Decimal::from_str(s.trim())
+        .or_else(|_| Decimal::from_scientific(s.trim()))
+        .map_err(serde::de::Error::custom)
+}
+
+/// Positions of `user` (the funder address) with a size above zero.
+pub async fn fetch_positions(client: &Client, base_url: &str, user: &str) -> Result<Vec<Position>> {
+    let url = format!(
+        "{}/positions?user={}&sizeThreshold=0&limit=500",
+        base_url.trim_end_matches('/'),
+        urlencoding::encode(user)
+    );
+    let res = client.get(&url).send().await.context("positions request")?;
+    if !res.status().is_success() {
+        anyhow::bail!("GET /positions HTTP {}", res.status());
+    }
+    let positions: Vec<Position> = res.json().await.context("positions JSON")?;
+    Ok(positions
+        .into_iter()
+        .filter(|p| p.size > Decimal::ZERO)
+        .collect())
+}
+
+/// Positions of every address in `users` (e.g. each account's funder), in order.
+pub async fn fetch_all_positions(
+    client: &Client,
+    base_url: &str,
+    users: &[String],
+) -> Result<Vec<Position>> {
+    let mut all = Vec::new();
+    for user in users {
+        all.extend(
+            fetch_positions(client, base_url, user)
+                .await
+                .with_context(|| format!("positions of {}", user))?,
+        );
+    }
+    Ok(all)
+}
@@ -0,0 +1,182 @@
+//! Token-bucket rate limiter for CLOB REST calls, one bucket per endpoint class.
+//!
+//! Keeps SL/TP retry loops and balance polling from getting the API key rate-limited during
+//! volatile intervals. Rates come from MM_RATE_LIMIT_*_RPS (0 = unlimited); burst = 1s of rate.
+
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Endpoint class: each has its own bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointClass {
+    /// POST /order, POST /orders.
+    Order,
+    /// DELETE cancel endpoints.
+    Cancel,
+    /// GET /book (public).
+    Book,
+    /// GET /balance-allowance and other authenticated reads.
+    Balance,
+}
+
+impl EndpointClass {
+    fn index(self) -> usize {
+        match self {
+            EndpointClass::Order => 0,
+            EndpointClass::Cancel => 1,
+            EndpointClass::Book => 2,
+            EndpointClass::Balance => 3,
+        }
+    }
+}
+
+struct TokenBucket {
+    rate_per_sec: f64,
+    capacity: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate_per_sec: f64) -> Self {
+        Self {
+            rate_per_sec,
+            capacity: rate_per_sec.max(1.0),
+            tokens: rate_per_sec.max(1.0),
+            last: Instant::now(),
+        }
+    }
+
+    /// Take one token at `now`, returning how long the caller must wait for it (tokens may go
+    /// negative, which queues later callers behind earlier ones).
+    fn reserve(&mut self, now: Instant) -> Duration {
+        if self.rate_per_sec <= 0.0 {
+            return Duration::ZERO;
+        }
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.tokens = (self.tokens + elapsed * self.rate_per_sec).min(self.capacity);
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate_per_sec)
+        }
+    }
+}
+
+/// Per-class token buckets.
+pub struct RateLimiter {
+    buckets: [Mutex<TokenBucket>; 4],
+}
+
+impl RateLimiter {
+    /// MM_RATE_LIMIT_ORDER_RPS, MM_RATE_LIMIT_CANCEL_RPS, MM_RATE_LIMIT_BOOK_RPS, MM_RATE_LIMIT_BALANCE_RPS.
+    pub fn from_env() -> Self {
+        let rps = |key: &str, default: f64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| *v >= 0.0)
+                .unwrap_or(default)
+        };
+        Self::with_rates([
+            rps("MM_RATE_LIMIT_ORDER_RPS", 20.0),
+            rps("MM_RATE_LIMIT_CANCEL_RPS", 10.0),
+            rps("MM_RATE_LIMIT_BOOK_RPS", 20.0),
+            rps("MM_RATE_LIMIT_BALANCE_RPS", 5.0),
+        ])
+    }
+
+    /// Requests per second of Order, Cancel, Book and Balance (0 = unlimited).
+    fn with_rates(rates: [f64; 4]) -> Self {
+        Self {
+            buckets: rates.map(|rps| Mutex::new(TokenBucket::new(rps))),
+        }
+    }
+
+    /// Take a token of `class` at `now`; the wait before sending.
+    fn reserve(&self, class: EndpointClass, now: Instant) -> Duration {
+        self.buckets[class.index()]
+            .lock()
+            .map(|mut b| b.reserve(now))
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Wait until a request of this class may be sent.
+    pub async fn acquire(&self, class: EndpointClass) {
+        let wait = self.reserve(class, Instant::now());
+        if !wait.is_zero() {
+            debug!("[RateLimit] {:?} throttled {} ms", class, wait.as_millis());
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Process-wide limiter (read from env once). Rate limits are per API key / IP, so all clients share it.
+pub fn limiter() -> &'static RateLimiter {
+    static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+    LIMITER.get_or_init(RateLimiter::from_env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn a_burst_of_one_second_then_queued_waits() {
+        let limiter = RateLimiter::with_rates([5.0, 0.0, 0.0, 0.0]);
+        let now = Instant::now();
+        for _ in 0..5 {
+            assert_eq!(limiter.reserve(EndpointClass::Order, now), Duration::ZERO);
+        }
+        assert_eq!(limiter.reserve(EndpointClass::Order, now), ms(200));
+        assert_eq!(limiter.reserve(EndpointClass::Order, now), ms(400));
+    }
+
+    #[test]
+    fn refills_at_the_rate_up_to_the_burst() {
+        let limiter = RateLimiter::with_rates([0.0, 10.0, 0.0, 0.0]);
+        let start = Instant::now();
+        for _ in 0..10 {
+            limiter.reserve(EndpointClass::Cancel, start);
+        }
+        // 300 ms bring back 3 tokens.
+        let later = start + ms(300);
+        for _ in 0..3 {
+            assert_eq!(
+                limiter.reserve(EndpointClass::Cancel, later),
+                Duration::ZERO
+            );
+        }
+        assert_eq!(limiter.reserve(EndpointClass::Cancel, later), ms(100));
+
+        // A long pause refills no more than one second's worth.
+        let idle = later + Duration::from_secs(60);
+        for _ in 0..10 {
+            assert_eq!(limiter.reserve(EndpointClass::Cancel, idle), Duration::ZERO);
+        }
+        assert_eq!(limiter.reserve(EndpointClass::Cancel, idle), ms(100));
+    }
+
+    #[test]
+    fn classes_have_their_own_bucket() {
+        let limiter = RateLimiter::with_rates([1.0, 1.0, 0.0, 0.5]);
+        let now = Instant::now();
+        assert_eq!(limiter.reserve(EndpointClass::Order, now), Duration::ZERO);
+        assert_eq!(limiter.reserve(EndpointClass::Order, now), ms(1000));
+        assert_eq!(limiter.reserve(EndpointClass::Cancel, now), Duration::ZERO);
+        // Below 1 rps the burst is still one request.
+        assert_eq!(limiter.reserve(EndpointClass::Balance, now), Duration::ZERO);
+        assert_eq!(limiter.reserve(EndpointClass::Balance, now), ms(2000));
+        // 0 = unlimited.
+        for _ in 0..100 {
+            assert_eq!(limiter.reserve(EndpointClass::Book, now), Duration::ZERO);
+        }
+    }
+}
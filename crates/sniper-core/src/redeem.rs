@@ -0,0 +1,102 @@
+//! Redemption of resolved positions (MM_AUTO_REDEEM): Conditional Tokens
+//! `redeemPositions(USDC.e, 0x0, conditionId, [1, 2])` turns the winning shares of a binary market
+//! into USDC.e and burns the losing ones. Sent by the signer itself when it trades as an EOA, or
+//! through its 1-of-1 Polymarket Safe; proxy wallets and neg-risk markets redeem on polymarket.com.
+
+use crate::chain::{
+    connect, eip1559_fees, exec_safe_transaction, pending_nonce, rpc_urls_from_env,
+    ConditionalTokens, CONDITIONAL_TOKENS_POLYGON, USDC_E_POLYGON,
+};
+use crate::signing::polymarket_safe_address;
+use anyhow::{bail, Context, Result};
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, Bytes, H160, H256, U256};
+use std::sync::Arc;
+
+/// Redeem both outcomes of `condition_id` held by `funder` (the signer or its Polymarket Safe).
+/// Returns the transaction hash once mined.
+pub async fn redeem_positions(
+    provider: Provider<Http>,
+    wallet: LocalWallet,
+    funder: H160,
+    condition_id: H256,
+) -> Result<String> {
+    let ctf_address = CONDITIONAL_TOKENS_POLYGON.parse::<Address>()?;
+    let usdc_address = USDC_E_POLYGON.parse::<Address>()?;
+    let index_sets = vec![U256::from(1), U256::from(2)];
+    let eoa = wallet.address();
+    if funder == polymarket_safe_address(eoa) {
+        let data: Bytes = ConditionalTokens::new(ctf_address, Arc::new(provider.clone()))
+            .redeem_positions(usdc_address, [0u8; 32], condition_id.into(), index_sets)
+            .calldata()
+            .context("encode redeemPositions")?;
+        return exec_safe_transaction(provider, wallet, funder, ctf_address, data).await;
+    }
+    if funder != eoa {
+        bail!(
+            "funder {:?} is neither the signer nor its Polymarket Safe; redeem on polymarket.com",
+            funder
+        );
+    }
+    let chain_id = provider.get_chainid().await?.as_u64();
+    let client = Arc::new(SignerMiddleware::new(
+        provider,
+        wallet.with_chain_id(chain_id),
+    ));
+    let mut call = ConditionalTokens::new(ctf_address, client.clone()).redeem_positions(
+        usdc_address,
+        [0u8; 32],
+        condition_id.into(),
+        index_sets,
+    );
+    let (max_fee, priority_fee) = eip1559_fees(client.as_ref()).await?;
+    if let Some(tx) = call.tx.as_eip1559_mut() {
+        tx.max_fee_per_gas = Some(max_fee);
+        tx.max_priority_fee_per_gas = Some(priority_fee);
+    }
+    call.tx
+        .set_nonce(pending_nonce(client.as_ref(), eoa).await?);
+    let pending = call.send().await.context("send redeemPositions")?;
+    let hash = format!("{:?}", pending.tx_hash());
+    let receipt = pending
+        .await?
+        .with_context(|| format!("redeemPositions {} dropped", hash))?;
+    if receipt.status != Some(1u64.into()) {
+        bail!("redeemPositions {} reverted", hash);
+    }
+    Ok(hash)
+}
+
+/// [redeem_positions] for the primary account: PRIVATE_KEY, FUNDER_ADDRESS (default: the
+/// signer) and POLYGON_RPC_URL, as the bot trades.
+pub async fn redeem_from_env(condition_id: &str) -> Result<String> {
+    if std::env::var("MM_NEG_RISK")
+        .map(|v| v.to_lowercase() == "true" || v == "1")
+        .unwrap_or(false)
+    {
+        bail!("neg-risk markets redeem through the NegRiskAdapter; redeem on polymarket.com");
+    }
+    let condition_id = condition_id
+        .trim()
+        .parse::<H256>()
+        .with_context(|| format!("Invalid conditionId {}", condition_id))?;
+    let pk = std::env::var("PRIVATE_KEY")
+        .or_else(|_| std::env::var("POLYMARKET_PRIVATE_KEY"))
+        .context("PRIVATE_KEY or POLYMARKET_PRIVATE_KEY required")?;
+    let wallet = pk
+        .trim()
+        .strip_prefix("0x")
+        .unwrap_or(pk.trim())
+        .parse::<LocalWallet>()
+        .context("Invalid PRIVATE_KEY")?;
+    let funder = match std::env::var("FUNDER_ADDRESS") {
+        Ok(s) if !s.trim().is_empty() => {
+            s.trim().parse::<H160>().context("Invalid FUNDER_ADDRESS")?
+        }
+        _ => wallet.address(),
+    };
+    let provider = connect(&rpc_urls_from_env()).await?;
+    redeem_positions(provider, wallet, funder, condition_id).await
+}
@@ -0,0 +1,284 @@
+//! Retry with exponential backoff and jitter for CLOB REST calls. Honors `Retry-After` on 429.
+//!
+//! Requests are rebuilt on every attempt (fresh POLY_TIMESTAMP / HMAC). Order POSTs are not
+//! idempotent: they are only retried when the request never reached the server (connect error)
+//! or was rejected with 429, so a timeout can never turn into a double order.
+//!
+//! Timeouts: the live client's overall timeout is MM_HTTP_TIMEOUT_MS; MM_HTTP_TIMEOUT_ORDER_MS,
+//! _CANCEL_MS, _BOOK_MS and _BALANCE_MS override it per attempt for each endpoint class (orders
+//! short, books shorter, balance reads longer). A request that runs out of time comes back as a
+//! [TimeoutError].
+//!
+//! Backoff waits go through a [Clock] ([send_with_clock]), so an engine on a simulated clock
+//! retries in its own time.
+
+use crate::clock::{Clock, SystemClock};
+use crate::rate_limit::{limiter, EndpointClass};
+use anyhow::Result;
+use rand::Rng;
+use reqwest::header::HeaderMap;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::warn;
+
+/// Upper bound for a server-provided Retry-After so a bad header cannot stall the loop.
+const MAX_RETRY_AFTER_MS: u64 = 10_000;
+
+/// Backoff policy shared by all CLOB REST calls.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one (1 = no retry).
+    pub max_attempts: u32,
+    /// Backoff cap for attempt n is base_delay_ms * 2^(n-1), bounded by max_delay_ms.
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl RetryPolicy {
+    /// MM_HTTP_RETRY_MAX_ATTEMPTS, MM_HTTP_RETRY_BASE_MS, MM_HTTP_RETRY_MAX_MS.
+    pub fn from_env() -> Self {
+        let var = |key: &str, default: u64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(default)
+        };
+        Self {
+            max_attempts: var("MM_HTTP_RETRY_MAX_ATTEMPTS", 3).clamp(1, 10) as u32,
+            base_delay_ms: var("MM_HTTP_RETRY_BASE_MS", 100),
+            max_delay_ms: var("MM_HTTP_RETRY_MAX_MS", 2000),
+        }
+    }
+
+    /// Full jitter: uniform in [0, min(max_delay, base * 2^(attempt-1))].
+    fn backoff_ms(&self, attempt: u32) -> u64 {
+        let exp = self
+            .base_delay_ms
+            .saturating_mul(1u64 << attempt.saturating_sub(1).min(16))
+            .min(self.max_delay_ms);
+        if exp == 0 {
+            return 0;
+        }
+        rand::thread_rng().gen_range(0..=exp)
+    }
+}
+
+/// Process-wide policy (read from env once).
+pub fn policy() -> &'static RetryPolicy {
+    static POLICY: OnceLock<RetryPolicy> = OnceLock::new();
+    POLICY.get_or_init(RetryPolicy::from_env)
+}
+
+/// Request timeouts: the client's, and per endpoint class (None = the client's).
+#[derive(Debug, Clone, Copy)]
+pub struct Timeouts {
+    /// Overall timeout of the live CLOB client.
+    pub client: Duration,
+    pub order: Option<Duration>,
+    pub cancel: Option<Duration>,
+    pub book: Option<Duration>,
+    pub balance: Option<Duration>,
+}
+
+impl Timeouts {
+    /// MM_HTTP_TIMEOUT_MS, MM_HTTP_TIMEOUT_ORDER_MS, MM_HTTP_TIMEOUT_CANCEL_MS,
+    /// MM_HTTP_TIMEOUT_BOOK_MS, MM_HTTP_TIMEOUT_BALANCE_MS (0 or unset = the client's).
+    pub fn from_env() -> Self {
+        let ms = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis)
+        };
+        Self {
+            client: ms("MM_HTTP_TIMEOUT_MS").unwrap_or(Duration::from_secs(15)),
+            order: ms("MM_HTTP_TIMEOUT_ORDER_MS"),
+            cancel: ms("MM_HTTP_TIMEOUT_CANCEL_MS"),
+            book: ms("MM_HTTP_TIMEOUT_BOOK_MS"),
+            balance: ms("MM_HTTP_TIMEOUT_BALANCE_MS"),
+        }
+    }
+
+    /// Timeout of one request of `class`, when it overrides the client's.
+    pub fn for_class(&self, class: EndpointClass) -> Option<Duration> {
+        match class {
+            EndpointClass::Order => self.order,
+            EndpointClass::Cancel => self.cancel,
+            EndpointClass::Book => self.book,
+            EndpointClass::Balance => self.balance,
+        }
+    }
+}
+
+/// Process-wide timeouts (read from env once).
+pub fn timeouts() -> &'static Timeouts {
+    static TIMEOUTS: OnceLock<Timeouts> = OnceLock::new();
+    TIMEOUTS.get_or_init(Timeouts::from_env)
+}
+
+/// A CLOB REST call with no response in time, after the retries its class allows.
+#[derive(Debug, Clone)]
+pub struct TimeoutError {
+    /// Request, e.g. "POST /order".
+    pub label: String,
+    pub class: EndpointClass,
+    /// The class timeout in force (None = the client's).
+    pub after: Option<Duration>,
+}
+
+impl TimeoutError {
+    /// Safe to send again as is: reads and cancels. An order POST may still have reached the
+    /// book, so it is looked up before it is resent (LiveClob does).
+    pub fn retryable(&self) -> bool {
+        idempotent(self.class)
+    }
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.after {
+            Some(after) => write!(f, "{} timed out after {} ms", self.label, after.as_millis()),
+            None => write!(f, "{} timed out", self.label),
+        }
+    }
+}
+
+impl std::error::Error for TimeoutError {}
+
+/// Whether `e` (or an error it wraps) is a [TimeoutError].
+pub fn is_timeout(e: &anyhow::Error) -> bool {
+    e.chain().any(|c| c.is::<TimeoutError>())
+}
+
+/// Requests of `class` can be sent twice without harm: everything but order POSTs.
+fn idempotent(class: EndpointClass) -> bool {
+    class != EndpointClass::Order
+}
+
+/// How a request failed before a response arrived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendFailure {
+    /// No connection: the request never reached the server.
+    Connect,
+    Timeout,
+    /// Failed while being sent (e.g. the connection dropped mid-request).
+    Request,
+    /// Anything else (body, decode, redirect).
+    Other,
+}
+
+impl SendFailure {
+    fn of(e: &reqwest::Error) -> Self {
+        if e.is_connect() {
+            Self::Connect
+        } else if e.is_timeout() {
+            Self::Timeout
+        } else if e.is_request() {
+            Self::Request
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// A response with `status` is sent again: 429 always, 5xx only when `class` is idempotent.
+pub fn retry_status(class: EndpointClass, status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || (idempotent(class) && status.is_server_error())
+}
+
+/// A request that failed with `failure` is sent again: after a connect error always, after a
+/// timeout or send error only when `class` is idempotent (an order may have reached the book).
+pub fn retry_failure(class: EndpointClass, failure: SendFailure) -> bool {
+    match failure {
+        SendFailure::Connect => true,
+        SendFailure::Timeout | SendFailure::Request => idempotent(class),
+        SendFailure::Other => false,
+    }
+}
+
+/// Retry-After in ms, seconds (fractions allowed) capped at [MAX_RETRY_AFTER_MS]. None when
+/// missing, negative or not a number (HTTP dates are not honored).
+pub fn retry_after_ms(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|s| *s >= 0.0)
+        .map(|s| ((s * 1000.0) as u64).min(MAX_RETRY_AFTER_MS))
+}
+
+/// [send_with_clock] on the wall clock.
+pub async fn send<F>(class: EndpointClass, label: &str, build: F) -> Result<Response>
+where
+    F: Fn() -> Result<RequestBuilder>,
+{
+    send_with_clock(&SystemClock, class, label, build).await
+}
+
+/// Send a request built by `build` with the process-wide [RetryPolicy], taking a rate-limit
+/// token for `class` before every attempt. Reads/cancels may also retry on timeouts and 5xx;
+/// order POSTs may not. The class timeout ([Timeouts]) applies to each attempt. Returns the last
+/// response (which may still be a non-success status) or the last transport error, a
+/// [TimeoutError] when it timed out. Backoffs are slept on `clock`.
+pub async fn send_with_clock<F>(
+    clock: &dyn Clock,
+    class: EndpointClass,
+    label: &str,
+    build: F,
+) -> Result<Response>
+where
+    F: Fn() -> Result<RequestBuilder>,
+{
+    let policy = policy();
+    let timeout = timeouts().for_class(class);
+    let mut attempt: u32 = 0;
+    loop {
+        attempt += 1;
+        let last = attempt >= policy.max_attempts;
+        limiter().acquire(class).await;
+        let request = match timeout {
+            Some(t) => build()?.timeout(t),
+            None => build()?,
+        };
+        match request.send().await {
+            Ok(res) => {
+                let status = res.status();
+                if !retry_status(class, status) || last {
+                    return Ok(res);
+                }
+                let delay_ms =
+                    retry_after_ms(res.headers()).unwrap_or_else(|| policy.backoff_ms(attempt));
+                warn!(
+                    "[Retry] {} HTTP {} (attempt {}/{}), retrying in {} ms",
+                    label, status, attempt, policy.max_attempts, delay_ms
+                );
+                clock.sleep(Duration::from_millis(delay_ms)).await;
+            }
+            Err(e) => {
+                if !retry_failure(class, SendFailure::of(&e)) || last {
+                    if e.is_timeout() {
+                        return Err(TimeoutError {
+                            label: label.to_string(),
+                            class,
+                            after: timeout,
+                        }
+                        .into());
+                    }
+                    return Err(e.into());
+                }
+                let delay_ms = policy.backoff_ms(attempt);
+                warn!(
+                    "[Retry] {} failed: {} (attempt {}/{}), retrying in {} ms",
+                    label, e, attempt, policy.max_attempts, delay_ms
+                );
+                clock.sleep(Duration::from_millis(delay_ms)).await;
+            }
+        }
+    }
+}
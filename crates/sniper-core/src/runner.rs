@@ -0,0 +1,4955 @@
+//! Main loop: interval switch, top-of-book, buy in range, TP/SL.
+
+use crate::book::{
+    book_age_ms, fetch_order_book, fetch_top_of_book, public_client, trigger_price, BookDepth,
+};
+use crate::book_recorder::BookRecorder;
+use crate::carryover::CarriedPosition;
+#[allow(unused_imports)]
+use crate::clob::{
+    estimated_fee_usd, ClobClient, ClobError, LimitOrderParams, OrderSide, OrderType,
+    PlaceOrderResult, GTD_MIN_LIFETIME_SECS,
+};
+use crate::clob_ws_book::{
+    ClobWsBook, RestRefresh, WsAssetStats, WsConnectionState, WS_STALE_SECS,
+};
+use crate::clock::{Clock, SystemClock};
+use crate::config::{
+    interval_slug, interval_slug_at, interval_start_unix, load_config, BTC_5MIN_INTERVAL_SEC,
+};
+use crate::decision_trace::{self, DecisionTrace};
+use crate::dry_run_report::DryRunReport;
+use crate::entry_gate::{self, ask_in_range, DeclineCounters, DeclineReason};
+use crate::event::{scan_event, scan_hourly_above, EventScan};
+use crate::event_calendar::EventCalendar;
+use crate::fee_rates::FeeRates;
+use crate::funds_lock::FundsLock;
+use crate::hourly::{hour_close_unix, hourly_event_slug, strike_allows};
+use crate::instance_lock::{self, InstanceLock};
+use crate::inventory::{Inventory, Lot};
+use crate::market::{
+    fetch_resolution, prefetch_next_interval, resolve_market, DEFAULT_MIN_ORDER_SIZE,
+    DEFAULT_TICK_SIZE,
+};
+use crate::messages;
+use crate::order_manager::{
+    resting_buy, resting_order, BatchError, BatchOutcome, OrderExpiry, OrderIntent, OrderManager,
+    OrderOutcome,
+};
+use crate::order_tracker::{order_sync_interval_ms, order_type_label, OrderTracker, TrackingClob};
+use crate::phase::Phases;
+use crate::positions::fetch_all_positions;
+use crate::sell_campaign::{
+    effective_sell_size, floor_to_decimals, partly_sold, sold_size, SellAction, SellCampaign,
+    SellKind, DUST_THRESHOLD, MIN_SELL_SIZE, SELL_SIZE_DECIMALS,
+};
+use crate::session_db::SessionDb;
+use crate::session_log::{ExitType, SessionLog};
+use crate::shadow::ShadowRunner;
+use crate::sizing::{interval_size, Sizing};
+use crate::spot::{hourly_spot_symbol, spot_symbol, SpotFeed};
+use crate::status::{self, StatusFile};
+use crate::strategy::{
+    create_strategy, round_to_tick, Action, FillEvent, TickContext, TickStrategy,
+};
+use crate::tick_profile::{TickPhase, TickProfiler};
+use crate::trades::realized_pnl;
+use crate::types::{
+    BookSource, Config, EntrySide, HedgedPair, IntervalMarketAsset, MarketTarget, PendingAutoSell,
+    PendingSoftStop, PendingStopLoss, ReEntryPolicy, ResolvedMarket, SellOrderTimeInForce,
+    SidePolicy, SizingMode, TargetWindow, TopOfBook, TopOfBookSide, TriggerPriceSource,
+    UnlockPolicy,
+};
+use anyhow::Result;
+use rand::Rng;
+use reqwest::Client;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Event target: wait this long between event scans when no outcome is in range.
+const EVENT_RESCAN_MS: u64 = 1000;
+/// Interval target: prefetch the next interval's Gamma market this many seconds before close.
+const NEXT_MARKET_PREFETCH_SECS: u64 = 20;
+/// Log order book and TP/SL status every this many loop ticks (e.g. 10 → ~1s if loop_ms=100).
+const LOG_BOOK_EVERY_TICKS: u64 = 10;
+/// Heartbeat: log the position (size, average entry, unrealized PnL) and the session's realized
+/// PnL and closes this often (ms).
+const HEARTBEAT_MS: u64 = 15_000;
+/// The data API lags fills by a few seconds; no holdings check this soon after one.
+const POSITIONS_SETTLE_MS: u64 = 15_000;
+/// Gamma poll for the resolution of positions carried past their market's close (and of the
+/// intervals the book recorder finished).
+const CARRYOVER_CHECK_MS: u64 = 5_000;
+/// At the interval switch, wait this long for entry orders still in flight before settling.
+const SWITCH_DRAIN_MS: u64 = 3_000;
+/// Poll of the order manager while waiting for in-flight orders.
+const DRAIN_POLL_MS: u64 = 50;
+/// After canceling our orders on a token, wait this long for the CLOB to see the freed balance
+/// before a TP / SL sells it.
+const UNLOCK_SETTLE_MS: u64 = 350;
+
+/// MM_SL_CONFIRM_MS: true once the SL condition has held for `confirm_ms` across ticks (at once
+/// when 0). `since` tracks when it started; a tick without the condition resets it.
+fn sl_confirmed(since: &mut Option<u64>, breached: bool, now_ms: u64, confirm_ms: u64) -> bool {
+    if !breached {
+        if since.take().is_some() {
+            info!("[IntervalSniper] SL condition cleared before confirmation");
+        }
+        return false;
+    }
+    let start = *since.get_or_insert_with(|| {
+        if confirm_ms > 0 {
+            info!(
+                "[IntervalSniper] SL condition hit, confirming for {} ms",
+                confirm_ms
+            );
+        }
+        now_ms
+    });
+    if now_ms.saturating_sub(start) < confirm_ms {
+        return false;
+    }
+    *since = None;
+    true
+}
+
+/// The WS book while its connection is live (stale or reconnecting: read REST instead).
+fn live_ws(ws_book: &Option<ClobWsBook>) -> Option<&ClobWsBook> {
+    ws_book.as_ref().filter(|ws| ws.is_live())
+}
+
+/// True if top has at least one side with book data (for WS fallback to REST).
+fn top_has_book_data(top: &TopOfBook) -> bool {
+    let up_ok = top
+        .token_id_up
+        .as_ref()
+        .map(|s| s.best_ask.is_some() || s.best_bid.is_some())
+        .unwrap_or(false);
+    let down_ok = top
+        .token_id_down
+        .as_ref()
+        .map(|s| s.best_ask.is_some() || s.best_bid.is_some())
+        .unwrap_or(false);
+    up_ok || down_ok
+}
+
+/// True when MM_MAX_BOOK_AGE_MS is set and the older side of `top` is past it at `now_ms`.
+fn book_too_old(config: &Config, top: &TopOfBook, now_ms: u64) -> bool {
+    config.max_book_age_ms > 0
+        && book_age_ms(top, now_ms).is_some_and(|age| age > config.max_book_age_ms)
+}
+
+/// Update per-interval min/max best_bid and inventory marks from current book.
+fn update_interval_bids(
+    state: &mut RunnerState,
+    token_id_up: &str,
+    token_id_down: &str,
+    top: &TopOfBook,
+) {
+    if let Some(ref up) = top.token_id_up {
+        if let Some(bid) = up.best_bid {
+            state.interval_min_bid_up =
+                Some(state.interval_min_bid_up.map(|m| m.min(bid)).unwrap_or(bid));
+            state.interval_max_bid_up =
+                Some(state.interval_max_bid_up.map(|m| m.max(bid)).unwrap_or(bid));
+        }
+    }
+    if let Some(ref down) = top.token_id_down {
+        if let Some(bid) = down.best_bid {
+            state.interval_min_bid_down = Some(
+                state
+                    .interval_min_bid_down
+                    .map(|m| m.min(bid))
+                    .unwrap_or(bid),
+            );
+            state.interval_max_bid_down = Some(
+                state
+                    .interval_max_bid_down
+                    .map(|m| m.max(bid))
+                    .unwrap_or(bid),
+            );
+        }
+    }
+    if let Some(bid) = top.token_id_up.as_ref().and_then(|s| s.best_bid) {
+        state.inventory.set_mark(token_id_up, bid);
+    }
+    if let Some(bid) = top.token_id_down.as_ref().and_then(|s| s.best_bid) {
+        state.inventory.set_mark(token_id_down, bid);
+    }
+}
+
+/// Widest best_bid range (max - min) seen so far this interval on either side.
+fn interval_bid_range(state: &RunnerState) -> Option<Decimal> {
+    let range = |min: Option<Decimal>, max: Option<Decimal>| min.zip(max).map(|(lo, hi)| hi - lo);
+    range(state.interval_min_bid_up, state.interval_max_bid_up).max(range(
+        state.interval_min_bid_down,
+        state.interval_max_bid_down,
+    ))
+}
+
+/// A GTC entry buy being chased (MM_MAX_CHASE_TICKS): the position opens once it fills or the
+/// chase ends.
+struct RestingBuy {
+    /// Exchange ID of the order on the book; None while its replacement is in flight.
+    order_id: Option<String>,
+    /// The order on the book (size = its unfilled rest).
+    intent: OrderIntent,
+    /// Replacements so far.
+    chases: u32,
+    /// (price, size) of each fill so far across the chased orders.
+    fills: Vec<(Decimal, Decimal)>,
+}
+
+impl RestingBuy {
+    fn filled(&self) -> Decimal {
+        self.fills.iter().map(|(_, size)| *size).sum()
+    }
+
+    /// Record `size` bought at the current order's price.
+    fn add_fill(&mut self, size: Decimal) {
+        if size > Decimal::ZERO {
+            self.fills.push((self.intent.params.price, size));
+        }
+    }
+}
+
+/// MM_TP_PASSIVE_MS: where the open position's post-only TP ask stands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PassiveTp {
+    /// Not posted yet.
+    Unposted,
+    /// Submitted, no result yet.
+    InFlight,
+    /// On the book under this exchange ID, at this price.
+    Resting { order_id: String, price: Decimal },
+    /// Ended (filled, rejected or expired): TP sells at the bid as usual.
+    Done,
+}
+
+impl PassiveTp {
+    /// The ask is posted or about to be: TP must not sell at the bid meanwhile.
+    fn pending(&self) -> bool {
+        matches!(self, PassiveTp::InFlight | PassiveTp::Resting { .. })
+    }
+}
+
+/// MM_SL_HEDGE_BID: GTC bid for the token opposite an unsellable position, resting until it fills
+/// or the interval settles.
+struct HedgeBid {
+    order_id: String,
+    token_id: String,
+    side: EntrySide,
+    price: Decimal,
+    size: Decimal,
+    /// Bought so far (already in the inventory and the hedged pair).
+    filled: Decimal,
+}
+
+/// Volume-weighted average price of (price, size) fills, rounded to 4 decimals.
+fn fills_vwap(fills: &[(Decimal, Decimal)]) -> Option<Decimal> {
+    let size: Decimal = fills.iter().map(|(_, s)| *s).sum();
+    if size <= Decimal::ZERO {
+        return None;
+    }
+    let cost: Decimal = fills.iter().map(|(p, s)| p * s).sum();
+    Some((cost / size).round_dp(4))
+}
+
+struct RunnerState {
+    config: Config,
+    market: Option<ResolvedMarket>,
+    /// WebSocket order book when connected; None = use REST only.
+    ws_book: Option<ClobWsBook>,
+    /// Last WS connection state seen (logged on change; REST is read while not live).
+    ws_state: Option<WsConnectionState>,
+    /// Entry and exit phases of the interval (ordered this interval, TP/SL placed, re-entry after
+    /// SL).
+    phases: Phases,
+    /// Number of buys executed this interval (max config.max_trades_per_interval).
+    trades_this_interval: u32,
+    total_shares_this_interval: Decimal,
+    pending_auto_sell: Option<PendingAutoSell>,
+    pending_stop_loss: Option<PendingStopLoss>,
+    /// MM_SOFT_STOP_PRICE: partial sell ahead of the SL; cleared once it fills.
+    pending_soft_stop: Option<PendingSoftStop>,
+    interval_switch_wall_time_ms: Option<u64>,
+    /// Session log (JSONL) when MM_SESSION_LOG=true.
+    session_log: Option<SessionLog>,
+    /// MM_DRY_RUN_REPORT: simulated trades of a dry run, reported when the run ends.
+    dry_run_report: Option<DryRunReport>,
+    /// Per-interval min/max best_bid for session log (ranged 0.01–0.99).
+    interval_min_bid_up: Option<Decimal>,
+    interval_max_bid_up: Option<Decimal>,
+    interval_min_bid_down: Option<Decimal>,
+    interval_max_bid_down: Option<Decimal>,
+    /// Dual-side entry: both legs bought this interval, held to resolution (no TP/SL).
+    hedged_pair: Option<HedgedPair>,
+    /// MM_SL_HEDGE_BID: opposite-token bid resting for the stopped-out position.
+    hedge_bid: Option<HedgeBid>,
+    /// Resting sells by exchange order ID and the exit that placed them (MM_AUTO_UNLOCK).
+    exit_orders: HashMap<String, SellKind>,
+    /// Entry strategy (MM_STRATEGY).
+    strategy: Box<dyn TickStrategy>,
+    /// Open lots per token (cost basis for closes and session log).
+    inventory: Inventory,
+    /// Last heartbeat log (ms).
+    last_heartbeat_ms: u64,
+    /// Ticks an entry was declined with the ask in range, per reason.
+    declines: DeclineCounters,
+    /// MM_DECISION_TRACE ring buffer (None when off).
+    decision_trace: Option<Arc<DecisionTrace>>,
+    /// Dump requests already handled ([decision_trace::request_dump]).
+    trace_dumps_seen: u64,
+    /// Last MM_POSITIONS_CHECK_SECS holdings check (ms).
+    last_positions_check_ms: u64,
+    /// Last MM_BALANCE_DRIFT_SECS check (ms).
+    last_balance_drift_check_ms: u64,
+    /// Collateral balance and inventory cash flow when the drift check first ran this interval.
+    usdc_baseline: Arc<std::sync::Mutex<Option<(Decimal, Decimal)>>>,
+    /// Last buy or sell fill (ms); no new entry until cooldown_between_orders_ms has passed.
+    last_fill_ms: Option<u64>,
+    /// Economic-event blackouts (MM_BLACKOUT_FILE); no new entries inside them.
+    calendar: Option<EventCalendar>,
+    /// Sampled WS depth -> CSV per interval (MM_BOOK_RECORD).
+    book_recorder: Option<BookRecorder>,
+    /// MM_STATUS_FILE snapshot writer.
+    status_file: Option<StatusFile>,
+    /// Fee rates of the current market's tokens (MM_FEE_RATE_BPS or GET /fee-rate per token at
+    /// the interval switch).
+    fee_rates: FeeRates,
+    /// Entry orders submitted in the background (results polled each tick).
+    orders: OrderManager,
+    /// MM_MAX_CHASE_TICKS: entry buy resting on the book (or being replaced).
+    resting_buy: Option<RestingBuy>,
+    passive_tp: PassiveTp,
+    /// Resolution-hold kicked in this interval: TP skipped, position settles at resolution.
+    holding_to_resolution: bool,
+    /// Positions held past their market's close, waiting for its resolution.
+    carryovers: Vec<CarriedPosition>,
+    /// The last market settled, without lots: an entry it answers after the switch is carried
+    /// with it.
+    closed_market: Option<CarriedPosition>,
+    /// Last carryover resolution poll (ms).
+    last_carryover_check_ms: u64,
+    /// MM_AUTO_REDEEM transactions in flight (awaited when the run ends).
+    redemptions: Vec<JoinHandle<()>>,
+    /// MM_SL_CONFIRM_MS: when the SL condition started holding (None while it does not).
+    sl_breach_since_ms: Option<u64>,
+    /// Next interval's Gamma market prefetch was started this interval.
+    next_market_prefetched: bool,
+    /// MM_SHADOW candidates evaluated on the same ticks (None when off).
+    shadow: Option<ShadowRunner>,
+    /// Spot poller for MM_SIDE_POLICY=spot_direction (interval markets only).
+    spot: Option<SpotFeed>,
+    /// Strike of the held hourly "above $X" market (MM_TARGET_HOURLY_ABOVE).
+    strike: Option<Decimal>,
+    /// Configured (min, max) buy prices of Up and Down; MM_BUY_RANGE_SCHEDULE replaces them in
+    /// state.config while one of its points applies.
+    base_buy_ranges: [(Decimal, Decimal); 2],
+    /// Time source (wall clock, or simulated for replays).
+    clock: Arc<dyn Clock>,
+}
+
+fn seconds_to_close(now_unix: u64, close_time_unix: u64) -> u64 {
+    close_time_unix.saturating_sub(now_unix)
+}
+
+/// True when the held market is no longer the one to trade: it closed, or (interval target)
+/// the 5-min slug rolled over.
+fn market_expired(target: &MarketTarget, market: &ResolvedMarket, now_unix: u64) -> bool {
+    match target {
+        // Past its close by design: traded until the slug rolls over at the next boundary.
+        MarketTarget::Interval(asset, TargetWindow::Closing) => {
+            interval_slug(*asset, TargetWindow::Closing, now_unix) != market.slug
+        }
+        MarketTarget::Interval(asset, window) => {
+            now_unix >= market.close_time_unix
+                || interval_slug(*asset, *window, now_unix) != market.slug
+        }
+        MarketTarget::IntervalAt(..)
+        | MarketTarget::Slug(_)
+        | MarketTarget::ConditionId(_)
+        | MarketTarget::Event(_)
+        | MarketTarget::HourlyAbove(_) => now_unix >= market.close_time_unix,
+    }
+}
+
+/// When the engine leaves `market`: its close, or one interval later for a closing window.
+fn switch_time_unix(target: &MarketTarget, market: &ResolvedMarket) -> u64 {
+    match target {
+        MarketTarget::Interval(_, TargetWindow::Closing) => {
+            market.close_time_unix
+                + market
+                    .close_time_unix
+                    .saturating_sub(market.interval_start_unix)
+        }
+        _ => market.close_time_unix,
+    }
+}
+
+/// Current market tick size (default before the first market resolves).
+fn market_tick_size(state: &RunnerState) -> Decimal {
+    state
+        .market
+        .as_ref()
+        .map(|m| m.tick_size)
+        .unwrap_or(DEFAULT_TICK_SIZE)
+}
+
+/// Adopt the book's tick size and min order size when they differ from the market's
+/// (e.g. tick 0.01 -> 0.001 near 0.99).
+fn update_market_params(state: &mut RunnerState, top: &TopOfBook) {
+    let Some(market) = state.market.as_mut() else {
+        return;
+    };
+    let sides = [top.token_id_up.as_ref(), top.token_id_down.as_ref()];
+    let tick = sides.iter().flatten().filter_map(|s| s.tick_size).min();
+    if let Some(tick) = tick.filter(|t| *t != market.tick_size) {
+        info!(
+            "[IntervalSniper] tick size {} -> {} ({})",
+            market.tick_size, tick, market.slug
+        );
+        market.tick_size = tick;
+    }
+    let min_size = sides
+        .iter()
+        .flatten()
+        .filter_map(|s| s.min_order_size)
+        .max();
+    if let Some(min_size) = min_size.filter(|m| *m != market.min_order_size) {
+        info!(
+            "[IntervalSniper] min order size {} -> {} ({})",
+            market.min_order_size, min_size, market.slug
+        );
+        market.min_order_size = min_size;
+    }
+}
+
+fn size_4_decimals(size: Decimal) -> Decimal {
+    size.round_dp(4)
+}
+
+/// Random size multiplier in [1 - pct/100, 1 + pct/100] (MM_SIZE_JITTER_PERCENT); 1 when off.
+fn size_jitter_factor(pct: Decimal) -> Decimal {
+    let bp = (pct * dec!(100)).trunc().to_i64().unwrap_or(0);
+    if bp <= 0 {
+        return Decimal::ONE;
+    }
+    Decimal::ONE + Decimal::new(rand::thread_rng().gen_range(-bp..=bp), 4)
+}
+
+/// Random 0..=max_ms delay before an entry (MM_ENTRY_JITTER_MS); 0 when off.
+fn entry_jitter_ms(max_ms: u64) -> u64 {
+    if max_ms > 0 {
+        rand::thread_rng().gen_range(0..=max_ms)
+    } else {
+        0
+    }
+}
+
+fn fmt_price(p: Option<&Decimal>) -> String {
+    p.map(fmt_decimal_2).unwrap_or_else(|| "-".to_string())
+}
+
+/// Format a decimal with exactly 2 decimal places (e.g. 0.4 → "0.40", 10.5 → "10.50").
+fn fmt_decimal_2(d: &Decimal) -> String {
+    let r = d.round_dp(2);
+    let s = r.to_string();
+    if let Some((int_part, frac_part)) = s.split_once('.') {
+        let frac = if frac_part.len() > 2 {
+            &frac_part[..2]
+        } else {
+            frac_part
+        };
+        let frac_padded = format!("{:0<2}", frac);
+        format!("{}.{}", int_part, frac_padded)
+    } else {
+        format!("{}.00", s)
+    }
+}
+
+/// Format a decimal rounded to 4 places (fee-adjusted prices rarely land on a tick).
+fn fmt_decimal_4(d: &Decimal) -> String {
+    d.round_dp(4).to_string()
+}
+
+/// Exit price at which a round trip (buy at entry, sell at exit) nets zero after estimated fees.
+/// Fee per share is ~rate * min(p, 1 - p), so solve x - fee(x) = entry + fee(entry) by iterating.
+fn breakeven_exit_price(fee_rate_bps: u64, entry_price: Decimal) -> Decimal {
+    let cost = entry_price + estimated_fee_usd(fee_rate_bps, entry_price, Decimal::ONE);
+    let mut x = cost;
+    for _ in 0..4 {
+        x = cost + estimated_fee_usd(fee_rate_bps, x.min(Decimal::ONE), Decimal::ONE);
+    }
+    x
+}
+
+/// Format seconds for log: at least 2 digits with leading zero (e.g. 9 → "09", 209 → "209").
+fn fmt_secs(n: u64) -> String {
+    format!("{:02}", n)
+}
+
+/// Trade-tape entry gate (MM_MIN_RECENT_VOLUME, MM_MAX_LAST_TRADE_DEVIATION). Returns the reason
+/// to skip the entry, or None when it may proceed.
+async fn trade_tape_block(
+    state: &RunnerState,
+    token_id: &str,
+    best_ask: Option<Decimal>,
+    now_ms: u64,
+) -> Option<String> {
+    let config = &state.config;
+    if config.min_recent_volume.is_zero() && config.max_last_trade_deviation.is_zero() {
+        return None;
+    }
+    let Some(ref ws) = state.ws_book else {
+        return Some("trade tape unavailable (WebSocket book not connected)".to_string());
+    };
+    let stats = ws
+        .trade_stats(token_id, now_ms, config.trade_window_secs * 1000)
+        .await;
+    if stats.volume < config.min_recent_volume {
+        return Some(format!(
+            "recent volume {} < {} over {}s",
+            stats.volume, config.min_recent_volume, config.trade_window_secs
+        ));
+    }
+    if !config.max_last_trade_deviation.is_zero() {
+        // A last trade older than the window says little about the current book.
+        let window_start = now_ms.saturating_sub(config.trade_window_secs * 1000);
+        let recent_last = stats
+            .last_price
+            .filter(|_| stats.last_trade_ms.is_some_and(|t| t >= window_start));
+        match (recent_last, best_ask) {
+            (None, _) => return Some("no trade in the window".to_string()),
+            (Some(last), Some(ask)) if (ask - last).abs() > config.max_last_trade_deviation => {
+                return Some(format!(
+                    "ask {} is {} from last trade {}",
+                    ask,
+                    (ask - last).abs(),
+                    last
+                ));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Depth-zone entry gate (MM_MIN_ZONE_DEPTH): the asks priced within MM_ZONE_TICKS ticks of
+/// `entry_price` must hold at least min_zone_depth shares, so a thin best ask with nothing behind
+/// it is not lifted. Reads the full WS depth, else the REST book. Returns the reason to skip the
+/// entry, or None when it may proceed.
+async fn depth_zone_block(
+    state: &RunnerState,
+    http: &Client,
+    clob_host: &str,
+    token_id: &str,
+    entry_price: Decimal,
+    tick_size: Decimal,
+) -> Option<String> {
+    let config = &state.config;
+    if config.min_zone_depth.is_zero() {
+        return None;
+    }
+    let half_width = tick_size * Decimal::from(config.zone_ticks);
+    let (lo, hi) = (entry_price - half_width, entry_price + half_width);
+    let ws_depth = match live_ws(&state.ws_book) {
+        Some(ws) => ws.ask_size_between(token_id, lo, hi).await,
+        None => None,
+    };
+    let depth = match ws_depth {
+        Some(d) => d,
+        None => match fetch_order_book(http, clob_host, token_id).await {
+            Ok(raw) => BookDepth::from_raw(&raw).size_between(false, lo, hi),
+            Err(e) => return Some(format!("zone depth unavailable ({})", e)),
+        },
+    };
+    (depth < config.min_zone_depth).then(|| {
+        format!(
+            "ask depth {} in [{}, {}] < {}",
+            depth, lo, hi, config.min_zone_depth
+        )
+    })
+}
+
+/// MM_BUY_RANGE_SCHEDULE: set both sides' entry range to the schedule's at `secs_after_open`
+/// (the configured `base` ranges before its first point). Logs when the range changes.
+fn apply_buy_range_schedule(
+    config: &mut Config,
+    base: [(Decimal, Decimal); 2],
+    secs_after_open: u64,
+) {
+    let [up, down] = match config.buy_range_at(secs_after_open) {
+        Some(range) => [range, range],
+        None => base,
+    };
+    let current = [
+        (config.up.min_buy_price, config.up.max_buy_price),
+        (config.down.min_buy_price, config.down.max_buy_price),
+    ];
+    if current == [up, down] {
+        return;
+    }
+    (config.up.min_buy_price, config.up.max_buy_price) = up;
+    (config.down.min_buy_price, config.down.max_buy_price) = down;
+    info!(
+        "[IntervalSniper] buy range {}s after open: Up {}-{} Down {}-{}",
+        secs_after_open, up.0, up.1, down.0, down.1
+    );
+}
+
+/// Dual-side entry: Up and Down asks whose sum is at most dual_side_max_pair_cost, with at least
+/// min_order_size on both asks. Returns (up_ask, down_ask, shares available on both).
+fn choose_pair(
+    config: &Config,
+    book: &TopOfBook,
+    min_order_size: Decimal,
+) -> Option<(Decimal, Decimal, Decimal)> {
+    if !config.allow_buy_up || !config.allow_buy_down {
+        return None;
+    }
+    let up = book.token_id_up.as_ref()?;
+    let down = book.token_id_down.as_ref()?;
+    let up_ask = up.best_ask?;
+    let down_ask = down.best_ask?;
+    if up_ask + down_ask > config.dual_side_max_pair_cost {
+        return None;
+    }
+    let size = up
+        .best_ask_size
+        .unwrap_or(Decimal::ZERO)
+        .min(down.best_ask_size.unwrap_or(Decimal::ZERO));
+    (size >= min_order_size).then_some((up_ask, down_ask, size))
+}
+
+/// Shares actually bought: filled_size from CLOB (FAK can be partial), else the order size.
+fn filled_buy_size(filled_size: Option<Decimal>, size: Decimal) -> Decimal {
+    filled_size
+        .filter(|s| *s > Decimal::ZERO && *s >= size * dec!(0.01))
+        .unwrap_or(size)
+        .min(size)
+}
+
+/// MM_SL_HEDGE_EXIT: buy `sl.size` of the token opposite the stop loss (FOK) when its ask is
+/// within 1 − trigger + MM_SL_HEDGE_SLIPPAGE. Returns the side, token, filled size and price.
+async fn hedge_exit(
+    clob: &dyn ClobClient,
+    config: &Config,
+    market: &ResolvedMarket,
+    top: &TopOfBook,
+    sl: &PendingStopLoss,
+    fee_rates: &FeeRates,
+    tick_size: Decimal,
+) -> Option<(EntrySide, String, Decimal, Decimal)> {
+    let (side, token_id, book) = if sl.token_id == market.token_id_up {
+        (EntrySide::Down, &market.token_id_down, &top.token_id_down)
+    } else {
+        (EntrySide::Up, &market.token_id_up, &top.token_id_up)
+    };
+    let limit = round_to_tick(
+        Decimal::ONE - sl.trigger_price + config.sl_hedge_slippage,
+        tick_size,
+    )
+    .min(dec!(0.99));
+    let ask = book.as_ref().and_then(|b| b.best_ask)?;
+    if ask > limit {
+        debug!(
+            "[IntervalSniper] SL hedge: {:?} ask {} above limit {}",
+            side,
+            fmt_price(Some(&ask)),
+            fmt_price(Some(&limit))
+        );
+        return None;
+    }
+    let params = LimitOrderParams {
+        token_id: token_id.clone(),
+        side: OrderSide::Buy,
+        price: limit,
+        size: sl.size,
+        expiration_unix: None,
+        post_only: false,
+        fee_rate_bps: fee_rates.token(token_id),
+        min_order_size: Some(market.min_order_size),
+    };
+    match clob.place_limit_order(params, OrderType::Fok).await {
+        Ok(r) if r.success => Some((
+            side,
+            token_id.clone(),
+            filled_buy_size(r.filled_size, sl.size),
+            limit,
+        )),
+        Ok(r) => {
+            debug!(
+                "[IntervalSniper] SL hedge: {:?} buy not filled: {}",
+                side,
+                r.error_msg.unwrap_or_default()
+            );
+            None
+        }
+        Err(e) => {
+            warn!("[IntervalSniper] SL hedge: {:?} buy failed: {:#}", side, e);
+            None
+        }
+    }
+}
+
+/// MM_SL_HEDGE_BID: the SL cannot sell (no bid on our side) and the opposite ask is out of reach:
+/// bid for `sl.size` of the opposite token at 1 − `fair` (our side's last bid), capped at the
+/// hedge exit limit, and leave it resting (GTC; the interval settlement cancels what is left).
+async fn post_hedge_bid(
+    clob: &dyn ClobClient,
+    config: &Config,
+    market: &ResolvedMarket,
+    sl: &PendingStopLoss,
+    fair: Decimal,
+    fee_rates: &FeeRates,
+    tick_size: Decimal,
+) -> Option<HedgeBid> {
+    let (side, token_id) = if sl.token_id == market.token_id_up {
+        (EntrySide::Down, &market.token_id_down)
+    } else {
+        (EntrySide::Up, &market.token_id_up)
+    };
+    let limit = round_to_tick(
+        Decimal::ONE - sl.trigger_price + config.sl_hedge_slippage,
+        tick_size,
+    );
+    let price = round_to_tick(Decimal::ONE - fair, tick_size)
+        .min(limit)
+        .clamp(tick_size, dec!(0.99));
+    let params = LimitOrderParams {
+        token_id: token_id.clone(),
+        side: OrderSide::Buy,
+        price,
+        size: sl.size,
+        expiration_unix: None,
+        post_only: false,
+        fee_rate_bps: fee_rates.token(token_id),
+        min_order_size: Some(market.min_order_size),
+    };
+    match clob.place_limit_order(params, OrderType::Gtc).await {
+        Ok(r) if r.success => Some(HedgeBid {
+            order_id: r.order_id.unwrap_or_default(),
+            token_id: token_id.clone(),
+            side,
+            price,
+            size: sl.size,
+            filled: Decimal::ZERO,
+        }),
+        Ok(r) => {
+            debug!(
+                "[IntervalSniper] SL hedge bid: {:?} not placed: {}",
+                side,
+                r.error_msg.unwrap_or_default()
+            );
+            None
+        }
+        Err(e) => {
+            warn!("[IntervalSniper] SL hedge bid: {:?} failed: {:#}", side, e);
+            None
+        }
+    }
+}
+
+/// MM_SL_HEDGE_BID: add what the resting opposite-token bid bought since the last check (token
+/// balance over what the inventory holds) to the inventory and the hedged pair.
+async fn track_hedge_bid(state: &mut RunnerState, clob: &dyn ClobClient, now_ms_u: u64) {
+    let (Some(hb), Some(market)) = (state.hedge_bid.as_mut(), state.market.as_ref()) else {
+        return;
+    };
+    let position_token = if hb.token_id == market.token_id_up {
+        &market.token_id_down
+    } else {
+        &market.token_id_up
+    };
+    let Ok(Some(available)) = clob.get_available_balance(&hb.token_id).await else {
+        return;
+    };
+    let bought = floor_to_decimals(
+        (available - state.inventory.size(&hb.token_id)).min(hb.size - hb.filled),
+        SELL_SIZE_DECIMALS,
+    );
+    if bought < DUST_THRESHOLD {
+        return;
+    }
+    hb.filled += bought;
+    state
+        .inventory
+        .add_lot(&hb.token_id, hb.side, hb.price, bought, now_ms_u);
+    state.last_fill_ms = Some(now_ms_u);
+    let held_price = state
+        .inventory
+        .avg_entry(position_token)
+        .unwrap_or(Decimal::ZERO);
+    let held_size = state.inventory.size(position_token);
+    let (price_up, price_down, size_up, size_down) = match hb.side {
+        EntrySide::Up => (hb.price, held_price, hb.filled, held_size),
+        EntrySide::Down => (held_price, hb.price, held_size, hb.filled),
+    };
+    info!(
+        "[IntervalSniper] {}",
+        messages::sl_hedge_bought(
+            hb.side,
+            fmt_decimal_2(&hb.filled),
+            fmt_price(Some(&hb.price)),
+            fmt_decimal_2(&(price_up + price_down))
+        )
+    );
+    state.hedged_pair = Some(HedgedPair {
+        size_up,
+        size_down,
+        price_up,
+        price_down,
+        timestamp_ms: now_ms_u,
+    });
+    if hb.size - hb.filled < DUST_THRESHOLD {
+        debug!("[IntervalSniper] SL hedge bid {} filled", hb.order_id);
+        state.hedge_bid = None;
+    }
+}
+
+/// Heartbeat: per held token, size, average entry, mark (last best bid) and unrealized PnL, then
+/// the session's realized PnL (before fees) and closes. Logged when flat too. With a WS book, also
+/// each token's feed stats, warning when one token is silent while the other updates.
+fn log_heartbeat(
+    inventory: &Inventory,
+    market: &ResolvedMarket,
+    ws: Option<&ClobWsBook>,
+    now_ms_u: u64,
+) {
+    let mut positions = Vec::new();
+    for (label, token_id) in [("Up", &market.token_id_up), ("Down", &market.token_id_down)] {
+        let Some(avg) = inventory.avg_entry(token_id) else {
+            continue;
+        };
+        let held_secs = inventory
+            .lots(token_id)
+            .next()
+            .map(|l| now_ms_u.saturating_sub(l.timestamp_ms) / 1000)
+            .unwrap_or(0);
+        let mark = inventory.mark(token_id);
+        let upnl = mark
+            .map(|bid| fmt_decimal_4(&inventory.unrealized_pnl(token_id, bid)))
+            .unwrap_or_else(|| "-".to_string());
+        positions.push(format!(
+            "{} held {} avg_entry={} mark={} uPnL={} ({} lot(s), oldest {}s)",
+            label,
+            fmt_decimal_2(&inventory.size(token_id)),
+            fmt_decimal_4(&avg),
+            fmt_price(mark.as_ref()),
+            upnl,
+            inventory.lots(token_id).count(),
+            held_secs
+        ));
+    }
+    let stats = inventory.stats();
+    info!(
+        "[IntervalSniper] heartbeat {} | session realized={} closes={} ({}W/{}L)",
+        if positions.is_empty() {
+            "flat".to_string()
+        } else {
+            positions.join(", ")
+        },
+        fmt_decimal_4(&stats.realized_pnl()),
+        stats.trades(),
+        stats.wins,
+        stats.losses
+    );
+    let Some(ws) = ws else {
+        return;
+    };
+    let up = ws.asset_stats(&market.token_id_up);
+    let down = ws.asset_stats(&market.token_id_down);
+    info!(
+        "[IntervalSniper] heartbeat ws Up {} | Down {}",
+        feed_line(&up),
+        feed_line(&down)
+    );
+    let silent = |s: &WsAssetStats| {
+        s.last_message_age_ms
+            .is_none_or(|age| age > WS_STALE_SECS * 1000)
+    };
+    for (label, feed, other) in [("Up", &up, &down), ("Down", &down, &up)] {
+        if silent(feed) && !silent(other) {
+            let last = feed
+                .last_message_age_ms
+                .map_or("no message yet".to_string(), |age| {
+                    format!("last message {} s ago", age / 1000)
+                });
+            warn!(
+                "[IntervalSniper] ws feed for {} is silent ({}) while the other token updates; its book may be frozen",
+                label, last
+            );
+        }
+    }
+}
+
+/// One token's WS feed stats for the heartbeat.
+fn feed_line(s: &WsAssetStats) -> String {
+    format!(
+        "{:.1} msg/s, last {}, {} snapshot(s) / {} delta(s) / {} trade(s), {} REST refresh(es)",
+        s.messages_per_sec,
+        s.last_message_age_ms
+            .map_or("-".to_string(), |age| format!("{} ms ago", age)),
+        s.snapshots,
+        s.deltas,
+        s.trades,
+        s.rest_refreshes
+    )
+}
+
+/// Count a tick whose entry `reason` declined while an ask was in range (ticks out of range are
+/// not counted), logging each change of reason.
+fn decline_entry(declines: &mut DeclineCounters, in_range: bool, reason: DeclineReason) {
+    if in_range && declines.record(reason) {
+        info!(
+            "[IntervalSniper] entry declined with the ask in range: {}",
+            reason.as_str()
+        );
+    }
+}
+
+/// Top of one side's book for the status file and the decision trace.
+fn book_json(side: &Option<TopOfBookSide>) -> serde_json::Value {
+    let side = side.as_ref();
+    let dec = |d: Option<Decimal>| d.map(|d| d.to_string());
+    serde_json::json!({
+        "bid": dec(side.and_then(|s| s.best_bid)),
+        "bid_size": dec(side.and_then(|s| s.best_bid_size)),
+        "ask": dec(side.and_then(|s| s.best_ask)),
+        "ask_size": dec(side.and_then(|s| s.best_ask_size)),
+        "updated_ms": side.and_then(|s| s.updated_ms),
+        "source": side.and_then(|s| s.source).map(BookSource::as_str),
+    })
+}
+
+/// MM_DECISION_TRACE record of one entry evaluation: what the strategy saw and what it returned.
+fn trace_record(state: &RunnerState, ctx: &TickContext, actions: &[Action]) -> serde_json::Value {
+    let market = ctx.market;
+    let config = ctx.config;
+    let position = |token_id: &str| {
+        serde_json::json!({
+            "size": state.inventory.size(token_id).to_string(),
+            "avg_entry": state.inventory.avg_entry(token_id).map(|p| p.round_dp(4).to_string()),
+        })
+    };
+    let range = |side: EntrySide| {
+        let s = config.side(side);
+        [s.min_buy_price.to_string(), s.max_buy_price.to_string()]
+    };
+    serde_json::json!({
+        "ts_ms": ctx.now_ms,
+        "slug": market.slug,
+        "strategy": state.strategy.name(),
+        "secs_to_close": ctx.secs_to_close,
+        "tick_size": ctx.tick_size.to_string(),
+        "min_order_size": ctx.min_order_size.to_string(),
+        "spot_move": ctx.spot_move.map(|m| m.to_string()),
+        "book": {
+            "up": book_json(&ctx.top.token_id_up),
+            "down": book_json(&ctx.top.token_id_down),
+        },
+        "position": {
+            "up": position(&market.token_id_up),
+            "down": position(&market.token_id_down),
+        },
+        "flags": {
+            "interval_phase": state.phases.interval().as_str(),
+            "position_phase": state.phases.position().as_str(),
+            "trades_this_interval": state.trades_this_interval,
+            "shares_this_interval": state.total_shares_this_interval.to_string(),
+            "last_fill_ms": state.last_fill_ms,
+            "allow_buy_up": config.allow_buy_up,
+            "allow_buy_down": config.allow_buy_down,
+        },
+        "buy_range": { "up": range(EntrySide::Up), "down": range(EntrySide::Down) },
+        "order_strategy": format!("{:?}", config.order_strategy),
+        "side_policy": format!("{:?}", config.side_policy),
+        "actions": actions
+            .iter()
+            .map(|a| {
+                let Action::Buy { side, price, size_available, order_type } = a;
+                serde_json::json!({
+                    "buy": format!("{:?}", side),
+                    "price": price.to_string(),
+                    "size_available": size_available.to_string(),
+                    "order_type": order_type_label(*order_type),
+                })
+            })
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Write the MM_DECISION_TRACE buffer, logging where (or why not).
+fn dump_decision_trace(trace: &DecisionTrace, why: &str, now_ms: u64) {
+    match trace.dump(now_ms) {
+        Ok(path) => info!(
+            "[IntervalSniper] decision trace ({}, {} evaluation(s)) written to {}",
+            why,
+            trace.len(),
+            path.display()
+        ),
+        Err(e) => warn!("[IntervalSniper] decision trace dump failed: {:#}", e),
+    }
+}
+
+/// Snapshot written to MM_STATUS_FILE: market, top of book, position, live orders, trades this
+/// interval and session results.
+fn status_json(
+    state: &RunnerState,
+    market: &ResolvedMarket,
+    top: &TopOfBook,
+    secs_to_close: u64,
+    tracker: Option<&OrderTracker>,
+    now_ms_u: u64,
+) -> serde_json::Value {
+    let dec = |d: Option<Decimal>| d.map(|d| d.to_string());
+    let position = |token_id: &str| {
+        let size = state.inventory.size(token_id);
+        let mark = state.inventory.mark(token_id);
+        let upnl = mark
+            .filter(|_| size > Decimal::ZERO)
+            .map(|bid| state.inventory.unrealized_pnl(token_id, bid).round_dp(6));
+        let locked = tracker.map(|t| FundsLock::scan(t, token_id, &state.exit_orders).locked());
+        serde_json::json!({
+            "size": size.to_string(),
+            "avg_entry": dec(state.inventory.avg_entry(token_id).map(|p| p.round_dp(4))),
+            "mark": dec(mark),
+            "unrealized_pnl": dec(upnl),
+            "locked_by_orders": dec(locked),
+        })
+    };
+    let outcome = |token_id: &str| {
+        if token_id == market.token_id_up {
+            "Up"
+        } else if token_id == market.token_id_down {
+            "Down"
+        } else {
+            "other"
+        }
+    };
+    let orders: Vec<serde_json::Value> = tracker
+        .map(|t| {
+            t.open()
+                .map(|o| {
+                    serde_json::json!({
+                        "order_id": o.exchange_id,
+                        "outcome": outcome(&o.token_id),
+                        "side": format!("{:?}", o.side),
+                        "type": order_type_label(o.order_type),
+                        "price": o.price.to_string(),
+                        "size": o.size.to_string(),
+                        "filled": o.filled.to_string(),
+                        "state": o.state.as_str(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let stats = state.inventory.stats();
+    serde_json::json!({
+        "updated_ms": now_ms_u,
+        "dry_run": state.config.dry_run,
+        "slug": market.slug,
+        "interval_start_unix": market.interval_start_unix,
+        "close_time_unix": market.close_time_unix,
+        "secs_to_close": secs_to_close,
+        "book": {
+            "up": book_json(&top.token_id_up),
+            "down": book_json(&top.token_id_down),
+        },
+        "ws": state.ws_book.as_ref().map(|ws| serde_json::json!({
+            "state": format!("{:?}", ws.connection_state()),
+            "up": ws.asset_stats(&market.token_id_up).to_json(),
+            "down": ws.asset_stats(&market.token_id_down).to_json(),
+        })),
+        "position": {
+            "up": position(&market.token_id_up),
+            "down": position(&market.token_id_down),
+            "take_profit": dec(state
+                .pending_auto_sell
+                .as_ref()
+                .filter(|_| state.phases.exits_armed())
+                .map(|tp| tp.target_price)),
+            "stop_loss": dec(state
+                .pending_stop_loss
+                .as_ref()
+                .filter(|_| state.phases.exits_armed())
+                .map(|sl| sl.trigger_price)),
+            "holding_to_resolution": state.holding_to_resolution,
+            "hedged_pair": state.hedged_pair.is_some(),
+            "hedge_bid": state.hedge_bid.as_ref().map(|hb| hb.price.to_string()),
+        },
+        "carryover": state.carryovers.iter().map(CarriedPosition::to_json).collect::<Vec<_>>(),
+        "orders": orders,
+        "orders_in_flight": state.orders.has_in_flight(),
+        "trades_this_interval": state.trades_this_interval,
+        "shares_this_interval": state.total_shares_this_interval.to_string(),
+        "session": {
+            "closes": stats.trades(),
+            "wins": stats.wins,
+            "losses": stats.losses,
+            "scratches": stats.scratches,
+            "win_rate": dec(stats.win_rate().map(|w| w.round_dp(4))),
+            "realized_pnl": stats.realized_pnl().round_dp(6).to_string(),
+            "declined_entries": entry_gate::counts_json(state.declines.session()),
+        },
+    })
+}
+
+/// Wallet holdings from the data API (positions of every funder). With `log_all`, log each one;
+/// then warn for each `(label, token_id, inventory size)` the wallet holds a different size of
+/// (fills the bookkeeping missed, or shares left from another session).
+async fn check_positions(
+    http: Client,
+    data_api_url: String,
+    funders: Vec<String>,
+    expected: Vec<(&'static str, String, Decimal)>,
+    log_all: bool,
+) {
+    let positions = match fetch_all_positions(&http, &data_api_url, &funders).await {
+        Ok(p) => p,
+        Err(e) => {
+            warn!("[Positions] fetch failed: {:#}", e);
+            return;
+        }
+    };
+    if log_all {
+        info!("[Positions] wallet holds {} position(s)", positions.len());
+        for p in &positions {
+            info!(
+                "[Positions]   {} {}: {} sh avg {} (now {}){}",
+                p.title,
+                p.outcome,
+                fmt_decimal_2(&p.size),
+                fmt_decimal_4(&p.avg_price),
+                fmt_price(Some(&p.cur_price)),
+                if p.redeemable { " resolved" } else { "" }
+            );
+        }
+    }
+    for (label, token_id, inventory_size) in expected {
+        let held: Decimal = positions
+            .iter()
+            .filter(|p| p.asset == token_id)
+            .map(|p| p.size)
+            .sum();
+        if (held - inventory_size).abs() >= dec!(0.01) {
+            warn!(
+                "[Positions] {} token: wallet holds {} sh, inventory has {}",
+                label,
+                fmt_decimal_2(&held),
+                fmt_decimal_2(&inventory_size)
+            );
+        }
+    }
+}
+
+/// Balance-allowance against the bookkeeping: each `(label, token_id, inventory size)` against
+/// the available shares, and the collateral change since `baseline` against the inventory's cash
+/// flow over the same time. The first check of an interval only records the baseline. Drift here
+/// is what later shows up as "not enough balance" rejections on sells and entries.
+async fn check_balance_drift(
+    clob: Arc<Box<dyn ClobClient>>,
+    expected: Vec<(&'static str, String, Decimal)>,
+    cash_flow: Decimal,
+    baseline: Arc<std::sync::Mutex<Option<(Decimal, Decimal)>>>,
+    shares_tolerance: Decimal,
+    usdc_tolerance: Decimal,
+) {
+    for (label, token_id, inventory_size) in expected {
+        let available = match clob.get_available_balance(&token_id).await {
+            Ok(Some(a)) => a,
+            Ok(None) => continue,
+            Err(e) => {
+                debug!("[BalanceDrift] {} balance fetch failed: {:#}", label, e);
+                continue;
+            }
+        };
+        if (available - inventory_size).abs() > shares_tolerance {
+            warn!(
+                "[BalanceDrift] {} token: balance-allowance has {} sh, inventory has {} (drift {})",
+                label,
+                fmt_decimal_2(&available),
+                fmt_decimal_2(&inventory_size),
+                fmt_decimal_2(&(available - inventory_size))
+            );
+        }
+    }
+    let usdc = match clob.get_collateral_balance().await {
+        Ok(Some(u)) => u,
+        Ok(None) => return,
+        Err(e) => {
+            debug!("[BalanceDrift] collateral fetch failed: {:#}", e);
+            return;
+        }
+    };
+    let Ok(mut baseline) = baseline.lock() else {
+        return;
+    };
+    let Some((base_usdc, base_flow)) = *baseline else {
+        *baseline = Some((usdc, cash_flow));
+        return;
+    };
+    let expected_usdc = base_usdc + cash_flow - base_flow;
+    let drift = usdc - expected_usdc;
+    if drift.abs() > usdc_tolerance {
+        warn!(
+            "[BalanceDrift] USDC: balance-allowance has {}, bookkeeping expects {} (drift {})",
+            fmt_decimal_4(&usdc),
+            fmt_decimal_4(&expected_usdc),
+            fmt_decimal_4(&drift)
+        );
+    }
+}
+
+/// Realized PnL rebuilt from the trades history since the session started, against what the
+/// session log recorded for TP/SL sells. A difference means sells the bookkeeping got wrong.
+async fn reconcile_trades(clob: &dyn ClobClient, log: &mut SessionLog) {
+    let fills = match clob.get_trades(log.session_start_ms() / 1000).await {
+        Ok(f) => f,
+        Err(e) => {
+            warn!("[Trades] trades history fetch failed: {:#}", e);
+            return;
+        }
+    };
+    let pnl = realized_pnl(&fills);
+    info!(
+        "[Trades] {} buy(s), {} sell(s) this session: realized PnL {} (fees ~{}) | session log TP/SL PnL {}",
+        pnl.buys,
+        pnl.sells,
+        fmt_decimal_4(&pnl.realized),
+        fmt_decimal_4(&pnl.fees),
+        fmt_decimal_4(&log.sold_pnl())
+    );
+    if pnl.unmatched_sold > Decimal::ZERO {
+        warn!(
+            "[Trades] {} sh sold were bought before the session (not in realized PnL)",
+            fmt_decimal_2(&pnl.unmatched_sold)
+        );
+    }
+    let diff = pnl.realized - log.sold_pnl();
+    if diff.abs() >= dec!(0.01) {
+        warn!(
+            "[Trades] realized PnL from executions differs from the session log by {}",
+            fmt_decimal_4(&diff)
+        );
+    }
+    let _ = log.log_trades_reconcile(&pnl);
+}
+
+/// Entry order result from the order manager: open the position on a fill.
+fn on_entry_outcome(state: &mut RunnerState, outcome: OrderOutcome, now_ms_u: u64) {
+    let OrderOutcome {
+        intent,
+        result,
+        elapsed_ms,
+        ..
+    } = outcome;
+    // Set while a chase replacement is in flight: this is its result.
+    let chase = state.resting_buy.take();
+    if !result.success {
+        if let Some(msg) = result.error_msg {
+            warn!("[IntervalSniper]  FAIL  BUY   {} ({} ms)", msg, elapsed_ms);
+        }
+        if let Some(rb) = chase {
+            finish_chase(state, rb, Decimal::ZERO, now_ms_u);
+        }
+        return;
+    }
+    // Position must use actual filled_size from CLOB (FAK can be partial; TP/SL must sell only what we have).
+    let filled = filled_buy_size(result.filled_size, intent.params.size);
+    if settled(state, &intent.slug) {
+        // A GTC answered late rests on a closed market: only what it filled is held.
+        let filled = match resting_buy(&intent, &result) {
+            Some(_) => result
+                .filled_size
+                .unwrap_or_default()
+                .min(intent.params.size),
+            None => filled,
+        };
+        if filled > Decimal::ZERO {
+            carry_late_fill(state, &intent, filled, elapsed_ms, now_ms_u);
+        }
+        return;
+    }
+    if chase.is_none() {
+        state.trades_this_interval += 1;
+    }
+    if state.config.max_chase_ticks > 0 {
+        if let Some(order_id) = resting_buy(&intent, &result) {
+            let mut rb = chase.unwrap_or(RestingBuy {
+                order_id: None,
+                intent: intent.clone(),
+                chases: 0,
+                fills: Vec::new(),
+            });
+            let filled_now = result
+                .filled_size
+                .unwrap_or(Decimal::ZERO)
+                .min(intent.params.size);
+            rb.order_id = Some(order_id);
+            rb.intent = intent;
+            rb.add_fill(filled_now);
+            rb.intent.params.size -= filled_now;
+            info!(
+                "[IntervalSniper] GTC buy resting: {} sh @ {} (chase {}/{})",
+                fmt_decimal_2(&rb.intent.params.size),
+                fmt_decimal_2(&rb.intent.params.price),
+                rb.chases,
+                state.config.max_chase_ticks
+            );
+            state.resting_buy = Some(rb);
+            return;
+        }
+    }
+    debug!("[IntervalSniper] buy filled in {} ms", elapsed_ms);
+    match chase {
+        Some(mut rb) => {
+            rb.intent.params.price = intent.params.price;
+            finish_chase(state, rb, filled, now_ms_u);
+        }
+        None => open_entry(state, &intent, filled, intent.params.price, now_ms_u),
+    }
+}
+
+/// Results the order manager reported since the last call: entries, pairs and passive TP asks.
+fn apply_order_outcomes(state: &mut RunnerState, now_ms_u: u64) {
+    for outcome in state.orders.poll() {
+        if outcome.intent.params.side == OrderSide::Sell {
+            on_passive_tp_outcome(state, outcome, now_ms_u);
+        } else {
+            on_entry_outcome(state, outcome, now_ms_u);
+        }
+    }
+    for outcome in state.orders.poll_batches() {
+        on_pair_outcome(state, outcome, now_ms_u);
+    }
+}
+
+/// At the switch, wait up to [SWITCH_DRAIN_MS] for orders still in flight and book their results
+/// against the market they were placed on, before it is settled.
+async fn drain_in_flight(state: &mut RunnerState, market: &ResolvedMarket, clock: &dyn Clock) {
+    if !state.orders.has_in_flight() {
+        return;
+    }
+    let started = clock.now_ms();
+    let deadline = started.saturating_add(SWITCH_DRAIN_MS);
+    while state.orders.has_in_flight() && clock.now_ms() < deadline {
+        clock.sleep(Duration::from_millis(DRAIN_POLL_MS)).await;
+        apply_order_outcomes(state, clock.now_ms());
+    }
+    if state.orders.has_in_flight() {
+        warn!(
+            "[IntervalSniper] orders still in flight {} ms after the close of {}",
+            SWITCH_DRAIN_MS, market.slug
+        );
+    } else {
+        debug!(
+            "[IntervalSniper] in-flight orders of {} done {} ms after the close",
+            market.slug,
+            clock.now_ms().saturating_sub(started)
+        );
+    }
+}
+
+/// The market of `slug` was settled (or left) already: its orders' results arrive after the
+/// switch.
+fn settled(state: &RunnerState, slug: &str) -> bool {
+    state
+        .closed_market
+        .as_ref()
+        .is_some_and(|c| c.market.slug == slug)
+        || state.market.as_ref().is_none_or(|m| m.slug != slug)
+}
+
+/// A buy answered after its market was settled: carry the fill with the market until it resolves
+/// (RESOLUTION close at the payout). Only the last settled market is known; a fill on an older one
+/// is not managed.
+fn carry_late_fill(
+    state: &mut RunnerState,
+    intent: &OrderIntent,
+    filled: Decimal,
+    elapsed_ms: u64,
+    now_ms_u: u64,
+) {
+    let index = match state
+        .carryovers
+        .iter()
+        .position(|c| c.market.slug == intent.slug)
+    {
+        Some(i) => i,
+        None => match state
+            .closed_market
+            .as_ref()
+            .filter(|c| c.market.slug == intent.slug)
+        {
+            Some(closed) => {
+                state.carryovers.push(closed.clone());
+                state.carryovers.len() - 1
+            }
+            None => {
+                warn!(
+                    "[IntervalSniper] buy of {} sh on {} filled after the interval switch ({} ms); not managed",
+                    fmt_decimal_2(&filled),
+                    intent.slug,
+                    elapsed_ms
+                );
+                return;
+            }
+        },
+    };
+    let carried = &mut state.carryovers[index];
+    let lot = Lot {
+        side: intent.entry_side,
+        entry_price: intent.params.price,
+        size: filled,
+        timestamp_ms: now_ms_u,
+    };
+    if intent.params.token_id == carried.market.token_id_up {
+        carried.lots_up.push(lot);
+    } else {
+        carried.lots_down.push(lot);
+    }
+    warn!(
+        "[IntervalSniper] buy of {} sh on {} filled after the interval switch ({} ms)",
+        fmt_decimal_2(&filled),
+        intent.slug,
+        elapsed_ms
+    );
+    info!(
+        "[IntervalSniper] {}",
+        messages::carried_over(
+            &intent.slug,
+            fmt_decimal_2(&carried.size()),
+            carried.inferred.0,
+            carried.inferred.1
+        )
+    );
+}
+
+/// A leg of a failed batch: filled by what its token balance holds beyond the inventory.
+fn reconciled_leg(
+    state: &RunnerState,
+    intent: &OrderIntent,
+    balance: Option<Decimal>,
+    error: &str,
+) -> PlaceOrderResult {
+    let Some(balance) = balance else {
+        warn!(
+            "[IntervalSniper] pair leg on {} unknown (balance unavailable); check the account",
+            intent.params.token_id
+        );
+        return PlaceOrderResult::failed(format!("{} (balance unknown)", error));
+    };
+    let held = state.inventory.size(&intent.params.token_id);
+    let filled = (balance - held).max(Decimal::ZERO).min(intent.params.size);
+    if filled.is_zero() {
+        return PlaceOrderResult::failed(error.to_string());
+    }
+    PlaceOrderResult {
+        order_id: None,
+        success: true,
+        error_msg: None,
+        error: None,
+        filled_size: Some(filled),
+        http_status: None,
+        avg_price: None,
+        failed_parts: Vec::new(),
+    }
+}
+
+/// Dual-side entry result: hold a filled pair to resolution, manage a lone filled leg with TP/SL.
+fn on_pair_outcome(state: &mut RunnerState, outcome: BatchOutcome, now_ms_u: u64) {
+    let BatchOutcome {
+        intents,
+        results,
+        elapsed_ms,
+        ..
+    } = outcome;
+    let results = match results {
+        Ok(results) => results,
+        Err(BatchError {
+            error,
+            balances,
+            resolved,
+        }) => {
+            warn!(
+                "[IntervalSniper] pair batch failed ({}) after {} ms; unknown legs read from balances {:?}",
+                error, elapsed_ms, balances
+            );
+            let mut resolved = resolved.into_iter();
+            intents
+                .iter()
+                .zip(balances)
+                .map(|(intent, balance)| match resolved.next().flatten() {
+                    Some(r) => r,
+                    None => reconciled_leg(state, intent, balance, &error),
+                })
+                .collect()
+        }
+    };
+    let filled_leg = |i: usize| {
+        let intent = intents.get(i)?;
+        results
+            .get(i)
+            .filter(|r| r.success)
+            .map(|r| filled_buy_size(r.filled_size, intent.params.size))
+    };
+    let (Some(up), Some(down)) = (intents.first(), intents.get(1)) else {
+        return;
+    };
+    let fills = (filled_leg(0), filled_leg(1));
+    if settled(state, &up.slug) {
+        for (intent, filled) in [(up, fills.0), (down, fills.1)] {
+            if let Some(filled) = filled {
+                carry_late_fill(state, intent, filled, elapsed_ms, now_ms_u);
+            }
+        }
+        return;
+    }
+    match fills {
+        (Some(size_up), Some(size_down)) => {
+            let (price_up, price_down) = (up.params.price, down.params.price);
+            state.trades_this_interval += 1;
+            state.total_shares_this_interval += size_up.min(size_down);
+            state.hedged_pair = Some(HedgedPair {
+                size_up,
+                size_down,
+                price_up,
+                price_down,
+                timestamp_ms: now_ms_u,
+            });
+            state.inventory.add_lot(
+                &up.params.token_id,
+                EntrySide::Up,
+                price_up,
+                size_up,
+                now_ms_u,
+            );
+            state.inventory.add_lot(
+                &down.params.token_id,
+                EntrySide::Down,
+                price_down,
+                size_down,
+                now_ms_u,
+            );
+            state.last_fill_ms = Some(now_ms_u);
+            info!(
+                "[IntervalSniper]  BUY   PAIR  Up @ {} size={}   Down @ {} size={}   cost={} (held to resolution)",
+                fmt_decimal_2(&price_up),
+                fmt_decimal_2(&size_up),
+                fmt_decimal_2(&price_down),
+                fmt_decimal_2(&size_down),
+                fmt_decimal_2(&(price_up + price_down))
+            );
+        }
+        (Some(filled), None) | (None, Some(filled)) => {
+            // One leg missed: manage the filled leg as a normal position with TP/SL.
+            let leg = if results[0].success { up } else { down };
+            warn!(
+                "[IntervalSniper] pair entry: only {:?} leg filled, managing it with TP/SL",
+                leg.entry_side
+            );
+            state.trades_this_interval += 1;
+            state.total_shares_this_interval += filled;
+            open_position(
+                state,
+                &leg.params.token_id,
+                leg.entry_side,
+                filled,
+                leg.params.price,
+                now_ms_u,
+            );
+        }
+        (None, None) => {
+            for r in results.iter() {
+                if let Some(ref msg) = r.error_msg {
+                    warn!("[IntervalSniper]  FAIL  PAIR  {}", msg);
+                }
+            }
+        }
+    }
+}
+
+/// Count an entry fill of `filled` at `price` and open the position for it.
+fn open_entry(
+    state: &mut RunnerState,
+    intent: &OrderIntent,
+    filled: Decimal,
+    price: Decimal,
+    now_ms_u: u64,
+) {
+    state.total_shares_this_interval += filled;
+    state.strategy.on_fill(&FillEvent {
+        token_id: intent.params.token_id.clone(),
+        side: OrderSide::Buy,
+        entry_side: intent.entry_side,
+        price,
+        size: filled,
+        timestamp_ms: now_ms_u,
+    });
+    open_position(
+        state,
+        &intent.params.token_id,
+        intent.entry_side,
+        filled,
+        price,
+        now_ms_u,
+    );
+}
+
+/// End a chase: open the position for everything bought (plus `last_fill` at the current order's
+/// price) at the volume-weighted average price, so TP/SL and PnL use what the entry really cost;
+/// with nothing bought the entry slot is free again.
+fn finish_chase(state: &mut RunnerState, mut rb: RestingBuy, last_fill: Decimal, now_ms_u: u64) {
+    rb.add_fill(last_fill);
+    let Some(price) = fills_vwap(&rb.fills) else {
+        info!("[IntervalSniper] chased buy ended without a fill");
+        state.trades_this_interval = state.trades_this_interval.saturating_sub(1);
+        if state.trades_this_interval == 0 {
+            state.phases.on_entry_void();
+        }
+        return;
+    };
+    let filled = rb.filled();
+    if rb.fills.len() > 1 {
+        info!(
+            "[IntervalSniper] entry filled in {} parts: {} → {} sh @ {} avg (last order @ {})",
+            rb.fills.len(),
+            rb.fills
+                .iter()
+                .map(|(p, s)| format!("{} @ {}", fmt_decimal_2(s), fmt_decimal_2(p)))
+                .collect::<Vec<_>>()
+                .join(", "),
+            fmt_decimal_2(&filled),
+            price,
+            fmt_decimal_2(&rb.intent.params.price)
+        );
+        if let Some(ref mut log) = state.session_log {
+            let _ = log.log_entry_fills(
+                &rb.intent.slug,
+                rb.intent.entry_side,
+                &rb.fills,
+                price,
+                rb.intent.params.price,
+            );
+        }
+    }
+    open_entry(state, &rb.intent, filled, price, now_ms_u);
+}
+
+/// After a resting buy was canceled: the part of it that filled while resting (the token balance
+/// above what we hold), added to the chase's fills.
+async fn add_resting_partial(
+    state: &RunnerState,
+    clob: &dyn ClobClient,
+    rb: &mut RestingBuy,
+) -> Decimal {
+    let token_id = &rb.intent.params.token_id;
+    let held = state.inventory.size(token_id) + rb.filled();
+    let partial = clob
+        .get_available_balance(token_id)
+        .await
+        .ok()
+        .flatten()
+        .map_or(Decimal::ZERO, |a| (a - held).max(Decimal::ZERO))
+        .min(rb.intent.params.size);
+    rb.add_fill(partial);
+    partial
+}
+
+/// MM_MAX_CHASE_TICKS: follow the ask with the resting entry buy. The ask at or below the bid
+/// means it matched; more than one tick above it, the buy is canceled and its unfilled rest
+/// re-placed at the ask (within the chase count and MM_MAX_CHASE_PRICE).
+async fn chase_resting_buy(
+    state: &mut RunnerState,
+    clob: &dyn ClobClient,
+    top: &TopOfBook,
+    now_ms_u: u64,
+) {
+    let Some(rb) = state.resting_buy.as_ref() else {
+        return;
+    };
+    let Some(order_id) = rb.order_id.clone() else {
+        return;
+    };
+    let side_book = match rb.intent.entry_side {
+        EntrySide::Up => &top.token_id_up,
+        EntrySide::Down => &top.token_id_down,
+    };
+    let Some(ask) = side_book.as_ref().and_then(|s| s.best_ask) else {
+        return;
+    };
+    let price = rb.intent.params.price;
+    let tick_size = market_tick_size(state);
+    if ask <= price {
+        // The ask came down to our bid: the resting buy matched.
+        let rb = state.resting_buy.take().expect("resting buy checked above");
+        let rest = rb.intent.params.size;
+        finish_chase(state, rb, rest, now_ms_u);
+        return;
+    }
+    if ask <= price + tick_size
+        || rb.chases >= state.config.max_chase_ticks
+        || ask > state.config.max_chase_price
+        || state.orders.has_in_flight()
+    {
+        return;
+    }
+    let canceled = match clob.cancel_order(&order_id).await {
+        Ok(r) => !r.canceled.is_empty(),
+        Err(e) => {
+            warn!(
+                "[IntervalSniper] chase: cancel of {} failed: {:#}",
+                order_id, e
+            );
+            return;
+        }
+    };
+    let mut rb = state.resting_buy.take().expect("resting buy checked above");
+    if !canceled {
+        // Off the book before the cancel: it filled.
+        let rest = rb.intent.params.size;
+        finish_chase(state, rb, rest, now_ms_u);
+        return;
+    }
+    let partial = add_resting_partial(state, clob, &mut rb).await;
+    let rest = (rb.intent.params.size - partial).round_dp(2);
+    let min_size = state
+        .market
+        .as_ref()
+        .map_or(Decimal::ZERO, |m| m.min_order_size);
+    if rest <= Decimal::ZERO || rest < min_size {
+        finish_chase(state, rb, Decimal::ZERO, now_ms_u);
+        return;
+    }
+    rb.chases += 1;
+    let new_price = round_to_tick(ask, tick_size);
+    info!(
+        "[IntervalSniper] chase {}/{}: ask {} left the bid {} behind, re-placing {} sh @ {}",
+        rb.chases,
+        state.config.max_chase_ticks,
+        fmt_decimal_2(&ask),
+        fmt_decimal_2(&price),
+        fmt_decimal_2(&rest),
+        fmt_decimal_2(&new_price)
+    );
+    let mut intent = rb.intent.clone();
+    intent.params.price = new_price;
+    intent.params.size = rest;
+    intent.delay_ms = 0;
+    rb.order_id = None;
+    match state.orders.submit(intent) {
+        Ok(_) => state.resting_buy = Some(rb),
+        Err(e) => {
+            warn!("[IntervalSniper] chase: re-place failed: {}", e);
+            finish_chase(state, rb, Decimal::ZERO, now_ms_u);
+        }
+    }
+}
+
+/// MM_SELL_GTD_MARGIN_SECS: a GTC sell goes out GTD, expiring the margin before the market
+/// closes, so it can never execute after the interval. Too close to the close for the CLOB's
+/// minimum GTD lifetime it is sent FAK instead.
+fn bounded_sell_tif(
+    config: &Config,
+    tif: SellOrderTimeInForce,
+    close_time_unix: u64,
+    now_unix: u64,
+) -> SellOrderTimeInForce {
+    if tif != SellOrderTimeInForce::Gtc || config.sell_gtd_margin_secs == 0 {
+        return tif;
+    }
+    let expiration_unix = close_time_unix.saturating_sub(config.sell_gtd_margin_secs);
+    if expiration_unix < now_unix + GTD_MIN_LIFETIME_SECS {
+        SellOrderTimeInForce::Fak
+    } else {
+        SellOrderTimeInForce::Gtd { expiration_unix }
+    }
+}
+
+/// MM_AUTO_UNLOCK: free the shares a TP / SL sell needs from our resting orders on the token.
+/// When no resting sell there outranks the exit, every open order of the token is canceled (also
+/// ones the tracker does not know, e.g. left by a previous run); otherwise only the sells the exit
+/// outranks, and the rest keep their shares. Returns whether anything was asked to cancel.
+async fn unlock_for_exit(
+    policy: UnlockPolicy,
+    exit_orders: &HashMap<String, SellKind>,
+    clob: &dyn ClobClient,
+    tracker: &std::sync::Mutex<OrderTracker>,
+    token_id: &str,
+    exit: SellKind,
+) -> bool {
+    let label = match exit {
+        SellKind::TakeProfit => "TP",
+        SellKind::StopLoss => "SL",
+    };
+    let lock = tracker
+        .lock()
+        .map(|t| FundsLock::scan(&t, token_id, exit_orders))
+        .unwrap_or_default();
+    let protected = lock.protected(policy, exit);
+    if protected.is_zero() {
+        match clob.cancel_orders_for_token(token_id).await {
+            Err(e) => warn!(
+                "[IntervalSniper] cancel orders before {} failed: {} (continuing with sell)",
+                label, e
+            ),
+            Ok(res) if !res.not_canceled.is_empty() => {
+                warn!(
+                    "[IntervalSniper] cancel before {}: {} order(s) not canceled, balance may still be locked",
+                    label,
+                    res.not_canceled.len()
+                );
+            }
+            _ => {}
+        }
+        return true;
+    }
+    let cancelable = lock.cancelable(policy, exit);
+    info!(
+        "[FundsLock] {}: {} of {} sh locked by resting sells stay ({}), canceling {} order(s)",
+        label,
+        fmt_decimal_2(&protected),
+        fmt_decimal_2(&lock.locked()),
+        match policy {
+            UnlockPolicy::TpPriority => "tp_priority",
+            UnlockPolicy::SlPriority => "sl_priority",
+        },
+        cancelable.len()
+    );
+    for order in &cancelable {
+        if let Err(e) = clob.cancel_order(&order.order_id).await {
+            warn!(
+                "[FundsLock] cancel {} before {} failed: {}",
+                order.order_id, label, e
+            );
+        }
+    }
+    !cancelable.is_empty()
+}
+
+/// MM_TP_PASSIVE_MS: once TP is due, post it as a post-only ask at the TP price (unless the bid
+/// already reached it); while it rests, a bid at or above it means it matched. The order manager
+/// cancels it at its max age.
+async fn manage_passive_tp(
+    state: &mut RunnerState,
+    clob: &dyn ClobClient,
+    top: &TopOfBook,
+    secs_to_close: u64,
+    now_ms_u: u64,
+) {
+    let tp_on = state.config.enable_auto_sell || state.config.auto_sell_at_max_price;
+    if !tp_on || state.holding_to_resolution || !state.phases.exits_armed() {
+        return;
+    }
+    let (Some(tp), Some(market)) = (state.pending_auto_sell.as_ref(), state.market.as_ref()) else {
+        return;
+    };
+    let is_up = tp.token_id == market.token_id_up;
+    let side_book = if is_up {
+        &top.token_id_up
+    } else {
+        &top.token_id_down
+    };
+    let best_bid = side_book
+        .as_ref()
+        .and_then(|s| s.best_bid)
+        .unwrap_or(Decimal::ZERO);
+    match state.passive_tp {
+        PassiveTp::Unposted => {
+            let elapsed_sec = now_ms_u.saturating_sub(tp.placed_at_ms) / 1000;
+            if elapsed_sec < state.config.min_seconds_after_buy_before_auto_sell as u64 {
+                return;
+            }
+            let tick_size = market.tick_size;
+            let price = scheduled_tp_price(&state.config, tp, secs_to_close, tick_size);
+            let tif = bounded_sell_tif(
+                &state.config,
+                SellOrderTimeInForce::Gtc,
+                market.close_time_unix,
+                now_ms_u / 1000,
+            );
+            let (order_type, expiration_unix) = match tif {
+                SellOrderTimeInForce::Gtd { expiration_unix } => {
+                    (OrderType::Gtd, Some(expiration_unix))
+                }
+                SellOrderTimeInForce::Gtc => (OrderType::Gtc, None),
+                // Too close to the close to rest.
+                _ => (OrderType::Fak, None),
+            };
+            if best_bid >= price - state.config.take_profit_price_margin
+                || matches!(order_type, OrderType::Fak)
+            {
+                // A post-only ask would cross (or could not rest): TP sells at the bid right away.
+                state.passive_tp = PassiveTp::Done;
+                return;
+            }
+            let intent = OrderIntent {
+                params: LimitOrderParams {
+                    token_id: tp.token_id.clone(),
+                    side: OrderSide::Sell,
+                    price,
+                    size: tp.size,
+                    expiration_unix,
+                    post_only: true,
+                    fee_rate_bps: None,
+                    min_order_size: None,
+                },
+                order_type,
+                delay_ms: 0,
+                slug: market.slug.clone(),
+                entry_side: if is_up {
+                    EntrySide::Up
+                } else {
+                    EntrySide::Down
+                },
+                max_age_ms: state.config.take_profit_passive_ms,
+            };
+            state.passive_tp = match state.orders.submit(intent) {
+                Ok(_) => PassiveTp::InFlight,
+                Err(e) => {
+                    warn!("[IntervalSniper] passive TP ask not submitted: {}", e);
+                    PassiveTp::Done
+                }
+            };
+        }
+        PassiveTp::Resting {
+            ref order_id,
+            price,
+        } if best_bid >= price => {
+            // The bid reached the ask: it matched (what the cancel leaves is sold at the bid).
+            let order_id = order_id.clone();
+            let canceled = clob
+                .cancel_order(&order_id)
+                .await
+                .is_ok_and(|r| !r.canceled.is_empty());
+            settle_passive_tp(state, clob, canceled, price, now_ms_u).await;
+        }
+        _ => {}
+    }
+}
+
+/// Result of the passive TP ask: resting, matched on arrival, or rejected (TP sells at the bid).
+fn on_passive_tp_outcome(state: &mut RunnerState, outcome: OrderOutcome, now_ms_u: u64) {
+    let OrderOutcome { intent, result, .. } = outcome;
+    let same_market = state.market.as_ref().is_some_and(|m| m.slug == intent.slug);
+    if state.passive_tp != PassiveTp::InFlight || !same_market {
+        debug!("[IntervalSniper] passive TP result for a position no longer open");
+        return;
+    }
+    if !result.success {
+        warn!(
+            "[IntervalSniper] passive TP ask not posted ({}); selling at the bid",
+            result.error_msg.as_deref().unwrap_or("rejected")
+        );
+        state.passive_tp = PassiveTp::Done;
+        return;
+    }
+    if let Some(order_id) = resting_order(&intent, &result) {
+        info!(
+            "[IntervalSniper] passive TP ask resting: {} sh @ {} for up to {} ms",
+            fmt_decimal_2(&intent.params.size),
+            fmt_decimal_2(&intent.params.price),
+            state.config.take_profit_passive_ms
+        );
+        state
+            .exit_orders
+            .insert(order_id.clone(), SellKind::TakeProfit);
+        state.passive_tp = PassiveTp::Resting {
+            order_id,
+            price: intent.params.price,
+        };
+        return;
+    }
+    state.passive_tp = PassiveTp::Done;
+    record_tp_fill(state, intent.params.size, intent.params.price, now_ms_u);
+}
+
+/// The passive TP ask reached MM_TP_PASSIVE_MS and was canceled (or was already gone).
+async fn on_passive_tp_expired(
+    state: &mut RunnerState,
+    clob: &dyn ClobClient,
+    expiry: OrderExpiry,
+) {
+    let current = matches!(
+        &state.passive_tp,
+        PassiveTp::Resting { order_id, .. } if *order_id == expiry.order_id
+    );
+    if !current {
+        return;
+    }
+    info!(
+        "[IntervalSniper] passive TP ask {} {} after {} ms",
+        expiry.order_id,
+        if expiry.canceled {
+            "canceled"
+        } else {
+            "no longer resting"
+        },
+        state.config.take_profit_passive_ms
+    );
+    settle_passive_tp(
+        state,
+        clob,
+        expiry.canceled,
+        expiry.intent.params.price,
+        state.clock.now_ms(),
+    )
+    .await;
+}
+
+/// The passive TP ask is off the book: record what it sold at `price`. Not canceled = it filled;
+/// canceled = the sold part is what we hold above the token balance. The rest sells at the bid.
+async fn settle_passive_tp(
+    state: &mut RunnerState,
+    clob: &dyn ClobClient,
+    canceled: bool,
+    price: Decimal,
+    now_ms_u: u64,
+) {
+    state.passive_tp = PassiveTp::Done;
+    let Some(tp) = state.pending_auto_sell.as_ref() else {
+        return;
+    };
+    if !state.phases.exits_armed() {
+        return;
+    }
+    let sold = if canceled {
+        match clob.get_available_balance(&tp.token_id).await {
+            Ok(Some(a)) => (state.inventory.size(&tp.token_id) - a).max(Decimal::ZERO),
+            _ => {
+                warn!("[IntervalSniper] balance unknown after the passive TP cancel; assuming nothing sold");
+                Decimal::ZERO
+            }
+        }
+    } else {
+        tp.size
+    };
+    let sold = floor_to_decimals(sold.min(tp.size), SELL_SIZE_DECIMALS);
+    if sold < DUST_THRESHOLD {
+        info!("[IntervalSniper] passive TP ask sold nothing; selling at the bid from now on");
+        return;
+    }
+    record_tp_fill(state, sold, price, now_ms_u);
+}
+
+/// A TP sell of `size` filled at `price`: close the lots, and the position once nothing is left.
+fn record_tp_fill(state: &mut RunnerState, size: Decimal, price: Decimal, now_ms_u: u64) {
+    let Some(tp) = state.pending_auto_sell.as_mut() else {
+        return;
+    };
+    let token_id = tp.token_id.clone();
+    let rest = floor_to_decimals(tp.size - size, SELL_SIZE_DECIMALS);
+    let closed = state.inventory.close(&token_id, size, price);
+    state.last_fill_ms = Some(now_ms_u);
+    if let (Some(log), Some(market)) = (state.session_log.as_mut(), state.market.as_ref()) {
+        for lot in &closed {
+            let _ = log.log_position_close(
+                &market.slug,
+                market.interval_start_unix,
+                market.close_time_unix,
+                lot.side,
+                lot.entry_price,
+                price,
+                lot.entry_ms,
+                now_ms_u,
+                ExitType::TakeProfit,
+                lot.size,
+                state.fee_rates.side(lot.side),
+                state.interval_min_bid_up,
+                state.interval_max_bid_up,
+                state.interval_min_bid_down,
+                state.interval_max_bid_down,
+            );
+        }
+    }
+    if let (Some(report), Some(market)) = (state.dry_run_report.as_mut(), state.market.as_ref()) {
+        report.on_close(
+            &market.slug,
+            ExitType::TakeProfit,
+            &closed,
+            &state.fee_rates,
+            now_ms_u,
+        );
+    }
+    if rest >= DUST_THRESHOLD {
+        info!(
+            "[IntervalSniper] passive TP ask sold {} sh @ {}; the other {} sell at the bid",
+            fmt_decimal_2(&size),
+            fmt_price(Some(&price)),
+            fmt_decimal_2(&rest)
+        );
+        tp.size = rest;
+        let held = floor_to_decimals(state.inventory.size(&token_id), SELL_SIZE_DECIMALS);
+        if let Some(ref mut sl) = state.pending_stop_loss {
+            sl.size = sl.size.min(held);
+        }
+        return;
+    }
+    info!(
+        "[IntervalSniper] {}",
+        messages::tp_filled(fmt_price(Some(&price)), true)
+    );
+    state.phases.on_exit(ExitType::TakeProfit);
+    state.pending_auto_sell = None;
+    state.pending_stop_loss = None;
+    state.total_shares_this_interval = Decimal::ZERO;
+}
+
+/// MM_BREAKEVEN_TRIGGER: once best_bid has exceeded entry + the trigger (at least one tick) and
+/// the fee break-even, raise the SL trigger to one tick over the fee break-even exit price
+/// ([breakeven_exit_price]) for the rest of the position.
+fn move_stop_to_breakeven(state: &mut RunnerState, top: &TopOfBook) {
+    let trigger = state.config.breakeven_trigger;
+    let (Some(sl), Some(market)) = (state.pending_stop_loss.as_mut(), state.market.as_ref()) else {
+        return;
+    };
+    if trigger <= Decimal::ZERO || sl.at_breakeven {
+        return;
+    }
+    let (side, side_book) = if sl.token_id == market.token_id_up {
+        (EntrySide::Up, &top.token_id_up)
+    } else {
+        (EntrySide::Down, &top.token_id_down)
+    };
+    let Some(best_bid) = side_book.as_ref().and_then(|s| s.best_bid) else {
+        return;
+    };
+    let tick_size = market.tick_size;
+    let breakeven = round_to_tick(
+        breakeven_exit_price(state.fee_rates.side(side), sl.entry_price) + tick_size,
+        tick_size,
+    );
+    if best_bid <= sl.entry_price + trigger.max(tick_size) || best_bid <= breakeven {
+        return;
+    }
+    sl.at_breakeven = true;
+    if breakeven <= sl.trigger_price {
+        return;
+    }
+    info!(
+        "[IntervalSniper] {}",
+        messages::sl_moved_to_breakeven(
+            fmt_price(Some(&best_bid)),
+            fmt_price(Some(&sl.trigger_price)),
+            fmt_price(Some(&breakeven))
+        )
+    );
+    sl.trigger_price = breakeven;
+}
+
+/// MM_SOFT_STOP_PRICE: best_bid fell to the soft trigger but is still above the SL: sell
+/// MM_SOFT_STOP_PERCENT of the position (FOK at the bid, retried on the next tick when it misses)
+/// and leave the rest to TP and the hard SL.
+async fn soft_stop(
+    state: &mut RunnerState,
+    clob: &dyn ClobClient,
+    top: &TopOfBook,
+    now_ms_u: u64,
+) -> Result<()> {
+    if !state.config.enable_stop_loss || !state.phases.exits_armed() || state.passive_tp.pending() {
+        return Ok(());
+    }
+    let (Some(soft), Some(sl), Some(market)) = (
+        state.pending_soft_stop.as_ref(),
+        state.pending_stop_loss.as_ref(),
+        state.market.as_ref(),
+    ) else {
+        return Ok(());
+    };
+    let side_book = if soft.token_id == market.token_id_up {
+        &top.token_id_up
+    } else {
+        &top.token_id_down
+    };
+    let Some(best_bid) = side_book.as_ref().and_then(|s| s.best_bid) else {
+        return Ok(());
+    };
+    if best_bid <= sl.trigger_price || best_bid > soft.trigger_price {
+        return Ok(());
+    }
+    let token_id = soft.token_id.clone();
+    let available = clob.get_available_balance(&token_id).await.ok().flatten();
+    let size = effective_sell_size(soft.size, available);
+    if size < MIN_SELL_SIZE {
+        debug!(
+            "[IntervalSniper] soft stop: available {:?} too low to sell {} (retrying)",
+            available, soft.size
+        );
+        return Ok(());
+    }
+    let price = round_to_tick(best_bid, market.tick_size);
+    let result = clob
+        .place_sell_order(&token_id, price, size, SellOrderTimeInForce::Fok)
+        .await?;
+    let sold = sold_size(&result, size);
+    if !result.success || sold.is_zero() {
+        debug!(
+            "[IntervalSniper] soft stop sell {} sh @ {} not filled: {:?}",
+            fmt_decimal_2(&size),
+            fmt_price(Some(&price)),
+            result.error_msg
+        );
+        return Ok(());
+    }
+    let closed = state.inventory.close(&token_id, sold, price);
+    state.last_fill_ms = Some(now_ms_u);
+    if let (Some(log), Some(market)) = (state.session_log.as_mut(), state.market.as_ref()) {
+        for lot in &closed {
+            let _ = log.log_position_close(
+                &market.slug,
+                market.interval_start_unix,
+                market.close_time_unix,
+                lot.side,
+                lot.entry_price,
+                price,
+                lot.entry_ms,
+                now_ms_u,
+                ExitType::SoftStop,
+                lot.size,
+                state.fee_rates.side(lot.side),
+                state.interval_min_bid_up,
+                state.interval_max_bid_up,
+                state.interval_min_bid_down,
+                state.interval_max_bid_down,
+            );
+        }
+    }
+    if let (Some(report), Some(market)) = (state.dry_run_report.as_mut(), state.market.as_ref()) {
+        report.on_close(
+            &market.slug,
+            ExitType::SoftStop,
+            &closed,
+            &state.fee_rates,
+            now_ms_u,
+        );
+    }
+    let held = trim_exits_to_held(
+        &state.inventory,
+        &token_id,
+        state.pending_auto_sell.as_mut(),
+        state.pending_stop_loss.as_mut(),
+    );
+    // A split sell with a failed part leaves the rest of the soft stop for the next tick.
+    if partly_sold(&result, size) {
+        if let Some(ref mut soft) = state.pending_soft_stop {
+            soft.size = (soft.size - sold).max(Decimal::ZERO);
+        }
+    } else {
+        state.pending_soft_stop = None;
+    }
+    info!(
+        "[IntervalSniper] {}",
+        messages::soft_stop_filled(
+            fmt_decimal_2(&sold),
+            fmt_price(Some(&price)),
+            fmt_decimal_2(&held)
+        )
+    );
+    Ok(())
+}
+
+/// Cut the pending TP and SL to the shares of `token_id` still held after a partial sell;
+/// returns that size.
+fn trim_exits_to_held(
+    inventory: &Inventory,
+    token_id: &str,
+    tp: Option<&mut PendingAutoSell>,
+    sl: Option<&mut PendingStopLoss>,
+) -> Decimal {
+    let held = floor_to_decimals(inventory.size(token_id), SELL_SIZE_DECIMALS);
+    if let Some(tp) = tp {
+        tp.size = tp.size.min(held);
+    }
+    if let Some(sl) = sl {
+        sl.size = sl.size.min(held);
+    }
+    held
+}
+
+/// "TP" / "SL" (for logs) and the exit type of a sell campaign.
+fn exit_label(kind: SellKind) -> (&'static str, ExitType) {
+    match kind {
+        SellKind::TakeProfit => ("TP", ExitType::TakeProfit),
+        SellKind::StopLoss => ("SL", ExitType::StopLoss),
+    }
+}
+
+/// The position is gone (sold, dust, or an amount the exchange rejects): clear TP and SL; a
+/// re-entry can use the full size again.
+fn end_exits(state: &mut RunnerState, exit: ExitType) {
+    state.phases.on_exit(exit);
+    state.pending_auto_sell = None;
+    state.pending_stop_loss = None;
+    state.total_shares_this_interval = Decimal::ZERO;
+}
+
+/// Book a TP / SL sell of `token_id` that filled `sold` at `price`: inventory, session log and
+/// dry-run report. A split sell with a failed part (`partly`) cuts TP and SL to what is still
+/// held, for the next tick to sell; otherwise the position is closed.
+fn book_exit_sell(
+    state: &mut RunnerState,
+    kind: SellKind,
+    token_id: &str,
+    sold: Decimal,
+    price: Decimal,
+    partly: bool,
+    now_ms_u: u64,
+) {
+    let (label, exit) = exit_label(kind);
+    let closed = state.inventory.close(token_id, sold, price);
+    state.last_fill_ms = Some(state.clock.now_ms());
+    if let (Some(log), Some(market)) = (state.session_log.as_mut(), state.market.as_ref()) {
+        for lot in &closed {
+            let _ = log.log_position_close(
+                &market.slug,
+                market.interval_start_unix,
+                market.close_time_unix,
+                lot.side,
+                lot.entry_price,
+                price,
+                lot.entry_ms,
+                now_ms_u,
+                exit,
+                lot.size,
+                state.fee_rates.side(lot.side),
+                state.interval_min_bid_up,
+                state.interval_max_bid_up,
+                state.interval_min_bid_down,
+                state.interval_max_bid_down,
+            );
+        }
+    }
+    if let (Some(report), Some(market)) = (state.dry_run_report.as_mut(), state.market.as_ref()) {
+        report.on_close(&market.slug, exit, &closed, &state.fee_rates, now_ms_u);
+    }
+    if partly {
+        let held = trim_exits_to_held(
+            &state.inventory,
+            token_id,
+            state.pending_auto_sell.as_mut(),
+            state.pending_stop_loss.as_mut(),
+        );
+        warn!(
+            "[IntervalSniper] {}",
+            messages::sell_partly_filled(
+                label,
+                fmt_decimal_2(&sold),
+                fmt_price(Some(&price)),
+                fmt_decimal_2(&held)
+            )
+        );
+        return;
+    }
+    let filled = match kind {
+        SellKind::TakeProfit => messages::tp_filled(fmt_price(Some(&price)), false),
+        SellKind::StopLoss => messages::sl_filled(fmt_price(Some(&price))),
+    };
+    info!("[IntervalSniper] {}", filled);
+    end_exits(state, exit);
+}
+
+/// Log the token's balance / allowance after a `label` sell of `size` was answered HTTP 400.
+async fn log_sell_400(clob: &dyn ClobClient, label: &str, token_id: &str, size: Decimal) {
+    let ba = clob
+        .get_balance_allowance(token_id)
+        .await
+        .unwrap_or_else(|e| format!("error: {}", e));
+    info!(
+        "[IntervalSniper] {} 400 — token_id={} sell_size={} balance_allowance (CONDITIONAL)={}",
+        label, token_id, size, ba
+    );
+}
+
+/// MM_SL_HEDGE_EXIT while the SL sell does not fill: buy the opposite token to lock the loss, or
+/// with MM_SL_HEDGE_BID and no bid on our side rest a bid for it; either way the position is held
+/// to resolution. Returns whether it was hedged.
+async fn hedge_stop_loss(
+    state: &mut RunnerState,
+    clob: &dyn ClobClient,
+    market: &ResolvedMarket,
+    top: &TopOfBook,
+    bid: Decimal,
+) -> bool {
+    let Some(sl) = state.pending_stop_loss.clone() else {
+        return false;
+    };
+    let tick_size = market.tick_size;
+    if let Some((side, token_id, size, price)) = hedge_exit(
+        clob,
+        &state.config,
+        market,
+        top,
+        &sl,
+        &state.fee_rates,
+        tick_size,
+    )
+    .await
+    {
+        let held = state
+            .inventory
+            .avg_entry(&sl.token_id)
+            .unwrap_or(Decimal::ZERO);
+        let (price_up, price_down, size_up, size_down) = match side {
+            EntrySide::Up => (price, held, size, sl.size),
+            EntrySide::Down => (held, price, sl.size, size),
+        };
+        info!(
+            "[IntervalSniper] {}",
+            messages::sl_hedge_bought(
+                side,
+                fmt_decimal_2(&size),
+                fmt_price(Some(&price)),
+                fmt_decimal_2(&(price_up + price_down))
+            )
+        );
+        let now_ms = state.clock.now_ms();
+        state
+            .inventory
+            .add_lot(&token_id, side, price, size, now_ms);
+        state.hedged_pair = Some(HedgedPair {
+            size_up,
+            size_down,
+            price_up,
+            price_down,
+            timestamp_ms: now_ms,
+        });
+        state.last_fill_ms = Some(now_ms);
+    } else {
+        // No bid to sell into and no ask in reach: rest a bid for the opposite token at 1 − our
+        // last bid.
+        let Some(fair) = state
+            .inventory
+            .mark(&sl.token_id)
+            .filter(|_| state.config.sl_hedge_bid && bid <= Decimal::ZERO)
+        else {
+            return false;
+        };
+        let Some(hb) = post_hedge_bid(
+            clob,
+            &state.config,
+            market,
+            &sl,
+            fair,
+            &state.fee_rates,
+            tick_size,
+        )
+        .await
+        else {
+            return false;
+        };
+        info!(
+            "[IntervalSniper] {}",
+            messages::sl_hedge_bid_posted(
+                hb.side,
+                fmt_decimal_2(&hb.size),
+                fmt_price(Some(&hb.price))
+            )
+        );
+        state.hedge_bid = Some(hb);
+    }
+    state.holding_to_resolution = true;
+    state.phases.on_exit(ExitType::Resolution);
+    state.pending_auto_sell = None;
+    state.pending_stop_loss = None;
+    true
+}
+
+/// A TP or SL that is due: free the shares our resting orders lock (MM_AUTO_UNLOCK) and sell the
+/// position at `bid`. A sell that does not fill (or can't be placed: SL balance still locked) is
+/// retried at the latest bid as its [SellCampaign] decides, until the position is sold, hedged
+/// (SL, [hedge_stop_loss]), left to the next tick or the market's switch time. `limit` is the TP
+/// target or the SL trigger. Every fill is booked with [book_exit_sell].
+#[allow(clippy::too_many_arguments)]
+async fn run_sell_campaign(
+    kind: SellKind,
+    state: &mut RunnerState,
+    clob: &dyn ClobClient,
+    tracker: &std::sync::Mutex<OrderTracker>,
+    http: &Client,
+    clob_host: &str,
+    bid: Decimal,
+    limit: Decimal,
+    now_ms_u: u64,
+) -> Result<()> {
+    let exit = match kind {
+        SellKind::TakeProfit => state
+            .pending_auto_sell
+            .as_ref()
+            .map(|tp| (tp.token_id.clone(), tp.size)),
+        SellKind::StopLoss => state
+            .pending_stop_loss
+            .as_ref()
+            .map(|sl| (sl.token_id.clone(), sl.size)),
+    };
+    let (Some((token_id, position_size)), Some(market)) = (exit, state.market.clone()) else {
+        return Ok(());
+    };
+    let (label, exit) = exit_label(kind);
+    let clock = state.clock.clone();
+    let tick_size = market.tick_size;
+    if unlock_for_exit(
+        state.config.auto_unlock,
+        &state.exit_orders,
+        clob,
+        tracker,
+        &token_id,
+        kind,
+    )
+    .await
+    {
+        clock.sleep(Duration::from_millis(UNLOCK_SETTLE_MS)).await;
+    }
+    let available = clob.get_available_balance(&token_id).await.ok().flatten();
+    let size = effective_sell_size(position_size, available);
+    let campaign = if size < MIN_SELL_SIZE {
+        warn!(
+            "[IntervalSniper] {} available too low to sell: token_id={} available_shares={:?} effective_sell_size={} min_sell_size={}",
+            label, token_id, available, size, MIN_SELL_SIZE
+        );
+        match kind {
+            // The balance may be locked: retry until it sells, the market switches or the bid
+            // is back above the trigger.
+            SellKind::StopLoss => SellCampaign::awaiting_balance(kind, position_size, limit),
+            SellKind::TakeProfit => return Ok(()),
+        }
+    } else if size < DUST_THRESHOLD {
+        if position_size < DUST_THRESHOLD {
+            info!(
+                "[IntervalSniper] {}",
+                messages::dust_closed(label, size, DUST_THRESHOLD)
+            );
+            end_exits(state, exit);
+        } else {
+            // A real position (e.g. a second entry) whose balance has not caught up yet.
+            warn!(
+                "[IntervalSniper] {} available too low to sell: token_id={} available_shares={:?} effective_sell_size={} position_size={} (retrying, balance may update)",
+                label, token_id, available, size, position_size
+            );
+        }
+        return Ok(());
+    } else {
+        let (price, tif) = match kind {
+            // FOK at the bid: all of it or nothing.
+            SellKind::StopLoss => (round_to_tick(bid, tick_size), SellOrderTimeInForce::Fok),
+            // A FAK must cross at the bid; a resting TP asks no more than the TP price.
+            SellKind::TakeProfit => {
+                let tif = state.config.take_profit_time_in_force;
+                let price = match tif {
+                    SellOrderTimeInForce::Fak => round_to_tick(bid, tick_size),
+                    _ => round_to_tick(
+                        bid.min(limit + state.config.take_profit_price_margin),
+                        tick_size,
+                    ),
+                };
+                let tif =
+                    bounded_sell_tif(&state.config, tif, market.close_time_unix, clock.now_unix());
+                (price, tif)
+            }
+        };
+        let result = clob.place_sell_order(&token_id, price, size, tif).await?;
+        if kind == SellKind::TakeProfit {
+            if let Some(order_id) = result.order_id.clone() {
+                state.exit_orders.insert(order_id, kind);
+            }
+        }
+        // A FOK success is 100% filled, a FAK's may be part; 0 filled sold nothing.
+        let sold = sold_size(&result, size);
+        if result.success && !sold.is_zero() {
+            let partly = partly_sold(&result, size);
+            book_exit_sell(state, kind, &token_id, sold, price, partly, now_ms_u);
+            return Ok(());
+        }
+        if result.http_status == Some(400) {
+            log_sell_400(clob, label, &token_id, size).await;
+        }
+        if result.is_error(&ClobError::InvalidAmounts) {
+            info!("[IntervalSniper] {}", messages::amount_rejected(label));
+            end_exits(state, exit);
+            return Ok(());
+        }
+        // No match: retry at the latest bid. Balance/allowance error: cancel our orders on the
+        // token once, then retry with backoff (100→200→400 ms).
+        let Some(campaign) = SellCampaign::start(kind, position_size, limit, &result) else {
+            if let Some(msg) = result.error_msg {
+                warn!("[IntervalSniper]  FAIL  {:<5} {}", label, msg);
+            }
+            return Ok(());
+        };
+        if campaign.balance_error() {
+            info!(
+                "[IntervalSniper] {}: balance/allowance error, canceling open orders once and retrying with backoff",
+                label
+            );
+        } else {
+            info!(
+                "[IntervalSniper] {} no match, retrying at the latest bid until filled",
+                label
+            );
+        }
+        campaign
+    };
+
+    let mut campaign =
+        campaign.until(switch_time_unix(&state.config.market_target, &market) * 1000);
+    let retry_tif = match kind {
+        SellKind::StopLoss => SellOrderTimeInForce::Fok,
+        SellKind::TakeProfit => SellOrderTimeInForce::Fak,
+    };
+    let retry_label = format!("{} retry", label);
+    let started_ms = clock.now_ms();
+    loop {
+        let Some(wait) = campaign.next_attempt(clock.now_ms()) else {
+            info!(
+                "[IntervalSniper] {}",
+                messages::interval_changed_during_retry(label, campaign.attempt())
+            );
+            break;
+        };
+        let attempt = campaign.attempt();
+        clock.sleep(Duration::from_millis(wait.delay_ms)).await;
+        if wait.cancel_first
+            && unlock_for_exit(
+                state.config.auto_unlock,
+                &state.exit_orders,
+                clob,
+                tracker,
+                &token_id,
+                kind,
+            )
+            .await
+        {
+            clock.sleep(Duration::from_millis(UNLOCK_SETTLE_MS)).await;
+        }
+        let top = match live_ws(&state.ws_book) {
+            Some(ws) => ws.get_top_of_book().await,
+            None => match fetch_top_of_book(
+                http,
+                clob_host,
+                &market.token_id_up,
+                &market.token_id_down,
+                clock.now_ms(),
+            )
+            .await
+            {
+                Ok(t) => t,
+                Err(_) => continue,
+            },
+        };
+        let side_book = if token_id == market.token_id_up {
+            &top.token_id_up
+        } else {
+            &top.token_id_down
+        };
+        let bid = side_book
+            .as_ref()
+            .and_then(|s| s.best_bid)
+            .unwrap_or(Decimal::ZERO);
+        // SL not filling: lock the loss with the opposite token instead.
+        if kind == SellKind::StopLoss
+            && state.config.sl_hedge_exit
+            && !campaign.balance_error()
+            && (bid <= Decimal::ZERO
+                || clock.now_ms().saturating_sub(started_ms) >= state.config.sl_hedge_after_ms)
+            && hedge_stop_loss(state, clob, &market, &top, bid).await
+        {
+            break;
+        }
+        match campaign.on_bid(bid) {
+            Some(SellAction::GiveUp) => {
+                match kind {
+                    SellKind::StopLoss => info!(
+                        "[IntervalSniper] SL retry: bid {} above trigger {}, stopping retries",
+                        fmt_price(Some(&bid)),
+                        fmt_price(Some(&limit))
+                    ),
+                    SellKind::TakeProfit => info!(
+                        "[IntervalSniper] TP retry: no bid, stopping retries (TP / SL checked again next tick)"
+                    ),
+                }
+                break;
+            }
+            Some(_) => continue,
+            None => {}
+        }
+        let available = clob.get_available_balance(&token_id).await.ok().flatten();
+        let (price, size) = match campaign.on_available(bid, available, tick_size) {
+            SellAction::Sell { price, size } => (price, size),
+            SellAction::Closed => {
+                info!(
+                    "[IntervalSniper] {}",
+                    messages::retry_dust_closed(label, available)
+                );
+                end_exits(state, exit);
+                break;
+            }
+            action => {
+                warn!(
+                    "[IntervalSniper] {} available too low to sell on retry: token_id={} attempt={} available_shares={:?} min_sell_size={}",
+                    label, token_id, attempt, available, MIN_SELL_SIZE
+                );
+                if action == SellAction::GiveUp {
+                    break;
+                }
+                continue;
+            }
+        };
+        let result = clob
+            .place_sell_order(&token_id, price, size, retry_tif)
+            .await?;
+        match campaign.on_result(&result, price, size) {
+            SellAction::Filled { size: sold, .. } => {
+                book_exit_sell(state, kind, &token_id, sold, price, false, now_ms_u);
+                break;
+            }
+            SellAction::PartFilled { size: sold, .. } => {
+                book_exit_sell(state, kind, &token_id, sold, price, true, now_ms_u);
+                break;
+            }
+            // Already canceled once: back off and retry.
+            _ if result.is_error(&ClobError::InsufficientBalance) => {
+                warn!(
+                    "[IntervalSniper] {} retry attempt {}: balance/allowance error (cancel already done), retrying with backoff",
+                    label, attempt
+                );
+            }
+            action => {
+                if result.http_status == Some(400) {
+                    log_sell_400(clob, &retry_label, &token_id, size).await;
+                }
+                match action {
+                    SellAction::Retry => continue,
+                    SellAction::Closed => {
+                        info!(
+                            "[IntervalSniper] {}",
+                            messages::amount_rejected(&retry_label)
+                        );
+                        end_exits(state, exit);
+                    }
+                    _ => {}
+                }
+                if let Some(msg) = result.error_msg {
+                    warn!("[IntervalSniper]  FAIL  {:<5} {}", label, msg);
+                }
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A resting GTC buy was canceled at MM_BUY_ORDER_MAX_AGE_MS. The entry counted the whole order
+/// as bought; with MM_BUY_ORDER_REEVALUATE the position shrinks to the token balance actually
+/// held, and when nothing was bought the entry slot is freed so the strategy can enter again.
+async fn on_buy_expired(state: &mut RunnerState, clob: &dyn ClobClient, expiry: OrderExpiry) {
+    let OrderExpiry {
+        intent,
+        order_id,
+        canceled,
+    } = expiry;
+    if !canceled {
+        debug!(
+            "[IntervalSniper] buy {} at max age was no longer resting (filled or gone)",
+            order_id
+        );
+        return;
+    }
+    info!(
+        "[IntervalSniper] resting buy {} ({} sh @ {}) canceled after {} ms",
+        order_id,
+        fmt_decimal_2(&intent.params.size),
+        fmt_decimal_2(&intent.params.price),
+        state.config.buy_order_max_age_ms
+    );
+    // A chased buy: its position is not open yet, so the chase just ends with what filled.
+    let chased = state
+        .resting_buy
+        .as_ref()
+        .is_some_and(|rb| rb.order_id.as_deref() == Some(order_id.as_str()));
+    if chased {
+        let mut rb = state.resting_buy.take().expect("chased buy checked above");
+        add_resting_partial(state, clob, &mut rb).await;
+        finish_chase(state, rb, Decimal::ZERO, state.clock.now_ms());
+        return;
+    }
+    let same_market = state.market.as_ref().is_some_and(|m| m.slug == intent.slug);
+    if !state.config.buy_order_reevaluate || !same_market {
+        return;
+    }
+    let token_id = &intent.params.token_id;
+    let available = match clob.get_available_balance(token_id).await {
+        Ok(Some(a)) => a,
+        _ => {
+            warn!(
+                "[IntervalSniper] balance of {} unknown; position left as is after the cancel",
+                token_id
+            );
+            return;
+        }
+    };
+    let held = state.inventory.size(token_id);
+    let unfilled = held - available;
+    if unfilled < DUST_THRESHOLD {
+        return;
+    }
+    state.inventory.unfill(token_id, unfilled);
+    state.total_shares_this_interval =
+        (state.total_shares_this_interval - unfilled).max(Decimal::ZERO);
+    let remaining = floor_to_decimals(available.max(Decimal::ZERO), SELL_SIZE_DECIMALS);
+    if remaining < DUST_THRESHOLD {
+        info!(
+            "[IntervalSniper] canceled buy never filled: position cleared, entry slot free again"
+        );
+        state.pending_auto_sell = None;
+        state.pending_stop_loss = None;
+        state.trades_this_interval = state.trades_this_interval.saturating_sub(1);
+        if state.trades_this_interval == 0 {
+            state.phases.on_entry_void();
+        }
+        return;
+    }
+    info!(
+        "[IntervalSniper] canceled buy filled {} of {} sh: TP/SL resized",
+        fmt_decimal_2(&remaining),
+        fmt_decimal_2(&held)
+    );
+    if let Some(ref mut tp) = state.pending_auto_sell {
+        tp.size = tp.size.min(remaining);
+    }
+    if let Some(ref mut sl) = state.pending_stop_loss {
+        sl.size = sl.size.min(remaining);
+    }
+}
+
+/// After a buy fill: add an inventory lot and arm TP/SL for the filled quantity.
+fn open_position(
+    state: &mut RunnerState,
+    token_id: &str,
+    entry_side: EntrySide,
+    filled: Decimal,
+    entry_price: Decimal,
+    now_ms_u: u64,
+) {
+    state
+        .inventory
+        .add_lot(token_id, entry_side, entry_price, filled, now_ms_u);
+    state.last_fill_ms = Some(now_ms_u);
+    if let (Some(report), Some(market)) = (state.dry_run_report.as_mut(), state.market.as_ref()) {
+        let secs_to_close = market.close_time_unix.saturating_sub(now_ms_u / 1000);
+        report.on_entry(
+            &market.slug,
+            entry_side,
+            entry_price,
+            filled,
+            secs_to_close,
+            now_ms_u,
+        );
+    }
+    let tick_size = market_tick_size(state);
+    let fee_rate_bps = state.fee_rates.side(entry_side);
+    let breakeven = breakeven_exit_price(fee_rate_bps, entry_price);
+    let configured_target = if state.config.auto_sell_at_max_price {
+        Decimal::ONE - tick_size
+    } else {
+        round_to_tick(state.config.side(entry_side).take_profit_price, tick_size)
+    };
+    // Never a TP that loses money after fees: it sells at the bid from the target less the margin,
+    // so that is kept at the break-even or above, on a tick (up to 1 − tick).
+    let lowest_target = breakeven + state.config.take_profit_price_margin;
+    let breakeven_tick = ((lowest_target / tick_size).ceil() * tick_size)
+        .round_dp(tick_size.normalize().scale())
+        .min(Decimal::ONE - tick_size);
+    let target_price = configured_target.max(breakeven_tick);
+    // Use actual bought quantity (filled), adjusted to Polymarket sell size decimals (4).
+    let base_sell_size =
+        floor_to_decimals(filled.min(state.config.size_shares), SELL_SIZE_DECIMALS)
+            .max(MIN_SELL_SIZE);
+    let pct_tp = Decimal::from(state.config.auto_sell_quantity_percent) / dec!(100);
+    let pct_sl = Decimal::from(state.config.stop_loss_quantity_percent) / dec!(100);
+    let tp_size = floor_to_decimals(base_sell_size * pct_tp, SELL_SIZE_DECIMALS)
+        .max(MIN_SELL_SIZE)
+        .min(base_sell_size);
+    let sl_size = floor_to_decimals(base_sell_size * pct_sl, SELL_SIZE_DECIMALS)
+        .max(MIN_SELL_SIZE)
+        .min(base_sell_size);
+    state.pending_auto_sell = Some(PendingAutoSell {
+        token_id: token_id.to_string(),
+        target_price,
+        breakeven_target: breakeven_tick,
+        size: tp_size,
+        placed_at_ms: now_ms_u,
+    });
+    let trigger_price = round_to_tick(state.config.side(entry_side).stop_loss_price, tick_size);
+    state.pending_stop_loss = Some(PendingStopLoss {
+        token_id: token_id.to_string(),
+        size: sl_size,
+        trigger_price,
+        entry_price,
+        at_breakeven: false,
+    });
+    // Soft stop: only between the entry and the SL, and only when both parts stay sellable.
+    let soft_trigger = round_to_tick(state.config.soft_stop_price, tick_size);
+    let pct_soft = Decimal::from(state.config.soft_stop_percent) / dec!(100);
+    let soft_size = floor_to_decimals(base_sell_size * pct_soft, SELL_SIZE_DECIMALS);
+    state.pending_soft_stop = (state.config.soft_stop_price > Decimal::ZERO
+        && soft_trigger > trigger_price
+        && soft_size >= MIN_SELL_SIZE
+        && base_sell_size - soft_size >= MIN_SELL_SIZE)
+        .then(|| PendingSoftStop {
+            token_id: token_id.to_string(),
+            size: soft_size,
+            trigger_price: soft_trigger,
+        });
+    state.phases.on_position_opened();
+    state.sl_breach_since_ms = None;
+    // A passive TP ask still out covers part of this position; it settles by balance.
+    if !state.passive_tp.pending() {
+        state.passive_tp = PassiveTp::Unposted;
+    }
+    let side_str = match entry_side {
+        EntrySide::Up => "Up  ",
+        EntrySide::Down => "Down",
+    };
+    info!(
+        "[IntervalSniper]  BUY   {}  @ {}   size={}   TP size={} ({}%)   SL size={} ({}%)   breakeven={} (fee {} bps)",
+        side_str,
+        fmt_decimal_2(&entry_price),
+        fmt_decimal_2(&filled),
+        fmt_decimal_2(&tp_size),
+        state.config.auto_sell_quantity_percent,
+        fmt_decimal_2(&sl_size),
+        state.config.stop_loss_quantity_percent,
+        fmt_decimal_4(&breakeven),
+        fee_rate_bps
+    );
+    if target_price > configured_target {
+        info!(
+            "[IntervalSniper] TP target {} is below fee breakeven {}: raised to {}",
+            fmt_decimal_2(&configured_target),
+            fmt_decimal_4(&breakeven),
+            fmt_decimal_2(&target_price)
+        );
+    }
+    if target_price < breakeven {
+        warn!(
+            "[IntervalSniper] fee breakeven {} is above the highest TP {}: a TP fill loses money",
+            fmt_decimal_4(&breakeven),
+            fmt_decimal_2(&target_price)
+        );
+    }
+}
+
+/// TP target at `secs_to_close`: MM_TP_SCHEDULE lowers it toward the close, but never below the
+/// fee break-even target it was raised to at entry.
+fn scheduled_tp_price(
+    config: &Config,
+    tp: &PendingAutoSell,
+    secs_to_close: u64,
+    tick_size: Decimal,
+) -> Decimal {
+    match config.take_profit_cap(secs_to_close) {
+        Some(cap) => tp
+            .target_price
+            .min(round_to_tick(cap, tick_size).max(tp.breakeven_target)),
+        None => tp.target_price,
+    }
+}
+
+/// Most USDC one entry can cost: size_shares at the highest allowed buy price (or the max pair
+/// cost for dual-side entry).
+fn max_entry_price(config: &Config) -> Decimal {
+    if config.dual_side_entry {
+        return config.dual_side_max_pair_cost;
+    }
+    let up = config.allow_buy_up.then_some(config.up.max_buy_price);
+    let down = config.allow_buy_down.then_some(config.down.max_buy_price);
+    up.max(down).unwrap_or(Decimal::ONE)
+}
+
+/// Startup balance check: compare the USDC balance with the max notional of one entry, and warn or
+/// (MM_AUTOSIZE_TO_BALANCE) lower size_shares so the first order does not fail on balance.
+async fn preflight_balance(clob: &dyn ClobClient, config: &mut Config) -> Result<()> {
+    let balance = match clob.get_collateral_balance().await {
+        Ok(Some(b)) => b,
+        Ok(None) => return Ok(()),
+        Err(e) => {
+            warn!(
+                "[IntervalSniper] balance preflight: could not fetch USDC balance: {}",
+                e
+            );
+            return Ok(());
+        }
+    };
+    let price = max_entry_price(config);
+    let notional = config.size_shares * price;
+    if balance >= notional {
+        info!(
+            "[IntervalSniper] balance preflight: {} USDC covers max entry {} ({} sh @ {})",
+            fmt_decimal_4(&balance),
+            fmt_decimal_4(&notional),
+            config.size_shares,
+            price
+        );
+        return Ok(());
+    }
+    let affordable = floor_to_decimals(balance / price, 2);
+    if !config.autosize_to_balance {
+        warn!(
+            "[IntervalSniper] balance preflight: {} USDC < max entry {} ({} sh @ {}); orders may fail with not enough balance (MM_AUTOSIZE_TO_BALANCE=true sizes down to {} sh)",
+            fmt_decimal_4(&balance),
+            fmt_decimal_4(&notional),
+            config.size_shares,
+            price,
+            affordable
+        );
+        return Ok(());
+    }
+    if affordable < DEFAULT_MIN_ORDER_SIZE {
+        anyhow::bail!(
+            "balance preflight: {} USDC buys {} sh @ {}, below the min order size {}",
+            fmt_decimal_4(&balance),
+            affordable,
+            price,
+            DEFAULT_MIN_ORDER_SIZE
+        );
+    }
+    warn!(
+        "[IntervalSniper] balance preflight: {} USDC < max entry {}; size_shares {} -> {}",
+        fmt_decimal_4(&balance),
+        fmt_decimal_4(&notional),
+        config.size_shares,
+        affordable
+    );
+    config.size_shares = affordable;
+    Ok(())
+}
+
+/// MM_SIZING: size the next interval's entries from the USDC balance and the session's realized
+/// trade stats. None (keep the current size) in fixed mode or when the balance is unknown; 0 shares
+/// skips the interval's entries.
+async fn interval_sizing(
+    clob: &dyn ClobClient,
+    config: &Config,
+    inventory: &Inventory,
+    min_order_size: Decimal,
+) -> Option<Sizing> {
+    if config.sizing == SizingMode::Fixed {
+        return None;
+    }
+    let bankroll = match clob.get_collateral_balance().await {
+        Ok(Some(b)) => b,
+        Ok(None) => {
+            debug!(
+                "[IntervalSniper] sizing: USDC balance unknown, keeping {} sh",
+                config.size_shares
+            );
+            return None;
+        }
+        Err(e) => {
+            warn!(
+                "[IntervalSniper] sizing: could not fetch USDC balance: {}, keeping {} sh",
+                e, config.size_shares
+            );
+            return None;
+        }
+    };
+    let stats = inventory.stats();
+    let price = max_entry_price(config);
+    let sizing = interval_size(config, bankroll, stats, price, min_order_size)?;
+    info!(
+        "[IntervalSniper] sizing {:?}: bankroll {} USDC, {} closes (win rate {}) -> stake {}% ({}) = {} sh @ {}",
+        config.sizing,
+        fmt_decimal_2(&bankroll),
+        stats.trades(),
+        stats
+            .win_rate()
+            .map_or("-".to_string(), |w| fmt_decimal_2(&w)),
+        (sizing.fraction * dec!(100)).round_dp(2),
+        sizing.basis,
+        sizing.shares,
+        price
+    );
+    if sizing.shares.is_zero() {
+        info!(
+            "[IntervalSniper] sizing: stake below the min order size of {} sh, no entry this interval",
+            min_order_size
+        );
+    }
+    Some(sizing)
+}
+
+/// Cancel every open order (resting buy, GTC TP/SL) on the tokens of a market being left, so
+/// nothing stays live or locks balance into the next window.
+async fn cancel_stale_orders(clob: &dyn ClobClient, market: &ResolvedMarket) {
+    for (label, token_id) in [("Up", &market.token_id_up), ("Down", &market.token_id_down)] {
+        match clob.cancel_orders_for_token(token_id).await {
+            Ok(res) => {
+                if !res.canceled.is_empty() {
+                    info!(
+                        "[IntervalSniper] interval switch: canceled {} stale {} order(s) on {}: {}",
+                        res.canceled.len(),
+                        label,
+                        market.slug,
+                        res.canceled.join(", ")
+                    );
+                }
+                for (order_id, reason) in &res.not_canceled {
+                    warn!(
+                        "[IntervalSniper] interval switch: {} order {} on {} not canceled: {}",
+                        label, order_id, market.slug, reason
+                    );
+                }
+            }
+            Err(e) => warn!(
+                "[IntervalSniper] interval switch: cancel {} orders on {} failed: {}",
+                label, market.slug, e
+            ),
+        }
+    }
+}
+
+/// End-of-interval settlement, before the switch: book the results of orders still in flight,
+/// cancel every order on the old market (recording what a resting entry buy, passive TP ask or SL
+/// hedge bid filled first), snapshot its final book, hold whatever is still owned to resolution
+/// (valued at the payout, or carried over until the market resolves), then write the closes and
+/// the interval summary.
+async fn settle_interval(
+    state: &mut RunnerState,
+    clob: &dyn ClobClient,
+    http: &Client,
+    clob_host: &str,
+    gamma_base_url: &str,
+    old_market: &ResolvedMarket,
+    clock: &dyn Clock,
+) {
+    let now_ms_u = clock.now_ms();
+    drain_in_flight(state, old_market, clock).await;
+    cancel_stale_orders(clob, old_market).await;
+    if let Some(mut rb) = state.resting_buy.take() {
+        if rb.order_id.is_some() {
+            add_resting_partial(state, clob, &mut rb).await;
+        }
+        finish_chase(state, rb, Decimal::ZERO, now_ms_u);
+    }
+    if let PassiveTp::Resting { price, .. } = state.passive_tp {
+        settle_passive_tp(state, clob, true, price, now_ms_u).await;
+    }
+    if state.hedge_bid.is_some() {
+        track_hedge_bid(state, clob, now_ms_u).await;
+        state.hedge_bid = None;
+    }
+
+    let final_book = match live_ws(&state.ws_book) {
+        Some(ws) => Some(ws.get_top_of_book().await),
+        None => fetch_top_of_book(
+            http,
+            clob_host,
+            &old_market.token_id_up,
+            &old_market.token_id_down,
+            state.clock.now_ms(),
+        )
+        .await
+        .ok(),
+    };
+    if let (Some(rec), Some(ws)) = (state.book_recorder.as_mut(), state.ws_book.as_ref()) {
+        if let Err(e) = rec.sample(old_market, ws, now_ms_u).await {
+            warn!("[IntervalSniper] book recorder sample failed: {}", e);
+        }
+    }
+    if let Some(ref top) = final_book {
+        let quote = |side: &Option<TopOfBookSide>| {
+            let side = side.as_ref();
+            format!(
+                "{}/{}",
+                fmt_price(side.and_then(|s| s.best_bid).as_ref()),
+                fmt_price(side.and_then(|s| s.best_ask).as_ref())
+            )
+        };
+        info!(
+            "[IntervalSniper] {} final book (bid/ask): Up {}  Down {}",
+            old_market.slug,
+            quote(&top.token_id_up),
+            quote(&top.token_id_down)
+        );
+    }
+
+    let residual = state.inventory.size(&old_market.token_id_up)
+        + state.inventory.size(&old_market.token_id_down);
+    if residual > Decimal::ZERO && !state.holding_to_resolution {
+        info!(
+            "[IntervalSniper] {} sh still held at the close of {}: held to resolution",
+            fmt_decimal_2(&residual),
+            old_market.slug
+        );
+        state.holding_to_resolution = true;
+    }
+    let exit_type = if state.holding_to_resolution {
+        if !settle_held_position(http, gamma_base_url, old_market, &mut state.inventory).await {
+            carry_over(state, closed_position(state, old_market, now_ms_u));
+        }
+        ExitType::Resolution
+    } else {
+        ExitType::MarketClose
+    };
+    state.closed_market = Some(closed_position(state, old_market, now_ms_u));
+    let closed = state.inventory.close_all_at_mark();
+    let declined = state.declines.take_interval();
+    if !declined.is_empty() {
+        info!(
+            "[IntervalSniper] {} entries declined with the ask in range (ticks): {}",
+            old_market.slug,
+            entry_gate::counts_line(&declined)
+        );
+    }
+    if let Some(ref mut log) = state.session_log {
+        for lot in closed.iter().filter(|l| l.size > Decimal::ZERO) {
+            let _ = log.log_position_close(
+                &old_market.slug,
+                old_market.interval_start_unix,
+                old_market.close_time_unix,
+                lot.side,
+                lot.entry_price,
+                lot.exit_price,
+                lot.entry_ms,
+                now_ms_u,
+                exit_type,
+                lot.size,
+                state.fee_rates.side(lot.side),
+                state.interval_min_bid_up,
+                state.interval_max_bid_up,
+                state.interval_min_bid_down,
+                state.interval_max_bid_down,
+            );
+        }
+        let _ = log.log_interval_summary(
+            &old_market.slug,
+            old_market.interval_start_unix,
+            old_market.close_time_unix,
+            state.interval_min_bid_up,
+            state.interval_max_bid_up,
+            state.interval_min_bid_down,
+            state.interval_max_bid_down,
+            final_book.as_ref(),
+            &declined,
+        );
+    }
+    if let Some(ref mut report) = state.dry_run_report {
+        report.on_close(
+            &old_market.slug,
+            exit_type,
+            &closed,
+            &state.fee_rates,
+            now_ms_u,
+        );
+    }
+}
+
+/// Mark both tokens of a market left with a position held to resolution at their payout: Gamma
+/// outcomePrices when already resolved, else inferred from the last best bid (>= 0.5 pays 1.00).
+/// Returns false for an inferred payout (the market is not resolved yet).
+async fn settle_held_position(
+    http: &Client,
+    gamma_base_url: &str,
+    market: &ResolvedMarket,
+    inventory: &mut Inventory,
+) -> bool {
+    let resolved = match fetch_resolution(http, gamma_base_url, &market.slug).await {
+        Ok(r) => r,
+        Err(e) => {
+            warn!(
+                "[IntervalSniper] resolution fetch for {} failed: {}",
+                market.slug, e
+            );
+            None
+        }
+    };
+    let (up, down, from_gamma) = match resolved {
+        Some((up, down)) => (up, down, true),
+        None => {
+            let (up, down) = inferred_payout(inventory, market);
+            (up, down, false)
+        }
+    };
+    if from_gamma {
+        info!(
+            "[IntervalSniper] {}",
+            messages::resolved(&market.slug, up, down, true)
+        );
+    }
+    inventory.set_mark(&market.token_id_up, up);
+    inventory.set_mark(&market.token_id_down, down);
+    from_gamma
+}
+
+/// (Up, Down) payout of `market` inferred from the last best bid (>= 0.5 pays 1.00).
+fn inferred_payout(inventory: &Inventory, market: &ResolvedMarket) -> (Decimal, Decimal) {
+    let up_wins = inventory
+        .mark(&market.token_id_up)
+        .map(|b| b >= dec!(0.5))
+        .or_else(|| inventory.mark(&market.token_id_down).map(|b| b < dec!(0.5)))
+        .unwrap_or(false);
+    let up = if up_wins { Decimal::ONE } else { Decimal::ZERO };
+    (up, Decimal::ONE - up)
+}
+
+/// `market` at its close as a carried position without lots: the payout inferred from the last
+/// best bid, the interval's fee rate and bids.
+fn closed_position(state: &RunnerState, market: &ResolvedMarket, now_ms_u: u64) -> CarriedPosition {
+    CarriedPosition {
+        market: market.clone(),
+        lots_up: Vec::new(),
+        lots_down: Vec::new(),
+        inferred: inferred_payout(&state.inventory, market),
+        fee_rates: state.fee_rates.clone(),
+        interval_bids: [
+            state.interval_min_bid_up,
+            state.interval_max_bid_up,
+            state.interval_min_bid_down,
+            state.interval_max_bid_down,
+        ],
+        since_ms: now_ms_u,
+    }
+}
+
+/// Move the lots of a closed, not yet resolved market out of the inventory into the carryover,
+/// with the payout [settle_held_position] inferred.
+fn carry_over(state: &mut RunnerState, mut carried: CarriedPosition) {
+    carried.lots_up = state.inventory.take_lots(&carried.market.token_id_up);
+    carried.lots_down = state.inventory.take_lots(&carried.market.token_id_down);
+    if carried.size() <= Decimal::ZERO {
+        return;
+    }
+    info!(
+        "[IntervalSniper] {}",
+        messages::carried_over(
+            &carried.market.slug,
+            fmt_decimal_2(&carried.size()),
+            carried.inferred.0,
+            carried.inferred.1
+        )
+    );
+    state.carryovers.push(carried);
+}
+
+/// Carried positions or recorded intervals wait for a Gamma resolution.
+fn awaiting_resolution(state: &RunnerState) -> bool {
+    !state.carryovers.is_empty()
+        || state
+            .book_recorder
+            .as_ref()
+            .is_some_and(|r| r.has_pending())
+}
+
+/// Label the intervals the book recorder finished with their resolution once Gamma has it.
+async fn resolve_recorded_books(
+    state: &mut RunnerState,
+    http: &Client,
+    gamma_base_url: &str,
+    now_unix: u64,
+) {
+    let Some(rec) = state.book_recorder.as_mut() else {
+        return;
+    };
+    for slug in rec.pending(now_unix) {
+        match fetch_resolution(http, gamma_base_url, &slug).await {
+            Ok(Some(payout)) => {
+                if let Err(e) = rec.resolve(&slug, payout) {
+                    warn!(
+                        "[IntervalSniper] book recorder outcome of {} failed: {}",
+                        slug, e
+                    );
+                }
+            }
+            Ok(None) => {}
+            Err(e) => debug!(
+                "[IntervalSniper] resolution fetch for {} failed: {}",
+                slug, e
+            ),
+        }
+    }
+}
+
+/// Poll Gamma for the resolution of every carried position and close the resolved ones at their
+/// payout. `give_up`: close the rest at the inferred payout (the run is ending).
+async fn check_carryovers(
+    state: &mut RunnerState,
+    http: &Client,
+    gamma_base_url: &str,
+    now_ms_u: u64,
+    give_up: bool,
+) {
+    for carried in std::mem::take(&mut state.carryovers) {
+        let resolved = match fetch_resolution(http, gamma_base_url, &carried.market.slug).await {
+            Ok(r) => r,
+            Err(e) => {
+                debug!(
+                    "[IntervalSniper] resolution fetch for {} failed: {}",
+                    carried.market.slug, e
+                );
+                None
+            }
+        };
+        match resolved {
+            Some(payout) => settle_carried(state, carried, payout, true, now_ms_u),
+            None if give_up => {
+                let inferred = carried.inferred;
+                settle_carried(state, carried, inferred, false, now_ms_u)
+            }
+            None => state.carryovers.push(carried),
+        }
+    }
+}
+
+/// Close a carried position at (Up, Down) payouts: session log and dry-run report closes
+/// (RESOLUTION), and with MM_AUTO_REDEEM a redemption of its shares once Gamma resolved it.
+fn settle_carried(
+    state: &mut RunnerState,
+    carried: CarriedPosition,
+    (up, down): (Decimal, Decimal),
+    from_gamma: bool,
+    now_ms_u: u64,
+) {
+    info!(
+        "[IntervalSniper] {}",
+        messages::resolved(&carried.market.slug, up, down, from_gamma)
+    );
+    let pnl = carried.pnl_at(up, down);
+    if from_gamma && (up, down) != carried.inferred {
+        warn!(
+            "[IntervalSniper] {} resolved against the payout inferred at the close (Up {} Down {}): PnL {} instead of {}",
+            carried.market.slug,
+            carried.inferred.0,
+            carried.inferred.1,
+            pnl.round_dp(4),
+            carried.pnl_at(carried.inferred.0, carried.inferred.1).round_dp(4)
+        );
+    }
+    let CarriedPosition {
+        market,
+        lots_up,
+        lots_down,
+        fee_rates,
+        interval_bids: [min_up, max_up, min_down, max_down],
+        ..
+    } = carried;
+    let mut closed = state.inventory.close_lots(&market.token_id_up, lots_up, up);
+    closed.extend(
+        state
+            .inventory
+            .close_lots(&market.token_id_down, lots_down, down),
+    );
+    if let Some(ref mut log) = state.session_log {
+        for lot in closed.iter().filter(|l| l.size > Decimal::ZERO) {
+            let _ = log.log_position_close(
+                &market.slug,
+                market.interval_start_unix,
+                market.close_time_unix,
+                lot.side,
+                lot.entry_price,
+                lot.exit_price,
+                lot.entry_ms,
+                now_ms_u,
+                ExitType::Resolution,
+                lot.size,
+                fee_rates.side(lot.side),
+                min_up,
+                max_up,
+                min_down,
+                max_down,
+            );
+        }
+    }
+    if let Some(ref mut report) = state.dry_run_report {
+        report.on_close(
+            &market.slug,
+            ExitType::Resolution,
+            &closed,
+            &fee_rates,
+            now_ms_u,
+        );
+    }
+    if from_gamma && state.config.auto_redeem && !state.config.dry_run {
+        let (slug, condition_id) = (market.slug.clone(), market.condition_id.clone());
+        state.redemptions.push(tokio::spawn(async move {
+            match crate::redeem::redeem_from_env(&condition_id).await {
+                Ok(tx) => info!("[Redeem] {} redeemed: tx {}", slug, tx),
+                Err(e) => warn!(
+                    "[Redeem] {} not redeemed ({:#}); redeem it on polymarket.com",
+                    slug, e
+                ),
+            }
+        }));
+    }
+}
+
+/// End of the run: carry the fills of entries still in flight, wait up to MM_CARRYOVER_WAIT_SECS
+/// for the carried positions and recorded intervals to resolve, settle the rest at their inferred
+/// payout and let pending redemptions finish.
+async fn finish_carryovers(
+    state: &mut RunnerState,
+    http: &Client,
+    gamma_base_url: &str,
+    clock: &dyn Clock,
+) {
+    // Entries still in flight at the end are answered by the HTTP timeout at the latest; their
+    // fills are carried with the closed market.
+    while state.orders.has_in_flight() && !SHUTDOWN.load(Ordering::Relaxed) {
+        clock.sleep(Duration::from_millis(DRAIN_POLL_MS)).await;
+        apply_order_outcomes(state, clock.now_ms());
+    }
+    let deadline = clock
+        .now_ms()
+        .saturating_add(state.config.carryover_wait_secs.saturating_mul(1000));
+    while awaiting_resolution(state) {
+        let now_ms_u = clock.now_ms();
+        let give_up = now_ms_u >= deadline || SHUTDOWN.load(Ordering::Relaxed);
+        check_carryovers(state, http, gamma_base_url, now_ms_u, give_up).await;
+        resolve_recorded_books(state, http, gamma_base_url, now_ms_u / 1000).await;
+        if give_up {
+            break;
+        }
+        if awaiting_resolution(state) {
+            clock
+                .sleep(Duration::from_millis(
+                    CARRYOVER_CHECK_MS.min(deadline.saturating_sub(now_ms_u)),
+                ))
+                .await;
+        }
+    }
+    for redemption in state.redemptions.drain(..) {
+        let _ = redemption.await;
+    }
+}
+
+/// Set on Ctrl-C in single-engine mode: the loop stops, reconciles with the trades history and
+/// writes the session summary.
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+
+/// Run the bot with the configuration from the environment ([load_config]) until the session
+/// ends.
+pub async fn run() -> Result<()> {
+    let config = load_config()?;
+    messages::set_lang(config.log_lang);
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            info!("[IntervalSniper] {}", messages::ctrl_c());
+            SHUTDOWN.store(true, Ordering::Relaxed);
+        }
+    });
+    #[cfg(unix)]
+    if config.decision_trace > 0 {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut usr1 = signal(SignalKind::user_defined1())?;
+        tokio::spawn(async move {
+            while usr1.recv().await.is_some() {
+                decision_trace::request_dump();
+            }
+        });
+    }
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+    match config.market_target {
+        MarketTarget::Interval(asset, TargetWindow::Active) if config.dual_window => {
+            run_dual_window(config, asset, clock).await
+        }
+        _ => run_with_clock(config, clock).await,
+    }
+}
+
+/// MM_DUAL_WINDOW: one engine per 5-min interval, each started MM_DUAL_WINDOW_LEAD_SECS before
+/// its interval opens (connected, but not entering before the open). At every boundary the engine
+/// of the closing interval finishes its exits and settles while the next one is already live, so
+/// the switch leaves no blind gap.
+async fn run_dual_window(
+    config: Config,
+    asset: IntervalMarketAsset,
+    clock: Arc<dyn Clock>,
+) -> Result<()> {
+    let mut engines: Vec<(String, JoinHandle<()>)> = Vec::new();
+    let mut next_start = interval_start_unix(clock.now_unix());
+    while !SHUTDOWN.load(Ordering::Relaxed) {
+        let start_at = next_start.saturating_sub(config.dual_window_lead_secs);
+        let now = clock.now_unix();
+        if now < start_at {
+            // Short sleeps so Ctrl-C is seen promptly.
+            clock
+                .sleep(Duration::from_secs((start_at - now).min(1)))
+                .await;
+            continue;
+        }
+        engines.retain(|(_, h)| !h.is_finished());
+        let mut engine_config = config.clone();
+        engine_config.market_target = MarketTarget::IntervalAt(asset, next_start);
+        engine_config.market_slug = interval_slug_at(asset, next_start);
+        engine_config.status_file = engine_config
+            .status_file
+            .map(|p| status::engine_path(&p, &engine_config.market_slug));
+        let label = engine_config.market_slug.clone();
+        info!(
+            "[DualWindow] starting engine for {} ({} running)",
+            label,
+            engines.len()
+        );
+        let engine_label = label.clone();
+        let engine_clock = clock.clone();
+        let handle = tokio::spawn(async move {
+            if let Err(e) = run_with_clock(engine_config, engine_clock).await {
+                warn!("[DualWindow] engine {} stopped: {}", engine_label, e);
+            }
+        });
+        engines.push((label, handle));
+        next_start += BTC_5MIN_INTERVAL_SEC;
+    }
+    for (label, handle) in engines {
+        if handle.await.is_err() {
+            warn!("[DualWindow] engine {} panicked", label);
+        }
+    }
+    Ok(())
+}
+
+/// Close the loop tick; warn with its phase breakdown when it ran past MM_SLOW_TICK_MS.
+fn end_tick(profiler: &mut TickProfiler) {
+    if let Some(t) = profiler.end() {
+        warn!(
+            "[IntervalSniper] slow tick {} ms: sync {} ms, evaluate {} ms, execute {} ms",
+            t.total.as_millis(),
+            t.sync.as_millis(),
+            t.evaluate.as_millis(),
+            t.execute.as_millis()
+        );
+    }
+}
+
+/// Lock every traded wallet on the market target so a second live instance refuses to start (see
+/// [crate::instance_lock]). Dry runs have no wallets and take no lock.
+fn lock_instance(config: &Config, funders: &[String], now_ms: u64) -> Result<Vec<InstanceLock>> {
+    if funders.is_empty() {
+        return Ok(Vec::new());
+    }
+    if config.allow_multiple_instances {
+        warn!("[IntervalSniper] MM_ALLOW_MULTIPLE_INSTANCES: instance lock skipped");
+        return Ok(Vec::new());
+    }
+    let dir = config
+        .instance_lock_dir
+        .as_deref()
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    let key = instance_lock::target_key(&config.market_target);
+    funders
+        .iter()
+        .map(|wallet| {
+            let lock = InstanceLock::acquire(&dir, wallet, &key, now_ms)?;
+            debug!("[IntervalSniper] instance lock {}", lock.path().display());
+            Ok(lock)
+        })
+        .collect()
+}
+
+/// Run one sniping engine with `config` (the scanner starts one per discovered market).
+pub async fn run_with_config(config: Config) -> Result<()> {
+    run_with_clock(config, Arc::new(SystemClock)).await
+}
+
+/// [run_with_config] on `clock`: a [crate::clock::SimClock] replays the engine in simulated time.
+pub async fn run_with_clock(config: Config, clock: Arc<dyn Clock>) -> Result<()> {
+    let decision_trace = (config.decision_trace > 0).then(|| {
+        Arc::new(DecisionTrace::new(
+            config.decision_trace,
+            &config.session_log_dir,
+            &config.market_slug,
+        ))
+    });
+    let result = run_engine(config, clock.clone(), decision_trace.clone()).await;
+    if let (Err(e), Some(trace)) = (&result, decision_trace.as_ref()) {
+        dump_decision_trace(trace, &format!("engine error: {:#}", e), clock.now_ms());
+    }
+    result
+}
+
+/// The engine behind [run_with_clock], recording entry evaluations to `decision_trace` when set.
+async fn run_engine(
+    mut config: Config,
+    clock: Arc<dyn Clock>,
+    decision_trace: Option<Arc<DecisionTrace>>,
+) -> Result<()> {
+    messages::set_lang(config.log_lang);
+    let clob_host = std::env::var("POLYMARKET_CLOB_HOST")
+        .unwrap_or_else(|_| "https://clob.polymarket.com".to_string());
+    let http = public_client(Duration::from_secs(10))?;
+    let session_start_ms = clock.now_ms();
+    // The JSONL files stay the record when the database is unavailable.
+    let session_db = config.session_db.as_deref().and_then(|path| {
+        SessionDb::open(std::path::Path::new(path), session_start_ms, config.dry_run)
+            .map_err(|e| warn!("[SessionDb] not used: {:#}", e))
+            .ok()
+            .map(Arc::new)
+    });
+    let tracker = Arc::new(std::sync::Mutex::new(
+        OrderTracker::new(
+            config
+                .order_audit
+                .then_some(config.session_log_dir.as_str()),
+            session_start_ms,
+        )?
+        .with_db(session_db.clone())
+        .with_clock(clock.clone()),
+    ));
+    let clob: Arc<Box<dyn ClobClient>> = Arc::new(Box::new(TrackingClob::new(
+        crate::clob::create_clob_client_with_clock(config.dry_run, clock.clone())?,
+        tracker.clone(),
+    )));
+    // Held until the engine returns.
+    let _instance_locks = lock_instance(&config, &clob.funders(), clock.now_ms())?;
+    preflight_balance(clob.as_ref().as_ref(), &mut config).await?;
+    clob.warm_up().await;
+    let funders = clob.funders();
+    if config.positions_check_secs > 0 && !funders.is_empty() {
+        tokio::spawn(check_positions(
+            http.clone(),
+            config.data_api_url.clone(),
+            funders.clone(),
+            Vec::new(),
+            true,
+        ));
+    }
+
+    let mut state = RunnerState {
+        market: None,
+        ws_book: None,
+        ws_state: None,
+        config: config.clone(),
+        phases: Phases::default(),
+        trades_this_interval: 0,
+        total_shares_this_interval: Decimal::ZERO,
+        pending_auto_sell: None,
+        pending_stop_loss: None,
+        pending_soft_stop: None,
+        interval_switch_wall_time_ms: None,
+        session_log: None,
+        dry_run_report: None,
+        interval_min_bid_up: None,
+        interval_max_bid_up: None,
+        interval_min_bid_down: None,
+        interval_max_bid_down: None,
+        hedged_pair: None,
+        hedge_bid: None,
+        exit_orders: HashMap::new(),
+        strategy: create_strategy(&config.strategy),
+        inventory: Inventory::default(),
+        last_heartbeat_ms: 0,
+        declines: DeclineCounters::default(),
+        decision_trace: decision_trace.clone(),
+        trace_dumps_seen: decision_trace::dump_requests(),
+        last_positions_check_ms: clock.now_ms(),
+        last_balance_drift_check_ms: clock.now_ms(),
+        usdc_baseline: Arc::default(),
+        last_fill_ms: None,
+        calendar: None,
+        book_recorder: None,
+        status_file: None,
+        fee_rates: FeeRates::default(),
+        holding_to_resolution: false,
+        carryovers: Vec::new(),
+        closed_market: None,
+        last_carryover_check_ms: 0,
+        redemptions: Vec::new(),
+        sl_breach_since_ms: None,
+        next_market_prefetched: false,
+        shadow: ShadowRunner::from_config(&config),
+        spot: None,
+        strike: None,
+        base_buy_ranges: [
+            (config.up.min_buy_price, config.up.max_buy_price),
+            (config.down.min_buy_price, config.down.max_buy_price),
+        ],
+        orders: OrderManager::spawn(clob.clone(), clock.clone()),
+        resting_buy: None,
+        passive_tp: PassiveTp::Unposted,
+        clock: clock.clone(),
+    };
+
+    if config.session_log_enabled || session_db.is_some() {
+        state.session_log =
+            SessionLog::new(session_start_ms, &config.session_log_dir)?.map(|log| {
+                log.with_db(session_db.clone())
+                    .with_orders(tracker.clone())
+                    .with_clock(clock.clone())
+            });
+    }
+    if config.dry_run && config.dry_run_report {
+        state.dry_run_report = Some(DryRunReport::new(
+            &config.session_log_dir,
+            crate::clob::sim_fills_enabled(),
+            session_start_ms,
+        )?);
+    }
+    if let Some(ref path) = config.blackout_file {
+        let calendar = EventCalendar::load(path)?;
+        info!(
+            "[IntervalSniper] loaded {} blackout window(s) from {}",
+            calendar.len(),
+            path
+        );
+        state.calendar = Some(calendar);
+    }
+    if let MarketTarget::HourlyAbove(asset) = config.market_target {
+        // Strike-aware entries compare the spot price to the strike.
+        state.spot = Some(SpotFeed::spawn(
+            config.spot_base_url.clone(),
+            hourly_spot_symbol(asset),
+            config.spot_poll_ms,
+        )?);
+    } else if config.side_policy == SidePolicy::SpotDirection {
+        match config.market_target {
+            MarketTarget::Interval(asset, _) | MarketTarget::IntervalAt(asset, _) => {
+                state.spot = Some(SpotFeed::spawn(
+                    config.spot_base_url.clone(),
+                    spot_symbol(asset),
+                    config.spot_poll_ms,
+                )?);
+            }
+            _ => warn!(
+                "[IntervalSniper] MM_SIDE_POLICY=spot_direction needs an interval market; no entries without a spot signal"
+            ),
+        }
+    }
+    if config.book_record_enabled {
+        state.book_recorder = Some(BookRecorder::new(
+            &config.book_record_dir,
+            config.book_record_levels,
+            config.book_record_interval_ms,
+            config.book_record_mid_outcome,
+        )?);
+    }
+    if let Some(ref path) = config.status_file {
+        state.status_file = Some(StatusFile::new(path, config.status_interval_secs)?);
+    }
+
+    info!(
+        "[IntervalSniper] {}",
+        messages::started(config.dry_run, &config.market_slug, state.strategy.name())
+    );
+    if config.observe_only {
+        info!(
+            "[IntervalSniper] observe only: no credentials loaded; would-be orders and closes go to {}",
+            config.session_log_dir
+        );
+    }
+
+    let loop_ms = config.loop_ms;
+    let mut tick_count: u64 = 0;
+    let mut last_order_sync_ms: u64 = 0;
+    let mut order_sync_after_unix = clock.now_unix();
+    let mut profiler = TickProfiler::new(config.slow_tick_ms);
+
+    loop {
+        if SHUTDOWN.load(Ordering::Relaxed) {
+            break;
+        }
+        if let Some(trace) = state.decision_trace.as_ref() {
+            let requests = decision_trace::dump_requests();
+            if requests != state.trace_dumps_seen {
+                state.trace_dumps_seen = requests;
+                dump_decision_trace(trace, "requested", clock.now_ms());
+            }
+        }
+        profiler.begin();
+        tick_count += 1;
+        let now_u = clock.now_unix();
+        let now_ms_u = clock.now_ms();
+
+        if awaiting_resolution(&state)
+            && now_ms_u.saturating_sub(state.last_carryover_check_ms) >= CARRYOVER_CHECK_MS
+        {
+            state.last_carryover_check_ms = now_ms_u;
+            check_carryovers(&mut state, &http, &config.gamma_base_url, now_ms_u, false).await;
+            resolve_recorded_books(&mut state, &http, &config.gamma_base_url, now_u).await;
+        }
+
+        // Refresh market if needed (interval switch) — interval target uses the current 5-min window slug
+        // e.g. 5:15–5:20 → btc-updown-5m-1772169300, 5:20–5:25 → btc-updown-5m-1772169600
+        let need_new_market = state
+            .market
+            .as_ref()
+            .map(|m| market_expired(&config.market_target, m, now_u))
+            .unwrap_or(true);
+
+        if need_new_market {
+            // Settle the market we're leaving before anything is reset for the next one.
+            if let Some(old_market) = state.market.clone() {
+                settle_interval(
+                    &mut state,
+                    clob.as_ref().as_ref(),
+                    &http,
+                    &clob_host,
+                    &config.gamma_base_url,
+                    &old_market,
+                    clock.as_ref(),
+                )
+                .await;
+                let stats = profiler.take_stats();
+                if let Some(ref mut log) = state.session_log {
+                    let _ = log.log_tick_profile(&old_market.slug, &stats);
+                }
+                // Redemptions and fees of the old interval are not in the bookkeeping.
+                if let Ok(mut baseline) = state.usdc_baseline.lock() {
+                    *baseline = None;
+                }
+            }
+            if let Some(ref mut rec) = state.book_recorder {
+                if let Err(e) = rec.finish() {
+                    warn!("[IntervalSniper] book recorder finish failed: {}", e);
+                }
+            }
+            // A fixed target market has no next interval: stop once it closes.
+            let fixed_target = matches!(
+                config.market_target,
+                MarketTarget::Slug(_) | MarketTarget::ConditionId(_) | MarketTarget::IntervalAt(..)
+            );
+            if fixed_target && state.market.is_some() {
+                info!(
+                    "[IntervalSniper] {}",
+                    messages::target_market_closed(&config.market_slug)
+                );
+                finish_carryovers(&mut state, &http, &config.gamma_base_url, clock.as_ref()).await;
+                return Ok(());
+            }
+            let mut strike = None;
+            let resolved = match &config.market_target {
+                // Event target: scan every market's book and trade the best outcome's market.
+                MarketTarget::Event(event_slug) => {
+                    match scan_event(&http, &clob_host, &config, event_slug, now_u).await {
+                        Ok(EventScan::Closed) => {
+                            info!("[IntervalSniper] {}", messages::event_closed(event_slug));
+                            finish_carryovers(
+                                &mut state,
+                                &http,
+                                &config.gamma_base_url,
+                                clock.as_ref(),
+                            )
+                            .await;
+                            return Ok(());
+                        }
+                        Ok(EventScan::NoEntry { open_markets }) => {
+                            debug!(
+                                "[IntervalSniper] event {}: no entry across {} open markets",
+                                event_slug, open_markets
+                            );
+                            state.market = None;
+                            clock.sleep(Duration::from_millis(EVENT_RESCAN_MS)).await;
+                            continue;
+                        }
+                        Ok(EventScan::Pick(pick)) => {
+                            info!(
+                                "[IntervalSniper] event {}: best entry {} {:?} ask={} size={}",
+                                event_slug,
+                                pick.market.slug,
+                                pick.side,
+                                fmt_decimal_2(&pick.best_ask),
+                                fmt_decimal_2(&pick.size_available)
+                            );
+                            Ok(pick.market)
+                        }
+                        Err(e) => Err(e),
+                    }
+                }
+                // Hourly "above" target: the event of the current hour, strikes the spot is beyond.
+                MarketTarget::HourlyAbove(asset) => {
+                    let event_slug = hourly_event_slug(*asset, hour_close_unix(now_u));
+                    let spot = state.spot.as_ref().and_then(|s| s.last_price());
+                    match scan_hourly_above(&http, &clob_host, &config, &event_slug, spot, now_u)
+                        .await
+                    {
+                        Ok(EventScan::Pick(pick)) => {
+                            info!(
+                                "[IntervalSniper] {}: strike {} {:?} ask={} spot={}",
+                                event_slug,
+                                pick.strike.unwrap_or_default(),
+                                pick.side,
+                                fmt_decimal_2(&pick.best_ask),
+                                spot.unwrap_or_default()
+                            );
+                            strike = pick.strike;
+                            Ok(pick.market)
+                        }
+                        Ok(scan) => {
+                            debug!(
+                                "[IntervalSniper] {}: no strike to enter ({:?}, spot {:?})",
+                                event_slug, scan, spot
+                            );
+                            state.market = None;
+                            clock.sleep(Duration::from_millis(EVENT_RESCAN_MS)).await;
+                            continue;
+                        }
+                        Err(e) => Err(e),
+                    }
+                }
+                _ => {
+                    resolve_market(&http, &config.gamma_base_url, &config.market_target, now_u)
+                        .await
+                }
+            };
+            match resolved {
+                Ok(market) if fixed_target && now_u >= market.close_time_unix => {
+                    anyhow::bail!("target market {} is already closed", market.slug);
+                }
+                Ok(market) => {
+                    // Keep the open WS and move it to the new tokens; connect only if there is none
+                    // (or the resubscribe fails).
+                    let reused = match state.ws_book.as_ref() {
+                        Some(ws) => match ws
+                            .replace_assets(&market.token_id_up, &market.token_id_down)
+                            .await
+                        {
+                            Ok(()) => true,
+                            Err(e) => {
+                                warn!(
+                                    "[IntervalSniper] WebSocket resubscribe failed: {:#}, reconnecting",
+                                    e
+                                );
+                                false
+                            }
+                        },
+                        None => false,
+                    };
+                    state.ws_state = None;
+                    if reused {
+                        info!("[IntervalSniper] WebSocket order book resubscribed to the new interval");
+                    } else {
+                        state.ws_book = None; // drop previous WS before creating new
+                        let ws_url = ClobWsBook::ws_url_from_rest_host(&clob_host);
+                        match ClobWsBook::connect_with_clock(
+                            &ws_url,
+                            &market.token_id_up,
+                            &market.token_id_down,
+                            clock.clone(),
+                        )
+                        .await
+                        {
+                            Ok(mut ws) => {
+                                if config.ws_quiet_refresh_secs > 0 {
+                                    ws.start_rest_refresh(
+                                        http.clone(),
+                                        &clob_host,
+                                        RestRefresh {
+                                            quiet: Duration::from_secs(
+                                                config.ws_quiet_refresh_secs,
+                                            ),
+                                            every: Duration::from_secs(
+                                                config.ws_quiet_refresh_every_secs,
+                                            ),
+                                        },
+                                    );
+                                }
+                                state.ws_book = Some(ws);
+                                info!(
+                                    "[IntervalSniper] WebSocket order book connected (real-time)"
+                                );
+                            }
+                            Err(e) => {
+                                warn!(
+                                    "[IntervalSniper] WebSocket book connect failed: {}, using REST",
+                                    e
+                                );
+                            }
+                        }
+                    }
+                    // Warm the CLOB connection for this window's first order, in the background.
+                    let warm_clob = clob.clone();
+                    tokio::spawn(async move { warm_clob.warm_up().await });
+                    state.strategy.on_interval_start(&market);
+                    if let Some(shadow) = state.shadow.as_mut() {
+                        if let Some(ref old_market) = state.market {
+                            shadow.on_interval_end(old_market);
+                        }
+                        shadow.on_interval_start(&market);
+                    }
+                    state.market = Some(market.clone());
+                    state.strike = strike;
+                    state.resting_buy = None;
+                    state.passive_tp = PassiveTp::Unposted;
+                    state.phases.on_interval_start();
+                    state.trades_this_interval = 0;
+                    state.total_shares_this_interval = Decimal::ZERO;
+                    state.pending_auto_sell = None;
+                    state.pending_stop_loss = None;
+                    state.pending_soft_stop = None;
+                    state.interval_switch_wall_time_ms = Some(now_ms_u);
+                    state.interval_min_bid_up = None;
+                    state.interval_max_bid_up = None;
+                    state.interval_min_bid_down = None;
+                    state.interval_max_bid_down = None;
+                    state.hedged_pair = None;
+                    state.hedge_bid = None;
+                    state.exit_orders.clear();
+                    state.holding_to_resolution = false;
+                    state.sl_breach_since_ms = None;
+                    state.next_market_prefetched = false;
+                    state.fee_rates =
+                        FeeRates::fetch(&http, &clob_host, &market, config.fee_rate_bps, now_ms_u)
+                            .await;
+                    if let Some(sizing) = interval_sizing(
+                        clob.as_ref().as_ref(),
+                        &state.config,
+                        &state.inventory,
+                        market.min_order_size,
+                    )
+                    .await
+                    {
+                        state.config.size_shares = sizing.shares;
+                    }
+                    let up_id = market.token_id_up.trim();
+                    let down_id = market.token_id_down.trim();
+                    info!(
+                        "[IntervalSniper] {}",
+                        messages::interval_switch(
+                            &market.slug,
+                            if up_id.len() > 12 {
+                                &up_id[..12]
+                            } else {
+                                up_id
+                            },
+                            if down_id.len() > 12 {
+                                &down_id[..12]
+                            } else {
+                                down_id
+                            },
+                            &state.fee_rates,
+                            market.tick_size,
+                            market.min_order_size
+                        )
+                    );
+                }
+                Err(e) => {
+                    warn!("[IntervalSniper] fetch market failed: {}", e);
+                    end_tick(&mut profiler);
+                    clock.sleep(Duration::from_millis(loop_ms)).await;
+                    continue;
+                }
+            }
+        }
+
+        let market = match &state.market {
+            Some(m) => m,
+            None => {
+                end_tick(&mut profiler);
+                clock.sleep(Duration::from_millis(loop_ms)).await;
+                continue;
+            }
+        };
+
+        let secs_to_close = seconds_to_close(now_u, market.close_time_unix);
+
+        // Resolve the next interval ahead of the switch; the switch then reads it from the cache.
+        if let MarketTarget::Interval(asset, window) = config.market_target {
+            let secs_to_switch =
+                seconds_to_close(now_u, switch_time_unix(&config.market_target, market));
+            if !state.next_market_prefetched && secs_to_switch <= NEXT_MARKET_PREFETCH_SECS {
+                state.next_market_prefetched = true;
+                let http = http.clone();
+                let gamma_base_url = config.gamma_base_url.clone();
+                tokio::spawn(async move {
+                    if let Err(e) =
+                        prefetch_next_interval(&http, &gamma_base_url, asset, window, now_u).await
+                    {
+                        debug!("[IntervalSniper] next market prefetch failed: {}", e);
+                    }
+                });
+            }
+        }
+
+        if let Some(ref ws) = state.ws_book {
+            let ws_state = ws.connection_state();
+            if state.ws_state != Some(ws_state) {
+                if ws_state != WsConnectionState::Live {
+                    warn!(
+                        "[IntervalSniper] WebSocket book {:?}, reading the book over REST",
+                        ws_state
+                    );
+                } else if state.ws_state.is_some() {
+                    info!("[IntervalSniper] WebSocket book live again");
+                }
+                state.ws_state = Some(ws_state);
+            }
+        }
+
+        // Top of book: WebSocket (instant) when live, else REST. Fallback to REST if WS has no data
+        // yet, or none newer than MM_MAX_BOOK_AGE_MS.
+        let top = if let Some(ws) = live_ws(&state.ws_book) {
+            let t = ws.get_top_of_book().await;
+            if top_has_book_data(&t) && !book_too_old(&state.config, &t, now_ms_u) {
+                t
+            } else {
+                fetch_top_of_book(
+                    &http,
+                    &clob_host,
+                    &market.token_id_up,
+                    &market.token_id_down,
+                    clock.now_ms(),
+                )
+                .await
+                .unwrap_or(t)
+            }
+        } else {
+            match fetch_top_of_book(
+                &http,
+                &clob_host,
+                &market.token_id_up,
+                &market.token_id_down,
+                clock.now_ms(),
+            )
+            .await
+            {
+                Ok(t) => t,
+                Err(e) => {
+                    warn!("[IntervalSniper] order book fetch failed: {}", e);
+                    end_tick(&mut profiler);
+                    clock.sleep(Duration::from_millis(loop_ms)).await;
+                    continue;
+                }
+            }
+        };
+
+        // A book still too old after the REST fallback is not traded on: no entry, TP / SL hold.
+        let stale_book = book_too_old(&state.config, &top, now_ms_u);
+        if stale_book && tick_count.is_multiple_of(LOG_BOOK_EVERY_TICKS) {
+            warn!(
+                "[IntervalSniper] book {} ms old (MM_MAX_BOOK_AGE_MS={}): entries and TP / SL on hold",
+                book_age_ms(&top, now_ms_u).unwrap_or_default(),
+                state.config.max_book_age_ms
+            );
+        }
+
+        let token_id_up = market.token_id_up.clone();
+        let token_id_down = market.token_id_down.clone();
+        update_interval_bids(&mut state, &token_id_up, &token_id_down, &top);
+        update_market_params(&mut state, &top);
+        if let Ok(mut t) = tracker.lock() {
+            t.set_book(&token_id_up, &token_id_down, &top);
+        }
+        apply_order_outcomes(&mut state, now_ms_u);
+        for expiry in state.orders.poll_expired() {
+            if expiry.intent.params.side == OrderSide::Sell {
+                on_passive_tp_expired(&mut state, clob.as_ref().as_ref(), expiry).await;
+            } else {
+                on_buy_expired(&mut state, clob.as_ref().as_ref(), expiry).await;
+            }
+        }
+        if state.resting_buy.is_some() {
+            chase_resting_buy(&mut state, clob.as_ref().as_ref(), &top, now_ms_u).await;
+        }
+        if state.config.take_profit_passive_ms > 0 {
+            manage_passive_tp(
+                &mut state,
+                clob.as_ref().as_ref(),
+                &top,
+                secs_to_close,
+                now_ms_u,
+            )
+            .await;
+        }
+        if state.hedge_bid.is_some() {
+            track_hedge_bid(&mut state, clob.as_ref().as_ref(), now_ms_u).await;
+        }
+        move_stop_to_breakeven(&mut state, &top);
+        if state.pending_soft_stop.is_some() {
+            soft_stop(&mut state, clob.as_ref().as_ref(), &top, now_ms_u).await?;
+        }
+        let market = state
+            .market
+            .as_ref()
+            .expect("market set after need_new_market check");
+        let tick_size = market.tick_size;
+        if let (Some(rec), Some(ws)) = (state.book_recorder.as_mut(), state.ws_book.as_ref()) {
+            if rec.due(now_ms_u) {
+                if let Err(e) = rec.sample(market, ws, now_ms_u).await {
+                    warn!("[IntervalSniper] book recorder sample failed: {}", e);
+                }
+            }
+        }
+        let min_order_size = market.min_order_size;
+        let spot_move = state
+            .spot
+            .as_ref()
+            .and_then(|spot| spot.move_since_open(market.interval_start_unix));
+
+        profiler.phase(TickPhase::Evaluate);
+        if let Some(shadow) = state.shadow.as_mut() {
+            shadow.on_tick(&TickContext {
+                config: &state.config,
+                market,
+                top: &top,
+                secs_to_close,
+                tick_size,
+                min_order_size,
+                now_ms: now_ms_u,
+                spot_move,
+            });
+        }
+
+        if now_ms_u.saturating_sub(state.last_heartbeat_ms) >= HEARTBEAT_MS {
+            state.last_heartbeat_ms = now_ms_u;
+            log_heartbeat(&state.inventory, market, state.ws_book.as_ref(), now_ms_u);
+        }
+        if state.status_file.as_ref().is_some_and(|f| f.due(now_ms_u)) {
+            let status = {
+                let tracker = tracker.lock().ok();
+                status_json(
+                    &state,
+                    market,
+                    &top,
+                    secs_to_close,
+                    tracker.as_deref(),
+                    now_ms_u,
+                )
+            };
+            if let Some(ref mut file) = state.status_file {
+                if let Err(e) = file.write(&status, now_ms_u) {
+                    warn!("[IntervalSniper] status file write failed: {}", e);
+                }
+            }
+        }
+
+        // Resting (GTC) orders: their fills only show up in the trades history. Synced more often
+        // the closer an order is to filling; not at all without one.
+        let order_sync_ms = tracker.lock().ok().filter(|t| t.has_open()).map(|t| {
+            order_sync_interval_ms(
+                t.ticks_to_fill(tick_size),
+                state.config.order_sync_min_ms,
+                state.config.order_sync_max_ms,
+            )
+        });
+        if !funders.is_empty()
+            && order_sync_ms.is_some_and(|ms| now_ms_u.saturating_sub(last_order_sync_ms) >= ms)
+        {
+            last_order_sync_ms = now_ms_u;
+            let after = order_sync_after_unix;
+            order_sync_after_unix = now_u.saturating_sub(60);
+            let (clob, tracker) = (clob.clone(), tracker.clone());
+            tokio::spawn(async move {
+                match clob.get_trades(after).await {
+                    Ok(fills) => {
+                        if let Ok(mut t) = tracker.lock() {
+                            t.on_fills(&fills);
+                        }
+                    }
+                    Err(e) => debug!("[OrderTracker] trades sync failed: {:#}", e),
+                }
+            });
+        }
+
+        if state.config.positions_check_secs > 0
+            && !funders.is_empty()
+            && now_ms_u.saturating_sub(state.last_positions_check_ms)
+                >= state.config.positions_check_secs * 1000
+            && !state.orders.has_in_flight()
+            && state
+                .last_fill_ms
+                .is_none_or(|t| now_ms_u.saturating_sub(t) >= POSITIONS_SETTLE_MS)
+        {
+            state.last_positions_check_ms = now_ms_u;
+            let expected = [("Up", &market.token_id_up), ("Down", &market.token_id_down)]
+                .into_iter()
+                .map(|(label, id)| (label, id.clone(), state.inventory.size(id)))
+                .collect();
+            tokio::spawn(check_positions(
+                http.clone(),
+                state.config.data_api_url.clone(),
+                funders.clone(),
+                expected,
+                false,
+            ));
+        }
+
+        if state.config.balance_drift_secs > 0
+            && now_ms_u.saturating_sub(state.last_balance_drift_check_ms)
+                >= state.config.balance_drift_secs * 1000
+            && !state.orders.has_in_flight()
+            && !tracker.lock().is_ok_and(|t| t.has_open())
+            && state
+                .last_fill_ms
+                .is_none_or(|t| now_ms_u.saturating_sub(t) >= POSITIONS_SETTLE_MS)
+        {
+            state.last_balance_drift_check_ms = now_ms_u;
+            let expected = [("Up", &market.token_id_up), ("Down", &market.token_id_down)]
+                .into_iter()
+                .map(|(label, id)| (label, id.clone(), state.inventory.size(id)))
+                .collect();
+            tokio::spawn(check_balance_drift(
+                clob.clone(),
+                expected,
+                state.inventory.cash_flow(),
+                state.usdc_baseline.clone(),
+                state.config.balance_drift_shares,
+                state.config.balance_drift_usdc,
+            ));
+        }
+
+        // Periodic log: order book scan (real-time visibility) — debug only so terminal shows only buy/sell events
+        if tick_count.is_multiple_of(LOG_BOOK_EVERY_TICKS) {
+            let up = top.token_id_up.as_ref();
+            let down = top.token_id_down.as_ref();
+            debug!(
+                "[IntervalSniper] order book Up bid={} ask={} | Down bid={} ask={} | secs_to_close={}",
+                fmt_price(up.and_then(|s| s.best_bid.as_ref())),
+                fmt_price(up.and_then(|s| s.best_ask.as_ref())),
+                fmt_price(down.and_then(|s| s.best_bid.as_ref())),
+                fmt_price(down.and_then(|s| s.best_ask.as_ref())),
+                fmt_secs(secs_to_close)
+            );
+            if let Some(ref pair) = state.hedged_pair {
+                debug!(
+                    "[IntervalSniper] pair held: Up {} @ {} | Down {} @ {} | {}s since entry",
+                    fmt_decimal_2(&pair.size_up),
+                    fmt_decimal_2(&pair.price_up),
+                    fmt_decimal_2(&pair.size_down),
+                    fmt_decimal_2(&pair.price_down),
+                    now_ms_u.saturating_sub(pair.timestamp_ms) / 1000
+                );
+            }
+            // When position open, log TP/SL monitoring so user sees we're checking for fills (debug only)
+            if let Some(ref tp) = state.pending_auto_sell {
+                if state.phases.exits_armed() {
+                    let is_up = tp.token_id == market.token_id_up;
+                    let side_book = if is_up {
+                        &top.token_id_up
+                    } else {
+                        &top.token_id_down
+                    };
+                    debug!(
+                        "[IntervalSniper]  POS   TP   target={}  best_bid={}  (sell when bid >= target)",
+                        fmt_price(Some(&tp.target_price)),
+                        fmt_price(side_book.as_ref().and_then(|s| s.best_bid.as_ref()))
+                    );
+                }
+            }
+            if let Some(ref sl) = state.pending_stop_loss {
+                if state.phases.exits_armed() {
+                    let is_up = sl.token_id == market.token_id_up;
+                    let side_book = if is_up {
+                        &top.token_id_up
+                    } else {
+                        &top.token_id_down
+                    };
+                    debug!(
+                        "[IntervalSniper]  POS   SL   trigger={}  best_bid={}  (sell when bid <= trigger)",
+                        fmt_price(Some(&sl.trigger_price)),
+                        fmt_price(side_book.as_ref().and_then(|s| s.best_bid.as_ref()))
+                    );
+                }
+            }
+        }
+
+        // Resolution-hold: near close with best_bid >= MM_HOLD_TO_RESOLUTION_ABOVE, skip TP and hold
+        // the position to settlement (payout 1.00). SL stays armed.
+        if state.config.hold_to_resolution_above > Decimal::ZERO
+            && !state.holding_to_resolution
+            && state.phases.exits_armed()
+            && secs_to_close <= state.config.hold_to_resolution_secs as u64
+        {
+            if let Some(ref tp) = state.pending_auto_sell {
+                let side_book = if tp.token_id == market.token_id_up {
+                    &top.token_id_up
+                } else {
+                    &top.token_id_down
+                };
+                let best_bid = side_book.as_ref().and_then(|s| s.best_bid);
+                if best_bid.is_some_and(|b| b >= state.config.hold_to_resolution_above) {
+                    info!(
+                        "[IntervalSniper] {}",
+                        messages::hold_to_resolution(
+                            fmt_decimal_2(&tp.size),
+                            fmt_price(best_bid.as_ref()),
+                            fmt_price(Some(&state.config.hold_to_resolution_above)),
+                            secs_to_close
+                        )
+                    );
+                    state.holding_to_resolution = true;
+                }
+            }
+        }
+
+        profiler.phase(TickPhase::Execute);
+        // Stop loss: once best_bid <= trigger_price (or MM_TRIGGER_PRICE_SOURCE's price) has held
+        // for MM_SL_CONFIRM_MS, sell the position FOK at the bid, retrying at the latest bid.
+        if state.config.enable_stop_loss && !stale_book && state.phases.exits_armed() {
+            let mut due = None;
+            if let Some(sl) = state.pending_stop_loss.as_ref() {
+                // Use book only for best_bid; token to sell is always position.token_id.
+                let side_book = if sl.token_id == market.token_id_up {
+                    &top.token_id_up
+                } else {
+                    &top.token_id_down
+                };
+                let best_bid = side_book
+                    .as_ref()
+                    .and_then(|s| s.best_bid)
+                    .unwrap_or(Decimal::ZERO);
+                // MM_TRIGGER_PRICE_SOURCE: what is compared with the trigger; the sell still
+                // prices off best_bid.
+                let source = state.config.trigger_price_source;
+                let last_trade = match live_ws(&state.ws_book) {
+                    Some(ws) if source == TriggerPriceSource::LastTrade => {
+                        let window_ms = state.config.trade_window_secs * 1000;
+                        let stats = ws.trade_stats(&sl.token_id, now_ms_u, window_ms).await;
+                        stats.last_price.filter(|_| stats.trades > 0)
+                    }
+                    _ => None,
+                };
+                let trigger_px = side_book
+                    .as_ref()
+                    .and_then(|s| trigger_price(source, s, last_trade))
+                    .unwrap_or(Decimal::ZERO);
+                let breached = best_bid > Decimal::ZERO
+                    && trigger_px > Decimal::ZERO
+                    && trigger_px <= sl.trigger_price;
+                if sl_confirmed(
+                    &mut state.sl_breach_since_ms,
+                    breached,
+                    now_ms_u,
+                    state.config.sl_confirm_ms,
+                ) {
+                    if source != TriggerPriceSource::Bid {
+                        info!(
+                            "[IntervalSniper] SL trigger: {:?} {} <= {} (best bid {})",
+                            source,
+                            fmt_price(Some(&trigger_px)),
+                            fmt_price(Some(&sl.trigger_price)),
+                            fmt_price(Some(&best_bid))
+                        );
+                    }
+                    due = Some((best_bid, sl.trigger_price));
+                }
+            }
+            if let Some((best_bid, trigger)) = due {
+                run_sell_campaign(
+                    SellKind::StopLoss,
+                    &mut state,
+                    clob.as_ref().as_ref(),
+                    &tracker,
+                    &http,
+                    &clob_host,
+                    best_bid,
+                    trigger,
+                    now_ms_u,
+                )
+                .await?;
+            }
+        }
+
+        // Take profit: once best_bid >= the target (MM_TP_SCHEDULE price less its margin), sell
+        // the position at the bid (MM_TAKE_PROFIT_TIME_IN_FORCE), retrying FAK at the latest bid.
+        let mut tp_due = None;
+        if (state.config.enable_auto_sell || state.config.auto_sell_at_max_price)
+            && !state.holding_to_resolution
+            && !stale_book
+            && state.phases.exits_armed()
+            && !state.passive_tp.pending()
+        {
+            if let Some(tp) = state.pending_auto_sell.as_ref() {
+                let elapsed_sec = now_ms_u.saturating_sub(tp.placed_at_ms) / 1000;
+                if elapsed_sec >= state.config.min_seconds_after_buy_before_auto_sell as u64 {
+                    let market = state
+                        .market
+                        .as_ref()
+                        .expect("market set after need_new_market check");
+                    // Use book only for best_bid; token to sell is always position.token_id.
+                    let side_book = if tp.token_id == market.token_id_up {
+                        &top.token_id_up
+                    } else {
+                        &top.token_id_down
+                    };
+                    let best_bid = side_book
+                        .as_ref()
+                        .and_then(|s| s.best_bid)
+                        .unwrap_or(Decimal::ZERO);
+                    // MM_TP_SCHEDULE: the target tightens as the interval nears close.
+                    let tp_price = scheduled_tp_price(&state.config, tp, secs_to_close, tick_size);
+                    let target = tp_price - state.config.take_profit_price_margin;
+                    if best_bid >= target {
+                        tp_due = Some((best_bid, target));
+                    }
+                }
+            }
+        }
+        if let Some((best_bid, target)) = tp_due {
+            run_sell_campaign(
+                SellKind::TakeProfit,
+                &mut state,
+                clob.as_ref().as_ref(),
+                &tracker,
+                &http,
+                &clob_host,
+                best_bid,
+                target,
+                now_ms_u,
+            )
+            .await?;
+        }
+        let market = state
+            .market
+            .as_ref()
+            .expect("market set after need_new_market check");
+
+        // Buy path: up to max_trades_per_interval per interval; re-entry per config.re_entry (after SL
+        // only, or after any close), and never within cooldown_between_orders_ms of the last fill.
+        // Require an idle interval phase for the first slot so we don't double-buy when first order
+        // returns success=false but actually filled on the exchange.
+        let no_open_position = state.pending_auto_sell.is_none()
+            && state.pending_stop_loss.is_none()
+            && state.hedged_pair.is_none()
+            && state.resting_buy.is_none();
+        let cooled_down = state
+            .last_fill_ms
+            .is_none_or(|t| now_ms_u.saturating_sub(t) >= state.config.cooldown_between_orders_ms);
+        let slot_open = if state.trades_this_interval == 0 {
+            !state.phases.entered()
+        } else {
+            state.trades_this_interval < state.config.max_trades_per_interval
+                && match state.config.re_entry {
+                    ReEntryPolicy::AfterStopLoss => state.phases.re_entry_after_sl(),
+                    ReEntryPolicy::Always => true,
+                }
+        };
+        // An entry still in flight counts as open until its result arrives.
+        let can_buy = no_open_position && !state.orders.has_in_flight() && cooled_down && slot_open;
+        let in_range = ask_in_range(&state.config, &top);
+        if !in_range {
+            state.declines.clear_last();
+        }
+        if !can_buy {
+            let reason = if !no_open_position {
+                DeclineReason::PositionOpen
+            } else if state.orders.has_in_flight() {
+                DeclineReason::OrderInFlight
+            } else if !cooled_down {
+                DeclineReason::Cooldown
+            } else {
+                DeclineReason::IntervalTraded
+            };
+            decline_entry(&mut state.declines, in_range, reason);
+        }
+        if can_buy {
+            let in_window = state.config.no_window_all_intervals
+                || secs_to_close <= state.config.seconds_before_close as u64;
+            let sec_since_start = now_u.saturating_sub(market.interval_start_unix);
+            if !state.config.buy_range_schedule.is_empty() {
+                let base = state.base_buy_ranges;
+                apply_buy_range_schedule(&mut state.config, base, sec_since_start);
+            }
+            let min_after_open = state.config.min_seconds_after_market_open.max(3);
+            let can_buy_after_open = sec_since_start >= min_after_open as u64;
+            if let Some(switch_ms) = state.interval_switch_wall_time_ms {
+                let elapsed_ms = now_ms_u.saturating_sub(switch_ms);
+                if elapsed_ms < (min_after_open as u64) * 1000 {
+                    // Skip first N seconds after interval switch
+                    decline_entry(
+                        &mut state.declines,
+                        in_range,
+                        DeclineReason::TooSoonAfterOpen,
+                    );
+                    end_tick(&mut profiler);
+                    clock.sleep(Duration::from_millis(loop_ms)).await;
+                    continue;
+                }
+            }
+
+            // Volatility filter: whipsawing intervals are where SL gets chewed up.
+            let max_range = state.config.max_interval_range;
+            let too_volatile = match interval_bid_range(&state) {
+                Some(range) if max_range > Decimal::ZERO && range > max_range => {
+                    if tick_count.is_multiple_of(LOG_BOOK_EVERY_TICKS) {
+                        debug!(
+                            "[IntervalSniper] entry skipped: interval bid range {} > max {}",
+                            fmt_decimal_2(&range),
+                            fmt_decimal_2(&max_range)
+                        );
+                    }
+                    true
+                }
+                _ => false,
+            };
+
+            // Economic-event blackout: 5-min intervals are blocked if any part overlaps a window,
+            // longer markets only while a window is active.
+            let (from_unix, to_unix) = match state.config.market_target {
+                MarketTarget::Interval(..) | MarketTarget::IntervalAt(..) => {
+                    (market.interval_start_unix, market.close_time_unix)
+                }
+                _ => (now_u, now_u),
+            };
+            let in_blackout = match state
+                .calendar
+                .as_ref()
+                .and_then(|c| c.blackout_for(from_unix, to_unix))
+            {
+                Some(b) => {
+                    if tick_count.is_multiple_of(LOG_BOOK_EVERY_TICKS) {
+                        debug!(
+                            "[IntervalSniper] entry skipped: blackout {} ({}..{})",
+                            b.name, b.start_unix, b.end_unix
+                        );
+                    }
+                    true
+                }
+                None => false,
+            };
+
+            // No entry is signed with a guessed fee: a failed fee-rate fetch is retried here.
+            state.fee_rates.retry(&http, &clob_host, now_ms_u).await;
+            let gate = if !in_window {
+                Some(DeclineReason::OutsideWindow)
+            } else if !can_buy_after_open {
+                Some(DeclineReason::TooSoonAfterOpen)
+            } else if too_volatile {
+                Some(DeclineReason::Volatile)
+            } else if in_blackout {
+                Some(DeclineReason::Blackout)
+            } else if stale_book {
+                Some(DeclineReason::StaleBook)
+            } else if !state.fee_rates.complete() {
+                Some(DeclineReason::FeeRateUnknown)
+            } else if state.config.size_shares < min_order_size {
+                // MM_SIZING staked less than one min-size order this interval.
+                Some(DeclineReason::BelowMinSize)
+            } else {
+                None
+            };
+            if let Some(reason) = gate {
+                decline_entry(&mut state.declines, in_range, reason);
+            } else {
+                // Dual-side entry: buy both legs in one batch so one side does not fill while the other misses.
+                if state.config.dual_side_entry {
+                    if let Some((up_ask, down_ask, size_available)) =
+                        choose_pair(&state.config, &top, min_order_size)
+                    {
+                        let shares_left =
+                            state.config.size_shares - state.total_shares_this_interval;
+                        // Jitter never takes the order past the configured size.
+                        let size = size_4_decimals(
+                            (shares_left * size_jitter_factor(state.config.size_jitter_percent))
+                                .min(size_available)
+                                .max(min_order_size)
+                                .min(shares_left)
+                                .round_dp(2),
+                        );
+                        if size < min_order_size {
+                            decline_entry(
+                                &mut state.declines,
+                                in_range,
+                                DeclineReason::BelowMinSize,
+                            );
+                            end_tick(&mut profiler);
+                            clock.sleep(Duration::from_millis(loop_ms)).await;
+                            continue;
+                        }
+                        let up_price = round_to_tick(up_ask, tick_size);
+                        let down_price = round_to_tick(down_ask, tick_size);
+                        let fee_rates = state.fee_rates.clone();
+                        let delay_ms = entry_jitter_ms(state.config.entry_jitter_ms);
+                        let leg = |token_id: &str, price: Decimal, entry_side| OrderIntent {
+                            params: LimitOrderParams {
+                                token_id: token_id.to_string(),
+                                side: OrderSide::Buy,
+                                price,
+                                size,
+                                expiration_unix: None,
+                                post_only: false,
+                                fee_rate_bps: fee_rates.token(token_id),
+                                min_order_size: Some(min_order_size),
+                            },
+                            order_type: OrderType::Fok,
+                            delay_ms,
+                            slug: market.slug.clone(),
+                            entry_side,
+                            max_age_ms: 0,
+                        };
+                        debug!(
+                            "[IntervalSniper] Placing FOK pair buy size={} Up @ {} Down @ {} (max pair cost {})",
+                            size,
+                            fmt_decimal_2(&up_price),
+                            fmt_decimal_2(&down_price),
+                            state.config.dual_side_max_pair_cost
+                        );
+                        // Submitted in the background like a single entry; the fills are handled
+                        // in on_pair_outcome.
+                        let legs = vec![
+                            leg(&token_id_up, up_price, EntrySide::Up),
+                            leg(&token_id_down, down_price, EntrySide::Down),
+                        ];
+                        match state.orders.submit_batch(legs) {
+                            Ok(_) => {
+                                state.phases.on_entry_sent();
+                                state.declines.clear_last();
+                            }
+                            Err(e) => warn!("[IntervalSniper]  FAIL  PAIR  {}", e),
+                        }
+                        end_tick(&mut profiler);
+                        clock.sleep(Duration::from_millis(loop_ms)).await;
+                        continue;
+                    }
+                }
+                // Entry logic lives in the strategy; the runner sizes and places each action.
+                let ctx = TickContext {
+                    config: &state.config,
+                    market,
+                    top: &top,
+                    secs_to_close,
+                    tick_size,
+                    min_order_size,
+                    now_ms: now_ms_u,
+                    spot_move,
+                };
+                let actions = state.strategy.on_book(&ctx);
+                if let Some(trace) = state.decision_trace.as_ref() {
+                    trace.record(trace_record(&state, &ctx, &actions));
+                }
+                if actions.is_empty() {
+                    decline_entry(&mut state.declines, in_range, DeclineReason::Strategy);
+                }
+                for action in actions {
+                    // One position at a time: later actions wait until this one is closed.
+                    if state.pending_auto_sell.is_some()
+                        || state.pending_stop_loss.is_some()
+                        || state.orders.has_in_flight()
+                    {
+                        break;
+                    }
+                    let Action::Buy {
+                        side,
+                        price: limit_price,
+                        size_available,
+                        order_type,
+                    } = action;
+                    let (token_id, best_ask) = match side {
+                        EntrySide::Up => (
+                            token_id_up.clone(),
+                            top.token_id_up.as_ref().and_then(|s| s.best_ask),
+                        ),
+                        EntrySide::Down => (
+                            token_id_down.clone(),
+                            top.token_id_down.as_ref().and_then(|s| s.best_ask),
+                        ),
+                    };
+                    if let Some(reason) =
+                        trade_tape_block(&state, &token_id, best_ask, now_ms_u).await
+                    {
+                        debug!("[IntervalSniper] entry skipped: {}", reason);
+                        decline_entry(&mut state.declines, in_range, DeclineReason::TradeTape);
+                        continue;
+                    }
+                    if let Some(reason) = depth_zone_block(
+                        &state,
+                        &http,
+                        &clob_host,
+                        &token_id,
+                        limit_price,
+                        tick_size,
+                    )
+                    .await
+                    {
+                        debug!("[IntervalSniper] entry skipped: {}", reason);
+                        decline_entry(&mut state.declines, in_range, DeclineReason::DepthZone);
+                        continue;
+                    }
+                    if let Some(strike) = state.strike {
+                        let spot = state.spot.as_ref().and_then(|s| s.last_price());
+                        let min_bps = state.config.strike_min_distance_bps;
+                        if !spot.is_some_and(|spot| strike_allows(side, spot, strike, min_bps)) {
+                            debug!(
+                                "[IntervalSniper] entry skipped: spot {:?} not on the {:?} side of strike {}",
+                                spot, side, strike
+                            );
+                            decline_entry(&mut state.declines, in_range, DeclineReason::Strike);
+                            continue;
+                        }
+                    }
+                    let effective_price = limit_price;
+                    let shares_left = state.config.size_shares - state.total_shares_this_interval;
+                    // Cap at shares_left so we never order more than configured size (e.g. exactly 7 shares).
+                    // Round to 2 decimals so we never send 7.24000001 when user wants 7.
+                    let size = size_4_decimals(
+                        (shares_left * size_jitter_factor(state.config.size_jitter_percent))
+                            .min(size_available)
+                            .max(min_order_size)
+                            .min(shares_left)
+                            .round_dp(2),
+                    );
+                    if size >= min_order_size && size > Decimal::ZERO {
+                        let params = LimitOrderParams {
+                            token_id: token_id.to_string(),
+                            side: OrderSide::Buy,
+                            price: effective_price,
+                            size,
+                            expiration_unix: None,
+                            post_only: false,
+                            fee_rate_bps: state.fee_rates.token(&token_id),
+                            min_order_size: Some(min_order_size),
+                        };
+                        let type_str = match order_type {
+                            OrderType::Gtc => "GTC limit",
+                            OrderType::Fok => "FOK",
+                            OrderType::Fak => "FAK",
+                            _ => "limit",
+                        };
+                        debug!(
+                            "[IntervalSniper] Placing {} buy size={} @ {} (range {}-{})",
+                            type_str,
+                            size,
+                            fmt_decimal_2(&effective_price),
+                            state.config.side(side).min_buy_price,
+                            state.config.side(side).max_buy_price
+                        );
+                        // Submitted in the background; the fill is handled in on_entry_outcome
+                        // while the loop keeps reading the book.
+                        let intent = OrderIntent {
+                            params,
+                            order_type,
+                            delay_ms: entry_jitter_ms(state.config.entry_jitter_ms),
+                            slug: market.slug.clone(),
+                            entry_side: side,
+                            max_age_ms: state.config.buy_order_max_age_ms,
+                        };
+                        match state.orders.submit(intent) {
+                            // Mark that we attempted a buy this interval (prevents second buy if first
+                            // returned success=false but filled on exchange; re-entry only after SL).
+                            Ok(_) => {
+                                state.phases.on_entry_sent();
+                                state.declines.clear_last();
+                            }
+                            Err(e) => warn!("[IntervalSniper]  FAIL  BUY   {}", e),
+                        }
+                    } else {
+                        decline_entry(&mut state.declines, in_range, DeclineReason::BelowMinSize);
+                    }
+                }
+            }
+        }
+
+        end_tick(&mut profiler);
+        clock.sleep(Duration::from_millis(loop_ms)).await;
+    }
+
+    finish_carryovers(&mut state, &http, &config.gamma_base_url, clock.as_ref()).await;
+    if let Some(ref mut log) = state.session_log {
+        if !funders.is_empty() {
+            reconcile_trades(clob.as_ref().as_ref(), log).await;
+        }
+    }
+    if let Some(ref mut report) = state.dry_run_report {
+        if let Err(e) = report.write() {
+            warn!("[DryRunReport] write failed: {}", e);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,86 @@
+//! Order salts from one process-wide counter, so two orders built in the same millisecond (a
+//! ladder, both legs of a dual-side entry, several accounts) never share a salt and with it an
+//! order hash, which the CLOB would reject as a duplicate. The counter starts at the wall clock in
+//! µs (53 bits, JSON-safe, until the year 2255) and only counts up. With MM_SALT_FILE it also
+//! keeps a high-water mark on disk, reserved a block at a time, and a restart resumes above it even
+//! when the clock has stepped back. The EIP-712 `nonce` is not touched: it is the maker's exchange
+//! nonce (raising it on chain cancels every order signed with the old one), so orders keep 0.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use tracing::warn;
+
+/// Salts reserved per write of the high-water mark.
+const SALT_BLOCK: u64 = 10_000;
+
+struct Counter {
+    next: u64,
+    /// Salts below this are covered by the file (or the process, without one).
+    reserved: u64,
+}
+
+/// Monotonic salt counter, optionally persisted.
+pub struct SaltAllocator {
+    counter: Mutex<Counter>,
+    path: Option<PathBuf>,
+}
+
+impl SaltAllocator {
+    /// Counter starting above both `now_ms` (in µs) and the high-water mark in `path` (None = in
+    /// memory only).
+    pub fn open(path: Option<&str>, now_ms: u64) -> Self {
+        let persisted = path
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .unwrap_or(0);
+        let next = persisted.max(now_ms.saturating_mul(1000));
+        Self {
+            counter: Mutex::new(Counter {
+                next,
+                reserved: next,
+            }),
+            path: path.map(PathBuf::from),
+        }
+    }
+
+    /// The next salt; never returns the same value twice, also across restarts with a file.
+    pub fn next_salt(&self) -> u64 {
+        let mut counter = self.counter.lock().unwrap_or_else(PoisonError::into_inner);
+        if counter.next >= counter.reserved {
+            counter.reserved = counter.next + SALT_BLOCK;
+            self.persist(counter.reserved);
+        }
+        let salt = counter.next;
+        counter.next += 1;
+        salt
+    }
+
+    /// Write the high-water mark (to a temp file, then renamed over the old one).
+    fn persist(&self, reserved: u64) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let tmp = path.with_extension("tmp");
+        if let Err(e) = fs::write(&tmp, reserved.to_string()).and_then(|_| fs::rename(&tmp, path)) {
+            warn!(
+                "[Salt] could not write {}: {} (salts stay unique in this run)",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Next salt of the process-wide counter (MM_SALT_FILE read on first use).
+pub fn next_salt() -> u64 {
+    static SALTS: OnceLock<SaltAllocator> = OnceLock::new();
+    SALTS
+        .get_or_init(|| {
+            let path = std::env::var("MM_SALT_FILE")
+                .ok()
+                .filter(|p| !p.trim().is_empty());
+            SaltAllocator::open(path.as_deref().map(str::trim), crate::clock::wall_ms())
+        })
+        .next_salt()
+}
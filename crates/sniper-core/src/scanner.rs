@@ -0,0 +1,212 @@
+//! New-market discovery: poll Gamma for recently created markets that match a filter (tag, title
+//! regex, liquidity threshold) and optionally start a sniping engine on each one as it appears.
+//!
+//! Enabled with MM_SCANNER_ENABLED=true. Markets already listed on the first poll are treated as
+//! seen, so only markets created while the scanner runs trigger an engine. A new market that fails
+//! the filter is checked again on every poll until it matches or closes (new markets start with
+//! almost no liquidity).
+
+use crate::book::public_client;
+use crate::market::positive_decimal;
+use crate::runner::run_with_config;
+use crate::status;
+use crate::types::{Config, GammaMarket, MarketTarget};
+use anyhow::{Context, Result};
+use regex::Regex;
+use reqwest::Client;
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Markets requested per poll (newest first).
+const SCANNER_PAGE_LIMIT: u32 = 100;
+
+/// Scanner settings (MM_SCANNER_*).
+#[derive(Debug, Clone)]
+pub struct ScannerConfig {
+    pub poll_ms: u64,
+    /// Gamma tag id filter (MM_SCANNER_TAG_ID), passed to /markets?tag_id=.
+    pub tag_id: Option<String>,
+    /// Match against the market question (MM_SCANNER_TITLE_REGEX).
+    pub title_regex: Option<Regex>,
+    /// Minimum Gamma liquidity in USD (MM_SCANNER_MIN_LIQUIDITY).
+    pub min_liquidity: Decimal,
+    /// Start a sniping engine on each new match (MM_SCANNER_AUTO_START); otherwise only log it.
+    pub auto_start: bool,
+    /// Max engines running at once (MM_SCANNER_MAX_ENGINES).
+    pub max_engines: usize,
+}
+
+impl ScannerConfig {
+    pub fn from_env() -> Result<Self> {
+        let var = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let title_regex = var("MM_SCANNER_TITLE_REGEX")
+            .map(|r| Regex::new(&r).context("MM_SCANNER_TITLE_REGEX"))
+            .transpose()?;
+        Ok(Self {
+            poll_ms: var("MM_SCANNER_POLL_MS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5000u64)
+                .max(500),
+            tag_id: var("MM_SCANNER_TAG_ID"),
+            title_regex,
+            min_liquidity: var("MM_SCANNER_MIN_LIQUIDITY")
+                .and_then(|v| Decimal::from_str(&v).ok())
+                .unwrap_or(Decimal::ZERO),
+            auto_start: var("MM_SCANNER_AUTO_START")
+                .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+                .unwrap_or(false),
+            max_engines: var("MM_SCANNER_MAX_ENGINES")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3),
+        })
+    }
+
+    fn matches(&self, m: &GammaMarket) -> bool {
+        let title_ok = match (&self.title_regex, m.question.as_deref()) {
+            (Some(re), Some(q)) => re.is_match(q),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let liquidity = positive_decimal(m.liquidity_num.as_ref())
+            .or_else(|| positive_decimal(m.liquidity.as_ref()))
+            .unwrap_or(Decimal::ZERO);
+        title_ok && liquidity >= self.min_liquidity
+    }
+}
+
+/// Markets the scanner has already reported, by slug.
+#[derive(Debug, Default)]
+pub struct SeenMarkets {
+    seen: HashSet<String>,
+    primed: bool,
+}
+
+impl SeenMarkets {
+    /// Markets of one poll that are new and pass `scanner`'s filter; these are marked as seen. A
+    /// market that fails the filter stays unseen. The first poll only marks every listed market as
+    /// seen and returns none.
+    pub fn new_matches(
+        &mut self,
+        scanner: &ScannerConfig,
+        markets: Vec<GammaMarket>,
+    ) -> Vec<GammaMarket> {
+        if !self.primed {
+            self.primed = true;
+            self.seen.extend(markets.into_iter().filter_map(|m| m.slug));
+            info!(
+                "[Scanner] {} existing markets marked as seen",
+                self.seen.len()
+            );
+            return Vec::new();
+        }
+        let mut new = Vec::new();
+        for m in markets {
+            let Some(slug) = m.slug.as_ref() else {
+                continue;
+            };
+            if !self.seen.contains(slug) && scanner.matches(&m) {
+                self.seen.insert(slug.clone());
+                new.push(m);
+            }
+        }
+        new
+    }
+}
+
+/// Fetch the newest active markets (GET /markets?active=true&closed=false&order=createdAt).
+async fn fetch_new_markets(
+    client: &Client,
+    base_url: &str,
+    tag_id: Option<&str>,
+) -> Result<Vec<GammaMarket>> {
+    let base = base_url.trim_end_matches('/');
+    let mut url = format!(
+        "{}/markets?active=true&closed=false&order=createdAt&ascending=false&limit={}",
+        base, SCANNER_PAGE_LIMIT
+    );
+    if let Some(tag) = tag_id {
+        url.push_str(&format!("&tag_id={}", urlencoding::encode(tag)));
+    }
+    let res = client
+        .get(&url)
+        .header("user-agent", "polybot-interval-sniper-rust")
+        .send()
+        .await
+        .context("Gamma API request")?;
+    if !res.status().is_success() {
+        anyhow::bail!("Gamma markets {}", res.status());
+    }
+    res.json().await.context("Gamma markets JSON")
+}
+
+/// Poll Gamma forever; log each new matching market and start an engine on it if enabled.
+pub async fn run(config: Config) -> Result<()> {
+    let scanner = ScannerConfig::from_env()?;
+    let http = public_client(Duration::from_secs(10))?;
+    let mut seen = SeenMarkets::default();
+    let mut engines: Vec<(String, JoinHandle<()>)> = Vec::new();
+
+    info!(
+        "[Scanner] started poll_ms={} tag_id={:?} title_regex={:?} min_liquidity={} auto_start={}",
+        scanner.poll_ms,
+        scanner.tag_id,
+        scanner.title_regex.as_ref().map(|r| r.as_str()),
+        scanner.min_liquidity,
+        scanner.auto_start
+    );
+
+    loop {
+        engines.retain(|(_, h)| !h.is_finished());
+        match fetch_new_markets(&http, &config.gamma_base_url, scanner.tag_id.as_deref()).await {
+            Ok(markets) => {
+                for m in seen.new_matches(&scanner, markets) {
+                    let Some(slug) = m.slug.clone() else {
+                        continue;
+                    };
+                    info!(
+                        "[Scanner] new market {} \"{}\"",
+                        slug,
+                        m.question.as_deref().unwrap_or("")
+                    );
+                    if !scanner.auto_start {
+                        continue;
+                    }
+                    if engines.len() >= scanner.max_engines {
+                        warn!(
+                            "[Scanner] {} engines running (max {}), not starting {}",
+                            engines.len(),
+                            scanner.max_engines,
+                            slug
+                        );
+                        continue;
+                    }
+                    let mut engine_config = config.clone();
+                    engine_config.market_target = MarketTarget::Slug(slug.clone());
+                    engine_config.market_slug = slug.clone();
+                    engine_config.status_file = engine_config
+                        .status_file
+                        .map(|p| status::engine_path(&p, &slug));
+                    let label = slug.clone();
+                    let handle = tokio::spawn(async move {
+                        if let Err(e) = run_with_config(engine_config).await {
+                            warn!("[Scanner] engine {} stopped: {}", label, e);
+                        }
+                    });
+                    info!("[Scanner] started engine on {}", slug);
+                    engines.push((slug, handle));
+                }
+            }
+            Err(e) => warn!("[Scanner] poll failed: {}", e),
+        }
+        tokio::time::sleep(Duration::from_millis(scanner.poll_ms)).await;
+    }
+}
@@ -0,0 +1,256 @@
+//! Retry decisions for a TP or SL sell the exchange did not fill at once: how long to wait, when
+//! to cancel the token's orders, what to sell at the latest bid and when to stop (the market's
+//! deadline, a TP's book emptying, an SL's bid recovering). Pure state; the runner's
+//! `run_sell_campaign` does the I/O (book, available balance, order) and feeds the results back
+//! in.
+
+use crate::clob::{ClobError, PlaceOrderResult};
+use crate::strategy::round_to_tick;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Delay between FAK retries when no match (ms). Kept low for near-instant retries.
+const FAK_RETRY_DELAY_MS: u64 = 30;
+/// Delay between SL FOK retries (ms). Each retry uses latest best bid.
+const SL_FOK_RETRY_DELAY_MS: u64 = 50;
+/// Backoff delays (ms) when 400 not enough balance/allowance: cancel once then retry with these delays.
+const BALANCE_RETRY_BACKOFF_MS: &[u64] = &[100, 200, 400];
+/// Sell size precision (Polymarket CLOB): 4 decimals; quantity bought is rounded to this when selling TP/SL.
+pub const SELL_SIZE_DECIMALS: u32 = 4;
+/// Minimum valid sell size accepted by API in this bot.
+pub const MIN_SELL_SIZE: Decimal = dec!(0.0001);
+/// Below this we consider position closed (dust); avoids spamming the API with tiny amounts the exchange rejects.
+pub const DUST_THRESHOLD: Decimal = dec!(0.01);
+/// One base unit in shares (1e-6) — subtract from available so we never exceed balance after rounding.
+const BALANCE_BUFFER_SHARES: Decimal = dec!(0.000001);
+
+pub fn floor_to_decimals(x: Decimal, decimals: u32) -> Decimal {
+    let factor = Decimal::from(10u64.pow(decimals));
+    ((x * factor).trunc()) / factor
+}
+
+/// Shares to sell: the position capped at the available balance, floored to the sell precision.
+pub fn effective_sell_size(position_size: Decimal, available: Option<Decimal>) -> Decimal {
+    let capped = available
+        .map(|a| {
+            // Leave 1 base unit headroom so encoded amount never exceeds balance after rounding
+            let safe = (a - BALANCE_BUFFER_SHARES).max(Decimal::ZERO);
+            position_size.min(safe)
+        })
+        .unwrap_or(position_size);
+    floor_to_decimals(capped, SELL_SIZE_DECIMALS)
+}
+
+/// Shares a successful sell of `size` sold: the exchange's report (capped at `size`, 0 when it
+/// matched nothing), or all of it when none is reported.
+pub fn sold_size(result: &PlaceOrderResult, size: Decimal) -> Decimal {
+    result.filled_size.map_or(size, |f| f.min(size))
+}
+
+/// A successful sell split across accounts whose failed parts left shares unsold: the position
+/// stays open for the rest.
+pub fn partly_sold(result: &PlaceOrderResult, size: Decimal) -> bool {
+    result.success && !result.failed_parts.is_empty() && sold_size(result, size) < size
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SellKind {
+    /// FAK at the bid while it stays at or above the target.
+    TakeProfit,
+    /// FOK at the bid while it stays at or below the trigger.
+    StopLoss,
+}
+
+/// What the runner does next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SellAction {
+    /// Nothing to do on this attempt; start the next one.
+    Retry,
+    /// Stop retrying; the position stays open for the next tick.
+    GiveUp,
+    /// Nothing left worth selling: treat the position as closed.
+    Closed,
+    /// Place the sell.
+    Sell { price: Decimal, size: Decimal },
+    /// The sell filled `size` at `price` (what the exchange reports; a FAK can fill part).
+    Filled { price: Decimal, size: Decimal },
+    /// A sell split across accounts filled `size` at `price` but some parts failed: the rest is
+    /// still held.
+    PartFilled { price: Decimal, size: Decimal },
+}
+
+/// One attempt's wait before reading the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttemptWait {
+    pub delay_ms: u64,
+    /// Cancel the token's open orders (which lock its balance) before this attempt.
+    pub cancel_first: bool,
+}
+
+/// Retry state of one TP or SL sell.
+#[derive(Debug, Clone)]
+pub struct SellCampaign {
+    kind: SellKind,
+    position_size: Decimal,
+    /// TP target or SL trigger.
+    limit: Decimal,
+    /// Started by a balance/allowance error rather than a no-match.
+    balance_error: bool,
+    canceled_for_balance: bool,
+    attempt: u32,
+    /// No attempt starts at or after this time (ms); None = no deadline.
+    deadline_ms: Option<u64>,
+}
+
+impl SellCampaign {
+    /// Campaign after the first sell failed with `first`; None when that error is not worth
+    /// retrying (the caller handles InvalidAmounts and other rejections itself).
+    pub fn start(
+        kind: SellKind,
+        position_size: Decimal,
+        limit: Decimal,
+        first: &PlaceOrderResult,
+    ) -> Option<Self> {
+        let balance_error = first.is_error(&ClobError::InsufficientBalance);
+        if !balance_error && !first.is_error(&ClobError::NoMatch) {
+            return None;
+        }
+        Some(Self {
+            kind,
+            position_size,
+            limit,
+            balance_error,
+            canceled_for_balance: false,
+            attempt: 0,
+            deadline_ms: None,
+        })
+    }
+
+    /// Campaign for a sell that could not be placed at all: the available balance was below the
+    /// minimum (locked by our orders, or the buy not yet credited). Retries like a no-match.
+    pub fn awaiting_balance(kind: SellKind, position_size: Decimal, limit: Decimal) -> Self {
+        Self {
+            kind,
+            position_size,
+            limit,
+            balance_error: false,
+            canceled_for_balance: false,
+            attempt: 0,
+            deadline_ms: None,
+        }
+    }
+
+    /// Stop retrying at `deadline_ms` (the market's switch time).
+    pub fn until(mut self, deadline_ms: u64) -> Self {
+        self.deadline_ms = Some(deadline_ms);
+        self
+    }
+
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Started by a balance/allowance error (cancel once, then back off).
+    pub fn balance_error(&self) -> bool {
+        self.balance_error
+    }
+
+    /// Begin the next attempt at `now_ms`: how long to wait and whether to cancel first (once, on
+    /// a balance error). None once the deadline has passed: the campaign is over.
+    pub fn next_attempt(&mut self, now_ms: u64) -> Option<AttemptWait> {
+        if self.deadline_ms.is_some_and(|d| now_ms >= d) {
+            return None;
+        }
+        self.attempt += 1;
+        let delay_ms = if self.balance_error {
+            BALANCE_RETRY_BACKOFF_MS
+                .get((self.attempt as usize).saturating_sub(1))
+                .copied()
+                .unwrap_or(400)
+        } else {
+            match self.kind {
+                SellKind::TakeProfit => FAK_RETRY_DELAY_MS,
+                // First SL retry immediate (latest bid); then every 50 ms.
+                SellKind::StopLoss if self.attempt == 1 => 0,
+                SellKind::StopLoss => SL_FOK_RETRY_DELAY_MS,
+            }
+        };
+        let cancel_first = self.balance_error && !self.canceled_for_balance;
+        self.canceled_for_balance |= cancel_first;
+        Some(AttemptWait {
+            delay_ms,
+            cancel_first,
+        })
+    }
+
+    /// Check the latest bid (0 = none): Some(GiveUp) when a TP's book side emptied or an SL bid
+    /// recovered above the trigger, Some(Retry) when a TP target is not met or an SL has no bid
+    /// to sell into yet, None to go on.
+    pub fn on_bid(&self, bid: Decimal) -> Option<SellAction> {
+        match self.kind {
+            // Back to the tick loop, where the SL still watches the position.
+            SellKind::TakeProfit if bid <= Decimal::ZERO => Some(SellAction::GiveUp),
+            SellKind::TakeProfit if bid < self.limit => Some(SellAction::Retry),
+            SellKind::StopLoss if bid <= Decimal::ZERO => Some(SellAction::Retry),
+            SellKind::StopLoss if bid > self.limit => Some(SellAction::GiveUp),
+            _ => None,
+        }
+    }
+
+    /// The sell for this attempt from the bid and the available balance. Available below the
+    /// minimum: SL retries (balance catching up), TP gives up. Dust left of a dust position
+    /// closes it; dust available of a real position is sold anyway (balance not yet updated).
+    pub fn on_available(
+        &self,
+        bid: Decimal,
+        available: Option<Decimal>,
+        tick_size: Decimal,
+    ) -> SellAction {
+        let size = effective_sell_size(self.position_size, available);
+        if size < MIN_SELL_SIZE {
+            return match self.kind {
+                SellKind::StopLoss => SellAction::Retry,
+                SellKind::TakeProfit => SellAction::GiveUp,
+            };
+        }
+        if size < DUST_THRESHOLD && self.position_size < DUST_THRESHOLD {
+            return SellAction::Closed;
+        }
+        SellAction::Sell {
+            price: round_to_tick(bid, tick_size),
+            size,
+        }
+    }
+
+    /// Outcome of the sell placed at `price` for `size`. A success that reports 0 filled sold
+    /// nothing: retry.
+    pub fn on_result(
+        &self,
+        result: &PlaceOrderResult,
+        price: Decimal,
+        size: Decimal,
+    ) -> SellAction {
+        if result.success {
+            let filled = sold_size(result, size);
+            if filled <= Decimal::ZERO {
+                return SellAction::Retry;
+            }
+            if partly_sold(result, size) {
+                return SellAction::PartFilled {
+                    price,
+                    size: filled,
+                };
+            }
+            return SellAction::Filled {
+                price,
+                size: filled,
+            };
+        }
+        match result.error {
+            // Already canceled once; just back off.
+            Some(ClobError::InsufficientBalance) | Some(ClobError::NoMatch) => SellAction::Retry,
+            Some(ClobError::InvalidAmounts) => SellAction::Closed,
+            _ => SellAction::GiveUp,
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! SQLite session database (MM_SESSION_DB, `sqlite` feature): the session log's events and the
+//! order tracker's transitions also go to one database shared by every run, so months of history
+//! can be queried with SQL (see the `analyze` binary). Tables: `sessions` (one row per run, with
+//! its summary), `intervals`, `closes` (position closes with PnL and fees), `fills` (the fills of
+//! entries bought across several orders) and `orders` (latest state of every order). The JSONL
+//! files stay the primary record: a database that cannot be opened or written only logs a warning.
+//! Prices, sizes and USD amounts are REAL columns.
+
+use anyhow::Result;
+use std::path::Path;
+
+#[cfg(feature = "sqlite")]
+mod imp {
+    use super::*;
+    use anyhow::Context;
+    use rusqlite::types::Value;
+    use rusqlite::{params, params_from_iter, Connection};
+    use std::sync::{Mutex, PoisonError};
+    use tracing::warn;
+
+    /// Columns of each table filled from the JSONL event of the same name (same keys).
+    const CLOSE_COLUMNS: &[&str] = &[
+        "slug",
+        "interval_start_unix",
+        "close_time_unix",
+        "side",
+        "entry_price",
+        "exit_price",
+        "entry_time_ms",
+        "exit_time_ms",
+        "exit_type",
+        "size",
+        "pnl_usd",
+        "fee_rate_bps",
+        "fees_usd",
+        "net_pnl_usd",
+        "duration_sec",
+    ];
+    const INTERVAL_COLUMNS: &[&str] = &[
+        "slug",
+        "interval_start_unix",
+        "close_time_unix",
+        "min_bid_up",
+        "max_bid_up",
+        "min_bid_down",
+        "max_bid_down",
+        "final_bid_up",
+        "final_ask_up",
+        "final_bid_down",
+        "final_ask_down",
+    ];
+    const SESSION_SUMMARY_COLUMNS: &[&str] = &[
+        "session_end_ms",
+        "tp_count",
+        "sl_count",
+        "soft_stop_count",
+        "market_close_count",
+        "resolution_count",
+        "total_closes",
+        "win_rate",
+        "total_pnl_usd",
+        "total_fees_usd",
+        "total_net_pnl_usd",
+    ];
+    const ORDER_COLUMNS: &[&str] = &[
+        "id",
+        "exchange_id",
+        "token_id",
+        "side",
+        "order_type",
+        "price",
+        "size",
+        "filled",
+        "state",
+        "reason",
+    ];
+
+    const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS sessions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_start_ms INTEGER NOT NULL,
+        dry_run INTEGER NOT NULL,
+        session_end_ms INTEGER,
+        tp_count INTEGER,
+        sl_count INTEGER,
+        soft_stop_count INTEGER,
+        market_close_count INTEGER,
+        resolution_count INTEGER,
+        total_closes INTEGER,
+        win_rate REAL,
+        total_pnl_usd REAL,
+        total_fees_usd REAL,
+        total_net_pnl_usd REAL
+    );
+    CREATE TABLE IF NOT EXISTS intervals (
+        session_id INTEGER NOT NULL REFERENCES sessions(id),
+        slug TEXT NOT NULL,
+        interval_start_unix INTEGER,
+        close_time_unix INTEGER,
+        min_bid_up REAL,
+        max_bid_up REAL,
+        min_bid_down REAL,
+        max_bid_down REAL,
+        final_bid_up REAL,
+        final_ask_up REAL,
+        final_bid_down REAL,
+        final_ask_down REAL
+    );
+    CREATE TABLE IF NOT EXISTS closes (
+        session_id INTEGER NOT NULL REFERENCES sessions(id),
+        slug TEXT NOT NULL,
+        interval_start_unix INTEGER,
+        close_time_unix INTEGER,
+        side TEXT NOT NULL,
+        entry_price REAL NOT NULL,
+        exit_price REAL NOT NULL,
+        entry_time_ms INTEGER,
+        exit_time_ms INTEGER NOT NULL,
+        exit_type TEXT NOT NULL,
+        size REAL NOT NULL,
+        pnl_usd REAL NOT NULL,
+        fee_rate_bps INTEGER,
+        fees_usd REAL,
+        net_pnl_usd REAL,
+        duration_sec INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS closes_exit_time ON closes(exit_time_ms);
+    CREATE TABLE IF NOT EXISTS fills (
+        session_id INTEGER NOT NULL REFERENCES sessions(id),
+        slug TEXT NOT NULL,
+        side TEXT NOT NULL,
+        price REAL NOT NULL,
+        size REAL NOT NULL,
+        ts_ms INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS orders (
+        session_id INTEGER NOT NULL REFERENCES sessions(id),
+        id INTEGER NOT NULL,
+        exchange_id TEXT,
+        token_id TEXT NOT NULL,
+        side TEXT NOT NULL,
+        order_type TEXT NOT NULL,
+        price REAL NOT NULL,
+        size REAL NOT NULL,
+        filled REAL NOT NULL,
+        state TEXT NOT NULL,
+        reason TEXT,
+        created_ms INTEGER NOT NULL,
+        updated_ms INTEGER NOT NULL,
+        PRIMARY KEY (session_id, id)
+    );
+    ";
+
+    /// JSON value of `key` as an SQL value (numeric strings land in REAL columns as numbers).
+    fn sql_value(obj: &serde_json::Value, key: &str) -> Value {
+        match obj.get(key) {
+            Some(serde_json::Value::String(s)) => Value::Text(s.clone()),
+            Some(serde_json::Value::Number(n)) => n
+                .as_i64()
+                .map(Value::Integer)
+                .or_else(|| n.as_f64().map(Value::Real))
+                .unwrap_or(Value::Null),
+            Some(serde_json::Value::Bool(b)) => Value::Integer(*b as i64),
+            _ => Value::Null,
+        }
+    }
+
+    /// One run's connection to the session database.
+    pub struct SessionDb {
+        conn: Mutex<Connection>,
+        session_id: i64,
+    }
+
+    impl SessionDb {
+        /// Open (or create) the database at `path` and add this run's `sessions` row.
+        pub fn open(path: &Path, session_start_ms: u64, dry_run: bool) -> Result<Self> {
+            if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+                std::fs::create_dir_all(dir)?;
+            }
+            let conn =
+                Connection::open(path).with_context(|| format!("open {}", path.display()))?;
+            conn.busy_timeout(std::time::Duration::from_secs(5))?;
+            conn.execute_batch(SCHEMA)?;
+            conn.execute(
+                "INSERT INTO sessions (session_start_ms, dry_run) VALUES (?1, ?2)",
+                params![session_start_ms as i64, dry_run],
+            )?;
+            let session_id = conn.last_insert_rowid();
+            tracing::info!("[SessionDb] session {} in {}", session_id, path.display());
+            Ok(Self {
+                conn: Mutex::new(conn),
+                session_id,
+            })
+        }
+
+        /// This run's row in `sessions`.
+        pub fn session_id(&self) -> i64 {
+            self.session_id
+        }
+
+        /// Record a session log or order audit event (the JSONL object).
+        pub fn on_event(&self, obj: &serde_json::Value) {
+            if let Err(e) = self.write_event(obj) {
+                warn!("[SessionDb] write failed: {:#}", e);
+            }
+        }
+
+        fn insert(
+            &self,
+            conn: &Connection,
+            table: &str,
+            columns: &[&str],
+            obj: &serde_json::Value,
+        ) -> Result<()> {
+            let placeholders = vec!["?"; columns.len() + 1].join(", ");
+            let sql = format!(
+                "INSERT INTO {} (session_id, {}) VALUES ({})",
+                table,
+                columns.join(", "),
+                placeholders
+            );
+            let values = std::iter::once(Value::Integer(self.session_id))
+                .chain(columns.iter().map(|c| sql_value(obj, c)));
+            conn.execute(&sql, params_from_iter(values))?;
+            Ok(())
+        }
+
+        fn write_event(&self, obj: &serde_json::Value) -> Result<()> {
+            let conn = self.conn.lock().unwrap_or_else(PoisonError::into_inner);
+            match obj.get("event").and_then(|e| e.as_str()) {
+                Some("close") => self.insert(&conn, "closes", CLOSE_COLUMNS, obj)?,
+                Some("interval_summary") => {
+                    self.insert(&conn, "intervals", INTERVAL_COLUMNS, obj)?
+                }
+                Some("entry_fills") => {
+                    let ts_ms = sql_value(obj, "ts_ms");
+                    for fill in obj["fills"].as_array().into_iter().flatten() {
+                        conn.execute(
+                            "INSERT INTO fills (session_id, slug, side, price, size, ts_ms) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                            params![
+                                self.session_id,
+                                sql_value(obj, "slug"),
+                                sql_value(obj, "side"),
+                                sql_value(fill, "price"),
+                                sql_value(fill, "size"),
+                                ts_ms
+                            ],
+                        )?;
+                    }
+                }
+                Some("session_summary") => {
+                    let sets: Vec<String> = SESSION_SUMMARY_COLUMNS
+                        .iter()
+                        .map(|c| format!("{} = ?", c))
+                        .collect();
+                    let sql = format!("UPDATE sessions SET {} WHERE id = ?", sets.join(", "));
+                    let values = SESSION_SUMMARY_COLUMNS
+                        .iter()
+                        .map(|c| sql_value(obj, c))
+                        .chain(std::iter::once(Value::Integer(self.session_id)));
+                    conn.execute(&sql, params_from_iter(values))?;
+                }
+                Some("order") => {
+                    let sql = format!(
+                        "INSERT INTO orders (session_id, {}, created_ms, updated_ms) VALUES ({})
+                         ON CONFLICT (session_id, id) DO UPDATE SET exchange_id = excluded.exchange_id,
+                         filled = excluded.filled, state = excluded.state, reason = excluded.reason,
+                         updated_ms = excluded.updated_ms",
+                        ORDER_COLUMNS.join(", "),
+                        vec!["?"; ORDER_COLUMNS.len() + 3].join(", ")
+                    );
+                    let ts_ms = sql_value(obj, "ts_ms");
+                    let values = std::iter::once(Value::Integer(self.session_id))
+                        .chain(ORDER_COLUMNS.iter().map(|c| sql_value(obj, c)))
+                        .chain([ts_ms.clone(), ts_ms]);
+                    conn.execute(&sql, params_from_iter(values))?;
+                }
+                // Tick profiles and trades reconciliations stay in the JSONL only.
+                _ => {}
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+mod imp {
+    use super::*;
+
+    /// Stand-in when built without the `sqlite` feature: [SessionDb::open] always fails.
+    pub struct SessionDb;
+
+    impl SessionDb {
+        /// Always an error: this build has no SQLite.
+        pub fn open(path: &Path, _session_start_ms: u64, _dry_run: bool) -> Result<Self> {
+            anyhow::bail!(
+                "{}: built without the sqlite feature (cargo build --features sqlite)",
+                path.display()
+            )
+        }
+
+        /// Never called (no database can be opened).
+        pub fn session_id(&self) -> i64 {
+            0
+        }
+
+        /// Never called (no database can be opened).
+        pub fn on_event(&self, _obj: &serde_json::Value) {}
+    }
+}
+
+pub use imp::SessionDb;
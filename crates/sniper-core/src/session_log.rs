@@ -0,0 +1,387 @@
+//! Session log: JSONL file per run with position closes, interval summaries, and session stats.
+//! One JSON object per line for easy append and parsing. With MM_SESSION_DB every line also goes
+//! to the SQLite session database (session_db.rs).
+
+use crate::clob::estimated_fee_usd;
+use crate::clock::{utc, wall_ms, Clock};
+use crate::entry_gate::{counts_json, DeclineReason};
+use crate::order_tracker::OrderTracker;
+use crate::session_db::SessionDb;
+use crate::tick_profile::{TickStats, TICK_BUCKETS_MS};
+use crate::trades::TradesPnl;
+use crate::types::{EntrySide, TopOfBook};
+use anyhow::Result;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Exit type for a closed position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitType {
+    TakeProfit,
+    StopLoss,
+    /// Part of the position sold by the soft stop (MM_SOFT_STOP_PRICE); the rest stays open.
+    SoftStop,
+    MarketClose,
+    /// Held to settlement (resolution-hold mode); exit price is the payout (1.00 or 0).
+    Resolution,
+}
+
+pub fn exit_type_str(t: ExitType) -> &'static str {
+    match t {
+        ExitType::TakeProfit => "TP",
+        ExitType::StopLoss => "SL",
+        ExitType::SoftStop => "SOFT_SL",
+        ExitType::MarketClose => "MARKET_CLOSE",
+        ExitType::Resolution => "RESOLUTION",
+    }
+}
+
+fn side_str(side: EntrySide) -> &'static str {
+    match side {
+        EntrySide::Up => "Up",
+        EntrySide::Down => "Down",
+    }
+}
+
+fn dec_opt(o: Option<Decimal>) -> Option<String> {
+    o.map(|d| d.to_string())
+}
+
+/// Session logger: appends JSONL lines to a file. Tracks counts for session summary.
+pub struct SessionLog {
+    file: File,
+    session_start_ms: u64,
+    tp_count: u32,
+    sl_count: u32,
+    soft_stop_count: u32,
+    market_close_count: u32,
+    resolution_count: u32,
+    total_pnl: Decimal,
+    total_fees: Decimal,
+    /// PnL of the closes that were sells (TP, SL and soft stop), comparable with the trades history.
+    sold_pnl: Decimal,
+    /// MM_SESSION_DB: the same events as rows.
+    db: Option<Arc<SessionDb>>,
+    /// The engine's orders, for the fill slippage in the summary.
+    orders: Option<Arc<Mutex<OrderTracker>>>,
+    /// Time of entry fills and of the session's end (None = the wall clock).
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl SessionLog {
+    /// Create a new session log in `dir` with filename `session_YYYY-MM-DDTHH-MM-SS.jsonl`.
+    /// Creates `dir` if it does not exist. Returns None if disabled or creation fails.
+    pub fn new(session_start_ms: u64, dir: &str) -> Result<Option<Self>> {
+        let path = Path::new(dir);
+        if !path.exists() {
+            fs::create_dir_all(path)?;
+        }
+        let filename = path.join(format!(
+            "session_{}.jsonl",
+            utc(session_start_ms).format("%Y-%m-%dT%H-%M-%S")
+        ));
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&filename)?;
+        tracing::info!("[SessionLog] writing to {}", filename.display());
+        Ok(Some(Self {
+            file,
+            session_start_ms,
+            tp_count: 0,
+            sl_count: 0,
+            soft_stop_count: 0,
+            market_close_count: 0,
+            resolution_count: 0,
+            total_pnl: Decimal::ZERO,
+            total_fees: Decimal::ZERO,
+            sold_pnl: Decimal::ZERO,
+            db: None,
+            orders: None,
+            clock: None,
+        }))
+    }
+
+    /// Time entry fills and the session's end on `clock` (the engine's) instead of the wall clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    fn now_ms(&self) -> u64 {
+        self.clock.as_ref().map_or_else(wall_ms, |c| c.now_ms())
+    }
+
+    /// Also record every event in the session database.
+    pub fn with_db(mut self, db: Option<Arc<SessionDb>>) -> Self {
+        self.db = db;
+        self
+    }
+
+    /// Report the slippage of the orders `tracker` saw in the session summary.
+    pub fn with_orders(mut self, tracker: Arc<Mutex<OrderTracker>>) -> Self {
+        self.orders = Some(tracker);
+        self
+    }
+
+    fn write_line(&mut self, obj: &serde_json::Value) -> Result<()> {
+        if let Some(ref db) = self.db {
+            db.on_event(obj);
+        }
+        let line = serde_json::to_string(obj)?;
+        writeln!(self.file, "{}", line)?;
+        self.file.flush()?;
+        Ok(())
+    }
+
+    /// Log a position close (TP, SL, or MARKET_CLOSE). Updates internal counts and PnL.
+    /// Fees are estimated for both the entry and the exit fill at `fee_rate_bps`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn log_position_close(
+        &mut self,
+        slug: &str,
+        interval_start_unix: u64,
+        close_time_unix: u64,
+        side: EntrySide,
+        entry_price: Decimal,
+        exit_price: Decimal,
+        entry_time_ms: u64,
+        exit_time_ms: u64,
+        exit_type: ExitType,
+        size: Decimal,
+        fee_rate_bps: u64,
+        min_bid_up: Option<Decimal>,
+        max_bid_up: Option<Decimal>,
+        min_bid_down: Option<Decimal>,
+        max_bid_down: Option<Decimal>,
+    ) -> Result<()> {
+        let duration_sec = (exit_time_ms.saturating_sub(entry_time_ms)) / 1000;
+        let pnl = size * (exit_price - entry_price);
+        let fees = estimated_fee_usd(fee_rate_bps, entry_price, size)
+            + estimated_fee_usd(fee_rate_bps, exit_price, size);
+
+        match exit_type {
+            ExitType::TakeProfit => self.tp_count += 1,
+            ExitType::StopLoss => self.sl_count += 1,
+            ExitType::SoftStop => self.soft_stop_count += 1,
+            ExitType::MarketClose => self.market_close_count += 1,
+            ExitType::Resolution => self.resolution_count += 1,
+        }
+        self.total_pnl += pnl;
+        self.total_fees += fees;
+        if matches!(
+            exit_type,
+            ExitType::TakeProfit | ExitType::StopLoss | ExitType::SoftStop
+        ) {
+            self.sold_pnl += pnl;
+        }
+
+        let ranged_01_99_up = min_bid_up
+            .zip(max_bid_up)
+            .map(|(min, max)| min <= dec!(0.02) && max >= dec!(0.98))
+            .unwrap_or(false);
+        let ranged_01_99_down = min_bid_down
+            .zip(max_bid_down)
+            .map(|(min, max)| min <= dec!(0.02) && max >= dec!(0.98))
+            .unwrap_or(false);
+
+        let obj = serde_json::json!({
+            "event": "close",
+            "slug": slug,
+            "interval_start_unix": interval_start_unix,
+            "close_time_unix": close_time_unix,
+            "side": side_str(side),
+            "entry_price": entry_price.to_string(),
+            "exit_price": exit_price.to_string(),
+            "entry_time_ms": entry_time_ms,
+            "exit_time_ms": exit_time_ms,
+            "exit_type": exit_type_str(exit_type),
+            "size": size.to_string(),
+            "pnl_usd": pnl.to_string(),
+            "fee_rate_bps": fee_rate_bps,
+            "fees_usd": fees.round_dp(6).to_string(),
+            "net_pnl_usd": (pnl - fees).round_dp(6).to_string(),
+            "duration_sec": duration_sec,
+            "min_bid_up": dec_opt(min_bid_up),
+            "max_bid_up": dec_opt(max_bid_up),
+            "min_bid_down": dec_opt(min_bid_down),
+            "max_bid_down": dec_opt(max_bid_down),
+            "ranged_01_99_up": ranged_01_99_up,
+            "ranged_01_99_down": ranged_01_99_down,
+        });
+        self.write_line(&obj)
+    }
+
+    /// Log interval summary (price range observed, final top of book). Call when leaving an
+    /// interval.
+    #[allow(clippy::too_many_arguments)]
+    pub fn log_interval_summary(
+        &mut self,
+        slug: &str,
+        interval_start_unix: u64,
+        close_time_unix: u64,
+        min_bid_up: Option<Decimal>,
+        max_bid_up: Option<Decimal>,
+        min_bid_down: Option<Decimal>,
+        max_bid_down: Option<Decimal>,
+        final_book: Option<&TopOfBook>,
+        declined: &BTreeMap<DeclineReason, u64>,
+    ) -> Result<()> {
+        let ranged_01_99_up = min_bid_up
+            .zip(max_bid_up)
+            .map(|(min, max)| min <= dec!(0.02) && max >= dec!(0.98))
+            .unwrap_or(false);
+        let ranged_01_99_down = min_bid_down
+            .zip(max_bid_down)
+            .map(|(min, max)| min <= dec!(0.02) && max >= dec!(0.98))
+            .unwrap_or(false);
+        let final_up = final_book.and_then(|b| b.token_id_up.as_ref());
+        let final_down = final_book.and_then(|b| b.token_id_down.as_ref());
+
+        let obj = serde_json::json!({
+            "event": "interval_summary",
+            "slug": slug,
+            "interval_start_unix": interval_start_unix,
+            "close_time_unix": close_time_unix,
+            "min_bid_up": dec_opt(min_bid_up),
+            "max_bid_up": dec_opt(max_bid_up),
+            "min_bid_down": dec_opt(min_bid_down),
+            "max_bid_down": dec_opt(max_bid_down),
+            "ranged_01_99_up": ranged_01_99_up,
+            "ranged_01_99_down": ranged_01_99_down,
+            "final_bid_up": dec_opt(final_up.and_then(|s| s.best_bid)),
+            "final_ask_up": dec_opt(final_up.and_then(|s| s.best_ask)),
+            "final_bid_down": dec_opt(final_down.and_then(|s| s.best_bid)),
+            "final_ask_down": dec_opt(final_down.and_then(|s| s.best_ask)),
+            "declined_entries": counts_json(declined),
+        });
+        self.write_line(&obj)
+    }
+
+    pub fn session_start_ms(&self) -> u64 {
+        self.session_start_ms
+    }
+
+    /// PnL recorded for TP and SL closes (positions exited with a sell).
+    pub fn sold_pnl(&self) -> Decimal {
+        self.sold_pnl
+    }
+
+    /// Log the realized PnL rebuilt from the trades history next to the session's TP/SL PnL.
+    pub fn log_trades_reconcile(&mut self, trades: &TradesPnl) -> Result<()> {
+        let obj = serde_json::json!({
+            "event": "trades_reconcile",
+            "trades_buys": trades.buys,
+            "trades_sells": trades.sells,
+            "trades_realized_pnl_usd": trades.realized.round_dp(6).to_string(),
+            "trades_fees_usd": trades.fees.round_dp(6).to_string(),
+            "trades_unmatched_sold": trades.unmatched_sold.to_string(),
+            "trades_held_tokens": trades.held.len(),
+            "session_sold_pnl_usd": self.sold_pnl.round_dp(6).to_string(),
+            "difference_usd": (trades.realized - self.sold_pnl).round_dp(6).to_string(),
+        });
+        self.write_line(&obj)
+    }
+
+    /// Log an entry bought across several orders: each (price, size) fill and their average.
+    pub fn log_entry_fills(
+        &mut self,
+        slug: &str,
+        side: EntrySide,
+        fills: &[(Decimal, Decimal)],
+        avg_price: Decimal,
+        last_order_price: Decimal,
+    ) -> Result<()> {
+        let obj = serde_json::json!({
+            "event": "entry_fills",
+            "slug": slug,
+            "side": side_str(side),
+            "fills": fills
+                .iter()
+                .map(|(p, s)| serde_json::json!({ "price": p.to_string(), "size": s.to_string() }))
+                .collect::<Vec<_>>(),
+            "size": fills.iter().map(|(_, s)| *s).sum::<Decimal>().to_string(),
+            "avg_price": avg_price.to_string(),
+            "last_order_price": last_order_price.to_string(),
+            "ts_ms": self.now_ms(),
+        });
+        self.write_line(&obj)
+    }
+
+    /// Log the loop's tick timings over an interval: count, slow ticks, mean, max and a histogram
+    /// keyed by bucket upper bound in ms ("inf" for the rest).
+    pub fn log_tick_profile(&mut self, slug: &str, stats: &TickStats) -> Result<()> {
+        let mut histogram = serde_json::Map::new();
+        for (i, count) in stats.buckets.iter().enumerate() {
+            let key = TICK_BUCKETS_MS
+                .get(i)
+                .map(|b| b.to_string())
+                .unwrap_or_else(|| "inf".to_string());
+            histogram.insert(key, serde_json::json!(count));
+        }
+        let obj = serde_json::json!({
+            "event": "tick_profile",
+            "slug": slug,
+            "ticks": stats.ticks,
+            "slow_ticks": stats.slow_ticks,
+            "mean_ms": stats.total_ms.checked_div(stats.ticks).unwrap_or(0),
+            "max_ms": stats.max_ms,
+            "histogram_ms": histogram,
+        });
+        self.write_line(&obj)
+    }
+
+    /// Write session summary (win rate, total PnL, counts, fill slippage per order type). Call when
+    /// bot exits.
+    pub fn write_session_summary(&mut self) -> Result<()> {
+        let end_ms = self.now_ms();
+        let session_duration_sec = (end_ms.saturating_sub(self.session_start_ms)) / 1000;
+        let closed_count = self.tp_count
+            + self.sl_count
+            + self.soft_stop_count
+            + self.market_close_count
+            + self.resolution_count;
+        let tp_sl_count = self.tp_count + self.sl_count;
+        let win_rate = if tp_sl_count > 0 {
+            (self.tp_count as f64) / (tp_sl_count as f64)
+        } else {
+            f64::NAN
+        };
+        let slippage = self.orders.as_ref().map(|t| {
+            t.lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .slippage()
+                .to_json()
+        });
+
+        let obj = serde_json::json!({
+            "event": "session_summary",
+            "session_start_ms": self.session_start_ms,
+            "session_end_ms": end_ms,
+            "session_duration_sec": session_duration_sec,
+            "tp_count": self.tp_count,
+            "sl_count": self.sl_count,
+            "soft_stop_count": self.soft_stop_count,
+            "market_close_count": self.market_close_count,
+            "resolution_count": self.resolution_count,
+            "total_closes": closed_count,
+            "win_rate": if win_rate.is_nan() { serde_json::Value::Null } else { serde_json::json!(win_rate) },
+            "total_pnl_usd": self.total_pnl.to_string(),
+            "total_fees_usd": self.total_fees.round_dp(6).to_string(),
+            "total_net_pnl_usd": (self.total_pnl - self.total_fees).round_dp(6).to_string(),
+            "slippage": slippage,
+        });
+        self.write_line(&obj)
+    }
+}
+
+impl Drop for SessionLog {
+    fn drop(&mut self) {
+        let _ = self.write_session_summary();
+    }
+}
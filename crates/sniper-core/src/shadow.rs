@@ -0,0 +1,271 @@
+//! Shadow mode (MM_SHADOW): candidate parameter sets run on the same tick stream as the live bot,
+//! each with its own strategy instance and a hypothetical position. Entries fill at the best ask
+//! when it is at or below the strategy's limit (up to the size at that level), TP/SL exit at the
+//! best bid, and a position still open at close settles at 1 or 0 by its last bid. No orders are
+//! sent; per-interval and running PnL are logged for each candidate. The same simulation replays
+//! recorded books in the `optimize` binary (see backtest.rs).
+
+use crate::book::trigger_price;
+use crate::clob::OrderSide;
+use crate::strategy::{create_strategy, Action, FillEvent, TickContext, TickStrategy};
+use crate::types::{Config, EntrySide, ResolvedMarket};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tracing::info;
+
+/// One candidate's result for one interval.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShadowResult {
+    pub pnl: Decimal,
+    pub trades: u32,
+    pub wins: u32,
+}
+
+struct ShadowPosition {
+    side: EntrySide,
+    size: Decimal,
+    entry_price: Decimal,
+}
+
+struct ShadowRun {
+    name: String,
+    config: Config,
+    strategy: Box<dyn TickStrategy>,
+    position: Option<ShadowPosition>,
+    /// Last best bid (Up, Down), for settling at close.
+    last_bids: (Option<Decimal>, Option<Decimal>),
+    entries_this_interval: u32,
+    /// MM_SL_CONFIRM_MS: when the SL condition started holding.
+    sl_breach_since_ms: Option<u64>,
+    interval: ShadowResult,
+    total_pnl: Decimal,
+    trades: u32,
+    wins: u32,
+}
+
+/// Every MM_SHADOW candidate, driven by the runner.
+pub struct ShadowRunner {
+    runs: Vec<ShadowRun>,
+    /// Slug of the interval being evaluated (None between intervals).
+    current_slug: Option<String>,
+}
+
+impl ShadowRunner {
+    /// None when MM_SHADOW has no candidates.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        if config.shadow.is_empty() {
+            return None;
+        }
+        let runs = config
+            .shadow
+            .iter()
+            .map(|params| {
+                let config = params.apply(config);
+                info!(
+                    "[Shadow] candidate {}: strategy={} size={} window={}s Up {}-{} TP {} SL {} | Down {}-{} TP {} SL {}",
+                    params.name,
+                    config.strategy,
+                    config.size_shares,
+                    config.seconds_before_close,
+                    config.up.min_buy_price,
+                    config.up.max_buy_price,
+                    config.up.take_profit_price,
+                    config.up.stop_loss_price,
+                    config.down.min_buy_price,
+                    config.down.max_buy_price,
+                    config.down.take_profit_price,
+                    config.down.stop_loss_price
+                );
+                ShadowRun {
+                    name: params.name.clone(),
+                    strategy: create_strategy(&config.strategy),
+                    config,
+                    position: None,
+                    last_bids: (None, None),
+                    entries_this_interval: 0,
+                    sl_breach_since_ms: None,
+                    interval: ShadowResult::default(),
+                    total_pnl: Decimal::ZERO,
+                    trades: 0,
+                    wins: 0,
+                }
+            })
+            .collect();
+        Some(Self {
+            runs,
+            current_slug: None,
+        })
+    }
+
+    pub fn on_interval_start(&mut self, market: &ResolvedMarket) {
+        self.current_slug = Some(market.slug.clone());
+        for run in &mut self.runs {
+            run.strategy.on_interval_start(market);
+            run.entries_this_interval = 0;
+            run.interval = ShadowResult::default();
+            run.last_bids = (None, None);
+        }
+    }
+
+    pub fn on_tick(&mut self, ctx: &TickContext) {
+        for run in &mut self.runs {
+            run.on_tick(ctx);
+        }
+    }
+
+    /// Settle open positions by their last bid and log each candidate's PnL for `market`.
+    /// Returns the interval's result per candidate, in MM_SHADOW order (empty if `market` is not
+    /// the interval being evaluated).
+    pub fn on_interval_end(&mut self, market: &ResolvedMarket) -> Vec<ShadowResult> {
+        if self.current_slug.as_deref() != Some(market.slug.as_str()) {
+            return Vec::new();
+        }
+        self.current_slug = None;
+        for run in &mut self.runs {
+            if let Some(ref pos) = run.position {
+                let bid = match pos.side {
+                    EntrySide::Up => run.last_bids.0,
+                    EntrySide::Down => run.last_bids.1,
+                };
+                let payout = if bid.is_some_and(|b| b >= dec!(0.5)) {
+                    Decimal::ONE
+                } else {
+                    Decimal::ZERO
+                };
+                run.close("RESOLUTION", payout);
+            }
+            info!(
+                "[Shadow] {} {}: interval PnL {} | total {} over {} trades ({} wins)",
+                run.name,
+                market.slug,
+                run.interval.pnl.round_dp(2),
+                run.total_pnl.round_dp(2),
+                run.trades,
+                run.wins
+            );
+        }
+        self.runs.iter().map(|run| run.interval).collect()
+    }
+}
+
+impl ShadowRun {
+    fn on_tick(&mut self, ctx: &TickContext) {
+        let book = |side: EntrySide| match side {
+            EntrySide::Up => ctx.top.token_id_up.as_ref(),
+            EntrySide::Down => ctx.top.token_id_down.as_ref(),
+        };
+        self.last_bids = (
+            book(EntrySide::Up)
+                .and_then(|b| b.best_bid)
+                .or(self.last_bids.0),
+            book(EntrySide::Down)
+                .and_then(|b| b.best_bid)
+                .or(self.last_bids.1),
+        );
+
+        if let Some(ref pos) = self.position {
+            let Some(bid) = book(pos.side).and_then(|b| b.best_bid) else {
+                return;
+            };
+            let side = self.config.side(pos.side);
+            let tp_price = self
+                .config
+                .take_profit_cap(ctx.secs_to_close)
+                .map_or(side.take_profit_price, |cap| {
+                    cap.min(side.take_profit_price)
+                });
+            if self.config.enable_auto_sell && bid >= tp_price {
+                self.close("TP", bid);
+            } else if self.config.enable_stop_loss {
+                let breached = book(pos.side)
+                    .and_then(|b| trigger_price(self.config.trigger_price_source, b, None))
+                    .is_some_and(|p| p <= side.stop_loss_price);
+                if !breached {
+                    self.sl_breach_since_ms = None;
+                } else {
+                    let since = *self.sl_breach_since_ms.get_or_insert(ctx.now_ms);
+                    if ctx.now_ms.saturating_sub(since) >= self.config.sl_confirm_ms {
+                        self.close("SL", bid);
+                    }
+                }
+            }
+            return;
+        }
+
+        let in_window = self.config.no_window_all_intervals
+            || ctx.secs_to_close <= self.config.seconds_before_close as u64;
+        if !in_window || self.entries_this_interval >= self.config.max_trades_per_interval {
+            return;
+        }
+        let actions = self.strategy.on_book(&TickContext {
+            config: &self.config,
+            ..*ctx
+        });
+        for Action::Buy {
+            side,
+            price: limit,
+            size_available,
+            ..
+        } in actions
+        {
+            let Some(ask) = book(side).and_then(|b| b.best_ask) else {
+                continue;
+            };
+            let size = self.config.size_shares.min(size_available);
+            if ask > limit || size < ctx.min_order_size {
+                continue;
+            }
+            info!(
+                "[Shadow] {} BUY  {:?} {} @ {}",
+                self.name,
+                side,
+                size.round_dp(2),
+                ask
+            );
+            self.strategy.on_fill(&FillEvent {
+                token_id: match side {
+                    EntrySide::Up => ctx.market.token_id_up.clone(),
+                    EntrySide::Down => ctx.market.token_id_down.clone(),
+                },
+                side: OrderSide::Buy,
+                entry_side: side,
+                price: ask,
+                size,
+                timestamp_ms: ctx.now_ms,
+            });
+            self.position = Some(ShadowPosition {
+                side,
+                size,
+                entry_price: ask,
+            });
+            self.entries_this_interval += 1;
+            break;
+        }
+    }
+
+    fn close(&mut self, exit: &str, price: Decimal) {
+        let Some(pos) = self.position.take() else {
+            return;
+        };
+        self.sl_breach_since_ms = None;
+        let pnl = (price - pos.entry_price) * pos.size;
+        self.interval.pnl += pnl;
+        self.interval.trades += 1;
+        self.total_pnl += pnl;
+        self.trades += 1;
+        if pnl > Decimal::ZERO {
+            self.interval.wins += 1;
+            self.wins += 1;
+        }
+        info!(
+            "[Shadow] {} {:<4} {:?} {} @ {} (entry {}) PnL {}",
+            self.name,
+            exit,
+            pos.side,
+            pos.size.round_dp(2),
+            price,
+            pos.entry_price,
+            pnl.round_dp(2)
+        );
+    }
+}
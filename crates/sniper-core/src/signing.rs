@@ -0,0 +1,345 @@
+//! EIP-712 order signing and HMAC L2 auth for Polymarket CLOB.
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use ethers::types::{H160, U256};
+use ethers::utils::keccak256;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+const PROTOCOL_NAME: &str = "Polymarket CTF Exchange";
+const PROTOCOL_VERSION: &str = "1";
+
+/// Polygon mainnet CTF Exchange (non-neg-risk).
+pub const EXCHANGE_ADDRESS_POLYGON: &str = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E";
+/// Neg-risk CTF Exchange (multi-outcome markets).
+pub const NEG_RISK_EXCHANGE_POLYGON: &str = "0xC5d563A36AE78145C45a50134d48A1215220f80a";
+
+/// Polymarket Gnosis Safe factory (signature type 2) and its proxy init code hash.
+const SAFE_FACTORY_POLYGON: &str = "0xaacFeEa03eb1561C4e67d661e40682Bd20E3541b";
+const SAFE_INIT_CODE_HASH: &str =
+    "2bce2127ff07fb632d16c8347c4ebf501f4841168bed00d9e6ef715ddb6fcecf";
+/// Polymarket proxy wallet factory (signature type 1, email / Magic accounts).
+const PROXY_FACTORY_POLYGON: &str = "0xaB45c5A4B0c941a2F231C04C3f49182e1A254052";
+const PROXY_INIT_CODE_HASH: &str =
+    "d21df8dc65880a8606f09fe0ce3df9b8869287ab0b058be05aa9e8af6330a00b";
+
+/// Signature types accepted by the CTF exchange: plain EOA (the signer holds the funds).
+pub const SIGNATURE_TYPE_EOA: u8 = 0;
+/// Polymarket proxy wallet (email / Magic accounts).
+pub const SIGNATURE_TYPE_POLY_PROXY: u8 = 1;
+/// Polymarket Gnosis Safe (browser wallet accounts).
+pub const SIGNATURE_TYPE_GNOSIS_SAFE: u8 = 2;
+
+fn factory_address(factory: &str, salt: [u8; 32], init_code_hash: &str) -> H160 {
+    let factory: H160 = factory.parse().expect("valid factory address");
+    let init_code_hash = hex::decode(init_code_hash).expect("valid init code hash");
+    ethers::utils::get_create2_address_from_hash(factory, salt, init_code_hash)
+}
+
+/// Polymarket Safe of `eoa` (CREATE2, salt = keccak256(abi.encode(eoa))). It may not be deployed yet.
+pub fn polymarket_safe_address(eoa: H160) -> H160 {
+    let salt = keccak256(address_to_32_bytes(&eoa));
+    factory_address(SAFE_FACTORY_POLYGON, salt, SAFE_INIT_CODE_HASH)
+}
+
+/// Polymarket proxy wallet of `eoa` (CREATE2, salt = keccak256(abi.encodePacked(eoa))).
+pub fn polymarket_proxy_address(eoa: H160) -> H160 {
+    let salt = keccak256(eoa.as_bytes());
+    factory_address(PROXY_FACTORY_POLYGON, salt, PROXY_INIT_CODE_HASH)
+}
+
+fn u256_to_32_bytes(u: U256) -> [u8; 32] {
+    let mut buf = [0u8; 32];
+    u.to_big_endian(&mut buf);
+    buf
+}
+
+fn address_to_32_bytes(addr: &H160) -> [u8; 32] {
+    let mut buf = [0u8; 32];
+    buf[12..32].copy_from_slice(addr.as_bytes());
+    buf
+}
+
+/// EIP-712 domain separator hash for Polymarket CTF Exchange.
+fn domain_separator(verifying_contract: H160, chain_id: u64) -> [u8; 32] {
+    let type_hash = keccak256(
+        "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)",
+    );
+    let name_hash = keccak256(PROTOCOL_NAME.as_bytes());
+    let version_hash = keccak256(PROTOCOL_VERSION.as_bytes());
+    let mut encoded = Vec::with_capacity(32 * 5);
+    encoded.extend_from_slice(&type_hash);
+    encoded.extend_from_slice(&name_hash);
+    encoded.extend_from_slice(&version_hash);
+    encoded.extend_from_slice(&u256_to_32_bytes(U256::from(chain_id)));
+    encoded.extend_from_slice(&address_to_32_bytes(&verifying_contract));
+    keccak256(encoded)
+}
+
+/// Order struct type hash (must match Polymarket exchange).
+fn order_type_hash() -> [u8; 32] {
+    keccak256(
+        "Order(uint256 salt,address maker,address signer,address taker,uint256 tokenId,uint256 makerAmount,uint256 takerAmount,uint256 expiration,uint256 nonce,uint256 feeRateBps,uint8 side,uint8 signatureType)",
+    )
+}
+
+/// EIP-712 digest for signing: keccak256("\x19\x01" || domain_sep || struct_hash).
+fn eip712_digest(domain_sep: [u8; 32], struct_hash: [u8; 32]) -> [u8; 32] {
+    let mut prefixed = Vec::with_capacity(2 + 32 + 32);
+    prefixed.extend_from_slice(b"\x19\x01");
+    prefixed.extend_from_slice(&domain_sep);
+    prefixed.extend_from_slice(&struct_hash);
+    keccak256(prefixed)
+}
+
+/// Fixed message the CLOB expects in the L1 ClobAuth struct.
+const CLOB_AUTH_MESSAGE: &str = "This message attests that I control the given wallet";
+
+/// L1 auth (POLY_SIGNATURE for /auth/*): EIP-712 ClobAuth{address, timestamp, nonce, message}
+/// in the "ClobAuthDomain" domain; returns 0x-prefixed hex signature.
+pub fn sign_clob_auth(
+    wallet: &ethers::signers::LocalWallet,
+    chain_id: u64,
+    timestamp: u64,
+    nonce: u64,
+) -> Result<String> {
+    use ethers::signers::Signer;
+    let mut domain = Vec::with_capacity(32 * 4);
+    domain.extend_from_slice(&keccak256(
+        "EIP712Domain(string name,string version,uint256 chainId)",
+    ));
+    domain.extend_from_slice(&keccak256("ClobAuthDomain"));
+    domain.extend_from_slice(&keccak256("1"));
+    domain.extend_from_slice(&u256_to_32_bytes(U256::from(chain_id)));
+    let mut encoded = Vec::with_capacity(32 * 5);
+    encoded.extend_from_slice(&keccak256(
+        "ClobAuth(address address,string timestamp,uint256 nonce,string message)",
+    ));
+    encoded.extend_from_slice(&address_to_32_bytes(&wallet.address()));
+    encoded.extend_from_slice(&keccak256(timestamp.to_string()));
+    encoded.extend_from_slice(&u256_to_32_bytes(U256::from(nonce)));
+    encoded.extend_from_slice(&keccak256(CLOB_AUTH_MESSAGE));
+    let digest = eip712_digest(keccak256(domain), keccak256(encoded));
+    let sig = wallet.sign_hash(ethers::types::H256::from(digest))?;
+    Ok(format!("0x{}", hex::encode(sig.to_vec())))
+}
+
+/// Parse token_id string (hex 0x... or decimal) to U256.
+pub fn parse_token_id(token_id: &str) -> Result<U256> {
+    let s = token_id.trim().trim_start_matches("0x");
+    if token_id.starts_with("0x") || token_id.starts_with("0X") {
+        U256::from_str_radix(s, 16).context("token_id hex parse")
+    } else {
+        U256::from_dec_str(token_id).context("token_id decimal parse")
+    }
+}
+
+/// Sign an order with the wallet; returns 0x-prefixed hex signature.
+pub async fn sign_order(
+    wallet: &ethers::signers::LocalWallet,
+    chain_id: u64,
+    verifying_contract: H160,
+    maker: H160,
+    signer: H160,
+    signature_type: u8,
+    order: &OrderFields,
+) -> Result<String> {
+    OrderSigner::new(chain_id, verifying_contract, maker, signer, signature_type)
+        .sign(wallet, order)
+}
+
+/// Per-order fields of the EIP-712 Order; maker, signer and signature type come from [OrderSigner].
+#[derive(Debug, Clone)]
+pub struct OrderFields {
+    /// Random salt making the order hash unique.
+    pub salt: u64,
+    /// Counterparty; zero for public orders.
+    pub taker: H160,
+    /// Outcome token.
+    pub token_id: U256,
+    /// Amount the maker gives (USDC for buys, shares for sells; 6 decimals).
+    pub maker_amount: U256,
+    /// Amount the maker receives (shares for buys, USDC for sells; 6 decimals).
+    pub taker_amount: U256,
+    /// Expiry (unix seconds); 0 = none.
+    pub expiration: u64,
+    /// Exchange nonce of the maker.
+    pub nonce: u64,
+    /// Fee rate (basis points) the order accepts.
+    pub fee_rate_bps: u64,
+    /// 0 = BUY, 1 = SELL.
+    pub side: u8,
+}
+
+/// Order signing with the per-account parts (domain separator, type hash, maker, signer,
+/// signature type) computed once, so the buy path only hashes the order fields and signs.
+#[derive(Debug, Clone)]
+pub struct OrderSigner {
+    domain_sep: [u8; 32],
+    type_hash: [u8; 32],
+    maker: H160,
+    signer: H160,
+    signature_type: u8,
+}
+
+impl OrderSigner {
+    /// Signer for orders on `verifying_contract` (the exchange) of `chain_id`.
+    pub fn new(
+        chain_id: u64,
+        verifying_contract: H160,
+        maker: H160,
+        signer: H160,
+        signature_type: u8,
+    ) -> Self {
+        Self {
+            domain_sep: domain_separator(verifying_contract, chain_id),
+            type_hash: order_type_hash(),
+            maker,
+            signer,
+            signature_type,
+        }
+    }
+
+    /// EIP-712 struct hash of `order` made by this signer's maker.
+    fn struct_hash(&self, order: &OrderFields) -> [u8; 32] {
+        let mut encoded = Vec::with_capacity(32 * 13);
+        encoded.extend_from_slice(&self.type_hash);
+        encoded.extend_from_slice(&u256_to_32_bytes(U256::from(order.salt)));
+        encoded.extend_from_slice(&address_to_32_bytes(&self.maker));
+        encoded.extend_from_slice(&address_to_32_bytes(&self.signer));
+        encoded.extend_from_slice(&address_to_32_bytes(&order.taker));
+        encoded.extend_from_slice(&u256_to_32_bytes(order.token_id));
+        encoded.extend_from_slice(&u256_to_32_bytes(order.maker_amount));
+        encoded.extend_from_slice(&u256_to_32_bytes(order.taker_amount));
+        encoded.extend_from_slice(&u256_to_32_bytes(U256::from(order.expiration)));
+        encoded.extend_from_slice(&u256_to_32_bytes(U256::from(order.nonce)));
+        encoded.extend_from_slice(&u256_to_32_bytes(U256::from(order.fee_rate_bps)));
+        encoded.extend_from_slice(&u256_to_32_bytes(U256::from(order.side)));
+        encoded.extend_from_slice(&u256_to_32_bytes(U256::from(self.signature_type)));
+        keccak256(encoded)
+    }
+
+    /// Sign one order; returns 0x-prefixed hex signature.
+    pub fn sign(
+        &self,
+        wallet: &ethers::signers::LocalWallet,
+        order: &OrderFields,
+    ) -> Result<String> {
+        self.sign_with_hash(wallet, order).map(|(sig, _)| sig)
+    }
+
+    /// Sign one order; returns the 0x-prefixed hex signature and order hash (the EIP-712 digest,
+    /// which the CLOB uses as the order ID).
+    pub fn sign_with_hash(
+        &self,
+        wallet: &ethers::signers::LocalWallet,
+        order: &OrderFields,
+    ) -> Result<(String, String)> {
+        let digest = self.digest(order);
+        let sig = wallet.sign_hash(ethers::types::H256::from(digest))?;
+        Ok((
+            format!("0x{}", hex::encode(sig.to_vec())),
+            format!("0x{}", hex::encode(digest)),
+        ))
+    }
+
+    /// 0x-prefixed order hash of `order` (the CLOB order ID), without signing.
+    pub fn order_hash(&self, order: &OrderFields) -> String {
+        format!("0x{}", hex::encode(self.digest(order)))
+    }
+
+    /// EIP-712 digest of `order`: what gets signed.
+    fn digest(&self, order: &OrderFields) -> [u8; 32] {
+        eip712_digest(self.domain_sep, self.struct_hash(order))
+    }
+
+    /// [OrderSigner::sign_with_hash] for several orders, results in input order. With `workers`
+    /// above 1 the orders are split across up to that many threads (joined before returning);
+    /// otherwise they are signed one after the other on the calling thread.
+    pub fn sign_batch(
+        &self,
+        wallet: &ethers::signers::LocalWallet,
+        orders: &[OrderFields],
+        workers: usize,
+    ) -> Result<Vec<(String, String)>> {
+        if workers <= 1 || orders.len() <= 1 {
+            return orders
+                .iter()
+                .map(|o| self.sign_with_hash(wallet, o))
+                .collect();
+        }
+        let per_worker = orders.len().div_ceil(workers);
+        std::thread::scope(|s| {
+            let handles: Vec<_> = orders
+                .chunks(per_worker)
+                .map(|chunk| {
+                    s.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|o| self.sign_with_hash(wallet, o))
+                            .collect::<Result<Vec<_>>>()
+                    })
+                })
+                .collect();
+            let mut signed = Vec::with_capacity(orders.len());
+            for h in handles {
+                signed.extend(h.join().map_err(|_| anyhow!("signing worker panicked"))??);
+            }
+            Ok(signed)
+        })
+    }
+}
+
+/// Build POLY_SIGNATURE for L2: HMAC-SHA256(secret, timestamp + method + path + body), base64url.
+pub fn build_poly_hmac(
+    secret_b64: &str,
+    timestamp: u64,
+    method: &str,
+    request_path: &str,
+    body: Option<&str>,
+) -> Result<String> {
+    PolyHmacKey::new(secret_b64)?.sign(timestamp, method, request_path, body)
+}
+
+/// L2 SECRET decoded once, so each request only runs the HMAC.
+#[derive(Clone)]
+pub struct PolyHmacKey(Vec<u8>);
+
+impl PolyHmacKey {
+    /// Key from the base64 (standard or url-safe) API SECRET.
+    pub fn new(secret_b64: &str) -> Result<Self> {
+        let secret_bytes = base64::engine::general_purpose::STANDARD
+            .decode(
+                secret_b64
+                    .replace('-', "+")
+                    .replace('_', "/")
+                    .trim()
+                    .as_bytes(),
+            )
+            .context("SECRET base64 decode")?;
+        Ok(Self(secret_bytes))
+    }
+
+    /// POLY_SIGNATURE for one request.
+    pub fn sign(
+        &self,
+        timestamp: u64,
+        method: &str,
+        request_path: &str,
+        body: Option<&str>,
+    ) -> Result<String> {
+        let message = if let Some(b) = body {
+            format!("{}{}{}{}", timestamp, method, request_path, b)
+        } else {
+            format!("{}{}{}", timestamp, method, request_path)
+        };
+        type HmacSha256 = Hmac<Sha256>;
+        let mut mac = HmacSha256::new_from_slice(&self.0).context("HMAC key")?;
+        mac.update(message.as_bytes());
+        let result = mac.finalize();
+        let sig_b64 = base64::engine::general_purpose::STANDARD.encode(result.into_bytes());
+        let sig_url_safe = sig_b64.replace('+', "-").replace('/', "_");
+        Ok(sig_url_safe)
+    }
+}
@@ -0,0 +1,241 @@
+//! Simulated CLOB for dry runs (MM_DRY_RUN_SIM_FILLS=true): no order is sent, but each order is
+//! matched against the live order book (GET /book) level by level, so FAK partials, FOK kills and
+//! slippage show up as they would live. Holdings (and cash, with MM_SIM_BALANCE_USDC) are tracked
+//! from the simulated fills, so TP/SL sells see the same balance checks as a live account.
+
+use crate::book::{fetch_order_book, public_client, BookDepth, Levels};
+use crate::clob::{
+    estimated_fee_usd, ClobClient, LimitOrderParams, OrderSide, OrderType, PlaceOrderResult,
+};
+use crate::types::OrderBookRaw;
+use anyhow::{Context, Result};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+use tracing::info;
+
+#[derive(Default)]
+struct SimState {
+    /// Shares held per token_id.
+    holdings: HashMap<String, Decimal>,
+    /// USDC left; None when MM_SIM_BALANCE_USDC is unset (unlimited, balance unknown).
+    cash: Option<Decimal>,
+    next_order_id: u64,
+}
+
+pub struct SimClob {
+    client: reqwest::Client,
+    clob_host: String,
+    state: Mutex<SimState>,
+}
+
+impl SimClob {
+    /// Host from POLYMARKET_CLOB_HOST (as the live client); starting cash from MM_SIM_BALANCE_USDC.
+    pub fn from_env() -> Result<Self> {
+        let clob_host = std::env::var("POLYMARKET_CLOB_HOST")
+            .or_else(|_| std::env::var("POLYMARKET_CLOB_URL"))
+            .unwrap_or_else(|_| "https://clob.polymarket.com".to_string());
+        let cash = match std::env::var("MM_SIM_BALANCE_USDC") {
+            Ok(v) if !v.trim().is_empty() => {
+                Some(Decimal::from_str(v.trim()).context("Invalid MM_SIM_BALANCE_USDC")?)
+            }
+            _ => None,
+        };
+        let client = public_client(Duration::from_secs(10))?;
+        info!(
+            "[SimClob] dry run with book-matched fills (host={}, cash={})",
+            clob_host,
+            cash.map_or("unlimited".to_string(), |c| c.to_string())
+        );
+        Ok(Self {
+            client,
+            clob_host,
+            state: Mutex::new(SimState {
+                cash,
+                ..SimState::default()
+            }),
+        })
+    }
+}
+
+/// Levels an order of `side` takes from, best first (asks ascending for a buy, bids descending
+/// for a sell).
+fn opposite_levels(raw: &OrderBookRaw, side: OrderSide) -> Levels {
+    let (bids, asks) = BookDepth::from_raw(raw).top_levels(usize::MAX);
+    match side {
+        OrderSide::Buy => asks,
+        OrderSide::Sell => bids,
+    }
+}
+
+/// Shares and USDC notional matched by an order at `limit` for `size`, walking the levels.
+fn match_levels(
+    levels: &[(Decimal, Decimal)],
+    side: OrderSide,
+    limit: Decimal,
+    size: Decimal,
+) -> (Decimal, Decimal) {
+    let mut filled = Decimal::ZERO;
+    let mut notional = Decimal::ZERO;
+    for &(price, available) in levels {
+        let crosses = match side {
+            OrderSide::Buy => price <= limit,
+            OrderSide::Sell => price >= limit,
+        };
+        if !crosses || filled >= size {
+            break;
+        }
+        let take = available.min(size - filled);
+        filled += take;
+        notional += take * price;
+    }
+    (filled, notional)
+}
+
+#[async_trait::async_trait]
+impl ClobClient for SimClob {
+    async fn place_limit_order(
+        &self,
+        params: LimitOrderParams,
+        order_type: OrderType,
+    ) -> Result<PlaceOrderResult> {
+        if params.side == OrderSide::Sell {
+            let held = self
+                .state
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .holdings
+                .get(&params.token_id)
+                .copied()
+                .unwrap_or_default();
+            if params.size > held {
+                return Ok(PlaceOrderResult {
+                    http_status: Some(400),
+                    ..PlaceOrderResult::failed(format!(
+                        "not enough balance / allowance (sim: hold {}, sell {})",
+                        held, params.size
+                    ))
+                });
+            }
+        }
+        let raw = fetch_order_book(&self.client, &self.clob_host, &params.token_id).await?;
+        let levels = opposite_levels(&raw, params.side);
+        let (mut filled, mut notional) =
+            match_levels(&levels, params.side, params.price, params.size);
+
+        match order_type {
+            OrderType::Fak if filled.is_zero() => {
+                return Ok(PlaceOrderResult::failed(
+                    "no orders found to match with FAK order (sim)".to_string(),
+                ));
+            }
+            OrderType::Fok if filled < params.size => {
+                return Ok(PlaceOrderResult::failed(format!(
+                    "order couldn't be fully filled, FOK orders are fully filled or killed (sim: {} of {} available)",
+                    filled, params.size
+                )));
+            }
+            _ if params.post_only && !filled.is_zero() => {
+                return Ok(PlaceOrderResult::failed(
+                    "invalid post-only order: order crosses book (sim)".to_string(),
+                ));
+            }
+            // Resting remainder of a GTC order: assumed filled at the limit (no queue simulation).
+            OrderType::Gtc | OrderType::Gtd if filled < params.size => {
+                notional += (params.size - filled) * params.price;
+                filled = params.size;
+            }
+            _ => {}
+        }
+
+        let avg_price = if filled.is_zero() {
+            params.price
+        } else {
+            notional / filled
+        };
+        let fee = estimated_fee_usd(params.fee_rate_bps.unwrap_or(0), avg_price, filled);
+        let order_id = {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            if params.side == OrderSide::Buy {
+                if let Some(cash) = state.cash {
+                    if notional + fee > cash {
+                        return Ok(PlaceOrderResult {
+                            http_status: Some(400),
+                            ..PlaceOrderResult::failed(format!(
+                                "not enough balance / allowance (sim: cash {}, need {})",
+                                cash,
+                                notional + fee
+                            ))
+                        });
+                    }
+                }
+            }
+            let held = state.holdings.entry(params.token_id.clone()).or_default();
+            let cash_delta = match params.side {
+                OrderSide::Buy => {
+                    *held += filled;
+                    -(notional + fee)
+                }
+                OrderSide::Sell => {
+                    *held -= filled;
+                    notional - fee
+                }
+            };
+            if let Some(cash) = state.cash.as_mut() {
+                *cash += cash_delta;
+            }
+            state.next_order_id += 1;
+            format!("sim-{}", state.next_order_id)
+        };
+        info!(
+            "[SimClob] {} {:?} {} of {} @ limit {} (avg {}, fee {}) token_id={}",
+            match params.side {
+                OrderSide::Buy => "BUY",
+                OrderSide::Sell => "SELL",
+            },
+            order_type,
+            filled,
+            params.size,
+            params.price,
+            avg_price.round_dp(4),
+            fee.round_dp(4),
+            &params.token_id[..params.token_id.len().min(18)]
+        );
+        Ok(PlaceOrderResult {
+            order_id: Some(order_id),
+            success: true,
+            error_msg: None,
+            error: None,
+            filled_size: Some(filled),
+            http_status: None,
+            avg_price: (!filled.is_zero()).then_some(avg_price),
+            failed_parts: Vec::new(),
+        })
+    }
+
+    async fn get_balance_allowance(&self, token_id: &str) -> Result<String> {
+        Ok(format!(
+            "(sim) balance={}",
+            self.get_available_balance(token_id)
+                .await?
+                .unwrap_or_default()
+        ))
+    }
+
+    async fn get_available_balance(&self, token_id: &str) -> Result<Option<Decimal>> {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        Ok(Some(
+            state.holdings.get(token_id).copied().unwrap_or_default(),
+        ))
+    }
+
+    async fn get_collateral_balance(&self) -> Result<Option<Decimal>> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .cash)
+    }
+}
@@ -0,0 +1,76 @@
+//! Per-interval position sizing (MM_SIZING). `fraction` stakes MM_SIZING_FRACTION of the USDC
+//! bankroll; `kelly` stakes MM_KELLY_MULTIPLIER × the Kelly fraction estimated from the session's
+//! realized closes (inventory::TradeStats), falling back to the fixed fraction until
+//! MM_KELLY_MIN_TRADES closes. The stake is capped at MM_SIZING_MAX_FRACTION of the bankroll and
+//! converted to shares at the max entry price. A stake that buys less than the market's min order
+//! size (no Kelly edge, or a cap too small) sizes the interval at 0: no entry rather than the
+//! minimum.
+
+use crate::inventory::TradeStats;
+use crate::types::{Config, SizingMode};
+use rust_decimal::Decimal;
+
+/// Size chosen for one interval.
+#[derive(Debug, Clone, Copy)]
+pub struct Sizing {
+    /// Bankroll fraction staked (after multiplier and cap).
+    pub fraction: Decimal,
+    /// 0 when the stake buys less than the min order size: no entry this interval.
+    pub shares: Decimal,
+    /// What the fraction came from, for the log.
+    pub basis: &'static str,
+}
+
+/// Full Kelly fraction for a bet won with probability `p` that gains `avg_win` or loses
+/// `avg_loss` per USDC staked: p / avg_loss − (1 − p) / avg_win. None without both a win and a
+/// loss to estimate from.
+pub fn kelly_fraction(stats: &TradeStats) -> Option<Decimal> {
+    let p = stats.win_rate()?;
+    let (win, loss) = (stats.avg_win()?, stats.avg_loss()?);
+    Some(p / loss - (Decimal::ONE - p) / win)
+}
+
+/// Bankroll fraction to stake this interval, before the cap.
+fn stake_fraction(config: &Config, stats: &TradeStats) -> (Decimal, &'static str) {
+    match config.sizing {
+        SizingMode::Fixed | SizingMode::Fraction => (config.sizing_fraction, "fraction"),
+        SizingMode::Kelly if stats.trades() < config.kelly_min_trades => (
+            config.sizing_fraction,
+            "fraction (too few trades for kelly)",
+        ),
+        SizingMode::Kelly => match kelly_fraction(stats) {
+            Some(k) if k > Decimal::ZERO => (k * config.kelly_multiplier, "kelly"),
+            Some(_) => (Decimal::ZERO, "kelly (no edge)"),
+            None => (config.sizing_fraction, "fraction (no win/loss yet)"),
+        },
+    }
+}
+
+/// Shares for the next interval from `bankroll` (USDC) at `price` (the max entry price): 0 when
+/// below `min_order_size`; None in fixed mode.
+pub fn interval_size(
+    config: &Config,
+    bankroll: Decimal,
+    stats: &TradeStats,
+    price: Decimal,
+    min_order_size: Decimal,
+) -> Option<Sizing> {
+    if config.sizing == SizingMode::Fixed || price <= Decimal::ZERO {
+        return None;
+    }
+    let (fraction, basis) = stake_fraction(config, stats);
+    let fraction = fraction.max(Decimal::ZERO).min(config.sizing_max_fraction);
+    let hundred = Decimal::from(100);
+    let mut shares = (bankroll.max(Decimal::ZERO) * fraction / price * hundred).trunc() / hundred;
+    if config.sizing_max_shares > Decimal::ZERO {
+        shares = shares.min(config.sizing_max_shares);
+    }
+    if shares < min_order_size {
+        shares = Decimal::ZERO;
+    }
+    Some(Sizing {
+        fraction,
+        shares,
+        basis,
+    })
+}
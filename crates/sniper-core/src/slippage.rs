@@ -0,0 +1,93 @@
+//! Fill slippage: each fill's price against the decision price, the best ask (buy) or best bid
+//! (sell) of the book the loop last read when the order was created. Slippage is per share and
+//! signed as a cost: positive when the fill was worse than that price (a buy above the ask, a sell
+//! below the bid), negative for price improvement (e.g. a resting TP filled above the bid). The
+//! order tracker aggregates it per order type; the session summary carries the totals, so the
+//! price of a slow or crossing order is a number rather than a guess.
+
+use crate::clob::{OrderSide, OrderType};
+use crate::order_tracker::order_type_label;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+/// Slippage per share of a fill at `fill_price` against `decision_price`; positive = cost.
+pub fn slippage(side: OrderSide, decision_price: Decimal, fill_price: Decimal) -> Decimal {
+    match side {
+        OrderSide::Buy => fill_price - decision_price,
+        OrderSide::Sell => decision_price - fill_price,
+    }
+}
+
+/// Fills of one order type.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SlippageAgg {
+    pub fills: u64,
+    pub shares: Decimal,
+    /// Sum of slippage × size: what the fills cost against the decision prices, in USDC.
+    pub cost_usd: Decimal,
+    /// Largest slippage per share (the worst fill).
+    pub worst: Option<Decimal>,
+    /// Smallest slippage per share (the best fill; negative = improvement).
+    pub best: Option<Decimal>,
+}
+
+impl SlippageAgg {
+    /// Size-weighted mean slippage per share.
+    pub fn mean(&self) -> Option<Decimal> {
+        (!self.shares.is_zero()).then(|| self.cost_usd / self.shares)
+    }
+}
+
+/// Slippage of the session's fills, per order type (GTC, GTD, FOK, FAK).
+#[derive(Debug, Clone, Default)]
+pub struct SlippageStats {
+    by_type: BTreeMap<&'static str, SlippageAgg>,
+}
+
+impl SlippageStats {
+    /// Count a fill of `size` shares; returns its slippage per share.
+    pub fn record(
+        &mut self,
+        order_type: OrderType,
+        side: OrderSide,
+        decision_price: Decimal,
+        fill_price: Decimal,
+        size: Decimal,
+    ) -> Decimal {
+        let slip = slippage(side, decision_price, fill_price);
+        let agg = self
+            .by_type
+            .entry(order_type_label(order_type))
+            .or_default();
+        agg.fills += 1;
+        agg.shares += size;
+        agg.cost_usd += slip * size;
+        agg.worst = Some(agg.worst.map_or(slip, |w| w.max(slip)));
+        agg.best = Some(agg.best.map_or(slip, |b| b.min(slip)));
+        slip
+    }
+
+    /// No fill recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.by_type.is_empty()
+    }
+
+    /// Per order type: fills, shares, mean/worst/best slippage per share and cost in USDC.
+    pub fn to_json(&self) -> serde_json::Value {
+        self.by_type
+            .iter()
+            .map(|(t, a)| {
+                let v = serde_json::json!({
+                    "fills": a.fills,
+                    "shares": a.shares.to_string(),
+                    "mean": a.mean().map(|m| m.round_dp(6).to_string()),
+                    "worst": a.worst.map(|w| w.to_string()),
+                    "best": a.best.map(|b| b.to_string()),
+                    "cost_usd": a.cost_usd.round_dp(6).to_string(),
+                });
+                (t.to_string(), v)
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
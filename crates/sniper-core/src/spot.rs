@@ -0,0 +1,111 @@
+//! Spot price of the interval's underlying (BTCUSDT / SOLUSDT 5m kline on the Binance REST API),
+//! polled in the background. Gives the move since the interval opened, which the
+//! `spot_direction` side policy (MM_SIDE_POLICY) uses to pick Up or Down, and the last price
+//! that hourly "above $X" entries compare to the strike (ETHUSDT / SOLUSDT).
+
+use crate::clock::wall_ms;
+use crate::types::{HourlyAsset, IntervalMarketAsset};
+use anyhow::{Context, Result};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::debug;
+
+/// A quote older than this is not used.
+const SPOT_MAX_AGE_MS: u64 = 5_000;
+
+/// Latest 5m candle: its open and the last traded price.
+#[derive(Debug, Clone, Copy)]
+pub struct SpotQuote {
+    pub open_time_unix: u64,
+    pub open: Decimal,
+    pub last: Decimal,
+    pub fetched_ms: u64,
+}
+
+/// Background poller of one symbol's current 5m candle.
+pub struct SpotFeed {
+    latest: watch::Receiver<Option<SpotQuote>>,
+}
+
+pub fn spot_symbol(asset: IntervalMarketAsset) -> &'static str {
+    match asset {
+        IntervalMarketAsset::Btc5m => "BTCUSDT",
+        IntervalMarketAsset::Sol5m => "SOLUSDT",
+    }
+}
+
+pub fn hourly_spot_symbol(asset: HourlyAsset) -> &'static str {
+    match asset {
+        HourlyAsset::Eth => "ETHUSDT",
+        HourlyAsset::Sol => "SOLUSDT",
+    }
+}
+
+/// GET /api/v3/klines?symbol=..&interval=5m&limit=1 (open time, open, high, low, close, ...).
+async fn fetch_quote(client: &Client, base_url: &str, symbol: &str) -> Result<SpotQuote> {
+    let url = format!(
+        "{}/api/v3/klines?symbol={}&interval=5m&limit=1",
+        base_url.trim_end_matches('/'),
+        symbol
+    );
+    let res = client.get(&url).send().await.context("spot request")?;
+    if !res.status().is_success() {
+        anyhow::bail!("HTTP {}", res.status());
+    }
+    let rows: Vec<Vec<serde_json::Value>> = res.json().await.context("spot kline JSON")?;
+    let row = rows.first().context("no kline")?;
+    let price = |i: usize| {
+        row.get(i)
+            .and_then(|v| v.as_str())
+            .and_then(|s| Decimal::from_str(s).ok())
+            .with_context(|| format!("kline field {}", i))
+    };
+    Ok(SpotQuote {
+        open_time_unix: row
+            .first()
+            .and_then(|v| v.as_u64())
+            .context("kline open time")?
+            / 1000,
+        open: price(1)?,
+        last: price(4)?,
+        fetched_ms: wall_ms(),
+    })
+}
+
+impl SpotFeed {
+    /// Poll `symbol` on `base_url` every `poll_ms` until the feed is dropped.
+    pub fn spawn(base_url: String, symbol: &'static str, poll_ms: u64) -> Result<Self> {
+        let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+        let (tx, rx) = watch::channel(None);
+        tokio::spawn(async move {
+            while !tx.is_closed() {
+                match fetch_quote(&client, &base_url, symbol).await {
+                    Ok(q) => {
+                        let _ = tx.send(Some(q));
+                    }
+                    Err(e) => debug!("[Spot] {} fetch failed: {:#}", symbol, e),
+                }
+                tokio::time::sleep(Duration::from_millis(poll_ms)).await;
+            }
+        });
+        Ok(Self { latest: rx })
+    }
+
+    /// Spot change (last − open) of the candle opened at `interval_start_unix`; None without a
+    /// fresh quote for that candle.
+    pub fn move_since_open(&self, interval_start_unix: u64) -> Option<Decimal> {
+        let q = (*self.latest.borrow())?;
+        (q.open_time_unix == interval_start_unix
+            && wall_ms().saturating_sub(q.fetched_ms) <= SPOT_MAX_AGE_MS)
+            .then_some(q.last - q.open)
+    }
+
+    /// Last traded price; None without a fresh quote.
+    pub fn last_price(&self) -> Option<Decimal> {
+        let q = (*self.latest.borrow())?;
+        (wall_ms().saturating_sub(q.fetched_ms) <= SPOT_MAX_AGE_MS).then_some(q.last)
+    }
+}
@@ -0,0 +1,61 @@
+//! Status file (MM_STATUS_FILE): a JSON snapshot of the engine (market, seconds to close, top of
+//! book, position, live orders, trades this interval, session PnL) rewritten every
+//! MM_STATUS_SECS, so dashboards and scripts can follow the bot without parsing logs. Each write
+//! goes to a temp file next to it that is then renamed over it, so readers never see a partial
+//! file.
+
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Writes the status snapshot when due.
+pub struct StatusFile {
+    path: PathBuf,
+    tmp: PathBuf,
+    every_ms: u64,
+    last_write_ms: u64,
+}
+
+impl StatusFile {
+    /// Creates the parent directory if it does not exist.
+    pub fn new(path: &str, every_secs: u64) -> Result<Self> {
+        let path = PathBuf::from(path);
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        tracing::info!("[Status] writing {} every {} s", path.display(), every_secs);
+        Ok(Self {
+            path,
+            tmp: PathBuf::from(tmp),
+            every_ms: every_secs * 1000,
+            last_write_ms: 0,
+        })
+    }
+
+    /// True when the next snapshot is due.
+    pub fn due(&self, now_ms: u64) -> bool {
+        now_ms.saturating_sub(self.last_write_ms) >= self.every_ms
+    }
+
+    /// Replace the file with `status`.
+    pub fn write(&mut self, status: &serde_json::Value, now_ms: u64) -> Result<()> {
+        self.last_write_ms = now_ms;
+        fs::write(&self.tmp, serde_json::to_vec_pretty(status)?)?;
+        fs::rename(&self.tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+/// The file of one engine among several (dual window, scanner): `status.json` ->
+/// `status_<slug>.json`.
+pub fn engine_path(path: &str, slug: &str) -> String {
+    let p = Path::new(path);
+    let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or("status");
+    let name = match p.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{}_{}.{}", stem, slug, ext),
+        None => format!("{}_{}", stem, slug),
+    };
+    p.with_file_name(name).to_string_lossy().into_owned()
+}
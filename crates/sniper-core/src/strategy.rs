@@ -0,0 +1,327 @@
+//! Entry strategies behind a trait: the runner calls [TickStrategy::on_book] on every book update
+//! while flat and executes the returned actions. Add a strategy by implementing the trait and
+//! registering it in [create_strategy] (MM_STRATEGY).
+
+use crate::clob::{OrderSide, OrderType};
+use crate::types::{
+    Config, EntrySide, OrderStrategy, ResolvedMarket, SidePolicy, TopOfBook, TopOfBookSide,
+};
+use rust_decimal::Decimal;
+use tracing::{debug, warn};
+
+/// What a strategy sees on each tick.
+#[allow(dead_code)] // not every field is read by the built-in strategy
+pub struct TickContext<'a> {
+    /// Engine configuration.
+    pub config: &'a Config,
+    /// Market being traded.
+    pub market: &'a ResolvedMarket,
+    /// Best bid / ask of both sides.
+    pub top: &'a TopOfBook,
+    /// Seconds until the market closes.
+    pub secs_to_close: u64,
+    /// Price increment of the market.
+    pub tick_size: Decimal,
+    /// Smallest order the market accepts, in shares.
+    pub min_order_size: Decimal,
+    /// Engine clock (ms since the unix epoch).
+    pub now_ms: u64,
+    /// Spot move since the interval opened (USD), when a spot feed is running (spot.rs).
+    pub spot_move: Option<Decimal>,
+}
+
+/// Order a strategy asks the runner to place.
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Buy `side` at `price`; the runner sizes it from size_shares, `size_available` and min size.
+    Buy {
+        /// Outcome to buy.
+        side: EntrySide,
+        /// Limit price.
+        price: Decimal,
+        /// Size offered at that price (caps the order).
+        size_available: Decimal,
+        /// How the order rests or fills.
+        order_type: OrderType,
+    },
+}
+
+/// A fill reported back to the strategy.
+#[allow(dead_code)] // not every field is read by the built-in strategy
+#[derive(Debug, Clone)]
+pub struct FillEvent {
+    /// Token that filled.
+    pub token_id: String,
+    /// Buy or sell.
+    pub side: OrderSide,
+    /// Outcome of the token.
+    pub entry_side: EntrySide,
+    /// Fill price.
+    pub price: Decimal,
+    /// Filled size in shares.
+    pub size: Decimal,
+    /// When the fill was seen (ms since the unix epoch).
+    pub timestamp_ms: u64,
+}
+
+/// Pluggable entry logic. Only `on_book` is required.
+pub trait TickStrategy: Send + Sync {
+    /// Name for logs (the MM_STRATEGY value that selects it).
+    fn name(&self) -> &'static str;
+
+    /// New market / interval resolved.
+    fn on_interval_start(&mut self, _market: &ResolvedMarket) {}
+
+    /// Book update while the runner may enter. Return zero or more actions.
+    fn on_book(&mut self, ctx: &TickContext) -> Vec<Action>;
+
+    /// An entry order filled (fully or partially).
+    fn on_fill(&mut self, _fill: &FillEvent) {}
+}
+
+/// Strategy by name (MM_STRATEGY). Unknown names fall back to the range sniper.
+pub fn create_strategy(name: &str) -> Box<dyn TickStrategy> {
+    match name.to_lowercase().as_str() {
+        "range_sniper" | "" => Box::new(RangeSniper),
+        "stop_entry" => Box::new(StopEntry::default()),
+        other => {
+            warn!(
+                "[Strategy] unknown strategy {:?}, using range_sniper",
+                other
+            );
+            Box::new(RangeSniper)
+        }
+    }
+}
+
+/// Default strategy: buy the side whose price is in [min_buy_price, max_buy_price], with the order
+/// type and limit price given by MM_ORDER_STRATEGY.
+pub struct RangeSniper;
+
+impl TickStrategy for RangeSniper {
+    fn name(&self) -> &'static str {
+        "range_sniper"
+    }
+
+    fn on_book(&mut self, ctx: &TickContext) -> Vec<Action> {
+        let config = ctx.config;
+        let tick_size = ctx.tick_size;
+        // GtcResting: trigger when best_bid touches range; place GTC limit at max_buy_price + 1 tick.
+        // FokCrossSpread: trigger when best_ask in range; place FOK at exact price if min==max else best_ask + 1 tick (all-or-nothing).
+        // Otherwise (FakCrossSpread etc): trigger when best_ask in range; place FAK at best_ask + 1 tick (clamped to range).
+        let order_type = match config.order_strategy {
+            OrderStrategy::GtcResting => {
+                return choose_side_by_bid(config, ctx.top, ctx.min_order_size)
+                    .map(|(side, _best_bid, size_available)| Action::Buy {
+                        side,
+                        price: round_to_tick(
+                            config.side(side).max_buy_price + tick_size,
+                            tick_size,
+                        ),
+                        size_available,
+                        order_type: OrderType::Gtc,
+                    })
+                    .into_iter()
+                    .collect();
+            }
+            OrderStrategy::FokCrossSpread => OrderType::Fok,
+            _ => OrderType::Fak,
+        };
+        choose_side(config, ctx.top, ctx.min_order_size, ctx.spot_move)
+            .map(|(side, best_ask, size_available)| {
+                let range = config.side(side);
+                let price = if range.min_buy_price == range.max_buy_price {
+                    round_to_tick(range.min_buy_price, tick_size)
+                } else {
+                    round_to_tick(
+                        (best_ask + tick_size)
+                            .max(range.min_buy_price)
+                            .min(range.max_buy_price),
+                        tick_size,
+                    )
+                    .max(best_ask)
+                };
+                Action::Buy {
+                    side,
+                    price,
+                    size_available,
+                    order_type,
+                }
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Stop-entry: arm a side while its best ask is below MM_STOP_ENTRY_TRIGGER and buy when the ask
+/// crosses up through it (breakout), instead of buying whatever is already inside the range.
+/// The fill price is capped at the side's max_buy_price; after a signal the side must re-arm.
+#[derive(Default)]
+pub struct StopEntry {
+    armed_up: bool,
+    armed_down: bool,
+}
+
+impl TickStrategy for StopEntry {
+    fn name(&self) -> &'static str {
+        "stop_entry"
+    }
+
+    fn on_interval_start(&mut self, _market: &ResolvedMarket) {
+        self.armed_up = false;
+        self.armed_down = false;
+    }
+
+    fn on_book(&mut self, ctx: &TickContext) -> Vec<Action> {
+        let config = ctx.config;
+        let trigger = config.stop_entry_trigger;
+        let order_type = match config.order_strategy {
+            OrderStrategy::FokCrossSpread => OrderType::Fok,
+            _ => OrderType::Fak,
+        };
+        let sides = [
+            (
+                EntrySide::Up,
+                config.allow_buy_up,
+                ctx.top.token_id_up.as_ref(),
+            ),
+            (
+                EntrySide::Down,
+                config.allow_buy_down,
+                ctx.top.token_id_down.as_ref(),
+            ),
+        ];
+        let mut actions = Vec::new();
+        for (side, allowed, book) in sides {
+            let Some((ask, ask_size)) =
+                book.and_then(|b| Some((b.best_ask?, b.best_ask_size.unwrap_or(Decimal::ZERO))))
+            else {
+                continue;
+            };
+            let armed = match side {
+                EntrySide::Up => &mut self.armed_up,
+                EntrySide::Down => &mut self.armed_down,
+            };
+            if ask < trigger {
+                *armed = true;
+                continue;
+            }
+            if !std::mem::take(armed) || !allowed {
+                continue;
+            }
+            let max_price = config.side(side).max_buy_price;
+            if ask > max_price || ask_size < ctx.min_order_size {
+                debug!(
+                    "[Strategy] stop_entry {:?} crossed {} but ask {} (size {}) is above max {} or too thin",
+                    side, trigger, ask, ask_size, max_price
+                );
+                continue;
+            }
+            actions.push(Action::Buy {
+                side,
+                price: round_to_tick((ask + ctx.tick_size).min(max_price), ctx.tick_size).max(ask),
+                size_available: ask_size,
+                order_type,
+            });
+        }
+        // One entry at a time: prefer the higher-priced side, as the range sniper does.
+        actions.sort_by(|a, b| {
+            let Action::Buy { price: pa, .. } = a;
+            let Action::Buy { price: pb, .. } = b;
+            pb.cmp(pa)
+        });
+        actions.truncate(1);
+        actions
+    }
+}
+
+/// Round a price to the nearest multiple of the market tick size.
+pub fn round_to_tick(price: Decimal, tick_size: Decimal) -> Decimal {
+    let ticks = (price / tick_size).round();
+    (ticks * tick_size).round_dp(tick_size.normalize().scale())
+}
+
+/// Top-level bid share: bid size / (bid size + ask size); None on an empty top level.
+fn top_imbalance(book: &TopOfBookSide) -> Option<Decimal> {
+    let bid = book.best_bid_size.unwrap_or(Decimal::ZERO);
+    let total = bid + book.best_ask_size.unwrap_or(Decimal::ZERO);
+    (total > Decimal::ZERO).then(|| bid / total)
+}
+
+/// Choose entry side: Up or Down with best ask in its [min_buy_price, max_buy_price] and min
+/// liquidity. When both qualify, MM_SIDE_POLICY picks: higher ask, more bid size, stronger
+/// top-level bid imbalance, or the side the spot has moved toward (`spot_move`, at least
+/// MM_MIN_BTC_PRICE_DIFF_USD; no entry without a spot signal).
+pub fn choose_side(
+    config: &Config,
+    book: &TopOfBook,
+    min_order_size: Decimal,
+    spot_move: Option<Decimal>,
+) -> Option<(EntrySide, Decimal, Decimal)> {
+    let up = book.token_id_up.as_ref()?;
+    let down = book.token_id_down.as_ref()?;
+    let up_ask = config.allow_buy_up.then_some(up.best_ask).flatten()?;
+    let down_ask = config.allow_buy_down.then_some(down.best_ask).flatten()?;
+    let up_size = up.best_ask_size.unwrap_or(Decimal::ZERO);
+    let down_size = down.best_ask_size.unwrap_or(Decimal::ZERO);
+
+    let mut candidates: Vec<(EntrySide, Decimal, Decimal)> = Vec::new();
+    if config.up.in_range(up_ask) && up_size >= min_order_size {
+        candidates.push((EntrySide::Up, up_ask, up_size));
+    }
+    if config.down.in_range(down_ask) && down_size >= min_order_size {
+        candidates.push((EntrySide::Down, down_ask, down_size));
+    }
+    let book_of = |side: EntrySide| match side {
+        EntrySide::Up => up,
+        EntrySide::Down => down,
+    };
+    match config.side_policy {
+        SidePolicy::HighestAsk => candidates.sort_by_key(|c| std::cmp::Reverse(c.1)),
+        SidePolicy::MostBidDepth => candidates.sort_by_key(|c| {
+            std::cmp::Reverse(book_of(c.0).best_bid_size.unwrap_or(Decimal::ZERO))
+        }),
+        SidePolicy::Imbalance => {
+            candidates.sort_by_key(|c| std::cmp::Reverse(top_imbalance(book_of(c.0))))
+        }
+        SidePolicy::SpotDirection => {
+            let toward = match spot_move {
+                Some(m) if m.abs() >= config.min_btc_price_diff_usd && !m.is_zero() => {
+                    if m > Decimal::ZERO {
+                        EntrySide::Up
+                    } else {
+                        EntrySide::Down
+                    }
+                }
+                _ => return None,
+            };
+            candidates.retain(|c| c.0 == toward);
+        }
+    }
+    candidates.into_iter().next()
+}
+
+/// Choose entry side when triggering on best bid: side with best_bid in its [min_buy_price, max_buy_price] and enough ask liquidity.
+/// Used for GTC limit entry: when best bid touches range, place limit at max_buy_price + 1 tick.
+pub fn choose_side_by_bid(
+    config: &Config,
+    book: &TopOfBook,
+    min_order_size: Decimal,
+) -> Option<(EntrySide, Decimal, Decimal)> {
+    let up = book.token_id_up.as_ref()?;
+    let down = book.token_id_down.as_ref()?;
+    let up_bid = config.allow_buy_up.then_some(up.best_bid).flatten()?;
+    let down_bid = config.allow_buy_down.then_some(down.best_bid).flatten()?;
+    let up_size = up.best_ask_size.unwrap_or(Decimal::ZERO);
+    let down_size = down.best_ask_size.unwrap_or(Decimal::ZERO);
+
+    let mut candidates: Vec<(EntrySide, Decimal, Decimal)> = Vec::new();
+    if config.up.in_range(up_bid) && up_size >= min_order_size {
+        candidates.push((EntrySide::Up, up_bid, up_size));
+    }
+    if config.down.in_range(down_bid) && down_size >= min_order_size {
+        candidates.push((EntrySide::Down, down_bid, down_size));
+    }
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.1)); // higher best_bid first
+    candidates.into_iter().next()
+}
@@ -0,0 +1,95 @@
+//! Profit sweep: move USDC.e above a working float from the Polymarket Safe to a cold address,
+//! as a 1-of-1 Safe transaction signed by the bot's EOA (which pays the gas in POL).
+
+use crate::chain::{
+    erc20_balance, exec_safe_transaction, rpc_urls_from_env, usdc_from_units, usdc_to_units, Erc20,
+    USDC_E_POLYGON,
+};
+use anyhow::{bail, Context, Result};
+use ethers::providers::{Http, Provider};
+use ethers::signers::LocalWallet;
+use ethers::types::{Address, Bytes, H160};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use tracing::info;
+
+/// Sweep settings from env (MM_SWEEP_*, POLYGON_RPC_URL).
+#[derive(Debug, Clone)]
+pub struct SweepConfig {
+    /// POLYGON_RPC_URL entries, tried in order.
+    pub rpc_urls: Vec<String>,
+    pub cold_address: H160,
+    /// Sweep only when the Safe balance is above this (USDC).
+    pub threshold: Decimal,
+    /// USDC left in the Safe after a sweep; at most the threshold.
+    pub float: Decimal,
+}
+
+impl SweepConfig {
+    pub fn from_env() -> Result<Self> {
+        let rpc_urls = rpc_urls_from_env();
+        let cold =
+            std::env::var("MM_SWEEP_COLD_ADDRESS").context("MM_SWEEP_COLD_ADDRESS required")?;
+        let cold_address = cold
+            .trim()
+            .parse::<H160>()
+            .context("Invalid MM_SWEEP_COLD_ADDRESS")?;
+        let usdc = |key: &str, default: Decimal| -> Result<Decimal> {
+            match std::env::var(key) {
+                Ok(v) if !v.trim().is_empty() => v
+                    .trim()
+                    .parse::<Decimal>()
+                    .with_context(|| format!("Invalid {}", key)),
+                _ => Ok(default),
+            }
+        };
+        let threshold = usdc("MM_SWEEP_THRESHOLD_USDC", Decimal::from(500))?;
+        let float = usdc("MM_SWEEP_FLOAT_USDC", Decimal::from(200))?;
+        if float < Decimal::ZERO || float > threshold {
+            bail!(
+                "MM_SWEEP_FLOAT_USDC ({}) must be between 0 and MM_SWEEP_THRESHOLD_USDC ({})",
+                float,
+                threshold
+            );
+        }
+        Ok(Self {
+            rpc_urls,
+            cold_address,
+            threshold,
+            float,
+        })
+    }
+
+    /// USDC to sweep for a Safe balance: everything above the float, once the balance exceeds
+    /// the threshold.
+    pub fn sweep_amount(&self, balance: Decimal) -> Option<Decimal> {
+        (balance > self.threshold).then(|| balance - self.float)
+    }
+}
+
+/// USDC.e balance of `owner` (USDC).
+pub async fn usdc_balance(provider: Arc<Provider<Http>>, owner: H160) -> Result<Decimal> {
+    let units = erc20_balance(provider, USDC_E_POLYGON.parse::<Address>()?, owner).await?;
+    Ok(usdc_from_units(units))
+}
+
+/// Transfer `amount` USDC.e from `safe` to `to` via execTransaction, signed by `wallet` as the
+/// Safe's only required owner. Returns the transaction hash once mined.
+pub async fn sweep_from_safe(
+    provider: Provider<Http>,
+    wallet: LocalWallet,
+    safe: H160,
+    to: H160,
+    amount: Decimal,
+) -> Result<String> {
+    let usdc_address = USDC_E_POLYGON.parse::<Address>()?;
+    let data: Bytes = Erc20::new(usdc_address, Arc::new(provider.clone()))
+        .transfer(to, usdc_to_units(amount)?)
+        .calldata()
+        .context("encode transfer")?;
+    info!(
+        "[Sweep] Safe {:?}: transfer {} USDC to {:?}",
+        safe, amount, to
+    );
+    exec_safe_transaction(provider, wallet, safe, usdc_address, data).await
+}
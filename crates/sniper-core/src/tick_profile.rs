@@ -0,0 +1,115 @@
+//! Timing of the runner loop: each tick is split into sync (market refresh, book read, order
+//! polls), evaluate (bookkeeping and trigger checks) and execute (TP/SL sells and entries). Ticks
+//! slower than MM_SLOW_TICK_MS are logged with that breakdown; every tick lands in a histogram
+//! that the runner writes to the session log once per interval.
+
+use std::time::{Duration, Instant};
+
+/// Upper bounds (ms) of the histogram buckets; one more bucket counts everything slower.
+pub const TICK_BUCKETS_MS: [u64; 9] = [5, 10, 25, 50, 100, 250, 500, 1000, 2500];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickPhase {
+    Sync,
+    Evaluate,
+    Execute,
+}
+
+impl TickPhase {
+    fn index(self) -> usize {
+        match self {
+            TickPhase::Sync => 0,
+            TickPhase::Evaluate => 1,
+            TickPhase::Execute => 2,
+        }
+    }
+}
+
+/// A finished tick: total and per-phase time.
+#[derive(Debug, Clone, Copy)]
+pub struct TickTiming {
+    pub total: Duration,
+    pub sync: Duration,
+    pub evaluate: Duration,
+    pub execute: Duration,
+}
+
+/// Tick counts since the last [TickProfiler::take_stats].
+#[derive(Debug, Clone, Default)]
+pub struct TickStats {
+    pub ticks: u64,
+    pub slow_ticks: u64,
+    pub max_ms: u64,
+    pub total_ms: u64,
+    /// One count per [TICK_BUCKETS_MS] bound plus the overflow bucket.
+    pub buckets: [u64; TICK_BUCKETS_MS.len() + 1],
+}
+
+pub struct TickProfiler {
+    slow_tick_ms: u64,
+    /// Start of the open tick and of its current phase; None between ticks.
+    open: Option<(Instant, Instant, TickPhase)>,
+    spent: [Duration; 3],
+    stats: TickStats,
+}
+
+impl TickProfiler {
+    /// `slow_tick_ms` = 0 keeps the histogram but never reports a tick as slow.
+    pub fn new(slow_tick_ms: u64) -> Self {
+        Self {
+            slow_tick_ms,
+            open: None,
+            spent: [Duration::ZERO; 3],
+            stats: TickStats::default(),
+        }
+    }
+
+    /// Start a tick in the sync phase.
+    pub fn begin(&mut self) {
+        let now = Instant::now();
+        self.open = Some((now, now, TickPhase::Sync));
+        self.spent = [Duration::ZERO; 3];
+    }
+
+    /// Charge the time since the last mark to the current phase and move on to `phase`.
+    pub fn phase(&mut self, phase: TickPhase) {
+        if let Some((start, phase_start, current)) = self.open {
+            let now = Instant::now();
+            self.spent[current.index()] += now.duration_since(phase_start);
+            self.open = Some((start, now, phase));
+        }
+    }
+
+    /// Close the open tick (call before the loop sleeps). Some(timing) when it was slower than
+    /// MM_SLOW_TICK_MS.
+    pub fn end(&mut self) -> Option<TickTiming> {
+        let (start, phase_start, current) = self.open.take()?;
+        let now = Instant::now();
+        self.spent[current.index()] += now.duration_since(phase_start);
+        let total = now.duration_since(start);
+        let ms = total.as_millis() as u64;
+        let bucket = TICK_BUCKETS_MS
+            .iter()
+            .position(|&b| ms <= b)
+            .unwrap_or(TICK_BUCKETS_MS.len());
+        self.stats.ticks += 1;
+        self.stats.total_ms += ms;
+        self.stats.max_ms = self.stats.max_ms.max(ms);
+        self.stats.buckets[bucket] += 1;
+        if self.slow_tick_ms == 0 || ms < self.slow_tick_ms {
+            return None;
+        }
+        self.stats.slow_ticks += 1;
+        Some(TickTiming {
+            total,
+            sync: self.spent[TickPhase::Sync.index()],
+            evaluate: self.spent[TickPhase::Evaluate.index()],
+            execute: self.spent[TickPhase::Execute.index()],
+        })
+    }
+
+    /// The counts so far, resetting them.
+    pub fn take_stats(&mut self) -> TickStats {
+        std::mem::take(&mut self.stats)
+    }
+}
@@ -0,0 +1,67 @@
+//! Rolling trade tape per token, fed by the CLOB WS `last_trade_price` events.
+//!
+//! Gives the strategy recent traded volume and the last trade price, e.g. to require some volume
+//! before entering or to sanity-check a crossed book snapshot against where trades actually print.
+
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+
+/// Trades older than this are dropped (longest window the strategy can ask for).
+const TAPE_RETENTION_MS: u64 = 10 * 60 * 1000;
+
+#[derive(Debug, Clone, Copy)]
+struct Trade {
+    timestamp_ms: u64,
+    price: Decimal,
+    size: Decimal,
+}
+
+/// Stats for one token over a window.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TradeStats {
+    /// Shares traded in the window.
+    pub volume: Decimal,
+    pub trades: u32,
+    /// Last trade price (any age).
+    pub last_price: Option<Decimal>,
+    pub last_trade_ms: Option<u64>,
+}
+
+/// Recent trades per token ID.
+#[derive(Debug, Default)]
+pub struct TradeTape {
+    trades: HashMap<String, VecDeque<Trade>>,
+}
+
+impl TradeTape {
+    pub fn record(&mut self, token_id: &str, timestamp_ms: u64, price: Decimal, size: Decimal) {
+        let q = self.trades.entry(token_id.to_string()).or_default();
+        q.push_back(Trade {
+            timestamp_ms,
+            price,
+            size,
+        });
+        let cutoff = timestamp_ms.saturating_sub(TAPE_RETENTION_MS);
+        while q.front().is_some_and(|t| t.timestamp_ms < cutoff) {
+            q.pop_front();
+        }
+    }
+
+    /// Volume and trade count since `now_ms - window_ms`, plus the last trade.
+    pub fn stats(&self, token_id: &str, now_ms: u64, window_ms: u64) -> TradeStats {
+        let Some(q) = self.trades.get(token_id) else {
+            return TradeStats::default();
+        };
+        let cutoff = now_ms.saturating_sub(window_ms);
+        let mut stats = TradeStats {
+            last_price: q.back().map(|t| t.price),
+            last_trade_ms: q.back().map(|t| t.timestamp_ms),
+            ..TradeStats::default()
+        };
+        for t in q.iter().rev().take_while(|t| t.timestamp_ms >= cutoff) {
+            stats.volume += t.size;
+            stats.trades += 1;
+        }
+        stats
+    }
+}
@@ -0,0 +1,184 @@
+//! The account's executions from the authenticated CLOB trades history (GET /data/trades), and
+//! the realized PnL rebuilt from them. At shutdown the runner compares that with the PnL the
+//! session log recorded for TP/SL sells (its own fill bookkeeping) and reports the difference.
+
+use crate::clob::{estimated_fee_usd, OrderSide};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// One of our fills: the taker side of a trade, or our maker order inside it.
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub trade_id: String,
+    pub asset_id: String,
+    pub side: OrderSide,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub fee_rate_bps: u64,
+    pub match_time_unix: u64,
+}
+
+fn field_decimal(v: &serde_json::Value, key: &str) -> Option<Decimal> {
+    let v = v.get(key)?;
+    v.as_str()
+        .map(String::from)
+        .or_else(|| v.as_f64().map(|f| f.to_string()))
+        .and_then(|s| Decimal::from_str(s.trim()).ok())
+}
+
+fn field_u64(v: &serde_json::Value, key: &str) -> Option<u64> {
+    let v = v.get(key)?;
+    v.as_u64()
+        .or_else(|| v.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+fn field_side(v: &serde_json::Value) -> Option<OrderSide> {
+    match v.get("side")?.as_str()?.to_uppercase().as_str() {
+        "BUY" => Some(OrderSide::Buy),
+        "SELL" => Some(OrderSide::Sell),
+        _ => None,
+    }
+}
+
+/// Our fills in one /data/trades page and its `next_cursor` (None on the last page). `addresses`
+/// are our maker addresses (lowercase) for picking our orders out of `maker_orders`. Failed
+/// trades are skipped.
+pub fn parse_trades_page(
+    json: &serde_json::Value,
+    addresses: &[String],
+) -> (Vec<Fill>, Option<String>) {
+    let mut fills = Vec::new();
+    for t in json
+        .get("data")
+        .and_then(|d| d.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let status = t.get("status").and_then(|s| s.as_str()).unwrap_or("");
+        if status.eq_ignore_ascii_case("FAILED") {
+            continue;
+        }
+        let trade_id = t
+            .get("id")
+            .and_then(|s| s.as_str())
+            .unwrap_or("")
+            .to_string();
+        let match_time_unix = field_u64(t, "match_time").unwrap_or(0);
+        let fee_rate_bps = field_u64(t, "fee_rate_bps").unwrap_or(0);
+        let is_taker = t
+            .get("trader_side")
+            .and_then(|s| s.as_str())
+            .is_none_or(|s| s.eq_ignore_ascii_case("TAKER"));
+        if is_taker {
+            if let (Some(asset_id), Some(side), Some(price), Some(size)) = (
+                t.get("asset_id").and_then(|s| s.as_str()),
+                field_side(t),
+                field_decimal(t, "price"),
+                field_decimal(t, "size"),
+            ) {
+                fills.push(Fill {
+                    trade_id,
+                    asset_id: asset_id.to_string(),
+                    side,
+                    price,
+                    size,
+                    fee_rate_bps,
+                    match_time_unix,
+                });
+            }
+            continue;
+        }
+        for m in t
+            .get("maker_orders")
+            .and_then(|m| m.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let ours = m
+                .get("maker_address")
+                .and_then(|a| a.as_str())
+                .is_some_and(|a| addresses.contains(&a.to_lowercase()));
+            if !ours {
+                continue;
+            }
+            if let (Some(asset_id), Some(side), Some(price), Some(size)) = (
+                m.get("asset_id").and_then(|s| s.as_str()),
+                field_side(m),
+                field_decimal(m, "price"),
+                field_decimal(m, "matched_amount"),
+            ) {
+                fills.push(Fill {
+                    trade_id: trade_id.clone(),
+                    asset_id: asset_id.to_string(),
+                    side,
+                    price,
+                    size,
+                    fee_rate_bps: field_u64(m, "fee_rate_bps").unwrap_or(fee_rate_bps),
+                    match_time_unix,
+                });
+            }
+        }
+    }
+    let next = json
+        .get("next_cursor")
+        .and_then(|c| c.as_str())
+        .filter(|c| !c.is_empty() && *c != END_CURSOR)
+        .map(String::from);
+    (fills, next)
+}
+
+/// `next_cursor` of the last page.
+pub const END_CURSOR: &str = "LTE=";
+
+/// Realized PnL rebuilt from fills.
+#[derive(Debug, Clone, Default)]
+pub struct TradesPnl {
+    pub buys: u32,
+    pub sells: u32,
+    /// Sells against the average cost of the earlier buys of the same token.
+    pub realized: Decimal,
+    /// Estimated fees over every fill (same curve as the session log).
+    pub fees: Decimal,
+    /// Shares sold without a buy in range (bought before the session); not in `realized`.
+    pub unmatched_sold: Decimal,
+    /// Shares still held per token (redeemed at resolution, which is not a trade).
+    pub held: HashMap<String, Decimal>,
+}
+
+/// Average-cost realized PnL over `fills`, in match time order.
+pub fn realized_pnl(fills: &[Fill]) -> TradesPnl {
+    let mut fills: Vec<&Fill> = fills.iter().collect();
+    fills.sort_by_key(|f| f.match_time_unix);
+    let mut out = TradesPnl::default();
+    // (shares, cost) per token.
+    let mut open: HashMap<&str, (Decimal, Decimal)> = HashMap::new();
+    for f in fills {
+        out.fees += estimated_fee_usd(f.fee_rate_bps, f.price, f.size);
+        let (shares, cost) = open.entry(f.asset_id.as_str()).or_default();
+        match f.side {
+            OrderSide::Buy => {
+                out.buys += 1;
+                *shares += f.size;
+                *cost += f.price * f.size;
+            }
+            OrderSide::Sell => {
+                out.sells += 1;
+                let matched = f.size.min(*shares);
+                if matched > Decimal::ZERO {
+                    let avg = *cost / *shares;
+                    out.realized += (f.price - avg) * matched;
+                    *cost -= avg * matched;
+                    *shares -= matched;
+                }
+                out.unmatched_sold += f.size - matched;
+            }
+        }
+    }
+    out.held = open
+        .into_iter()
+        .filter(|(_, (shares, _))| *shares > Decimal::ZERO)
+        .map(|(id, (shares, _))| (id.to_string(), shares))
+        .collect();
+    out
+}
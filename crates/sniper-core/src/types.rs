@@ -0,0 +1,661 @@
+//! Types for Interval Sniper: config, market, order book, runner state.
+
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+/// Market asset: BTC or SOL 5m interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntervalMarketAsset {
+    #[default]
+    Btc5m,
+    Sol5m,
+}
+
+impl std::str::FromStr for IntervalMarketAsset {
+    type Err = std::convert::Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "sol_5m" => IntervalMarketAsset::Sol5m,
+            _ => IntervalMarketAsset::Btc5m,
+        })
+    }
+}
+
+/// Underlying of the hourly "above $X" markets (MM_TARGET_HOURLY_ABOVE).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HourlyAsset {
+    Eth,
+    Sol,
+}
+
+impl HourlyAsset {
+    /// `eth` / `sol` (or the full name); None for anything else.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "eth" | "ethereum" => Some(HourlyAsset::Eth),
+            "sol" | "solana" => Some(HourlyAsset::Sol),
+            _ => None,
+        }
+    }
+}
+
+/// Which 5-min window an interval target trades (MM_TARGET_WINDOW).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TargetWindow {
+    /// The window in progress, left at its close.
+    #[default]
+    Active,
+    /// The window that closed at the last boundary, traded until the next boundary while its
+    /// market is still open (not yet resolved).
+    Closing,
+}
+
+/// What the sniper trades: the rolling 5-min interval market, or one fixed market.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarketTarget {
+    /// BTC/SOL 5-min interval (the active or the closing window); the slug rolls over every 300s.
+    Interval(IntervalMarketAsset, TargetWindow),
+    /// The 5-min interval starting at this unix time (one MM_DUAL_WINDOW engine). The engine
+    /// stops when it closes.
+    IntervalAt(IntervalMarketAsset, u64),
+    /// Any market by Gamma slug (MM_TARGET_SLUG). The bot stops when it closes.
+    Slug(String),
+    /// Any market by condition ID (MM_TARGET_CONDITION_ID). The bot stops when it closes.
+    ConditionId(String),
+    /// All markets of a Gamma event (MM_TARGET_EVENT_SLUG); trades the outcome with the best entry.
+    Event(String),
+    /// The hourly "above $X" event of the asset (MM_TARGET_HOURLY_ABOVE): one market per strike,
+    /// the event slug rolls over every hour. Entries need the spot on the bought side of the strike.
+    HourlyAbove(HourlyAsset),
+}
+
+/// Order strategy: how aggressive the buy order is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStrategy {
+    GtcResting,
+    FokSamePrice,
+    FakSamePrice,
+    CrossSpread,
+    FokCrossSpread,
+    FakCrossSpread,
+    MarketFok,
+}
+
+/// Which side to enter when both are in range (MM_SIDE_POLICY), see strategy::choose_side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SidePolicy {
+    /// Higher best ask (the favourite).
+    HighestAsk,
+    /// More size at the best bid.
+    MostBidDepth,
+    /// Larger best-bid share of the top level (bid size / (bid size + ask size)).
+    Imbalance,
+    /// The side the spot price has moved toward since the interval opened (spot.rs).
+    SpotDirection,
+}
+
+/// Price the stop loss compares with its trigger (MM_TRIGGER_PRICE_SOURCE).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerPriceSource {
+    Bid,
+    Ask,
+    /// (best bid + best ask) / 2; falls back to the bid on a one-sided book.
+    Mid,
+    /// Last trade on the WS tape within MM_TRADE_WINDOW_SECONDS; falls back to the bid.
+    LastTrade,
+}
+
+/// Language of the operator-facing log lines (MM_LOG_LANG), see messages.rs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogLang {
+    #[default]
+    En,
+    Es,
+}
+
+/// How the per-interval entry size is chosen (MM_SIZING), see sizing.rs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizingMode {
+    /// Always MM_SIZE_SHARES.
+    Fixed,
+    /// A fixed fraction of the USDC bankroll.
+    Fraction,
+    /// Fractional Kelly from the session's realized win rate and payoffs.
+    Kelly,
+}
+
+/// When a second (or later) entry is allowed in the same interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReEntryPolicy {
+    /// Only after the previous position was closed by stop loss (not TP).
+    AfterStopLoss,
+    /// After any close (TP, SL).
+    Always,
+}
+
+/// Which exit may cancel the other's resting sell to free the balance it needs (MM_AUTO_UNLOCK).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnlockPolicy {
+    /// A take profit cancels a resting stop loss; a stop loss leaves a resting take profit alone
+    /// and sells only the free balance.
+    TpPriority,
+    /// A stop loss cancels a resting take profit.
+    SlPriority,
+}
+
+/// Time-in-force for sell orders (TP/SL).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SellOrderTimeInForce {
+    Gtc,
+    /// Resting until `expiration_unix` (a GTC sell bounded by MM_SELL_GTD_MARGIN_SECS).
+    Gtd {
+        expiration_unix: u64,
+    },
+    Fok,
+    Fak,
+}
+
+/// Main config: same semantics as TypeScript Interval Sniper.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub interval_market: IntervalMarketAsset,
+    /// Market to trade; defaults to the interval market unless MM_TARGET_* is set.
+    pub market_target: MarketTarget,
+    pub market_slug: String,
+    /// MM_DUAL_WINDOW: run one engine per 5-min interval, the next one started
+    /// dual_window_lead_secs (MM_DUAL_WINDOW_LEAD_SECS) before its interval opens.
+    pub dual_window: bool,
+    pub dual_window_lead_secs: u64,
+    pub gamma_base_url: String,
+    /// POLYMARKET_DATA_API_URL: data API (wallet positions).
+    pub data_api_url: String,
+    /// MM_POSITIONS_CHECK_SECS: compare the wallet's holdings of the current market (data API)
+    /// with the inventory this often (0 = off; live only).
+    pub positions_check_secs: u64,
+    /// MM_BALANCE_DRIFT_SECS: compare the balance-allowance endpoint (shares of the current
+    /// market, USDC) with the inventory this often and warn on drift (0 = off).
+    pub balance_drift_secs: u64,
+    /// MM_ORDER_SYNC_MIN_MS: trades-history sync interval while an open order is within a tick of
+    /// the touch that fills it.
+    pub order_sync_min_ms: u64,
+    /// MM_ORDER_SYNC_MAX_MS: sync interval for open orders far from the touch (at least the min).
+    pub order_sync_max_ms: u64,
+    /// MM_BALANCE_DRIFT_SHARES: tolerated share difference per token.
+    pub balance_drift_shares: Decimal,
+    /// MM_BALANCE_DRIFT_USDC: tolerated USDC difference since the interval started (fees land
+    /// here).
+    pub balance_drift_usdc: Decimal,
+    pub seconds_before_close: u32,
+    pub size_shares: Decimal,
+    /// MM_AUTOSIZE_TO_BALANCE: at startup, lower size_shares to what the USDC balance can buy at
+    /// the max buy price (otherwise only warn).
+    pub autosize_to_balance: bool,
+    /// MM_SIZING: fixed (MM_SIZE_SHARES), fraction or kelly (resized from the bankroll at each
+    /// interval switch).
+    pub sizing: SizingMode,
+    /// MM_SIZING_FRACTION: bankroll fraction staked per interval (fraction mode, and kelly until
+    /// MM_KELLY_MIN_TRADES closes).
+    pub sizing_fraction: Decimal,
+    /// MM_KELLY_MULTIPLIER: multiplier on the full Kelly fraction (0.5 = half Kelly).
+    pub kelly_multiplier: Decimal,
+    /// MM_KELLY_MIN_TRADES: closed trades needed before the Kelly estimate is used.
+    pub kelly_min_trades: u32,
+    /// MM_SIZING_MAX_FRACTION: most of the bankroll one interval can stake.
+    pub sizing_max_fraction: Decimal,
+    /// MM_SIZING_MAX_SHARES: share cap on a sized entry (0 = none).
+    pub sizing_max_shares: Decimal,
+    /// Buy range and TP/SL for Up (MM_UP_*, defaults to the shared MM_* values).
+    pub up: SideConfig,
+    /// Buy range and TP/SL for Down (MM_DOWN_*, defaults to the shared MM_* values).
+    pub down: SideConfig,
+    pub allow_buy_up: bool,
+    pub allow_buy_down: bool,
+    /// MM_MIN_BTC_PRICE_DIFF_USD: spot_direction policy needs the spot to have moved at least this
+    /// far from the interval open.
+    pub min_btc_price_diff_usd: Decimal,
+    /// MM_SIDE_POLICY: side to enter when both are in range.
+    pub side_policy: SidePolicy,
+    /// MM_SPOT_URL: Binance-compatible REST base for the spot_direction policy.
+    pub spot_base_url: String,
+    /// MM_SPOT_POLL_MS: spot poll interval.
+    pub spot_poll_ms: u64,
+    /// MM_STRIKE_MIN_DISTANCE_BPS: hourly "above" markets only enter Yes with the spot at least
+    /// this far above the strike (No: below), in basis points of the strike.
+    pub strike_min_distance_bps: u64,
+    pub dry_run: bool,
+    /// MM_OBSERVE_ONLY: characterize a market before funding it. Forces dry_run (no CLOB client
+    /// with credentials is ever created, whatever MM_DRY_RUN says), session_log_enabled and
+    /// order_audit, so the would-be orders and closes land in MM_SESSION_LOG_DIR.
+    pub observe_only: bool,
+    pub order_strategy: OrderStrategy,
+    /// Entry strategy name (MM_STRATEGY), see strategy::create_strategy.
+    pub strategy: String,
+    /// stop_entry strategy: buy when the best ask crosses up through this price.
+    pub stop_entry_trigger: Decimal,
+    pub enable_auto_sell: bool,
+    pub auto_sell_at_max_price: bool,
+    pub auto_sell_quantity_percent: u8,
+    pub take_profit_time_in_force: SellOrderTimeInForce,
+    /// MM_SELL_GTD_MARGIN_SECS: resting sells are sent GTD, expiring this long before the market
+    /// closes (0 = plain GTC).
+    pub sell_gtd_margin_secs: u64,
+    pub enable_stop_loss: bool,
+    pub stop_loss_quantity_percent: u8,
+    /// MM_SOFT_STOP_PRICE: soft stop; when best_bid drops to this (above the SL trigger), sell
+    /// MM_SOFT_STOP_PERCENT of the position and keep the rest for TP or the hard SL (0 = off).
+    pub soft_stop_price: Decimal,
+    pub soft_stop_percent: u8,
+    /// MM_BREAKEVEN_TRIGGER: once best_bid exceeds entry + this, the SL moves up to one tick over
+    /// the fee break-even exit price (0 = off).
+    pub breakeven_trigger: Decimal,
+    /// MM_TRIGGER_PRICE_SOURCE: price checked against the SL trigger (the sell still prices off the
+    /// best bid).
+    pub trigger_price_source: TriggerPriceSource,
+    /// MM_SL_CONFIRM_MS: the SL condition must hold this long across ticks before the stop fires
+    /// (0 = first tick).
+    pub sl_confirm_ms: u64,
+    /// MM_SL_HEDGE_EXIT: when the SL sell has not filled for MM_SL_HEDGE_AFTER_MS (or the book has
+    /// no bid), buy the same size of the opposite token instead and hold the pair to resolution.
+    pub sl_hedge_exit: bool,
+    pub sl_hedge_after_ms: u64,
+    /// MM_SL_HEDGE_SLIPPAGE: the opposite token is bought up to 1 − SL trigger + this.
+    pub sl_hedge_slippage: Decimal,
+    /// MM_SL_HEDGE_BID: when our side's book is empty and the opposite ask is out of reach, rest a
+    /// GTC bid for the opposite token at 1 − our last bid and hold whatever fills as a pair.
+    pub sl_hedge_bid: bool,
+    /// MM_AUTO_UNLOCK: which resting sells a TP / SL may cancel when they hold the shares it sells.
+    pub auto_unlock: UnlockPolicy,
+    pub loop_ms: u64,
+    /// MM_SLOW_TICK_MS: warn with a per-phase breakdown when a loop tick takes at least this long (0 = off).
+    pub slow_tick_ms: u64,
+    /// MM_DECISION_TRACE: keep the last this many entry evaluations in memory and write them out
+    /// on error or on demand (see decision_trace.rs; 0 = off).
+    pub decision_trace: usize,
+    /// MM_LOG_LANG: language of the operator-facing log lines (fills, closes, switches).
+    pub log_lang: LogLang,
+    pub cooldown_between_orders_ms: u64,
+    /// Skip entries once the interval's best_bid range (max - min, either side) exceeds this (0 = off).
+    pub max_interval_range: Decimal,
+    /// MM_BLACKOUT_FILE: JSON or ICS of economic-event windows in which no position is opened.
+    pub blackout_file: Option<String>,
+    /// Random entry size change of up to ± this percent (0 = off); still capped at the book size
+    /// and floored at the min order size.
+    pub size_jitter_percent: Decimal,
+    /// Random delay of 0..=this ms before each entry order (0 = off).
+    pub entry_jitter_ms: u64,
+    /// MM_BUY_ORDER_MAX_AGE_MS: a GTC buy still resting this long after submission is canceled
+    /// (0 = never).
+    pub buy_order_max_age_ms: u64,
+    /// MM_BUY_ORDER_REEVALUATE: after such a cancel, shrink the position to the shares actually
+    /// held and free the entry slot when none were bought.
+    pub buy_order_reevaluate: bool,
+    /// MM_MAX_CHASE_TICKS: a GTC entry buy the ask has moved more than one tick above is
+    /// canceled and replaced at the new ask, up to this many times (0 = off).
+    pub max_chase_ticks: u32,
+    /// MM_MAX_CHASE_PRICE: never chase the ask above this price.
+    pub max_chase_price: Decimal,
+    /// Max entries per interval (MM_MAX_TRADES_PER_INTERVAL); 1 = no re-entry.
+    pub max_trades_per_interval: u32,
+    /// Re-entry rule for entries after the first (MM_REENTRY).
+    pub re_entry: ReEntryPolicy,
+    pub no_window_all_intervals: bool,
+    pub min_seconds_after_market_open: u32,
+    pub min_seconds_after_buy_before_auto_sell: u32,
+    pub take_profit_price_margin: Decimal,
+    /// MM_TP_SCHEDULE: TP caps that tighten toward close, sorted by secs_to_close descending.
+    pub tp_schedule: Vec<TpSchedulePoint>,
+    /// MM_BUY_RANGE_SCHEDULE: entry ranges by seconds since the interval opened, sorted
+    /// ascending. Before the first point the per-side ranges apply.
+    pub buy_range_schedule: Vec<BuyRangePoint>,
+    /// MM_TP_PASSIVE_MS: first rest a post-only GTC ask at the TP price for this long, then sell
+    /// at the bid as usual (0 = off).
+    pub take_profit_passive_ms: u64,
+    /// Resolution-hold: within hold_to_resolution_secs of close, if best_bid >= this, skip TP and hold
+    /// the position to settlement (0 = off).
+    pub hold_to_resolution_above: Decimal,
+    /// Window before close (seconds) in which resolution-hold may kick in.
+    pub hold_to_resolution_secs: u32,
+    /// MM_CARRYOVER_WAIT_SECS: when the run ends, keep polling Gamma this long for positions
+    /// carried past their market's close before settling them at the payout inferred from the
+    /// last best bid.
+    pub carryover_wait_secs: u64,
+    /// MM_AUTO_REDEEM: redeem a carried position's shares on chain once its market resolves (live
+    /// only; EOA or Polymarket Safe funder).
+    pub auto_redeem: bool,
+    /// If true, append session events to a JSONL file in session_log_dir (close, interval_summary, session_summary).
+    pub session_log_enabled: bool,
+    /// Directory for session log files (e.g. "logs"). Created if missing.
+    pub session_log_dir: String,
+    /// MM_SESSION_DB: SQLite database the session log and order transitions also go to (`sqlite`
+    /// feature; None = JSONL only).
+    pub session_db: Option<String>,
+    /// MM_ALLOW_MULTIPLE_INSTANCES: trade even when another live instance holds the wallet's lock
+    /// on this market target (see [crate::instance_lock]).
+    pub allow_multiple_instances: bool,
+    /// MM_INSTANCE_LOCK_DIR: directory of the instance lock files (None = the system temp dir).
+    pub instance_lock_dir: Option<String>,
+    /// MM_ORDER_AUDIT: append every order state transition to `orders_<time>.jsonl` in
+    /// session_log_dir.
+    pub order_audit: bool,
+    /// MM_DRY_RUN_REPORT: in dry runs, write `dry_run_report_<time>.json` (simulated trades,
+    /// hypothetical PnL, seconds to close at entry) in session_log_dir when the run ends.
+    pub dry_run_report: bool,
+    /// MM_FEE_RATE_BPS: fixed fee rate instead of fetching it from the CLOB per market.
+    pub fee_rate_bps: Option<u64>,
+    /// If true, buy Up and Down together (one batch) when the pair costs at most dual_side_max_pair_cost.
+    pub dual_side_entry: bool,
+    /// Max ask_up + ask_down for dual-side entry (each pair pays 1.00 at resolution).
+    pub dual_side_max_pair_cost: Decimal,
+    /// Min shares traded on the entry token in the last trade_window_secs (0 = off). Needs the WS book.
+    pub min_recent_volume: Decimal,
+    /// Window for min_recent_volume (seconds).
+    pub trade_window_secs: u64,
+    /// Skip entry when best ask is further than this from the last trade price (0 = off).
+    pub max_last_trade_deviation: Decimal,
+    /// Min shares on the asks within zone_ticks of the entry price (0 = off).
+    pub min_zone_depth: Decimal,
+    /// Half-width of the min_zone_depth zone, in ticks.
+    pub zone_ticks: u32,
+    /// MM_WS_QUIET_REFRESH_SECS: a token with no WS message for this long has its WS book refreshed
+    /// from GET /book (0 = off).
+    pub ws_quiet_refresh_secs: u64,
+    /// MM_WS_QUIET_REFRESH_EVERY_SECS: time between two REST refreshes of a quiet token.
+    pub ws_quiet_refresh_every_secs: u64,
+    /// MM_MAX_BOOK_AGE_MS: a book side older than this is not traded on (0 = off).
+    pub max_book_age_ms: u64,
+    /// If true, record sampled WS book depth to one CSV per interval in book_record_dir.
+    pub book_record_enabled: bool,
+    pub book_record_dir: String,
+    /// Levels per side written per sample.
+    pub book_record_levels: usize,
+    /// Sample interval (ms).
+    pub book_record_interval_ms: u64,
+    /// Label an interval Gamma has not resolved with the outcome of its last Up mid.
+    pub book_record_mid_outcome: bool,
+    /// MM_STATUS_FILE: JSON status snapshot rewritten every status_interval_secs (None = off).
+    pub status_file: Option<String>,
+    pub status_interval_secs: u64,
+    /// MM_SHADOW: candidate parameter sets evaluated on the live tick stream (hypothetical fills).
+    pub shadow: Vec<ShadowParams>,
+}
+
+impl Config {
+    /// Buy range and TP/SL for one side.
+    pub fn side(&self, side: EntrySide) -> &SideConfig {
+        match side {
+            EntrySide::Up => &self.up,
+            EntrySide::Down => &self.down,
+        }
+    }
+
+    /// Entry range (min, max) from buy_range_schedule at `secs_after_open`: the last point at or
+    /// before it, None before the first point (or without a schedule).
+    pub fn buy_range_at(&self, secs_after_open: u64) -> Option<(Decimal, Decimal)> {
+        self.buy_range_schedule
+            .iter()
+            .take_while(|p| p.secs_after_open <= secs_after_open)
+            .last()
+            .map(|p| (p.min_buy_price, p.max_buy_price))
+    }
+
+    /// TP cap from tp_schedule at `secs_to_close`: linear between points, the last point's price
+    /// after it, None before the first point (or without a schedule).
+    pub fn take_profit_cap(&self, secs_to_close: u64) -> Option<Decimal> {
+        let first = self.tp_schedule.first()?;
+        if secs_to_close > first.secs_to_close {
+            return None;
+        }
+        for w in self.tp_schedule.windows(2) {
+            let (hi, lo) = (w[0], w[1]);
+            if secs_to_close >= lo.secs_to_close {
+                let span = Decimal::from(hi.secs_to_close - lo.secs_to_close);
+                let t = Decimal::from(secs_to_close - lo.secs_to_close) / span;
+                return Some(lo.price + (hi.price - lo.price) * t);
+            }
+        }
+        self.tp_schedule.last().map(|p| p.price)
+    }
+}
+
+/// One MM_SHADOW candidate: a name and the parameters it changes from the live config.
+#[derive(Debug, Clone, Default)]
+pub struct ShadowParams {
+    pub name: String,
+    pub min_buy_price: Option<Decimal>,
+    pub max_buy_price: Option<Decimal>,
+    pub take_profit_price: Option<Decimal>,
+    pub stop_loss_price: Option<Decimal>,
+    pub size_shares: Option<Decimal>,
+    pub seconds_before_close: Option<u32>,
+    pub strategy: Option<String>,
+}
+
+impl ShadowParams {
+    /// The live config with this candidate's overrides (prices apply to both sides).
+    pub fn apply(&self, base: &Config) -> Config {
+        let mut config = base.clone();
+        config.shadow.clear();
+        for side in [&mut config.up, &mut config.down] {
+            side.min_buy_price = self.min_buy_price.unwrap_or(side.min_buy_price);
+            side.max_buy_price = self.max_buy_price.unwrap_or(side.max_buy_price);
+            side.take_profit_price = self.take_profit_price.unwrap_or(side.take_profit_price);
+            side.stop_loss_price = self.stop_loss_price.unwrap_or(side.stop_loss_price);
+        }
+        config.size_shares = self.size_shares.unwrap_or(config.size_shares);
+        config.seconds_before_close = self
+            .seconds_before_close
+            .unwrap_or(config.seconds_before_close);
+        if let Some(ref strategy) = self.strategy {
+            config.strategy = strategy.clone();
+        }
+        config
+    }
+}
+
+/// One MM_BUY_RANGE_SCHEDULE point (`secs:min-max`): from `secs_after_open` on, both sides buy
+/// in [min_buy_price, max_buy_price].
+#[derive(Debug, Clone, Copy)]
+pub struct BuyRangePoint {
+    pub secs_after_open: u64,
+    pub min_buy_price: Decimal,
+    pub max_buy_price: Decimal,
+}
+
+/// One MM_TP_SCHEDULE point (`secs:price`): at `secs_to_close`, TP is at most `price`.
+#[derive(Debug, Clone, Copy)]
+pub struct TpSchedulePoint {
+    pub secs_to_close: u64,
+    pub price: Decimal,
+}
+
+/// Per-side entry range and exits; the Up and Down books often differ in liquidity.
+#[derive(Debug, Clone)]
+pub struct SideConfig {
+    pub min_buy_price: Decimal,
+    pub max_buy_price: Decimal,
+    /// Fixed price: sell when best_bid >= this (take profit).
+    pub take_profit_price: Decimal,
+    /// Fixed price: sell when best_bid <= this (stop loss).
+    pub stop_loss_price: Decimal,
+}
+
+impl SideConfig {
+    pub fn in_range(&self, price: Decimal) -> bool {
+        price >= self.min_buy_price && price <= self.max_buy_price
+    }
+}
+
+/// Resolved market from Gamma API.
+#[derive(Debug, Clone)]
+pub struct ResolvedMarket {
+    pub slug: String,
+    pub condition_id: String,
+    pub close_time_unix: u64,
+    /// Interval start (close - 300) for 5-min markets; Gamma startDate (0 if unknown) otherwise.
+    pub interval_start_unix: u64,
+    pub token_id_up: String,
+    pub token_id_down: String,
+    /// Minimum price increment (0.01, or 0.001 once the market trades near the extremes).
+    pub tick_size: Decimal,
+    /// Minimum order size in shares (CLOB rejects smaller orders).
+    pub min_order_size: Decimal,
+    /// Gamma negRisk: a market of a neg-risk event (orders go to the neg-risk exchange).
+    pub neg_risk: bool,
+}
+
+/// One side of the book (Up or Down token).
+#[derive(Debug, Clone, Default)]
+pub struct TopOfBookSide {
+    pub best_bid: Option<Decimal>,
+    pub best_bid_size: Option<Decimal>,
+    pub best_ask: Option<Decimal>,
+    pub best_ask_size: Option<Decimal>,
+    /// Tick size reported by the book (REST `tick_size` or WS `tick_size_change`), if known.
+    pub tick_size: Option<Decimal>,
+    /// Min order size reported by the REST book, if known.
+    pub min_order_size: Option<Decimal>,
+    /// When this side was last updated (ms, REST fetch or WS message); see book::book_age_ms.
+    pub updated_ms: Option<u64>,
+    /// Feed the side was last updated from (None for recorded books).
+    pub source: Option<BookSource>,
+}
+
+/// Where a book side was last updated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSource {
+    /// A WS message.
+    Ws,
+    /// GET /book: the runner's REST read, or the quiet-token refresh of the WS book.
+    Rest,
+}
+
+impl BookSource {
+    /// Label in logs and JSON.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BookSource::Ws => "ws",
+            BookSource::Rest => "rest",
+        }
+    }
+}
+
+/// Top of book for both tokens.
+#[derive(Debug, Clone, Default)]
+pub struct TopOfBook {
+    pub token_id_up: Option<TopOfBookSide>,
+    pub token_id_down: Option<TopOfBookSide>,
+}
+
+/// Side for entry: Up (YES) or Down (NO).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntrySide {
+    Up,
+    Down,
+}
+
+/// Up and Down bought together (dual-side entry); held to resolution, one leg pays 1.00.
+#[derive(Debug, Clone)]
+pub struct HedgedPair {
+    pub size_up: Decimal,
+    pub size_down: Decimal,
+    pub price_up: Decimal,
+    pub price_down: Decimal,
+    pub timestamp_ms: u64,
+}
+
+/// Pending take profit: sell when best_bid >= target_price.
+#[derive(Debug, Clone)]
+pub struct PendingAutoSell {
+    pub token_id: String,
+    pub target_price: Decimal,
+    /// Lowest target that still covers the round-trip fees; MM_TP_SCHEDULE never goes below it.
+    pub breakeven_target: Decimal,
+    pub size: Decimal,
+    pub placed_at_ms: u64,
+}
+
+/// Pending stop loss: sell when best_bid <= trigger_price.
+#[derive(Debug, Clone)]
+pub struct PendingStopLoss {
+    pub token_id: String,
+    pub size: Decimal,
+    pub trigger_price: Decimal,
+    pub entry_price: Decimal,
+    /// MM_BREAKEVEN_TRIGGER: trigger_price was raised to one tick over the fee break-even.
+    pub at_breakeven: bool,
+}
+
+/// Pending soft stop: sell part of the position when best_bid <= trigger_price, ahead of the SL.
+#[derive(Debug, Clone)]
+pub struct PendingSoftStop {
+    pub token_id: String,
+    pub size: Decimal,
+    pub trigger_price: Decimal,
+}
+
+/// Order book from CLOB REST (raw); the WS `book` snapshot has the same levels.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrderBookRaw {
+    pub bids: Option<Vec<BookLevel>>,
+    pub asks: Option<Vec<BookLevel>>,
+    #[serde(rename = "min_order_size")]
+    pub min_order_size: Option<String>,
+    #[serde(rename = "tick_size")]
+    pub tick_size: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BookLevel {
+    pub price: String,
+    pub size: String,
+}
+
+/// Gamma API market response (minimal).
+#[derive(Debug, Clone, Deserialize)]
+pub struct GammaMarket {
+    #[serde(rename = "conditionId")]
+    pub condition_id: Option<String>,
+    pub id: Option<String>,
+    pub slug: Option<String>,
+    pub question: Option<String>,
+    /// Label of the market within its event (the strike of an "above $X" market, e.g. "4,200").
+    #[serde(rename = "groupItemTitle")]
+    pub group_item_title: Option<String>,
+    /// Gamma sends liquidity as a number (liquidityNum) and as a string (liquidity).
+    #[serde(rename = "liquidityNum")]
+    pub liquidity_num: Option<serde_json::Value>,
+    pub liquidity: Option<serde_json::Value>,
+    #[serde(rename = "startDate")]
+    pub start_date: Option<String>,
+    #[serde(rename = "endDate")]
+    pub end_date: Option<String>,
+    #[serde(rename = "endDateIso")]
+    pub end_date_iso: Option<String>,
+    #[serde(rename = "clobTokenIds")]
+    pub clob_token_ids: Option<String>,
+    #[serde(rename = "orderPriceMinTickSize")]
+    pub order_price_min_tick_size: Option<serde_json::Value>,
+    #[serde(rename = "orderMinSize")]
+    pub order_min_size: Option<serde_json::Value>,
+    /// Outcome labels in clobTokenIds order, as a JSON-encoded string (e.g. "[\"Up\", \"Down\"]").
+    pub outcomes: Option<serde_json::Value>,
+    /// Outcome prices in clobTokenIds order, as a JSON-encoded string (e.g. "[\"1\", \"0\"]" once resolved).
+    #[serde(rename = "outcomePrices")]
+    pub outcome_prices: Option<serde_json::Value>,
+    pub tokens: Option<Vec<GammaToken>>,
+    #[serde(rename = "negRisk")]
+    pub neg_risk: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GammaToken {
+    #[serde(rename = "token_id")]
+    pub token_id: Option<String>,
+    pub outcome: Option<String>,
+}
+
+/// Gamma event response (for /events/slug/...).
+#[derive(Debug, Clone, Deserialize)]
+pub struct GammaEvent {
+    pub markets: Option<Vec<GammaMarket>>,
+}
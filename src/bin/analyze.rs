@@ -0,0 +1,102 @@
+//! Trade history report from the session database (MM_SESSION_DB, `sqlite` feature).
+//!
+//! Usage: `cargo run --bin analyze -- [--db logs/sniper.db] [--days 30] [--sql "SELECT ..."]`.
+//! The database defaults to MM_SESSION_DB. The report shows the runs, the closes by exit type and
+//! the closes per UTC day over the last `--days` days (net of estimated fees). `--sql` runs one
+//! query instead and prints its rows tab-separated, for anything the report does not cover.
+
+use anyhow::{bail, Context, Result};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+
+struct Args {
+    db: String,
+    days: u64,
+    sql: Option<String>,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut args = Args {
+        db: std::env::var("MM_SESSION_DB").unwrap_or_else(|_| "logs/sniper.db".to_string()),
+        days: 30,
+        sql: None,
+    };
+    let mut it = std::env::args().skip(1);
+    while let Some(flag) = it.next() {
+        let value = it
+            .next()
+            .with_context(|| format!("{} needs a value", flag))?;
+        match flag.as_str() {
+            "--db" => args.db = value,
+            "--days" => args.days = value.parse().context("--days")?,
+            "--sql" => args.sql = Some(value),
+            other => bail!("unknown argument {}", other),
+        }
+    }
+    Ok(args)
+}
+
+fn cell(v: ValueRef<'_>) -> String {
+    match v {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => format!("{:.4}", f),
+        ValueRef::Text(t) | ValueRef::Blob(t) => String::from_utf8_lossy(t).into_owned(),
+    }
+}
+
+/// Print every row of `sql`, tab-separated, with a header line.
+fn print_query(conn: &Connection, sql: &str) -> Result<()> {
+    let mut stmt = conn.prepare(sql)?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    println!("{}", columns.join("\t"));
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let cells: Vec<String> = (0..columns.len())
+            .map(|i| row.get_ref(i).map(cell))
+            .collect::<rusqlite::Result<_>>()?;
+        println!("{}", cells.join("\t"));
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    let args = parse_args()?;
+    let conn = Connection::open_with_flags(&args.db, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("open {}", args.db))?;
+    if let Some(sql) = &args.sql {
+        return print_query(&conn, sql);
+    }
+
+    println!("Runs:");
+    print_query(
+        &conn,
+        "SELECT COUNT(*) AS runs, SUM(dry_run) AS dry_runs,
+                datetime(MIN(session_start_ms) / 1000, 'unixepoch') AS first,
+                datetime(MAX(session_start_ms) / 1000, 'unixepoch') AS last
+         FROM sessions",
+    )?;
+    println!("\nCloses by exit type:");
+    print_query(
+        &conn,
+        "SELECT exit_type, COUNT(*) AS closes, SUM(pnl_usd > 0) AS wins, SUM(size) AS shares,
+                SUM(pnl_usd) AS pnl, SUM(fees_usd) AS fees, SUM(net_pnl_usd) AS net
+         FROM closes GROUP BY exit_type ORDER BY closes DESC",
+    )?;
+    println!("\nCloses per day (last {} days):", args.days);
+    print_query(
+        &conn,
+        &format!(
+            "SELECT date(exit_time_ms / 1000, 'unixepoch') AS day, COUNT(*) AS closes,
+                    SUM(pnl_usd > 0) AS wins, SUM(net_pnl_usd) AS net,
+                    SUM(SUM(net_pnl_usd)) OVER (ORDER BY date(exit_time_ms / 1000, 'unixepoch'))
+                        AS cumulative
+             FROM closes
+             WHERE exit_time_ms >= (strftime('%s', 'now') - {} * 86400) * 1000
+             GROUP BY day ORDER BY day",
+            args.days
+        ),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,73 @@
+//! Create or derive CLOB API credentials from PRIVATE_KEY and write them to .env.
+//!
+//! Usage: `cargo run --bin derive_api_key [ENV_FILE]` (default `.env`). `MM_API_KEY_NONCE`
+//! selects the key nonce (default 0); `--print` prints the credentials instead of writing them.
+
+use anyhow::{Context, Result};
+use ethers::signers::{LocalWallet, Signer};
+use sniper_core::auth::{create_or_derive_api_key, write_env_file};
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let print_only = args.iter().any(|a| a == "--print");
+    let env_file = args
+        .iter()
+        .find(|a| !a.starts_with("--"))
+        .cloned()
+        .unwrap_or_else(|| ".env".to_string());
+
+    let pk = std::env::var("PRIVATE_KEY")
+        .or_else(|_| std::env::var("POLYMARKET_PRIVATE_KEY"))
+        .context("PRIVATE_KEY or POLYMARKET_PRIVATE_KEY required")?;
+    let wallet = pk
+        .trim()
+        .strip_prefix("0x")
+        .unwrap_or(pk.trim())
+        .parse::<LocalWallet>()
+        .context("Invalid PRIVATE_KEY")?;
+    let clob_host = std::env::var("POLYMARKET_CLOB_HOST")
+        .or_else(|_| std::env::var("POLYMARKET_CLOB_URL"))
+        .unwrap_or_else(|_| "https://clob.polymarket.com".to_string());
+    let chain_id: u64 = std::env::var("POLYMARKET_CHAIN_ID")
+        .unwrap_or_else(|_| "137".to_string())
+        .parse()
+        .unwrap_or(137);
+    let nonce: u64 = std::env::var("MM_API_KEY_NONCE")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(0);
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()?;
+    tracing::info!(
+        "[Auth] requesting API key for {:?} (nonce {}) from {}",
+        wallet.address(),
+        nonce,
+        clob_host
+    );
+    let creds = create_or_derive_api_key(&client, &clob_host, &wallet, chain_id, nonce).await?;
+    if print_only {
+        println!("API_KEY={}", creds.api_key);
+        println!("SECRET={}", creds.secret);
+        println!("PASSPHRASE={}", creds.passphrase);
+    } else {
+        write_env_file(&env_file, &creds)?;
+        tracing::info!(
+            "[Auth] wrote API_KEY={} SECRET=*** PASSPHRASE=*** to {}",
+            creds.api_key,
+            env_file
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! gRPC executor: serves book snapshots, order placement and cancellation, and position queries
+//! (crates/sniper-core/proto/executor.proto) so a strategy in another language trades through
+//! this crate's CLOB client.
+//!
+//! Usage: `cargo run --features grpc --bin executor`. Listens on MM_GRPC_ADDR (default
+//! 127.0.0.1:50051). Uses the bot's credentials and hosts (PRIVATE_KEY, API_KEY, ...,
+//! POLYMARKET_CLOB_HOST, POLYMARKET_DATA_API_URL); MM_DRY_RUN=true (the default, as for the bot)
+//! answers orders without sending them.
+
+use anyhow::{Context, Result};
+use sniper_core::clob::create_clob_client;
+use sniper_core::grpc::{serve, ExecutorService};
+use std::net::SocketAddr;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
+    let addr: SocketAddr = std::env::var("MM_GRPC_ADDR")
+        .unwrap_or_else(|_| "127.0.0.1:50051".to_string())
+        .trim()
+        .parse()
+        .context("MM_GRPC_ADDR")?;
+    let dry_run = std::env::var("MM_DRY_RUN")
+        .map(|v| v.to_lowercase() == "true" || v == "1")
+        .unwrap_or(true);
+    let clob_host = std::env::var("POLYMARKET_CLOB_HOST")
+        .or_else(|_| std::env::var("POLYMARKET_CLOB_URL"))
+        .unwrap_or_else(|_| "https://clob.polymarket.com".to_string());
+    let data_api_url = std::env::var("POLYMARKET_DATA_API_URL")
+        .unwrap_or_else(|_| "https://data-api.polymarket.com".to_string());
+    if dry_run {
+        tracing::info!("[Executor] MM_DRY_RUN=true: orders are answered, not sent");
+    }
+
+    let service = ExecutorService::new(create_clob_client(dry_run)?, &clob_host, &data_api_url)?;
+    serve(service, addr).await
+}
@@ -0,0 +1,179 @@
+//! Panic flatten: cancel every open order and sell every outcome token the account holds, across
+//! all markets. For emergencies, when the bot is misbehaving (stop it first).
+//!
+//! Usage: `cargo run --bin flatten [--dry-run] [--min-price 0.01]`. Uses the same credentials as
+//! the bot (PRIVATE_KEY, API_KEY, ... and the `_2` ... `_9` extra accounts). Positions come from
+//! the data API (POLYMARKET_DATA_API_URL); each is sold FAK down to `--min-price`, i.e. into
+//! whatever bids there are. Resolved (redeemable) positions are listed but not sold. `--dry-run`
+//! only lists what would be canceled and sold.
+
+use anyhow::{bail, Context, Result};
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+use sniper_core::clob::{ClobClient, LiveClob};
+use sniper_core::multi_clob::MAX_ACCOUNTS;
+use sniper_core::positions::fetch_positions;
+use sniper_core::types::SellOrderTimeInForce;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Smallest sell the exchange accepts.
+const MIN_SELL_SIZE: Decimal = dec!(0.01);
+
+struct Args {
+    dry_run: bool,
+    min_price: Decimal,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut args = Args {
+        dry_run: false,
+        min_price: dec!(0.01),
+    };
+    let mut it = std::env::args().skip(1);
+    while let Some(flag) = it.next() {
+        match flag.as_str() {
+            "--dry-run" => args.dry_run = true,
+            "--min-price" => {
+                let v = it.next().context("--min-price needs a value")?;
+                args.min_price = Decimal::from_str(v.trim()).context("--min-price")?;
+                if args.min_price <= Decimal::ZERO || args.min_price >= Decimal::ONE {
+                    bail!("--min-price must be between 0 and 1");
+                }
+            }
+            other => bail!("unknown argument {}", other),
+        }
+    }
+    Ok(args)
+}
+
+/// Cancel all orders of one account, then sell its positions. Returns the number of failed sells.
+async fn flatten_account(
+    label: &str,
+    clob: &LiveClob,
+    http: &reqwest::Client,
+    data_api_url: &str,
+    args: &Args,
+) -> Result<usize> {
+    if args.dry_run {
+        info!(
+            "[Flatten] account {}: --dry-run, not canceling orders",
+            label
+        );
+    } else {
+        let r = clob.cancel_all().await?;
+        info!(
+            "[Flatten] account {}: canceled {} open order(s)",
+            label,
+            r.canceled.len()
+        );
+        if !r.not_canceled.is_empty() {
+            warn!(
+                "[Flatten] account {}: {} order(s) not canceled: {:?}",
+                label,
+                r.not_canceled.len(),
+                r.not_canceled
+            );
+        }
+        // Let the exchange release the balance locked by the canceled orders.
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    let funder = format!("{:?}", clob.funder());
+    let positions = fetch_positions(http, data_api_url, &funder).await?;
+    info!(
+        "[Flatten] account {}: {} position(s) held by {}",
+        label,
+        positions.len(),
+        funder
+    );
+    let mut failed = 0;
+    for p in positions {
+        if p.redeemable {
+            info!(
+                "[Flatten]   {} {} — {} sh resolved, redeem instead of selling",
+                p.title, p.outcome, p.size
+            );
+            continue;
+        }
+        let available = clob.get_available_balance(&p.asset).await.ok().flatten();
+        let size = available
+            .map_or(p.size, |a| a.min(p.size))
+            .round_dp_with_strategy(2, RoundingStrategy::ToZero);
+        if size < MIN_SELL_SIZE {
+            info!(
+                "[Flatten]   {} {} — {} sh (available {:?}) below the minimum sell, skipping",
+                p.title, p.outcome, p.size, available
+            );
+            continue;
+        }
+        info!(
+            "[Flatten]   {} {} — selling {} sh (last price {}) down to {}",
+            p.title, p.outcome, size, p.cur_price, args.min_price
+        );
+        if args.dry_run {
+            continue;
+        }
+        match clob
+            .place_sell_order(&p.asset, args.min_price, size, SellOrderTimeInForce::Fak)
+            .await
+        {
+            Ok(r) if r.success => info!("[Flatten]   ✓ sold {} sh", r.filled_size.unwrap_or(size)),
+            Ok(r) => {
+                failed += 1;
+                warn!(
+                    "[Flatten]   ✗ sell failed: {}",
+                    r.error_msg.unwrap_or_default()
+                );
+            }
+            Err(e) => {
+                failed += 1;
+                warn!("[Flatten]   ✗ sell failed: {:#}", e);
+            }
+        }
+    }
+    Ok(failed)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
+    let args = parse_args()?;
+    let data_api_url = std::env::var("POLYMARKET_DATA_API_URL")
+        .unwrap_or_else(|_| "https://data-api.polymarket.com".to_string());
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()?;
+
+    let mut accounts = vec![("1".to_string(), LiveClob::from_env()?)];
+    for i in 2..=MAX_ACCOUNTS {
+        let suffix = format!("_{}", i);
+        if std::env::var(format!("PRIVATE_KEY{}", suffix)).is_ok() {
+            accounts.push((i.to_string(), LiveClob::from_env_account(&suffix)?));
+        }
+    }
+
+    let mut failed = 0;
+    for (label, clob) in &accounts {
+        match flatten_account(label, clob, &http, &data_api_url, &args).await {
+            Ok(n) => failed += n,
+            Err(e) => {
+                failed += 1;
+                warn!("[Flatten] account {} failed: {:#}", label, e);
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{} step(s) failed; check the log and rerun", failed);
+    }
+    info!("[Flatten] done");
+    Ok(())
+}
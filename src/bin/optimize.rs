@@ -0,0 +1,250 @@
+//! Walk-forward parameter search over recorded books (MM_BOOK_RECORD output).
+//!
+//! Usage: `cargo run --release --bin optimize -- [--dir data/books] [--min-buy 0.80,0.85]
+//! [--max-buy 0.90,0.95] [--tp 0.97,0.99] [--sl 0.80,0.85] [--random N] [--folds 4] [--top 10]`.
+//!
+//! Candidates are the grid of the listed values (or N random draws from it); parameters that are
+//! not listed keep their env value, as does everything else (strategy, size, window, TP schedule).
+//! Intervals are split in time order into `folds` chunks. For each chunk after the first, the
+//! candidate with the best PnL on all earlier chunks is validated on it (expanding window); the
+//! report shows that walk-forward result, then the candidates ranked by how many validation
+//! chunks they were profitable in and by total validation PnL.
+
+use anyhow::{bail, Context, Result};
+use rand::seq::SliceRandom;
+use rust_decimal::Decimal;
+use sniper_core::backtest::{load_intervals, replay, RecordedInterval};
+use sniper_core::config::load_config;
+use sniper_core::shadow::{ShadowResult, ShadowRunner};
+use sniper_core::types::{Config, ShadowParams};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+struct Args {
+    dir: PathBuf,
+    /// Values to try per parameter; `[None]` keeps the env value.
+    min_buy: Vec<Option<Decimal>>,
+    max_buy: Vec<Option<Decimal>>,
+    tp: Vec<Option<Decimal>>,
+    sl: Vec<Option<Decimal>>,
+    random: Option<usize>,
+    folds: usize,
+    top: usize,
+}
+
+fn parse_args(config: &Config) -> Result<Args> {
+    let mut args = Args {
+        dir: PathBuf::from(&config.book_record_dir),
+        min_buy: vec![None],
+        max_buy: vec![None],
+        tp: vec![None],
+        sl: vec![None],
+        random: None,
+        folds: 4,
+        top: 10,
+    };
+    let mut it = std::env::args().skip(1);
+    while let Some(flag) = it.next() {
+        let value = it
+            .next()
+            .with_context(|| format!("{} needs a value", flag))?;
+        let prices = || -> Result<Vec<Option<Decimal>>> {
+            value
+                .split(',')
+                .map(|p| {
+                    Decimal::from_str(p.trim())
+                        .map(Some)
+                        .with_context(|| format!("{} {}", flag, p))
+                })
+                .collect()
+        };
+        match flag.as_str() {
+            "--dir" => args.dir = PathBuf::from(&value),
+            "--min-buy" => args.min_buy = prices()?,
+            "--max-buy" => args.max_buy = prices()?,
+            "--tp" => args.tp = prices()?,
+            "--sl" => args.sl = prices()?,
+            "--random" => args.random = Some(value.parse().context("--random")?),
+            "--folds" => args.folds = value.parse().context("--folds")?,
+            "--top" => args.top = value.parse().context("--top")?,
+            other => bail!("unknown argument {}", other),
+        }
+    }
+    Ok(args)
+}
+
+fn candidates(args: &Args) -> Vec<ShadowParams> {
+    let label = |key: &str, v: Option<Decimal>| v.map(|v| format!(" {}={}", key, v));
+    let mut out = Vec::new();
+    for &min_buy in &args.min_buy {
+        for &max_buy in &args.max_buy {
+            if matches!((min_buy, max_buy), (Some(lo), Some(hi)) if hi < lo) {
+                continue;
+            }
+            for &tp in &args.tp {
+                for &sl in &args.sl {
+                    let name: String = [
+                        label("min", min_buy),
+                        label("max", max_buy),
+                        label("tp", tp),
+                        label("sl", sl),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                    out.push(ShadowParams {
+                        name: if name.is_empty() {
+                            "env".to_string()
+                        } else {
+                            name.trim_start().to_string()
+                        },
+                        min_buy_price: min_buy,
+                        max_buy_price: max_buy,
+                        take_profit_price: tp,
+                        stop_loss_price: sl,
+                        ..ShadowParams::default()
+                    });
+                }
+            }
+        }
+    }
+    if let Some(n) = args.random {
+        out.shuffle(&mut rand::thread_rng());
+        out.truncate(n);
+    }
+    out
+}
+
+/// Result per candidate per interval, candidates split across threads.
+fn evaluate(
+    config: &Config,
+    candidates: &[ShadowParams],
+    intervals: &[RecordedInterval],
+) -> Vec<Vec<ShadowResult>> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk = candidates.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = candidates
+            .chunks(chunk)
+            .map(|params| {
+                scope.spawn(move || {
+                    let mut config = config.clone();
+                    config.shadow = params.to_vec();
+                    let mut shadow =
+                        ShadowRunner::from_config(&config).expect("at least one candidate");
+                    let per_interval: Vec<Vec<ShadowResult>> = intervals
+                        .iter()
+                        .map(|i| replay(&mut shadow, &config, i))
+                        .collect();
+                    // Transpose to per candidate.
+                    (0..params.len())
+                        .map(|c| per_interval.iter().map(|r| r[c]).collect::<Vec<_>>())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("evaluation thread"))
+            .collect()
+    })
+}
+
+fn pnl(results: &[ShadowResult]) -> Decimal {
+    results.iter().map(|r| r.pnl).sum()
+}
+
+fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
+        )
+        .init();
+
+    let config = load_config()?;
+    let args = parse_args(&config)?;
+    let intervals = load_intervals(&args.dir)?;
+    let folds = args.folds.max(2);
+    if intervals.len() < folds {
+        bail!(
+            "{} recorded intervals in {}, need at least {} (one per fold)",
+            intervals.len(),
+            args.dir.display(),
+            folds
+        );
+    }
+    let candidates = candidates(&args);
+    if candidates.is_empty() {
+        bail!("no candidates (every max-buy is below every min-buy?)");
+    }
+    println!(
+        "{} intervals from {}, {} candidates, {} folds",
+        intervals.len(),
+        args.dir.display(),
+        candidates.len(),
+        folds
+    );
+    let results = evaluate(&config, &candidates, &intervals);
+
+    let n = intervals.len();
+    let bounds: Vec<usize> = (0..=folds).map(|k| k * n / folds).collect();
+    println!("\nWalk-forward (best on earlier folds, validated on the next):");
+    let mut walk_forward = Decimal::ZERO;
+    for k in 1..folds {
+        let (train, val) = (0..bounds[k], bounds[k]..bounds[k + 1]);
+        let best = (0..candidates.len())
+            .max_by_key(|&c| pnl(&results[c][train.clone()]))
+            .expect("candidates");
+        let val_pnl = pnl(&results[best][val.clone()]);
+        walk_forward += val_pnl;
+        println!(
+            "  fold {}: train {} intervals PnL {:>8} | validate {} intervals PnL {:>8} | {}",
+            k,
+            train.len(),
+            pnl(&results[best][train]).round_dp(2),
+            val.len(),
+            val_pnl.round_dp(2),
+            candidates[best].name
+        );
+    }
+    println!("  total validation PnL {}", walk_forward.round_dp(2));
+
+    // Stability: profitable validation folds first, then total validation PnL.
+    let mut ranked: Vec<(usize, usize, Decimal, Decimal, u32, u32)> = (0..candidates.len())
+        .map(|c| {
+            let fold_pnls: Vec<Decimal> = (1..folds)
+                .map(|k| pnl(&results[c][bounds[k]..bounds[k + 1]]))
+                .collect();
+            let positive = fold_pnls.iter().filter(|p| **p > Decimal::ZERO).count();
+            let worst = fold_pnls.iter().copied().min().unwrap_or_default();
+            let trades = results[c].iter().map(|r| r.trades).sum();
+            let wins = results[c].iter().map(|r| r.wins).sum();
+            (c, positive, fold_pnls.iter().sum(), worst, trades, wins)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)));
+    println!("\nMost stable candidates:");
+    println!(
+        "  {:>9} {:>10} {:>10} {:>7} {:>6}  params",
+        "folds+", "val PnL", "worst", "trades", "win%"
+    );
+    for &(c, positive, val_pnl, worst, trades, wins) in ranked.iter().take(args.top) {
+        let win_pct = if trades > 0 {
+            Decimal::from(wins * 100) / Decimal::from(trades)
+        } else {
+            Decimal::ZERO
+        };
+        println!(
+            "  {:>9} {:>10} {:>10} {:>7} {:>6}  {}",
+            format!("{}/{}", positive, folds - 1),
+            val_pnl.round_dp(2),
+            worst.round_dp(2),
+            trades,
+            win_pct.round_dp(1),
+            candidates[c].name
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! Sweep profits: when the Polymarket Safe holds more than MM_SWEEP_THRESHOLD_USDC, transfer
+//! everything above MM_SWEEP_FLOAT_USDC to MM_SWEEP_COLD_ADDRESS.
+//!
+//! Usage: `cargo run --bin sweep_profits [--dry-run]`. The Safe is FUNDER_ADDRESS (default: the
+//! PRIVATE_KEY's Polymarket Safe); the EOA signs and needs POL for gas. Run it from cron for
+//! unattended deployments.
+
+use anyhow::{Context, Result};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::H160;
+use sniper_core::chain::connect;
+use sniper_core::signing::polymarket_safe_address;
+use sniper_core::sweep::{sweep_from_safe, usdc_balance, SweepConfig};
+use std::sync::Arc;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
+    let dry_run = std::env::args().skip(1).any(|a| a == "--dry-run");
+    let config = SweepConfig::from_env()?;
+    let pk = std::env::var("PRIVATE_KEY")
+        .or_else(|_| std::env::var("POLYMARKET_PRIVATE_KEY"))
+        .context("PRIVATE_KEY or POLYMARKET_PRIVATE_KEY required")?;
+    let wallet = pk
+        .trim()
+        .strip_prefix("0x")
+        .unwrap_or(pk.trim())
+        .parse::<LocalWallet>()
+        .context("Invalid PRIVATE_KEY")?;
+    let safe = match std::env::var("FUNDER_ADDRESS") {
+        Ok(s) if !s.trim().is_empty() => {
+            s.trim().parse::<H160>().context("Invalid FUNDER_ADDRESS")?
+        }
+        _ => polymarket_safe_address(wallet.address()),
+    };
+
+    let provider = connect(&config.rpc_urls).await?;
+    let balance = usdc_balance(Arc::new(provider.clone()), safe).await?;
+    let Some(amount) = config.sweep_amount(balance) else {
+        tracing::info!(
+            "[Sweep] Safe {:?} holds {} USDC, not above threshold {}; nothing to do",
+            safe,
+            balance,
+            config.threshold
+        );
+        return Ok(());
+    };
+    tracing::info!(
+        "[Sweep] Safe {:?} holds {} USDC (threshold {}); sweeping {} to {:?}, keeping {}",
+        safe,
+        balance,
+        config.threshold,
+        amount,
+        config.cold_address,
+        config.float
+    );
+    if dry_run {
+        tracing::info!("[Sweep] --dry-run: no transaction sent");
+        return Ok(());
+    }
+    let tx = sweep_from_safe(provider, wallet, safe, config.cold_address, amount).await?;
+    tracing::info!("[Sweep] done: tx {}", tx);
+    Ok(())
+}
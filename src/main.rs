@@ -1,15 +1,7 @@
 //! Interval Sniper (Rust): buy in range [min_buy_price, max_buy_price], sell on take profit and stop loss.
 //! Same logic as the TypeScript bot in src/bot/marketMaker/.
 
-mod clob;
-mod clob_ws_book;
-mod config;
-mod market;
-mod orderbook;
-mod runner;
-mod session_log;
-mod signing;
-mod types;
+use sniper_core::{config, runner, scanner};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -21,5 +13,8 @@ async fn main() -> anyhow::Result<()> {
         )
         .init();
 
+    if std::env::var("MM_SCANNER_ENABLED").is_ok_and(|v| v == "true" || v == "1") {
+        return scanner::run(config::load_config()?).await;
+    }
     runner::run().await
 }
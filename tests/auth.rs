@@ -0,0 +1,70 @@
+//! API credentials: [ApiCreds] keeps the secret and passphrase out of `{:?}`, and
+//! [write_env_file] sets API_KEY, SECRET and PASSPHRASE in an env file without touching its
+//! other lines.
+
+use sniper_core::auth::{write_env_file, ApiCreds};
+use std::path::PathBuf;
+
+fn creds() -> ApiCreds {
+    ApiCreds {
+        api_key: "key-1".to_string(),
+        secret: "c2VjcmV0".to_string(),
+        passphrase: "pass-1".to_string(),
+    }
+}
+
+/// A fresh path in the temp dir (the file does not exist).
+fn env_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("sniper-auth-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn write(path: &PathBuf) -> String {
+    write_env_file(path.to_str().unwrap(), &creds()).unwrap();
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn debug_redacts_the_secret_and_passphrase() {
+    let shown = format!("{:?}", creds());
+    assert!(shown.contains("key-1"), "{}", shown);
+    assert!(!shown.contains("c2VjcmV0"), "{}", shown);
+    assert!(!shown.contains("pass-1"), "{}", shown);
+}
+
+#[test]
+fn creates_a_missing_file() {
+    let path = env_path("missing.env");
+    assert_eq!(
+        write(&path),
+        "API_KEY=key-1\nSECRET=c2VjcmV0\nPASSPHRASE=pass-1\n"
+    );
+}
+
+#[test]
+fn replaces_existing_keys_in_place() {
+    let path = env_path("replace.env");
+    std::fs::write(&path, "SECRET=old\nAPI_KEY=old\nPASSPHRASE=old\n").unwrap();
+    assert_eq!(
+        write(&path),
+        "SECRET=c2VjcmV0\nAPI_KEY=key-1\nPASSPHRASE=pass-1\n"
+    );
+}
+
+#[test]
+fn appends_missing_keys_and_keeps_other_lines() {
+    let path = env_path("append.env");
+    std::fs::write(
+        &path,
+        "# Polymarket\nPRIVATE_KEY=0xabc\nAPI_KEY=old\n\nMM_DRY_RUN=true\nAPI_KEY_2=other",
+    )
+    .unwrap();
+    assert_eq!(
+        write(&path),
+        "# Polymarket\nPRIVATE_KEY=0xabc\nAPI_KEY=key-1\n\nMM_DRY_RUN=true\nAPI_KEY_2=other\n\
+         SECRET=c2VjcmV0\nPASSPHRASE=pass-1\n"
+    );
+}
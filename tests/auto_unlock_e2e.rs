@@ -0,0 +1,64 @@
+//! Funds-locked policy (MM_AUTO_UNLOCK=tp_priority) against the mock CLOB: a passive TP ask holds
+//! the position's shares when the bid drops under the SL, and the SL leaves it resting instead of
+//! canceling it; the TP then sells when the bid comes back.
+
+mod mock_clob;
+
+use mock_clob::{live_env, now_unix, wait_for, MockClob, Side};
+use rust_decimal_macros::dec;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stop_loss_keeps_the_resting_take_profit() {
+    let mock = MockClob::start().await;
+    let (up, down) = ("6001", "6002");
+    let now = now_unix();
+    mock.add_market("mock-auto-unlock", up, down, now - 60, now + 25);
+    mock.set_collateral(dec!(100));
+    mock.set_book(up, &[("0.90", "100")], &[("0.91", "100")]);
+    mock.set_book(down, &[("0.08", "100")], &[("0.10", "100")]);
+    live_env(&mock);
+    std::env::set_var("MM_TARGET_SLUG", "mock-auto-unlock");
+    std::env::set_var("MM_TP_PASSIVE_MS", "60000");
+    std::env::set_var("MM_AUTO_UNLOCK", "tp_priority");
+
+    let config = sniper_core::config::load_config().unwrap();
+    let engine = tokio::spawn(sniper_core::runner::run_with_config(config));
+
+    assert!(
+        wait_for(30, || mock.shares(up) == dec!(5)).await,
+        "no entry: orders {:?}",
+        mock.orders()
+    );
+    let is_tp = |o: &mock_clob::MockOrder| o.token_id == up && o.side == Side::Sell;
+    assert!(
+        wait_for(10, || mock
+            .orders()
+            .iter()
+            .any(|o| is_tp(o) && o.status == "LIVE"))
+        .await,
+        "no passive TP ask: orders {:?}",
+        mock.orders()
+    );
+    let posts = mock.request_count("POST /order");
+
+    // Under the SL: it tries to sell, but the TP ask keeps its shares.
+    mock.set_book(up, &[("0.75", "100")], &[("0.95", "100")]);
+    assert!(
+        wait_for(10, || mock.request_count("POST /order") > posts).await,
+        "no SL attempt: orders {:?}",
+        mock.orders()
+    );
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    let tp = mock.orders().into_iter().find(is_tp).unwrap();
+    assert_eq!(tp.status, "LIVE");
+    assert_eq!(mock.shares(up), dec!(5));
+
+    // The bid comes back through the ask: the kept TP sells the position.
+    mock.set_book(up, &[("0.97", "100")], &[("0.98", "100")]);
+    assert!(
+        wait_for(10, || mock.shares(up) < dec!(0.1)).await,
+        "TP did not sell: orders {:?}",
+        mock.orders()
+    );
+    engine.abort();
+}
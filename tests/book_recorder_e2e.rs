@@ -0,0 +1,44 @@
+//! Book recorder against the mock CLOB: the interval's meta sidecar carries the market's
+//! resolution from Gamma, not the outcome of the last book mid (MM_BOOK_RECORD_MID_OUTCOME off).
+
+mod e2e;
+mod mock_clob;
+
+use e2e::{read_json, Run};
+use mock_clob::wait_for;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn labels_the_interval_with_its_resolution() {
+    let run = Run::start("book-record", "9001", "9002", 6).await;
+    // Up mid 0.71 at the close, below the entry range.
+    run.mock
+        .set_book(&run.up, &[("0.70", "100")], &[("0.72", "100")]);
+    run.mock
+        .set_book(&run.down, &[("0.28", "100")], &[("0.30", "100")]);
+    std::env::set_var("MM_BOOK_RECORD", "true");
+    std::env::set_var("MM_BOOK_RECORD_DIR", &run.dir);
+    std::env::set_var("MM_BOOK_RECORD_INTERVAL_MS", "100");
+    std::env::set_var("MM_CARRYOVER_WAIT_SECS", "30");
+
+    let engine = run.spawn();
+    let file = run.dir.join(format!("{}.meta.json", run.slug));
+    assert!(
+        wait_for(10, || read_json(&file).is_some()).await,
+        "no meta sidecar"
+    );
+    let pending = read_json(&file).unwrap();
+    assert!(pending["rows"].as_u64().unwrap() > 0);
+    assert_eq!(pending["up_mid_at_close"], "0.71");
+    assert!(pending["outcome"].is_null(), "meta {}", pending);
+    assert!(
+        !engine.is_finished(),
+        "engine stopped before the resolution"
+    );
+
+    // The last mid says Up, but Down wins.
+    run.mock.resolve(&run.slug, false);
+    run.finish(engine).await;
+    let resolved = read_json(&file).unwrap();
+    assert_eq!(resolved["outcome"], "Down");
+    assert_eq!(resolved["outcome_source"], "gamma");
+}
@@ -0,0 +1,47 @@
+//! Break-even stop (MM_BREAKEVEN_TRIGGER) against the mock CLOB: once the bid has run past entry +
+//! the trigger, a pullback to one tick over the fee break-even exit price sells, well above the
+//! configured SL.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::Run;
+use mock_clob::wait_for;
+use rust_decimal_macros::dec;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stop_moves_to_break_even_after_the_bid_runs() {
+    let run = Run::start("breakeven", "3001", "3002", 10).await;
+    let (mock, up) = (&run.mock, run.up.as_str());
+    mock.set_book(up, &[("0.90", "100")], &[("0.91", "100")]);
+    mock.set_book(&run.down, &[("0.08", "100")], &[("0.10", "100")]);
+    mock.set_fee_rate(up, Some(1000));
+    mock.set_fee_rate(&run.down, Some(1000));
+    std::env::set_var("MM_BREAKEVEN_TRIGGER", "0.02");
+
+    let engine = run.spawn();
+    assert!(
+        wait_for(10, || mock.shares(up) == dec!(5)).await,
+        "no entry: orders {:?}",
+        mock.orders()
+    );
+    assert_eq!(mock.orders()[0].price, dec!(0.92));
+
+    // 1000 bps: ~0.008 fee per share at 0.92 and ~0.007 at the exit, so the round trip breaks
+    // even near 0.935 and the stop goes one tick over, to 0.94 (not entry + one tick, 0.93).
+    // Past that but under the TP (0.97 less its 0.01 margin): nothing sells, the SL moves up.
+    mock.set_book(up, &[("0.95", "100")], &[("0.96", "100")]);
+    run.at_secs_to_close(run.secs_to_close() - 2).await;
+    assert_eq!(mock.shares(up), dec!(5), "orders {:?}", mock.orders());
+
+    // Back to 0.94: far above MM_STOP_LOSS_PRICE (0.80), the break-even stop sells.
+    mock.set_book(up, &[("0.94", "100")], &[("0.95", "100")]);
+    assert!(
+        wait_for(5, || mock.shares(up) < dec!(0.1)).await,
+        "no break-even stop: orders {:?}",
+        mock.orders()
+    );
+    let sell = mock.orders().into_iter().last().unwrap();
+    assert_eq!(sell.price, dec!(0.94));
+    run.finish(engine).await;
+}
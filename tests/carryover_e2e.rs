@@ -0,0 +1,45 @@
+//! Carryover against the mock CLOB: a position still held when the market closes, before Gamma
+//! resolves it, is carried past the close and logged at the payout it resolves to, not at the one
+//! inferred from the last best bid.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::Run;
+use mock_clob::wait_for;
+use rust_decimal_macros::dec;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn logs_the_close_at_the_resolved_payout() {
+    let run = Run::start("carryover", "6001", "6002", 8).await;
+    run.mock
+        .set_book(&run.up, &[("0.91", "100")], &[("0.92", "100")]);
+    run.mock
+        .set_book(&run.down, &[("0.06", "100")], &[("0.08", "100")]);
+    std::env::set_var("MM_SESSION_LOG", "true");
+    std::env::set_var("MM_CARRYOVER_WAIT_SECS", "30");
+
+    let engine = run.spawn();
+    // Entry, then the bid stays between the SL and the TP: the shares are still held at the close.
+    assert!(
+        wait_for(6, || run.mock.shares(&run.up) == dec!(5)).await,
+        "no entry: orders {:?}",
+        run.mock.orders()
+    );
+    run.at_secs_after_close(2).await;
+    assert!(
+        !engine.is_finished(),
+        "engine stopped before the resolution"
+    );
+
+    // Up was bid 0.91 at the close (inferred to pay 1), but Down wins.
+    run.mock.resolve(&run.slug, false);
+    run.finish(engine).await;
+
+    let closes = run.session_events("close");
+    assert_eq!(closes.len(), 1, "closes: {:?}", closes);
+    assert_eq!(closes[0]["exit_type"], "RESOLUTION");
+    assert_eq!(closes[0]["side"], "Up");
+    assert_eq!(closes[0]["exit_price"], "0");
+    assert_eq!(closes[0]["size"], "5");
+}
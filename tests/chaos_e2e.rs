@@ -0,0 +1,181 @@
+//! Fault injection (MM_CHAOS) on full engines against the mock CLOB, each on its own simulated
+//! clock ([e2e::Run::spawn_market]) with its files in a temp dir: dry runs with book-matched
+//! fills (MM_DRY_RUN_SIM_FILLS) whose orders get latency, 400/429/500 answers (some 500s after the
+//! order executed), FAK partials and WS gaps. Six markets trade at once; half of them rally to the
+//! TP, half drop to the SL. From the sim's own fills: no market buys more than one entry (no
+//! double-buy), and no sell ever asks for more than the sim holds (no over-sell).
+//! `cargo test --features chaos --test chaos_e2e`.
+//!
+//! The sim's fills are read from its log lines, captured by a subscriber scoped to the test's
+//! thread: the runtime is single-threaded so every engine task logs there.
+#![cfg(feature = "chaos")]
+
+mod e2e;
+mod mock_clob;
+
+use e2e::{Run, CLOSE, START};
+use mock_clob::wait_for;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Log lines of every engine in the process.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Captured {
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8_lossy(&self.0.lock().unwrap())
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// Shares the sim filled per (token, "BUY" / "SELL"), from its `[SimClob] BUY Fak 5 of 5 ...
+/// token_id=...` lines.
+fn sim_fills(lines: &[String]) -> HashMap<(String, String), Decimal> {
+    let mut fills = HashMap::new();
+    for line in lines {
+        let Some(rest) = line.split("[SimClob] ").nth(1) else {
+            continue;
+        };
+        let words: Vec<&str> = rest.split_whitespace().collect();
+        let (Some(side), Some(filled), Some(token)) = (
+            words.first(),
+            words.get(2).and_then(|f| Decimal::from_str(f).ok()),
+            words.last().and_then(|w| w.strip_prefix("token_id=")),
+        ) else {
+            continue;
+        };
+        *fills
+            .entry((token.to_string(), side.to_string()))
+            .or_default() += filled;
+    }
+    fills
+}
+
+/// Dry run with book-matched fills and every fault of the chaos feature, over [Run::start]'s live
+/// configuration.
+const CHAOS_ENV: [(&str, &str); 12] = [
+    ("MM_DRY_RUN", "true"),
+    ("MM_DRY_RUN_SIM_FILLS", "true"),
+    ("MM_CHAOS", "true"),
+    ("MM_CHAOS_LATENCY_MIN_MS", "20"),
+    ("MM_CHAOS_LATENCY_MAX_MS", "300"),
+    ("MM_CHAOS_400_PCT", "5"),
+    ("MM_CHAOS_429_PCT", "10"),
+    ("MM_CHAOS_500_PCT", "20"),
+    ("MM_CHAOS_500_PLACED_PCT", "50"),
+    ("MM_CHAOS_PARTIAL_PCT", "30"),
+    ("MM_CHAOS_WS_GAP_PCT", "2"),
+    ("MM_CHAOS_WS_GAP_MS", "500"),
+];
+
+#[tokio::test]
+async fn never_double_buys_or_over_sells() {
+    let logs = Captured::default();
+    let writer = logs.clone();
+    let _logs = tracing::subscriber::set_default(
+        tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_env_filter("info")
+            .with_writer(move || writer.clone())
+            .finish(),
+    );
+
+    let run = Run::start("chaos", "9101", "9102", 15).await;
+    for (k, v) in CHAOS_ENV {
+        std::env::set_var(k, v);
+    }
+    let mut markets = vec![(run.slug.clone(), run.up.clone(), run.down.clone())];
+    for i in 1..6 {
+        let market = (
+            format!("mock-chaos-{}", i),
+            format!("91{}1", i),
+            format!("91{}2", i),
+        );
+        run.mock
+            .add_market(&market.0, &market.1, &market.2, START, CLOSE);
+        markets.push(market);
+    }
+    let mut engines = Vec::new();
+    for (slug, up, down) in &markets {
+        run.mock
+            .set_book(up, &[("0.91", "100")], &[("0.92", "100")]);
+        run.mock
+            .set_book(down, &[("0.06", "100")], &[("0.08", "100")]);
+        engines.push(run.spawn_market(slug));
+    }
+    let bought = |token: &str| {
+        sim_fills(&logs.lines())
+            .get(&(token.to_string(), "BUY".to_string()))
+            .copied()
+            .unwrap_or_default()
+    };
+    assert!(
+        wait_for(10, || markets
+            .iter()
+            .any(|(_, up, _)| bought(up) > Decimal::ZERO))
+        .await,
+        "no entry filled on any market"
+    );
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    // Half the markets rally through the TP, half drop through the SL, and resolve that way.
+    for (i, (slug, up, _)) in markets.iter().enumerate() {
+        if i % 2 == 0 {
+            run.mock
+                .set_book(up, &[("0.98", "100")], &[("0.99", "100")]);
+        } else {
+            run.mock
+                .set_book(up, &[("0.78", "100")], &[("0.79", "100")]);
+        }
+        run.mock.resolve(slug, i % 2 == 0);
+    }
+    for engine in engines {
+        run.finish(engine).await;
+    }
+
+    let lines = logs.lines();
+    let fills = sim_fills(&lines);
+    let total = |token: &str, side: &str| {
+        fills
+            .get(&(token.to_string(), side.to_string()))
+            .copied()
+            .unwrap_or_default()
+    };
+    for (slug, up, down) in &markets {
+        let bought = total(up, "BUY") + total(down, "BUY");
+        assert!(bought <= dec!(5), "{} bought {} sh", slug, bought);
+        for token in [up, down] {
+            let (bought, sold) = (total(token, "BUY"), total(token, "SELL"));
+            assert!(
+                sold <= bought,
+                "{} sold {} of {} bought",
+                token,
+                sold,
+                bought
+            );
+        }
+    }
+    let over_sells: Vec<&String> = lines.iter().filter(|l| l.contains("(sim: hold")).collect();
+    assert!(
+        over_sells.is_empty(),
+        "sells above the holdings: {:?}",
+        over_sells
+    );
+}
@@ -0,0 +1,90 @@
+//! Order errors classified from the CLOB's error body: the `code` when the API sends one, else
+//! the `error` / `errorMsg` message, else the bare text.
+
+use sniper_core::clob::ClobError;
+
+#[test]
+fn insufficient_balance() {
+    for body in [
+        r#"{"error": "not enough balance / allowance"}"#,
+        r#"{"success": false, "errorMsg": "not enough balance / allowance"}"#,
+        r#"{"code": "INVALID_ORDER_NOT_ENOUGH_BALANCE", "error": "balance check failed"}"#,
+        "INVALID_ORDER_NOT_ENOUGH_BALANCE",
+        "not enough balance / allowance (sim: hold 0, sell 5)",
+    ] {
+        assert_eq!(
+            ClobError::parse(body),
+            ClobError::InsufficientBalance,
+            "{}",
+            body
+        );
+    }
+}
+
+#[test]
+fn invalid_amounts() {
+    for body in [
+        r#"{"error": "invalid amounts, the market buy order maker amount supports a max accuracy of 2 decimals, taker amount a max of 4 decimals"}"#,
+        "Invalid amounts",
+    ] {
+        assert_eq!(
+            ClobError::parse(body),
+            ClobError::InvalidAmounts,
+            "{}",
+            body
+        );
+    }
+}
+
+#[test]
+fn no_match() {
+    for body in [
+        r#"{"error": "order couldn't be fully filled. FOK orders are fully filled or killed."}"#,
+        r#"{"error": "no orders found to match with FAK order. FAK orders are partially filled or killed if no match is found."}"#,
+        r#"{"errorMsg": "FOK_ORDER_NOT_FILLED_ERROR"}"#,
+        r#"{"code": "FOK_ORDER_NOT_FILLED_ERROR"}"#,
+    ] {
+        assert_eq!(ClobError::parse(body), ClobError::NoMatch, "{}", body);
+    }
+}
+
+#[test]
+fn duplicate() {
+    for body in [
+        r#"{"error": "order 0xab12 is invalid. Duplicated."}"#,
+        r#"{"code": "INVALID_ORDER_DUPLICATED"}"#,
+    ] {
+        assert_eq!(ClobError::parse(body), ClobError::Duplicate, "{}", body);
+    }
+}
+
+#[test]
+fn other_keeps_the_api_message() {
+    assert_eq!(
+        ClobError::parse(r#"{"error": "invalid order payload"}"#),
+        ClobError::Other("invalid order payload".to_string())
+    );
+    assert_eq!(
+        ClobError::parse(r#"{"code": "MARKET_NOT_READY"}"#),
+        ClobError::Other("MARKET_NOT_READY".to_string())
+    );
+    assert_eq!(
+        ClobError::parse("HTTP 502: bad gateway"),
+        ClobError::Other("HTTP 502: bad gateway".to_string())
+    );
+}
+
+#[test]
+fn a_message_merely_mentioning_a_variant_is_other() {
+    // Substrings no longer decide: only the API's codes and message starts do.
+    for body in [
+        r#"{"error": "the order owner has to be the owner of the API KEY (allowance ok)"}"#,
+        r#"{"error": "invalid order: not duplicated, price out of range"}"#,
+    ] {
+        assert!(
+            matches!(ClobError::parse(body), ClobError::Other(_)),
+            "{}",
+            body
+        );
+    }
+}
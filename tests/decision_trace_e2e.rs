@@ -0,0 +1,68 @@
+//! Decision trace (MM_DECISION_TRACE): every entry evaluation is kept in memory with its inputs
+//! and actions, and written to `decision_trace_*.jsonl` when a dump is requested.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::Run;
+use mock_clob::wait_for;
+use rust_decimal_macros::dec;
+use sniper_core::decision_trace::request_dump;
+use std::path::Path;
+
+fn records(file: &Path) -> Vec<serde_json::Value> {
+    std::fs::read_to_string(file)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dumps_the_evaluations_on_request() {
+    let run = Run::start("trace", "8401", "8402", 10).await;
+    // In range but too thin: evaluated, nothing bought.
+    run.mock
+        .set_book(&run.up, &[("0.91", "100")], &[("0.92", "1")]);
+    run.mock
+        .set_book(&run.down, &[("0.06", "100")], &[("0.08", "100")]);
+    std::env::set_var("MM_DECISION_TRACE", "20");
+
+    let engine = run.spawn();
+    run.at_secs_to_close(5).await;
+    request_dump();
+    let dumps = || run.files("decision_trace_");
+    assert!(wait_for(5, || dumps().len() == 1).await, "no dump");
+    let first = records(&dumps()[0]);
+    assert!(
+        !first.is_empty() && first.len() <= 20,
+        "{} records",
+        first.len()
+    );
+    let last = first.last().unwrap();
+    assert_eq!(last["slug"], run.slug);
+    assert_eq!(last["book"]["up"]["ask"], "0.92");
+    assert_eq!(last["book"]["up"]["ask_size"], "1");
+    assert_eq!(last["flags"]["interval_phase"], "idle");
+    assert_eq!(last["actions"].as_array().unwrap().len(), 0);
+
+    // Liquidity arrives: the evaluation that buys is traced with its action.
+    run.mock
+        .set_book(&run.up, &[("0.91", "100")], &[("0.92", "100")]);
+    assert!(
+        wait_for(4, || run.mock.shares(&run.up) > dec!(0)).await,
+        "no entry"
+    );
+    request_dump();
+    assert!(wait_for(5, || dumps().len() == 2).await, "no second dump");
+    let second = records(&dumps()[1]);
+    let buy = second
+        .iter()
+        .find(|r| !r["actions"].as_array().unwrap().is_empty())
+        .expect("no evaluation with an action");
+    assert_eq!(buy["actions"][0]["buy"], "Up");
+    assert_eq!(buy["actions"][0]["order_type"], "FAK");
+    assert_eq!(buy["book"]["up"]["ask_size"], "100");
+
+    run.finish(engine).await;
+}
@@ -0,0 +1,35 @@
+//! Declined entries: with the ask in range but too thin to buy, no order goes out and the interval
+//! summary counts the ticks per gating reason.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::Run;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn counts_why_the_entry_was_declined() {
+    let run = Run::start("declined", "8301", "8302", 6).await;
+    // In range (0.90-0.95) but under the 5-share minimum.
+    run.mock
+        .set_book(&run.up, &[("0.91", "100")], &[("0.92", "1")]);
+    run.mock
+        .set_book(&run.down, &[("0.06", "100")], &[("0.08", "100")]);
+    std::env::set_var("MM_SESSION_LOG", "true");
+
+    run.finish(run.spawn()).await;
+    assert!(
+        run.mock.orders().is_empty(),
+        "bought: {:?}",
+        run.mock.orders()
+    );
+
+    let summaries = run.session_events("interval_summary");
+    let declined = &summaries.first().expect("no interval summary")["declined_entries"];
+    assert!(
+        declined["too_soon_after_open"].as_u64().unwrap() > 0,
+        "{}",
+        declined
+    );
+    assert!(declined["strategy"].as_u64().unwrap() > 0, "{}", declined);
+    assert!(declined.get("position_open").is_none(), "{}", declined);
+}
@@ -0,0 +1,39 @@
+//! Dry-run report (MM_DRY_RUN_REPORT) from a dry run on the mock CLOB's book: the run ends with
+//! the market and leaves `dry_run_report_<time>.json` with the simulated trade.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::{read_json, Run};
+use rust_decimal_macros::dec;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn writes_the_simulated_trades_when_the_run_ends() {
+    let run = Run::start("dry-run", "5001", "5002", 8).await;
+    run.mock
+        .set_book(&run.up, &[("0.91", "100")], &[("0.93", "100")]);
+    run.mock
+        .set_book(&run.down, &[("0.06", "100")], &[("0.08", "100")]);
+    std::env::set_var("MM_DRY_RUN", "true");
+
+    let engine = run.spawn();
+    // Simulated entry (3 s after the switch), then a bid over the TP: nothing reaches the
+    // exchange.
+    run.at_secs_to_close(3).await;
+    run.mock
+        .set_book(&run.up, &[("0.98", "100")], &[("0.99", "100")]);
+    run.mock.resolve(&run.slug, true);
+    run.finish(engine).await;
+    assert_eq!(run.mock.request_count("POST /order"), 0);
+
+    let files = run.files("dry_run_report_");
+    let report = read_json(files.first().expect("no dry_run_report_*.json")).unwrap();
+    assert_eq!(report["fill_model"]["mode"], "limit_price");
+    assert_eq!(report["entries"], 1);
+    let trade = &report["trades"][0];
+    assert_eq!(trade["exit_type"], "TP");
+    assert_eq!(trade["side"], "Up");
+    assert!(trade["secs_to_close_at_entry"].as_u64().unwrap() <= 5);
+    let gross: rust_decimal::Decimal = report["gross_pnl_usd"].as_str().unwrap().parse().unwrap();
+    assert!(gross > dec!(0));
+}
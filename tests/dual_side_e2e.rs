@@ -0,0 +1,68 @@
+//! Dual-side entry against the mock CLOB: the FOK pair goes out as one batch from the order
+//! manager, so the loop keeps reading the book while the batch (and its entry jitter) is in
+//! flight, and a size jitter never buys more than MM_SIZE_SHARES on either leg.
+
+mod mock_clob;
+
+use mock_clob::{live_env, now_unix, wait_for, MockClob};
+use rust_decimal_macros::dec;
+use std::path::Path;
+use std::time::Duration;
+
+fn status(path: &Path) -> Option<serde_json::Value> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pair_entry_does_not_block_the_loop() {
+    let mock = MockClob::start().await;
+    let (up, down) = ("8701", "8702");
+    let now = now_unix();
+    mock.add_market("mock-dual-side", up, down, now - 60, now + 15);
+    mock.set_collateral(dec!(100));
+    mock.set_book(up, &[("0.44", "100")], &[("0.45", "100")]);
+    mock.set_book(down, &[("0.44", "100")], &[("0.45", "100")]);
+    mock.set_delay("POST /orders", 3000);
+    live_env(&mock);
+    let dir = std::env::temp_dir().join(format!("sniper-dual-side-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("status.json");
+    std::env::set_var("MM_TARGET_SLUG", "mock-dual-side");
+    std::env::set_var("MM_DUAL_SIDE_ENTRY", "true");
+    std::env::set_var("MM_ENTRY_JITTER_MS", "1000");
+    std::env::set_var("MM_SIZE_JITTER_PERCENT", "50");
+    std::env::set_var("MM_STATUS_FILE", &file);
+    std::env::set_var("MM_STATUS_SECS", "1");
+
+    let config = sniper_core::config::load_config().unwrap();
+    let engine = tokio::spawn(sniper_core::runner::run_with_config(config));
+    assert!(wait_for(20, || mock.request_count("POST /orders") == 1).await);
+    // The batch is held by the mock; the loop still sees the book move.
+    mock.set_book(up, &[("0.40", "100")], &[("0.45", "100")]);
+    assert!(
+        wait_for(2, || status(&file)
+            .is_some_and(|s| s["book"]["up"]["bid"] == "0.40"))
+        .await,
+        "loop blocked on the pair batch: status {:?}",
+        status(&file)
+    );
+    assert_eq!(status(&file).unwrap()["orders_in_flight"], true);
+    assert!(
+        wait_for(10, || mock.shares(up) > dec!(0)
+            && mock.shares(down) > dec!(0))
+        .await,
+        "no pair fill: orders {:?}",
+        mock.orders()
+    );
+    assert_eq!(mock.shares(up), dec!(5));
+    assert_eq!(mock.shares(down), dec!(5));
+
+    tokio::time::timeout(Duration::from_secs(30), engine)
+        .await
+        .expect("engine did not stop at close")
+        .unwrap()
+        .unwrap();
+    assert_eq!(mock.request_count("POST /orders"), 1);
+    let _ = std::fs::remove_dir_all(&dir);
+}
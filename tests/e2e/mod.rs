@@ -0,0 +1,179 @@
+//! Shared setup of the engine end-to-end tests: one [MockClob] market traded by a full engine on a
+//! [SimClock], and readers for what the run leaves behind (session log, order audit, status file).
+//!
+//! The market sits at a fixed time ([START] to [CLOSE]) and the clock starts a few seconds before
+//! its close, so a run takes only as long as the test needs and book changes are pinned to market
+//! times with [Run::at_secs_to_close]. The clock is paced ([SimClock::paced]): the mock's WS feed,
+//! its delayed answers and the WS book's refresh timers run on the wall clock, and an unpaced run
+//! would reach the close before they arrive.
+
+#![allow(dead_code)]
+
+use crate::mock_clob::{live_env, MockClob};
+use rust_decimal_macros::dec;
+use sniper_core::clock::{Clock, SimClock};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Interval start of the test market (simulated unix seconds).
+pub const START: u64 = 1_800_000_000;
+/// Close of the test market: a 5-minute interval.
+pub const CLOSE: u64 = START + 300;
+
+/// One market on the mock CLOB, the engine's session log dir and its simulated clock.
+pub struct Run {
+    pub mock: MockClob,
+    pub clock: Arc<SimClock>,
+    pub slug: String,
+    pub up: String,
+    pub down: String,
+    /// Session log, order audit, status file and any other output of the run.
+    pub dir: PathBuf,
+}
+
+impl Run {
+    /// Market `mock-<name>` with Up / Down tokens `up` / `down`, 100 USDC and the live
+    /// configuration of [live_env], the clock `lead_secs` before the close (entries open 3 s after
+    /// the start); session files go to a fresh temp dir (MM_SESSION_LOG_DIR).
+    pub async fn start(name: &str, up: &str, down: &str, lead_secs: u64) -> Self {
+        let mock = MockClob::start().await;
+        let slug = format!("mock-{}", name);
+        mock.add_market(&slug, up, down, START, CLOSE);
+        mock.set_collateral(dec!(100));
+        live_env(&mock);
+        let dir = std::env::temp_dir().join(format!("sniper-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::env::set_var("MM_TARGET_SLUG", &slug);
+        std::env::set_var("MM_SESSION_LOG_DIR", &dir);
+        Self {
+            mock,
+            clock: Arc::new(SimClock::paced((CLOSE - lead_secs) * 1000)),
+            slug,
+            up: up.to_string(),
+            down: down.to_string(),
+            dir,
+        }
+    }
+
+    /// Write the status file to `<dir>/status.json` every second.
+    pub fn with_status_file(&self) {
+        std::env::set_var("MM_STATUS_FILE", self.dir.join("status.json"));
+        std::env::set_var("MM_STATUS_SECS", "1");
+    }
+
+    /// Start the engine with the configuration from the environment.
+    pub fn spawn(&self) -> JoinHandle<anyhow::Result<()>> {
+        let config = sniper_core::config::load_config().unwrap();
+        tokio::spawn(sniper_core::runner::run_with_clock(
+            config,
+            self.clock.clone(),
+        ))
+    }
+
+    /// Start another engine on `slug`, a market added to the mock with [START] / [CLOSE]: on its
+    /// own clock from the run's current time, its session files in `<dir>/<slug>`.
+    pub fn spawn_market(&self, slug: &str) -> JoinHandle<anyhow::Result<()>> {
+        let dir = self.dir.join(slug);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::env::set_var("MM_TARGET_SLUG", slug);
+        std::env::set_var("MM_SESSION_LOG_DIR", &dir);
+        let config = sniper_core::config::load_config().unwrap();
+        tokio::spawn(sniper_core::runner::run_with_clock(
+            config,
+            Arc::new(SimClock::paced(self.clock.now_ms())),
+        ))
+    }
+
+    /// Seconds left to the close on the run's clock.
+    pub fn secs_to_close(&self) -> u64 {
+        CLOSE.saturating_sub(self.clock.now_unix())
+    }
+
+    /// Wait until the run's clock is `secs` from the close.
+    pub async fn at_secs_to_close(&self, secs: u64) {
+        while self.clock.now_ms() < (CLOSE - secs) * 1000 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    /// Wait until the run's clock is `secs` past the close.
+    pub async fn at_secs_after_close(&self, secs: u64) {
+        while self.clock.now_ms() < (CLOSE + secs) * 1000 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    /// Wait for the engine to stop at the close.
+    pub async fn finish(&self, engine: JoinHandle<anyhow::Result<()>>) {
+        tokio::time::timeout(Duration::from_secs(30), engine)
+            .await
+            .expect("engine did not stop at close")
+            .unwrap()
+            .unwrap();
+    }
+
+    /// Events of every `<prefix>*.jsonl` file of the run (`session_`, `orders_`, ...), in file
+    /// name order.
+    pub fn events(&self, prefix: &str) -> Vec<serde_json::Value> {
+        let mut files = self.files(prefix);
+        files.retain(|p| p.extension().is_some_and(|e| e == "jsonl"));
+        assert!(
+            !files.is_empty(),
+            "no {}*.jsonl in {}",
+            prefix,
+            self.dir.display()
+        );
+        files
+            .iter()
+            .flat_map(|p| {
+                std::fs::read_to_string(p)
+                    .unwrap()
+                    .lines()
+                    .map(|l| serde_json::from_str(l).unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Session log events of type `event` (`close`, `interval_summary`, ...).
+    pub fn session_events(&self, event: &str) -> Vec<serde_json::Value> {
+        self.events("session_")
+            .into_iter()
+            .filter(|e| e["event"] == event)
+            .collect()
+    }
+
+    /// Files of the run whose name starts with `prefix`, sorted by name.
+    pub fn files(&self, prefix: &str) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with(prefix))
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Last status snapshot ([Run::with_status_file]).
+    pub fn status(&self) -> Option<serde_json::Value> {
+        read_json(&self.dir.join("status.json"))
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// A JSON file, None while it is missing or half written.
+pub fn read_json(path: &Path) -> Option<serde_json::Value> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
@@ -0,0 +1,50 @@
+//! Full engine against the mock CLOB: the live client signs and posts real orders, the book comes
+//! over the WS market channel, and the market over Gamma, all from [mock_clob::MockClob].
+
+mod mock_clob;
+
+use mock_clob::{live_env, now_unix, wait_for, MockClob};
+use rust_decimal_macros::dec;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn buys_in_range_and_takes_profit() {
+    let mock = MockClob::start().await;
+    let (up, down) = ("1001", "1002");
+    let now = now_unix();
+    mock.add_market("mock-e2e", up, down, now - 60, now + 25);
+    mock.set_collateral(dec!(100));
+    mock.set_book(up, &[("0.91", "100")], &[("0.93", "100")]);
+    mock.set_book(down, &[("0.06", "100")], &[("0.08", "100")]);
+    live_env(&mock);
+    std::env::set_var("MM_TARGET_SLUG", "mock-e2e");
+
+    let config = sniper_core::config::load_config().unwrap();
+    let engine = tokio::spawn(sniper_core::runner::run_with_config(config));
+
+    assert!(
+        wait_for(30, || mock.shares(up) == dec!(5)).await,
+        "no entry: orders {:?}",
+        mock.orders()
+    );
+    assert!(mock.ws_subscribed(up) && mock.ws_subscribed(down));
+    assert_eq!(mock.collateral(), dec!(100) - dec!(5) * dec!(0.93));
+
+    // The bid jumps over the TP price: the engine sells the position (down to dust).
+    mock.set_book(up, &[("0.98", "100")], &[("0.99", "100")]);
+    assert!(
+        wait_for(10, || mock.shares(up) < dec!(0.1)).await,
+        "no TP: orders {:?}",
+        mock.orders()
+    );
+    assert!(mock.collateral() > dec!(100));
+
+    // A fixed slug: the engine stops once the market closes.
+    mock.resolve("mock-e2e", true);
+    tokio::time::timeout(Duration::from_secs(30), engine)
+        .await
+        .expect("engine did not stop at close")
+        .unwrap()
+        .unwrap();
+    assert!(mock.request_count("POST /order") >= 2);
+}
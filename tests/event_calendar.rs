@@ -0,0 +1,93 @@
+//! Blackout calendars from ICS: UTC times, times local to a TZID (daylight saving included), and
+//! floating times rejected instead of read as UTC.
+
+use sniper_core::event_calendar::EventCalendar;
+
+fn load_ics(name: &str, events: &str) -> anyhow::Result<EventCalendar> {
+    let path = std::env::temp_dir().join(format!("sniper-{}-{}.ics", name, std::process::id()));
+    let text = format!(
+        "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n",
+        events
+    );
+    std::fs::write(&path, text).unwrap();
+    let calendar = EventCalendar::load(path.to_str().unwrap());
+    let _ = std::fs::remove_file(&path);
+    calendar
+}
+
+fn event(dtstart: &str, dtend: &str, summary: &str) -> String {
+    format!(
+        "BEGIN:VEVENT\r\n{}\r\n{}\r\nSUMMARY:{}\r\nEND:VEVENT\r\n",
+        dtstart, dtend, summary
+    )
+}
+
+#[test]
+fn utc_times() {
+    let cal = load_ics(
+        "utc",
+        &event("DTSTART:20261028T180000Z", "DTEND:20261028T190000Z", "FOMC"),
+    )
+    .unwrap();
+    // 2026-10-28 18:00 / 19:00 UTC.
+    let b = cal.blackout_for(1_793_210_400, 1_793_210_400).unwrap();
+    assert_eq!((b.start_unix, b.end_unix), (1_793_210_400, 1_793_214_000));
+    assert_eq!(b.name, "FOMC");
+}
+
+#[test]
+fn tzid_times_are_local_to_the_zone() {
+    let events = [
+        // EDT (UTC−4): 14:00 local = 18:00 UTC.
+        event(
+            "DTSTART;TZID=America/New_York:20261028T140000",
+            "DTEND;TZID=America/New_York:20261028T150000",
+            "FOMC",
+        ),
+        // EST (UTC−5) after the November switch: 08:30 local = 13:30 UTC.
+        event(
+            "DTSTART;TZID=\"America/New_York\":20261112T083000",
+            "DTEND;TZID=America/New_York:20261112T090000",
+            "CPI",
+        ),
+    ]
+    .concat();
+    let cal = load_ics("tzid", &events).unwrap();
+    assert_eq!(cal.len(), 2);
+    let fomc = cal.blackout_for(1_793_210_400, 1_793_210_400).unwrap();
+    assert_eq!(fomc.name, "FOMC");
+    assert_eq!(
+        (fomc.start_unix, fomc.end_unix),
+        (1_793_210_400, 1_793_214_000)
+    );
+    // 2026-11-12 13:30 UTC.
+    let cpi = cal.blackout_for(1_794_490_200, 1_794_490_200).unwrap();
+    assert_eq!(cpi.name, "CPI");
+    assert_eq!(cpi.start_unix, 1_794_490_200);
+    // Read as UTC, 14:00 would have been four hours early.
+    assert!(cal.blackout_for(1_793_196_000, 1_793_196_000).is_none());
+}
+
+#[test]
+fn floating_times_are_rejected() {
+    let err = load_ics(
+        "floating",
+        &event("DTSTART:20261028T140000", "DTEND:20261028T150000", "FOMC"),
+    )
+    .unwrap_err();
+    assert!(format!("{:#}", err).contains("floating time"), "{:#}", err);
+}
+
+#[test]
+fn unknown_tzid_is_rejected() {
+    let err = load_ics(
+        "bad-tzid",
+        &event(
+            "DTSTART;TZID=Mars/Olympus:20261028T140000",
+            "DTEND;TZID=Mars/Olympus:20261028T150000",
+            "FOMC",
+        ),
+    )
+    .unwrap_err();
+    assert!(format!("{:#}", err).contains("unknown TZID"), "{:#}", err);
+}
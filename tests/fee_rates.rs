@@ -0,0 +1,117 @@
+//! Fee rates per token (GET /fee-rate on the mock CLOB): each token keeps its own rate, a failed
+//! fetch leaves its token without one until a retry succeeds, and an engine whose market has a
+//! token without a rate declines its entries instead of signing with a guessed fee.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::Run;
+use mock_clob::MockClob;
+use rust_decimal_macros::dec;
+use sniper_core::book::public_client;
+use sniper_core::fee_rates::{FeeRates, FEE_RATE_RETRY_MS};
+use sniper_core::types::{EntrySide, ResolvedMarket};
+use std::time::Duration;
+
+fn market(up: &str, down: &str) -> ResolvedMarket {
+    ResolvedMarket {
+        slug: "mock-fees".to_string(),
+        condition_id: "0x01".to_string(),
+        close_time_unix: 0,
+        interval_start_unix: 0,
+        token_id_up: up.to_string(),
+        token_id_down: down.to_string(),
+        tick_size: dec!(0.01),
+        min_order_size: dec!(5),
+        neg_risk: false,
+    }
+}
+
+#[tokio::test]
+async fn keeps_each_tokens_rate() {
+    let mock = MockClob::start().await;
+    mock.set_fee_rate("7101", Some(0));
+    mock.set_fee_rate("7102", Some(100));
+    let http = public_client(Duration::from_secs(5)).unwrap();
+
+    let rates = FeeRates::fetch(&http, &mock.rest_url(), &market("7101", "7102"), None, 0).await;
+    assert!(rates.complete());
+    assert_eq!(rates.token("7101"), Some(0));
+    assert_eq!(rates.token("7102"), Some(100));
+    assert_eq!(rates.token("7103"), None);
+    assert_eq!(rates.side(EntrySide::Down), 100);
+    assert_eq!(rates.to_string(), "0/100");
+}
+
+#[tokio::test]
+async fn retries_a_failed_fetch_without_guessing() {
+    let mock = MockClob::start().await;
+    mock.set_fee_rate("7202", None);
+    let http = public_client(Duration::from_secs(5)).unwrap();
+
+    let mut rates =
+        FeeRates::fetch(&http, &mock.rest_url(), &market("7201", "7202"), None, 0).await;
+    assert!(!rates.complete());
+    assert_eq!(rates.token("7201"), Some(0));
+    assert_eq!(rates.token("7202"), None);
+    assert_eq!(rates.side(EntrySide::Down), 0);
+    assert_eq!(rates.to_string(), "0/?");
+
+    let fetched = mock.request_count("GET /fee-rate");
+    rates
+        .retry(&http, &mock.rest_url(), FEE_RATE_RETRY_MS - 1)
+        .await;
+    assert_eq!(mock.request_count("GET /fee-rate"), fetched);
+
+    mock.set_fee_rate("7202", Some(50));
+    rates
+        .retry(&http, &mock.rest_url(), FEE_RATE_RETRY_MS)
+        .await;
+    assert_eq!(mock.request_count("GET /fee-rate"), fetched + 1);
+    assert!(rates.complete());
+    assert_eq!(rates.token("7202"), Some(50));
+}
+
+#[tokio::test]
+async fn a_fixed_rate_is_not_fetched() {
+    let mock = MockClob::start().await;
+    let http = public_client(Duration::from_secs(5)).unwrap();
+
+    let rates = FeeRates::fetch(
+        &http,
+        &mock.rest_url(),
+        &market("7301", "7302"),
+        Some(30),
+        0,
+    )
+    .await;
+    assert_eq!(mock.request_count("GET /fee-rate"), 0);
+    assert_eq!(rates.token("7301"), Some(30));
+    assert_eq!(rates.token("7302"), Some(30));
+    assert_eq!(rates.to_string(), "30");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn declines_entries_while_a_fee_rate_is_unknown() {
+    let run = Run::start("fee-unknown", "7401", "7402", 6).await;
+    run.mock
+        .set_book(&run.up, &[("0.91", "100")], &[("0.92", "100")]);
+    run.mock
+        .set_book(&run.down, &[("0.06", "100")], &[("0.08", "100")]);
+    run.mock.set_fee_rate(&run.down, None);
+    std::env::set_var("MM_SESSION_LOG", "true");
+
+    run.finish(run.spawn()).await;
+    assert!(
+        run.mock.orders().is_empty(),
+        "bought: {:?}",
+        run.mock.orders()
+    );
+    let summaries = run.session_events("interval_summary");
+    let declined = &summaries.first().expect("no interval summary")["declined_entries"];
+    assert!(
+        declined["fee_rate_unknown"].as_u64().unwrap() > 0,
+        "{}",
+        declined
+    );
+}
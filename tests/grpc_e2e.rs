@@ -0,0 +1,133 @@
+//! gRPC executor service against the mock CLOB: books over GET /book and the WS feed, an order
+//! placed and one cancelled through the live client, and the positions it leaves.
+//! `cargo test --features grpc --test grpc_e2e`.
+#![cfg(feature = "grpc")]
+
+mod mock_clob;
+
+use mock_clob::{live_env, MockClob};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sniper_core::grpc::proto::executor_client::ExecutorClient;
+use sniper_core::grpc::proto::{
+    CancelOrderRequest, GetBookRequest, GetPositionsRequest, OrderType, PlaceOrderRequest, Side,
+    WatchBooksRequest,
+};
+use sniper_core::grpc::{serve_on, ExecutorService};
+use std::str::FromStr;
+use std::time::Duration;
+
+fn order(side: Side, price: &str, order_type: OrderType) -> PlaceOrderRequest {
+    PlaceOrderRequest {
+        token_id: "9101".to_string(),
+        side: side as i32,
+        price: price.to_string(),
+        size: "5".to_string(),
+        order_type: order_type as i32,
+        ..Default::default()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn trades_and_reads_through_the_service() {
+    let mock = MockClob::start().await;
+    let (up, down) = ("9101", "9102");
+    mock.set_book(up, &[("0.91", "100"), ("0.90", "50")], &[("0.92", "100")]);
+    mock.set_book(down, &[("0.06", "100")], &[("0.08", "100")]);
+    mock.set_collateral(dec!(100));
+    live_env(&mock);
+
+    let clob = sniper_core::clob::create_clob_client(false).unwrap();
+    let service = ExecutorService::new(clob, &mock.rest_url(), &mock.rest_url()).unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve_on(service, listener));
+    let mut client = ExecutorClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    let book = client
+        .get_book(GetBookRequest {
+            token_id: up.to_string(),
+            depth: 1,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(book.bids.len(), 1);
+    assert_eq!(
+        (book.bids[0].price.as_str(), book.asks[0].price.as_str()),
+        ("0.91", "0.92")
+    );
+
+    // The WS stream sends each token's book, then the change.
+    let mut books = client
+        .watch_books(WatchBooksRequest {
+            up_token_id: up.to_string(),
+            down_token_id: down.to_string(),
+            depth: 0,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let mut seen = Vec::new();
+    while seen.len() < 2 {
+        let snapshot = tokio::time::timeout(Duration::from_secs(5), books.message())
+            .await
+            .expect("no book on the stream")
+            .unwrap()
+            .unwrap();
+        seen.push(snapshot.token_id);
+    }
+    seen.sort();
+    assert_eq!(seen, [up, down]);
+    mock.set_book(up, &[("0.91", "100")], &[("0.93", "100")]);
+    let changed = tokio::time::timeout(Duration::from_secs(5), books.message())
+        .await
+        .expect("no change on the stream")
+        .unwrap()
+        .unwrap();
+    assert_eq!(changed.token_id, up);
+    assert_eq!(changed.asks[0].price, "0.93");
+    drop(books);
+
+    // FAK buy at the ask fills; a GTC sell above the bid rests and is cancelled.
+    let buy = client
+        .place_order(order(Side::Buy, "0.93", OrderType::Fak))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(buy.success, "{}", buy.error);
+    assert_eq!(Decimal::from_str(&buy.filled_size).unwrap(), dec!(5));
+    assert_eq!(mock.shares(up), dec!(5));
+    let ask = client
+        .place_order(order(Side::Sell, "0.99", OrderType::Gtc))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(ask.success && !ask.order_id.is_empty(), "{}", ask.error);
+    let cancelled = client
+        .cancel_order(CancelOrderRequest {
+            order_id: ask.order_id.clone(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(cancelled.canceled, [ask.order_id]);
+
+    let held = client
+        .get_positions(GetPositionsRequest {})
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(held.positions.len(), 1);
+    assert_eq!(held.positions[0].token_id, up);
+    assert_eq!(held.positions[0].size, "5");
+    assert_eq!(Decimal::from_str(&held.collateral).unwrap(), dec!(95.35));
+
+    let refused = client
+        .place_order(order(Side::Buy, "-1", OrderType::Fak))
+        .await
+        .unwrap_err();
+    assert!(refused.message().contains("price"), "{}", refused.message());
+}
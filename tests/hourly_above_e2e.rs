@@ -0,0 +1,50 @@
+//! Hourly "above $X" target against [mock_clob::MockClob]: the engine finds the event of the
+//! current hour, reads the spot and only buys the side of a strike the spot is on.
+
+mod mock_clob;
+
+use mock_clob::{live_env, now_unix, wait_for, MockClob};
+use rust_decimal_macros::dec;
+use sniper_core::hourly::{hour_close_unix, hourly_event_slug};
+use sniper_core::types::HourlyAsset;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn buys_only_the_strike_side_the_spot_is_on() {
+    // Not in the last seconds of the hour: the engine would move on to the next event.
+    let left = hour_close_unix(now_unix()) - now_unix();
+    if left < 30 {
+        tokio::time::sleep(Duration::from_secs(left + 1)).await;
+    }
+    let mock = MockClob::start().await;
+    let close = hour_close_unix(now_unix());
+    let event = hourly_event_slug(HourlyAsset::Eth, close);
+    mock.add_strike_event(
+        &event,
+        close,
+        &[("3,000", "3001", "3002"), ("4,000", "4001", "4002")],
+    );
+    mock.set_spot(dec!(3500));
+    // 4,000 Yes has the highest ask in range, but the spot is below that strike.
+    mock.set_book("3001", &[("0.90", "100")], &[("0.92", "100")]);
+    mock.set_book("3002", &[("0.07", "100")], &[("0.09", "100")]);
+    mock.set_book("4001", &[("0.92", "100")], &[("0.94", "100")]);
+    mock.set_book("4002", &[("0.05", "100")], &[("0.07", "100")]);
+    mock.set_collateral(dec!(100));
+    live_env(&mock);
+    std::env::set_var("MM_TARGET_HOURLY_ABOVE", "eth");
+    std::env::set_var("MM_SECONDS_BEFORE_CLOSE", "3600");
+
+    let config = sniper_core::config::load_config().unwrap();
+    assert_eq!(config.market_slug, event);
+    let engine = tokio::spawn(sniper_core::runner::run_with_config(config));
+
+    assert!(
+        wait_for(30, || mock.shares("3001") == dec!(5)).await,
+        "no entry on the 3,000 strike: orders {:?}",
+        mock.orders()
+    );
+    assert!(mock.orders().iter().all(|o| o.token_id == "3001"));
+    assert_eq!(mock.shares("4001"), dec!(0));
+    engine.abort();
+}
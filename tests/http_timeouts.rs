@@ -0,0 +1,48 @@
+//! Per-endpoint REST timeouts (MM_HTTP_TIMEOUT_*_MS): a slow endpoint times out on its own
+//! deadline, is retried when safe, and the caller gets a [TimeoutError] it can tell apart.
+
+mod mock_clob;
+
+use mock_clob::MockClob;
+use sniper_core::book::fetch_order_book;
+use sniper_core::rate_limit::EndpointClass;
+use sniper_core::retry::{is_timeout, TimeoutError};
+use std::time::{Duration, Instant};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn slow_book_times_out_on_its_own_deadline() {
+    std::env::set_var("MM_HTTP_TIMEOUT_BOOK_MS", "300");
+    std::env::set_var("MM_HTTP_RETRY_MAX_ATTEMPTS", "2");
+    std::env::set_var("MM_HTTP_RETRY_MAX_MS", "0");
+    let mock = MockClob::start().await;
+    mock.set_book("8701", &[("0.49", "100")], &[("0.51", "100")]);
+    // The client's own timeout is much longer than the book's.
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap();
+
+    mock.set_delay("GET /book", 2000);
+    let started = Instant::now();
+    let err = fetch_order_book(&http, &mock.rest_url(), "8701")
+        .await
+        .expect_err("slow book answered");
+    assert!(
+        started.elapsed() < Duration::from_millis(1500),
+        "{:?}",
+        started.elapsed()
+    );
+    assert!(is_timeout(&err), "{:#}", err);
+    let timeout = err.downcast_ref::<TimeoutError>().unwrap();
+    assert_eq!(timeout.class, EndpointClass::Book);
+    assert_eq!(timeout.after, Some(Duration::from_millis(300)));
+    assert!(timeout.retryable());
+    assert_eq!(err.to_string(), "GET /book timed out after 300 ms");
+    assert_eq!(mock.request_count("GET /book"), 2, "one retry");
+
+    mock.set_delay("GET /book", 100);
+    let book = fetch_order_book(&http, &mock.rest_url(), "8701")
+        .await
+        .unwrap();
+    assert_eq!(book.asks.unwrap()[0].price, "0.51");
+}
@@ -0,0 +1,56 @@
+//! Instance lock: a live engine refuses to trade a market target another process already holds
+//! for the same wallet, while engines of one process share the lock.
+
+mod mock_clob;
+
+use mock_clob::{live_env, now_unix, test_wallet, MockClob};
+use rust_decimal_macros::dec;
+use sniper_core::instance_lock::{lock_path, target_key, InstanceLock};
+use sniper_core::types::MarketTarget;
+use std::fs::File;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn refuses_to_trade_a_market_another_instance_holds() {
+    let mock = MockClob::start().await;
+    let now = now_unix();
+    mock.add_market("mock-locked", "8101", "8102", now - 60, now + 240);
+    mock.set_collateral(dec!(100));
+    mock.set_book("8101", &[("0.91", "100")], &[("0.92", "100")]);
+    mock.set_book("8102", &[("0.06", "100")], &[("0.08", "100")]);
+    live_env(&mock);
+    let dir = std::env::temp_dir().join(format!("sniper-instance-lock-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::env::set_var("MM_TARGET_SLUG", "mock-locked");
+    std::env::set_var("MM_INSTANCE_LOCK_DIR", &dir);
+
+    // The other instance: its own open file, locked.
+    let key = target_key(&MarketTarget::Slug("mock-locked".to_string()));
+    let path = lock_path(&dir, &test_wallet(), &key);
+    std::fs::write(&path, "{\"pid\":1}\n").unwrap();
+    let other = File::options().read(true).write(true).open(&path).unwrap();
+    other.lock().unwrap();
+
+    let config = sniper_core::config::load_config().unwrap();
+    let err = sniper_core::runner::run_with_config(config)
+        .await
+        .expect_err("started next to another instance");
+    assert!(
+        err.to_string().contains("another instance"),
+        "unexpected error: {:#}",
+        err
+    );
+    assert!(err.to_string().contains("\"pid\":1"));
+    assert!(mock.orders().is_empty(), "traded: {:?}", mock.orders());
+
+    // Once it is gone the lock is free, and shared within this process.
+    drop(other);
+    let first = InstanceLock::acquire(&dir, &test_wallet(), &key, 0).unwrap();
+    let second = InstanceLock::acquire(&dir, &test_wallet(), &key, 0).unwrap();
+    assert_eq!(first.path(), path.as_path());
+    let outsider = File::options().read(true).write(true).open(&path).unwrap();
+    assert!(outsider.try_lock().is_err());
+    drop((first, second));
+    assert!(outsider.try_lock().is_ok());
+    let _ = std::fs::remove_dir_all(&dir);
+}
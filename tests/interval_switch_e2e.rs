@@ -0,0 +1,81 @@
+//! Full engine on the rolling BTC 5-minute markets of [mock_clob::MockClob], across an interval
+//! boundary: it moves to the next market (Gamma lookup and WS resubscribe) on its own.
+//!
+//! On a [SimClock] the boundary is reached in seconds. The wall-clock run is ignored by default
+//! (it waits for the next boundary, up to ~5 min):
+//! `cargo test --test interval_switch_e2e -- --ignored`.
+
+mod mock_clob;
+
+use mock_clob::{interval_start, interval_tokens, live_env, now_unix, wait_for, MockClob};
+use rust_decimal_macros::dec;
+use sniper_core::clock::{Clock, SimClock};
+use std::sync::Arc;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn switches_to_the_next_interval_on_a_simulated_clock() {
+    let mock = MockClob::start().await;
+    mock.list_interval_markets(mock_clob::BTC_5M_PREFIX);
+    // A fixed interval far from the wall clock: only the simulated time drives the switch.
+    let start = 1_800_000_000;
+    for s in [start, start + 300] {
+        let (up, down) = interval_tokens(s);
+        mock.set_book(&up, &[("0.91", "100")], &[("0.93", "100")]);
+        mock.set_book(&down, &[("0.06", "100")], &[("0.08", "100")]);
+    }
+    mock.set_collateral(dec!(100));
+    live_env(&mock);
+
+    let clock = Arc::new(SimClock::new((start + 240) * 1000));
+    let config = sniper_core::config::load_config().unwrap();
+    let engine = tokio::spawn(sniper_core::runner::run_with_clock(config, clock.clone()));
+
+    let (up, down) = interval_tokens(start);
+    let (next_up, next_down) = interval_tokens(start + 300);
+    assert!(
+        wait_for(5, || mock.ws_subscribed(&up) && mock.ws_subscribed(&down)).await,
+        "no subscription to the first interval"
+    );
+    assert!(
+        wait_for(30, || mock.ws_subscribed(&next_up)
+            && mock.ws_subscribed(&next_down))
+        .await,
+        "no switch to the next interval at sim time {}",
+        clock.now_unix()
+    );
+    assert!(clock.now_unix() >= start + 300);
+    assert!(!engine.is_finished());
+    engine.abort();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "runs across a 5-minute interval boundary"]
+async fn enters_and_switches_to_the_next_interval() {
+    let mock = MockClob::start().await;
+    mock.list_interval_markets(mock_clob::BTC_5M_PREFIX);
+    let start = interval_start(now_unix());
+    for s in [start, start + 300] {
+        let (up, down) = interval_tokens(s);
+        mock.set_book(&up, &[("0.91", "100")], &[("0.93", "100")]);
+        mock.set_book(&down, &[("0.06", "100")], &[("0.08", "100")]);
+    }
+    mock.set_collateral(dec!(100));
+    live_env(&mock);
+
+    let config = sniper_core::config::load_config().unwrap();
+    let engine = tokio::spawn(sniper_core::runner::run_with_config(config));
+
+    let (up, _) = interval_tokens(start);
+    let (next_up, next_down) = interval_tokens(start + 300);
+    assert!(
+        wait_for(330, || mock.ws_subscribed(&next_up)
+            && mock.ws_subscribed(&next_down))
+        .await,
+        "no switch to the next interval: orders {:?}",
+        mock.orders()
+    );
+    // Entered in the last 20 s of the first interval and held through its close (no TP / SL).
+    assert_eq!(mock.shares(&up), dec!(5));
+    assert!(!engine.is_finished());
+    engine.abort();
+}
@@ -0,0 +1,167 @@
+//! Inventory: sells consume lots oldest first (a partial close leaves the rest of a lot open),
+//! `unfill` drops the newest lots without a trade, and every position is one trade in the stats
+//! however many lots and sells it took to close.
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sniper_core::inventory::Inventory;
+use sniper_core::types::EntrySide;
+
+/// Two lots of token "t": 10 @ 0.80 (t=1) then 10 @ 0.90 (t=2).
+fn two_lots() -> Inventory {
+    let mut inv = Inventory::default();
+    inv.add_lot("t", EntrySide::Up, dec!(0.80), dec!(10), 1);
+    inv.add_lot("t", EntrySide::Up, dec!(0.90), dec!(10), 2);
+    inv
+}
+
+#[test]
+fn sells_close_the_oldest_lots_first() {
+    let mut inv = two_lots();
+    let closed = inv.close("t", dec!(15), dec!(0.95));
+    let parts: Vec<_> = closed.iter().map(|c| (c.entry_price, c.size)).collect();
+    assert_eq!(parts, [(dec!(0.80), dec!(10)), (dec!(0.90), dec!(5))]);
+    assert_eq!(closed[1].entry_ms, 2);
+    // 5 of the second lot are still open.
+    assert_eq!(inv.size("t"), dec!(5));
+    assert_eq!(inv.avg_entry("t"), Some(dec!(0.90)));
+    let lots: Vec<_> = inv.lots("t").map(|l| (l.entry_price, l.size)).collect();
+    assert_eq!(lots, [(dec!(0.90), dec!(5))]);
+
+    // Selling more than is held closes what is held.
+    let closed = inv.close("t", dec!(50), dec!(0.95));
+    assert_eq!(closed.len(), 1);
+    assert_eq!(closed[0].size, dec!(5));
+    assert_eq!(inv.size("t"), Decimal::ZERO);
+    assert_eq!(inv.avg_entry("t"), None);
+    assert!(inv.close("t", dec!(1), dec!(0.95)).is_empty());
+}
+
+#[test]
+fn partial_close_keeps_the_rest_of_the_lot() {
+    let mut inv = Inventory::default();
+    inv.add_lot("t", EntrySide::Down, dec!(0.60), dec!(10), 1);
+    let closed = inv.close("t", dec!(4), dec!(0.70));
+    assert_eq!(closed.len(), 1);
+    assert_eq!(closed[0].size, dec!(4));
+    assert_eq!(closed[0].side, EntrySide::Down);
+    assert_eq!(inv.size("t"), dec!(6));
+    assert_eq!(inv.cost("t"), dec!(3.6));
+    assert_eq!(inv.unrealized_pnl("t", dec!(0.70)), dec!(0.6));
+}
+
+#[test]
+fn unfill_drops_the_newest_lots_without_a_trade() {
+    let mut inv = two_lots();
+    inv.unfill("t", dec!(12));
+    // The 0.90 lot is gone and 2 of the 0.80 lot with it.
+    let lots: Vec<_> = inv.lots("t").map(|l| (l.entry_price, l.size)).collect();
+    assert_eq!(lots, [(dec!(0.80), dec!(8))]);
+    assert_eq!(inv.stats().trades(), 0);
+    // Only what is still held was paid for.
+    assert_eq!(inv.cash_flow(), dec!(-6.4));
+
+    inv.unfill("t", dec!(8));
+    assert_eq!(inv.size("t"), Decimal::ZERO);
+    assert_eq!(inv.cash_flow(), Decimal::ZERO);
+}
+
+#[test]
+fn one_trade_per_sell_with_its_realized_pnl() {
+    let mut inv = two_lots();
+    // One sell over both lots: +1.5 on the first, +0.5 on the second.
+    inv.close("t", dec!(20), dec!(0.95));
+    let stats = inv.stats();
+    assert_eq!(stats.trades(), 1);
+    assert_eq!(stats.wins, 1);
+    assert_eq!(stats.realized_pnl(), dec!(2.0));
+    // 2.0 gained on 17 USDC staked.
+    assert_eq!(stats.avg_win(), Some(dec!(2.0) / dec!(17)));
+    assert_eq!(inv.cash_flow(), dec!(2.0));
+
+    // A sell that wins on one lot and loses on the other counts by its sum: a loss.
+    let mut inv = two_lots();
+    inv.close("t", dec!(20), dec!(0.84));
+    let stats = inv.stats();
+    assert_eq!(stats.trades(), 1);
+    assert_eq!(stats.losses, 1);
+    assert_eq!(stats.realized_pnl(), dec!(-0.2));
+    assert_eq!(stats.win_rate(), Some(Decimal::ZERO));
+}
+
+#[test]
+fn closes_at_the_mark_count_one_trade_per_token() {
+    let mut inv = two_lots();
+    inv.add_lot("u", EntrySide::Down, dec!(0.10), dec!(10), 3);
+    inv.set_mark("t", dec!(1));
+    let closed = inv.close_all_at_mark();
+    assert_eq!(closed.len(), 3);
+    let stats = inv.stats();
+    assert_eq!(stats.trades(), 2);
+    assert_eq!((stats.wins, stats.losses), (1, 1));
+    // "t" won 3.0 at 1; "u" was never marked and lost its 1.0 at 0.
+    assert_eq!(stats.realized_pnl(), dec!(2.0));
+    // Not cash: only the buys count.
+    assert_eq!(inv.cash_flow(), dec!(-18));
+}
+
+#[test]
+fn an_exit_over_several_sells_is_one_trade_once_flat() {
+    let mut inv = two_lots();
+    // A FAK exit filling over three retries.
+    inv.close("t", dec!(6), dec!(0.95));
+    inv.close("t", dec!(9), dec!(0.95));
+    assert_eq!(inv.stats().trades(), 0);
+    inv.close("t", dec!(5), dec!(0.85));
+    let stats = inv.stats();
+    assert_eq!(stats.trades(), 1);
+    assert_eq!(stats.wins, 1);
+    // +1.5 on the first lot, +0.25 and -0.25 on the second.
+    assert_eq!(stats.realized_pnl(), dec!(1.5));
+
+    // The rest of a partly sold position closed at the mark joins the same trade.
+    let mut inv = two_lots();
+    inv.close("t", dec!(10), dec!(0.70));
+    inv.set_mark("t", dec!(1));
+    inv.close_all_at_mark();
+    let stats = inv.stats();
+    // -1.0 on the sell, +1.0 at the mark.
+    assert_eq!((stats.trades(), stats.scratches), (1, 1));
+    assert_eq!(stats.realized_pnl(), Decimal::ZERO);
+
+    // So does the rest carried past the close and settled at the resolution.
+    let mut inv = two_lots();
+    inv.close("t", dec!(10), dec!(0.95));
+    let lots = inv.take_lots("t");
+    assert_eq!(inv.stats().trades(), 0);
+    inv.close_lots("t", lots, Decimal::ZERO);
+    let stats = inv.stats();
+    assert_eq!((stats.trades(), stats.losses), (1, 1));
+    assert_eq!(stats.realized_pnl(), dec!(1.5) - dec!(9));
+}
+
+#[test]
+fn unfilling_the_rest_ends_the_trade() {
+    let mut inv = two_lots();
+    inv.close("t", dec!(10), dec!(0.90));
+    // The rest of the buy never filled: the sell so far is the whole trade.
+    inv.unfill("t", dec!(10));
+    let stats = inv.stats();
+    assert_eq!((stats.trades(), stats.wins), (1, 1));
+    assert_eq!(stats.realized_pnl(), dec!(1.0));
+}
+
+#[test]
+fn a_trade_at_its_entry_cost_is_neither_a_win_nor_a_loss() {
+    let mut inv = Inventory::default();
+    inv.add_lot("t", EntrySide::Up, dec!(0.80), dec!(10), 1);
+    inv.close("t", dec!(10), dec!(0.80));
+    inv.add_lot("t", EntrySide::Up, dec!(0.80), dec!(10), 2);
+    inv.close("t", dec!(10), dec!(0.90));
+    let stats = inv.stats();
+    assert_eq!(stats.trades(), 2);
+    assert_eq!((stats.wins, stats.losses, stats.scratches), (1, 0, 1));
+    assert_eq!(stats.win_rate(), Some(Decimal::ONE));
+    assert_eq!(stats.avg_win(), Some(dec!(0.125)));
+    assert_eq!(stats.avg_loss(), None);
+}
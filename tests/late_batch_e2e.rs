@@ -0,0 +1,92 @@
+//! A batch POST answered with a 5xx whose orders land only after the first lookup: the live
+//! client looks a missing order up again instead of journaling it as not placed, and an order
+//! whose lookup fails is reported unresolved (still pending in the journal) next to the other
+//! orders of the batch instead of failing the whole batch.
+
+mod mock_clob;
+
+use mock_clob::{live_env, MockClob};
+use rust_decimal_macros::dec;
+use sniper_core::clob::{ClobClient, ClobError, LimitOrderParams, LiveClob, OrderSide, OrderType};
+
+fn buy(token: &str) -> (LimitOrderParams, OrderType) {
+    (
+        LimitOrderParams {
+            token_id: token.to_string(),
+            side: OrderSide::Buy,
+            price: dec!(0.92),
+            size: dec!(5),
+            expiration_unix: None,
+            post_only: false,
+            fee_rate_bps: Some(0),
+            min_order_size: None,
+        },
+        OrderType::Fok,
+    )
+}
+
+/// Journal events ("submitted", "resolved") of `order_id`, with the outcome of a resolution.
+fn journal_events(journal: &std::path::Path, order_id: &str) -> Vec<String> {
+    std::fs::read_to_string(journal)
+        .unwrap()
+        .lines()
+        .filter_map(|l| serde_json::from_str::<serde_json::Value>(l).ok())
+        .filter(|v| v["order_id"] == order_id)
+        .map(|v| match v["outcome"].as_str() {
+            Some(outcome) => format!("{} {}", v["event"].as_str().unwrap_or_default(), outcome),
+            None => v["event"].as_str().unwrap_or_default().to_string(),
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn late_batch_orders_are_looked_up_again_and_lookup_errors_stay_per_order() {
+    let mock = MockClob::start().await;
+    live_env(&mock);
+    std::env::set_var("MM_HTTP_RETRY_MAX_ATTEMPTS", "1");
+    let journal =
+        std::env::temp_dir().join(format!("sniper-late-batch-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&journal);
+    std::env::set_var("MM_ORDER_JOURNAL", &journal);
+    mock.set_collateral(dec!(100));
+    let tokens = ["880021", "880022", "880031", "880032"];
+    for token in tokens {
+        mock.set_book(token, &[("0.90", "100")], &[("0.92", "100")]);
+    }
+    let clob = LiveClob::from_env().unwrap();
+
+    // The first order's lookup misses it; both land right after and are found.
+    mock.land_batch_late(1);
+    let results = clob
+        .place_orders_batch(vec![buy(tokens[0]), buy(tokens[1])])
+        .await
+        .unwrap();
+    for (token, r) in tokens[..2].iter().zip(&results) {
+        assert!(r.success, "{}: {:?}", token, r.error_msg);
+        assert_eq!(r.filled_size, Some(dec!(5)));
+        assert_eq!(mock.shares(token), dec!(5));
+        let id = r.order_id.as_deref().unwrap();
+        assert_eq!(
+            journal_events(&journal, id),
+            ["submitted", "resolved accepted"]
+        );
+    }
+    assert_eq!(mock.request_count("POST /orders"), 1);
+
+    // The second order can't be looked up: only it is left unresolved.
+    mock.land_batch_late(1);
+    mock.fail_lookups(tokens[3]);
+    let results = clob
+        .place_orders_batch(vec![buy(tokens[2]), buy(tokens[3])])
+        .await
+        .unwrap();
+    assert!(results[0].success, "{:?}", results[0].error_msg);
+    assert_eq!(results[0].filled_size, Some(dec!(5)));
+    assert!(!results[1].success);
+    assert!(results[1].is_error(&ClobError::Unresolved));
+    let unresolved = results[1].order_id.as_deref().unwrap();
+    assert_eq!(journal_events(&journal, unresolved), ["submitted"]);
+    // It did land: the journal recovery at the next start looks it up again.
+    assert_eq!(mock.shares(tokens[3]), dec!(5));
+    let _ = std::fs::remove_file(&journal);
+}
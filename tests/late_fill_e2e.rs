@@ -0,0 +1,52 @@
+//! Late fill against the mock CLOB: an entry buy answered only after its market was settled is
+//! carried with that market and closed at the payout it resolves to, not dropped.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::{Run, CLOSE};
+use mock_clob::wait_for;
+use rust_decimal_macros::dec;
+use sniper_core::clock::Clock;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn carries_a_fill_that_lands_after_the_settlement() {
+    let run = Run::start("late-fill", "8901", "8902", 6).await;
+    run.mock
+        .set_book(&run.up, &[("0.91", "100")], &[("0.92", "100")]);
+    run.mock
+        .set_book(&run.down, &[("0.06", "100")], &[("0.08", "100")]);
+    // The buy is answered ~5 s after the close, past the wait for in-flight orders at the switch.
+    run.mock.set_delay("POST /order", 8000);
+    std::env::set_var("MM_SESSION_LOG", "true");
+    std::env::set_var("MM_CARRYOVER_WAIT_SECS", "30");
+
+    let engine = run.spawn();
+    assert!(wait_for(8, || run.mock.request_count("POST /order") == 1).await);
+    assert!(
+        wait_for(15, || run.mock.shares(&run.up) == dec!(5)).await,
+        "no fill: orders {:?}",
+        run.mock.orders()
+    );
+    assert!(
+        run.clock.now_unix() >= CLOSE + 3,
+        "filled before the switch was settled"
+    );
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!(
+        !engine.is_finished(),
+        "engine stopped before the resolution"
+    );
+
+    // Up was bid 0.91 at the close (inferred to pay 1), but Down wins.
+    run.mock.resolve(&run.slug, false);
+    run.finish(engine).await;
+
+    let closes = run.session_events("close");
+    assert_eq!(closes.len(), 1, "closes: {:?}", closes);
+    assert_eq!(closes[0]["exit_type"], "RESOLUTION");
+    assert_eq!(closes[0]["side"], "Up");
+    assert_eq!(closes[0]["exit_price"], "0");
+    assert_eq!(closes[0]["size"], "5");
+}
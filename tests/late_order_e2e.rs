@@ -0,0 +1,60 @@
+//! An order POST answered with a 5xx whose order lands only after the lookup: the resend is
+//! refused as a duplicate, and the live client reports (and journals) the order that landed
+//! instead of a failure.
+
+mod mock_clob;
+
+use mock_clob::{live_env, MockClob};
+use rust_decimal_macros::dec;
+use sniper_core::clob::{ClobClient, LimitOrderParams, LiveClob, OrderSide, OrderType};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_duplicate_resend_reports_the_order_that_landed() {
+    let mock = MockClob::start().await;
+    live_env(&mock);
+    let journal =
+        std::env::temp_dir().join(format!("sniper-late-order-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&journal);
+    std::env::set_var("MM_ORDER_JOURNAL", &journal);
+    let token = "880011";
+    mock.set_collateral(dec!(100));
+    mock.set_book(token, &[("0.90", "100")], &[("0.92", "100")]);
+    mock.land_late(1);
+
+    let clob = LiveClob::from_env().unwrap();
+    let placed = clob
+        .place_limit_order(
+            LimitOrderParams {
+                token_id: token.to_string(),
+                side: OrderSide::Buy,
+                price: dec!(0.92),
+                size: dec!(5),
+                expiration_unix: None,
+                post_only: false,
+                fee_rate_bps: Some(0),
+                min_order_size: None,
+            },
+            OrderType::Fok,
+        )
+        .await
+        .unwrap();
+
+    assert!(placed.success, "{:?}", placed.error_msg);
+    assert_eq!(placed.filled_size, Some(dec!(5)));
+    assert_eq!(mock.request_count("POST /order"), 2);
+    // Bought once: the resend did not fill again.
+    assert_eq!(mock.shares(token), dec!(5));
+    assert_eq!(mock.orders().len(), 1);
+    assert_eq!(
+        placed.order_id.as_deref(),
+        Some(mock.orders()[0].id.as_str())
+    );
+
+    let lines = std::fs::read_to_string(&journal).unwrap();
+    assert!(
+        lines.contains(r#""outcome":"accepted""#),
+        "journal: {}",
+        lines
+    );
+    let _ = std::fs::remove_file(&journal);
+}
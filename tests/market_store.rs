@@ -0,0 +1,66 @@
+//! Market metadata cache (MM_MARKET_CACHE_FILE): markets resolved from Gamma are appended to the
+//! file, markets already in it resolve without Gamma, and token IDs map back to their slug.
+
+mod mock_clob;
+
+use mock_clob::{now_unix, MockClob};
+use sniper_core::market::resolve_market;
+use sniper_core::market_store::MarketStore;
+use sniper_core::types::MarketTarget;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stores_markets_and_resolves_known_ones_offline() {
+    let path = std::env::temp_dir().join(format!("sniper-markets-{}.jsonl", std::process::id()));
+    let now = now_unix();
+    // Left by a previous run; Gamma does not list it.
+    std::fs::write(
+        &path,
+        format!(
+            "{}\n",
+            serde_json::json!({
+                "slug": "mock-seen-before",
+                "condition_id": "0xabc",
+                "token_id_up": "7101",
+                "token_id_down": "7102",
+                "interval_start_unix": now - 60,
+                "close_time_unix": now + 240,
+                "tick_size": "0.01",
+                "min_order_size": "5",
+                "neg_risk": false,
+            })
+        ),
+    )
+    .unwrap();
+    std::env::set_var("MM_MARKET_CACHE_FILE", &path);
+    let http = reqwest::Client::new();
+    let mock = MockClob::start().await;
+    mock.add_market("mock-stored", "7001", "7002", now - 60, now + 240);
+    let gamma = mock.rest_url();
+
+    let fresh = resolve_market(
+        &http,
+        &gamma,
+        &MarketTarget::Slug("mock-stored".to_string()),
+        now,
+    )
+    .await
+    .unwrap();
+    assert_eq!(fresh.token_id_up, "7001");
+    let known = resolve_market(
+        &http,
+        &gamma,
+        &MarketTarget::Slug("mock-seen-before".to_string()),
+        now,
+    )
+    .await
+    .unwrap();
+    assert_eq!(known.condition_id, "0xabc");
+    assert_eq!(known.token_id_down, "7102");
+
+    // The next run sees both.
+    let store = MarketStore::open(&path);
+    assert_eq!(store.len(), 2);
+    assert_eq!(store.slug_for_token("7002"), Some("mock-stored"));
+    assert_eq!(store.slug_for_token("7101"), Some("mock-seen-before"));
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,1160 @@
+//! Simulated Polymarket for end-to-end tests: the REST and WS endpoints the bot talks to, served
+//! on localhost so a full engine (live client, WS book, Gamma lookups) runs without touching
+//! production.
+//!
+//! - CLOB REST: `GET /book`, `GET /fee-rate`, `POST /order`, `POST /orders`, `DELETE /order`,
+//!   `DELETE /cancel-market-orders`, `DELETE /cancel-all`, `GET /data/order/{id}`,
+//!   `GET /balance-allowance`, `GET /data/trades`, and `HEAD /` (connection warm-up).
+//! - Gamma: `GET /markets/slug/{slug}` and `GET /markets?slug=`; data API: `GET /positions`.
+//! - WS market channel: `book` snapshots for the subscribed tokens, again on every book change,
+//!   and the `last_trade_price` events of [MockClob::print_trade].
+//!
+//! Orders are matched against the book the test sets: FOK / FAK take what crosses (FOK all or
+//! nothing), GTC / GTD rest the remainder, and a resting order fills at its price when a later
+//! [MockClob::set_book] crosses it. The wallet (USDC and shares) moves with every fill, and
+//! placement checks it net of resting orders, answering "not enough balance / allowance" like the
+//! exchange. Order IDs are the order hashes the client computes, and posting an order the
+//! exchange already holds is refused as duplicated. Signatures and HMAC headers are not verified;
+//! private endpoints only require POLY_API_KEY. Fees are zero; GET /fee-rate reports 0 unless [MockClob::set_fee_rate] says
+//! otherwise.
+//!
+//! Point the bot at it with POLYMARKET_CLOB_HOST, POLYMARKET_REST_BASE and POLYMARKET_DATA_API_URL
+//! ([MockClob::rest_url]) and POLYMARKET_WS_URL ([MockClob::ws_url]).
+
+#![allow(dead_code)]
+
+use ethers::types::{H160, U256};
+use futures_util::{SinkExt, StreamExt};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde_json::{json, Value};
+use sniper_core::signing::{OrderFields, OrderSigner, EXCHANGE_ADDRESS_POLYGON};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Slug prefix of the BTC 5-minute interval markets.
+pub const BTC_5M_PREFIX: &str = "btc-updown-5m";
+const INTERVAL_SECS: u64 = 300;
+/// Order amounts and balances are in 6-decimal base units.
+const BASE_UNITS: Decimal = dec!(1000000);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// An order as the exchange holds it.
+#[derive(Debug, Clone)]
+pub struct MockOrder {
+    pub id: String,
+    pub token_id: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub matched: Decimal,
+    /// LIVE, MATCHED or CANCELED.
+    pub status: &'static str,
+    pub order_type: String,
+    /// Funder address the fills are credited to.
+    maker: String,
+}
+
+impl MockOrder {
+    fn remaining(&self) -> Decimal {
+        self.size - self.matched
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct Book {
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Gamma market JSON by slug.
+    markets: HashMap<String, Value>,
+    /// Slug prefixes answered with a generated 5-minute market ([interval_tokens]).
+    interval_prefixes: Vec<String>,
+    /// Market slugs of each Gamma event.
+    events: HashMap<String, Vec<String>>,
+    /// Spot price served as the last 5m kline (MM_SPOT_URL).
+    spot: Option<Decimal>,
+    books: HashMap<String, Book>,
+    orders: Vec<MockOrder>,
+    collateral: Decimal,
+    shares: HashMap<String, Decimal>,
+    /// /data/trades records, oldest first.
+    trades: Vec<Value>,
+    /// "METHOD /path" of every request served.
+    requests: Vec<String>,
+    /// Connection (numbered in accept order) each of `requests` came in on.
+    request_connections: Vec<usize>,
+    /// Every token a WS client subscribed to, in order.
+    ws_subscriptions: Vec<String>,
+    /// ("METHOD /path" prefix, ms) held before answering matching requests.
+    delays: Vec<(String, u64)>,
+    /// GET /fee-rate answer per token: the base fee, or None for an HTTP 500 (unset = 0).
+    fee_rates: HashMap<String, Option<u64>>,
+    /// Order POSTs still to answer with HTTP 502 while their order is on its way ([MockClob::land_late]).
+    late_posts: usize,
+    /// (order, order type) of those POSTs, placed when the next order POST arrives.
+    landing: Vec<(Value, String)>,
+    /// Batch POSTs still to answer with HTTP 502 while their orders are on their way
+    /// ([MockClob::land_batch_late]).
+    late_batches: usize,
+    /// (order, order type) of those batches, placed right after a lookup misses one of them.
+    landing_on_miss: Vec<(Value, String)>,
+    /// Tokens whose order lookups fail with HTTP 503 ([MockClob::fail_lookups]).
+    failing_lookups: HashSet<String>,
+    /// GET /book answers HTTP 503 ([MockClob::fail_books]).
+    failing_books: bool,
+}
+
+/// The Up / Down token IDs of the generated interval market starting at `start_unix`.
+pub fn interval_tokens(start_unix: u64) -> (String, String) {
+    (format!("{}1", start_unix), format!("{}2", start_unix))
+}
+
+/// Start of the 5-minute interval containing `unix`.
+pub fn interval_start(unix: u64) -> u64 {
+    unix / INTERVAL_SECS * INTERVAL_SECS
+}
+
+pub fn now_unix() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn iso(unix: u64) -> String {
+    chrono::DateTime::from_timestamp(unix as i64, 0)
+        .unwrap()
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string()
+}
+
+fn gamma_market(slug: &str, up: &str, down: &str, start_unix: u64, close_unix: u64) -> Value {
+    json!({
+        "id": format!("mock-{}", slug),
+        "conditionId": format!("0x{:064x}", start_unix),
+        "slug": slug,
+        "question": format!("Mock market {}", slug),
+        "startDate": iso(start_unix),
+        "endDate": iso(close_unix),
+        "clobTokenIds": json!([up, down]).to_string(),
+        "outcomes": "[\"Up\", \"Down\"]",
+        "outcomePrices": "[\"0.5\", \"0.5\"]",
+        "orderPriceMinTickSize": 0.01,
+        "orderMinSize": 5,
+    })
+}
+
+fn levels_json(levels: &BTreeMap<Decimal, Decimal>) -> Value {
+    levels
+        .iter()
+        .map(|(p, s)| json!({"price": p.to_string(), "size": s.to_string()}))
+        .collect()
+}
+
+fn book_event(token_id: &str, book: &Book) -> String {
+    json!({
+        "event_type": "book",
+        "asset_id": token_id,
+        "market": "",
+        "bids": levels_json(&book.bids),
+        "asks": levels_json(&book.asks),
+        "timestamp": (now_unix() * 1000).to_string(),
+        "hash": "",
+    })
+    .to_string()
+}
+
+fn base_units(d: Decimal) -> String {
+    (d * BASE_UNITS).trunc().to_string()
+}
+
+fn decimal_field(v: &Value, key: &str) -> Option<Decimal> {
+    let f = v.get(key)?;
+    f.as_str()
+        .map(str::to_string)
+        .or_else(|| f.as_u64().map(|n| n.to_string()))
+        .and_then(|s| Decimal::from_str(s.trim()).ok())
+}
+
+fn query_param<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|kv| kv.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v)
+}
+
+impl State {
+    fn market(&mut self, slug: &str) -> Option<Value> {
+        if let Some(m) = self.markets.get(slug) {
+            return Some(m.clone());
+        }
+        let (prefix, start) = slug.rsplit_once('-')?;
+        let start: u64 = start.parse().ok()?;
+        if !self.interval_prefixes.iter().any(|p| p == prefix) {
+            return None;
+        }
+        let (up, down) = interval_tokens(start);
+        let m = gamma_market(slug, &up, &down, start, start + INTERVAL_SECS);
+        self.markets.insert(slug.to_string(), m.clone());
+        Some(m)
+    }
+
+    fn shares(&self, token_id: &str) -> Decimal {
+        self.shares.get(token_id).copied().unwrap_or_default()
+    }
+
+    /// USDC or shares of `token_id` held by resting orders.
+    fn reserved(&self, token_id: Option<&str>) -> Decimal {
+        self.orders
+            .iter()
+            .filter(|o| o.status == "LIVE")
+            .map(|o| match (token_id, o.side) {
+                (None, Side::Buy) => o.remaining() * o.price,
+                (Some(t), Side::Sell) if t == o.token_id => o.remaining(),
+                _ => Decimal::ZERO,
+            })
+            .sum()
+    }
+
+    /// Levels of the book of `token_id` that `side` at `price` crosses, best first.
+    fn crossing(&self, token_id: &str, side: Side, price: Decimal) -> Vec<(Decimal, Decimal)> {
+        let Some(book) = self.books.get(token_id) else {
+            return Vec::new();
+        };
+        match side {
+            Side::Buy => book
+                .asks
+                .iter()
+                .take_while(|(p, _)| **p <= price)
+                .map(|(p, s)| (*p, *s))
+                .collect(),
+            Side::Sell => book
+                .bids
+                .iter()
+                .rev()
+                .take_while(|(p, _)| **p >= price)
+                .map(|(p, s)| (*p, *s))
+                .collect(),
+        }
+    }
+
+    /// Take `size` from the level at `price` on the side `side` trades against.
+    fn consume(&mut self, token_id: &str, side: Side, price: Decimal, size: Decimal) {
+        let Some(book) = self.books.get_mut(token_id) else {
+            return;
+        };
+        let levels = match side {
+            Side::Buy => &mut book.asks,
+            Side::Sell => &mut book.bids,
+        };
+        if let Some(s) = levels.get_mut(&price) {
+            *s -= size;
+            if *s <= Decimal::ZERO {
+                levels.remove(&price);
+            }
+        }
+    }
+
+    /// Move the wallet for a fill and record the trade.
+    fn settle_fill(&mut self, order: &MockOrder, price: Decimal, size: Decimal, taker: bool) {
+        let cash = price * size;
+        let held = self.shares.entry(order.token_id.clone()).or_default();
+        match order.side {
+            Side::Buy => {
+                *held += size;
+                self.collateral -= cash;
+            }
+            Side::Sell => {
+                *held -= size;
+                self.collateral += cash;
+            }
+        }
+        let side = match order.side {
+            Side::Buy => "BUY",
+            Side::Sell => "SELL",
+        };
+        let id = format!("trade-{}", self.trades.len() + 1);
+        let trade = if taker {
+            json!({
+                "id": id, "status": "MATCHED", "match_time": now_unix().to_string(),
+                "trader_side": "TAKER", "asset_id": order.token_id, "side": side,
+                "price": price.to_string(), "size": size.to_string(), "fee_rate_bps": "0",
+                "taker_order_id": order.id,
+            })
+        } else {
+            json!({
+                "id": id, "status": "MATCHED", "match_time": now_unix().to_string(),
+                "trader_side": "MAKER", "fee_rate_bps": "0",
+                "maker_orders": [{
+                    "maker_address": order.maker, "order_id": order.id,
+                    "asset_id": order.token_id, "side": side, "price": price.to_string(),
+                    "matched_amount": size.to_string(), "fee_rate_bps": "0",
+                }],
+            })
+        };
+        self.trades.push(trade);
+    }
+
+    /// POST /order for one signed order: (HTTP status, response body).
+    fn place(&mut self, order: &Value, order_type: &str) -> (u16, Value) {
+        let (Some(token_id), Some(side), Some(maker_amount), Some(taker_amount)) = (
+            order.get("tokenId").and_then(Value::as_str),
+            order.get("side").and_then(Value::as_str),
+            decimal_field(order, "makerAmount"),
+            decimal_field(order, "takerAmount"),
+        ) else {
+            return (400, json!({"error": "invalid order payload"}));
+        };
+        let id = order_hash(order).unwrap_or_else(|| format!("0x{:064x}", self.orders.len() + 1));
+        if self.orders.iter().any(|o| o.id == id) {
+            return (
+                400,
+                json!({"error": format!("order {} is invalid. Duplicated.", id)}),
+            );
+        }
+        let (maker_amount, taker_amount) = (maker_amount / BASE_UNITS, taker_amount / BASE_UNITS);
+        if maker_amount <= Decimal::ZERO || taker_amount <= Decimal::ZERO {
+            return (400, json!({"error": "invalid amounts"}));
+        }
+        let (side, price, size) = match side {
+            "BUY" => (Side::Buy, maker_amount / taker_amount, taker_amount),
+            "SELL" => (Side::Sell, taker_amount / maker_amount, maker_amount),
+            _ => return (400, json!({"error": "invalid side"})),
+        };
+        let price = price.round_dp(4);
+        let enough = match side {
+            Side::Buy => self.collateral - self.reserved(None) >= price * size,
+            Side::Sell => self.shares(token_id) - self.reserved(Some(token_id)) >= size,
+        };
+        if !enough {
+            return (400, json!({"error": "not enough balance / allowance"}));
+        }
+        let mut fills = Vec::new();
+        let mut left = size;
+        for (p, s) in self.crossing(token_id, side, price) {
+            if left <= Decimal::ZERO {
+                break;
+            }
+            let take = s.min(left);
+            fills.push((p, take));
+            left -= take;
+        }
+        let filled: Decimal = fills.iter().map(|(_, s)| *s).sum();
+        match order_type {
+            "FOK" if left > Decimal::ZERO => {
+                return (
+                    400,
+                    json!({"error": "order couldn't be fully filled. FOK orders are fully filled or killed."}),
+                )
+            }
+            "FAK" if filled <= Decimal::ZERO => {
+                return (
+                    400,
+                    json!({"error": "no orders found to match with FAK order. FAK orders are partially filled or killed if no match is found."}),
+                )
+            }
+            _ => {}
+        }
+        let resting = matches!(order_type, "GTC" | "GTD") && left > Decimal::ZERO;
+        let mut placed = MockOrder {
+            id,
+            token_id: token_id.to_string(),
+            side,
+            price,
+            size,
+            matched: filled,
+            status: if resting { "LIVE" } else { "MATCHED" },
+            order_type: order_type.to_string(),
+            maker: order
+                .get("maker")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_lowercase(),
+        };
+        if !resting {
+            placed.size = filled;
+        }
+        let mut cash = Decimal::ZERO;
+        for (p, s) in &fills {
+            self.consume(token_id, side, *p, *s);
+            self.settle_fill(&placed, *p, *s, true);
+            cash += *p * *s;
+        }
+        let (making, taking) = match side {
+            Side::Buy => (cash, filled),
+            Side::Sell => (filled, cash),
+        };
+        let body = json!({
+            "success": true,
+            "errorMsg": "",
+            "orderID": placed.id,
+            "status": if resting { "live" } else { "matched" },
+            "makingAmount": if filled > Decimal::ZERO { base_units(making) } else { String::new() },
+            "takingAmount": if filled > Decimal::ZERO { base_units(taking) } else { String::new() },
+        });
+        self.orders.push(placed);
+        (200, body)
+    }
+
+    /// Place the orders of [MockClob::land_late] POSTs that are still on their way.
+    fn land_held_orders(&mut self, updates: &broadcast::Sender<(String, String)>) {
+        for (order, order_type) in std::mem::take(&mut self.landing) {
+            self.place(&order, &order_type);
+            publish(self, order["tokenId"].as_str().unwrap_or_default(), updates);
+        }
+    }
+
+    /// Fill resting orders of `token_id` that the book now crosses, at their own price.
+    fn match_resting(&mut self, token_id: &str) {
+        for i in 0..self.orders.len() {
+            let o = &self.orders[i];
+            if o.status != "LIVE" || o.token_id != token_id {
+                continue;
+            }
+            let (side, price) = (o.side, o.price);
+            let mut left = o.remaining();
+            for (p, s) in self.crossing(token_id, side, price) {
+                if left <= Decimal::ZERO {
+                    break;
+                }
+                let take = s.min(left);
+                self.consume(token_id, side, p, take);
+                let order = self.orders[i].clone();
+                self.settle_fill(&order, price, take, false);
+                left -= take;
+            }
+            let o = &mut self.orders[i];
+            o.matched = o.size - left;
+            if left <= Decimal::ZERO {
+                o.status = "MATCHED";
+            }
+        }
+    }
+
+    /// Cancel the live orders `which` selects: `{"canceled": [...], "not_canceled": {...}}`.
+    fn cancel(&mut self, which: impl Fn(&MockOrder) -> bool) -> Value {
+        let mut canceled = Vec::new();
+        for o in self.orders.iter_mut().filter(|o| which(o)) {
+            if o.status == "LIVE" {
+                o.status = "CANCELED";
+                canceled.push(o.id.clone());
+            }
+        }
+        json!({"canceled": canceled, "not_canceled": {}})
+    }
+
+    fn order_json(o: &MockOrder) -> Value {
+        json!({
+            "id": o.id,
+            "status": o.status,
+            "asset_id": o.token_id,
+            "side": if o.side == Side::Buy { "BUY" } else { "SELL" },
+            "price": o.price.to_string(),
+            "original_size": o.size.to_string(),
+            "size_matched": o.matched.to_string(),
+            "order_type": o.order_type,
+        })
+    }
+}
+
+/// Throwaway key: orders are signed but never leave localhost.
+const TEST_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+/// Point the bot at `mock` and trade as a live account: 5 sh bought at 0.90–0.95 in the last 20 s,
+/// TP 0.97, SL 0.80.
+pub fn live_env(mock: &MockClob) {
+    for (k, v) in [
+        ("POLYMARKET_CLOB_HOST", mock.rest_url()),
+        ("POLYMARKET_REST_BASE", mock.rest_url()),
+        ("POLYMARKET_DATA_API_URL", mock.rest_url()),
+        ("POLYMARKET_WS_URL", mock.ws_url()),
+        ("PRIVATE_KEY", TEST_PRIVATE_KEY.to_string()),
+        ("API_KEY", "mock-key".to_string()),
+        ("SECRET", "bW9jay1zZWNyZXQ=".to_string()),
+        ("PASSPHRASE", "mock-passphrase".to_string()),
+        ("MM_DRY_RUN", "false".to_string()),
+        ("MM_CLOB_KEEPALIVE_SECS", "0".to_string()),
+        ("MM_SIZE_SHARES", "5".to_string()),
+        ("MM_MIN_BUY_PRICE", "0.90".to_string()),
+        ("MM_MAX_BUY_PRICE", "0.95".to_string()),
+        ("MM_TAKE_PROFIT_PRICE", "0.97".to_string()),
+        ("MM_STOP_LOSS_PRICE", "0.80".to_string()),
+        ("MM_SECONDS_BEFORE_CLOSE", "20".to_string()),
+        ("MM_SPOT_URL", mock.rest_url()),
+    ] {
+        std::env::set_var(k, v);
+    }
+}
+
+/// Address [live_env] trades from (the signer EOA, as the engine formats it).
+pub fn test_wallet() -> String {
+    let wallet: ethers::signers::LocalWallet = TEST_PRIVATE_KEY.parse().unwrap();
+    format!("{:?}", ethers::signers::Signer::address(&wallet))
+}
+
+/// Poll `cond` every 100 ms for up to `secs`.
+pub async fn wait_for(secs: u64, mut cond: impl FnMut() -> bool) -> bool {
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(secs);
+    while tokio::time::Instant::now() < deadline {
+        if cond() {
+            return true;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    cond()
+}
+
+/// A running mock; the server tasks stop when it is dropped.
+pub struct MockClob {
+    state: Arc<Mutex<State>>,
+    rest_addr: SocketAddr,
+    ws_addr: SocketAddr,
+    /// (token ID, WS message) for every book change.
+    updates: broadcast::Sender<(String, String)>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Drop for MockClob {
+    fn drop(&mut self) {
+        for t in &self.tasks {
+            t.abort();
+        }
+    }
+}
+
+impl MockClob {
+    /// Bind the REST and WS listeners on ephemeral localhost ports and start serving.
+    pub async fn start() -> MockClob {
+        let state = Arc::new(Mutex::new(State::default()));
+        let (updates, _) = broadcast::channel(1024);
+        let rest = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let rest_addr = rest.local_addr().unwrap();
+        let ws_addr = ws.local_addr().unwrap();
+        let rest_task = {
+            let state = state.clone();
+            let updates = updates.clone();
+            tokio::spawn(async move {
+                let mut connection = 0;
+                while let Ok((stream, _)) = rest.accept().await {
+                    connection += 1;
+                    tokio::spawn(serve_http(
+                        stream,
+                        connection,
+                        state.clone(),
+                        updates.clone(),
+                    ));
+                }
+            })
+        };
+        let ws_task = {
+            let state = state.clone();
+            let updates = updates.clone();
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = ws.accept().await {
+                    tokio::spawn(serve_ws(stream, state.clone(), updates.subscribe()));
+                }
+            })
+        };
+        MockClob {
+            state,
+            rest_addr,
+            ws_addr,
+            updates,
+            tasks: vec![rest_task, ws_task],
+        }
+    }
+
+    /// Base URL of the CLOB, Gamma and data API endpoints.
+    pub fn rest_url(&self) -> String {
+        format!("http://{}", self.rest_addr)
+    }
+
+    /// URL of the WS market channel.
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}/ws/market", self.ws_addr)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// List a market on Gamma.
+    pub fn add_market(&self, slug: &str, up: &str, down: &str, start_unix: u64, close_unix: u64) {
+        self.state().markets.insert(
+            slug.to_string(),
+            gamma_market(slug, up, down, start_unix, close_unix),
+        );
+    }
+
+    /// Answer every `<prefix>-<start>` slug with a 5-minute market (tokens from
+    /// [interval_tokens]), as Gamma lists the rolling interval markets.
+    pub fn list_interval_markets(&self, prefix: &str) {
+        self.state().interval_prefixes.push(prefix.to_string());
+    }
+
+    /// List an "above $X" event closing at `close_unix`: one Yes / No market per
+    /// `(strike, yes token, no token)`, the strike in groupItemTitle (e.g. "4,000").
+    pub fn add_strike_event(
+        &self,
+        event_slug: &str,
+        close_unix: u64,
+        strikes: &[(&str, &str, &str)],
+    ) {
+        let mut s = self.state();
+        let mut slugs = Vec::new();
+        for (strike, yes, no) in strikes {
+            let slug = format!("{}-{}", event_slug, strike.replace(',', ""));
+            let mut m = gamma_market(&slug, yes, no, close_unix - 3600, close_unix);
+            m["groupItemTitle"] = json!(strike);
+            m["outcomes"] = json!("[\"Yes\", \"No\"]");
+            s.markets.insert(slug.clone(), m);
+            slugs.push(slug);
+        }
+        s.events.insert(event_slug.to_string(), slugs);
+    }
+
+    pub fn set_spot(&self, price: Decimal) {
+        self.state().spot = Some(price);
+    }
+
+    /// Set Gamma's outcomePrices of `slug` to the settled payouts.
+    pub fn resolve(&self, slug: &str, up_wins: bool) {
+        let payouts = if up_wins { ["1", "0"] } else { ["0", "1"] };
+        let mut s = self.state();
+        if let Some(mut m) = s.market(slug) {
+            m["outcomePrices"] = json!(json!(payouts).to_string());
+            s.markets.insert(slug.to_string(), m);
+        }
+    }
+
+    /// Replace the book of `token_id` with `(price, size)` levels, fill the resting orders it
+    /// crosses and push the new book to WS subscribers.
+    pub fn set_book(&self, token_id: &str, bids: &[(&str, &str)], asks: &[(&str, &str)]) {
+        let event = self.store_book(token_id, bids, asks);
+        let _ = self.updates.send((token_id.to_string(), event));
+    }
+
+    /// Push a `last_trade_price` event of `token_id` to WS subscribers (the book is unchanged).
+    pub fn print_trade(&self, token_id: &str, price: &str, size: &str, timestamp_ms: u64) {
+        let event = json!({
+            "event_type": "last_trade_price",
+            "asset_id": token_id,
+            "market": "",
+            "fee_rate_bps": "0",
+            "price": price,
+            "side": "BUY",
+            "size": size,
+            "timestamp": timestamp_ms.to_string(),
+        });
+        let _ = self.updates.send((token_id.to_string(), event.to_string()));
+    }
+
+    /// Change a book on REST only; the WS sends nothing (a feed gone quiet).
+    pub fn set_book_rest_only(&self, token_id: &str, bids: &[(&str, &str)], asks: &[(&str, &str)]) {
+        self.store_book(token_id, bids, asks);
+    }
+
+    /// Store a book (matching resting orders against it); returns its WS event.
+    fn store_book(&self, token_id: &str, bids: &[(&str, &str)], asks: &[(&str, &str)]) -> String {
+        let parse = |levels: &[(&str, &str)]| {
+            levels
+                .iter()
+                .map(|(p, s)| (Decimal::from_str(p).unwrap(), Decimal::from_str(s).unwrap()))
+                .collect()
+        };
+        let mut s = self.state();
+        s.books.insert(
+            token_id.to_string(),
+            Book {
+                bids: parse(bids),
+                asks: parse(asks),
+            },
+        );
+        s.match_resting(token_id);
+        book_event(token_id, &s.books[token_id])
+    }
+
+    pub fn set_collateral(&self, usdc: Decimal) {
+        self.state().collateral = usdc;
+    }
+
+    pub fn collateral(&self) -> Decimal {
+        self.state().collateral
+    }
+
+    /// Shares of `token_id` in the wallet.
+    pub fn shares(&self, token_id: &str) -> Decimal {
+        self.state().shares(token_id)
+    }
+
+    pub fn orders(&self) -> Vec<MockOrder> {
+        self.state().orders.clone()
+    }
+
+    /// Requests served whose "METHOD /path" starts with `prefix`, e.g. "POST /order".
+    pub fn request_count(&self, prefix: &str) -> usize {
+        self.state()
+            .requests
+            .iter()
+            .filter(|r| r.starts_with(prefix))
+            .count()
+    }
+
+    /// Connections that carried a request whose "METHOD /path" starts with `prefix`.
+    pub fn connections(&self, prefix: &str) -> HashSet<usize> {
+        let s = self.state();
+        s.requests
+            .iter()
+            .zip(&s.request_connections)
+            .filter(|(r, _)| r.starts_with(prefix))
+            .map(|(_, c)| *c)
+            .collect()
+    }
+
+    /// Base fee GET /fee-rate reports for `token_id`; None answers it with HTTP 500.
+    pub fn set_fee_rate(&self, token_id: &str, bps: Option<u64>) {
+        self.state().fee_rates.insert(token_id.to_string(), bps);
+    }
+
+    /// Answer requests whose "METHOD /path" starts with `prefix` after `ms` (0 = right away).
+    pub fn set_delay(&self, prefix: &str, ms: u64) {
+        let mut s = self.state();
+        s.delays.retain(|(p, _)| p != prefix);
+        if ms > 0 {
+            s.delays.push((prefix.to_string(), ms));
+        }
+    }
+
+    /// Answer the next `n` order POSTs with HTTP 502 before their order reaches the book: it lands
+    /// (and matches) only when the following order POST arrives, so a lookup in between misses it.
+    pub fn land_late(&self, n: usize) {
+        self.state().late_posts = n;
+    }
+
+    /// Answer the next `n` batch POSTs with HTTP 502 before their orders reach the book: they
+    /// land (and match) right after the first lookup that misses one of them.
+    pub fn land_batch_late(&self, n: usize) {
+        self.state().late_batches = n;
+    }
+
+    /// Fail every lookup of an order on `token_id` with HTTP 503.
+    pub fn fail_lookups(&self, token_id: &str) {
+        self.state().failing_lookups.insert(token_id.to_string());
+    }
+
+    /// Answer GET /book with HTTP 503 while `fail` (the WS feed still sends the books).
+    pub fn fail_books(&self, fail: bool) {
+        self.state().failing_books = fail;
+    }
+
+    /// Whether a WS client has subscribed to `token_id`.
+    pub fn ws_subscribed(&self, token_id: &str) -> bool {
+        self.state().ws_subscriptions.iter().any(|t| t == token_id)
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    authed: bool,
+    body: String,
+}
+
+/// Read one HTTP/1.1 request; None when the connection closed.
+async fn read_request(reader: &mut BufReader<TcpStream>) -> Option<Request> {
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        let mut byte = [0u8; 1];
+        if reader.read(&mut byte).await.ok()? == 0 {
+            return None;
+        }
+        head.push(byte[0]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.lines();
+    let mut first = lines.next()?.split_whitespace();
+    let method = first.next()?.to_string();
+    let target = first.next()?.to_string();
+    let mut content_length = 0usize;
+    let mut authed = false;
+    for line in lines {
+        let Some((k, v)) = line.split_once(':') else {
+            continue;
+        };
+        match k.trim().to_ascii_lowercase().as_str() {
+            "content-length" => content_length = v.trim().parse().unwrap_or(0),
+            "poly_api_key" => authed = !v.trim().is_empty(),
+            _ => {}
+        }
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).await.ok()?;
+    let (path, query) = target.split_once('?').unwrap_or((target.as_str(), ""));
+    Some(Request {
+        method,
+        path: path.to_string(),
+        query: query.to_string(),
+        authed,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+async fn serve_http(
+    stream: TcpStream,
+    connection: usize,
+    state: Arc<Mutex<State>>,
+    updates: broadcast::Sender<(String, String)>,
+) {
+    let mut reader = BufReader::new(stream);
+    while let Some(req) = read_request(&mut reader).await {
+        // Counted on arrival, so requests the client gave up on are counted too.
+        let delay_ms = {
+            let line = format!("{} {}", req.method, req.path);
+            let mut s = state.lock().unwrap();
+            let delay_ms = s
+                .delays
+                .iter()
+                .find(|(prefix, _)| line.starts_with(prefix.as_str()))
+                .map_or(0, |(_, ms)| *ms);
+            s.requests.push(line);
+            s.request_connections.push(connection);
+            delay_ms
+        };
+        if delay_ms > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+        }
+        let (status, body) = route(&req, &state, &updates);
+        let reason = match status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            502 => "Bad Gateway",
+            _ => "Not Found",
+        };
+        let body = if req.method == "HEAD" {
+            String::new()
+        } else {
+            body.to_string()
+        };
+        let response = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            status,
+            reason,
+            body.len(),
+            body
+        );
+        if reader
+            .get_mut()
+            .write_all(response.as_bytes())
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+fn route(
+    req: &Request,
+    state: &Mutex<State>,
+    updates: &broadcast::Sender<(String, String)>,
+) -> (u16, Value) {
+    let mut s = state.lock().unwrap();
+    let body: Value = serde_json::from_str(&req.body).unwrap_or(Value::Null);
+    let private = req.path.starts_with("/order")
+        || req.path.starts_with("/cancel")
+        || req.path.starts_with("/data/")
+        || req.path == "/balance-allowance";
+    if private && !req.authed {
+        return (401, json!({"error": "Unauthorized/Invalid api key"}));
+    }
+    let token = query_param(&req.query, "token_id").unwrap_or_default();
+    match (req.method.as_str(), req.path.as_str()) {
+        ("HEAD", _) | ("GET", "/") => (200, json!({})),
+        ("GET", "/book") if s.failing_books => (503, json!({"error": "service unavailable"})),
+        ("GET", "/book") => {
+            let book = s.books.get(token).cloned().unwrap_or_default();
+            (
+                200,
+                json!({
+                    "asset_id": token,
+                    "bids": levels_json(&book.bids),
+                    "asks": levels_json(&book.asks),
+                    "tick_size": "0.01",
+                    "min_order_size": "5",
+                }),
+            )
+        }
+        ("GET", "/fee-rate") => match s.fee_rates.get(token).copied().unwrap_or(Some(0)) {
+            Some(bps) => (200, json!({"base_fee": bps})),
+            None => (500, json!({"error": "internal error"})),
+        },
+        ("GET", "/markets") => {
+            let slug = query_param(&req.query, "slug").unwrap_or_default();
+            (200, Value::Array(s.market(slug).into_iter().collect()))
+        }
+        ("GET", path) if path.starts_with("/markets/slug/") => {
+            match s.market(&path["/markets/slug/".len()..]) {
+                Some(m) => (200, m),
+                None => (404, json!({"error": "market not found"})),
+            }
+        }
+        ("GET", path) if path.starts_with("/events/slug/") => {
+            match s.events.get(&path["/events/slug/".len()..]) {
+                Some(slugs) => {
+                    let markets: Vec<Value> = slugs
+                        .iter()
+                        .filter_map(|m| s.markets.get(m).cloned())
+                        .collect();
+                    (200, json!({ "markets": markets }))
+                }
+                None => (404, json!({"error": "event not found"})),
+            }
+        }
+        ("GET", "/api/v3/klines") => match s.spot {
+            // [open time, open, high, low, close]: flat candle at the spot.
+            Some(p) => (
+                200,
+                json!([[
+                    interval_start(now_unix()) * 1000,
+                    p.to_string(),
+                    p.to_string(),
+                    p.to_string(),
+                    p.to_string()
+                ]]),
+            ),
+            None => (404, json!({"error": "no spot"})),
+        },
+        ("GET", "/positions") => {
+            let positions: Vec<Value> = s
+                .shares
+                .iter()
+                .filter(|(_, size)| **size > Decimal::ZERO)
+                .map(|(asset, size)| json!({"asset": asset, "size": size.to_string()}))
+                .collect();
+            (200, Value::Array(positions))
+        }
+        ("POST", "/order") => {
+            s.land_held_orders(updates);
+            let order_type = body["orderType"].as_str().unwrap_or("GTC");
+            if s.late_posts > 0 {
+                s.late_posts -= 1;
+                s.landing
+                    .push((body["order"].clone(), order_type.to_string()));
+                return (502, json!({"error": "bad gateway"}));
+            }
+            let (status, resp) = s.place(&body["order"], order_type);
+            let token = body["order"]["tokenId"].as_str().unwrap_or_default();
+            publish(&s, token, updates);
+            (status, resp)
+        }
+        ("POST", "/orders") => {
+            s.land_held_orders(updates);
+            let items = body.as_array().cloned().unwrap_or_default();
+            if s.late_batches > 0 {
+                s.late_batches -= 1;
+                for item in &items {
+                    let order_type = item["orderType"].as_str().unwrap_or("GTC");
+                    s.landing_on_miss
+                        .push((item["order"].clone(), order_type.to_string()));
+                }
+                return (502, json!({"error": "bad gateway"}));
+            }
+            let results: Vec<Value> = items
+                .iter()
+                .map(|item| {
+                    let order_type = item["orderType"].as_str().unwrap_or("GTC");
+                    let (status, resp) = s.place(&item["order"], order_type);
+                    publish(
+                        &s,
+                        item["order"]["tokenId"].as_str().unwrap_or_default(),
+                        updates,
+                    );
+                    if status == 200 {
+                        resp
+                    } else {
+                        json!({"success": false, "errorMsg": resp["error"]})
+                    }
+                })
+                .collect();
+            (200, Value::Array(results))
+        }
+        ("DELETE", "/order") => {
+            let id = body["orderID"].as_str().unwrap_or_default().to_string();
+            (200, s.cancel(|o| o.id == id))
+        }
+        ("DELETE", "/cancel-market-orders") => {
+            let asset = body["asset_id"].as_str().unwrap_or_default().to_string();
+            (200, s.cancel(|o| o.token_id == asset))
+        }
+        ("DELETE", "/cancel-all") => (200, s.cancel(|_| true)),
+        ("GET", path) if path.starts_with("/data/order/") => {
+            let id = &path["/data/order/".len()..];
+            match s.orders.iter().find(|o| o.id == id) {
+                Some(o) if s.failing_lookups.contains(&o.token_id) => {
+                    (503, json!({"error": "service unavailable"}))
+                }
+                Some(o) => (200, State::order_json(o)),
+                None => {
+                    for (order, order_type) in std::mem::take(&mut s.landing_on_miss) {
+                        s.place(&order, &order_type);
+                        publish(&s, order["tokenId"].as_str().unwrap_or_default(), updates);
+                    }
+                    (404, json!({"error": "order not found"}))
+                }
+            }
+        }
+        ("GET", "/balance-allowance") => {
+            let balance = match query_param(&req.query, "asset_type") {
+                Some("COLLATERAL") => s.collateral,
+                _ => s.shares(token),
+            };
+            (
+                200,
+                json!({"balance": base_units(balance), "allowances": {}}),
+            )
+        }
+        ("GET", "/data/trades") => {
+            let after: u64 = query_param(&req.query, "after")
+                .and_then(|a| a.parse().ok())
+                .unwrap_or(0);
+            let data: Vec<Value> = s
+                .trades
+                .iter()
+                .filter(|t| {
+                    t["match_time"]
+                        .as_str()
+                        .and_then(|m| m.parse::<u64>().ok())
+                        .is_some_and(|m| m >= after)
+                })
+                .cloned()
+                .collect();
+            (200, json!({"data": data, "next_cursor": "LTE="}))
+        }
+        _ => (404, json!({"error": "not found"})),
+    }
+}
+
+/// The order's hash as the client signs it (Polygon, regular exchange); None when a field is
+/// missing.
+fn order_hash(order: &Value) -> Option<String> {
+    let text = |k: &str| match order.get(k)? {
+        Value::String(s) => Some(s.clone()),
+        v => Some(v.to_string()),
+    };
+    let address = |k: &str| {
+        let a = text(k)?;
+        H160::from_str(a.trim_start_matches("0x")).ok()
+    };
+    let uint = |k: &str| U256::from_dec_str(&text(k)?).ok();
+    let small = |k: &str| text(k)?.parse::<u64>().ok();
+    let fields = OrderFields {
+        salt: small("salt")?,
+        taker: address("taker")?,
+        token_id: uint("tokenId")?,
+        maker_amount: uint("makerAmount")?,
+        taker_amount: uint("takerAmount")?,
+        expiration: small("expiration")?,
+        nonce: small("nonce")?,
+        fee_rate_bps: small("feeRateBps")?,
+        side: if text("side")? == "BUY" { 0 } else { 1 },
+    };
+    let exchange = H160::from_str(EXCHANGE_ADDRESS_POLYGON.trim_start_matches("0x")).ok()?;
+    let signer = OrderSigner::new(
+        137,
+        exchange,
+        address("maker")?,
+        address("signer")?,
+        small("signatureType")? as u8,
+    );
+    Some(signer.order_hash(&fields))
+}
+
+/// Push the book of `token_id` to WS subscribers (after a fill consumed some of it).
+fn publish(s: &State, token_id: &str, updates: &broadcast::Sender<(String, String)>) {
+    if let Some(book) = s.books.get(token_id) {
+        let _ = updates.send((token_id.to_string(), book_event(token_id, book)));
+    }
+}
+
+/// One market-channel connection: `book` snapshots on subscribe, then every book change of the
+/// subscribed tokens.
+async fn serve_ws(
+    stream: TcpStream,
+    state: Arc<Mutex<State>>,
+    mut updates: broadcast::Receiver<(String, String)>,
+) {
+    let Ok(ws) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let (mut write, mut read) = ws.split();
+    let mut assets: HashSet<String> = HashSet::new();
+    loop {
+        tokio::select! {
+            msg = read.next() => {
+                let text = match msg {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                    Some(Ok(_)) => continue,
+                };
+                let Ok(sub) = serde_json::from_str::<Value>(&text) else {
+                    continue;
+                };
+                let ids: Vec<String> = sub["assets_ids"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect();
+                if sub["operation"].as_str() == Some("unsubscribe") {
+                    for id in &ids {
+                        assets.remove(id);
+                    }
+                    continue;
+                }
+                let snapshots: Vec<String> = {
+                    let mut s = state.lock().unwrap();
+                    s.ws_subscriptions.extend(ids.iter().cloned());
+                    ids.iter()
+                        .filter_map(|id| s.books.get(id).map(|b| book_event(id, b)))
+                        .collect()
+                };
+                assets.extend(ids);
+                for snapshot in snapshots {
+                    if write.send(Message::Text(snapshot)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            update = updates.recv() => {
+                let (token_id, event) = match update {
+                    Ok(u) => u,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                if assets.contains(&token_id) && write.send(Message::Text(event)).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
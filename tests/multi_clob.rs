@@ -0,0 +1,346 @@
+//! Multi-account positions: a GTC order that rests counts only what it filled on arrival; its
+//! later fills come from the trades history and move the account's position, which routes sells
+//! when the exchange balance is unknown. Split buys and sells keep each part at the market's min
+//! order size and merge a FOK part that reports no size as filled; a part that failed next to
+//! filled ones is reported in `failed_parts`. An order is canceled only by the account that
+//! placed it.
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sniper_core::clob::{
+    CancelOrdersResult, ClobClient, ClobError, LimitOrderParams, OrderSide, OrderType,
+    PlaceOrderResult,
+};
+use sniper_core::multi_clob::{AccountMode, MultiClob};
+use sniper_core::trades::Fill;
+use std::sync::{Arc, Mutex};
+
+/// One account: every order is accepted with `filled` shares (sells are refused with no match
+/// when `refuse_sells`); the trades history is `trades`. Order IDs are `<label>-<n>`.
+struct FakeAccount {
+    label: String,
+    filled: Option<Decimal>,
+    refuse_sells: bool,
+    trades: Arc<Mutex<Vec<Fill>>>,
+    placed: Arc<Mutex<Vec<LimitOrderParams>>>,
+    canceled: Arc<Mutex<Vec<String>>>,
+}
+
+impl FakeAccount {
+    fn new(label: &str, filled: Option<Decimal>) -> Self {
+        Self {
+            label: label.to_string(),
+            filled,
+            refuse_sells: false,
+            trades: Arc::new(Mutex::new(Vec::new())),
+            placed: Arc::new(Mutex::new(Vec::new())),
+            canceled: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ClobClient for FakeAccount {
+    async fn place_limit_order(
+        &self,
+        params: LimitOrderParams,
+        _order_type: OrderType,
+    ) -> Result<PlaceOrderResult> {
+        let refused = self.refuse_sells && params.side == OrderSide::Sell;
+        let n = {
+            let mut placed = self.placed.lock().unwrap();
+            placed.push(params);
+            placed.len()
+        };
+        Ok(PlaceOrderResult {
+            order_id: (!refused).then(|| format!("{}-{}", self.label, n)),
+            success: !refused,
+            error_msg: refused.then(|| "no orders found to match".to_string()),
+            error: refused.then_some(ClobError::NoMatch),
+            filled_size: self.filled.filter(|_| !refused),
+            http_status: None,
+            avg_price: None,
+            failed_parts: Vec::new(),
+        })
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<CancelOrdersResult> {
+        self.canceled.lock().unwrap().push(order_id.to_string());
+        Ok(CancelOrdersResult {
+            canceled: vec![order_id.to_string()],
+            ..Default::default()
+        })
+    }
+
+    async fn get_trades(&self, _after_unix: u64) -> Result<Vec<Fill>> {
+        Ok(self.trades.lock().unwrap().clone())
+    }
+}
+
+fn multi(accounts: Vec<FakeAccount>, mode: AccountMode) -> MultiClob {
+    let accounts = accounts
+        .into_iter()
+        .map(|a| {
+            let label = a.label.clone();
+            let client: Box<dyn ClobClient> = Box::new(a);
+            (label, client)
+        })
+        .collect();
+    MultiClob::new(accounts, mode)
+}
+
+fn order(side: OrderSide, price: Decimal, size: Decimal) -> LimitOrderParams {
+    LimitOrderParams {
+        token_id: "111".to_string(),
+        side,
+        price,
+        size,
+        expiration_unix: None,
+        post_only: false,
+        fee_rate_bps: None,
+        min_order_size: None,
+    }
+}
+
+fn fill(trade_id: &str, price: Decimal, size: Decimal) -> Fill {
+    Fill {
+        trade_id: trade_id.to_string(),
+        asset_id: "111".to_string(),
+        side: OrderSide::Buy,
+        price,
+        size,
+        fee_rate_bps: 0,
+        match_time_unix: 0,
+    }
+}
+
+#[tokio::test]
+async fn resting_buy_counts_only_its_fills() {
+    let account = FakeAccount::new("1", Some(Decimal::ZERO));
+    let (trades, placed) = (account.trades.clone(), account.placed.clone());
+    let multi = multi(vec![account], AccountMode::RoundRobin);
+
+    let r = multi
+        .place_limit_order(order(OrderSide::Buy, dec!(0.90), dec!(10)), OrderType::Gtc)
+        .await
+        .unwrap();
+    assert!(r.success);
+    // Nothing filled yet: no account holds the token.
+    let r = multi
+        .place_limit_order(order(OrderSide::Sell, dec!(0.95), dec!(10)), OrderType::Fak)
+        .await
+        .unwrap();
+    assert!(!r.success, "sold shares the resting buy never bought");
+
+    // 4 shares match at the buy's price (a fill at another price is someone else's order).
+    trades.lock().unwrap().extend([
+        fill("t1", dec!(0.90), dec!(4)),
+        fill("t2", dec!(0.91), dec!(3)),
+    ]);
+    multi.get_trades(0).await.unwrap();
+    // Seen trades are not counted twice.
+    multi.get_trades(0).await.unwrap();
+    let r = multi
+        .place_limit_order(order(OrderSide::Sell, dec!(0.95), dec!(10)), OrderType::Fak)
+        .await
+        .unwrap();
+    assert!(r.success);
+    assert_eq!(placed.lock().unwrap().last().unwrap().size, dec!(4));
+}
+
+#[tokio::test]
+async fn canceled_resting_buy_takes_no_later_fills() {
+    let account = FakeAccount::new("1", None);
+    let trades = account.trades.clone();
+    let multi = multi(vec![account], AccountMode::RoundRobin);
+    multi
+        .place_limit_order(order(OrderSide::Buy, dec!(0.90), dec!(10)), OrderType::Gtc)
+        .await
+        .unwrap();
+    multi.cancel_order("1-1").await.unwrap();
+    trades
+        .lock()
+        .unwrap()
+        .push(fill("t1", dec!(0.90), dec!(10)));
+    multi.get_trades(0).await.unwrap();
+    let r = multi
+        .place_limit_order(order(OrderSide::Sell, dec!(0.95), dec!(10)), OrderType::Fak)
+        .await
+        .unwrap();
+    assert!(!r.success);
+}
+
+#[tokio::test]
+async fn orders_are_canceled_by_the_account_that_placed_them() {
+    let accounts = vec![FakeAccount::new("a", None), FakeAccount::new("b", None)];
+    let canceled: Vec<_> = accounts.iter().map(|a| a.canceled.clone()).collect();
+    let multi = multi(accounts, AccountMode::Split);
+    let r = multi
+        .place_limit_order(order(OrderSide::Buy, dec!(0.90), dec!(20)), OrderType::Gtc)
+        .await
+        .unwrap();
+    assert_eq!(r.order_id.as_deref(), Some("a-1,b-1"));
+
+    let c = multi.cancel_order("a-1,b-1").await.unwrap();
+    assert_eq!(c.canceled, ["a-1", "b-1"]);
+    assert_eq!(*canceled[0].lock().unwrap(), ["a-1"]);
+    assert_eq!(*canceled[1].lock().unwrap(), ["b-1"]);
+
+    // An ID this client never placed is tried on every account.
+    multi.cancel_order("old-7").await.unwrap();
+    assert_eq!(*canceled[0].lock().unwrap(), ["a-1", "old-7"]);
+    assert_eq!(*canceled[1].lock().unwrap(), ["b-1", "old-7"]);
+}
+
+/// `n` accounts in split mode, each filling orders with `filled`; also the orders each one got.
+fn split_accounts(
+    n: usize,
+    filled: Option<Decimal>,
+) -> (MultiClob, Vec<Arc<Mutex<Vec<LimitOrderParams>>>>) {
+    let accounts: Vec<_> = (1..=n)
+        .map(|i| FakeAccount::new(&i.to_string(), filled))
+        .collect();
+    let placed = accounts.iter().map(|a| a.placed.clone()).collect();
+    (multi(accounts, AccountMode::Split), placed)
+}
+
+/// Round-robin accounts that bought `held[i]` shares each by FOK; also the orders each one got.
+async fn holding_accounts(
+    accounts: Vec<FakeAccount>,
+    held: &[Decimal],
+) -> (MultiClob, Vec<Arc<Mutex<Vec<LimitOrderParams>>>>) {
+    let placed = accounts.iter().map(|a| a.placed.clone()).collect();
+    let multi = multi(accounts, AccountMode::RoundRobin);
+    for size in held {
+        let r = multi
+            .place_limit_order(order(OrderSide::Buy, dec!(0.90), *size), OrderType::Fok)
+            .await
+            .unwrap();
+        assert!(r.success);
+    }
+    (multi, placed)
+}
+
+/// Sizes of the sells each account got.
+fn sell_sizes(placed: &[Arc<Mutex<Vec<LimitOrderParams>>>]) -> Vec<Decimal> {
+    placed
+        .iter()
+        .flat_map(|p| {
+            p.lock()
+                .unwrap()
+                .iter()
+                .filter(|o| o.side == OrderSide::Sell)
+                .map(|o| o.size)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn sizes(placed: &[Arc<Mutex<Vec<LimitOrderParams>>>]) -> Vec<Decimal> {
+    placed
+        .iter()
+        .flat_map(|p| p.lock().unwrap().iter().map(|o| o.size).collect::<Vec<_>>())
+        .collect()
+}
+
+#[tokio::test]
+async fn split_parts_keep_the_market_min_order_size() {
+    let (multi, placed) = split_accounts(3, None);
+    let mut buy = order(OrderSide::Buy, dec!(0.90), dec!(25));
+    buy.min_order_size = Some(dec!(10));
+    multi.place_limit_order(buy, OrderType::Gtc).await.unwrap();
+    assert_eq!(sizes(&placed), [dec!(12.5), dec!(12.5)]);
+
+    // Without a market minimum: the default of 5.
+    let (multi, placed) = split_accounts(3, None);
+    let buy = order(OrderSide::Buy, dec!(0.90), dec!(25));
+    multi.place_limit_order(buy, OrderType::Gtc).await.unwrap();
+    assert_eq!(sizes(&placed), [dec!(8.33), dec!(8.33), dec!(8.34)]);
+}
+
+#[tokio::test]
+async fn split_fok_parts_without_a_size_count_as_filled() {
+    let (multi, _) = split_accounts(2, None);
+    let r = multi
+        .place_limit_order(order(OrderSide::Buy, dec!(0.90), dec!(20)), OrderType::Fok)
+        .await
+        .unwrap();
+    assert!(r.success);
+    assert_eq!(r.filled_size, Some(dec!(20)));
+
+    // A resting part that reports nothing filled nothing yet.
+    let (multi, _) = split_accounts(2, None);
+    let r = multi
+        .place_limit_order(order(OrderSide::Buy, dec!(0.90), dec!(20)), OrderType::Gtc)
+        .await
+        .unwrap();
+    assert_eq!(r.filled_size, None);
+
+    let (multi, _) = split_accounts(2, Some(dec!(4)));
+    let r = multi
+        .place_limit_order(order(OrderSide::Buy, dec!(0.90), dec!(20)), OrderType::Fok)
+        .await
+        .unwrap();
+    assert_eq!(r.filled_size, Some(dec!(8)));
+}
+
+#[tokio::test]
+async fn sell_parts_keep_the_market_min_order_size() {
+    let accounts = vec![FakeAccount::new("a", None), FakeAccount::new("b", None)];
+    let (multi, placed) = holding_accounts(accounts, &[dec!(9), dec!(6)]).await;
+    let sell = order(OrderSide::Sell, dec!(0.95), dec!(12));
+    multi.place_limit_order(sell, OrderType::Fok).await.unwrap();
+    // 9 + 3 would leave the second part below 5: the first gives up 2.
+    assert_eq!(sell_sizes(&placed), [dec!(7), dec!(5)]);
+
+    // A holder below the min sells nothing: the share it can't place is reported unsold.
+    let accounts = vec![FakeAccount::new("a", None), FakeAccount::new("b", None)];
+    let (multi, placed) = holding_accounts(accounts, &[dec!(9), dec!(3)]).await;
+    let sell = order(OrderSide::Sell, dec!(0.95), dec!(10));
+    let r = multi.place_limit_order(sell, OrderType::Fok).await.unwrap();
+    assert_eq!(sell_sizes(&placed), [dec!(9)]);
+    assert!(r.success);
+    assert_eq!(r.filled_size, Some(dec!(9)));
+    assert_eq!(
+        r.failed_parts,
+        [ClobError::Other(
+            "1 sh not sellable at the min order size".to_string()
+        )]
+    );
+
+    // Below the min: the largest holder sells it alone.
+    let accounts = vec![FakeAccount::new("a", None), FakeAccount::new("b", None)];
+    let (multi, placed) = holding_accounts(accounts, &[dec!(3), dec!(4)]).await;
+    let sell = order(OrderSide::Sell, dec!(0.95), dec!(4));
+    let r = multi.place_limit_order(sell, OrderType::Fok).await.unwrap();
+    assert_eq!(sell_sizes(&placed), [dec!(4)]);
+    assert!(r.failed_parts.is_empty());
+}
+
+#[tokio::test]
+async fn failed_sell_parts_are_reported_next_to_the_fill() {
+    let mut refusing = FakeAccount::new("a", None);
+    refusing.refuse_sells = true;
+    let accounts = vec![refusing, FakeAccount::new("b", None)];
+    let (multi, placed) = holding_accounts(accounts, &[dec!(6), dec!(6)]).await;
+    let r = multi
+        .place_limit_order(order(OrderSide::Sell, dec!(0.95), dec!(12)), OrderType::Fok)
+        .await
+        .unwrap();
+    assert_eq!(sell_sizes(&placed), [dec!(6), dec!(6)]);
+    assert!(r.success);
+    assert_eq!(r.filled_size, Some(dec!(6)));
+    assert_eq!(r.failed_parts, [ClobError::NoMatch]);
+    assert_eq!(r.error, None);
+    assert_eq!(r.error_msg, None);
+
+    // Only the refusing account still holds shares: the whole sell failed, with its error.
+    let r = multi
+        .place_limit_order(order(OrderSide::Sell, dec!(0.95), dec!(6)), OrderType::Fok)
+        .await
+        .unwrap();
+    assert!(!r.success);
+    assert_eq!(r.error, Some(ClobError::NoMatch));
+    assert!(r.failed_parts.is_empty());
+}
@@ -0,0 +1,45 @@
+//! Observe-only mode (MM_OBSERVE_ONLY): with a live configuration but no credentials, the engine
+//! runs on the mock CLOB's book, sends nothing to the exchange and logs its would-be orders and
+//! closes.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::Run;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn logs_would_be_trades_without_credentials() {
+    let run = Run::start("observe", "8201", "8202", 8).await;
+    run.mock
+        .set_book(&run.up, &[("0.91", "100")], &[("0.93", "100")]);
+    run.mock
+        .set_book(&run.down, &[("0.06", "100")], &[("0.08", "100")]);
+    for key in ["PRIVATE_KEY", "API_KEY", "SECRET", "PASSPHRASE"] {
+        std::env::remove_var(key);
+    }
+    std::env::set_var("MM_OBSERVE_ONLY", "true");
+
+    let config = sniper_core::config::load_config().unwrap();
+    assert!(config.dry_run);
+    let engine = run.spawn();
+    // Entry 3 s after the start, then a bid over the TP.
+    run.at_secs_to_close(3).await;
+    run.mock
+        .set_book(&run.up, &[("0.98", "100")], &[("0.99", "100")]);
+    run.mock.resolve(&run.slug, true);
+    run.finish(engine).await;
+    assert!(
+        run.mock.orders().is_empty(),
+        "sent: {:?}",
+        run.mock.orders()
+    );
+
+    let closes = run.session_events("close");
+    assert_eq!(closes.len(), 1);
+    assert_eq!(closes[0]["exit_type"], "TP");
+    let orders = run.events("orders_");
+    assert!(orders
+        .iter()
+        .any(|o| o["side"] == "BUY" && o["state"] == "filled"));
+    assert!(orders.iter().any(|o| o["side"] == "SELL"));
+}
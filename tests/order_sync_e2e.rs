@@ -0,0 +1,53 @@
+//! Adaptive order sync (MM_ORDER_SYNC_MIN_MS / MM_ORDER_SYNC_MAX_MS) against the mock CLOB: a GTC
+//! buy resting a tick under the ask is synced with the trades history at the fast rate; once the
+//! ask moves 5 ticks away the sync backs off to the slow one.
+
+mod mock_clob;
+
+use mock_clob::{live_env, now_unix, wait_for, MockClob};
+use rust_decimal_macros::dec;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn syncs_faster_near_the_touch() {
+    let mock = MockClob::start().await;
+    let (up, down) = ("8201", "8202");
+    let now = now_unix();
+    mock.add_market("mock-order-sync", up, down, now - 60, now + 20);
+    mock.set_collateral(dec!(100));
+    mock.set_book(up, &[("0.92", "100")], &[("0.95", "100")]);
+    mock.set_book(down, &[("0.06", "100")], &[("0.08", "100")]);
+    live_env(&mock);
+    std::env::set_var("MM_TARGET_SLUG", "mock-order-sync");
+    // Bid in range: a GTC buy rests at max + 1 tick = 0.94, a tick under the ask.
+    std::env::set_var("MM_ORDER_STRATEGY", "gtc_resting");
+    std::env::set_var("MM_MAX_BUY_PRICE", "0.93");
+    std::env::set_var("MM_ORDER_SYNC_MIN_MS", "200");
+    std::env::set_var("MM_ORDER_SYNC_MAX_MS", "3000");
+
+    let config = sniper_core::config::load_config().unwrap();
+    let engine = tokio::spawn(sniper_core::runner::run_with_config(config));
+    assert!(
+        wait_for(15, || mock.orders().iter().any(|o| o.status == "LIVE")).await,
+        "no resting buy: orders {:?}",
+        mock.orders()
+    );
+
+    let before = mock.request_count("GET /data/trades");
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let near = mock.request_count("GET /data/trades") - before;
+    assert!(near >= 6, "{} syncs in 2 s a tick from the ask", near);
+
+    mock.set_book(up, &[("0.92", "100")], &[("0.99", "100")]);
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let before = mock.request_count("GET /data/trades");
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    let far = mock.request_count("GET /data/trades") - before;
+    assert!(far <= 1, "{} syncs in 2.5 s 5 ticks from the ask", far);
+
+    tokio::time::timeout(Duration::from_secs(30), engine)
+        .await
+        .expect("engine did not stop at close")
+        .unwrap()
+        .unwrap();
+}
@@ -0,0 +1,282 @@
+//! Order lifecycle: a resting order goes created → submitted → live → partially_filled → filled
+//! from its REST result and the trades history, an immediate order ends on its result (a FAK
+//! partial fill cancels the rest), a cancel response ends the orders it names, a refused or lost
+//! submission is rejected, and a transition the state machine does not allow is ignored. Every
+//! step is in the audit, timed on the tracker's clock.
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sniper_core::clob::{
+    CancelOrdersResult, ClobError, LimitOrderParams, OrderSide, OrderType, PlaceOrderResult,
+};
+use sniper_core::clock::SimClock;
+use sniper_core::order_tracker::{OrderState, OrderTracker};
+use sniper_core::trades::Fill;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn params(token: &str, side: OrderSide, size: Decimal) -> LimitOrderParams {
+    LimitOrderParams {
+        token_id: token.to_string(),
+        side,
+        price: dec!(0.90),
+        size,
+        expiration_unix: None,
+        post_only: false,
+        fee_rate_bps: Some(0),
+        min_order_size: None,
+    }
+}
+
+fn accepted(order_id: &str, filled_size: Option<Decimal>) -> PlaceOrderResult {
+    PlaceOrderResult {
+        order_id: Some(order_id.to_string()),
+        success: true,
+        error_msg: None,
+        error: None,
+        filled_size,
+        http_status: Some(200),
+        avg_price: filled_size.map(|_| dec!(0.90)),
+        failed_parts: Vec::new(),
+    }
+}
+
+fn refused(msg: &str) -> PlaceOrderResult {
+    PlaceOrderResult {
+        order_id: None,
+        success: false,
+        error_msg: Some(msg.to_string()),
+        error: Some(ClobError::parse(msg)),
+        filled_size: None,
+        http_status: Some(400),
+        avg_price: None,
+        failed_parts: Vec::new(),
+    }
+}
+
+fn fill(trade_id: &str, token: &str, size: Decimal) -> Fill {
+    Fill {
+        trade_id: trade_id.to_string(),
+        asset_id: token.to_string(),
+        side: OrderSide::Buy,
+        price: dec!(0.90),
+        size,
+        fee_rate_bps: 0,
+        match_time_unix: 0,
+    }
+}
+
+/// Tracker auditing to a fresh temp dir named after `name`.
+fn tracker(name: &str) -> (OrderTracker, PathBuf) {
+    let dir = std::env::temp_dir().join(format!(
+        "sniper-order-tracker-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    let tracker = OrderTracker::new(dir.to_str(), 1_000).unwrap();
+    (tracker, dir)
+}
+
+/// "state" or "state (reason)" of each audited transition of order `id`, oldest first.
+fn audit(dir: &PathBuf, id: u64) -> Vec<String> {
+    let file = std::fs::read_dir(dir).unwrap().next().unwrap().unwrap();
+    std::fs::read_to_string(file.path())
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+        .filter(|v| v["id"] == id)
+        .map(|v| {
+            let state = v["state"].as_str().unwrap().to_string();
+            match v["reason"].as_str() {
+                Some(reason) => format!("{} ({})", state, reason),
+                None => state,
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn a_resting_order_fills_from_the_trades_history() {
+    let (tracker, dir) = tracker("rest");
+    let clock = Arc::new(SimClock::new(5_000));
+    let mut tracker = tracker.with_clock(clock.clone());
+    let id = tracker.create(&params("t", OrderSide::Buy, dec!(10)), OrderType::Gtc);
+    tracker.submitted(id);
+    clock.advance(Duration::from_millis(40));
+    tracker.on_result(id, &accepted("0xa", None));
+    let order = tracker.get(id).unwrap();
+    assert_eq!(order.state, OrderState::Live);
+    assert_eq!(order.exchange_id.as_deref(), Some("0xa"));
+    assert_eq!(order.filled, Decimal::ZERO);
+    let times: Vec<_> = order.history.iter().map(|(_, ms)| *ms).collect();
+    assert_eq!(times, [5_000, 5_000, 5_040]);
+
+    // A fill on another token does not touch it; a trade seen twice counts once.
+    tracker.on_fills(&[fill("1", "u", dec!(4)), fill("2", "t", dec!(4))]);
+    tracker.on_fills(&[fill("2", "t", dec!(4))]);
+    let order = tracker.get(id).unwrap();
+    assert_eq!(order.state, OrderState::PartiallyFilled);
+    assert_eq!(order.filled, dec!(4));
+
+    // More than the rest fills only the rest; a filled order is no longer open.
+    tracker.on_fills(&[fill("3", "t", dec!(9))]);
+    assert!(tracker.get(id).is_none());
+    assert!(!tracker.has_open());
+    assert_eq!(
+        audit(&dir, id),
+        ["created", "submitted", "live", "partially_filled", "filled"]
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn immediate_orders_end_on_their_result() {
+    let (mut tracker, dir) = tracker("immediate");
+    // A FOK success without a size filled in full.
+    let fok = tracker.create(&params("t", OrderSide::Buy, dec!(5)), OrderType::Fok);
+    tracker.submitted(fok);
+    tracker.on_result(fok, &accepted("0xf", None));
+    assert!(tracker.get(fok).is_none());
+
+    // A FAK filling 6 of 10 cancels the other 4.
+    let fak = tracker.create(&params("t", OrderSide::Sell, dec!(10)), OrderType::Fak);
+    tracker.submitted(fak);
+    tracker.on_result(fak, &accepted("0xg", Some(dec!(6))));
+    assert!(tracker.get(fak).is_none());
+
+    assert_eq!(audit(&dir, fok), ["created", "submitted", "filled"]);
+    assert_eq!(
+        audit(&dir, fak),
+        [
+            "created",
+            "submitted",
+            "partially_filled",
+            "cancelled (unfilled remainder)"
+        ]
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn a_cancel_ends_the_orders_it_names() {
+    let (mut tracker, dir) = tracker("cancel");
+    let mut place = |token: &str, exchange_id: &str| {
+        let id = tracker.create(&params(token, OrderSide::Buy, dec!(10)), OrderType::Gtc);
+        tracker.submitted(id);
+        tracker.on_result(id, &accepted(exchange_id, None));
+        id
+    };
+    let (a, b, c) = (place("t", "0xa"), place("t", "0xb"), place("u", "0xc"));
+    tracker.on_fills(&[fill("1", "t", dec!(3))]);
+    let canceled = |ids: &[&str]| CancelOrdersResult {
+        canceled: ids.iter().map(|s| s.to_string()).collect(),
+        ..Default::default()
+    };
+
+    // "0xa" rests on "t": a cancel for "u" naming it does not end it.
+    tracker.on_cancel(Some("u"), &canceled(&["0xa"]));
+    assert_eq!(tracker.get(a).unwrap().state, OrderState::PartiallyFilled);
+    // "0xb" was not canceled.
+    tracker.on_cancel(Some("t"), &canceled(&["0xa"]));
+    assert!(tracker.get(a).is_none());
+    assert_eq!(tracker.get(b).unwrap().state, OrderState::Live);
+    // A single-order cancel names no token.
+    tracker.on_cancel(None, &canceled(&["0xc"]));
+    assert!(tracker.get(c).is_none());
+    assert_eq!(tracker.open().map(|o| o.id).collect::<Vec<_>>(), [b]);
+
+    assert_eq!(
+        audit(&dir, a),
+        [
+            "created",
+            "submitted",
+            "live",
+            "partially_filled",
+            "cancelled (canceled)"
+        ]
+    );
+    assert_eq!(
+        audit(&dir, c),
+        ["created", "submitted", "live", "cancelled (canceled)"]
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn refused_and_lost_submissions_are_rejected() {
+    let (mut tracker, dir) = tracker("reject");
+    let refused_id = tracker.create(&params("t", OrderSide::Buy, dec!(5)), OrderType::Fok);
+    tracker.submitted(refused_id);
+    tracker.on_result(refused_id, &refused("not enough balance / allowance"));
+
+    // Transport error: no response at all.
+    let lost = tracker.create(&params("t", OrderSide::Sell, dec!(5)), OrderType::Fak);
+    tracker.submitted(lost);
+    tracker.on_error(lost, "connection reset");
+
+    // Never sent (the batch returned no result for it).
+    let unsent = tracker.create(&params("t", OrderSide::Buy, dec!(5)), OrderType::Gtc);
+    tracker.on_error(unsent, "no result in batch");
+
+    assert!(!tracker.has_open());
+    assert_eq!(
+        audit(&dir, refused_id),
+        [
+            "created",
+            "submitted",
+            "rejected (not enough balance / allowance)"
+        ]
+    );
+    assert_eq!(
+        audit(&dir, lost),
+        ["created", "submitted", "rejected (connection reset)"]
+    );
+    assert_eq!(
+        audit(&dir, unsent),
+        ["created", "rejected (no result in batch)"]
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn illegal_transitions_are_ignored() {
+    let (mut tracker, dir) = tracker("illegal");
+    let id = tracker.create(&params("t", OrderSide::Buy, dec!(10)), OrderType::Gtc);
+    // A result before the submission.
+    tracker.on_result(id, &accepted("0xa", Some(dec!(10))));
+    assert_eq!(tracker.get(id).unwrap().state, OrderState::Created);
+    assert_eq!(tracker.get(id).unwrap().filled, Decimal::ZERO);
+
+    tracker.submitted(id);
+    tracker.submitted(id);
+    tracker.on_result(id, &accepted("0xa", None));
+    // A second result, and an error once the order is on the book.
+    tracker.on_result(id, &accepted("0xb", Some(dec!(10))));
+    tracker.on_error(id, "late transport error");
+    let order = tracker.get(id).unwrap();
+    assert_eq!(order.state, OrderState::Live);
+    assert_eq!(order.exchange_id.as_deref(), Some("0xa"));
+    assert_eq!(order.filled, Decimal::ZERO);
+
+    // Nothing moves a terminal order.
+    tracker.on_fills(&[fill("1", "t", dec!(10))]);
+    tracker.on_error(id, "late transport error");
+    tracker.on_fills(&[fill("2", "t", dec!(10))]);
+    assert!(tracker.get(id).is_none());
+    assert_eq!(audit(&dir, id), ["created", "submitted", "live", "filled"]);
+
+    for terminal in [
+        OrderState::Filled,
+        OrderState::Cancelled,
+        OrderState::Rejected,
+    ] {
+        assert!(!terminal.can_become(OrderState::Live));
+        assert!(!terminal.can_become(OrderState::Cancelled));
+    }
+    assert!(!OrderState::Live.can_become(OrderState::Rejected));
+    assert!(!OrderState::Live.can_become(OrderState::Submitted));
+    assert!(!OrderState::Created.can_become(OrderState::Live));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,64 @@
+//! Up / Down token IDs from Gamma's outcome labels: Up/Down and Yes/No in either order, and two
+//! other labels (team names) mapped by MM_OUTCOME_UP or kept in Gamma's order.
+
+use sniper_core::market::token_ids_with_up_label;
+use sniper_core::types::GammaMarket;
+
+fn market(outcomes: &str) -> GammaMarket {
+    serde_json::from_value(serde_json::json!({
+        "slug": "m",
+        "clobTokenIds": "[\"111\", \"222\"]",
+        "outcomes": outcomes,
+    }))
+    .unwrap()
+}
+
+fn ids(up: &str, down: &str) -> (String, String) {
+    (up.to_string(), down.to_string())
+}
+
+#[test]
+fn swaps_reversed_up_down_labels() {
+    let m = market("[\"No\", \"Yes\"]");
+    assert_eq!(
+        token_ids_with_up_label(&m, None).unwrap(),
+        ids("222", "111")
+    );
+    let m = market("[\"Up\", \"Down\"]");
+    assert_eq!(
+        token_ids_with_up_label(&m, None).unwrap(),
+        ids("111", "222")
+    );
+}
+
+#[test]
+fn maps_team_outcomes_by_the_configured_label() {
+    let m = market("[\"Lakers\", \"Celtics\"]");
+    assert_eq!(
+        token_ids_with_up_label(&m, Some("celtics")).unwrap(),
+        ids("222", "111")
+    );
+    assert_eq!(
+        token_ids_with_up_label(&m, Some("Lakers")).unwrap(),
+        ids("111", "222")
+    );
+}
+
+#[test]
+fn keeps_gamma_order_for_team_outcomes_without_a_label() {
+    let m = market("[\"Lakers\", \"Celtics\"]");
+    assert_eq!(
+        token_ids_with_up_label(&m, None).unwrap(),
+        ids("111", "222")
+    );
+    assert_eq!(
+        token_ids_with_up_label(&m, Some("Knicks")).unwrap(),
+        ids("111", "222")
+    );
+}
+
+#[test]
+fn refuses_more_than_two_outcomes() {
+    let m = market("[\"Lakers\", \"Celtics\", \"Draw\"]");
+    assert!(token_ids_with_up_label(&m, None).is_err());
+}
@@ -0,0 +1,68 @@
+//! A dual-side batch whose outcome the client never learns: the POST times out and so does the
+//! order lookup, while the exchange fills both legs. The engine keeps running, reads the legs
+//! back from the token balances and holds the pair; it does not buy a second one.
+
+mod mock_clob;
+
+use mock_clob::{live_env, now_unix, wait_for, MockClob};
+use rust_decimal_macros::dec;
+use std::path::Path;
+use std::time::Duration;
+
+fn status(path: &Path) -> Option<serde_json::Value> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failed_batch_is_reconciled_from_balances() {
+    let mock = MockClob::start().await;
+    let (up, down) = ("8801", "8802");
+    let now = now_unix();
+    mock.add_market("mock-pair-error", up, down, now - 60, now + 15);
+    mock.set_collateral(dec!(100));
+    mock.set_book(up, &[("0.44", "100")], &[("0.45", "100")]);
+    mock.set_book(down, &[("0.44", "100")], &[("0.45", "100")]);
+    // The batch fills after the client gave up on it; the lookup never answers in time.
+    mock.set_delay("POST /orders", 700);
+    mock.set_delay("GET /data/order", 2000);
+    live_env(&mock);
+    let dir = std::env::temp_dir().join(format!("sniper-pair-error-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("status.json");
+    std::env::set_var("MM_TARGET_SLUG", "mock-pair-error");
+    std::env::set_var("MM_DUAL_SIDE_ENTRY", "true");
+    std::env::set_var("MM_HTTP_TIMEOUT_ORDER_MS", "400");
+    std::env::set_var("MM_HTTP_TIMEOUT_BALANCE_MS", "300");
+    std::env::set_var("MM_HTTP_RETRY_MAX_ATTEMPTS", "1");
+    std::env::set_var("MM_STATUS_FILE", &file);
+    std::env::set_var("MM_STATUS_SECS", "1");
+
+    let config = sniper_core::config::load_config().unwrap();
+    let engine = tokio::spawn(sniper_core::runner::run_with_config(config));
+    assert!(wait_for(20, || mock.request_count("GET /data/order") >= 1).await);
+    assert_eq!(mock.shares(up), dec!(5));
+    assert_eq!(mock.shares(down), dec!(5));
+    assert!(
+        wait_for(10, || status(&file)
+            .is_some_and(|s| s["position"]["hedged_pair"] == true))
+        .await,
+        "pair not reconciled: status {:?}",
+        status(&file)
+    );
+    let s = status(&file).unwrap();
+    assert_eq!(s["position"]["up"]["size"], "5", "{}", s["position"]);
+    assert_eq!(s["position"]["down"]["size"], "5", "{}", s["position"]);
+
+    tokio::time::timeout(Duration::from_secs(30), engine)
+        .await
+        .expect("engine did not stop at close")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        mock.request_count("POST /orders"),
+        1,
+        "bought a second pair"
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
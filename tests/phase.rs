@@ -0,0 +1,155 @@
+//! Runner phases: every transition of the interval (idle / entered / stopped out) and position
+//! (armed / exited) state machine, and what the entry and exit gates read from it.
+
+use sniper_core::phase::{IntervalPhase, Phases, PositionPhase};
+use sniper_core::session_log::ExitType;
+
+fn phases(interval: IntervalPhase, position: PositionPhase) -> Phases {
+    let mut p = Phases::default();
+    match interval {
+        IntervalPhase::Idle => {}
+        IntervalPhase::Entered => p.on_entry_sent(),
+        IntervalPhase::StoppedOut => {
+            p.on_entry_sent();
+            p.on_exit(ExitType::StopLoss);
+        }
+    }
+    match position {
+        PositionPhase::Armed => p.on_position_opened(),
+        PositionPhase::Exited => p.on_exit(ExitType::Resolution),
+    }
+    assert_eq!((p.interval(), p.position()), (interval, position));
+    p
+}
+
+const ALL: [(IntervalPhase, PositionPhase); 6] = [
+    (IntervalPhase::Idle, PositionPhase::Armed),
+    (IntervalPhase::Idle, PositionPhase::Exited),
+    (IntervalPhase::Entered, PositionPhase::Armed),
+    (IntervalPhase::Entered, PositionPhase::Exited),
+    (IntervalPhase::StoppedOut, PositionPhase::Armed),
+    (IntervalPhase::StoppedOut, PositionPhase::Exited),
+];
+
+#[test]
+fn starts_idle_and_armed() {
+    let p = Phases::default();
+    assert_eq!(p.interval(), IntervalPhase::Idle);
+    assert_eq!(p.position(), PositionPhase::Armed);
+    assert!(!p.entered() && !p.re_entry_after_sl() && p.exits_armed());
+}
+
+#[test]
+fn interval_start_resets_every_state() {
+    for (i, pos) in ALL {
+        let mut p = phases(i, pos);
+        p.on_interval_start();
+        assert_eq!(p, Phases::default(), "from {:?}/{:?}", i, pos);
+    }
+}
+
+#[test]
+fn entry_sent_enters_an_idle_interval_only() {
+    for (i, pos) in ALL {
+        let mut p = phases(i, pos);
+        p.on_entry_sent();
+        let want = match i {
+            IntervalPhase::Idle => IntervalPhase::Entered,
+            other => other,
+        };
+        assert_eq!(p.interval(), want, "from {:?}/{:?}", i, pos);
+        assert_eq!(p.position(), pos);
+        assert!(p.entered());
+    }
+}
+
+#[test]
+fn entry_void_frees_the_slot() {
+    for (i, pos) in ALL {
+        let mut p = phases(i, pos);
+        p.on_entry_void();
+        assert_eq!(p.interval(), IntervalPhase::Idle, "from {:?}/{:?}", i, pos);
+        assert_eq!(p.position(), pos);
+        assert!(!p.entered());
+    }
+}
+
+#[test]
+fn position_opened_arms_the_exits() {
+    for (i, pos) in ALL {
+        let mut p = phases(i, pos);
+        p.on_position_opened();
+        assert_eq!(p.interval(), i, "from {:?}/{:?}", i, pos);
+        assert!(p.exits_armed());
+    }
+}
+
+#[test]
+fn take_profit_ends_re_entry_after_sl() {
+    for (i, pos) in ALL {
+        let mut p = phases(i, pos);
+        p.on_exit(ExitType::TakeProfit);
+        let want = match i {
+            IntervalPhase::Idle => IntervalPhase::Idle,
+            _ => IntervalPhase::Entered,
+        };
+        assert_eq!(p.interval(), want, "from {:?}/{:?}", i, pos);
+        assert_eq!(p.position(), PositionPhase::Exited);
+        assert!(!p.re_entry_after_sl() && !p.exits_armed());
+    }
+}
+
+#[test]
+fn stop_loss_allows_re_entry_after_sl() {
+    for (i, pos) in ALL {
+        let mut p = phases(i, pos);
+        p.on_exit(ExitType::StopLoss);
+        let want = match i {
+            IntervalPhase::Idle => IntervalPhase::Idle,
+            _ => IntervalPhase::StoppedOut,
+        };
+        assert_eq!(p.interval(), want, "from {:?}/{:?}", i, pos);
+        assert_eq!(p.position(), PositionPhase::Exited);
+        assert_eq!(p.re_entry_after_sl(), i != IntervalPhase::Idle);
+    }
+}
+
+#[test]
+fn resolution_and_market_close_keep_the_interval() {
+    for exit in [ExitType::Resolution, ExitType::MarketClose] {
+        for (i, pos) in ALL {
+            let mut p = phases(i, pos);
+            p.on_exit(exit);
+            assert_eq!(p.interval(), i, "{:?} from {:?}/{:?}", exit, i, pos);
+            assert_eq!(p.position(), PositionPhase::Exited);
+        }
+    }
+}
+
+#[test]
+fn soft_stop_changes_nothing() {
+    for (i, pos) in ALL {
+        let mut p = phases(i, pos);
+        let before = p;
+        p.on_exit(ExitType::SoftStop);
+        assert_eq!(p, before, "from {:?}/{:?}", i, pos);
+    }
+}
+
+#[test]
+fn re_entry_after_sl_survives_the_re_entry_until_a_take_profit() {
+    let mut p = Phases::default();
+    p.on_entry_sent();
+    p.on_position_opened();
+    p.on_exit(ExitType::StopLoss);
+    assert!(p.re_entry_after_sl());
+    p.on_entry_sent();
+    p.on_position_opened();
+    assert_eq!(p.interval(), IntervalPhase::StoppedOut);
+    assert!(p.exits_armed());
+    p.on_exit(ExitType::TakeProfit);
+    assert_eq!(
+        (p.interval(), p.position()),
+        (IntervalPhase::Entered, PositionPhase::Exited)
+    );
+}
@@ -0,0 +1,67 @@
+//! Public reads (book, fee rate, markets) go over their own unauthenticated connections, never
+//! over the ones carrying orders and balance calls: the engine's reads, and the fee rate the live
+//! client looks up itself when an order does not carry one.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::Run;
+use mock_clob::wait_for;
+use rust_decimal_macros::dec;
+use sniper_core::clob::{ClobClient, LimitOrderParams, LiveClob, OrderSide, OrderType};
+use std::collections::HashSet;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reads_do_not_share_the_order_connections() {
+    let run = Run::start("public-reads", "8801", "8802", 6).await;
+    let (mock, up) = (&run.mock, run.up.as_str());
+    mock.set_book(up, &[("0.91", "100")], &[("0.92", "100")]);
+    mock.set_book(&run.down, &[("0.06", "100")], &[("0.08", "100")]);
+
+    let engine = run.spawn();
+    assert!(
+        wait_for(6, || mock.shares(up) == dec!(5)).await,
+        "no entry: orders {:?}",
+        mock.orders()
+    );
+    run.finish(engine).await;
+    let clob = LiveClob::from_env().unwrap();
+    let fee_reads = mock.request_count("GET /fee-rate");
+    let placed = clob
+        .place_limit_order(
+            LimitOrderParams {
+                token_id: up.to_string(),
+                side: OrderSide::Buy,
+                price: dec!(0.92),
+                size: dec!(5),
+                expiration_unix: None,
+                post_only: false,
+                fee_rate_bps: None,
+                min_order_size: None,
+            },
+            OrderType::Fak,
+        )
+        .await
+        .unwrap();
+    assert!(placed.success, "{:?}", placed.error_msg);
+    assert_eq!(mock.request_count("GET /fee-rate"), fee_reads + 1);
+
+    let authed: HashSet<usize> = ["POST /order", "GET /balance-allowance", "DELETE /"]
+        .iter()
+        .flat_map(|p| mock.connections(p))
+        .collect();
+    let reads: HashSet<usize> = ["GET /book", "GET /fee-rate", "GET /markets"]
+        .iter()
+        .flat_map(|p| mock.connections(p))
+        .collect();
+    assert!(
+        mock.request_count("GET /fee-rate") > 0,
+        "fee rate not fetched"
+    );
+    assert!(!authed.is_empty() && !reads.is_empty());
+    assert!(
+        authed.is_disjoint(&reads),
+        "shared connections: {:?}",
+        authed.intersection(&reads).collect::<Vec<_>>()
+    );
+}
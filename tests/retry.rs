@@ -0,0 +1,126 @@
+//! REST retries: which answers and failures each endpoint class sends again (orders only when
+//! they cannot have reached the book), Retry-After parsing, and backoffs slept on the caller's
+//! clock.
+
+mod mock_clob;
+
+use mock_clob::MockClob;
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use reqwest::StatusCode;
+use sniper_core::clock::{Clock, SimClock};
+use sniper_core::rate_limit::EndpointClass;
+use sniper_core::retry::{
+    retry_after_ms, retry_failure, retry_status, send_with_clock, SendFailure,
+};
+use std::time::{Duration, Instant};
+
+const READS: [EndpointClass; 3] = [
+    EndpointClass::Cancel,
+    EndpointClass::Book,
+    EndpointClass::Balance,
+];
+
+#[test]
+fn orders_retry_only_429() {
+    assert!(retry_status(
+        EndpointClass::Order,
+        StatusCode::TOO_MANY_REQUESTS
+    ));
+    assert!(!retry_status(
+        EndpointClass::Order,
+        StatusCode::SERVICE_UNAVAILABLE
+    ));
+    assert!(!retry_status(
+        EndpointClass::Order,
+        StatusCode::INTERNAL_SERVER_ERROR
+    ));
+    for class in READS {
+        assert!(
+            retry_status(class, StatusCode::TOO_MANY_REQUESTS),
+            "{:?}",
+            class
+        );
+        assert!(retry_status(class, StatusCode::BAD_GATEWAY), "{:?}", class);
+    }
+    for class in READS.into_iter().chain([EndpointClass::Order]) {
+        assert!(!retry_status(class, StatusCode::OK), "{:?}", class);
+        assert!(!retry_status(class, StatusCode::BAD_REQUEST), "{:?}", class);
+    }
+}
+
+#[test]
+fn orders_retry_only_failures_before_sending() {
+    assert!(retry_failure(EndpointClass::Order, SendFailure::Connect));
+    assert!(!retry_failure(EndpointClass::Order, SendFailure::Timeout));
+    assert!(!retry_failure(EndpointClass::Order, SendFailure::Request));
+    for class in READS {
+        assert!(retry_failure(class, SendFailure::Connect), "{:?}", class);
+        assert!(retry_failure(class, SendFailure::Timeout), "{:?}", class);
+        assert!(retry_failure(class, SendFailure::Request), "{:?}", class);
+        assert!(!retry_failure(class, SendFailure::Other), "{:?}", class);
+    }
+}
+
+#[test]
+fn parses_retry_after_seconds() {
+    let header = |v: &str| {
+        let mut h = HeaderMap::new();
+        h.insert(RETRY_AFTER, HeaderValue::from_str(v).unwrap());
+        h
+    };
+    assert_eq!(retry_after_ms(&header("2")), Some(2000));
+    assert_eq!(retry_after_ms(&header(" 0.25 ")), Some(250));
+    assert_eq!(retry_after_ms(&header("0")), Some(0));
+    // Capped so a bad header cannot stall the loop.
+    assert_eq!(retry_after_ms(&header("3600")), Some(10_000));
+    assert_eq!(retry_after_ms(&header("-1")), None);
+    assert_eq!(retry_after_ms(&header("soon")), None);
+    assert_eq!(
+        retry_after_ms(&header("Wed, 21 Oct 2026 07:28:00 GMT")),
+        None
+    );
+    assert_eq!(retry_after_ms(&HeaderMap::new()), None);
+}
+
+#[tokio::test]
+async fn backs_off_on_the_given_clock() {
+    // Backoffs of up to a minute: only a simulated clock gets through them in time.
+    std::env::set_var("MM_HTTP_RETRY_MAX_ATTEMPTS", "3");
+    std::env::set_var("MM_HTTP_RETRY_BASE_MS", "60000");
+    std::env::set_var("MM_HTTP_RETRY_MAX_MS", "60000");
+    let mock = MockClob::start().await;
+    mock.set_fee_rate("8801", None);
+    let http = reqwest::Client::new();
+    let url = format!("{}/fee-rate?token_id=8801", mock.rest_url());
+    let clock = SimClock::new(1_000_000);
+
+    let started = Instant::now();
+    let res = send_with_clock(&clock, EndpointClass::Book, "GET /fee-rate", || {
+        Ok(http.get(&url))
+    })
+    .await
+    .unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(mock.request_count("GET /fee-rate"), 3);
+    assert!(
+        clock.now_ms() <= 1_000_000 + 2 * 60_000,
+        "{}",
+        clock.now_ms()
+    );
+    assert!(
+        started.elapsed() < Duration::from_secs(5),
+        "{:?}",
+        started.elapsed()
+    );
+
+    // An order is not sent again after a 5xx: it may have reached the book.
+    let before = clock.now_ms();
+    let res = send_with_clock(&clock, EndpointClass::Order, "GET /fee-rate", || {
+        Ok(http.get(&url))
+    })
+    .await
+    .unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(mock.request_count("GET /fee-rate"), 4);
+    assert_eq!(clock.now_ms(), before);
+}
@@ -0,0 +1,23 @@
+//! Order salt counter (MM_SALT_FILE): unique and increasing within a run, and a restart with the
+//! file resumes above the previous run even when the clock reads earlier.
+
+use sniper_core::salt::SaltAllocator;
+
+#[test]
+fn salts_keep_increasing_across_restarts() {
+    let path = std::env::temp_dir().join(format!("sniper-salt-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let path = path.to_str().unwrap();
+    let now_ms = 1_772_169_300_000;
+
+    let first = SaltAllocator::open(Some(path), now_ms);
+    let salts: Vec<u64> = (0..25_000).map(|_| first.next_salt()).collect();
+    assert!(salts.windows(2).all(|w| w[1] > w[0]));
+    assert!(salts[0] >= now_ms * 1000);
+    assert!(*salts.last().unwrap() < 1 << 53);
+
+    // Restart with the clock a minute back: the file keeps the new run above the old one.
+    let second = SaltAllocator::open(Some(path), now_ms - 60_000);
+    assert!(second.next_salt() > *salts.last().unwrap());
+    let _ = std::fs::remove_file(path);
+}
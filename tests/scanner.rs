@@ -0,0 +1,70 @@
+//! Scanner discovery (MM_SCANNER_*): markets listed on the first poll are seen, a new market is
+//! reported once, and one below MM_SCANNER_MIN_LIQUIDITY is checked again until its liquidity
+//! arrives.
+
+use rust_decimal_macros::dec;
+use sniper_core::scanner::{ScannerConfig, SeenMarkets};
+use sniper_core::types::GammaMarket;
+
+fn scanner() -> ScannerConfig {
+    ScannerConfig {
+        poll_ms: 5000,
+        tag_id: None,
+        title_regex: None,
+        min_liquidity: dec!(500),
+        auto_start: true,
+        max_engines: 3,
+    }
+}
+
+fn market(slug: &str, liquidity: f64) -> GammaMarket {
+    serde_json::from_value(serde_json::json!({
+        "slug": slug,
+        "question": slug,
+        "liquidityNum": liquidity,
+    }))
+    .unwrap()
+}
+
+fn slugs(markets: &[GammaMarket]) -> Vec<&str> {
+    markets.iter().filter_map(|m| m.slug.as_deref()).collect()
+}
+
+#[test]
+fn first_poll_only_marks_markets_as_seen() {
+    let scanner = scanner();
+    let mut seen = SeenMarkets::default();
+    assert!(seen
+        .new_matches(&scanner, vec![market("old", 1000.0)])
+        .is_empty());
+    assert!(seen
+        .new_matches(&scanner, vec![market("old", 1000.0)])
+        .is_empty());
+}
+
+#[test]
+fn reports_a_new_market_once() {
+    let scanner = scanner();
+    let mut seen = SeenMarkets::default();
+    seen.new_matches(&scanner, Vec::new());
+    let new = seen.new_matches(&scanner, vec![market("new", 1000.0)]);
+    assert_eq!(slugs(&new), ["new"]);
+    assert!(seen
+        .new_matches(&scanner, vec![market("new", 1000.0)])
+        .is_empty());
+}
+
+#[test]
+fn matches_a_market_once_its_liquidity_rises() {
+    let scanner = scanner();
+    let mut seen = SeenMarkets::default();
+    seen.new_matches(&scanner, Vec::new());
+    assert!(seen
+        .new_matches(&scanner, vec![market("new", 0.0)])
+        .is_empty());
+    assert!(seen
+        .new_matches(&scanner, vec![market("new", 120.0)])
+        .is_empty());
+    let new = seen.new_matches(&scanner, vec![market("new", 800.0)]);
+    assert_eq!(slugs(&new), ["new"]);
+}
@@ -0,0 +1,248 @@
+//! TP / SL sell retries: what a fill reports (a FAK fills part, a split sell with a failed part
+//! leaves the rest held), when the campaign stops (the market's deadline, a TP's book emptying,
+//! an SL's bid recovering), how a balance/allowance error backs off after one cancel, and what is
+//! sold of a short or dust balance.
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sniper_core::clob::{ClobError, PlaceOrderResult};
+use sniper_core::sell_campaign::{partly_sold, AttemptWait, SellAction, SellCampaign, SellKind};
+
+const NO_MATCH: &str = "no orders found to match";
+const NO_BALANCE: &str = "not enough balance / allowance";
+
+fn success(filled: Option<Decimal>) -> PlaceOrderResult {
+    PlaceOrderResult {
+        order_id: Some("0x1".to_string()),
+        success: true,
+        error_msg: None,
+        error: None,
+        filled_size: filled,
+        http_status: Some(200),
+        avg_price: None,
+        failed_parts: Vec::new(),
+    }
+}
+
+fn failed(msg: &str) -> PlaceOrderResult {
+    PlaceOrderResult {
+        order_id: None,
+        success: false,
+        error_msg: Some(msg.to_string()),
+        error: Some(ClobError::parse(msg)),
+        filled_size: None,
+        http_status: Some(400),
+        avg_price: None,
+        failed_parts: Vec::new(),
+    }
+}
+
+/// Campaign of a sell at limit 0.80 whose first order failed with `first`.
+fn campaign(kind: SellKind, position_size: Decimal, first: &str) -> SellCampaign {
+    SellCampaign::start(kind, position_size, dec!(0.80), &failed(first))
+        .expect("retryable first error")
+}
+
+#[test]
+fn only_no_match_and_balance_errors_are_retried() {
+    assert_eq!(
+        campaign(SellKind::TakeProfit, dec!(5), NO_MATCH).attempt(),
+        0
+    );
+    assert!(!campaign(SellKind::TakeProfit, dec!(5), NO_MATCH).balance_error());
+    assert!(campaign(SellKind::StopLoss, dec!(5), NO_BALANCE).balance_error());
+    for msg in [
+        "invalid amounts",
+        "order 0x1 is invalid. Duplicated.",
+        "market closed",
+    ] {
+        assert!(
+            SellCampaign::start(SellKind::StopLoss, dec!(5), dec!(0.80), &failed(msg)).is_none()
+        );
+    }
+}
+
+#[test]
+fn partial_fill_reports_the_filled_size() {
+    let c = campaign(SellKind::TakeProfit, dec!(5), NO_MATCH);
+    assert_eq!(
+        c.on_result(&success(Some(dec!(2))), dec!(0.97), dec!(5)),
+        SellAction::Filled {
+            price: dec!(0.97),
+            size: dec!(2)
+        }
+    );
+    // No size reported: the order size; never more than it.
+    assert_eq!(
+        c.on_result(&success(None), dec!(0.97), dec!(5)),
+        SellAction::Filled {
+            price: dec!(0.97),
+            size: dec!(5)
+        }
+    );
+    assert_eq!(
+        c.on_result(&success(Some(dec!(6))), dec!(0.97), dec!(5)),
+        SellAction::Filled {
+            price: dec!(0.97),
+            size: dec!(5)
+        }
+    );
+    // Reported 0 filled: nothing sold, not the order size.
+    assert_eq!(
+        c.on_result(&success(Some(Decimal::ZERO)), dec!(0.97), dec!(5)),
+        SellAction::Retry
+    );
+}
+
+#[test]
+fn split_sell_with_a_failed_part_is_partly_filled() {
+    let c = campaign(SellKind::StopLoss, dec!(12), NO_MATCH);
+    let mut r = success(Some(dec!(6)));
+    r.failed_parts = vec![ClobError::NoMatch];
+    assert_eq!(
+        c.on_result(&r, dec!(0.79), dec!(12)),
+        SellAction::PartFilled {
+            price: dec!(0.79),
+            size: dec!(6)
+        }
+    );
+    // Every part went through: a plain fill.
+    r.failed_parts.clear();
+    assert!(!partly_sold(&r, dec!(12)));
+}
+
+#[test]
+fn no_attempt_starts_at_the_deadline() {
+    let mut c = campaign(SellKind::StopLoss, dec!(5), NO_MATCH).until(10_000);
+    // First SL retry at once, then every 50 ms.
+    let waits: Vec<_> = [0, 9_000, 9_999]
+        .iter()
+        .map(|now| c.next_attempt(*now).map(|w| w.delay_ms))
+        .collect();
+    assert_eq!(waits, [Some(0), Some(50), Some(50)]);
+    assert_eq!(c.next_attempt(10_000), None);
+    assert_eq!(c.next_attempt(12_000), None);
+    assert_eq!(c.attempt(), 3);
+
+    // Without a deadline it goes on until the caller stops it.
+    let mut c = campaign(SellKind::TakeProfit, dec!(5), NO_MATCH);
+    assert_eq!(c.next_attempt(u64::MAX).map(|w| w.delay_ms), Some(30));
+}
+
+#[test]
+fn an_empty_book_ends_a_tp_but_not_an_sl() {
+    let tp = campaign(SellKind::TakeProfit, dec!(5), NO_MATCH);
+    // Book side gone: back to the tick loop, where the SL still watches the position.
+    assert_eq!(tp.on_bid(Decimal::ZERO), Some(SellAction::GiveUp));
+    // Below the target: wait for it.
+    assert_eq!(tp.on_bid(dec!(0.79)), Some(SellAction::Retry));
+    assert_eq!(tp.on_bid(dec!(0.80)), None);
+
+    let sl = campaign(SellKind::StopLoss, dec!(5), NO_MATCH);
+    // No bid to sell into yet: keep trying (or hedge).
+    assert_eq!(sl.on_bid(Decimal::ZERO), Some(SellAction::Retry));
+    // Recovered above the trigger: stop.
+    assert_eq!(sl.on_bid(dec!(0.81)), Some(SellAction::GiveUp));
+    assert_eq!(sl.on_bid(dec!(0.80)), None);
+}
+
+#[test]
+fn balance_below_the_campaign_size() {
+    let mut c = campaign(SellKind::StopLoss, dec!(5), NO_BALANCE);
+    // Cancel once, then back off.
+    let waits: Vec<AttemptWait> = (0..4).map(|_| c.next_attempt(0).unwrap()).collect();
+    assert_eq!(
+        waits.iter().map(|w| w.delay_ms).collect::<Vec<_>>(),
+        [100, 200, 400, 400]
+    );
+    assert_eq!(
+        waits.iter().map(|w| w.cancel_first).collect::<Vec<_>>(),
+        [true, false, false, false]
+    );
+    // Only what is available is sold, a base unit under it.
+    assert_eq!(
+        c.on_available(dec!(0.79), Some(dec!(3)), dec!(0.01)),
+        SellAction::Sell {
+            price: dec!(0.79),
+            size: dec!(2.9999)
+        }
+    );
+    // Balance unknown: the whole campaign size.
+    assert_eq!(
+        c.on_available(dec!(0.79), None, dec!(0.01)),
+        SellAction::Sell {
+            price: dec!(0.79),
+            size: dec!(5)
+        }
+    );
+    // Still short: back off again.
+    assert_eq!(
+        c.on_result(&failed(NO_BALANCE), dec!(0.79), dec!(2.9999)),
+        SellAction::Retry
+    );
+    // Any other refusal ends the campaign.
+    assert_eq!(
+        c.on_result(
+            &failed("order 0x1 is invalid. Duplicated."),
+            dec!(0.79),
+            dec!(5)
+        ),
+        SellAction::GiveUp
+    );
+}
+
+#[test]
+fn an_sl_waits_for_a_locked_balance() {
+    // The first sell could not be placed: nothing was available.
+    let mut c = SellCampaign::awaiting_balance(SellKind::StopLoss, dec!(5), dec!(0.80));
+    assert!(!c.balance_error());
+    let wait = c.next_attempt(0).unwrap();
+    assert_eq!((wait.delay_ms, wait.cancel_first), (0, false));
+    assert_eq!(
+        c.on_available(dec!(0.79), Some(Decimal::ZERO), dec!(0.01)),
+        SellAction::Retry
+    );
+    // The shares are freed.
+    assert_eq!(
+        c.on_available(dec!(0.794), Some(dec!(10)), dec!(0.01)),
+        SellAction::Sell {
+            price: dec!(0.79),
+            size: dec!(5)
+        }
+    );
+}
+
+#[test]
+fn dust_below_the_min_sell_size() {
+    let tiny = Some(dec!(0.00005));
+    let sl = campaign(SellKind::StopLoss, dec!(5), NO_MATCH);
+    let tp = campaign(SellKind::TakeProfit, dec!(5), NO_MATCH);
+    // Nothing sellable available: SL waits for the balance, TP stops.
+    assert_eq!(
+        sl.on_available(dec!(0.79), tiny, dec!(0.01)),
+        SellAction::Retry
+    );
+    assert_eq!(
+        tp.on_available(dec!(0.97), tiny, dec!(0.01)),
+        SellAction::GiveUp
+    );
+    // Dust of a dust position closes it.
+    let dust = campaign(SellKind::StopLoss, dec!(0.005), NO_MATCH);
+    assert_eq!(
+        dust.on_available(dec!(0.79), Some(dec!(0.005)), dec!(0.01)),
+        SellAction::Closed
+    );
+    // Dust available of a real position is sold (the balance has not caught up).
+    assert_eq!(
+        sl.on_available(dec!(0.79), Some(dec!(0.005)), dec!(0.01)),
+        SellAction::Sell {
+            price: dec!(0.79),
+            size: dec!(0.0049)
+        }
+    );
+    // The exchange rejecting the amount closes it too.
+    assert_eq!(
+        sl.on_result(&failed("invalid amounts"), dec!(0.79), dec!(0.0049)),
+        SellAction::Closed
+    );
+}
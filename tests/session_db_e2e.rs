@@ -0,0 +1,83 @@
+//! Session database (MM_SESSION_DB) from a live run against the mock CLOB: the run's close, its
+//! orders, the interval and the session summary land in SQLite next to the JSONL session log.
+#![cfg(feature = "sqlite")]
+
+mod mock_clob;
+
+use mock_clob::{live_env, now_unix, wait_for, MockClob};
+use rust_decimal_macros::dec;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn records_the_run_in_sqlite() {
+    let mock = MockClob::start().await;
+    let (up, down) = ("8001", "8002");
+    let now = now_unix();
+    mock.add_market("mock-session-db", up, down, now - 60, now + 20);
+    mock.set_collateral(dec!(100));
+    mock.set_book(up, &[("0.91", "100")], &[("0.92", "100")]);
+    mock.set_book(down, &[("0.06", "100")], &[("0.08", "100")]);
+    live_env(&mock);
+    let dir = std::env::temp_dir().join(format!("sniper-session-db-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let db = dir.join("sniper.db");
+    std::env::set_var("MM_TARGET_SLUG", "mock-session-db");
+    std::env::set_var("MM_SESSION_LOG_DIR", &dir);
+    std::env::set_var("MM_SESSION_DB", &db);
+
+    let config = sniper_core::config::load_config().unwrap();
+    let engine = tokio::spawn(sniper_core::runner::run_with_config(config));
+    assert!(
+        wait_for(30, || mock.shares(up) == dec!(5)).await,
+        "no entry: orders {:?}",
+        mock.orders()
+    );
+    mock.set_book(up, &[("0.98", "100")], &[("0.99", "100")]);
+    assert!(
+        wait_for(10, || mock.shares(up) < dec!(1)).await,
+        "no TP: orders {:?}",
+        mock.orders()
+    );
+    tokio::time::timeout(Duration::from_secs(30), engine)
+        .await
+        .expect("engine did not stop at close")
+        .unwrap()
+        .unwrap();
+
+    let conn = rusqlite::Connection::open(&db).unwrap();
+    let (exit_type, side, pnl): (String, String, f64) = conn
+        .query_row("SELECT exit_type, side, pnl_usd FROM closes", [], |r| {
+            Ok((r.get(0)?, r.get(1)?, r.get(2)?))
+        })
+        .unwrap();
+    assert_eq!((exit_type.as_str(), side.as_str()), ("TP", "Up"));
+    assert!(pnl > 0.0);
+    let filled: Vec<(String, f64)> = conn
+        .prepare("SELECT side, filled FROM orders ORDER BY id")
+        .unwrap()
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(
+        filled.len(),
+        2,
+        "the entry buy and the TP sell: {:?}",
+        filled
+    );
+    assert_eq!(filled[0], ("BUY".to_string(), 5.0));
+    assert_eq!(filled[1].0, "SELL");
+    assert!(filled[1].1 > 4.9);
+    let intervals: i64 = conn
+        .query_row("SELECT COUNT(*) FROM intervals", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(intervals, 1);
+    let (tp_count, ended): (i64, Option<i64>) = conn
+        .query_row("SELECT tp_count, session_end_ms FROM sessions", [], |r| {
+            Ok((r.get(0)?, r.get(1)?))
+        })
+        .unwrap();
+    assert_eq!(tp_count, 1);
+    assert!(ended.is_some());
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,87 @@
+//! Per-interval sizing (MM_SIZING): a stake that buys at least the min order size is converted to
+//! shares; one that buys less (no Kelly edge, a small cap or bankroll) sizes the interval at 0.
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sniper_core::inventory::Inventory;
+use sniper_core::sizing::interval_size;
+use sniper_core::types::{Config, EntrySide, SizingMode};
+
+fn config(sizing: SizingMode) -> Config {
+    let mut c = sniper_core::config::load_config().unwrap();
+    c.sizing = sizing;
+    c.sizing_fraction = dec!(0.05);
+    c.kelly_multiplier = dec!(0.5);
+    c.kelly_min_trades = 2;
+    c.sizing_max_fraction = dec!(0.25);
+    c.sizing_max_shares = Decimal::ZERO;
+    c
+}
+
+/// One close at `exit` per entry at 0.90, 10 shares each.
+fn closes(exits: &[Decimal]) -> Inventory {
+    let mut inv = Inventory::default();
+    for (i, exit) in exits.iter().enumerate() {
+        let token = format!("t{}", i);
+        inv.add_lot(&token, EntrySide::Up, dec!(0.90), dec!(10), 0);
+        inv.close(&token, dec!(10), *exit);
+    }
+    inv
+}
+
+#[test]
+fn fixed_mode_keeps_the_configured_size() {
+    let inv = Inventory::default();
+    let c = config(SizingMode::Fixed);
+    assert!(interval_size(&c, dec!(1000), inv.stats(), dec!(0.9), dec!(5)).is_none());
+}
+
+#[test]
+fn fraction_of_the_bankroll_at_the_entry_price() {
+    let inv = Inventory::default();
+    let c = config(SizingMode::Fraction);
+    let s = interval_size(&c, dec!(1000), inv.stats(), dec!(0.9), dec!(5)).unwrap();
+    // 5% of 1000 USDC at 0.90.
+    assert_eq!(s.shares, dec!(55.55));
+    assert_eq!(s.fraction, dec!(0.05));
+}
+
+#[test]
+fn stake_below_the_min_order_size_is_zero() {
+    let inv = Inventory::default();
+    let c = config(SizingMode::Fraction);
+    // 5% of 50 USDC at 0.90 = 2.77 sh < 5.
+    let s = interval_size(&c, dec!(50), inv.stats(), dec!(0.9), dec!(5)).unwrap();
+    assert_eq!(s.shares, Decimal::ZERO);
+}
+
+#[test]
+fn max_shares_cap_below_the_min_order_size_is_zero() {
+    let inv = Inventory::default();
+    let mut c = config(SizingMode::Fraction);
+    c.sizing_max_shares = dec!(3);
+    let s = interval_size(&c, dec!(1000), inv.stats(), dec!(0.9), dec!(5)).unwrap();
+    assert_eq!(s.shares, Decimal::ZERO);
+}
+
+#[test]
+fn kelly_without_an_edge_stakes_nothing() {
+    // One win of 0.05 and one loss of 0.30 per share: negative Kelly.
+    let inv = closes(&[dec!(0.95), dec!(0.60)]);
+    let c = config(SizingMode::Kelly);
+    let s = interval_size(&c, dec!(1000), inv.stats(), dec!(0.9), dec!(5)).unwrap();
+    assert_eq!(s.fraction, Decimal::ZERO);
+    assert_eq!(s.basis, "kelly (no edge)");
+    assert_eq!(s.shares, Decimal::ZERO);
+}
+
+#[test]
+fn kelly_with_an_edge_is_capped() {
+    // Two wins of 0.09 and one loss of 0.01 per share.
+    let inv = closes(&[dec!(0.99), dec!(0.99), dec!(0.89)]);
+    let c = config(SizingMode::Kelly);
+    let s = interval_size(&c, dec!(1000), inv.stats(), dec!(0.9), dec!(5)).unwrap();
+    assert_eq!(s.basis, "kelly");
+    assert_eq!(s.fraction, dec!(0.25));
+    assert_eq!(s.shares, dec!(277.77));
+}
@@ -0,0 +1,64 @@
+//! SL hedge bid (MM_SL_HEDGE_BID) against the mock CLOB: the stop loss finds our side's book
+//! empty and the opposite ask out of reach, so it rests a bid for the opposite token at 1 − our
+//! last bid and holds the pair once it fills.
+
+mod mock_clob;
+
+use mock_clob::{live_env, now_unix, wait_for, MockClob, Side};
+use rust_decimal_macros::dec;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bids_for_the_opposite_token_when_the_book_empties() {
+    let mock = MockClob::start().await;
+    let (up, down) = ("4001", "4002");
+    let now = now_unix();
+    mock.add_market("mock-hedge-bid", up, down, now - 60, now + 25);
+    mock.set_collateral(dec!(100));
+    mock.set_book(up, &[("0.90", "100")], &[("0.91", "100")]);
+    mock.set_book(down, &[("0.08", "100")], &[("0.10", "100")]);
+    live_env(&mock);
+    std::env::set_var("MM_TARGET_SLUG", "mock-hedge-bid");
+    std::env::set_var("MM_SL_HEDGE_EXIT", "true");
+    std::env::set_var("MM_SL_HEDGE_BID", "true");
+    std::env::set_var("MM_SL_HEDGE_SLIPPAGE", "0.10");
+
+    let config = sniper_core::config::load_config().unwrap();
+    let engine = tokio::spawn(sniper_core::runner::run_with_config(config));
+
+    assert!(
+        wait_for(30, || mock.shares(up) == dec!(5)).await,
+        "no entry: orders {:?}",
+        mock.orders()
+    );
+
+    // Under the SL (0.80) with too little size for the FOK sell; then the bids vanish and the
+    // Down ask sits above the hedge limit (1 − 0.80 + 0.10 = 0.30).
+    mock.set_book(down, &[("0.05", "100")], &[("0.60", "100")]);
+    mock.set_book(up, &[("0.75", "1")], &[("0.95", "100")]);
+    assert!(
+        wait_for(10, || mock.request_count("POST /order") >= 2).await,
+        "no SL attempt: orders {:?}",
+        mock.orders()
+    );
+    mock.set_book(up, &[], &[("0.95", "100")]);
+
+    let is_hedge_bid = |o: &mock_clob::MockOrder| o.token_id == down && o.side == Side::Buy;
+    assert!(
+        wait_for(10, || mock.orders().iter().any(is_hedge_bid)).await,
+        "no hedge bid: orders {:?}",
+        mock.orders()
+    );
+    let bid = mock.orders().into_iter().find(is_hedge_bid).unwrap();
+    assert_eq!(bid.price, dec!(0.25));
+    assert_eq!(bid.status, "LIVE");
+
+    // A seller reaches the bid: the pair is held, nothing of Up is sold.
+    mock.set_book(down, &[("0.05", "100")], &[("0.25", "100")]);
+    assert!(
+        wait_for(10, || mock.shares(down) == dec!(5)).await,
+        "hedge bid not filled: orders {:?}",
+        mock.orders()
+    );
+    assert_eq!(mock.shares(up), dec!(5));
+    engine.abort();
+}
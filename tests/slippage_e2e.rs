@@ -0,0 +1,73 @@
+//! Fill slippage from a live run against the mock CLOB: order requests are slowed down and the
+//! book thins out while the entry is in flight, so it fills partly a tick above the best ask the
+//! engine decided on; the TP sells at the bid it saw. The session summary reports that per order
+//! type and the order audit carries each order's decision and fill price.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::Run;
+use mock_clob::wait_for;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::str::FromStr;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn session_summary_reports_slippage_per_order_type() {
+    let run = Run::start("slippage", "8101", "8102", 10).await;
+    let (mock, up) = (&run.mock, run.up.as_str());
+    mock.set_book(up, &[("0.90", "100")], &[("0.91", "100")]);
+    mock.set_book(&run.down, &[("0.06", "100")], &[("0.08", "100")]);
+    mock.set_delay("POST /order", 1000);
+    std::env::set_var("MM_SESSION_LOG", "true");
+    std::env::set_var("MM_ORDER_AUDIT", "true");
+
+    let engine = run.spawn();
+    // The buy is in flight: two shares left at the best ask, the rest one tick up.
+    assert!(wait_for(6, || mock.request_count("POST /order") == 1).await);
+    mock.set_book_rest_only(up, &[("0.90", "100")], &[("0.91", "2"), ("0.92", "100")]);
+    assert!(
+        wait_for(3, || mock.shares(up) == dec!(5)).await,
+        "no entry: orders {:?}",
+        mock.orders()
+    );
+    mock.set_book(up, &[("0.98", "100")], &[("0.99", "100")]);
+    assert!(
+        wait_for(4, || mock.shares(up) < dec!(1)).await,
+        "no TP: orders {:?}",
+        mock.orders()
+    );
+    run.finish(engine).await;
+
+    let summaries = run.session_events("session_summary");
+    let summary = summaries.first().expect("no session summary");
+    let slippage = &summary["slippage"];
+    let dec_at = |v: &serde_json::Value| Decimal::from_str(v.as_str().unwrap()).unwrap();
+    let (fills, shares, cost): (u64, Decimal, Decimal) = slippage
+        .as_object()
+        .expect("no slippage object")
+        .values()
+        .map(|a| {
+            (
+                a["fills"].as_u64().unwrap(),
+                dec_at(&a["shares"]),
+                dec_at(&a["cost_usd"]),
+            )
+        })
+        .fold((0, Decimal::ZERO, Decimal::ZERO), |acc, x| {
+            (acc.0 + x.0, acc.1 + x.1, acc.2 + x.2)
+        });
+    assert_eq!(fills, 2, "entry and TP: {}", slippage);
+    assert!(shares >= dec!(9.9), "{}", slippage);
+    // Entry: 3 of 5 shares a tick above the 0.91 ask; TP: all at the 0.98 bid.
+    assert_eq!(cost, dec!(0.03), "{}", slippage);
+    assert_eq!(slippage["FAK"]["worst"], "0.006", "{}", slippage);
+
+    let audit = run.events("orders_");
+    let entry = audit
+        .iter()
+        .find(|l| l["side"] == "BUY" && l["state"] == "filled")
+        .expect("no filled entry in the audit");
+    assert_eq!(entry["decision_price"], "0.91");
+    assert_eq!(dec_at(&entry["avg_fill_price"]), dec!(0.916));
+}
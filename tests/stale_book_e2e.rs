@@ -0,0 +1,39 @@
+//! MM_MAX_BOOK_AGE_MS against the mock CLOB: a WebSocket book that stops updating while REST is
+//! down is not entered on (declined as "stale_book") until the book is fresh again.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::Run;
+use mock_clob::wait_for;
+use rust_decimal_macros::dec;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn entries_wait_for_a_fresh_book() {
+    let run = Run::start("stale-book", "3101", "3102", 24).await;
+    let (mock, up) = (&run.mock, run.up.as_str());
+    mock.set_book(up, &[("0.90", "100")], &[("0.91", "100")]);
+    mock.set_book(&run.down, &[("0.08", "100")], &[("0.10", "100")]);
+    // REST is down: only the WS snapshot at subscribe stamps the book.
+    mock.fail_books(true);
+    std::env::set_var("MM_HTTP_RETRY_MAX_ATTEMPTS", "1");
+    std::env::set_var("MM_MAX_BOOK_AGE_MS", "1000");
+    std::env::set_var("MM_SESSION_LOG", "true");
+
+    let engine = run.spawn();
+    // Entries open 3 s after the start and 20 s before the close, on a book 4 s old.
+    run.at_secs_to_close(18).await;
+    assert!(mock.orders().is_empty(), "bought: {:?}", mock.orders());
+
+    mock.fail_books(false);
+    assert!(
+        wait_for(5, || mock.shares(up) == dec!(5)).await,
+        "no entry on the fresh book: orders {:?}",
+        mock.orders()
+    );
+    run.finish(engine).await;
+
+    let summaries = run.session_events("interval_summary");
+    let declined = &summaries.first().expect("no interval summary")["declined_entries"];
+    assert!(declined["stale_book"].as_u64().unwrap() > 0, "{}", declined);
+}
@@ -0,0 +1,60 @@
+//! Profit sweep amounts: nothing until the Safe balance is above MM_SWEEP_THRESHOLD_USDC, then
+//! everything above MM_SWEEP_FLOAT_USDC; and USDC <-> 6-decimal token units.
+
+use ethers::types::{H160, U256};
+use rust_decimal_macros::dec;
+use sniper_core::chain::{usdc_from_units, usdc_to_units};
+use sniper_core::sweep::SweepConfig;
+
+fn config(threshold: rust_decimal::Decimal, float: rust_decimal::Decimal) -> SweepConfig {
+    SweepConfig {
+        rpc_urls: Vec::new(),
+        cold_address: H160::zero(),
+        threshold,
+        float,
+    }
+}
+
+#[test]
+fn no_sweep_at_the_threshold() {
+    let c = config(dec!(500), dec!(200));
+    assert_eq!(c.sweep_amount(dec!(500)), None);
+    assert_eq!(c.sweep_amount(dec!(120)), None);
+    assert_eq!(c.sweep_amount(dec!(0)), None);
+}
+
+#[test]
+fn sweeps_down_to_the_float_above_the_threshold() {
+    let c = config(dec!(500), dec!(200));
+    assert_eq!(c.sweep_amount(dec!(500.000001)), Some(dec!(300.000001)));
+    assert_eq!(c.sweep_amount(dec!(812.5)), Some(dec!(612.5)));
+}
+
+#[test]
+fn float_equal_to_the_threshold() {
+    let c = config(dec!(500), dec!(500));
+    assert_eq!(c.sweep_amount(dec!(500)), None);
+    assert_eq!(c.sweep_amount(dec!(500.01)), Some(dec!(0.01)));
+}
+
+#[test]
+fn usdc_units_truncate_to_six_decimals() {
+    assert_eq!(usdc_to_units(dec!(1)).unwrap(), U256::from(1_000_000u64));
+    assert_eq!(
+        usdc_to_units(dec!(300.000001)).unwrap(),
+        U256::from(300_000_001u64)
+    );
+    assert_eq!(usdc_to_units(dec!(0.0000019)).unwrap(), U256::from(1u64));
+    assert_eq!(usdc_to_units(dec!(0.0000009)).unwrap(), U256::zero());
+    assert!(usdc_to_units(dec!(-1)).is_err());
+}
+
+#[test]
+fn usdc_units_round_trip() {
+    for amount in [dec!(0), dec!(0.000001), dec!(12.345678), dec!(1000000.5)] {
+        assert_eq!(usdc_from_units(usdc_to_units(amount).unwrap()), amount);
+    }
+    assert_eq!(usdc_from_units(U256::from(612_500_000u64)), dec!(612.5));
+    assert_eq!(usdc_from_units(U256::from(612_500_000u64)).scale(), 6);
+    assert_eq!(usdc_from_units(U256::MAX), rust_decimal::Decimal::MAX);
+}
@@ -0,0 +1,39 @@
+//! Interval switch against the mock CLOB: an entry buy still in flight when the market closes is
+//! waited for, and its fill is held to resolution with the market instead of being dropped.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::{Run, CLOSE};
+use mock_clob::wait_for;
+use rust_decimal_macros::dec;
+use sniper_core::clock::Clock;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn books_a_fill_that_lands_after_the_close() {
+    let run = Run::start("switch-drain", "8801", "8802", 6).await;
+    run.mock
+        .set_book(&run.up, &[("0.91", "100")], &[("0.92", "100")]);
+    run.mock
+        .set_book(&run.down, &[("0.06", "100")], &[("0.08", "100")]);
+    // The buy is answered about a second after the close.
+    run.mock.set_delay("POST /order", 4000);
+    std::env::set_var("MM_SESSION_LOG", "true");
+
+    let engine = run.spawn();
+    assert!(wait_for(8, || run.mock.request_count("POST /order") == 1).await);
+    run.finish(engine).await;
+    assert!(run.clock.now_unix() >= CLOSE);
+    assert_eq!(
+        run.mock.shares(&run.up),
+        dec!(5),
+        "orders {:?}",
+        run.mock.orders()
+    );
+
+    let closes = run.session_events("close");
+    assert_eq!(closes.len(), 1, "closes: {:?}", closes);
+    assert_eq!(closes[0]["exit_type"], "RESOLUTION");
+    assert_eq!(closes[0]["side"], "Up");
+    assert_eq!(closes[0]["size"], "5");
+}
@@ -0,0 +1,44 @@
+//! TP below the fee break-even against the mock CLOB: with MM_TAKE_PROFIT_PRICE under what a
+//! round trip costs in fees, the entry's TP target is raised so its fill never loses money.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::Run;
+use mock_clob::wait_for;
+use rust_decimal_macros::dec;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tp_target_is_raised_to_the_fee_break_even() {
+    let run = Run::start("tp-breakeven", "3101", "3102", 10).await;
+    let (mock, up) = (&run.mock, run.up.as_str());
+    mock.set_book(up, &[("0.90", "100")], &[("0.91", "100")]);
+    mock.set_book(&run.down, &[("0.08", "100")], &[("0.10", "100")]);
+    mock.set_fee_rate(up, Some(1000));
+    mock.set_fee_rate(&run.down, Some(1000));
+    std::env::set_var("MM_TAKE_PROFIT_PRICE", "0.93");
+
+    let engine = run.spawn();
+    assert!(
+        wait_for(10, || mock.shares(up) == dec!(5)).await,
+        "no entry: orders {:?}",
+        mock.orders()
+    );
+    assert_eq!(mock.orders()[0].price, dec!(0.92));
+
+    // 1000 bps: the round trip from 0.92 breaks even near 0.935, so the TP (selling from its
+    // target less the 0.01 margin) moves from 0.93 to 0.95. A bid of 0.93 no longer sells.
+    mock.set_book(up, &[("0.93", "100")], &[("0.94", "100")]);
+    run.at_secs_to_close(run.secs_to_close() - 2).await;
+    assert_eq!(mock.shares(up), dec!(5), "orders {:?}", mock.orders());
+
+    mock.set_book(up, &[("0.94", "100")], &[("0.95", "100")]);
+    assert!(
+        wait_for(5, || mock.shares(up) < dec!(0.1)).await,
+        "no TP: orders {:?}",
+        mock.orders()
+    );
+    let sell = mock.orders().into_iter().last().unwrap();
+    assert_eq!(sell.price, dec!(0.94));
+    run.finish(engine).await;
+}
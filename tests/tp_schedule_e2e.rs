@@ -0,0 +1,45 @@
+//! MM_TP_SCHEDULE against the mock CLOB: a schedule cap under the fee break-even does not lower
+//! the TP below it, so the tightened TP still never sells at a loss.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::Run;
+use mock_clob::wait_for;
+use rust_decimal_macros::dec;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn schedule_cap_stops_at_the_fee_break_even() {
+    let run = Run::start("tp-schedule", "3201", "3202", 10).await;
+    let (mock, up) = (&run.mock, run.up.as_str());
+    mock.set_book(up, &[("0.90", "100")], &[("0.91", "100")]);
+    mock.set_book(&run.down, &[("0.08", "100")], &[("0.10", "100")]);
+    mock.set_fee_rate(up, Some(1000));
+    mock.set_fee_rate(&run.down, Some(1000));
+    // Past the only point, the cap is 0.90 for the rest of the interval.
+    std::env::set_var("MM_TP_SCHEDULE", "120:0.90");
+
+    let engine = run.spawn();
+    assert!(
+        wait_for(10, || mock.shares(up) == dec!(5)).await,
+        "no entry: orders {:?}",
+        mock.orders()
+    );
+    assert_eq!(mock.orders()[0].price, dec!(0.92));
+
+    // The cap would sell from 0.89; the break-even from 0.92 at 1000 bps (~0.935) holds the
+    // TP at 0.95, so it sells from 0.94.
+    mock.set_book(up, &[("0.93", "100")], &[("0.94", "100")]);
+    run.at_secs_to_close(run.secs_to_close() - 2).await;
+    assert_eq!(mock.shares(up), dec!(5), "orders {:?}", mock.orders());
+
+    mock.set_book(up, &[("0.94", "100")], &[("0.95", "100")]);
+    assert!(
+        wait_for(5, || mock.shares(up) < dec!(0.1)).await,
+        "no TP: orders {:?}",
+        mock.orders()
+    );
+    let sell = mock.orders().into_iter().last().unwrap();
+    assert_eq!(sell.price, dec!(0.94));
+    run.finish(engine).await;
+}
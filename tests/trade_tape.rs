@@ -0,0 +1,91 @@
+//! Trade tape (`last_trade_price` events): volume and count over the asked window, trades past
+//! the 10 min retention dropped, the last trade reported at any age, and the WS event parsed into
+//! the tape.
+
+mod mock_clob;
+
+use mock_clob::{wait_for, MockClob};
+use rust_decimal_macros::dec;
+use sniper_core::clob_ws_book::ClobWsBook;
+use sniper_core::trade_tape::TradeTape;
+
+const MIN: u64 = 60_000;
+
+#[test]
+fn counts_trades_inside_the_window() {
+    let mut tape = TradeTape::default();
+    tape.record("up", 1_000, dec!(0.90), dec!(5));
+    tape.record("up", 20_000, dec!(0.91), dec!(7));
+    tape.record("up", 30_000, dec!(0.92), dec!(11));
+
+    let stats = tape.stats("up", 30_000, 10_000);
+    assert_eq!(stats.volume, dec!(18));
+    assert_eq!(stats.trades, 2);
+    assert_eq!(stats.last_price, Some(dec!(0.92)));
+    assert_eq!(stats.last_trade_ms, Some(30_000));
+
+    // A trade exactly at the cutoff counts.
+    let stats = tape.stats("up", 30_000, 29_000);
+    assert_eq!(stats.volume, dec!(23));
+    assert_eq!(stats.trades, 3);
+}
+
+#[test]
+fn drops_trades_past_the_retention() {
+    let mut tape = TradeTape::default();
+    tape.record("up", 0, dec!(0.50), dec!(100));
+    tape.record("up", 5 * MIN, dec!(0.60), dec!(10));
+    tape.record("up", 11 * MIN, dec!(0.70), dec!(1));
+
+    // The first trade is older than the retention at the last record: gone even for a longer window.
+    let stats = tape.stats("up", 11 * MIN, 60 * MIN);
+    assert_eq!(stats.volume, dec!(11));
+    assert_eq!(stats.trades, 2);
+}
+
+#[test]
+fn reports_a_stale_last_trade_with_an_empty_window() {
+    let mut tape = TradeTape::default();
+    tape.record("up", 1_000, dec!(0.93), dec!(5));
+
+    let stats = tape.stats("up", 9 * MIN, 30_000);
+    assert_eq!(stats.volume, dec!(0));
+    assert_eq!(stats.trades, 0);
+    assert_eq!(stats.last_price, Some(dec!(0.93)));
+    assert_eq!(stats.last_trade_ms, Some(1_000));
+}
+
+#[test]
+fn an_unknown_token_has_no_trades() {
+    let mut tape = TradeTape::default();
+    tape.record("up", 1_000, dec!(0.93), dec!(5));
+
+    let stats = tape.stats("down", 1_000, 30_000);
+    assert_eq!(stats.trades, 0);
+    assert_eq!(stats.volume, dec!(0));
+    assert_eq!(stats.last_price, None);
+    assert_eq!(stats.last_trade_ms, None);
+}
+
+#[tokio::test]
+async fn records_last_trade_price_events_from_the_ws() {
+    let mock = MockClob::start().await;
+    mock.set_book("9601", &[("0.90", "100")], &[("0.92", "100")]);
+    mock.set_book("9602", &[("0.07", "100")], &[("0.09", "100")]);
+    let ws = ClobWsBook::connect(&mock.ws_url(), "9601", "9602")
+        .await
+        .unwrap();
+    assert!(wait_for(5, || mock.ws_subscribed("9601")).await);
+
+    mock.print_trade("9601", "0.91", "12.5", 50_000);
+    mock.print_trade("9601", "0.92", "0", 51_000);
+    mock.print_trade("9601", "0.93", "2", 53_000);
+    assert!(wait_for(5, || ws.asset_stats("9601").trades == 3).await);
+
+    // The zero-size trade is counted as a message but not taped.
+    let stats = ws.trade_stats("9601", 53_000, 10_000).await;
+    assert_eq!(stats.trades, 2);
+    assert_eq!(stats.volume, dec!(14.5));
+    assert_eq!(stats.last_price, Some(dec!(0.93)));
+    assert_eq!(stats.last_trade_ms, Some(53_000));
+}
@@ -0,0 +1,49 @@
+//! WS feed statistics: the status file (MM_STATUS_FILE) carries per-token message counts, rate
+//! and age of the last message, so a feed that went quiet on one token shows up.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::Run;
+use mock_clob::wait_for;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn counts_messages_per_token() {
+    let run = Run::start("ws-stats", "8501", "8502", 8).await;
+    // Out of range: nothing is bought.
+    run.mock
+        .set_book(&run.up, &[("0.49", "100")], &[("0.51", "100")]);
+    run.mock
+        .set_book(&run.down, &[("0.49", "100")], &[("0.51", "100")]);
+    run.with_status_file();
+
+    let engine = run.spawn();
+    assert!(wait_for(5, || run.mock.ws_subscribed(&run.up)).await);
+    for bid in ["0.45", "0.46", "0.47", "0.48"] {
+        run.mock
+            .set_book(&run.up, &[(bid, "100")], &[("0.51", "100")]);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    // The initial snapshot of each token, then four more for Up.
+    let counted = |s: &serde_json::Value| {
+        s["ws"]["up"]["snapshots"].as_u64() == Some(5)
+            && s["ws"]["down"]["snapshots"].as_u64() == Some(1)
+    };
+    assert!(
+        wait_for(5, || run.status().is_some_and(|s| counted(&s))).await,
+        "status: {:?}",
+        run.status()
+    );
+    let ws = &run.status().unwrap()["ws"];
+    assert_eq!(ws["state"], "Live");
+    assert!(
+        ws["up"]["messages_per_sec"].as_f64().unwrap() > 0.0,
+        "{}",
+        ws
+    );
+    assert!(ws["up"]["last_message_age_ms"].as_u64().is_some(), "{}", ws);
+    assert!(run.mock.orders().is_empty());
+
+    run.finish(engine).await;
+}
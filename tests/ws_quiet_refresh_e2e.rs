@@ -0,0 +1,57 @@
+//! Quiet-token refresh (MM_WS_QUIET_REFRESH_SECS): a book that changes on REST while the WS sends
+//! nothing for the token is pulled into the WS book, tagged as a REST update, and traded on.
+
+mod e2e;
+mod mock_clob;
+
+use e2e::Run;
+use mock_clob::wait_for;
+use rust_decimal_macros::dec;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn refreshes_a_quiet_book_from_rest() {
+    let run = Run::start("quiet-refresh", "8601", "8602", 12).await;
+    run.mock
+        .set_book(&run.up, &[("0.49", "100")], &[("0.51", "100")]);
+    run.mock
+        .set_book(&run.down, &[("0.49", "100")], &[("0.51", "100")]);
+    run.with_status_file();
+    std::env::set_var("MM_WS_QUIET_REFRESH_SECS", "2");
+    std::env::set_var("MM_WS_QUIET_REFRESH_EVERY_SECS", "1");
+
+    let engine = run.spawn();
+    assert!(wait_for(5, || run.mock.ws_subscribed(&run.up)).await);
+    // Up moves into range, but only REST sees it.
+    run.mock
+        .set_book_rest_only(&run.up, &[("0.91", "100")], &[("0.92", "100")]);
+    assert!(
+        wait_for(6, || run
+            .status()
+            .is_some_and(|s| s["book"]["up"]["ask"] == "0.92"))
+        .await,
+        "status: {:?}",
+        run.status()
+    );
+    let s = run.status().unwrap();
+    assert_eq!(s["book"]["up"]["source"], "rest", "{}", s["book"]);
+    assert!(s["ws"]["up"]["rest_refreshes"].as_u64().unwrap() >= 1);
+    assert_eq!(s["ws"]["up"]["snapshots"], 1);
+    assert!(
+        wait_for(4, || run.mock.shares(&run.up) == dec!(5)).await,
+        "no entry on the refreshed book: orders {:?}",
+        run.mock.orders()
+    );
+
+    // A WS message takes over again.
+    run.mock
+        .set_book(&run.up, &[("0.93", "100")], &[("0.95", "100")]);
+    assert!(
+        wait_for(3, || run
+            .status()
+            .is_some_and(|s| s["book"]["up"]["ask"] == "0.95"))
+        .await
+    );
+    assert_eq!(run.status().unwrap()["book"]["up"]["source"], "ws");
+
+    run.finish(engine).await;
+}